@@ -12,6 +12,7 @@ use async_trait::async_trait;
 /// Minimal Provider trait (mirrors src/providers/traits.rs)
 #[async_trait]
 pub trait Provider: Send + Sync {
+    fn name(&self) -> &str;
     async fn chat(&self, message: &str, model: &str, temperature: f64) -> Result<String>;
 }
 
@@ -32,6 +33,10 @@ impl OllamaProvider {
 
 #[async_trait]
 impl Provider for OllamaProvider {
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
     async fn chat(&self, message: &str, model: &str, temperature: f64) -> Result<String> {
         let url = format!("{}/api/generate", self.base_url);
 
@@ -0,0 +1,166 @@
+//! `--attach-file <path>`: file contents prepended to the first user message,
+//! so known inputs reach the model without a `file_read` round-trip.
+//!
+//! Attachments go through the same checks as `file_read`: relative paths
+//! are taken from the workspace, and the resolved path must be inside it (or,
+//! with `workspace_only = false`, outside every forbidden path) and no larger
+//! than `max_file_read_bytes`.
+//!
+//! Only UTF-8 text can be attached. Messages are plain text for every
+//! provider, so images are refused rather than sent as image blocks.
+
+use crate::security::SecurityPolicy;
+use anyhow::{Context, Result};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Extensions sent as images by multimodal providers; every provider here
+/// takes text-only messages, so these are refused rather than mangled.
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp"];
+
+/// Read each file into one labeled block, or `None` when there are none.
+pub fn load(paths: &[impl AsRef<Path>], security: &SecurityPolicy) -> Result<Option<String>> {
+    let mut block = String::new();
+    for path in paths {
+        let path = path.as_ref();
+        let content = read(path, security)?;
+        let label = path.display();
+        let _ = writeln!(block, "[Attached file: {label}]");
+        block.push_str(content.trim_end());
+        let _ = write!(block, "\n[End of {label}]\n\n");
+    }
+    Ok((!block.is_empty()).then_some(block))
+}
+
+/// `message` with the attachment block in front of it.
+pub fn prepend(attached: Option<&str>, message: &str) -> String {
+    match attached {
+        Some(block) => format!("{block}{message}"),
+        None => message.to_string(),
+    }
+}
+
+fn read(path: &Path, security: &SecurityPolicy) -> Result<String> {
+    let is_image = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()));
+    if is_image {
+        anyhow::bail!(
+            "Cannot attach {}: the configured providers take text-only messages, \
+             not image blocks",
+            path.display()
+        );
+    }
+
+    let resolved = security
+        .workspace_dir
+        .join(path)
+        .canonicalize()
+        .with_context(|| format!("Failed to read attachment: {}", path.display()))?;
+    let allowed = security.is_resolved_path_allowed(&resolved)
+        || security.is_path_allowed(&resolved.to_string_lossy());
+    if !allowed {
+        anyhow::bail!(
+            "Attachment not allowed by security policy: {} (outside the workspace; \
+             see autonomy.workspace_only and autonomy.forbidden_paths)",
+            resolved.display()
+        );
+    }
+
+    let size = std::fs::metadata(&resolved)
+        .with_context(|| format!("Failed to read attachment: {}", path.display()))?
+        .len();
+    if size > security.max_file_read_bytes {
+        anyhow::bail!(
+            "Attachment too large: {} is {size} bytes (limit: {} bytes)",
+            path.display(),
+            security.max_file_read_bytes
+        );
+    }
+
+    let bytes = std::fs::read(&resolved)
+        .with_context(|| format!("Failed to read attachment: {}", path.display()))?;
+    String::from_utf8(bytes)
+        .map_err(|_| anyhow::anyhow!("Cannot attach {}: not a UTF-8 text file", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn policy(workspace: &Path) -> SecurityPolicy {
+        SecurityPolicy {
+            workspace_dir: workspace.to_path_buf(),
+            ..SecurityPolicy::default()
+        }
+    }
+
+    #[test]
+    fn text_file_is_labeled_and_prepended() {
+        let tmp = TempDir::new().unwrap();
+        let notes = tmp.path().join("notes.md");
+        std::fs::write(&notes, "# Notes\nship on friday\n").unwrap();
+
+        let block = load(&[&notes], &policy(tmp.path())).unwrap();
+        let message = prepend(block.as_deref(), "Summarize the notes");
+
+        let label = notes.display();
+        assert_eq!(
+            message,
+            format!(
+                "[Attached file: {label}]\n# Notes\nship on friday\n[End of {label}]\n\n\
+                 Summarize the notes"
+            )
+        );
+        assert_eq!(prepend(None, "hi"), "hi");
+        assert!(load(&[] as &[&Path], &policy(tmp.path()))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn files_outside_workspace_are_refused() {
+        let workspace = TempDir::new().unwrap();
+        let elsewhere = TempDir::new().unwrap();
+        let secret = elsewhere.path().join("secret.txt");
+        std::fs::write(&secret, "token").unwrap();
+
+        let err = load(&[&secret], &policy(workspace.path())).unwrap_err();
+        assert!(err.to_string().contains("security policy"), "{err}");
+    }
+
+    #[test]
+    fn oversized_binary_and_image_files_are_refused() {
+        let tmp = TempDir::new().unwrap();
+        let security = SecurityPolicy {
+            max_file_read_bytes: 8,
+            ..policy(tmp.path())
+        };
+        let big = tmp.path().join("big.txt");
+        std::fs::write(&big, "more than eight bytes").unwrap();
+        let binary = tmp.path().join("blob.bin");
+        std::fs::write(&binary, [0xff, 0xfe, 0x00]).unwrap();
+        let image = tmp.path().join("shot.PNG");
+        std::fs::write(&image, "png").unwrap();
+
+        let err = |path: &Path| load(&[path], &security).unwrap_err().to_string();
+        assert!(err(&big).contains("too large"));
+        assert!(err(&binary).contains("not a UTF-8 text file"));
+        assert!(err(&image).contains("image blocks"));
+    }
+
+    #[test]
+    fn relative_paths_are_read_from_the_workspace() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir(tmp.path().join("docs")).unwrap();
+        std::fs::write(tmp.path().join("docs/plan.md"), "step one\n").unwrap();
+
+        let block = load(&[Path::new("docs/plan.md")], &policy(tmp.path())).unwrap();
+        assert_eq!(
+            block.as_deref(),
+            Some("[Attached file: docs/plan.md]\nstep one\n[End of docs/plan.md]\n\n")
+        );
+    }
+}
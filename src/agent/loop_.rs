@@ -1,14 +1,16 @@
 use crate::config::Config;
 use crate::memory::{self, Memory, MemoryCategory};
-use crate::observability::{self, Observer, ObserverEvent};
-use crate::providers::{self, ChatMessage, Provider, UsageTracker};
+use crate::observability::{self, MultiObserver, Observer, ObserverEvent, ToolStats, TurnContext};
+use crate::providers::{self, ChatMessage, Provider, Served, UsageTracker};
 use crate::runtime;
-use crate::security::SecurityPolicy;
+use crate::security::{OutputTransforms, SecurityPolicy};
+use crate::session;
 use crate::tools::{self, Tool};
 use crate::util::truncate_with_ellipsis;
 use anyhow::Result;
 use std::fmt::Write;
 use std::io::Write as IoWrite;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::mpsc;
@@ -40,14 +42,18 @@ fn trim_history(history: &mut Vec<ChatMessage>) {
     history.drain(start..start + to_remove);
 }
 
-/// Build context preamble by searching memory for relevant entries
-async fn build_context(mem: &dyn Memory, user_msg: &str) -> String {
+/// Build context preamble by searching memory for up to `limit` relevant
+/// entries; empty when `limit` is 0
+async fn build_context(mem: &dyn Memory, user_msg: &str, limit: usize) -> String {
     let mut context = String::new();
+    if limit == 0 {
+        return context;
+    }
 
     // Pull relevant memories for this message
-    if let Ok(entries) = mem.recall(user_msg, 5).await {
+    if let Ok(entries) = mem.recall(user_msg, limit).await {
         if !entries.is_empty() {
-            context.push_str("[Memory context]\n");
+            context.push_str(memory::MEMORY_CONTEXT_PREFIX);
             for entry in &entries {
                 let _ = writeln!(context, "- {}: {}", entry.key, entry.content);
             }
@@ -118,6 +124,143 @@ fn parse_tool_calls(response: &str) -> (String, Vec<ParsedToolCall>) {
     (text_parts.join("\n"), calls)
 }
 
+/// Remove leaked tool-protocol markup from a final answer.
+///
+/// Drops `<tool_result ...>...</tool_result>` and `<tool_call>...</tool_call>`
+/// blocks (including empty pairs) and stray opening/closing tags. Fenced code
+/// blocks and inline code spans are left untouched.
+pub(crate) fn strip_tool_tags(text: &str) -> String {
+    if !text.contains("<tool_") && !text.contains("</tool_") {
+        return text.to_string();
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut prose = String::new();
+    let mut fence: Option<&str> = None;
+
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let marker = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m));
+        match (fence, marker) {
+            (Some(open), Some(m)) if open == m => {
+                out.push_str(line);
+                fence = None;
+            }
+            (Some(_), _) => out.push_str(line),
+            (None, Some(m)) => {
+                out.push_str(&strip_tool_markup(&prose));
+                prose.clear();
+                out.push_str(line);
+                fence = Some(m);
+            }
+            (None, None) => prose.push_str(line),
+        }
+    }
+    out.push_str(&strip_tool_markup(&prose));
+
+    out.trim().to_string()
+}
+
+/// Strip tool markup from prose (text outside fenced code blocks).
+fn strip_tool_markup(prose: &str) -> String {
+    let mut out = String::with_capacity(prose.len());
+    let mut rest = prose;
+
+    while let Some(pos) = rest.find(['`', '<']) {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+
+        if rest.starts_with('`') {
+            // Inline code span: copy through the matching backtick run verbatim
+            let ticks = rest.len() - rest.trim_start_matches('`').len();
+            let delim = &rest[..ticks];
+            let stop = rest[ticks..]
+                .find(delim)
+                .map_or(ticks, |end| ticks + end + ticks);
+            out.push_str(&rest[..stop]);
+            rest = &rest[stop..];
+        } else if let Some(after) = skip_tool_markup(rest) {
+            rest = after;
+        } else {
+            out.push('<');
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+
+    // Removed blocks leave blank lines behind; keep at most one
+    while out.contains("\n\n\n") {
+        out = out.replace("\n\n\n", "\n\n");
+    }
+    out
+}
+
+/// If `s` starts with a tool tag, return the text following the tag (and its
+/// body and closing tag, when present).
+fn skip_tool_markup(s: &str) -> Option<&str> {
+    for tag in ["tool_result", "tool_call"] {
+        let close = format!("</{tag}>");
+        if let Some(after) = s.strip_prefix(close.as_str()) {
+            return Some(after);
+        }
+
+        let Some(after_name) = s.strip_prefix('<').and_then(|t| t.strip_prefix(tag)) else {
+            continue;
+        };
+        if !after_name.starts_with(['>', '/', ' ', '\t', '\n']) {
+            continue;
+        }
+        let tag_end = after_name.find('>')?;
+        let body = &after_name[tag_end + 1..];
+        if after_name[..tag_end].ends_with('/') {
+            return Some(body);
+        }
+        // An unterminated block is a truncated fragment; drop it entirely
+        return Some(body.find(&close).map_or("", |i| &body[i + close.len()..]));
+    }
+    None
+}
+
+/// Delimiters models use around reasoning (`<think>` is the DeepSeek/Qwen form).
+const THINKING_TAGS: &[(&str, &str)] = &[
+    ("<thinking>", "</thinking>"),
+    ("<think>", "</think>"),
+    ("<reasoning>", "</reasoning>"),
+];
+
+/// Split reasoning blocks out of a response, returning `(thinking, answer)`.
+///
+/// Multiple blocks are joined with blank lines. An unterminated block (e.g. a
+/// response still streaming) counts as thinking through the end of the text.
+pub(crate) fn split_thinking(text: &str) -> (String, String) {
+    let mut thinking: Vec<&str> = Vec::new();
+    let mut answer = String::with_capacity(text.len());
+    let mut rest = text;
+
+    loop {
+        let next = THINKING_TAGS
+            .iter()
+            .filter_map(|&(open, close)| rest.find(open).map(|i| (i, open, close)))
+            .min_by_key(|&(i, _, _)| i);
+        let Some((start, open, close)) = next else {
+            answer.push_str(rest);
+            break;
+        };
+        answer.push_str(&rest[..start]);
+        let body = &rest[start + open.len()..];
+        if let Some(end) = body.find(close) {
+            thinking.push(body[..end].trim());
+            rest = &body[end + close.len()..];
+        } else {
+            thinking.push(body.trim());
+            break;
+        }
+    }
+
+    thinking.retain(|t| !t.is_empty());
+    (thinking.join("\n\n"), answer.trim().to_string())
+}
+
 #[derive(Debug)]
 struct ParsedToolCall {
     name: String,
@@ -126,17 +269,22 @@ struct ParsedToolCall {
 
 /// Execute a single turn of the agent loop: send messages, parse tool calls,
 /// execute tools, and loop until the LLM produces a final text response.
+/// With `stream` off nothing is printed until the caller prints the answer.
+/// The reply names the provider that served the final call.
+#[allow(clippy::too_many_arguments)]
 async fn agent_turn(
     provider: &dyn Provider,
     history: &mut Vec<ChatMessage>,
     tools_registry: &Arc<Vec<Box<dyn Tool>>>,
     observer: &dyn Observer,
+    turn: &TurnContext,
     model: &str,
     temperature: f64,
-) -> Result<String> {
+    stream: bool,
+) -> Result<Served> {
     for _iteration in 0..MAX_TOOL_ITERATIONS {
         // Use streaming if available for real-time CLI output
-        let response = if provider.supports_streaming() {
+        let response = if stream && provider.supports_streaming() {
             let (stream_tx, mut stream_rx) = mpsc::channel::<String>(64);
 
             // Forward tokens to stdout as they arrive
@@ -148,44 +296,54 @@ async fn agent_turn(
             });
 
             let result = provider
-                .chat_with_history_stream(history, model, temperature, stream_tx)
+                .chat_with_history_stream_served(history, model, temperature, stream_tx)
                 .await;
             printer.abort();
             println!(); // newline after streaming
             result?
         } else {
             provider
-                .chat_with_history(history, model, temperature)
+                .chat_with_history_served(history, model, temperature)
                 .await?
         };
+        let Served {
+            text: response,
+            provider: served_by,
+        } = response;
 
         let (text, tool_calls) = parse_tool_calls(&response);
 
         if tool_calls.is_empty() {
             // No tool calls — this is the final response
-            history.push(ChatMessage::assistant(&response));
+            let cleaned = strip_tool_tags(if text.is_empty() { &response } else { &text });
+            history.push(
+                ChatMessage::assistant(if cleaned.is_empty() {
+                    &response
+                } else {
+                    &cleaned
+                })
+                .with_model(model),
+            );
             // If we streamed, text was already printed; return it for logging
-            return Ok(if text.is_empty() {
-                response
-            } else {
-                text
+            return Ok(Served {
+                text: cleaned,
+                provider: served_by,
             });
         }
 
         // Print any text the LLM produced alongside tool calls (non-streaming path)
-        if !text.is_empty() && !provider.supports_streaming() {
+        if stream && !text.is_empty() && !provider.supports_streaming() {
             print!("{text}");
             let _ = std::io::stdout().flush();
         }
 
         // Execute tool calls concurrently when multiple are requested
-        let tool_results = execute_tools_parallel(&tool_calls, tools_registry, observer).await;
+        let tool_results =
+            execute_tools_parallel(&tool_calls, tools_registry, observer, turn).await;
 
         // Add assistant message with tool calls + tool results to history
-        history.push(ChatMessage::assistant(&response));
-        history.push(ChatMessage::user(format!(
-            "[Tool results]\n{tool_results}"
-        )));
+        history.push(ChatMessage::assistant(&response).with_model(model));
+        history.push(ChatMessage::tool(tool_results));
     }
 
     anyhow::bail!("Agent exceeded maximum tool iterations ({MAX_TOOL_ITERATIONS})")
@@ -193,19 +351,24 @@ async fn agent_turn(
 
 /// Execute tool calls concurrently when multiple are present.
 /// Uses tokio::spawn with Arc-wrapped tools for true parallelism.
+/// Calls run one by one, in order, when any of them edits files.
 async fn execute_tools_parallel(
     calls: &[ParsedToolCall],
     tools_registry: &Arc<Vec<Box<dyn Tool>>>,
     observer: &dyn Observer,
+    turn: &TurnContext,
 ) -> String {
     let mut tool_results = String::new();
 
-    if calls.len() <= 1 {
-        // Single tool — execute directly, no parallelism overhead
+    let sequential = calls
+        .iter()
+        .any(|call| find_tool(tools_registry, &call.name).is_some_and(|t| !t.parallel_safe()));
+    if calls.len() <= 1 || sequential {
+        // Single tool or ordered calls — execute directly, one after another
         for call in calls {
             let start = Instant::now();
             let output = if let Some(tool) = find_tool(tools_registry, &call.name) {
-                match tool.execute(call.arguments.clone()).await {
+                match tool.execute_validated(call.arguments.clone()).await {
                     Ok(r) if r.success => r.output,
                     Ok(r) => format!("Error: {}", r.error.unwrap_or_else(|| r.output)),
                     Err(e) => format!("Error executing {}: {e}", call.name),
@@ -217,6 +380,7 @@ async fn execute_tools_parallel(
                 tool: call.name.clone(),
                 duration: start.elapsed(),
                 success: !output.starts_with("Error"),
+                turn: Some(turn.clone()),
             });
             let _ = writeln!(
                 tool_results,
@@ -237,7 +401,7 @@ async fn execute_tools_parallel(
         handles.push(tokio::spawn(async move {
             let start = Instant::now();
             let output = if let Some(tool) = tools.iter().find(|t| t.name() == name) {
-                match tool.execute(args).await {
+                match tool.execute_validated(args).await {
                     Ok(r) if r.success => r.output,
                     Ok(r) => format!("Error: {}", r.error.unwrap_or_else(|| r.output)),
                     Err(e) => format!("Error executing {name}: {e}"),
@@ -257,6 +421,7 @@ async fn execute_tools_parallel(
                     tool: name.clone(),
                     duration,
                     success: !output.starts_with("Error"),
+                    turn: Some(turn.clone()),
                 });
                 let _ = writeln!(
                     tool_results,
@@ -275,6 +440,27 @@ async fn execute_tools_parallel(
     tool_results
 }
 
+/// Tools from `available` that `--tools-only` (`[autonomy] tools_only`)
+/// leaves out; none when `allowed` is empty. Allowed names matching no tool
+/// are warned about and otherwise ignored.
+pub fn tools_outside_allowlist<'a>(
+    available: impl IntoIterator<Item = &'a str>,
+    allowed: &[String],
+) -> std::collections::HashSet<String> {
+    let available: Vec<&str> = available.into_iter().collect();
+    if allowed.is_empty() {
+        return std::collections::HashSet::new();
+    }
+    for name in allowed.iter().filter(|a| !available.contains(&a.as_str())) {
+        tracing::warn!(tool = name.as_str(), "--tools-only names an unknown tool");
+    }
+    available
+        .into_iter()
+        .filter(|t| !allowed.iter().any(|a| a == t))
+        .map(String::from)
+        .collect()
+}
+
 /// Build the tool instruction block for the system prompt so the LLM knows
 /// how to invoke tools.
 fn build_tool_instructions(tools_registry: &[Box<dyn Tool>]) -> String {
@@ -288,35 +474,247 @@ fn build_tool_instructions(tools_registry: &[Box<dyn Tool>]) -> String {
     instructions.push_str("### Available Tools\n\n");
 
     for tool in tools_registry {
-        let _ = writeln!(
-            instructions,
-            "**{}**: {}\nParameters: `{}`\n",
-            tool.name(),
-            tool.description(),
-            tool.parameters_schema()
-        );
+        let _ = writeln!(instructions, "{}", tools::prompt_entry(tool.as_ref()));
     }
 
     instructions
 }
 
-#[allow(clippy::too_many_lines)]
-pub async fn run(
-    config: Config,
-    message: Option<String>,
-    provider_override: Option<String>,
-    model_override: Option<String>,
+/// Banner for a provider that rejected its credentials, with the fix-it
+/// steps instead of a one-line error.
+fn auth_banner(auth: &providers::AuthError) -> String {
+    format!(
+        "\n🔑 Authentication failed: {}\n\n{}\n",
+        auth.summary(),
+        auth.guidance()
+    )
+}
+
+/// How single-message mode prints the reply (`agent --output`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    /// One JSON object with the reply, session ID and model
+    Json,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!(
+                "invalid output format {s:?}: expected text or json"
+            )),
+        }
+    }
+}
+
+/// What `tinyclaw agent` should do; the default is an interactive session
+/// with the configured provider, model and temperature.
+#[derive(Default)]
+pub struct RunOptions {
+    /// Answer this one message and exit (`--message`, `--template`, `--pipe`)
+    pub message: Option<String>,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub temperature: Option<f64>,
+    /// Replaces the built-in system prompt
+    pub system_file: Option<PathBuf>,
+    /// End an interactive session after this many turns
+    pub max_turns: Option<u32>,
+    pub persona: Option<String>,
+    /// Session ID to continue
+    pub resume: Option<String>,
+    pub output: OutputFormat,
+    /// Prompts for `--batch`, each answered in a fresh conversation
+    pub batch: Option<Vec<String>>,
+    /// Report a failed batch prompt and go on, instead of stopping with its
+    /// error
+    pub continue_on_error: bool,
+    /// Files whose contents go in front of every prompt
+    pub attach_files: Vec<PathBuf>,
+}
+
+/// The prompt for `agent --pipe`: all of `input` up to EOF, without the
+/// trailing newline.
+pub fn read_piped_message(mut input: impl std::io::Read) -> Result<String> {
+    let mut message = String::new();
+    input
+        .read_to_string(&mut message)
+        .map_err(|e| anyhow::anyhow!("Failed to read the prompt from stdin: {e}"))?;
+    let message = message.trim_end_matches(['\n', '\r']);
+    if message.trim().is_empty() {
+        anyhow::bail!("--pipe read an empty prompt from stdin");
+    }
+    Ok(message.to_string())
+}
+
+/// An explicit `--temperature` beats the persona's, which beats the selected
+/// model route's, which beats the 0.7 default.
+fn resolve_temperature(flag: Option<f64>, persona: Option<f64>, route: Option<f64>) -> f64 {
+    flag.or(persona).or(route).unwrap_or(0.7)
+}
+
+/// Whether `completed` user/assistant exchanges hit the `--max-turns` ceiling.
+fn turn_limit_reached(completed: u32, max_turns: Option<u32>) -> bool {
+    max_turns.is_some_and(|max| completed >= max)
+}
+
+/// The session file a headless run saves its conversation to, so an
+/// interrupted run can be picked up with `--resume`.
+struct SessionLog {
+    workspace_dir: PathBuf,
+    id: String,
+    tool_stats: ToolStats,
+    /// `[[security.output_transforms]]`, applied to each reply before saving
+    output_transforms: OutputTransforms,
+    /// Off for scheduler and heartbeat runs, which nobody resumes
+    persist: bool,
+}
+
+impl SessionLog {
+    /// Write `history` to the session file. Failures are logged, not fatal.
+    fn save(&self, model: &str, history: &[ChatMessage]) {
+        if !self.persist {
+            return;
+        }
+        if let Err(e) = session::update(
+            &self.workspace_dir,
+            &self.id,
+            model,
+            history,
+            self.tool_stats.snapshot(),
+        ) {
+            tracing::warn!(session = self.id, "Failed to save session: {e}");
+        }
+    }
+}
+
+/// [`agent_turn`] with the output transforms applied to the reply, then save
+/// the session with the completed exchange. With `stream` off nothing is
+/// printed; the caller prints the reply. Every observer event of the turn
+/// carries the same fresh request id and the session id.
+#[allow(clippy::too_many_arguments)]
+async fn logged_turn(
+    provider: &dyn Provider,
+    history: &mut Vec<ChatMessage>,
+    tools_registry: &Arc<Vec<Box<dyn Tool>>>,
+    observer: &dyn Observer,
+    model: &str,
     temperature: f64,
-) -> Result<()> {
+    session_log: &SessionLog,
+    stream: bool,
+) -> Result<Served> {
+    let transforms = &session_log.output_transforms;
+    let turn = TurnContext::new(Some(&session_log.id));
+    // Streamed tokens would show the reply before the transforms run
+    let result = agent_turn(
+        provider,
+        history,
+        tools_registry,
+        observer,
+        &turn,
+        model,
+        temperature,
+        stream && transforms.is_empty(),
+    )
+    .await;
+    let mut response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            observer.record_event(&ObserverEvent::Error {
+                component: "agent".into(),
+                message: format!("{e:#}"),
+                turn: Some(turn),
+            });
+            return Err(e);
+        }
+    };
+    if !transforms.is_empty() {
+        response.text = transforms.apply(&response.text);
+        if let Some(last) = history.last_mut().filter(|m| m.role == "assistant") {
+            last.content = transforms.apply(&last.content);
+        }
+    }
+    session_log.save(model, history);
+    Ok(response)
+}
+
+/// Run `tinyclaw agent` as `options` describe.
+pub async fn run(config: Config, options: RunOptions) -> Result<()> {
+    run_with(config, options, false).await?;
+    Ok(())
+}
+
+/// Answer `prompt` for the scheduler or heartbeat rather than a user and
+/// return the reply. The `schedule_reminder` tool is left out, so a
+/// scheduled run cannot keep scheduling more of them.
+pub async fn run_unattended(config: Config, prompt: String) -> Result<String> {
+    let options = RunOptions {
+        message: Some(prompt),
+        temperature: Some(config.default_temperature),
+        ..RunOptions::default()
+    };
+    Box::pin(run_with(config, options, true))
+        .await
+        .map(Option::unwrap_or_default)
+}
+
+/// Returns the last reply of a single message or batch; `None` for an
+/// interactive session.
+#[allow(clippy::too_many_lines)]
+async fn run_with(config: Config, options: RunOptions, unattended: bool) -> Result<Option<String>> {
+    let RunOptions {
+        message,
+        provider: provider_override,
+        model: model_override,
+        temperature,
+        system_file,
+        max_turns,
+        persona,
+        resume,
+        output,
+        batch,
+        continue_on_error,
+        attach_files,
+    } = options;
+    // Validate --resume, --persona and --system-file before wiring anything up
+    let resumed = resume
+        .as_deref()
+        .map(|id| session::load(&config.workspace_dir, id))
+        .transpose()?;
+    let persona = persona
+        .as_deref()
+        .map(|name| crate::persona::load(&config.workspace_dir, name))
+        .transpose()?;
+    let persona_prompt = persona
+        .as_ref()
+        .and_then(|p| p.prompt.as_deref())
+        .map(|prompt| crate::channels::custom_system_prompt(prompt, Some(&config.identity)));
+    let custom_prompt = system_file
+        .as_deref()
+        .map(|path| crate::channels::load_system_prompt_file(path, Some(&config.identity)))
+        .transpose()?
+        .or(persona_prompt);
+    let output_transforms = OutputTransforms::from_config(&config.security)?;
+
     // ── Wire up agnostic subsystems ──────────────────────────────
-    let observer: Arc<dyn Observer> =
-        Arc::from(observability::create_observer(&config.observability));
+    let tool_stats = ToolStats::new();
+    let observer: Arc<dyn Observer> = Arc::new(MultiObserver::new(vec![
+        observability::create_observer(&config.observability),
+        Box::new(tool_stats.clone()),
+    ]));
     let runtime: Arc<dyn runtime::RuntimeAdapter> =
         Arc::from(runtime::create_runtime(&config.runtime)?);
     let security = Arc::new(SecurityPolicy::from_config(
         &config.autonomy,
         &config.workspace_dir,
     ));
+    let mut attached = super::attach::load(&attach_files, &security)?;
 
     // ── Memory (the brain) ────────────────────────────────────────
     let mem: Arc<dyn Memory> = Arc::from(memory::create_memory(
@@ -324,6 +722,7 @@ pub async fn run(
         &config.workspace_dir,
         config.api_key.as_deref(),
     )?);
+    memory::gc_expired(mem.as_ref()).await;
     tracing::info!(backend = mem.name(), "Memory initialized");
 
     // ── Tools (including memory tools) ────────────────────────────
@@ -332,13 +731,30 @@ pub async fn run(
     } else {
         None
     };
-    let tools_registry = Arc::new(tools::all_tools_with_runtime(
-        &security,
-        runtime,
-        mem.clone(),
-        composio_key,
-        &config.browser,
+    let mut tools_registry = tools::with_redaction(
+        tools::all_tools_with_runtime(
+            &security,
+            runtime,
+            mem.clone(),
+            composio_key,
+            &config.browser,
+            &config.check,
+        ),
+        &config.security,
+    );
+    let mut disabled_tools = match &persona {
+        Some(p) => p.disabled_tools(tools_registry.iter().map(|t| t.name()))?,
+        None => std::collections::HashSet::new(),
+    };
+    disabled_tools.extend(tools_outside_allowlist(
+        tools_registry.iter().map(|t| t.name()),
+        &config.autonomy.tools_only,
     ));
+    if unattended {
+        disabled_tools.insert("schedule_reminder".to_string());
+    }
+    tools_registry.retain(|t| !disabled_tools.contains(t.name()));
+    let tools_registry = Arc::new(tools_registry);
 
     // ── Resolve provider ─────────────────────────────────────────
     let provider_name = provider_override
@@ -347,24 +763,51 @@ pub async fn run(
         .unwrap_or("openrouter");
 
     let model_name = model_override
-        .as_deref()
-        .or(config.default_model.as_deref())
-        .unwrap_or("anthropic/claude-sonnet-4-20250514");
+        .or_else(|| persona.as_ref().and_then(|p| p.model.clone()))
+        .unwrap_or_else(|| config.default_model_for(provider_name));
+    let model_name = model_name.as_str();
+    let temperature = resolve_temperature(
+        temperature,
+        persona.as_ref().and_then(|p| p.temperature),
+        providers::route_temperature(&config.model_routes, model_name),
+    );
 
-    let mut provider: Box<dyn Provider> = providers::create_routed_provider(
+    let mut provider: Box<dyn Provider> = providers::with_response_cache(
+        providers::create_routed_provider(
+            provider_name,
+            config.api_key.as_deref(),
+            &config.providers,
+            &config.reliability,
+            &config.model_routes,
+            model_name,
+        )?,
         provider_name,
-        config.api_key.as_deref(),
         &config.reliability,
-        &config.model_routes,
-        model_name,
-    )?;
+        &config.workspace_dir,
+    );
 
     let usage_tracker = UsageTracker::new();
     provider.set_usage_tracker(usage_tracker.clone());
+    provider.set_seed(config.providers.seed);
+    provider.set_reasoning_effort(config.providers.reasoning_effort);
+    provider.set_stop_sequences(&config.providers.stop_sequences)?;
+    // A single message or batch would only wait on the warmup before making
+    // the same connection itself
+    if message.is_none() && batch.is_none() {
+        providers::warm_up_in_background(providers::create_routed_provider(
+            provider_name,
+            config.api_key.as_deref(),
+            &config.providers,
+            &config.reliability,
+            &config.model_routes,
+            model_name,
+        )?);
+    }
 
     observer.record_event(&ObserverEvent::AgentStart {
-        provider: provider_name.to_string(),
+        provider: provider.name().to_string(),
         model: model_name.to_string(),
+        turn: None,
     });
 
     // ── Build system prompt from workspace MD files (OpenClaw framework) ──
@@ -382,6 +825,10 @@ pub async fn run(
             "file_write",
             "Write file contents. Use when: applying focused edits, scaffolding files, updating docs/code. Don't use when: side effects are unclear or file ownership is uncertain.",
         ),
+        (
+            "git",
+            "Run whitelisted git subcommands (status, diff, log, show, branch; add/commit when allowed). Use when: inspecting repository state or history. Don't use when: the operation is not in the whitelist.",
+        ),
         (
             "memory_store",
             "Save to memory. Use when: preserving durable preferences, decisions, key context. Don't use when: information is transient/noisy/sensitive without need.",
@@ -403,6 +850,16 @@ pub async fn run(
         "image_info",
         "Read image file metadata (format, dimensions, size) and optionally base64-encode it. Use when: inspecting images, preparing visual data for analysis.",
     ));
+    #[cfg(feature = "plot-feature")]
+    tool_descs.push((
+        "plot",
+        "Render a line or bar chart from data series to an SVG file. Use when: a trend or comparison is clearer as a picture. Don't use when: a short table answers the question.",
+    ));
+    #[cfg(feature = "daemon-feature")]
+    tool_descs.push((
+        "schedule_reminder",
+        "Schedule a follow-up run of the agent with a stored prompt. Use when: work must be checked on later (e.g. 'in 2h'). Don't use when: the answer is available now.",
+    ));
     if config.browser.enabled {
         tool_descs.push((
             "browser_open",
@@ -415,61 +872,173 @@ pub async fn run(
             "Execute actions on 1000+ apps via Composio (Gmail, Notion, GitHub, Slack, etc.). Use action='list' to discover, 'execute' to run, 'connect' to OAuth.",
         ));
     }
-    let mut system_prompt = crate::channels::build_system_prompt(
-        &config.workspace_dir,
-        model_name,
-        &tool_descs,
-        &skills,
-        Some(&config.identity),
-    );
+    tool_descs.retain(|(name, _)| !disabled_tools.contains(*name));
+    let mut system_prompt = custom_prompt.unwrap_or_else(|| {
+        crate::channels::build_system_prompt(
+            &config.workspace_dir,
+            model_name,
+            &tool_descs,
+            &skills,
+            Some(&config.identity),
+        )
+    });
 
     // Append structured tool-use instructions with schemas
     system_prompt.push_str(&build_tool_instructions(&tools_registry));
 
+    // ── Session (new, or continued with --resume) ────────────────
+    let mut history = vec![ChatMessage::system(&system_prompt)];
+    let session_id = match resumed {
+        Some(saved) => {
+            history.extend(saved.messages.into_iter().filter(|m| m.role != "system"));
+            saved.id
+        }
+        None => session::new_session_id(),
+    };
+    let mut session_log = SessionLog {
+        workspace_dir: config.workspace_dir.clone(),
+        id: session_id,
+        tool_stats,
+        output_transforms,
+        persist: !unattended,
+    };
+    if session_log.persist {
+        tracing::info!(session = session_log.id, "Saving session after each turn");
+    }
+
     // ── Execute ──────────────────────────────────────────────────
     let start = Instant::now();
+    // Provider that answered the latest turn, for the AgentEnd event
+    let mut served_by = provider.name().to_string();
+    let mut last_reply = None;
+
+    let batched = batch.is_some();
+    // Text replies stream as they arrive, piped or not; streamed tokens would
+    // show the reply before the output transforms run
+    let streams = output == OutputFormat::Text && !batched;
+    let streamed =
+        streams && provider.supports_streaming() && session_log.output_transforms.is_empty();
+    // A single message never continues past its own error
+    let continue_on_error = continue_on_error && batched;
+    if let Some(prompts) = batch.or_else(|| message.map(|msg| vec![msg])) {
+        let base_history = history;
+        let total = prompts.len();
+        let mut failed = 0;
+
+        for (index, msg) in prompts.into_iter().enumerate() {
+            // Every batch prompt is its own conversation and session
+            let mut history = base_history.clone();
+            if index > 0 {
+                session_log.id = session::new_session_id();
+            }
 
-    if let Some(msg) = message {
-        // Auto-save user message to memory
-        if config.memory.auto_save {
-            let _ = mem
-                .store("user_msg", &msg, MemoryCategory::Conversation)
-                .await;
-        }
+            // Auto-save user message to memory
+            if config.memory.auto_save {
+                let _ = mem
+                    .store("user_msg", &msg, MemoryCategory::Conversation)
+                    .await;
+            }
 
-        // Inject memory context into user message
-        let context = build_context(mem.as_ref(), &msg).await;
-        let enriched = if context.is_empty() {
-            msg.clone()
-        } else {
-            format!("{context}{msg}")
-        };
+            // Inject memory context into user message
+            let context = build_context(mem.as_ref(), &msg, config.memory.recall_limit).await;
+            let enriched = format!(
+                "{context}{}",
+                super::attach::prepend(attached.as_deref(), &msg)
+            );
 
-        let mut history = vec![
-            ChatMessage::system(&system_prompt),
-            ChatMessage::user(&enriched),
-        ];
+            history.push(ChatMessage::user(&enriched));
 
-        let response = agent_turn(
-            provider.as_ref(),
-            &mut history,
-            &tools_registry,
-            observer.as_ref(),
-            model_name,
-            temperature,
-        )
-        .await?;
-        println!("{response}");
-
-        // Auto-save assistant response to daily log
-        if config.memory.auto_save {
-            let summary = truncate_with_ellipsis(&response, 100);
-            let _ = mem
-                .store("assistant_resp", &summary, MemoryCategory::Daily)
-                .await;
+            let result = logged_turn(
+                provider.as_ref(),
+                &mut history,
+                &tools_registry,
+                observer.as_ref(),
+                model_name,
+                temperature,
+                &session_log,
+                // JSON and batch output is only the final reply, printed once below
+                streams,
+            )
+            .await;
+            let response = match result {
+                Ok(served) => {
+                    served_by = served.provider;
+                    served.text
+                }
+                Err(e) if continue_on_error => {
+                    failed += 1;
+                    let error = format!("{e:#}");
+                    match output {
+                        OutputFormat::Text => {
+                            println!("── Prompt {}/{total} failed ──\n{error}\n", index + 1);
+                        }
+                        OutputFormat::Json => println!(
+                            "{}",
+                            serde_json::json!({
+                                "index": index + 1,
+                                "status": "error",
+                                "error": error,
+                                "session": session_log.id,
+                                "model": model_name,
+                            })
+                        ),
+                    }
+                    continue;
+                }
+                Err(e) => {
+                    if let Some(auth) = providers::auth_failure(&e) {
+                        eprintln!("{}", auth_banner(auth));
+                        anyhow::bail!("{} authentication failed", auth.provider);
+                    }
+                    if batched {
+                        return Err(e.context(format!("Prompt {}/{total} failed", index + 1)));
+                    }
+                    return Err(e);
+                }
+            };
+            match (output, batched) {
+                // Already on stdout when streamed
+                (OutputFormat::Text, false) if streamed => {}
+                (OutputFormat::Text, false) => println!("{response}"),
+                (OutputFormat::Text, true) => {
+                    println!("── Prompt {}/{total} ──\n{response}\n", index + 1);
+                }
+                (OutputFormat::Json, false) => println!(
+                    "{}",
+                    serde_json::json!({
+                        "response": response,
+                        "session": session_log.id,
+                        "model": model_name,
+                    })
+                ),
+                (OutputFormat::Json, true) => println!(
+                    "{}",
+                    serde_json::json!({
+                        "index": index + 1,
+                        "status": "ok",
+                        "response": response,
+                        "session": session_log.id,
+                        "model": model_name,
+                    })
+                ),
+            }
+
+            // Auto-save assistant response to daily log
+            if config.memory.auto_save {
+                let summary = truncate_with_ellipsis(&response, 100);
+                let _ = mem
+                    .store("assistant_resp", &summary, MemoryCategory::Daily)
+                    .await;
+            }
+            last_reply = Some(response);
+        }
+
+        if failed > 0 {
+            eprintln!("{failed} of {total} prompts failed");
         }
     } else {
         println!("🦀 TinyClaw Interactive Mode");
+        println!("Session {} (continue later with --resume)", session_log.id);
         println!("Type /quit to exit.\n");
 
         let (tx, mut rx) = tokio::sync::mpsc::channel(32);
@@ -480,8 +1049,7 @@ pub async fn run(
             let _ = crate::channels::Channel::listen(&cli, tx).await;
         });
 
-        // Persistent conversation history across turns
-        let mut history = vec![ChatMessage::system(&system_prompt)];
+        let mut turns: u32 = 0;
 
         while let Some(msg) = rx.recv().await {
             // Auto-save conversation turns
@@ -492,65 +1060,360 @@ pub async fn run(
             }
 
             // Inject memory context into user message
-            let context = build_context(mem.as_ref(), &msg.content).await;
-            let enriched = if context.is_empty() {
-                msg.content.clone()
-            } else {
-                format!("{context}{}", msg.content)
-            };
+            let context =
+                build_context(mem.as_ref(), &msg.content, config.memory.recall_limit).await;
+            // Attachments go with the first message only
+            let enriched = format!(
+                "{context}{}",
+                super::attach::prepend(attached.take().as_deref(), &msg.content)
+            );
 
             history.push(ChatMessage::user(&enriched));
+            turns += 1;
 
-            let response = match agent_turn(
+            match logged_turn(
                 provider.as_ref(),
                 &mut history,
                 &tools_registry,
                 observer.as_ref(),
                 model_name,
                 temperature,
+                &session_log,
+                true,
             )
             .await
             {
-                Ok(resp) => resp,
-                Err(e) => {
-                    eprintln!("\nError: {e}\n");
-                    continue;
+                Ok(Served {
+                    text: response,
+                    provider,
+                }) => {
+                    served_by = provider;
+                    println!("\n{response}\n");
+
+                    // Prevent unbounded history growth in long interactive sessions
+                    trim_history(&mut history);
+
+                    if config.memory.auto_save {
+                        let summary = truncate_with_ellipsis(&response, 100);
+                        let _ = mem
+                            .store("assistant_resp", &summary, MemoryCategory::Daily)
+                            .await;
+                    }
                 }
-            };
-            println!("\n{response}\n");
-
-            // Prevent unbounded history growth in long interactive sessions
-            trim_history(&mut history);
+                Err(e) => match providers::auth_failure(&e) {
+                    Some(auth) => eprintln!("{}", auth_banner(auth)),
+                    None => eprintln!("\nError: {e}\n"),
+                },
+            }
 
-            if config.memory.auto_save {
-                let summary = truncate_with_ellipsis(&response, 100);
-                let _ = mem
-                    .store("assistant_resp", &summary, MemoryCategory::Daily)
-                    .await;
+            if turn_limit_reached(turns, max_turns) {
+                println!("⏹ Turn limit reached ({turns}); stopping.");
+                break;
             }
         }
 
         listen_handle.abort();
     }
 
+    if let Some(fingerprint) = usage_tracker.system_fingerprint() {
+        tracing::info!(system_fingerprint = %fingerprint, "Provider backend fingerprint");
+    }
+
     let duration = start.elapsed();
     let total_tokens = usage_tracker.snapshot().total_tokens;
     observer.record_event(&ObserverEvent::AgentEnd {
+        provider: served_by,
         duration,
         tokens_used: if total_tokens > 0 {
             Some(total_tokens)
         } else {
             None
         },
+        turn: None,
     });
 
-    Ok(())
+    Ok(last_reply)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    struct MockProvider;
+
+    #[async_trait::async_trait]
+    impl Provider for MockProvider {
+        fn name(&self) -> &str {
+            "mock"
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> Result<String> {
+            Ok("Hello from the mock.".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn completed_turn_saves_a_loadable_session() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let log = SessionLog {
+            workspace_dir: tmp.path().to_path_buf(),
+            id: session::new_session_id(),
+            tool_stats: ToolStats::new(),
+            output_transforms: OutputTransforms::default(),
+            persist: true,
+        };
+        let mut history = vec![ChatMessage::system("sys"), ChatMessage::user("hi")];
+
+        let reply = logged_turn(
+            &MockProvider,
+            &mut history,
+            &Arc::new(Vec::new()),
+            &crate::observability::NoopObserver,
+            "mock-model",
+            0.7,
+            &log,
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(reply.text, "Hello from the mock.");
+
+        let saved = session::load(tmp.path(), &log.id).unwrap();
+        assert_eq!(saved.model, "mock-model");
+        let turns: Vec<(&str, &str)> = saved
+            .messages
+            .iter()
+            .map(|m| (m.role.as_str(), m.content.as_str()))
+            .collect();
+        assert_eq!(
+            turns,
+            vec![
+                ("system", "sys"),
+                ("user", "hi"),
+                ("assistant", "Hello from the mock."),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn unattended_turn_saves_no_session() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let log = SessionLog {
+            workspace_dir: tmp.path().to_path_buf(),
+            id: session::new_session_id(),
+            tool_stats: ToolStats::new(),
+            output_transforms: OutputTransforms::default(),
+            persist: false,
+        };
+        let mut history = vec![ChatMessage::system("sys"), ChatMessage::user("hi")];
+
+        logged_turn(
+            &MockProvider,
+            &mut history,
+            &Arc::new(Vec::new()),
+            &crate::observability::NoopObserver,
+            "mock-model",
+            0.7,
+            &log,
+            false,
+        )
+        .await
+        .unwrap();
+        assert!(session::load(tmp.path(), &log.id).is_err());
+    }
+
+    #[tokio::test]
+    async fn output_transforms_rewrite_reply_before_saving() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let security = crate::config::SecurityConfig {
+            output_transforms: vec![
+                crate::config::schema::OutputTransform {
+                    pattern: "mock".into(),
+                    replacement: "****".into(),
+                },
+                crate::config::schema::OutputTransform {
+                    pattern: r"\z".into(),
+                    replacement: " [checked]".into(),
+                },
+            ],
+            ..crate::config::SecurityConfig::default()
+        };
+        let log = SessionLog {
+            workspace_dir: tmp.path().to_path_buf(),
+            id: session::new_session_id(),
+            tool_stats: ToolStats::new(),
+            output_transforms: OutputTransforms::from_config(&security).unwrap(),
+            persist: true,
+        };
+        let mut history = vec![ChatMessage::system("sys"), ChatMessage::user("hi")];
+
+        let reply = logged_turn(
+            &MockProvider,
+            &mut history,
+            &Arc::new(Vec::new()),
+            &crate::observability::NoopObserver,
+            "mock-model",
+            0.7,
+            &log,
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(reply.text, "Hello from the ****. [checked]");
+
+        let saved = session::load(tmp.path(), &log.id).unwrap();
+        assert_eq!(
+            saved.messages.last().unwrap().content,
+            "Hello from the ****. [checked]"
+        );
+    }
+
+    /// Keeps every event it is given.
+    #[derive(Default)]
+    struct RecordingObserver(std::sync::Mutex<Vec<ObserverEvent>>);
+
+    impl Observer for RecordingObserver {
+        fn record_event(&self, event: &ObserverEvent) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+
+        fn record_metric(&self, _metric: &crate::observability::traits::ObserverMetric) {}
+
+        fn name(&self) -> &str {
+            "recording"
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_events_carry_the_turn() {
+        let observer = RecordingObserver::default();
+        let turn = TurnContext::new(Some("session-1"));
+        let calls: Vec<ParsedToolCall> = ["a", "b"]
+            .into_iter()
+            .map(|name| ParsedToolCall {
+                name: name.into(),
+                arguments: serde_json::json!({}),
+            })
+            .collect();
+
+        execute_tools_parallel(&calls, &Arc::new(Vec::new()), &observer, &turn).await;
+        execute_tools_parallel(&calls[..1], &Arc::new(Vec::new()), &observer, &turn).await;
+
+        let events = observer.0.lock().unwrap();
+        assert_eq!(events.len(), 3);
+        for event in events.iter() {
+            let ObserverEvent::ToolCall { turn: Some(t), .. } = event else {
+                panic!("unexpected event {event:?}");
+            };
+            assert_eq!(t, &turn);
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_turn_reports_error_with_session() {
+        struct FailingProvider;
+
+        #[async_trait::async_trait]
+        impl Provider for FailingProvider {
+            fn name(&self) -> &str {
+                "failing"
+            }
+
+            async fn chat_with_system(
+                &self,
+                _system_prompt: Option<&str>,
+                _message: &str,
+                _model: &str,
+                _temperature: f64,
+            ) -> Result<String> {
+                anyhow::bail!("upstream down")
+            }
+        }
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let log = SessionLog {
+            workspace_dir: tmp.path().to_path_buf(),
+            id: "session-2".into(),
+            tool_stats: ToolStats::new(),
+            output_transforms: OutputTransforms::default(),
+            persist: true,
+        };
+        let observer = RecordingObserver::default();
+        let mut history = vec![ChatMessage::user("hi")];
+        for _ in 0..2 {
+            let result = logged_turn(
+                &FailingProvider,
+                &mut history,
+                &Arc::new(Vec::new()),
+                &observer,
+                "mock-model",
+                0.7,
+                &log,
+                false,
+            )
+            .await;
+            assert!(result.is_err());
+        }
+
+        let events = observer.0.lock().unwrap();
+        let turns: Vec<&TurnContext> = events
+            .iter()
+            .map(|event| match event {
+                ObserverEvent::Error {
+                    component,
+                    message,
+                    turn: Some(turn),
+                } => {
+                    assert_eq!(component, "agent");
+                    assert!(message.contains("upstream down"), "{message}");
+                    turn
+                }
+                other => panic!("unexpected event {other:?}"),
+            })
+            .collect();
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0].session_id.as_deref(), Some("session-2"));
+        // Each turn gets its own request id
+        assert_ne!(turns[0].request_id, turns[1].request_id);
+    }
+
+    #[test]
+    fn route_temperature_applies_unless_overridden() {
+        let route = Some(0.1);
+        assert!((resolve_temperature(None, None, route) - 0.1).abs() < f64::EPSILON);
+        assert!((resolve_temperature(Some(0.9), None, route) - 0.9).abs() < f64::EPSILON);
+        assert!((resolve_temperature(None, Some(0.4), route) - 0.4).abs() < f64::EPSILON);
+        assert!((resolve_temperature(None, None, None) - 0.7).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn output_format_parses_text_and_json() {
+        assert_eq!("text".parse::<OutputFormat>(), Ok(OutputFormat::Text));
+        assert_eq!(" JSON ".parse::<OutputFormat>(), Ok(OutputFormat::Json));
+        assert!("yaml".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn piped_message_drops_trailing_newline_only() {
+        let msg = read_piped_message("  line one\nline two\r\n\n".as_bytes()).unwrap();
+        assert_eq!(msg, "  line one\nline two");
+        assert!(read_piped_message(" \n\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn turn_limit_defaults_to_unlimited() {
+        assert!(!turn_limit_reached(1_000, None));
+        assert!(!turn_limit_reached(2, Some(3)));
+        assert!(turn_limit_reached(3, Some(3)));
+        assert!(turn_limit_reached(4, Some(3)));
+    }
+
     #[test]
     fn parse_tool_calls_extracts_single_call() {
         let response = r#"Let me check that.
@@ -591,6 +1454,67 @@ mod tests {
         assert!(calls.is_empty());
     }
 
+    #[test]
+    fn split_thinking_separates_reasoning() {
+        let (thinking, answer) =
+            split_thinking("<thinking>\nUser wants a sum.\n</thinking>\n\nThe answer is 4.");
+        assert_eq!(thinking, "User wants a sum.");
+        assert_eq!(answer, "The answer is 4.");
+
+        let (thinking, answer) = split_thinking("<think>a</think>Mid <reasoning>b</reasoning>end");
+        assert_eq!(thinking, "a\n\nb");
+        assert_eq!(answer, "Mid end");
+    }
+
+    #[test]
+    fn split_thinking_handles_plain_and_unterminated_text() {
+        assert_eq!(
+            split_thinking("No reasoning here."),
+            (String::new(), "No reasoning here.".to_string())
+        );
+        assert_eq!(
+            split_thinking("<thinking>still going"),
+            ("still going".to_string(), String::new())
+        );
+    }
+
+    #[test]
+    fn strip_tool_tags_removes_leaked_results() {
+        let response = "Here is the answer.\n\n<tool_result name=\"shell\">\nfile.txt\n</tool_result>\n\nAll done.";
+        assert_eq!(
+            strip_tool_tags(response),
+            "Here is the answer.\n\nAll done."
+        );
+    }
+
+    #[test]
+    fn strip_tool_tags_removes_stray_and_empty_tags() {
+        let response = "<tool_call></tool_call>Result: 42</tool_result> <tool_result name=\"x\"/>";
+        assert_eq!(strip_tool_tags(response), "Result: 42");
+        assert_eq!(strip_tool_tags("Done.\n</tool_call>"), "Done.");
+        assert_eq!(
+            strip_tool_tags("Partial <tool_call>{\"name\": \"sh"),
+            "Partial"
+        );
+    }
+
+    #[test]
+    fn strip_tool_tags_preserves_code() {
+        let response = "Use this format:\n\n```xml\n<tool_result name=\"shell\">\nok\n</tool_result>\n```\n\nOr inline: `<tool_call>{}</tool_call>`.\n<tool_result>leak</tool_result>";
+        assert_eq!(
+            strip_tool_tags(response),
+            "Use this format:\n\n```xml\n<tool_result name=\"shell\">\nok\n</tool_result>\n```\n\nOr inline: `<tool_call>{}</tool_call>`."
+        );
+    }
+
+    #[test]
+    fn strip_tool_tags_leaves_other_angle_brackets() {
+        let response = "If a < b and <div> is used, <tool_results> are fine.";
+        assert_eq!(strip_tool_tags(response), response);
+        let mixed = "a < b <tool_result>x</tool_result> c > d";
+        assert_eq!(strip_tool_tags(mixed), "a < b  c > d");
+    }
+
     #[test]
     fn parse_tool_calls_handles_malformed_json() {
         let response = r#"<tool_call>
@@ -634,6 +1558,37 @@ After text."#;
         assert!(instructions.contains("file_write"));
     }
 
+    #[test]
+    fn tools_only_leaves_just_the_allowlist_in_the_prompt() {
+        let mut tools = tools::default_tools(Arc::new(SecurityPolicy::default()));
+        let allowed = ["file_read", "search_files", "teleport"].map(String::from);
+        let disabled = tools_outside_allowlist(tools.iter().map(|t| t.name()), &allowed);
+        tools.retain(|t| !disabled.contains(t.name()));
+        let instructions = build_tool_instructions(&tools);
+
+        let listed: Vec<&str> = tools.iter().map(|t| t.name()).collect();
+        assert_eq!(listed, ["file_read", "search_files"]);
+        assert!(instructions.contains("**file_read**"));
+        assert!(instructions.contains("**search_files**"));
+        for name in ["shell", "file_write", "file_patch", "file_multi_patch"] {
+            assert!(!instructions.contains(&format!("**{name}**")), "{name}");
+        }
+
+        assert!(tools_outside_allowlist(["shell", "file_read"], &[]).is_empty());
+    }
+
+    #[test]
+    fn build_tool_instructions_includes_examples() {
+        let security = Arc::new(SecurityPolicy::default());
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(tools::FilePatchTool::new(security))];
+        let instructions = build_tool_instructions(&tools);
+
+        assert!(
+            instructions.contains(r#"Example: <tool_call>{"arguments":{"new_string":"const TIMEOUT_SECS: u64 = 60;""#),
+            "{instructions}"
+        );
+    }
+
     #[test]
     fn trim_history_preserves_system_prompt() {
         let mut history = vec![ChatMessage::system("system prompt")];
@@ -668,4 +1623,74 @@ After text."#;
         trim_history(&mut history);
         assert_eq!(history.len(), 3);
     }
+
+    /// Hands back one entry per recalled slot, recording each limit asked for.
+    #[derive(Default)]
+    struct RecordingMemory {
+        limits: std::sync::Mutex<Vec<usize>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Memory for RecordingMemory {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn store(&self, _key: &str, _content: &str, _category: MemoryCategory) -> Result<()> {
+            Ok(())
+        }
+
+        async fn recall(&self, _query: &str, limit: usize) -> Result<Vec<memory::MemoryEntry>> {
+            self.limits.lock().unwrap().push(limit);
+            Ok((0..limit)
+                .map(|i| memory::MemoryEntry {
+                    id: i.to_string(),
+                    key: format!("fact{i}"),
+                    content: "remembered".into(),
+                    category: MemoryCategory::Core,
+                    timestamp: String::new(),
+                    session_id: None,
+                    score: None,
+                })
+                .collect())
+        }
+
+        async fn get(&self, _key: &str) -> Result<Option<memory::MemoryEntry>> {
+            Ok(None)
+        }
+
+        async fn list(
+            &self,
+            _category: Option<&MemoryCategory>,
+        ) -> Result<Vec<memory::MemoryEntry>> {
+            Ok(Vec::new())
+        }
+
+        async fn forget(&self, _key: &str) -> Result<bool> {
+            Ok(false)
+        }
+
+        async fn count(&self) -> Result<usize> {
+            Ok(0)
+        }
+
+        async fn health_check(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn build_context_recalls_the_configured_number_of_memories() {
+        let mem = RecordingMemory::default();
+
+        let context = build_context(&mem, "hi", 2).await;
+        assert_eq!(
+            context,
+            "[Memory context]\n- fact0: remembered\n- fact1: remembered\n\n"
+        );
+
+        // 0 turns memory injection off without searching at all
+        assert_eq!(build_context(&mem, "hi", 0).await, "");
+        assert_eq!(*mem.limits.lock().unwrap(), vec![2]);
+    }
 }
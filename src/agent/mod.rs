@@ -1,3 +1,7 @@
+pub mod attach;
 pub mod loop_;
+pub mod template;
 
-pub use loop_::run;
+#[cfg(feature = "daemon-feature")]
+pub use loop_::run_unattended;
+pub use loop_::{run, OutputFormat, RunOptions};
@@ -0,0 +1,141 @@
+//! Prompt templates for `tinyclaw agent --template <file> --var key=value`.
+//!
+//! Placeholders are written `{{key}}` (inner whitespace allowed). Keys use
+//! letters, digits, `_`, `-` and `.`; anything else between braces is left
+//! as-is so templates can still contain literal `{{ ... }}` text.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+fn is_key(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Parse a `--var key=value` argument.
+pub fn parse_var(arg: &str) -> Result<(String, String), String> {
+    let (key, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, got {arg:?}"))?;
+    let key = key.trim();
+    if !is_key(key) {
+        return Err(format!(
+            "invalid variable name {key:?}: use letters, digits, '_', '-' and '.'"
+        ));
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// Substitute every `{{key}}` in `template`. Errors listing each placeholder
+/// that has no value; when a key is given twice the last value wins.
+pub fn render(template: &str, vars: &[(String, String)]) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut missing = BTreeSet::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        let name = after[..end].trim();
+        if is_key(name) {
+            match vars.iter().rev().find(|(key, _)| key == name) {
+                Some((_, value)) => out.push_str(value),
+                None => {
+                    missing.insert(name);
+                }
+            }
+        } else {
+            out.push_str(&rest[start..start + 2 + end + 2]);
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+
+    if !missing.is_empty() {
+        anyhow::bail!(
+            "Template has unsubstituted placeholders: {} (pass --var KEY=VALUE)",
+            missing.into_iter().collect::<Vec<_>>().join(", ")
+        );
+    }
+    Ok(out)
+}
+
+/// Read the template at `path` and render it with `vars`.
+pub fn load(path: &Path, vars: &[(String, String)]) -> Result<String> {
+    let template = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read template: {}", path.display()))?;
+    let message = render(&template, vars)
+        .with_context(|| format!("Failed to render template: {}", path.display()))?;
+    if message.trim().is_empty() {
+        anyhow::bail!("Template is empty: {}", path.display());
+    }
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn substitutes_placeholders() {
+        let out = render(
+            "Summarize {{file}} for {{ audience }}. Again: {{file}}.",
+            &vars(&[("file", "notes.md"), ("audience", "execs")]),
+        )
+        .unwrap();
+        assert_eq!(out, "Summarize notes.md for execs. Again: notes.md.");
+
+        // Later --var wins; non-key braces and unclosed braces stay literal
+        let out = render(
+            "{{x}} {{ not a key }} {{x",
+            &vars(&[("x", "1"), ("x", "2")]),
+        )
+        .unwrap();
+        assert_eq!(out, "2 {{ not a key }} {{x");
+    }
+
+    #[test]
+    fn missing_variables_are_an_error() {
+        let err = render("Hi {{name}}, see {{ticket}} and {{name}}", &vars(&[]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("name, ticket"), "{err}");
+    }
+
+    #[test]
+    fn parses_var_arguments() {
+        assert_eq!(
+            parse_var("lang=Rust = fun").unwrap(),
+            ("lang".to_string(), "Rust = fun".to_string())
+        );
+        assert_eq!(parse_var("empty=").unwrap().1, "");
+        assert!(parse_var("novalue").is_err());
+        assert!(parse_var("bad key=1").is_err());
+    }
+
+    #[test]
+    fn load_reads_and_renders_file() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("review.txt");
+        std::fs::write(&path, "Review {{pr}}\n").unwrap();
+        assert_eq!(
+            load(&path, &vars(&[("pr", "#42")])).unwrap(),
+            "Review #42\n"
+        );
+        assert!(load(&tmp.path().join("missing.txt"), &[]).is_err());
+    }
+}
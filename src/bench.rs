@@ -0,0 +1,474 @@
+//! `tinyclaw bench`: run a fixed prompt set against several provider/model
+//! combos and compare latency, token usage and cost side by side.
+//!
+//! Combos run one after another so they don't compete for bandwidth; within a
+//! combo up to `--concurrency` prompts are in flight at once. Responses are
+//! not cached, no tools are offered and each provider is called directly
+//! (no retries or fallbacks), so every combo answers the same bare prompts.
+
+use crate::config::Config;
+use crate::providers::{self, Provider, UsageTracker};
+use crate::util::truncate_with_ellipsis;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// A provider, optionally pinned to a model, from `--combo PROVIDER[=MODEL]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Combo {
+    pub provider: String,
+    pub model: Option<String>,
+}
+
+/// Parse a `--combo` argument. The model is split off at the first `=`, so
+/// provider URLs and model ids may contain `:` and `/`.
+pub fn parse_combo(arg: &str) -> Result<Combo, String> {
+    let (provider, model) = match arg.split_once('=') {
+        Some((provider, model)) => (provider.trim(), Some(model.trim())),
+        None => (arg.trim(), None),
+    };
+    if provider.is_empty() {
+        return Err(format!("expected PROVIDER[=MODEL], got {arg:?}"));
+    }
+    if model.is_some_and(str::is_empty) {
+        return Err(format!("empty model in {arg:?}"));
+    }
+    Ok(Combo {
+        provider: provider.to_string(),
+        model: model.map(String::from),
+    })
+}
+
+/// Read prompts: a JSON array of strings for `.json` files, otherwise one
+/// prompt per line with blank lines and `#` comments skipped.
+pub fn load_prompts(path: &Path) -> Result<Vec<String>> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read prompts file: {}", path.display()))?;
+    let prompts: Vec<String> = if path.extension().and_then(|e| e.to_str()) == Some("json") {
+        serde_json::from_str(&raw)
+            .with_context(|| format!("Expected a JSON array of strings: {}", path.display()))?
+    } else {
+        raw.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect()
+    };
+    if prompts.iter().all(|p| p.trim().is_empty()) {
+        anyhow::bail!("No prompts in {}", path.display());
+    }
+    Ok(prompts)
+}
+
+/// Outcome of one prompt against one combo.
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptResult {
+    pub index: usize,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Everything measured for one combo.
+#[derive(Debug, Clone, Serialize)]
+pub struct ComboReport {
+    pub provider: String,
+    pub model: String,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    pub cost_usd: f64,
+    /// True when the provider reported no pricing for the model and the
+    /// flat [`UsageTracker::estimated_cost_usd`] rate was used instead
+    pub cost_estimated: bool,
+    /// Wall-clock time for the whole prompt set
+    pub wall_ms: u64,
+    pub results: Vec<PromptResult>,
+}
+
+impl ComboReport {
+    pub fn succeeded(&self) -> usize {
+        self.results.iter().filter(|r| r.error.is_none()).count()
+    }
+
+    /// Mean latency of successful prompts.
+    pub fn avg_latency_ms(&self) -> Option<u64> {
+        let ok: Vec<u64> = self
+            .results
+            .iter()
+            .filter(|r| r.error.is_none())
+            .map(|r| r.latency_ms)
+            .collect();
+        (!ok.is_empty()).then(|| ok.iter().sum::<u64>() / ok.len() as u64)
+    }
+
+    pub fn max_latency_ms(&self) -> Option<u64> {
+        self.results
+            .iter()
+            .filter(|r| r.error.is_none())
+            .map(|r| r.latency_ms)
+            .max()
+    }
+}
+
+fn elapsed_ms(start: Instant) -> u64 {
+    u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Send every prompt to `provider` with at most `concurrency` in flight.
+/// Failures are recorded per prompt; results come back in prompt order.
+pub async fn run_prompts(
+    provider: Arc<dyn Provider>,
+    usage: &UsageTracker,
+    model: &str,
+    prompts: &[String],
+    temperature: f64,
+    concurrency: usize,
+) -> Vec<PromptResult> {
+    let permits = Arc::new(tokio::sync::Semaphore::new(concurrency.max(1)));
+    let mut tasks = tokio::task::JoinSet::new();
+    for (index, prompt) in prompts.iter().enumerate() {
+        let provider = Arc::clone(&provider);
+        let permits = Arc::clone(&permits);
+        let usage = usage.clone();
+        let model = model.to_string();
+        let prompt = prompt.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let start = Instant::now();
+            let outcome = provider.chat(&prompt, &model, temperature).await;
+            usage.record_latency(start.elapsed());
+            let latency_ms = elapsed_ms(start);
+            match outcome {
+                Ok(response) => PromptResult {
+                    index,
+                    latency_ms,
+                    response: Some(response),
+                    error: None,
+                },
+                Err(e) => PromptResult {
+                    index,
+                    latency_ms,
+                    response: None,
+                    error: Some(format!("{e:#}")),
+                },
+            }
+        });
+    }
+
+    let mut results = Vec::with_capacity(prompts.len());
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(result) => results.push(result),
+            Err(e) => tracing::warn!("Bench task failed: {e}"),
+        }
+    }
+    results.sort_by_key(|r| r.index);
+    results
+}
+
+/// USD cost from the provider's advertised pricing for `model`, if any.
+async fn priced_cost(provider: &dyn Provider, model: &str, usage: &UsageTracker) -> Option<f64> {
+    let models = provider.list_models().await.ok()?;
+    let info = models.into_iter().find(|m| m.id == model)?;
+    let (input, output) = (info.prompt_price_per_m?, info.completion_price_per_m?);
+    let snap = usage.snapshot();
+    #[allow(clippy::cast_precision_loss)]
+    Some((snap.prompt_tokens as f64 * input + snap.completion_tokens as f64 * output) / 1_000_000.0)
+}
+
+/// Comparison table, one row per combo.
+pub fn format_table(reports: &[ComboReport], prompt_count: usize) -> String {
+    let mut out = format!(
+        "{:<14} {:<36} {:>7} {:>8} {:>8} {:>9} {:>9} {:>10}\n",
+        "PROVIDER", "MODEL", "OK", "AVG ms", "MAX ms", "PROMPT", "COMPL", "COST"
+    );
+    for report in reports {
+        let dash = || "-".to_string();
+        let cost = if report.cost_estimated {
+            format!("≈${:.4}", report.cost_usd)
+        } else {
+            format!("${:.4}", report.cost_usd)
+        };
+        let _ = writeln!(
+            out,
+            "{:<14} {:<36} {:>7} {:>8} {:>8} {:>9} {:>9} {:>10}",
+            truncate_with_ellipsis(&report.provider, 14),
+            truncate_with_ellipsis(&report.model, 36),
+            format!("{}/{prompt_count}", report.succeeded()),
+            report
+                .avg_latency_ms()
+                .map_or_else(dash, |ms| ms.to_string()),
+            report
+                .max_latency_ms()
+                .map_or_else(dash, |ms| ms.to_string()),
+            report.prompt_tokens,
+            report.completion_tokens,
+            cost,
+        );
+    }
+    if reports.iter().any(|r| r.cost_estimated) {
+        out.push_str(
+            "≈ estimated at a flat rate; the provider reported no pricing for the model\n",
+        );
+    }
+    out
+}
+
+#[derive(Serialize)]
+struct BenchOutput<'a> {
+    generated_at: String,
+    temperature: f64,
+    prompts: &'a [String],
+    combos: &'a [ComboReport],
+}
+
+/// Run the prompt set in `prompts_path` against each combo and print the
+/// comparison, optionally writing full results to `json_path`.
+pub async fn run(
+    config: Config,
+    prompts_path: &Path,
+    combos: Vec<Combo>,
+    temperature: f64,
+    concurrency: usize,
+    json_path: Option<PathBuf>,
+) -> Result<()> {
+    let prompts = load_prompts(prompts_path)?;
+    let combos = if combos.is_empty() {
+        vec![Combo {
+            provider: config
+                .default_provider
+                .clone()
+                .unwrap_or_else(|| "openrouter".into()),
+            model: None,
+        }]
+    } else {
+        combos
+    };
+
+    println!(
+        "Benchmarking {} prompts across {} combos (concurrency {})",
+        prompts.len(),
+        combos.len(),
+        concurrency.max(1)
+    );
+
+    let mut reports = Vec::with_capacity(combos.len());
+    for combo in combos {
+        let model = combo
+            .model
+            .unwrap_or_else(|| config.default_model_for(&combo.provider));
+        println!("  {} / {model} ...", combo.provider);
+
+        // The named provider itself: no retries, fallbacks or model routes
+        // that would time a different backend than the one in the table
+        let api_key = config
+            .providers
+            .api_key_for(&combo.provider)
+            .or(config.api_key.as_deref());
+        let mut provider = providers::create_provider(&combo.provider, api_key)
+            .with_context(|| format!("Failed to create provider '{}'", combo.provider))?;
+        let usage = UsageTracker::new();
+        provider.set_usage_tracker(usage.clone());
+        provider.set_seed(config.providers.seed);
+        provider.set_reasoning_effort(config.providers.reasoning_effort);
+        provider.set_stop_sequences(&config.providers.stop_sequences)?;
+        let provider: Arc<dyn Provider> = Arc::from(provider);
+
+        let start = Instant::now();
+        let results = run_prompts(
+            Arc::clone(&provider),
+            &usage,
+            &model,
+            &prompts,
+            temperature,
+            concurrency,
+        )
+        .await;
+        let wall_ms = elapsed_ms(start);
+
+        for failure in results
+            .iter()
+            .filter_map(|r| r.error.as_ref().map(|e| (r.index, e)))
+        {
+            println!(
+                "    ❌ prompt {}: {}",
+                failure.0 + 1,
+                truncate_with_ellipsis(failure.1, 120)
+            );
+        }
+
+        let priced = priced_cost(provider.as_ref(), &model, &usage).await;
+        let snap = usage.snapshot();
+        reports.push(ComboReport {
+            provider: combo.provider,
+            model,
+            prompt_tokens: snap.prompt_tokens,
+            completion_tokens: snap.completion_tokens,
+            total_tokens: snap.total_tokens,
+            cost_usd: priced.unwrap_or_else(|| usage.estimated_cost_usd()),
+            cost_estimated: priced.is_none(),
+            wall_ms,
+            results,
+        });
+    }
+
+    println!();
+    print!("{}", format_table(&reports, prompts.len()));
+
+    if let Some(path) = json_path {
+        let output = BenchOutput {
+            generated_at: chrono::Utc::now().to_rfc3339(),
+            temperature,
+            prompts: &prompts,
+            combos: &reports,
+        };
+        std::fs::write(&path, serde_json::to_string_pretty(&output)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        println!("\nResults written to {}", path.display());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn parses_combos() {
+        assert_eq!(
+            parse_combo("openrouter=anthropic/claude-sonnet-4").unwrap(),
+            Combo {
+                provider: "openrouter".into(),
+                model: Some("anthropic/claude-sonnet-4".into()),
+            }
+        );
+        assert_eq!(
+            parse_combo("custom:http://localhost:1234=llama3:8b").unwrap(),
+            Combo {
+                provider: "custom:http://localhost:1234".into(),
+                model: Some("llama3:8b".into()),
+            }
+        );
+        assert_eq!(parse_combo("groq").unwrap().model, None);
+        assert!(parse_combo("=gpt-4o").is_err());
+        assert!(parse_combo("openai=").is_err());
+    }
+
+    #[test]
+    fn loads_line_and_json_prompt_files() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let text = tmp.path().join("prompts.txt");
+        std::fs::write(&text, "# warmup\nWhat is 2+2?\n\n  Name a prime.  \n").unwrap();
+        assert_eq!(
+            load_prompts(&text).unwrap(),
+            vec!["What is 2+2?", "Name a prime."]
+        );
+
+        let json = tmp.path().join("prompts.json");
+        std::fs::write(&json, r#"["Line one\nline two", "Second"]"#).unwrap();
+        assert_eq!(load_prompts(&json).unwrap()[0], "Line one\nline two");
+
+        let empty = tmp.path().join("empty.txt");
+        std::fs::write(&empty, "# nothing\n").unwrap();
+        assert!(load_prompts(&empty).is_err());
+    }
+
+    /// Fails prompts containing "fail" and tracks the peak number in flight.
+    struct SlowEcho {
+        in_flight: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Provider for SlowEcho {
+        fn name(&self) -> &str {
+            "slow-echo"
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            if message.contains("fail") {
+                anyhow::bail!("boom");
+            }
+            Ok(format!("echo: {message}"))
+        }
+    }
+
+    #[tokio::test]
+    async fn run_prompts_bounds_concurrency_and_keeps_order() {
+        let provider = Arc::new(SlowEcho {
+            in_flight: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        });
+        let prompts: Vec<String> = ["a", "fail b", "c", "d", "e"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let usage = UsageTracker::new();
+
+        let results = run_prompts(provider.clone(), &usage, "m", &prompts, 0.0, 2).await;
+        assert_eq!(provider.peak.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            results.iter().map(|r| r.index).collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+        assert_eq!(results[0].response.as_deref(), Some("echo: a"));
+        assert!(results[1].error.as_deref().unwrap().contains("boom"));
+        assert_eq!(usage.latency_samples.load(Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn table_shows_one_row_per_combo() {
+        let report = |provider: &str, estimated: bool| ComboReport {
+            provider: provider.into(),
+            model: "m".into(),
+            prompt_tokens: 100,
+            completion_tokens: 50,
+            total_tokens: 150,
+            cost_usd: 0.0012,
+            cost_estimated: estimated,
+            wall_ms: 300,
+            results: vec![
+                PromptResult {
+                    index: 0,
+                    latency_ms: 100,
+                    response: Some("ok".into()),
+                    error: None,
+                },
+                PromptResult {
+                    index: 1,
+                    latency_ms: 300,
+                    response: None,
+                    error: Some("boom".into()),
+                },
+            ],
+        };
+        let table = format_table(&[report("groq", false), report("openai", true)], 2);
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(lines[0].starts_with("PROVIDER"));
+        assert!(lines[1].starts_with("groq"));
+        // Failed prompts count against OK and are left out of latency
+        assert!(lines[1].contains("1/2") && lines[1].contains(" 100 "));
+        assert!(lines[1].contains("$0.0012") && !lines[1].contains('≈'));
+        assert!(lines[2].contains("≈$0.0012"));
+        assert!(table.contains("estimated at a flat rate"));
+    }
+}
@@ -12,6 +12,8 @@ pub mod irc;
 #[cfg(feature = "channels-feature")]
 pub mod matrix;
 #[cfg(feature = "channels-feature")]
+pub mod pool;
+#[cfg(feature = "channels-feature")]
 pub mod slack;
 #[cfg(feature = "channels-feature")]
 pub mod telegram;
@@ -41,9 +43,12 @@ pub use whatsapp::WhatsAppChannel;
 use crate::config::Config;
 use crate::identity;
 use crate::memory::{self, Memory};
+#[cfg(feature = "channels-feature")]
+use crate::observability::{self, Observer, ObserverEvent, TurnContext};
 use crate::providers::{self, Provider};
-use crate::util::truncate_with_ellipsis;
-use anyhow::Result;
+#[cfg(feature = "channels-feature")]
+use crate::util::{truncate_with_ellipsis, AbortOnDrop};
+use anyhow::{Context, Result};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
@@ -53,6 +58,11 @@ const BOOTSTRAP_MAX_CHARS: usize = 20_000;
 const DEFAULT_CHANNEL_INITIAL_BACKOFF_SECS: u64 = 2;
 const DEFAULT_CHANNEL_MAX_BACKOFF_SECS: u64 = 60;
 const CHANNEL_MESSAGE_TIMEOUT_SECS: u64 = 90;
+/// Messages (user and assistant) remembered per channel sender.
+const CHANNEL_HISTORY_MAX_MESSAGES: usize = 20;
+/// A sender's history is forgotten after this long without a message.
+const CHANNEL_HISTORY_IDLE_SECS: u64 = 24 * 60 * 60;
+const CHANNEL_START_PROBE_SECS: u64 = 10;
 
 fn spawn_supervised_listener(
     ch: Arc<dyn Channel>,
@@ -252,6 +262,9 @@ pub fn build_system_prompt(
         std::env::consts::OS,
     );
 
+    // ── 8. Organization policy suffix ───────────────────────────
+    append_prompt_suffix(&mut prompt, identity_config);
+
     if prompt.is_empty() {
         "You are TinyClaw, a fast and efficient AI assistant built in Rust. Be helpful, concise, and direct.".to_string()
     } else {
@@ -259,6 +272,46 @@ pub fn build_system_prompt(
     }
 }
 
+fn append_prompt_suffix(
+    prompt: &mut String,
+    identity_config: Option<&crate::config::IdentityConfig>,
+) {
+    if let Some(suffix) = identity_config
+        .and_then(|c| c.prompt_suffix.as_deref())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        prompt.push_str(suffix);
+        prompt.push_str("\n\n");
+    }
+}
+
+/// Load a hand-written base system prompt (`--system-file`) in place of
+/// [`build_system_prompt`]. The organization policy suffix is still appended.
+pub fn load_system_prompt_file(
+    path: &std::path::Path,
+    identity_config: Option<&crate::config::IdentityConfig>,
+) -> Result<String> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read system prompt file: {}", path.display()))?;
+    let content = content.trim();
+    if content.is_empty() {
+        anyhow::bail!("System prompt file is empty: {}", path.display());
+    }
+    Ok(custom_system_prompt(content, identity_config))
+}
+
+/// Use `content` as the base system prompt (a `--system-file` or persona
+/// prompt), followed by the organization policy suffix.
+pub fn custom_system_prompt(
+    content: &str,
+    identity_config: Option<&crate::config::IdentityConfig>,
+) -> String {
+    let mut prompt = format!("{}\n\n", content.trim());
+    append_prompt_suffix(&mut prompt, identity_config);
+    prompt
+}
+
 /// Inject a single workspace file into the prompt with truncation and missing-file markers.
 fn inject_workspace_file(prompt: &mut String, workspace_dir: &std::path::Path, filename: &str) {
     use std::fmt::Write;
@@ -331,11 +384,26 @@ pub fn handle_command(command: crate::ChannelCommands, config: &Config) -> Resul
         }
         crate::ChannelCommands::Add {
             channel_type,
-            config: _,
+            config: json,
         } => {
-            anyhow::bail!(
-                "Channel type '{channel_type}' — use `tinyclaw onboard` to configure channels"
-            );
+            // Only config.toml is saved back, so config.local.toml and
+            // environment overrides never end up in it
+            let mut base = Config::load_base_or_init()?;
+            let name = add_channel_config(&mut base.channels_config, &channel_type, &json)?;
+            base.save()?;
+            println!("✅ {name} saved to {}", base.config_path.display());
+            let whatsapp = base.channels_config.whatsapp.as_ref();
+            if let (Some(whatsapp), "WhatsApp") = (whatsapp, name) {
+                println!("   Run `tinyclaw gateway` and set the Meta webhook callback URL to");
+                println!("   https://<public host>/whatsapp with the same verify_token.");
+                if whatsapp.allowed_numbers.is_empty() {
+                    println!(
+                        "   ⚠️  allowed_numbers is empty, so every sender is ignored; \
+                         add E.164 numbers or \"*\"."
+                    );
+                }
+            }
+            Ok(())
         }
         crate::ChannelCommands::Remove { name } => {
             anyhow::bail!("Remove channel '{name}' — edit ~/.tinyclaw/config.toml directly");
@@ -343,6 +411,133 @@ pub fn handle_command(command: crate::ChannelCommands, config: &Config) -> Resul
     }
 }
 
+/// Store the channel described by `json` in `channels`, as `channel add
+/// <type> <json>` and `onboard --channels-only --add <type> <json>` do.
+/// Returns the channel's display name.
+fn add_channel_config(
+    channels: &mut crate::config::ChannelsConfig,
+    channel_type: &str,
+    json: &str,
+) -> Result<&'static str> {
+    let name = match channel_type.to_ascii_lowercase().as_str() {
+        "telegram" => {
+            channels.telegram = Some(parse_channel_config(
+                "Telegram",
+                json,
+                r#"{"bot_token": "...", "allowed_users": ["..."]}"#,
+            )?);
+            "Telegram"
+        }
+        "discord" => {
+            channels.discord = Some(parse_channel_config(
+                "Discord",
+                json,
+                r#"{"bot_token": "...", "allowed_users": ["..."]}"#,
+            )?);
+            "Discord"
+        }
+        "slack" => {
+            channels.slack = Some(parse_channel_config(
+                "Slack",
+                json,
+                r#"{"bot_token": "...", "channel_id": "..."}"#,
+            )?);
+            "Slack"
+        }
+        "webhook" => {
+            channels.webhook = Some(parse_channel_config(
+                "Webhook",
+                json,
+                r#"{"port": 8080, "secret": "..."}"#,
+            )?);
+            "Webhook"
+        }
+        "imessage" => {
+            channels.imessage = Some(parse_channel_config(
+                "iMessage",
+                json,
+                r#"{"allowed_contacts": ["..."]}"#,
+            )?);
+            "iMessage"
+        }
+        "matrix" => {
+            channels.matrix = Some(parse_channel_config(
+                "Matrix",
+                json,
+                r#"{"homeserver": "...", "access_token": "...", "room_id": "...", "allowed_users": ["..."]}"#,
+            )?);
+            "Matrix"
+        }
+        "whatsapp" => {
+            channels.whatsapp = Some(parse_channel_config(
+                "WhatsApp",
+                json,
+                r#"{"phone_number_id": "...", "access_token": "...", "verify_token": "..."}"#,
+            )?);
+            "WhatsApp"
+        }
+        "email" => {
+            channels.email = Some(parse_channel_config(
+                "Email",
+                json,
+                r#"{"imap_host": "...", "smtp_host": "...", "username": "...", "password": "...", "from_address": "..."}"#,
+            )?);
+            "Email"
+        }
+        "irc" => {
+            channels.irc = Some(parse_channel_config(
+                "IRC",
+                json,
+                r##"{"server": "...", "nickname": "...", "channels": ["#..."]}"##,
+            )?);
+            "IRC"
+        }
+        _ => anyhow::bail!(
+            "Unknown channel type '{channel_type}' (expected telegram, discord, slack, \
+             webhook, imessage, matrix, whatsapp, email or irc)"
+        ),
+    };
+    Ok(name)
+}
+
+/// Parse the JSON object configuring one channel. Missing required fields,
+/// fields the channel doesn't have and blank strings are all rejected, so a
+/// typo fails here instead of being dropped from the saved config. `example`
+/// shows the required fields in the error.
+fn parse_channel_config<T>(name: &str, json: &str, example: &str) -> Result<T>
+where
+    T: serde::de::DeserializeOwned + serde::Serialize,
+{
+    let context = || format!("Invalid {name} config; expected JSON like {example}");
+    let given: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(json).with_context(context)?;
+    let config: T =
+        serde_json::from_value(serde_json::Value::Object(given.clone())).with_context(context)?;
+
+    let serde_json::Value::Object(known) = serde_json::to_value(&config)? else {
+        return Ok(config);
+    };
+    let unknown: Vec<&str> = given
+        .keys()
+        .filter(|field| !known.contains_key(*field))
+        .map(String::as_str)
+        .collect();
+    if !unknown.is_empty() {
+        let expected: Vec<&str> = known.keys().map(String::as_str).collect();
+        anyhow::bail!(
+            "Invalid {name} config: unknown field(s) {} (expected {})",
+            unknown.join(", "),
+            expected.join(", ")
+        );
+    }
+    for (field, value) in &given {
+        if value.as_str().is_some_and(|s| s.trim().is_empty()) {
+            anyhow::bail!("Invalid {name} config: {field} must not be empty");
+        }
+    }
+    Ok(config)
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ChannelHealthState {
     Healthy,
@@ -491,32 +686,231 @@ pub async fn doctor_channels(config: Config) -> Result<()> {
     Ok(())
 }
 
-/// Start all configured channels and route messages to the agent
+/// Run each channel's health check concurrently (bounded by `timeout`) and
+/// split them into channels that can start and `(name, reason)` failures.
+async fn probe_channels(
+    channels: Vec<Arc<dyn Channel>>,
+    timeout: Duration,
+) -> (Vec<Arc<dyn Channel>>, Vec<(String, String)>) {
+    let probes: Vec<_> = channels
+        .into_iter()
+        .map(|ch| {
+            let probe = Arc::clone(&ch);
+            let handle = tokio::spawn(async move {
+                let result = tokio::time::timeout(timeout, probe.health_check()).await;
+                classify_health_result(&result)
+            });
+            (ch, handle)
+        })
+        .collect();
+
+    let mut started = Vec::new();
+    let mut failed = Vec::new();
+    for (ch, probe) in probes {
+        let reason = match probe.await {
+            Ok(ChannelHealthState::Healthy) => {
+                started.push(ch);
+                continue;
+            }
+            Ok(ChannelHealthState::Unhealthy) => {
+                "health check failed (auth/config/network)".to_string()
+            }
+            Ok(ChannelHealthState::Timeout) => {
+                format!("health check timed out (>{}s)", timeout.as_secs())
+            }
+            Err(e) => format!("health check panicked: {e}"),
+        };
+        failed.push((ch.name().to_string(), reason));
+    }
+    (started, failed)
+}
+
+fn format_start_summary(started: &[String], failed: &[(String, String)]) -> String {
+    use std::fmt::Write;
+
+    let mut out = format!(
+        "  📡 Channels: {} started, {} failed",
+        started.len(),
+        failed.len()
+    );
+    for name in started {
+        let _ = write!(out, "\n     ✅ {name}");
+    }
+    for (name, reason) in failed {
+        let _ = write!(out, "\n     ❌ {name}: {reason}");
+    }
+    out
+}
+
+/// Shared state for the channel message handlers.
+#[cfg(feature = "channels-feature")]
+struct MessageContext {
+    provider: Arc<dyn Provider>,
+    model: String,
+    temperature: f64,
+    mem: Arc<dyn Memory>,
+    auto_save: bool,
+    system_prompt: String,
+    channels: Vec<Arc<dyn Channel>>,
+    conversations: Arc<pool::Conversations>,
+    observer: Arc<dyn Observer>,
+}
+
+/// Answer one inbound message with the sender's conversation so far, and
+/// reply on the channel it came from. The observer events of the exchange,
+/// including its own `AgentStart`/`AgentEnd`, share one request id; the
+/// session id is `<channel>_<sender>`.
 #[allow(clippy::too_many_lines)]
 #[cfg(feature = "channels-feature")]
+async fn handle_message(ctx: &MessageContext, msg: traits::ChannelMessage) {
+    println!(
+        "  💬 [{}] from {}: {}",
+        msg.channel,
+        msg.sender,
+        truncate_with_ellipsis(&msg.content, 80)
+    );
+    let turn = TurnContext::new(Some(&format!("{}_{}", msg.channel, msg.sender)));
+    ctx.observer.record_event(&ObserverEvent::ChannelMessage {
+        channel: msg.channel.clone(),
+        direction: "inbound".into(),
+        turn: Some(turn.clone()),
+    });
+
+    // Auto-save to memory
+    if ctx.auto_save {
+        let _ = ctx
+            .mem
+            .store(
+                &format!("{}_{}", msg.channel, msg.sender),
+                &msg.content,
+                crate::memory::MemoryCategory::Conversation,
+            )
+            .await;
+    }
+
+    // Only one of a sender's messages is answered at a time (see
+    // `Conversations::enqueue`), so this is current until the reply is recorded
+    let history = ctx.conversations.history(&msg.channel, &msg.sender);
+    let mut messages = Vec::with_capacity(history.len() + 2);
+    messages.push(providers::ChatMessage::system(&ctx.system_prompt));
+    messages.extend(history.iter().cloned());
+    messages.push(providers::ChatMessage::user(&msg.content));
+
+    // Call the LLM with system prompt (identity + soul + tools)
+    println!("  ⏳ Processing message...");
+    let started_at = Instant::now();
+    ctx.observer.record_event(&ObserverEvent::AgentStart {
+        provider: ctx.provider.name().to_string(),
+        model: ctx.model.clone(),
+        turn: Some(turn.clone()),
+    });
+
+    let llm_result = tokio::time::timeout(
+        Duration::from_secs(CHANNEL_MESSAGE_TIMEOUT_SECS),
+        ctx.provider
+            .chat_with_history_served(&messages, &ctx.model, ctx.temperature),
+    )
+    .await;
+
+    let mut served_by = ctx.provider.name().to_string();
+    let reply = match llm_result {
+        Ok(Ok(providers::Served {
+            text: response,
+            provider,
+        })) => {
+            served_by = provider;
+            println!(
+                "  🤖 Reply ({}ms): {}",
+                started_at.elapsed().as_millis(),
+                truncate_with_ellipsis(&response, 80)
+            );
+            ctx.conversations
+                .record(&msg.channel, &msg.sender, &msg.content, &response);
+            response
+        }
+        Ok(Err(e)) => {
+            eprintln!(
+                "  ❌ LLM error after {}ms: {e}",
+                started_at.elapsed().as_millis()
+            );
+            ctx.observer.record_event(&ObserverEvent::Error {
+                component: "channel".into(),
+                message: format!("{e:#}"),
+                turn: Some(turn.clone()),
+            });
+            format!("⚠️ Error: {e}")
+        }
+        Err(_) => {
+            let timeout_msg = format!(
+                "LLM response timed out after {}s",
+                CHANNEL_MESSAGE_TIMEOUT_SECS
+            );
+            eprintln!(
+                "  ❌ {} (elapsed: {}ms)",
+                timeout_msg,
+                started_at.elapsed().as_millis()
+            );
+            ctx.observer.record_event(&ObserverEvent::Error {
+                component: "channel".into(),
+                message: timeout_msg,
+                turn: Some(turn.clone()),
+            });
+            "⚠️ Request timed out while waiting for the model. Please try again.".to_string()
+        }
+    };
+    ctx.observer.record_event(&ObserverEvent::AgentEnd {
+        provider: served_by,
+        duration: started_at.elapsed(),
+        tokens_used: None,
+        turn: Some(turn.clone()),
+    });
+
+    // Find the channel that sent this message and reply
+    if let Some(ch) = ctx.channels.iter().find(|ch| ch.name() == msg.channel) {
+        match ch.send(&reply, &msg.sender).await {
+            Ok(()) => ctx.observer.record_event(&ObserverEvent::ChannelMessage {
+                channel: msg.channel.clone(),
+                direction: "outbound".into(),
+                turn: Some(turn),
+            }),
+            Err(e) => eprintln!("  ❌ Failed to reply on {}: {e}", ch.name()),
+        }
+    }
+}
+
+/// Start all configured channels and route messages to the agent
+#[cfg(feature = "channels-feature")]
 pub async fn start_channels(config: Config) -> Result<()> {
-    let provider: Arc<dyn Provider> = Arc::from(providers::create_resilient_provider(
+    serve_channels(config, Arc::default()).await
+}
+
+/// [`start_channels`], holding a read lock on `busy` while each message is
+/// answered, so whoever takes the write lock knows no turn is in flight.
+#[allow(clippy::too_many_lines)]
+#[cfg(feature = "channels-feature")]
+pub async fn serve_channels(config: Config, busy: Arc<tokio::sync::RwLock<()>>) -> Result<()> {
+    let mut provider = providers::create_resilient_provider(
         config.default_provider.as_deref().unwrap_or("openrouter"),
         config.api_key.as_deref(),
+        &config.providers,
         &config.reliability,
-    )?);
+    )?;
+    provider.set_seed(config.providers.seed);
+    provider.set_reasoning_effort(config.providers.reasoning_effort);
+    provider.set_stop_sequences(&config.providers.stop_sequences)?;
+    let provider: Arc<dyn Provider> = Arc::from(provider);
 
-    // Warm up the provider connection pool (TLS handshake, DNS, HTTP/2 setup)
-    // so the first real message doesn't hit a cold-start timeout.
-    if let Err(e) = provider.warmup().await {
-        tracing::warn!("Provider warmup failed (non-fatal): {e}");
-    }
+    providers::warm_up(provider.as_ref()).await;
 
-    let model = config
-        .default_model
-        .clone()
-        .unwrap_or_else(|| "anthropic/claude-sonnet-4-20250514".into());
+    let model =
+        config.default_model_for(config.default_provider.as_deref().unwrap_or("openrouter"));
     let temperature = config.default_temperature;
     let mem: Arc<dyn Memory> = Arc::from(memory::create_memory(
         &config.memory,
         &config.workspace_dir,
         config.api_key.as_deref(),
     )?);
+    memory::gc_expired(mem.as_ref()).await;
 
     // Build system prompt from workspace identity files + skills
     let workspace = config.workspace_dir.clone();
@@ -556,6 +950,10 @@ pub async fn start_channels(config: Config) -> Result<()> {
             "Open approved HTTPS URLs in Brave Browser (allowlist-only, no scraping)",
         ));
     }
+    let tools_only = &config.autonomy.tools_only;
+    if !tools_only.is_empty() {
+        tool_descs.retain(|(name, _)| tools_only.iter().any(|t| t == name));
+    }
 
     let system_prompt = build_system_prompt(
         &workspace,
@@ -648,6 +1046,20 @@ pub async fn start_channels(config: Config) -> Result<()> {
         return Ok(());
     }
 
+    // Probe each channel independently so one bad token doesn't take down the rest
+    let (channels, failed) =
+        probe_channels(channels, Duration::from_secs(CHANNEL_START_PROBE_SECS)).await;
+    for (name, reason) in &failed {
+        crate::health::mark_component_error(&format!("channel:{name}"), reason);
+        tracing::warn!("Channel {name} failed to start: {reason}");
+        eprintln!("⚠️  Channel {name} failed to start: {reason}");
+    }
+    let started: Vec<String> = channels.iter().map(|c| c.name().to_string()).collect();
+    let summary = format_start_summary(&started, &failed);
+    if channels.is_empty() {
+        anyhow::bail!("No channels could be started:\n{summary}");
+    }
+
     println!("🦀 TinyClaw Channel Server");
     println!("  🤖 Model:    {model}");
     println!(
@@ -655,19 +1067,21 @@ pub async fn start_channels(config: Config) -> Result<()> {
         config.memory.backend,
         if config.memory.auto_save { "on" } else { "off" }
     );
-    println!(
-        "  📡 Channels: {}",
-        channels
-            .iter()
-            .map(|c| c.name())
-            .collect::<Vec<_>>()
-            .join(", ")
-    );
+    println!("{summary}");
     println!();
     println!("  Listening for messages... (Ctrl+C to stop)");
     println!();
 
-    crate::health::mark_component_ok("channels");
+    if failed.is_empty() {
+        crate::health::mark_component_ok("channels");
+    } else {
+        // Keep the skipped channels visible in `status` and /health
+        let names: Vec<&str> = failed.iter().map(|(name, _)| name.as_str()).collect();
+        crate::health::mark_component_error(
+            "channels",
+            format!("not started: {}", names.join(", ")),
+        );
+    }
 
     let initial_backoff_secs = config
         .reliability
@@ -681,105 +1095,67 @@ pub async fn start_channels(config: Config) -> Result<()> {
     // Single message bus — all channels send messages here
     let (tx, mut rx) = tokio::sync::mpsc::channel::<traits::ChannelMessage>(100);
 
-    // Spawn a listener for each channel
+    // Spawn a listener for each channel. They are aborted along with this
+    // future, so a daemon reload that cancels it doesn't leave old pollers
+    // running next to the new ones.
     let mut handles = Vec::new();
     for ch in &channels {
-        handles.push(spawn_supervised_listener(
+        handles.push(AbortOnDrop(spawn_supervised_listener(
             ch.clone(),
             tx.clone(),
             initial_backoff_secs,
             max_backoff_secs,
-        ));
+        )));
     }
     drop(tx); // Drop our copy so rx closes when all channels stop
 
-    // Process incoming messages — call the LLM and reply
+    // Process incoming messages on a bounded pool. While every worker is
+    // busy the rest wait on the bus, in arrival order. A message from a
+    // sender who is still being answered waits in that sender's queue, and
+    // the worker answering them takes it next.
+    let ctx = Arc::new(MessageContext {
+        provider,
+        model,
+        temperature,
+        mem,
+        auto_save: config.memory.auto_save,
+        system_prompt,
+        channels,
+        conversations: Arc::new(pool::Conversations::new(
+            CHANNEL_HISTORY_MAX_MESSAGES,
+            Duration::from_secs(CHANNEL_HISTORY_IDLE_SECS),
+        )),
+        observer: Arc::from(observability::create_observer(&config.observability)),
+    });
+    let mut workers = pool::MessagePool::new(config.channels_config.max_concurrent_messages);
     while let Some(msg) = rx.recv().await {
-        println!(
-            "  💬 [{}] from {}: {}",
-            msg.channel,
-            msg.sender,
-            truncate_with_ellipsis(&msg.content, 80)
-        );
-
-        // Auto-save to memory
-        if config.memory.auto_save {
-            let _ = mem
-                .store(
-                    &format!("{}_{}", msg.channel, msg.sender),
-                    &msg.content,
-                    crate::memory::MemoryCategory::Conversation,
-                )
-                .await;
-        }
-
-        // Call the LLM with system prompt (identity + soul + tools)
-        println!("  ⏳ Processing message...");
-        let started_at = Instant::now();
-
-        let llm_result = tokio::time::timeout(
-            Duration::from_secs(CHANNEL_MESSAGE_TIMEOUT_SECS),
-            provider.chat_with_system(Some(&system_prompt), &msg.content, &model, temperature),
-        )
-        .await;
-
-        match llm_result {
-            Ok(Ok(response)) => {
-                println!(
-                    "  🤖 Reply ({}ms): {}",
-                    started_at.elapsed().as_millis(),
-                    truncate_with_ellipsis(&response, 80)
-                );
-                // Find the channel that sent this message and reply
-                for ch in &channels {
-                    if ch.name() == msg.channel {
-                        if let Err(e) = ch.send(&response, &msg.sender).await {
-                            eprintln!("  ❌ Failed to reply on {}: {e}", ch.name());
-                        }
-                        break;
-                    }
-                }
-            }
-            Ok(Err(e)) => {
-                eprintln!(
-                    "  ❌ LLM error after {}ms: {e}",
-                    started_at.elapsed().as_millis()
-                );
-                for ch in &channels {
-                    if ch.name() == msg.channel {
-                        let _ = ch.send(&format!("⚠️ Error: {e}"), &msg.sender).await;
-                        break;
-                    }
-                }
-            }
-            Err(_) => {
-                let timeout_msg = format!(
-                    "LLM response timed out after {}s",
-                    CHANNEL_MESSAGE_TIMEOUT_SECS
-                );
-                eprintln!(
-                    "  ❌ {} (elapsed: {}ms)",
-                    timeout_msg,
-                    started_at.elapsed().as_millis()
-                );
-                for ch in &channels {
-                    if ch.name() == msg.channel {
-                        let _ = ch
-                            .send(
-                                "⚠️ Request timed out while waiting for the model. Please try again.",
-                                &msg.sender,
-                            )
-                            .await;
-                        break;
-                    }
+        let Some(work) = crate::health::work_tracker().begin() else {
+            tracing::info!("Shutdown in progress; no longer accepting channel messages");
+            break;
+        };
+        let Some((msg, mut sender_busy)) = ctx.conversations.enqueue(msg) else {
+            continue;
+        };
+        let ctx = Arc::clone(&ctx);
+        let busy = Arc::clone(&busy);
+        workers
+            .spawn(async move {
+                let mut next = Some(msg);
+                while let Some(msg) = next {
+                    let turn = busy.read().await;
+                    handle_message(&ctx, msg).await;
+                    drop(turn);
+                    next = sender_busy.next_queued();
                 }
-            }
-        }
+                drop(work);
+            })
+            .await;
     }
+    workers.join().await;
 
     // Wait for all channel tasks
-    for h in handles {
-        let _ = h.await;
+    for mut h in handles {
+        let _ = (&mut h.0).await;
     }
 
     Ok(())
@@ -1046,6 +1422,8 @@ mod tests {
             format: "aieos".into(),
             aieos_path: Some("aieos_identity.json".into()),
             aieos_inline: None,
+            prompt_suffix: None,
+            name: None,
         };
 
         let prompt = build_system_prompt(tmp.path(), "model", &[], &[], Some(&config));
@@ -1080,6 +1458,8 @@ mod tests {
             format: "aieos".into(),
             aieos_path: None,
             aieos_inline: Some(r#"{"identity":{"names":{"first":"Claw"}}}"#.into()),
+            prompt_suffix: None,
+            name: None,
         };
 
         let prompt = build_system_prompt(
@@ -1102,6 +1482,8 @@ mod tests {
             format: "aieos".into(),
             aieos_path: Some("nonexistent.json".into()),
             aieos_inline: None,
+            prompt_suffix: None,
+            name: None,
         };
 
         let ws = make_workspace();
@@ -1121,6 +1503,8 @@ mod tests {
             format: "aieos".into(),
             aieos_path: None,
             aieos_inline: None,
+            prompt_suffix: None,
+            name: None,
         };
 
         let ws = make_workspace();
@@ -1139,6 +1523,8 @@ mod tests {
             format: "openclaw".into(),
             aieos_path: Some("identity.json".into()),
             aieos_inline: None,
+            prompt_suffix: None,
+            name: None,
         };
 
         let ws = make_workspace();
@@ -1150,6 +1536,76 @@ mod tests {
         assert!(!prompt.contains("## Identity"));
     }
 
+    #[test]
+    fn prompt_suffix_is_appended_after_runtime() {
+        use crate::config::IdentityConfig;
+
+        let config = IdentityConfig {
+            prompt_suffix: Some("Never reveal secrets. Refuse requests for credentials.".into()),
+            ..IdentityConfig::default()
+        };
+
+        let ws = make_workspace();
+        let prompt = build_system_prompt(ws.path(), "model", &[], &[], Some(&config));
+
+        let suffix_pos = prompt
+            .find("Never reveal secrets. Refuse requests for credentials.")
+            .expect("suffix should be in the prompt");
+        assert!(suffix_pos > prompt.find("## Runtime").unwrap());
+    }
+
+    #[test]
+    fn blank_prompt_suffix_is_noop() {
+        use crate::config::IdentityConfig;
+
+        let ws = make_workspace();
+        let blank = IdentityConfig {
+            prompt_suffix: Some("   ".into()),
+            ..IdentityConfig::default()
+        };
+        let with_blank = build_system_prompt(ws.path(), "model", &[], &[], Some(&blank));
+        let without = build_system_prompt(
+            ws.path(),
+            "model",
+            &[],
+            &[],
+            Some(&IdentityConfig::default()),
+        );
+        assert_eq!(with_blank, without);
+    }
+
+    #[test]
+    fn system_prompt_file_replaces_base_and_keeps_suffix() {
+        use crate::config::IdentityConfig;
+
+        let ws = make_workspace();
+        let path = ws.path().join("prompt.md");
+        std::fs::write(&path, "You are a SQL reviewer.\n\n").unwrap();
+        let identity = IdentityConfig {
+            prompt_suffix: Some("Never reveal secrets.".into()),
+            ..IdentityConfig::default()
+        };
+
+        let prompt = load_system_prompt_file(&path, Some(&identity)).unwrap();
+        assert_eq!(
+            prompt,
+            "You are a SQL reviewer.\n\nNever reveal secrets.\n\n"
+        );
+        assert!(!prompt.contains("## Safety"));
+    }
+
+    #[test]
+    fn system_prompt_file_must_exist_and_be_non_empty() {
+        let ws = make_workspace();
+        let err = load_system_prompt_file(&ws.path().join("nope.md"), None).unwrap_err();
+        assert!(err.to_string().contains("Failed to read"));
+
+        let empty = ws.path().join("empty.md");
+        std::fs::write(&empty, "  \n").unwrap();
+        let err = load_system_prompt_file(&empty, None).unwrap_err();
+        assert!(err.to_string().contains("empty"));
+    }
+
     #[test]
     fn none_identity_config_uses_openclaw() {
         let ws = make_workspace();
@@ -1234,4 +1690,220 @@ mod tests {
             .contains("listen boom"));
         assert!(calls.load(Ordering::SeqCst) >= 1);
     }
+
+    struct ProbeChannel {
+        name: &'static str,
+        healthy: bool,
+        delay: Duration,
+    }
+
+    #[async_trait::async_trait]
+    impl Channel for ProbeChannel {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn send(&self, _message: &str, _recipient: &str) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn listen(
+            &self,
+            _tx: tokio::sync::mpsc::Sender<traits::ChannelMessage>,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn health_check(&self) -> bool {
+            tokio::time::sleep(self.delay).await;
+            self.healthy
+        }
+    }
+
+    #[tokio::test]
+    async fn probe_keeps_healthy_channels_and_reports_broken_ones() {
+        let channels: Vec<Arc<dyn Channel>> = vec![
+            Arc::new(ProbeChannel {
+                name: "good",
+                healthy: true,
+                delay: Duration::ZERO,
+            }),
+            Arc::new(ProbeChannel {
+                name: "bad-token",
+                healthy: false,
+                delay: Duration::ZERO,
+            }),
+            Arc::new(ProbeChannel {
+                name: "hung",
+                healthy: true,
+                delay: Duration::from_secs(5),
+            }),
+        ];
+
+        let (started, failed) = probe_channels(channels, Duration::from_millis(50)).await;
+
+        assert_eq!(started.len(), 1);
+        assert_eq!(started[0].name(), "good");
+        assert_eq!(failed.len(), 2);
+        assert_eq!(failed[0].0, "bad-token");
+        assert!(failed[0].1.contains("health check failed"));
+        assert_eq!(failed[1].0, "hung");
+        assert!(failed[1].1.contains("timed out"));
+    }
+
+    #[test]
+    fn start_summary_lists_started_and_failed() {
+        let summary = format_start_summary(
+            &["telegram".to_string()],
+            &[("discord".to_string(), "health check failed".to_string())],
+        );
+        assert!(summary.contains("1 started, 1 failed"));
+        assert!(summary.contains("✅ telegram"));
+        assert!(summary.contains("❌ discord: health check failed"));
+    }
+
+    #[test]
+    fn whatsapp_add_config_requires_cloud_api_fields() {
+        let parse_whatsapp_config = |json| {
+            parse_channel_config::<crate::config::schema::WhatsAppConfig>(
+                "WhatsApp",
+                json,
+                r#"{"phone_number_id": "...", "access_token": "...", "verify_token": "..."}"#,
+            )
+        };
+        let config = parse_whatsapp_config(
+            r#"{"phone_number_id": "123456", "access_token": "EAAG", "verify_token": "hook",
+                "allowed_numbers": ["+15551234567"]}"#,
+        )
+        .unwrap();
+        assert_eq!(config.phone_number_id, "123456");
+        assert_eq!(config.verify_token, "hook");
+        assert_eq!(config.allowed_numbers, ["+15551234567"]);
+        assert!(config.app_secret.is_none());
+
+        let err = parse_whatsapp_config(r#"{"phone_number_id": "123456"}"#).unwrap_err();
+        assert!(format!("{err:#}").contains("verify_token"), "{err:#}");
+        let err = parse_whatsapp_config(
+            r#"{"phone_number_id": " ", "access_token": "EAAG", "verify_token": "hook"}"#,
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("phone_number_id must not be empty"));
+        assert!(parse_whatsapp_config("not json").is_err());
+    }
+
+    #[test]
+    fn add_channel_config_checks_fields_against_the_channel() {
+        let mut channels = crate::config::ChannelsConfig::default();
+        let name = add_channel_config(
+            &mut channels,
+            "Telegram",
+            r#"{"bot_token": "123:abc", "allowed_users": ["alice"]}"#,
+        )
+        .unwrap();
+        assert_eq!(name, "Telegram");
+        let telegram = channels.telegram.as_ref().unwrap();
+        assert_eq!(telegram.bot_token, "123:abc");
+        assert_eq!(telegram.allowed_users, ["alice"]);
+
+        let err = add_channel_config(
+            &mut channels,
+            "discord",
+            r#"{"bot_token": "x", "guild": "1"}"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown field(s) guild"), "{err}");
+        assert!(err.to_string().contains("guild_id"), "{err}");
+        assert!(channels.discord.is_none());
+
+        let err = add_channel_config(&mut channels, "fax", "{}").unwrap_err();
+        assert!(err.to_string().contains("Unknown channel type 'fax'"));
+    }
+
+    /// Keeps every event it is given.
+    #[cfg(feature = "channels-feature")]
+    #[derive(Default)]
+    struct RecordingObserver {
+        events: std::sync::Mutex<Vec<ObserverEvent>>,
+    }
+
+    #[cfg(feature = "channels-feature")]
+    impl Observer for RecordingObserver {
+        fn record_event(&self, event: &ObserverEvent) {
+            self.events.lock().unwrap().push(event.clone());
+        }
+
+        fn record_metric(&self, _metric: &crate::observability::traits::ObserverMetric) {}
+
+        fn name(&self) -> &str {
+            "recording"
+        }
+    }
+
+    #[cfg(feature = "channels-feature")]
+    struct EchoProvider;
+
+    #[cfg(feature = "channels-feature")]
+    #[async_trait::async_trait]
+    impl Provider for EchoProvider {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok(message.to_string())
+        }
+    }
+
+    #[cfg(feature = "channels-feature")]
+    #[tokio::test]
+    async fn message_events_share_one_turn() {
+        let ws = make_workspace();
+        let observer = Arc::new(RecordingObserver::default());
+        let ctx = MessageContext {
+            provider: Arc::new(EchoProvider),
+            model: "m".into(),
+            temperature: 0.7,
+            mem: Arc::new(crate::memory::markdown::MarkdownMemory::new(ws.path())),
+            auto_save: false,
+            system_prompt: "system".into(),
+            channels: Vec::new(),
+            conversations: Arc::new(pool::Conversations::new(10, Duration::from_secs(60))),
+            observer: observer.clone(),
+        };
+
+        handle_message(
+            &ctx,
+            traits::ChannelMessage {
+                id: "1".into(),
+                sender: "alice".into(),
+                content: "hi".into(),
+                channel: "test".into(),
+                timestamp: 0,
+            },
+        )
+        .await;
+
+        let events = observer.events.lock().unwrap();
+        let turns: Vec<_> = events
+            .iter()
+            .map(|event| match event {
+                ObserverEvent::ChannelMessage { turn, .. }
+                | ObserverEvent::AgentStart { turn, .. }
+                | ObserverEvent::AgentEnd { turn, .. } => turn.clone().unwrap(),
+                other => panic!("unexpected event {other:?}"),
+            })
+            .collect();
+        assert_eq!(turns.len(), 3, "{events:?}");
+        assert!(turns.iter().all(|t| t == &turns[0]));
+        assert_eq!(turns[0].session_id.as_deref(), Some("test_alice"));
+        assert!(matches!(events[2], ObserverEvent::AgentEnd { .. }));
+    }
 }
@@ -0,0 +1,338 @@
+//! Concurrency for inbound channel messages: a bounded worker pool, and one
+//! conversation history and message queue per channel + sender.
+
+use super::traits::ChannelMessage;
+use crate::providers::ChatMessage;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Runs message handlers on at most `size` tasks at once.
+///
+/// [`MessagePool::spawn`] waits for a free worker, so while the pool is full
+/// the caller stops reading and later messages stay queued, in order, on the
+/// channel message bus.
+pub struct MessagePool {
+    permits: Arc<Semaphore>,
+    tasks: JoinSet<()>,
+}
+
+impl MessagePool {
+    /// A pool of `size` workers (at least one).
+    pub fn new(size: usize) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(size.max(1))),
+            tasks: JoinSet::new(),
+        }
+    }
+
+    /// Wait for a free worker, then run `job` on it.
+    pub async fn spawn<F>(&mut self, job: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        // Reap finished handlers so the set doesn't grow for the whole run
+        while self.tasks.try_join_next().is_some() {}
+        let Ok(permit) = Arc::clone(&self.permits).acquire_owned().await else {
+            return;
+        };
+        self.tasks.spawn(async move {
+            job.await;
+            drop(permit);
+        });
+    }
+
+    /// Wait for every running handler to finish.
+    pub async fn join(mut self) {
+        while let Some(result) = self.tasks.join_next().await {
+            if let Err(e) = result {
+                tracing::error!("Channel message handler panicked: {e}");
+            }
+        }
+    }
+}
+
+/// One sender's chat history and the messages waiting behind the one being
+/// answered.
+struct Conversation {
+    history: Vec<ChatMessage>,
+    queued: VecDeque<ChannelMessage>,
+    /// A handler is answering this sender
+    busy: bool,
+    last_active: Instant,
+}
+
+/// Per-sender chat history and message queue, keyed by channel name and
+/// sender.
+///
+/// One sender's messages are answered in order by a single handler at a
+/// time; later ones wait here instead of tying up a pool worker, while
+/// different senders are handled in parallel. Conversations idle for longer
+/// than the TTL are forgotten.
+pub struct Conversations {
+    max_messages: usize,
+    idle_ttl: Duration,
+    conversations: Mutex<HashMap<(String, String), Conversation>>,
+}
+
+impl Conversations {
+    /// Keep at most `max_messages` messages (user and assistant) per sender,
+    /// for senders heard from within `idle_ttl`.
+    pub fn new(max_messages: usize, idle_ttl: Duration) -> Self {
+        Self {
+            max_messages,
+            idle_ttl,
+            conversations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<(String, String), Conversation>> {
+        self.conversations
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Take `msg` for its sender. When the sender was idle, returns the
+    /// message the caller should answer now (the oldest one still queued)
+    /// and a guard marking the sender busy; `None` when it was queued behind
+    /// a message still being answered, to be handed out by
+    /// [`BusyGuard::next_queued`].
+    pub fn enqueue(self: &Arc<Self>, msg: ChannelMessage) -> Option<(ChannelMessage, BusyGuard)> {
+        let mut conversations = self.lock();
+        let now = Instant::now();
+        conversations.retain(|_, c| c.busy || now.duration_since(c.last_active) <= self.idle_ttl);
+        let conversation = conversations
+            .entry((msg.channel.clone(), msg.sender.clone()))
+            .or_insert_with(|| Conversation {
+                history: Vec::new(),
+                queued: VecDeque::new(),
+                busy: false,
+                last_active: now,
+            });
+        conversation.last_active = now;
+        // Messages left queued by a handler that panicked go first
+        conversation.queued.push_back(msg);
+        if conversation.busy {
+            return None;
+        }
+        let msg = conversation.queued.pop_front()?;
+        conversation.busy = true;
+        let guard = BusyGuard {
+            conversations: Arc::clone(self),
+            key: (msg.channel.clone(), msg.sender.clone()),
+            idle: false,
+        };
+        Some((msg, guard))
+    }
+
+    /// The history for `sender` on `channel` so far.
+    pub fn history(&self, channel: &str, sender: &str) -> Vec<ChatMessage> {
+        self.lock()
+            .get(&(channel.to_string(), sender.to_string()))
+            .map(|c| c.history.clone())
+            .unwrap_or_default()
+    }
+
+    /// Append one exchange to the sender's history, dropping the oldest
+    /// messages once it grows past the limit.
+    pub fn record(&self, channel: &str, sender: &str, user: &str, assistant: &str) {
+        let mut conversations = self.lock();
+        let Some(conversation) = conversations.get_mut(&(channel.to_string(), sender.to_string()))
+        else {
+            return;
+        };
+        let history = &mut conversation.history;
+        history.push(ChatMessage::user(user));
+        history.push(ChatMessage::assistant(assistant));
+        let excess = history.len().saturating_sub(self.max_messages);
+        history.drain(..excess);
+    }
+}
+
+/// Marks one sender busy while its messages are answered.
+///
+/// Dropping it, whether the handler returned, panicked or was aborted, makes
+/// the sender idle again, so their later messages are still answered.
+pub struct BusyGuard {
+    conversations: Arc<Conversations>,
+    key: (String, String),
+    /// The sender was already marked idle by [`Self::next_queued`]
+    idle: bool,
+}
+
+impl BusyGuard {
+    /// The sender's next queued message, or `None` (and the sender is idle
+    /// again) once the queue is empty.
+    pub fn next_queued(&mut self) -> Option<ChannelMessage> {
+        let mut conversations = self.conversations.lock();
+        let conversation = conversations.get_mut(&self.key)?;
+        conversation.last_active = Instant::now();
+        let next = conversation.queued.pop_front();
+        conversation.busy = next.is_some();
+        self.idle = next.is_none();
+        next
+    }
+}
+
+impl Drop for BusyGuard {
+    fn drop(&mut self) {
+        if self.idle {
+            return;
+        }
+        if let Some(conversation) = self.conversations.lock().get_mut(&self.key) {
+            conversation.busy = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn pool_caps_concurrent_handlers() {
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let done = Arc::new(AtomicUsize::new(0));
+        let mut pool = MessagePool::new(2);
+
+        for _ in 0..6 {
+            let (running, peak, done) = (active.clone(), peak.clone(), done.clone());
+            pool.spawn(async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(30)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                done.fetch_add(1, Ordering::SeqCst);
+            })
+            .await;
+            assert!(active.load(Ordering::SeqCst) <= 2);
+        }
+        pool.join().await;
+
+        assert_eq!(done.load(Ordering::SeqCst), 6);
+        assert_eq!(peak.load(Ordering::SeqCst), 2, "both workers were used");
+    }
+
+    #[tokio::test]
+    async fn spawn_waits_while_pool_is_full() {
+        let mut pool = MessagePool::new(1);
+        let (release_tx, release_rx) = tokio::sync::oneshot::channel::<()>();
+        pool.spawn(async move {
+            let _ = release_rx.await;
+        })
+        .await;
+
+        // The second job can't start until the first finishes
+        let second = tokio::time::timeout(Duration::from_millis(50), pool.spawn(async {})).await;
+        assert!(
+            second.is_err(),
+            "spawn should block while the worker is busy"
+        );
+
+        release_tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(1), pool.spawn(async {}))
+            .await
+            .expect("worker is free again");
+        pool.join().await;
+    }
+
+    fn message(channel: &str, sender: &str, content: &str) -> ChannelMessage {
+        ChannelMessage {
+            id: content.into(),
+            sender: sender.into(),
+            content: content.into(),
+            channel: channel.into(),
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn conversations_are_kept_per_channel_and_sender() {
+        let conversations = Arc::new(Conversations::new(4, Duration::from_secs(60)));
+        assert!(conversations
+            .enqueue(message("telegram", "alice", "hi"))
+            .is_some());
+        conversations.record("telegram", "alice", "hi", "hello");
+        conversations.record("telegram", "alice", "how are you", "fine");
+        conversations.record("telegram", "alice", "bye", "see you");
+        let history = conversations.history("telegram", "alice");
+        assert_eq!(history.len(), 4, "oldest exchange dropped");
+        assert_eq!(history[0].content, "how are you");
+
+        assert!(conversations.history("telegram", "bob").is_empty());
+        assert!(conversations.history("discord", "alice").is_empty());
+    }
+
+    #[test]
+    fn a_busy_sender_has_messages_queued_in_order() {
+        let conversations = Arc::new(Conversations::new(4, Duration::from_secs(60)));
+        let (first, mut alice) = conversations
+            .enqueue(message("telegram", "alice", "1"))
+            .unwrap();
+        assert_eq!(first.content, "1");
+        assert!(conversations
+            .enqueue(message("telegram", "alice", "2"))
+            .is_none());
+        assert!(conversations
+            .enqueue(message("telegram", "alice", "3"))
+            .is_none());
+        // Another sender is not held up
+        assert!(conversations
+            .enqueue(message("telegram", "bob", "x"))
+            .is_some());
+
+        let mut next = || alice.next_queued().map(|m| m.content);
+        assert_eq!(next().as_deref(), Some("2"));
+        assert_eq!(next().as_deref(), Some("3"));
+        assert_eq!(next(), None);
+        drop(alice);
+        // Idle again: the next message is answered straight away
+        assert!(conversations
+            .enqueue(message("telegram", "alice", "4"))
+            .is_some());
+    }
+
+    #[test]
+    fn a_sender_is_idle_again_after_a_handler_panics() {
+        let conversations = Arc::new(Conversations::new(4, Duration::from_secs(60)));
+        let (_, alice) = conversations
+            .enqueue(message("telegram", "alice", "1"))
+            .unwrap();
+        assert!(conversations
+            .enqueue(message("telegram", "alice", "2"))
+            .is_none());
+        let handler = std::thread::spawn(move || {
+            let _alice = alice;
+            panic!("handler failed");
+        });
+        assert!(handler.join().is_err());
+
+        // Not stuck busy; what was queued is answered first, in order
+        let (next, mut alice) = conversations
+            .enqueue(message("telegram", "alice", "3"))
+            .expect("the sender is idle again");
+        assert_eq!(next.content, "2");
+        assert_eq!(alice.next_queued().map(|m| m.content).as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn idle_conversations_are_forgotten() {
+        let conversations = Arc::new(Conversations::new(4, Duration::ZERO));
+        let (_, mut alice) = conversations
+            .enqueue(message("telegram", "alice", "hi"))
+            .unwrap();
+        conversations.record("telegram", "alice", "hi", "hello");
+        assert!(alice.next_queued().is_none());
+        std::thread::sleep(Duration::from_millis(5));
+
+        conversations.enqueue(message("telegram", "bob", "hi"));
+        assert!(conversations.history("telegram", "alice").is_empty());
+        assert_eq!(conversations.lock().len(), 1);
+    }
+}
@@ -1,9 +1,11 @@
 pub mod schema;
 
+pub use schema::{set_tinyclaw_dir, tinyclaw_dir};
 pub use schema::{
-    AutonomyConfig, BrowserConfig, ChannelsConfig, ComposioConfig, Config, DiscordConfig,
-    DockerRuntimeConfig, EmailConfig, GatewayConfig, HeartbeatConfig, IMessageConfig,
-    IdentityConfig, MatrixConfig, MemoryConfig, ModelRouteConfig, ObservabilityConfig,
-    ReliabilityConfig, RuntimeConfig, SecretsConfig, SlackConfig, TelegramConfig, TunnelConfig,
+    AutonomyConfig, BrowserConfig, ChannelsConfig, CheckConfig, ComposioConfig, Config,
+    DiscordConfig, DockerRuntimeConfig, EmailConfig, GatewayConfig, HeartbeatConfig,
+    IMessageConfig, IdentityConfig, MatrixConfig, MemoryConfig, ModelRouteConfig, NetworkConfig,
+    ObservabilityConfig, ProvidersConfig, ReliabilityConfig, RuntimeConfig, SecretsConfig,
+    SecurityConfig, SessionConfig, SlackConfig, TelegramConfig, TuiConfig, TunnelConfig,
     WebhookConfig,
 };
@@ -2,9 +2,11 @@ use crate::security::AutonomyLevel;
 use anyhow::{Context, Result};
 use directories::UserDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::{self, File, OpenOptions};
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 // ── Top-level config ──────────────────────────────────────────────
 
@@ -19,6 +21,7 @@ pub struct Config {
     pub api_key: Option<String>,
     pub default_provider: Option<String>,
     pub default_model: Option<String>,
+    #[serde(default = "default_temperature")]
     pub default_temperature: f64,
 
     #[serde(default)]
@@ -63,6 +66,28 @@ pub struct Config {
 
     #[serde(default)]
     pub identity: IdentityConfig,
+
+    #[serde(default)]
+    pub tui: TuiConfig,
+
+    #[serde(default)]
+    pub network: NetworkConfig,
+
+    #[serde(default)]
+    pub security: SecurityConfig,
+
+    #[serde(default)]
+    pub providers: ProvidersConfig,
+
+    #[serde(default)]
+    pub session: SessionConfig,
+
+    #[serde(default)]
+    pub check: CheckConfig,
+}
+
+fn default_temperature() -> f64 {
+    0.7
 }
 
 // ── Identity (AIEOS / OpenClaw format) ──────────────────────────
@@ -78,6 +103,12 @@ pub struct IdentityConfig {
     /// Inline AIEOS JSON (alternative to file path)
     #[serde(default)]
     pub aieos_inline: Option<String>,
+    /// Policy text appended to every system prompt (e.g. organization guardrails)
+    #[serde(default)]
+    pub prompt_suffix: Option<String>,
+    /// Display name for the assistant in interactive UIs (e.g. the TUI prefix)
+    #[serde(default)]
+    pub name: Option<String>,
 }
 
 fn default_identity_format() -> String {
@@ -90,6 +121,191 @@ impl Default for IdentityConfig {
             format: default_identity_format(),
             aieos_path: None,
             aieos_inline: None,
+            prompt_suffix: None,
+            name: None,
+        }
+    }
+}
+
+// ── Network ──────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Proxy URL for all provider HTTP(S) traffic (e.g. `http://proxy.corp:3128`)
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// Hosts that bypass the proxy (e.g. `["localhost", ".internal.corp"]`)
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+    /// Idle provider connections kept open per host (default: unlimited)
+    #[serde(default)]
+    pub pool_max_idle_per_host: Option<usize>,
+    /// Seconds an idle provider connection stays open; 0 keeps it until the
+    /// server closes it (default: 90)
+    #[serde(default = "default_pool_idle_timeout_secs")]
+    pub pool_idle_timeout_secs: u64,
+    /// Interval of TCP keep-alive probes on provider connections; 0 turns
+    /// them off (default: 15)
+    #[serde(default = "default_tcp_keepalive_secs")]
+    pub tcp_keepalive_secs: u64,
+    /// Extra root certificate(s) to trust, as a PEM bundle or one DER
+    /// certificate, for endpoints signed by a private CA
+    #[serde(default)]
+    pub ca_cert_path: Option<PathBuf>,
+    /// Skip TLS certificate verification for provider requests. Insecure:
+    /// only for local development against self-signed endpoints
+    /// (default: false)
+    #[serde(default)]
+    pub danger_accept_invalid_certs: bool,
+}
+
+fn default_pool_idle_timeout_secs() -> u64 {
+    90
+}
+
+fn default_tcp_keepalive_secs() -> u64 {
+    15
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            no_proxy: Vec::new(),
+            pool_max_idle_per_host: None,
+            pool_idle_timeout_secs: default_pool_idle_timeout_secs(),
+            tcp_keepalive_secs: default_tcp_keepalive_secs(),
+            ca_cert_path: None,
+            danger_accept_invalid_certs: false,
+        }
+    }
+}
+
+// ── TUI ──────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct TuiConfig {
+    /// Color preset: "dark" (default), "light", "high-contrast" or
+    /// "monochrome". The `*_color` settings override single colors of it.
+    #[serde(default)]
+    pub theme: Option<String>,
+    /// Accent color for the header badge and input border (name or `#rrggbb`)
+    #[serde(default)]
+    pub accent_color: Option<String>,
+    /// Color of the user's message prefix
+    #[serde(default)]
+    pub user_color: Option<String>,
+    /// Color of the assistant's message prefix
+    #[serde(default)]
+    pub assistant_color: Option<String>,
+    /// Color of tool results
+    #[serde(default)]
+    pub tool_color: Option<String>,
+    /// Color of error messages
+    #[serde(default)]
+    pub error_color: Option<String>,
+    /// Also recall slash commands with Up/Down, not just messages
+    /// (default: false)
+    #[serde(default)]
+    pub history_includes_commands: bool,
+    /// Start each read-only tool call as soon as its `<tool_call>` block has
+    /// streamed, while the rest of the reply is still generating. Calls that
+    /// may have side effects still wait for the full reply (default: false)
+    #[serde(default)]
+    pub eager_tool_calls: bool,
+    /// Reuse results of identical read-only tool calls (`file_read`,
+    /// `search_files`, `web_fetch`) within a session (default: false)
+    #[serde(default)]
+    pub cache_tool_results: bool,
+    /// After this many identical replies (same text and tool calls) in a
+    /// row, skip the calls and tell the model it is repeating itself; one
+    /// more repeat ends the turn. 0 disables the guard (default: 3)
+    #[serde(default = "default_repeat_limit")]
+    pub repeat_limit: usize,
+    /// Release streamed text at a steady pace instead of in bursts as it
+    /// arrives; the finished reply is unchanged (default: false)
+    #[serde(default)]
+    pub smooth_output: bool,
+    /// Characters per second shown when `smooth_output` is on; the pace
+    /// picks up when the stream gets further ahead (default: 400)
+    #[serde(default = "default_smooth_output_cps")]
+    pub smooth_output_cps: u32,
+    /// Save the session and exit after this many seconds without a key
+    /// press while no turn is running. 0 disables it (default: 0)
+    #[serde(default)]
+    pub idle_timeout_secs: u64,
+    /// Characters of each tool result shown in the chat. Unset, each tool
+    /// uses its own length (400 for `git`, 300 for `check`, otherwise 120)
+    #[serde(default)]
+    pub tool_preview_len: Option<usize>,
+    /// Run the tool calls of a reply one at a time, in the order the model
+    /// wrote them, instead of concurrently; `/ordered` toggles it. Calls to
+    /// file-editing tools always run this way (default: false). TUI only:
+    /// `tinyclaw agent` orders a reply's calls only when one edits files, and
+    /// channels run no tools.
+    #[serde(default)]
+    pub sequential_tools: bool,
+}
+
+fn default_repeat_limit() -> usize {
+    3
+}
+
+fn default_smooth_output_cps() -> u32 {
+    400
+}
+
+impl Default for TuiConfig {
+    fn default() -> Self {
+        Self {
+            theme: None,
+            accent_color: None,
+            user_color: None,
+            assistant_color: None,
+            tool_color: None,
+            error_color: None,
+            history_includes_commands: false,
+            eager_tool_calls: false,
+            cache_tool_results: false,
+            repeat_limit: default_repeat_limit(),
+            smooth_output: false,
+            smooth_output_cps: default_smooth_output_cps(),
+            idle_timeout_secs: 0,
+            tool_preview_len: None,
+            sequential_tools: false,
+        }
+    }
+}
+
+// ── Session ──────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionConfig {
+    /// Append a human-readable log of each turn to
+    /// `transcripts/<session>.log` as it happens (default: false)
+    #[serde(default)]
+    pub transcript: bool,
+    /// TUI: write the session file once this many turns are unsaved
+    /// (default: 1, every turn; 0 = only on `save_interval_secs`)
+    #[serde(default = "default_save_every_turns")]
+    pub save_every_turns: u32,
+    /// TUI: also write it when this many seconds have passed since the last
+    /// save (default: 0, off). Unsaved turns are always written on exit
+    #[serde(default)]
+    pub save_interval_secs: u64,
+}
+
+fn default_save_every_turns() -> u32 {
+    1
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            transcript: false,
+            save_every_turns: default_save_every_turns(),
+            save_interval_secs: 0,
         }
     }
 }
@@ -210,6 +426,134 @@ impl Default for SecretsConfig {
     }
 }
 
+// ── Providers (request options shared by all providers) ─────────
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ProvidersConfig {
+    /// Sampling seed for reproducible outputs (honoured by the `openai` and
+    /// `openrouter` providers; others ignore it)
+    #[serde(default)]
+    pub seed: Option<u64>,
+
+    /// Sequences that end generation (`stop` for `openai`/`openrouter`, at
+    /// most 4; `stop_sequences` for `anthropic`)
+    #[serde(default)]
+    pub stop_sequences: Vec<String>,
+
+    /// Reasoning effort: `low`, `medium` or `high` (`reasoning_effort` for
+    /// `openai`, `reasoning.effort` for `openrouter`, a thinking budget for
+    /// `anthropic`; others ignore it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<crate::providers::ReasoningEffort>,
+
+    /// Cut off a streamed reply after this many characters, ending the
+    /// request and keeping what arrived with a truncation notice
+    /// (default: no limit)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_response_chars: Option<usize>,
+
+    /// Per-provider settings from `[providers.<name>]` tables. Other keys
+    /// under `[providers]` that are not tables (a setting from a newer or
+    /// older version, or a typo) are warned about and ignored.
+    #[serde(flatten, deserialize_with = "deserialize_provider_entries")]
+    pub entries: HashMap<String, ProviderEntryConfig>,
+}
+
+fn deserialize_provider_entries<'de, D>(
+    deserializer: D,
+) -> std::result::Result<HashMap<String, ProviderEntryConfig>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let raw = HashMap::<String, toml::Value>::deserialize(deserializer)?;
+    let mut entries = HashMap::new();
+    for (name, value) in raw {
+        if value.is_table() {
+            let entry = value
+                .try_into()
+                .map_err(|e| serde::de::Error::custom(format!("[providers.{name}]: {e}")))?;
+            entries.insert(name, entry);
+        } else {
+            tracing::warn!("Ignoring unknown setting `{name}` under [providers]");
+        }
+    }
+    Ok(entries)
+}
+
+impl ProvidersConfig {
+    /// Model configured under `[providers.<name>] default_model`, if any.
+    pub fn default_model_for(&self, name: &str) -> Option<&str> {
+        self.entries
+            .get(name)
+            .and_then(|entry| entry.default_model.as_deref())
+            .map(str::trim)
+            .filter(|model| !model.is_empty())
+    }
+
+    /// API key configured under `[providers.<name>]`, if any.
+    pub fn api_key_for(&self, name: &str) -> Option<&str> {
+        self.entries
+            .get(name)
+            .and_then(|entry| entry.api_key.as_deref())
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ProviderEntryConfig {
+    /// API key for this provider; takes precedence over the top-level `api_key`
+    #[serde(default)]
+    pub api_key: Option<String>,
+
+    /// App URL sent as `HTTP-Referer` for `OpenRouter` app attribution
+    /// (`openrouter` only)
+    #[serde(default)]
+    pub referer: Option<String>,
+
+    /// App name sent as `X-Title` for `OpenRouter` app attribution
+    /// (`openrouter` only)
+    #[serde(default)]
+    pub app_title: Option<String>,
+
+    /// Send the system prompt at the top of the first user message instead
+    /// of as a `system` message, for models that ignore the system role
+    #[serde(default)]
+    pub fold_system_into_user: bool,
+
+    /// Model used with this provider when neither `--model` nor, for the
+    /// default provider, `default_model` is set
+    #[serde(default)]
+    pub default_model: Option<String>,
+}
+
+// ── Security (tool output redaction) ────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SecurityConfig {
+    /// Replace secrets (API keys, private keys, high-entropy tokens) in tool
+    /// output with `[REDACTED]` before it is added to the conversation
+    #[serde(default)]
+    pub redact_secrets: bool,
+    /// Extra regex patterns to redact, on top of the built-in ones
+    #[serde(default)]
+    pub redact_patterns: Vec<String>,
+    /// Rewrites applied, in order, to every final assistant reply before it
+    /// is shown or saved
+    #[serde(default)]
+    pub output_transforms: Vec<OutputTransform>,
+}
+
+/// One `[[security.output_transforms]]` rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputTransform {
+    /// Regex matched against the reply
+    pub pattern: String,
+    /// Replacement for each match; `$1` / `${name}` expand capture groups
+    #[serde(default)]
+    pub replacement: String,
+}
+
 // ── Browser (friendly-service browsing only) ───────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -225,6 +569,21 @@ pub struct BrowserConfig {
     pub session_name: Option<String>,
 }
 
+// ── Check tool ───────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CheckConfig {
+    /// Checker for the `check` tool: "rust", "typescript" or "python"
+    /// (default: detected from Cargo.toml, tsconfig.json or *.py files)
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Program and arguments to run instead of the built-in checker,
+    /// e.g. `["cargo", "clippy", "--message-format", "short"]`. The program
+    /// must be in `autonomy.allowed_commands`, like the built-in ones.
+    #[serde(default)]
+    pub command: Vec<String>,
+}
+
 // ── Memory ───────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -266,6 +625,15 @@ pub struct MemoryConfig {
     /// Max tokens per chunk for document splitting
     #[serde(default = "default_chunk_size")]
     pub chunk_max_tokens: usize,
+    /// Days after its last write that an entry of a category stops being
+    /// recalled and is deleted at startup, e.g. `{ conversation = 7,
+    /// daily = 30 }`. Categories left out never expire (default: none)
+    #[serde(default)]
+    pub ttl_days: HashMap<String, u32>,
+    /// Memories recalled for each message and put in front of it as
+    /// `[Memory context]`; 0 turns that off (default: 5)
+    #[serde(default = "default_recall_limit")]
+    pub recall_limit: usize,
 }
 
 fn default_embedding_provider() -> String {
@@ -301,6 +669,9 @@ fn default_cache_size() -> usize {
 fn default_chunk_size() -> usize {
     512
 }
+fn default_recall_limit() -> usize {
+    5
+}
 
 impl Default for MemoryConfig {
     fn default() -> Self {
@@ -318,6 +689,8 @@ impl Default for MemoryConfig {
             keyword_weight: default_keyword_weight(),
             embedding_cache_size: default_cache_size(),
             chunk_max_tokens: default_chunk_size(),
+            ttl_days: HashMap::new(),
+            recall_limit: default_recall_limit(),
         }
     }
 }
@@ -336,6 +709,19 @@ pub struct ObservabilityConfig {
     /// Service name reported to the OTel collector. Defaults to "tinyclaw".
     #[serde(default)]
     pub otel_service_name: Option<String>,
+
+    /// Extra HTTP headers sent with every OTLP export (e.g. auth tokens).
+    #[serde(default)]
+    pub otel_headers: HashMap<String, String>,
+
+    /// Console log level: "off" | "error" | "warn" | "info" | "debug" | "trace".
+    /// The daemon reapplies it on SIGHUP.
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+}
+
+fn default_log_level() -> String {
+    "info".into()
 }
 
 impl Default for ObservabilityConfig {
@@ -344,6 +730,8 @@ impl Default for ObservabilityConfig {
             backend: "none".into(),
             otel_endpoint: None,
             otel_service_name: None,
+            otel_headers: HashMap::new(),
+            log_level: default_log_level(),
         }
     }
 }
@@ -366,6 +754,29 @@ pub struct AutonomyConfig {
     /// Block high-risk shell commands even if allowlisted.
     #[serde(default = "default_true")]
     pub block_high_risk_commands: bool,
+
+    /// Largest file `file_read` will load, in bytes.
+    #[serde(default = "default_max_file_read_bytes")]
+    pub max_file_read_bytes: u64,
+
+    /// Largest response `web_fetch` will save to disk with `save_to`, in bytes.
+    #[serde(default = "default_max_download_bytes")]
+    pub max_download_bytes: u64,
+
+    /// Offer the model only these tools in `tinyclaw agent`, the TUI and
+    /// channels (`--tools-only` overrides it for `agent`); empty means every
+    /// tool. The TUI starts with the rest disabled, so `/tools enable` can
+    /// bring one back. The gateway webhook runs no tools.
+    #[serde(default)]
+    pub tools_only: Vec<String>,
+}
+
+fn default_max_file_read_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_max_download_bytes() -> u64 {
+    100 * 1024 * 1024
 }
 
 impl Default for AutonomyConfig {
@@ -411,6 +822,9 @@ impl Default for AutonomyConfig {
             max_cost_per_day_cents: 500,
             require_approval_for_medium_risk: true,
             block_high_risk_commands: true,
+            max_file_read_bytes: default_max_file_read_bytes(),
+            max_download_bytes: default_max_download_bytes(),
+            tools_only: Vec::new(),
         }
     }
 }
@@ -515,6 +929,14 @@ pub struct ReliabilityConfig {
     /// Fallback provider chain (e.g. `["anthropic", "openai"]`).
     #[serde(default)]
     pub fallback_providers: Vec<String>,
+    /// Consecutive retryable failures (5xx, timeouts, 429) after which a
+    /// provider is skipped for `circuit_breaker_cooldown_secs` (default: 5,
+    /// 0 disables). Rejected requests and keys do not count.
+    #[serde(default = "default_circuit_breaker_threshold")]
+    pub circuit_breaker_threshold: u32,
+    /// How long an opened circuit skips its provider before probing it again.
+    #[serde(default = "default_circuit_breaker_cooldown_secs")]
+    pub circuit_breaker_cooldown_secs: u64,
     /// Initial backoff for channel/daemon restarts.
     #[serde(default = "default_channel_backoff_secs")]
     pub channel_initial_backoff_secs: u64,
@@ -527,6 +949,27 @@ pub struct ReliabilityConfig {
     /// Max retries for cron job execution attempts.
     #[serde(default = "default_scheduler_retries")]
     pub scheduler_retries: u32,
+    /// Send each request to both `race_providers` and use whichever answers
+    /// first (default: false). Doubles request cost; the model name must be
+    /// valid for both providers.
+    #[serde(default)]
+    pub race: bool,
+    /// The two providers raced when `race = true` (e.g. `["openrouter", "openai"]`).
+    #[serde(default)]
+    pub race_providers: Vec<String>,
+    /// Answer repeated identical requests from an on-disk cache under
+    /// `workspace/cache/responses` (default: false). Meant for development.
+    #[serde(default)]
+    pub cache: bool,
+    /// How long cached responses stay valid, in seconds (default: 24h).
+    #[serde(default = "default_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+    /// Provider calls per minute shared by all daemon components, so the
+    /// gateway, channels, heartbeat and cron jobs queue for one budget
+    /// instead of tripping the provider's rate limit together (default: 0,
+    /// no limit). A reload doesn't change it; restart the daemon instead.
+    #[serde(default)]
+    pub requests_per_minute: u32,
 }
 
 fn default_provider_retries() -> u32 {
@@ -537,6 +980,14 @@ fn default_provider_backoff_ms() -> u64 {
     500
 }
 
+fn default_circuit_breaker_threshold() -> u32 {
+    5
+}
+
+fn default_circuit_breaker_cooldown_secs() -> u64 {
+    30
+}
+
 fn default_channel_backoff_secs() -> u64 {
     2
 }
@@ -553,16 +1004,27 @@ fn default_scheduler_retries() -> u32 {
     2
 }
 
+fn default_cache_ttl_secs() -> u64 {
+    24 * 60 * 60
+}
+
 impl Default for ReliabilityConfig {
     fn default() -> Self {
         Self {
             provider_retries: default_provider_retries(),
             provider_backoff_ms: default_provider_backoff_ms(),
             fallback_providers: Vec::new(),
+            circuit_breaker_threshold: default_circuit_breaker_threshold(),
+            circuit_breaker_cooldown_secs: default_circuit_breaker_cooldown_secs(),
             channel_initial_backoff_secs: default_channel_backoff_secs(),
             channel_max_backoff_secs: default_channel_backoff_max_secs(),
             scheduler_poll_secs: default_scheduler_poll_secs(),
             scheduler_retries: default_scheduler_retries(),
+            race: false,
+            race_providers: Vec::new(),
+            cache: false,
+            cache_ttl_secs: default_cache_ttl_secs(),
+            requests_per_minute: 0,
         }
     }
 }
@@ -581,6 +1043,7 @@ impl Default for ReliabilityConfig {
 /// hint = "fast"
 /// provider = "groq"
 /// model = "llama-3.3-70b-versatile"
+/// temperature = 0.2
 /// ```
 ///
 /// Usage: pass `hint:reasoning` as the model parameter to route the request.
@@ -595,6 +1058,10 @@ pub struct ModelRouteConfig {
     /// Optional API key override for this route's provider
     #[serde(default)]
     pub api_key: Option<String>,
+    /// Default temperature when this route's hint is the model; an explicit
+    /// `--temperature` still wins
+    #[serde(default)]
+    pub temperature: Option<f64>,
 }
 
 // ── Heartbeat ────────────────────────────────────────────────────
@@ -694,6 +1161,14 @@ pub struct ChannelsConfig {
     pub whatsapp: Option<WhatsAppConfig>,
     pub email: Option<EmailConfig>,
     pub irc: Option<IrcConfig>,
+    /// Inbound messages handled at once across all channels; the rest wait
+    /// in order. Each sender keeps their own conversation (default: 4)
+    #[serde(default = "default_max_concurrent_messages")]
+    pub max_concurrent_messages: usize,
+}
+
+fn default_max_concurrent_messages() -> usize {
+    4
 }
 
 impl Default for ChannelsConfig {
@@ -709,6 +1184,7 @@ impl Default for ChannelsConfig {
             whatsapp: None,
             email: None,
             irc: None,
+            max_concurrent_messages: default_max_concurrent_messages(),
         }
     }
 }
@@ -850,11 +1326,49 @@ impl Default for EmailConfig {
 
 // ── Config impl ──────────────────────────────────────────────────
 
+/// `--workspace`, set once by `main` before any config is loaded.
+static DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Use `dir` instead of `~/.tinyclaw` for `config.toml` and the workspace
+/// (`tinyclaw --workspace <dir>`). Only the first call takes effect.
+pub fn set_tinyclaw_dir(dir: PathBuf) {
+    let _ = DIR_OVERRIDE.set(dir);
+}
+
+/// The directory holding `config.toml` and `workspace/`: `--workspace`,
+/// then `TINYCLAW_WORKSPACE`, then `~/.tinyclaw`.
+pub fn tinyclaw_dir() -> Result<PathBuf> {
+    let chosen = DIR_OVERRIDE.get().cloned().or_else(|| {
+        std::env::var_os("TINYCLAW_WORKSPACE")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+    });
+    match chosen {
+        Some(dir) => std::path::absolute(&dir)
+            .with_context(|| format!("Invalid workspace path: {}", dir.display())),
+        None => UserDirs::new()
+            .map(|u| u.home_dir().join(".tinyclaw"))
+            .context("Could not find home directory"),
+    }
+}
+
+/// The value of environment variable `name`, or else of the first of its
+/// deprecated `legacy` names that is set, with a warning to rename it.
+/// Empty values count as unset.
+pub fn env_var_or_legacy(name: &str, legacy: &[&str]) -> Option<String> {
+    let read = |var: &str| std::env::var(var).ok().filter(|v| !v.trim().is_empty());
+    read(name).or_else(|| {
+        legacy.iter().find_map(|old| {
+            let value = read(old)?;
+            tracing::warn!("{old} is deprecated and will stop working; set {name} instead");
+            Some(value)
+        })
+    })
+}
+
 impl Default for Config {
     fn default() -> Self {
-        let home =
-            UserDirs::new().map_or_else(|| PathBuf::from("."), |u| u.home_dir().to_path_buf());
-        let tinyclaw_dir = home.join(".tinyclaw");
+        let tinyclaw_dir = tinyclaw_dir().unwrap_or_else(|_| PathBuf::from(".").join(".tinyclaw"));
 
         Self {
             workspace_dir: tinyclaw_dir.join("workspace"),
@@ -862,7 +1376,7 @@ impl Default for Config {
             api_key: None,
             default_provider: Some("openrouter".to_string()),
             default_model: Some("anthropic/claude-sonnet-4-20250514".to_string()),
-            default_temperature: 0.7,
+            default_temperature: default_temperature(),
             observability: ObservabilityConfig::default(),
             autonomy: AutonomyConfig::default(),
             runtime: RuntimeConfig::default(),
@@ -877,93 +1391,170 @@ impl Default for Config {
             secrets: SecretsConfig::default(),
             browser: BrowserConfig::default(),
             identity: IdentityConfig::default(),
+            tui: TuiConfig::default(),
+            network: NetworkConfig::default(),
+            security: SecurityConfig::default(),
+            providers: ProvidersConfig::default(),
+            session: SessionConfig::default(),
+            check: CheckConfig::default(),
         }
     }
 }
 
+/// Overrides merged on top of `config.toml`, next to it; keeps secrets and
+/// machine-specific settings out of a committed base file.
+pub const LOCAL_CONFIG_FILE: &str = "config.local.toml";
+
+/// Merge `layer` into `base`: tables merge key by key, any other value
+/// (arrays included) in `layer` replaces the one in `base`.
+fn merge_toml(base: &mut toml::Table, layer: toml::Table) {
+    for (key, value) in layer {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(table)) => {
+                merge_toml(existing, table);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn read_toml_table(path: &Path) -> Result<toml::Table> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file {}", path.display()))?;
+    toml::from_str(&contents)
+        .with_context(|| format!("Failed to parse config file {}", path.display()))
+}
+
 impl Config {
+    /// Load the layered config from [`tinyclaw_dir`]; see
+    /// [`Config::load_or_init_in`].
     pub fn load_or_init() -> Result<Self> {
-        let home = UserDirs::new()
-            .map(|u| u.home_dir().to_path_buf())
-            .context("Could not find home directory")?;
-        let tinyclaw_dir = home.join(".tinyclaw");
+        Self::load_or_init_in(&tinyclaw_dir()?)
+    }
+
+    /// Load `dir/config.toml` (writing defaults if it is missing), with the
+    /// workspace at `dir/workspace`.
+    ///
+    /// Later layers win: built-in defaults, `config.toml`, then
+    /// `config.local.toml` (merged table by table), then environment
+    /// variables (see [`Config::apply_env_overrides`]). The merged result is
+    /// validated.
+    pub fn load_or_init_in(tinyclaw_dir: &Path) -> Result<Self> {
+        let mut config = Self::load_in(tinyclaw_dir, true)?;
+        config.apply_env_overrides();
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// `config.toml` alone, without `config.local.toml` or environment
+    /// overrides, for commands that save the config back.
+    pub fn load_base_or_init() -> Result<Self> {
+        let config = Self::load_in(&tinyclaw_dir()?, false)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn load_in(tinyclaw_dir: &Path, with_local: bool) -> Result<Self> {
         let config_path = tinyclaw_dir.join("config.toml");
 
-        if !tinyclaw_dir.exists() {
-            fs::create_dir_all(&tinyclaw_dir).context("Failed to create .tinyclaw directory")?;
+        // An existing directory given with --workspace may have no workspace yet
+        if !tinyclaw_dir.join("workspace").exists() {
+            fs::create_dir_all(tinyclaw_dir).context("Failed to create .tinyclaw directory")?;
             fs::create_dir_all(tinyclaw_dir.join("workspace"))
                 .context("Failed to create workspace directory")?;
         }
 
-        if config_path.exists() {
-            let contents =
-                fs::read_to_string(&config_path).context("Failed to read config file")?;
-            let mut config: Config =
-                toml::from_str(&contents).context("Failed to parse config file")?;
-            // Set computed paths that are skipped during serialization
-            config.config_path = config_path.clone();
-            config.workspace_dir = tinyclaw_dir.join("workspace");
-            Ok(config)
-        } else {
-            let mut config = Config::default();
-            config.config_path = config_path.clone();
-            config.workspace_dir = tinyclaw_dir.join("workspace");
-            config.save()?;
-            Ok(config)
+        if !config_path.exists() {
+            let defaults = Config {
+                config_path: config_path.clone(),
+                ..Config::default()
+            };
+            defaults.save()?;
+        }
+
+        let mut table = read_toml_table(&config_path)?;
+        let local_path = tinyclaw_dir.join(LOCAL_CONFIG_FILE);
+        if with_local && local_path.exists() {
+            merge_toml(&mut table, read_toml_table(&local_path)?);
+        }
+        let mut config: Config = toml::Value::Table(table)
+            .try_into()
+            .context("Failed to parse config file")?;
+        // Set computed paths that are skipped during serialization
+        config.config_path = config_path;
+        config.workspace_dir = tinyclaw_dir.join("workspace");
+        Ok(config)
+    }
+
+    /// Model to use with `provider` when no `--model` is given:
+    /// `[providers.<name>] default_model`, then `default_model` (only for the
+    /// configured `default_provider`, or a `hint:` route), then the
+    /// provider's built-in default.
+    pub fn default_model_for(&self, provider: &str) -> String {
+        if let Some(model) = self.providers.default_model_for(provider) {
+            return model.to_string();
+        }
+        let is_default_provider =
+            self.default_provider.as_deref().unwrap_or("openrouter") == provider;
+        self.default_model
+            .as_deref()
+            .filter(|model| is_default_provider || model.starts_with("hint:"))
+            .unwrap_or_else(|| crate::providers::default_model(provider))
+            .to_string()
+    }
+
+    /// Check settings that parse but cannot be used, such as invalid regexes.
+    pub fn validate(&self) -> Result<()> {
+        crate::security::OutputTransforms::from_config(&self.security)?;
+        if let Some(path) = &self.network.ca_cert_path {
+            crate::providers::http::load_ca_certs(path)?;
         }
+        Ok(())
     }
 
-    /// Apply environment variable overrides to config
+    /// Apply environment variable overrides to config. Empty or unparseable
+    /// values are ignored. The deprecated names in brackets are still read,
+    /// with a warning, when the `TINYCLAW_` one isn't set:
+    ///
+    /// - `TINYCLAW_API_KEY` (`ZEROCLAW_API_KEY`, `API_KEY`): `api_key`
+    /// - `TINYCLAW_PROVIDER` (`ZEROCLAW_PROVIDER`, `PROVIDER`): `default_provider`
+    /// - `TINYCLAW_MODEL` (`ZEROCLAW_MODEL`): `default_model`
+    /// - `TINYCLAW_TEMPERATURE` (`ZEROCLAW_TEMPERATURE`): `default_temperature` (0.0 to 2.0)
+    /// - `TINYCLAW_GATEWAY_PORT` (`ZEROCLAW_GATEWAY_PORT`, `PORT`): `gateway.port`
+    /// - `TINYCLAW_GATEWAY_HOST` (`ZEROCLAW_GATEWAY_HOST`, `HOST`): `gateway.host`
     pub fn apply_env_overrides(&mut self) {
-        // API Key: ZEROCLAW_API_KEY or API_KEY
-        if let Ok(key) = std::env::var("ZEROCLAW_API_KEY").or_else(|_| std::env::var("API_KEY")) {
-            if !key.is_empty() {
-                self.api_key = Some(key);
-            }
+        if let Some(key) = env_var_or_legacy("TINYCLAW_API_KEY", &["ZEROCLAW_API_KEY", "API_KEY"]) {
+            self.api_key = Some(key);
         }
 
-        // Provider: ZEROCLAW_PROVIDER or PROVIDER
-        if let Ok(provider) =
-            std::env::var("ZEROCLAW_PROVIDER").or_else(|_| std::env::var("PROVIDER"))
+        if let Some(provider) =
+            env_var_or_legacy("TINYCLAW_PROVIDER", &["ZEROCLAW_PROVIDER", "PROVIDER"])
         {
-            if !provider.is_empty() {
-                self.default_provider = Some(provider);
-            }
+            self.default_provider = Some(provider);
         }
 
-        // Model: ZEROCLAW_MODEL
-        if let Ok(model) = std::env::var("ZEROCLAW_MODEL") {
-            if !model.is_empty() {
-                self.default_model = Some(model);
-            }
+        if let Some(model) = env_var_or_legacy("TINYCLAW_MODEL", &["ZEROCLAW_MODEL"]) {
+            self.default_model = Some(model);
         }
 
-        // Workspace directory: ZEROCLAW_WORKSPACE
-        if let Ok(workspace) = std::env::var("ZEROCLAW_WORKSPACE") {
-            if !workspace.is_empty() {
-                self.workspace_dir = PathBuf::from(workspace);
-            }
-        }
-
-        // Gateway port: ZEROCLAW_GATEWAY_PORT or PORT
-        if let Ok(port_str) =
-            std::env::var("ZEROCLAW_GATEWAY_PORT").or_else(|_| std::env::var("PORT"))
+        if let Some(port_str) =
+            env_var_or_legacy("TINYCLAW_GATEWAY_PORT", &["ZEROCLAW_GATEWAY_PORT", "PORT"])
         {
             if let Ok(port) = port_str.parse::<u16>() {
                 self.gateway.port = port;
             }
         }
 
-        // Gateway host: ZEROCLAW_GATEWAY_HOST or HOST
-        if let Ok(host) = std::env::var("ZEROCLAW_GATEWAY_HOST").or_else(|_| std::env::var("HOST"))
+        if let Some(host) =
+            env_var_or_legacy("TINYCLAW_GATEWAY_HOST", &["ZEROCLAW_GATEWAY_HOST", "HOST"])
         {
-            if !host.is_empty() {
-                self.gateway.host = host;
-            }
+            self.gateway.host = host;
         }
 
-        // Temperature: ZEROCLAW_TEMPERATURE
-        if let Ok(temp_str) = std::env::var("ZEROCLAW_TEMPERATURE") {
+        if let Some(temp_str) = env_var_or_legacy("TINYCLAW_TEMPERATURE", &["ZEROCLAW_TEMPERATURE"])
+        {
             if let Ok(temp) = temp_str.parse::<f64>() {
                 if (0.0..=2.0).contains(&temp) {
                     self.default_temperature = temp;
@@ -1058,6 +1649,15 @@ fn sync_directory(_path: &Path) -> Result<()> {
 mod tests {
     use super::*;
     use std::path::PathBuf;
+    use std::sync::{Mutex, PoisonError};
+
+    /// Held by tests that set environment variables or load configs, which
+    /// read them.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    fn env_lock() -> std::sync::MutexGuard<'static, ()> {
+        ENV_LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+    }
 
     // ── Defaults ─────────────────────────────────────────────
 
@@ -1076,6 +1676,7 @@ mod tests {
     fn observability_config_default() {
         let o = ObservabilityConfig::default();
         assert_eq!(o.backend, "none");
+        assert_eq!(o.log_level, "info");
     }
 
     #[test]
@@ -1090,6 +1691,9 @@ mod tests {
         assert_eq!(a.max_cost_per_day_cents, 500);
         assert!(a.require_approval_for_medium_risk);
         assert!(a.block_high_risk_commands);
+        assert_eq!(a.max_file_read_bytes, 10 * 1024 * 1024);
+        assert_eq!(a.max_download_bytes, 100 * 1024 * 1024);
+        assert!(a.tools_only.is_empty());
     }
 
     #[test]
@@ -1128,6 +1732,7 @@ mod tests {
         assert!(c.cli);
         assert!(c.telegram.is_none());
         assert!(c.discord.is_none());
+        assert_eq!(c.max_concurrent_messages, 4);
     }
 
     // ── Serde round-trip ─────────────────────────────────────
@@ -1154,6 +1759,9 @@ mod tests {
                 max_cost_per_day_cents: 1000,
                 require_approval_for_medium_risk: false,
                 block_high_risk_commands: true,
+                max_file_read_bytes: 1024,
+                max_download_bytes: 2048,
+                tools_only: vec!["file_read".into()],
             },
             runtime: RuntimeConfig {
                 kind: "docker".into(),
@@ -1179,6 +1787,7 @@ mod tests {
                 whatsapp: None,
                 email: None,
                 irc: None,
+                ..ChannelsConfig::default()
             },
             memory: MemoryConfig::default(),
             tunnel: TunnelConfig::default(),
@@ -1187,6 +1796,12 @@ mod tests {
             secrets: SecretsConfig::default(),
             browser: BrowserConfig::default(),
             identity: IdentityConfig::default(),
+            tui: TuiConfig::default(),
+            network: NetworkConfig::default(),
+            security: SecurityConfig::default(),
+            providers: ProvidersConfig::default(),
+            session: SessionConfig::default(),
+            check: CheckConfig::default(),
         };
 
         let toml_str = toml::to_string_pretty(&config).unwrap();
@@ -1199,6 +1814,7 @@ mod tests {
         assert_eq!(parsed.observability.backend, "log");
         assert_eq!(parsed.autonomy.level, AutonomyLevel::Full);
         assert!(!parsed.autonomy.workspace_only);
+        assert_eq!(parsed.autonomy.tools_only, ["file_read"]);
         assert_eq!(parsed.runtime.kind, "docker");
         assert!(parsed.heartbeat.enabled);
         assert_eq!(parsed.heartbeat.interval_minutes, 15);
@@ -1220,6 +1836,7 @@ default_temperature = 0.7
         assert!(parsed.api_key.is_none());
         assert!(parsed.default_provider.is_none());
         assert_eq!(parsed.observability.backend, "none");
+        assert_eq!(parsed.observability.log_level, "info");
         assert_eq!(parsed.autonomy.level, AutonomyLevel::Supervised);
         assert_eq!(parsed.runtime.kind, "native");
         assert!(!parsed.heartbeat.enabled);
@@ -1228,6 +1845,447 @@ default_temperature = 0.7
         assert_eq!(parsed.memory.archive_after_days, 7);
         assert_eq!(parsed.memory.purge_after_days, 30);
         assert_eq!(parsed.memory.conversation_retention_days, 30);
+        assert!(parsed.tui.accent_color.is_none());
+        assert!(parsed.tui.theme.is_none());
+        assert!(parsed.tui.tool_preview_len.is_none());
+        assert!(parsed.identity.name.is_none());
+    }
+
+    #[test]
+    fn tui_and_identity_name_parse_from_toml() {
+        let toml_str = r##"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[identity]
+name = "Nova"
+
+[tui]
+theme = "light"
+accent_color = "magenta"
+user_color = "#00ff88"
+tool_color = "blue"
+history_includes_commands = true
+eager_tool_calls = true
+cache_tool_results = true
+repeat_limit = 5
+smooth_output = true
+tool_preview_len = 200
+"##;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(parsed.identity.name.as_deref(), Some("Nova"));
+        assert_eq!(parsed.tui.theme.as_deref(), Some("light"));
+        assert_eq!(parsed.tui.accent_color.as_deref(), Some("magenta"));
+        assert_eq!(parsed.tui.user_color.as_deref(), Some("#00ff88"));
+        assert_eq!(parsed.tui.tool_color.as_deref(), Some("blue"));
+        assert!(parsed.tui.assistant_color.is_none());
+        assert!(parsed.tui.error_color.is_none());
+        assert!(parsed.tui.history_includes_commands);
+        assert!(parsed.tui.eager_tool_calls);
+        assert!(parsed.tui.cache_tool_results);
+        assert_eq!(parsed.tui.repeat_limit, 5);
+        assert!(parsed.tui.smooth_output);
+        assert_eq!(parsed.tui.smooth_output_cps, 400);
+        assert_eq!(parsed.tui.tool_preview_len, Some(200));
+    }
+
+    #[test]
+    fn load_or_init_in_keeps_config_and_workspace_in_the_given_dir() {
+        let _env = env_lock();
+        let tmp = std::env::temp_dir().join(format!("tinyclaw_test_dir_{}", uuid::Uuid::new_v4()));
+        let dir = tmp.join("project");
+
+        let config = Config::load_or_init_in(&dir).unwrap();
+        assert_eq!(config.config_path, dir.join("config.toml"));
+        assert_eq!(config.workspace_dir, dir.join("workspace"));
+        assert!(dir.join("config.toml").exists());
+        assert!(dir.join("workspace").is_dir());
+
+        // An existing directory without a workspace gets one
+        let existing = tmp.join("existing");
+        fs::create_dir(&existing).unwrap();
+        let config = Config::load_or_init_in(&existing).unwrap();
+        assert!(config.workspace_dir.is_dir());
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn local_config_overrides_base_and_env_overrides_both() {
+        let _env = env_lock();
+        let dir =
+            std::env::temp_dir().join(format!("tinyclaw_test_layers_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("config.toml"),
+            r#"
+default_provider = "openai"
+default_temperature = 0.2
+
+[memory]
+backend = "sqlite"
+auto_save = true
+
+[providers.openai]
+default_model = "gpt-4o-mini"
+"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join(LOCAL_CONFIG_FILE),
+            r#"
+default_temperature = 0.9
+
+[memory]
+auto_save = false
+
+[providers.openai]
+api_key = "sk-local"
+"#,
+        )
+        .unwrap();
+
+        let config = Config::load_or_init_in(&dir).unwrap();
+        // Local wins where both set a value
+        assert!((config.default_temperature - 0.9).abs() < f64::EPSILON);
+        assert!(!config.memory.auto_save);
+        // Base fills what local leaves out, inside merged tables too
+        assert_eq!(config.default_provider.as_deref(), Some("openai"));
+        assert_eq!(config.memory.backend, "sqlite");
+        assert_eq!(
+            config.providers.default_model_for("openai"),
+            Some("gpt-4o-mini")
+        );
+        assert_eq!(config.providers.api_key_for("openai"), Some("sk-local"));
+
+        // The base alone, for saving back, leaves the local secrets out
+        let base = Config::load_in(&dir, false).unwrap();
+        assert!(base.providers.api_key_for("openai").is_none());
+        assert!(base.memory.auto_save);
+
+        // Environment variables win over both files
+        std::env::set_var("TINYCLAW_TEMPERATURE", "1.5");
+        let config = Config::load_or_init_in(&dir).unwrap();
+        std::env::remove_var("TINYCLAW_TEMPERATURE");
+        assert!((config.default_temperature - 1.5).abs() < f64::EPSILON);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn merged_config_is_validated() {
+        let _env = env_lock();
+        let dir =
+            std::env::temp_dir().join(format!("tinyclaw_test_layers_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("config.toml"), "default_provider = \"openai\"\n").unwrap();
+        fs::write(
+            dir.join(LOCAL_CONFIG_FILE),
+            "[[security.output_transforms]]\npattern = \"(\"\nreplacement = \"\"\n",
+        )
+        .unwrap();
+
+        assert!(Config::load_or_init_in(&dir).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn memory_ttl_days_parse_per_category() {
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[memory]
+backend = "sqlite"
+auto_save = true
+ttl_days = { conversation = 7, daily = 30 }
+"#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(parsed.memory.ttl_days["conversation"], 7);
+        assert_eq!(parsed.memory.ttl_days["daily"], 30);
+        assert!(!parsed.memory.ttl_days.contains_key("core"));
+        assert!(Config::default().memory.ttl_days.is_empty());
+    }
+
+    #[test]
+    fn default_model_follows_the_selected_provider() {
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_provider = "openrouter"
+default_model = "anthropic/claude-sonnet-4-20250514"
+default_temperature = 0.7
+
+[providers.ollama]
+default_model = "qwen2.5-coder"
+"#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            parsed.default_model_for("openrouter"),
+            "anthropic/claude-sonnet-4-20250514"
+        );
+        assert_eq!(parsed.default_model_for("ollama"), "qwen2.5-coder");
+        assert_eq!(parsed.default_model_for("groq"), "llama-3.3-70b-versatile");
+
+        let hinted = Config {
+            default_model: Some("hint:code".into()),
+            ..parsed
+        };
+        assert_eq!(hinted.default_model_for("groq"), "hint:code");
+        assert_eq!(hinted.default_model_for("ollama"), "qwen2.5-coder");
+    }
+
+    #[test]
+    fn check_config_parses_language_and_command() {
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[check]
+language = "typescript"
+command = ["npx", "tsc", "--noEmit"]
+"#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(parsed.check.language.as_deref(), Some("typescript"));
+        assert_eq!(parsed.check.command, ["npx", "tsc", "--noEmit"]);
+        assert!(Config::default().check.language.is_none());
+        assert!(Config::default().check.command.is_empty());
+    }
+
+    #[test]
+    fn network_config_parses_proxy_settings() {
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[network]
+proxy = "http://proxy.corp:3128"
+no_proxy = ["localhost", ".internal.corp"]
+"#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            parsed.network.proxy.as_deref(),
+            Some("http://proxy.corp:3128")
+        );
+        assert_eq!(parsed.network.no_proxy, vec!["localhost", ".internal.corp"]);
+        assert!(Config::default().network.proxy.is_none());
+        assert_eq!(parsed.network.pool_max_idle_per_host, None);
+        assert_eq!(parsed.network.pool_idle_timeout_secs, 90);
+        assert_eq!(parsed.network.tcp_keepalive_secs, 15);
+    }
+
+    #[test]
+    fn network_config_parses_pool_settings() {
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[network]
+pool_max_idle_per_host = 4
+pool_idle_timeout_secs = 0
+tcp_keepalive_secs = 30
+"#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(parsed.network.pool_max_idle_per_host, Some(4));
+        assert_eq!(parsed.network.pool_idle_timeout_secs, 0);
+        assert_eq!(parsed.network.tcp_keepalive_secs, 30);
+    }
+
+    #[test]
+    fn network_config_parses_tls_settings_and_validates_ca() {
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[network]
+ca_cert_path = "/nonexistent/corp-ca.pem"
+danger_accept_invalid_certs = true
+"#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            parsed.network.ca_cert_path.as_deref(),
+            Some(std::path::Path::new("/nonexistent/corp-ca.pem"))
+        );
+        assert!(parsed.network.danger_accept_invalid_certs);
+        assert!(!Config::default().network.danger_accept_invalid_certs);
+
+        let err = parsed.validate().unwrap_err().to_string();
+        assert!(err.contains("corp-ca.pem"), "{err}");
+    }
+
+    #[test]
+    fn security_config_parses_redaction_settings() {
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[security]
+redact_secrets = true
+redact_patterns = ["corp-[0-9]{6}"]
+"#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert!(parsed.security.redact_secrets);
+        assert_eq!(parsed.security.redact_patterns, vec!["corp-[0-9]{6}"]);
+        assert!(!Config::default().security.redact_secrets);
+    }
+
+    #[test]
+    fn security_output_transforms_parse_in_order_and_validate() {
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[[security.output_transforms]]
+pattern = "ACCT-[0-9]+"
+replacement = "ACCT-****"
+
+[[security.output_transforms]]
+pattern = '\z'
+replacement = "\n-- sent by tinyclaw"
+"#;
+        let mut parsed: Config = toml::from_str(toml_str).unwrap();
+        let patterns: Vec<&str> = parsed
+            .security
+            .output_transforms
+            .iter()
+            .map(|t| t.pattern.as_str())
+            .collect();
+        assert_eq!(patterns, vec!["ACCT-[0-9]+", r"\z"]);
+        assert!(parsed.validate().is_ok());
+
+        parsed.security.output_transforms[0].pattern = "ACCT-[0-9".into();
+        assert!(parsed.validate().is_err());
+    }
+
+    #[test]
+    fn providers_seed_parses_from_toml() {
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[providers]
+seed = 42
+stop_sequences = ["</answer>"]
+max_response_chars = 20000
+"#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(parsed.providers.seed, Some(42));
+        assert_eq!(parsed.providers.stop_sequences, vec!["</answer>"]);
+        assert_eq!(parsed.providers.max_response_chars, Some(20000));
+        assert!(Config::default().providers.seed.is_none());
+        assert!(Config::default().providers.max_response_chars.is_none());
+    }
+
+    #[test]
+    fn provider_api_keys_parse_per_provider() {
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+api_key = "sk-shared"
+
+[providers]
+seed = 7
+reasoning_effort = "high"
+
+[providers.openai]
+api_key = "sk-openai"
+
+[providers.anthropic]
+api_key = "sk-ant"
+
+[providers.ollama]
+fold_system_into_user = true
+
+[providers.openrouter]
+referer = "https://myapp.example"
+app_title = "My App"
+"#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(parsed.providers.seed, Some(7));
+        assert_eq!(
+            parsed.providers.reasoning_effort,
+            Some(crate::providers::ReasoningEffort::High)
+        );
+        let openrouter = &parsed.providers.entries["openrouter"];
+        assert_eq!(openrouter.referer.as_deref(), Some("https://myapp.example"));
+        assert_eq!(openrouter.app_title.as_deref(), Some("My App"));
+        assert!(!openrouter.fold_system_into_user);
+        assert!(parsed.providers.entries["ollama"].fold_system_into_user);
+        assert_eq!(parsed.providers.api_key_for("openai"), Some("sk-openai"));
+        assert_eq!(parsed.providers.api_key_for("anthropic"), Some("sk-ant"));
+        assert_eq!(parsed.providers.api_key_for("ollama"), None);
+        assert_eq!(parsed.providers.api_key_for("groq"), None);
+
+        let saved = toml::to_string(&parsed).unwrap();
+        let reparsed: Config = toml::from_str(&saved).unwrap();
+        assert_eq!(reparsed.providers.api_key_for("openai"), Some("sk-openai"));
+        assert_eq!(reparsed.providers.seed, Some(7));
+    }
+
+    #[test]
+    fn unknown_scalar_provider_settings_are_ignored() {
+        let parsed: ProvidersConfig = toml::from_str(
+            r#"
+seed = 7
+retired_option = true
+timeout = 30
+
+[openai]
+api_key = "sk-openai"
+"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.seed, Some(7));
+        assert_eq!(parsed.entries.len(), 1);
+        assert_eq!(parsed.api_key_for("openai"), Some("sk-openai"));
+
+        // A provider table with a wrongly typed field is still an error
+        assert!(toml::from_str::<ProvidersConfig>("[openai]\napi_key = 1\n").is_err());
+    }
+
+    #[test]
+    fn session_transcript_is_opt_in() {
+        assert!(!Config::default().session.transcript);
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[session]
+transcript = true
+"#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert!(parsed.session.transcript);
+        assert_eq!(parsed.session.save_every_turns, 1);
+        assert_eq!(parsed.session.save_interval_secs, 0);
+    }
+
+    #[test]
+    fn session_saves_can_be_batched() {
+        assert_eq!(Config::default().session.save_every_turns, 1);
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[session]
+save_every_turns = 5
+save_interval_secs = 30
+"#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(parsed.session.save_every_turns, 5);
+        assert_eq!(parsed.session.save_interval_secs, 30);
     }
 
     #[test]
@@ -1258,6 +2316,12 @@ default_temperature = 0.7
             secrets: SecretsConfig::default(),
             browser: BrowserConfig::default(),
             identity: IdentityConfig::default(),
+            tui: TuiConfig::default(),
+            network: NetworkConfig::default(),
+            security: SecurityConfig::default(),
+            providers: ProvidersConfig::default(),
+            session: SessionConfig::default(),
+            check: CheckConfig::default(),
         };
 
         config.save().unwrap();
@@ -1425,6 +2489,7 @@ default_temperature = 0.7
             whatsapp: None,
             email: None,
             irc: None,
+            ..ChannelsConfig::default()
         };
         let toml_str = toml::to_string_pretty(&c).unwrap();
         let parsed: ChannelsConfig = toml::from_str(&toml_str).unwrap();
@@ -1583,6 +2648,7 @@ channel_id = "C123"
             }),
             email: None,
             irc: None,
+            ..ChannelsConfig::default()
         };
         let toml_str = toml::to_string_pretty(&c).unwrap();
         let parsed: ChannelsConfig = toml::from_str(&toml_str).unwrap();
@@ -1834,44 +2900,60 @@ default_temperature = 0.7
 
     #[test]
     fn env_override_api_key() {
+        let _env = env_lock();
         let mut config = Config::default();
         assert!(config.api_key.is_none());
 
-        std::env::set_var("ZEROCLAW_API_KEY", "sk-test-env-key");
+        std::env::set_var("TINYCLAW_API_KEY", "sk-test-env-key");
         config.apply_env_overrides();
         assert_eq!(config.api_key.as_deref(), Some("sk-test-env-key"));
 
-        std::env::remove_var("ZEROCLAW_API_KEY");
+        std::env::remove_var("TINYCLAW_API_KEY");
     }
 
     #[test]
-    fn env_override_api_key_fallback() {
+    fn env_override_provider() {
+        let _env = env_lock();
         let mut config = Config::default();
 
-        std::env::remove_var("ZEROCLAW_API_KEY");
-        std::env::set_var("API_KEY", "sk-fallback-key");
+        std::env::set_var("TINYCLAW_PROVIDER", "anthropic");
         config.apply_env_overrides();
-        assert_eq!(config.api_key.as_deref(), Some("sk-fallback-key"));
+        assert_eq!(config.default_provider.as_deref(), Some("anthropic"));
 
-        std::env::remove_var("API_KEY");
+        std::env::remove_var("TINYCLAW_PROVIDER");
     }
 
     #[test]
-    fn env_override_provider() {
+    fn env_override_model() {
+        let _env = env_lock();
         let mut config = Config::default();
 
-        std::env::set_var("ZEROCLAW_PROVIDER", "anthropic");
+        std::env::set_var("TINYCLAW_MODEL", "gpt-4o");
         config.apply_env_overrides();
-        assert_eq!(config.default_provider.as_deref(), Some("anthropic"));
+        assert_eq!(config.default_model.as_deref(), Some("gpt-4o"));
 
-        std::env::remove_var("ZEROCLAW_PROVIDER");
+        std::env::remove_var("TINYCLAW_MODEL");
+    }
+
+    #[test]
+    fn env_override_api_key_fallback() {
+        let _env = env_lock();
+        let mut config = Config::default();
+
+        std::env::remove_var("TINYCLAW_API_KEY");
+        std::env::set_var("API_KEY", "sk-fallback-key");
+        config.apply_env_overrides();
+        assert_eq!(config.api_key.as_deref(), Some("sk-fallback-key"));
+
+        std::env::remove_var("API_KEY");
     }
 
     #[test]
     fn env_override_provider_fallback() {
+        let _env = env_lock();
         let mut config = Config::default();
 
-        std::env::remove_var("ZEROCLAW_PROVIDER");
+        std::env::remove_var("TINYCLAW_PROVIDER");
         std::env::set_var("PROVIDER", "openai");
         config.apply_env_overrides();
         assert_eq!(config.default_provider.as_deref(), Some("openai"));
@@ -1880,127 +2962,157 @@ default_temperature = 0.7
     }
 
     #[test]
-    fn env_override_model() {
+    fn env_override_port_fallback() {
+        let _env = env_lock();
         let mut config = Config::default();
 
-        std::env::set_var("ZEROCLAW_MODEL", "gpt-4o");
+        std::env::remove_var("TINYCLAW_GATEWAY_PORT");
+        std::env::set_var("PORT", "9000");
         config.apply_env_overrides();
-        assert_eq!(config.default_model.as_deref(), Some("gpt-4o"));
+        assert_eq!(config.gateway.port, 9000);
 
-        std::env::remove_var("ZEROCLAW_MODEL");
+        std::env::remove_var("PORT");
     }
 
     #[test]
-    fn env_override_workspace() {
+    fn env_override_host_fallback() {
+        let _env = env_lock();
         let mut config = Config::default();
 
-        std::env::set_var("ZEROCLAW_WORKSPACE", "/custom/workspace");
+        std::env::remove_var("TINYCLAW_GATEWAY_HOST");
+        std::env::set_var("HOST", "0.0.0.0");
         config.apply_env_overrides();
-        assert_eq!(config.workspace_dir, PathBuf::from("/custom/workspace"));
+        assert_eq!(config.gateway.host, "0.0.0.0");
 
-        std::env::remove_var("ZEROCLAW_WORKSPACE");
+        std::env::remove_var("HOST");
     }
 
     #[test]
-    fn env_override_empty_values_ignored() {
+    fn env_override_legacy_zeroclaw_names_still_work() {
+        let _env = env_lock();
         let mut config = Config::default();
-        let original_provider = config.default_provider.clone();
 
-        std::env::set_var("ZEROCLAW_PROVIDER", "");
+        std::env::set_var("ZEROCLAW_MODEL", "gpt-4o");
+        std::env::set_var("ZEROCLAW_TEMPERATURE", "0.3");
         config.apply_env_overrides();
-        assert_eq!(config.default_provider, original_provider);
+        std::env::remove_var("ZEROCLAW_MODEL");
+        std::env::remove_var("ZEROCLAW_TEMPERATURE");
 
-        std::env::remove_var("ZEROCLAW_PROVIDER");
+        assert_eq!(config.default_model.as_deref(), Some("gpt-4o"));
+        assert!((config.default_temperature - 0.3).abs() < f64::EPSILON);
     }
 
     #[test]
-    fn env_override_gateway_port() {
+    fn env_override_prefixed_name_wins_over_legacy() {
+        let _env = env_lock();
         let mut config = Config::default();
-        assert_eq!(config.gateway.port, 3000);
 
-        std::env::set_var("ZEROCLAW_GATEWAY_PORT", "8080");
+        std::env::set_var("TINYCLAW_API_KEY", "sk-new");
+        std::env::set_var("ZEROCLAW_API_KEY", "sk-legacy");
+        std::env::set_var("API_KEY", "sk-fallback");
         config.apply_env_overrides();
-        assert_eq!(config.gateway.port, 8080);
+        for name in ["TINYCLAW_API_KEY", "ZEROCLAW_API_KEY", "API_KEY"] {
+            std::env::remove_var(name);
+        }
 
-        std::env::remove_var("ZEROCLAW_GATEWAY_PORT");
+        assert_eq!(config.api_key.as_deref(), Some("sk-new"));
     }
 
     #[test]
-    fn env_override_port_fallback() {
+    fn env_override_ignores_legacy_workspace_variable() {
+        let _env = env_lock();
         let mut config = Config::default();
+        let original = config.workspace_dir.clone();
 
-        std::env::remove_var("ZEROCLAW_GATEWAY_PORT");
-        std::env::set_var("PORT", "9000");
+        std::env::set_var("ZEROCLAW_WORKSPACE", "/custom/workspace");
         config.apply_env_overrides();
-        assert_eq!(config.gateway.port, 9000);
+        assert_eq!(config.workspace_dir, original);
 
-        std::env::remove_var("PORT");
+        std::env::remove_var("ZEROCLAW_WORKSPACE");
     }
 
     #[test]
-    fn env_override_gateway_host() {
+    fn env_override_empty_values_ignored() {
+        let _env = env_lock();
         let mut config = Config::default();
-        assert_eq!(config.gateway.host, "127.0.0.1");
+        let original_provider = config.default_provider.clone();
 
-        std::env::set_var("ZEROCLAW_GATEWAY_HOST", "0.0.0.0");
+        std::env::set_var("TINYCLAW_PROVIDER", "");
         config.apply_env_overrides();
-        assert_eq!(config.gateway.host, "0.0.0.0");
+        assert_eq!(config.default_provider, original_provider);
 
-        std::env::remove_var("ZEROCLAW_GATEWAY_HOST");
+        std::env::remove_var("TINYCLAW_PROVIDER");
     }
 
     #[test]
-    fn env_override_host_fallback() {
+    fn env_override_gateway_port() {
+        let _env = env_lock();
         let mut config = Config::default();
+        assert_eq!(config.gateway.port, 3000);
 
-        std::env::remove_var("ZEROCLAW_GATEWAY_HOST");
-        std::env::set_var("HOST", "0.0.0.0");
+        std::env::set_var("TINYCLAW_GATEWAY_PORT", "8080");
+        config.apply_env_overrides();
+        assert_eq!(config.gateway.port, 8080);
+
+        std::env::remove_var("TINYCLAW_GATEWAY_PORT");
+    }
+
+    #[test]
+    fn env_override_gateway_host() {
+        let _env = env_lock();
+        let mut config = Config::default();
+        assert_eq!(config.gateway.host, "127.0.0.1");
+
+        std::env::set_var("TINYCLAW_GATEWAY_HOST", "0.0.0.0");
         config.apply_env_overrides();
         assert_eq!(config.gateway.host, "0.0.0.0");
 
-        std::env::remove_var("HOST");
+        std::env::remove_var("TINYCLAW_GATEWAY_HOST");
     }
 
     #[test]
     fn env_override_temperature() {
+        let _env = env_lock();
         let mut config = Config::default();
 
-        std::env::set_var("ZEROCLAW_TEMPERATURE", "0.5");
+        std::env::set_var("TINYCLAW_TEMPERATURE", "0.5");
         config.apply_env_overrides();
         assert!((config.default_temperature - 0.5).abs() < f64::EPSILON);
 
-        std::env::remove_var("ZEROCLAW_TEMPERATURE");
+        std::env::remove_var("TINYCLAW_TEMPERATURE");
     }
 
     #[test]
     fn env_override_temperature_out_of_range_ignored() {
+        let _env = env_lock();
         // Clean up any leftover env vars from other tests
-        std::env::remove_var("ZEROCLAW_TEMPERATURE");
+        std::env::remove_var("TINYCLAW_TEMPERATURE");
 
         let mut config = Config::default();
         let original_temp = config.default_temperature;
 
         // Temperature > 2.0 should be ignored
-        std::env::set_var("ZEROCLAW_TEMPERATURE", "3.0");
+        std::env::set_var("TINYCLAW_TEMPERATURE", "3.0");
         config.apply_env_overrides();
         assert!(
             (config.default_temperature - original_temp).abs() < f64::EPSILON,
             "Temperature 3.0 should be ignored (out of range)"
         );
 
-        std::env::remove_var("ZEROCLAW_TEMPERATURE");
+        std::env::remove_var("TINYCLAW_TEMPERATURE");
     }
 
     #[test]
     fn env_override_invalid_port_ignored() {
+        let _env = env_lock();
         let mut config = Config::default();
         let original_port = config.gateway.port;
 
-        std::env::set_var("PORT", "not_a_number");
+        std::env::set_var("TINYCLAW_GATEWAY_PORT", "not_a_number");
         config.apply_env_overrides();
         assert_eq!(config.gateway.port, original_port);
 
-        std::env::remove_var("PORT");
+        std::env::remove_var("TINYCLAW_GATEWAY_PORT");
     }
 
     #[test]
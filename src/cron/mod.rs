@@ -3,11 +3,22 @@ use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use cron::Schedule;
 use rusqlite::{params, Connection};
+use std::path::Path;
 use std::str::FromStr;
 use uuid::Uuid;
 
 pub mod scheduler;
 
+/// Expression of one-shot jobs; they are removed after their single run.
+pub const ONCE: &str = "@once";
+/// Command prefix of jobs that re-invoke the agent with a stored prompt
+/// instead of running a shell command.
+pub const AGENT_PREFIX: &str = "agent: ";
+/// Runs kept per job by [`record_run`]; older ones are pruned.
+const MAX_RUNS_PER_JOB: u32 = 50;
+/// Characters of output kept per logged run.
+const MAX_RUN_OUTPUT_CHARS: usize = 4000;
+
 #[derive(Debug, Clone)]
 pub struct CronJob {
     pub id: String,
@@ -18,6 +29,27 @@ pub struct CronJob {
     pub last_status: Option<String>,
 }
 
+/// One logged execution of a job, from `tinyclaw cron logs`.
+#[derive(Debug, Clone)]
+pub struct CronRun {
+    pub ran_at: DateTime<Utc>,
+    pub success: bool,
+    /// Exit status and output, truncated to a few thousand characters
+    pub output: String,
+}
+
+impl CronJob {
+    /// The stored prompt, for jobs created by [`add_agent_reminder`]. Only
+    /// those are one-shot, so a recurring `cron add` job whose command
+    /// happens to start with [`AGENT_PREFIX`] still runs as a shell command.
+    pub fn agent_prompt(&self) -> Option<&str> {
+        if self.expression != ONCE {
+            return None;
+        }
+        self.command.strip_prefix(AGENT_PREFIX)
+    }
+}
+
 #[allow(clippy::needless_pass_by_value)]
 pub fn handle_command(command: crate::CronCommands, config: &Config) -> Result<()> {
     match command {
@@ -60,6 +92,23 @@ pub fn handle_command(command: crate::CronCommands, config: &Config) -> Result<(
             Ok(())
         }
         crate::CronCommands::Remove { id } => remove_job(config, &id),
+        crate::CronCommands::Logs { id, limit } => {
+            let runs = job_runs(config, &id, limit)?;
+            if runs.is_empty() {
+                println!("No runs recorded for job {id}.");
+                return Ok(());
+            }
+
+            println!("📜 Last {} run(s) of job {id}:", runs.len());
+            for run in runs {
+                let status = if run.success { "ok" } else { "error" };
+                println!("- {} | {status}", run.ran_at.to_rfc3339());
+                for line in run.output.lines() {
+                    println!("    {line}");
+                }
+            }
+            Ok(())
+        }
     }
 }
 
@@ -94,6 +143,42 @@ pub fn add_job(config: &Config, expression: &str, command: &str) -> Result<CronJ
     })
 }
 
+/// Schedule a one-shot job that runs the agent on `prompt` at `run_at`.
+pub fn add_agent_reminder(
+    workspace_dir: &Path,
+    run_at: DateTime<Utc>,
+    prompt: &str,
+) -> Result<CronJob> {
+    let now = Utc::now();
+    if run_at <= now {
+        anyhow::bail!("Reminder time {} is in the past", run_at.to_rfc3339());
+    }
+    if prompt.trim().is_empty() {
+        anyhow::bail!("Reminder prompt is empty");
+    }
+    let id = Uuid::new_v4().to_string();
+    let command = format!("{AGENT_PREFIX}{}", prompt.trim());
+
+    with_db(workspace_dir, |conn| {
+        conn.execute(
+            "INSERT INTO cron_jobs (id, expression, command, created_at, next_run)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![id, ONCE, command, now.to_rfc3339(), run_at.to_rfc3339()],
+        )
+        .context("Failed to insert reminder")?;
+        Ok(())
+    })?;
+
+    Ok(CronJob {
+        id,
+        expression: ONCE.to_string(),
+        command,
+        next_run: run_at,
+        last_run: None,
+        last_status: None,
+    })
+}
+
 pub fn list_jobs(config: &Config) -> Result<Vec<CronJob>> {
     with_connection(config, |conn| {
         let mut stmt = conn.prepare(
@@ -192,6 +277,19 @@ pub fn reschedule_after_run(
     success: bool,
     output: &str,
 ) -> Result<()> {
+    if job.expression == ONCE {
+        tracing::info!(
+            "One-shot job {} finished ({}): {output}",
+            job.id,
+            if success { "ok" } else { "error" }
+        );
+        return with_connection(config, |conn| {
+            conn.execute("DELETE FROM cron_jobs WHERE id = ?1", params![job.id])
+                .context("Failed to remove one-shot cron job")?;
+            Ok(())
+        });
+    }
+
     let now = Utc::now();
     let next_run = next_run_for(&job.expression, now)?;
     let status = if success { "ok" } else { "error" };
@@ -214,6 +312,57 @@ pub fn reschedule_after_run(
     })
 }
 
+/// Log one execution of `job_id`, keeping its latest [`MAX_RUNS_PER_JOB`]
+/// runs. Runs outlive their job, so a finished one-shot job can still be
+/// inspected.
+pub fn record_run(config: &Config, job_id: &str, success: bool, output: &str) -> Result<()> {
+    let output = crate::util::truncate_with_ellipsis(output, MAX_RUN_OUTPUT_CHARS);
+    with_connection(config, |conn| {
+        conn.execute(
+            "INSERT INTO cron_runs (job_id, ran_at, success, output) VALUES (?1, ?2, ?3, ?4)",
+            params![job_id, Utc::now().to_rfc3339(), success, output],
+        )
+        .context("Failed to log cron run")?;
+        conn.execute(
+            "DELETE FROM cron_runs WHERE job_id = ?1 AND id NOT IN (
+                SELECT id FROM cron_runs WHERE job_id = ?1 ORDER BY id DESC LIMIT ?2
+             )",
+            params![job_id, MAX_RUNS_PER_JOB],
+        )
+        .context("Failed to prune cron run log")?;
+        Ok(())
+    })
+}
+
+/// The latest `limit` runs of `job_id`, newest first.
+pub fn job_runs(config: &Config, job_id: &str, limit: usize) -> Result<Vec<CronRun>> {
+    with_connection(config, |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT ran_at, success, output FROM cron_runs
+             WHERE job_id = ?1 ORDER BY id DESC LIMIT ?2",
+        )?;
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let rows = stmt.query_map(params![job_id, limit], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, bool>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+
+        let mut runs = Vec::new();
+        for row in rows {
+            let (ran_at, success, output) = row?;
+            runs.push(CronRun {
+                ran_at: parse_rfc3339(&ran_at)?,
+                success,
+                output,
+            });
+        }
+        Ok(runs)
+    })
+}
+
 fn next_run_for(expression: &str, from: DateTime<Utc>) -> Result<DateTime<Utc>> {
     let normalized = normalize_expression(expression)?;
     let schedule = Schedule::from_str(&normalized)
@@ -246,7 +395,11 @@ fn parse_rfc3339(raw: &str) -> Result<DateTime<Utc>> {
 }
 
 fn with_connection<T>(config: &Config, f: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
-    let db_path = config.workspace_dir.join("cron").join("jobs.db");
+    with_db(&config.workspace_dir, f)
+}
+
+fn with_db<T>(workspace_dir: &Path, f: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
+    let db_path = workspace_dir.join("cron").join("jobs.db");
     if let Some(parent) = db_path.parent() {
         std::fs::create_dir_all(parent)
             .with_context(|| format!("Failed to create cron directory: {}", parent.display()))?;
@@ -266,7 +419,15 @@ fn with_connection<T>(config: &Config, f: impl FnOnce(&Connection) -> Result<T>)
             last_status TEXT,
             last_output TEXT
         );
-        CREATE INDEX IF NOT EXISTS idx_cron_jobs_next_run ON cron_jobs(next_run);",
+        CREATE INDEX IF NOT EXISTS idx_cron_jobs_next_run ON cron_jobs(next_run);
+        CREATE TABLE IF NOT EXISTS cron_runs (
+            id      INTEGER PRIMARY KEY AUTOINCREMENT,
+            job_id  TEXT NOT NULL,
+            ran_at  TEXT NOT NULL,
+            success INTEGER NOT NULL,
+            output  TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_cron_runs_job ON cron_runs(job_id, id);",
     )
     .context("Failed to initialize cron schema")?;
 
@@ -352,4 +513,75 @@ mod tests {
         assert_eq!(stored.last_status.as_deref(), Some("error"));
         assert!(stored.last_run.is_some());
     }
+
+    #[test]
+    fn run_log_is_newest_first_truncated_and_pruned() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+
+        record_run(&config, "job-a", false, "first").unwrap();
+        record_run(
+            &config,
+            "job-a",
+            true,
+            &"x".repeat(MAX_RUN_OUTPUT_CHARS + 10),
+        )
+        .unwrap();
+        record_run(&config, "job-b", true, "other job").unwrap();
+
+        let runs = job_runs(&config, "job-a", 10).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert!(runs[0].success);
+        assert_eq!(runs[0].output.chars().count(), MAX_RUN_OUTPUT_CHARS + 3);
+        assert_eq!(runs[1].output, "first");
+        assert_eq!(job_runs(&config, "job-a", 1).unwrap().len(), 1);
+
+        for i in 0..MAX_RUNS_PER_JOB {
+            record_run(&config, "job-a", true, &format!("run {i}")).unwrap();
+        }
+        let runs = job_runs(&config, "job-a", 100).unwrap();
+        assert_eq!(runs.len(), MAX_RUNS_PER_JOB as usize);
+        assert_eq!(runs.last().unwrap().output, "run 0");
+        assert_eq!(job_runs(&config, "job-b", 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn agent_reminder_runs_once_then_disappears() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+        let run_at = Utc::now() + ChronoDuration::hours(2);
+
+        let job = add_agent_reminder(&config.workspace_dir, run_at, "check the build").unwrap();
+        assert_eq!(job.expression, ONCE);
+        assert_eq!(job.agent_prompt(), Some("check the build"));
+
+        assert!(due_jobs(&config, Utc::now()).unwrap().is_empty());
+        let due = due_jobs(&config, run_at + ChronoDuration::seconds(1)).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].agent_prompt(), Some("check the build"));
+
+        reschedule_after_run(&config, &due[0], true, "done").unwrap();
+        assert!(list_jobs(&config).unwrap().is_empty());
+    }
+
+    #[test]
+    fn only_reminder_jobs_run_the_agent() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+
+        let job = add_job(&config, "*/5 * * * *", "agent: echo hi").unwrap();
+        assert_eq!(job.agent_prompt(), None);
+    }
+
+    #[test]
+    fn agent_reminder_rejects_past_time_and_empty_prompt() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+        let past = Utc::now() - ChronoDuration::minutes(1);
+        let future = Utc::now() + ChronoDuration::minutes(5);
+
+        assert!(add_agent_reminder(&config.workspace_dir, past, "late").is_err());
+        assert!(add_agent_reminder(&config.workspace_dir, future, "  ").is_err());
+        assert!(list_jobs(&config).unwrap().is_empty());
+    }
 }
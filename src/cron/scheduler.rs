@@ -1,14 +1,17 @@
 use crate::config::Config;
-use crate::cron::{due_jobs, reschedule_after_run, CronJob};
+use crate::cron::{due_jobs, record_run, reschedule_after_run, CronJob};
 use crate::security::SecurityPolicy;
 use anyhow::Result;
 use chrono::Utc;
+use std::sync::Arc;
 use tokio::process::Command;
 use tokio::time::{self, Duration};
 
 const MIN_POLL_SECONDS: u64 = 5;
 
-pub async fn run(config: Config) -> Result<()> {
+/// Poll for due jobs forever. Each tick holds `busy` while its jobs run, so
+/// whoever holds it knows no job is in flight.
+pub async fn run(config: Config, busy: Arc<tokio::sync::Mutex<()>>) -> Result<()> {
     let poll_secs = config.reliability.scheduler_poll_secs.max(MIN_POLL_SECONDS);
     let mut interval = time::interval(Duration::from_secs(poll_secs));
     let security = SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
@@ -18,29 +21,51 @@ pub async fn run(config: Config) -> Result<()> {
     loop {
         interval.tick().await;
 
-        let jobs = match due_jobs(&config, Utc::now()) {
-            Ok(jobs) => jobs,
-            Err(e) => {
-                crate::health::mark_component_error("scheduler", e.to_string());
-                tracing::warn!("Scheduler query failed: {e}");
-                continue;
-            }
-        };
+        let _busy = busy.lock().await;
+        if let Err(e) = tick(&config, &security).await {
+            crate::health::mark_component_error("scheduler", e.to_string());
+            tracing::warn!("Scheduler query failed: {e}");
+        }
+    }
+}
 
-        for job in jobs {
-            crate::health::mark_component_ok("scheduler");
-            let (success, output) = execute_job_with_retry(&config, &security, &job).await;
+/// Outcome of a single scheduler evaluation.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TickSummary {
+    pub ran: usize,
+    pub failed: usize,
+}
 
-            if !success {
-                crate::health::mark_component_error("scheduler", format!("job {} failed", job.id));
-            }
+/// Run every job that is due right now, once. Errors only when the job store
+/// cannot be queried; individual job failures are counted in the summary.
+pub async fn tick(config: &Config, security: &SecurityPolicy) -> Result<TickSummary> {
+    let jobs = due_jobs(config, Utc::now())?;
+    let mut summary = TickSummary::default();
 
-            if let Err(e) = reschedule_after_run(&config, &job, success, &output) {
-                crate::health::mark_component_error("scheduler", e.to_string());
-                tracing::warn!("Failed to persist scheduler run result: {e}");
-            }
+    for job in jobs {
+        let Some(_work) = crate::health::work_tracker().begin() else {
+            tracing::info!("Shutdown in progress; deferring remaining scheduled jobs");
+            break;
+        };
+        crate::health::mark_component_ok("scheduler");
+        let (success, output) = execute_job_with_retry(config, security, &job).await;
+        summary.ran += 1;
+
+        if !success {
+            summary.failed += 1;
+            crate::health::mark_component_error("scheduler", format!("job {} failed", job.id));
+        }
+
+        if let Err(e) = record_run(config, &job.id, success, &output) {
+            tracing::warn!("Failed to log run of job {}: {e}", job.id);
+        }
+        if let Err(e) = reschedule_after_run(config, &job, success, &output) {
+            crate::health::mark_component_error("scheduler", e.to_string());
+            tracing::warn!("Failed to persist scheduler run result: {e}");
         }
     }
+
+    Ok(summary)
 }
 
 async fn execute_job_with_retry(
@@ -48,6 +73,12 @@ async fn execute_job_with_retry(
     security: &SecurityPolicy,
     job: &CronJob,
 ) -> (bool, String) {
+    // A failed agent turn may already have called tools with side effects,
+    // so it runs exactly once
+    if let Some(prompt) = job.agent_prompt() {
+        return run_agent_prompt(config, prompt).await;
+    }
+
     let mut last_output = String::new();
     let retries = config.reliability.scheduler_retries;
     let mut backoff_ms = config.reliability.provider_backoff_ms.max(200);
@@ -178,6 +209,16 @@ async fn run_job_command(
     }
 }
 
+/// Re-invoke the agent with a reminder prompt scheduled by the agent itself,
+/// returning its reply.
+async fn run_agent_prompt(config: &Config, prompt: &str) -> (bool, String) {
+    let message = format!("[Reminder] {prompt}");
+    match crate::agent::run_unattended(config.clone(), message).await {
+        Ok(reply) => (true, reply),
+        Err(e) => (false, format!("agent run failed: {e:#}")),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -206,6 +247,40 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn tick_with_no_jobs_runs_nothing() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+        let security = SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
+
+        let summary = tick(&config, &security).await.unwrap();
+        assert_eq!(summary, TickSummary::default());
+    }
+
+    #[tokio::test]
+    async fn tick_logs_each_run() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+        let security = SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
+        let job = crate::cron::add_job(&config, "*/5 * * * *", "echo logged-run").unwrap();
+        crate::cron::with_connection(&config, |conn| {
+            conn.execute(
+                "UPDATE cron_jobs SET next_run = ?1",
+                [(Utc::now() - chrono::Duration::minutes(1)).to_rfc3339()],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+
+        let summary = tick(&config, &security).await.unwrap();
+        assert_eq!(summary.ran, 1);
+
+        let runs = crate::cron::job_runs(&config, &job.id, 10).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert!(runs[0].success);
+        assert!(runs[0].output.contains("logged-run"), "{}", runs[0].output);
+    }
+
     #[tokio::test]
     async fn run_job_command_success() {
         let tmp = TempDir::new().unwrap();
@@ -296,4 +371,82 @@ mod tests {
         assert!(!success);
         assert!(output.contains("always_missing_for_retry_test"));
     }
+
+    /// Answer every chat request with `status` and `body`; returns the base
+    /// URL and how many requests arrived.
+    fn fake_provider(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let hits = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let seen = hits.clone();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut buf = [0; 8192];
+                let _ = stream.read(&mut buf);
+                seen.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+            }
+        });
+        (base, hits)
+    }
+
+    fn agent_job_config(tmp: &TempDir, provider_url: &str) -> Config {
+        let mut config = test_config(tmp);
+        config.api_key = Some("test-key".into());
+        config.default_provider = Some(format!("custom:{provider_url}"));
+        config.default_model = Some("test-model".into());
+        config.memory.backend = "none".into();
+        config.memory.auto_save = false;
+        config.reliability.provider_retries = 0;
+        config.reliability.scheduler_retries = 2;
+        config.reliability.provider_backoff_ms = 1;
+        config
+    }
+
+    fn agent_job(prompt: &str) -> CronJob {
+        CronJob {
+            expression: crate::cron::ONCE.into(),
+            ..test_job(&format!("{}{prompt}", crate::cron::AGENT_PREFIX))
+        }
+    }
+
+    #[tokio::test]
+    async fn agent_jobs_run_once_and_log_the_reply() {
+        let tmp = TempDir::new().unwrap();
+        let (base, hits) = fake_provider(
+            "200 OK",
+            r#"{"choices":[{"message":{"role":"assistant","content":"Build is green."}}]}"#,
+        );
+        let config = agent_job_config(&tmp, &base);
+        let security = SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
+
+        let (success, output) =
+            execute_job_with_retry(&config, &security, &agent_job("check the build")).await;
+        assert!(success, "{output}");
+        assert_eq!(output, "Build is green.");
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_agent_jobs_are_not_retried() {
+        let tmp = TempDir::new().unwrap();
+        let (base, hits) = fake_provider("400 Bad Request", r#"{"error":"bad request"}"#);
+        let config = agent_job_config(&tmp, &base);
+        let security = SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
+
+        let (success, output) =
+            execute_job_with_retry(&config, &security, &agent_job("check the build")).await;
+        assert!(!success);
+        assert!(output.starts_with("agent run failed"), "{output}");
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
 }
@@ -1,27 +1,32 @@
 use crate::config::Config;
-use anyhow::Result;
+use crate::observability::Observer;
+use crate::util::AbortOnDrop;
+use anyhow::{Context, Result};
 use chrono::Utc;
+use std::fmt::Write;
 use std::future::Future;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokio::task::JoinHandle;
 use tokio::time::Duration;
 
 const STATUS_FLUSH_SECONDS: u64 = 5;
+/// Upper bound on how long shutdown waits for in-flight turns to finish.
+const SHUTDOWN_DRAIN_SECONDS: u64 = 30;
 
-pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
-    let initial_backoff = config.reliability.channel_initial_backoff_secs.max(1);
-    let max_backoff = config
-        .reliability
-        .channel_max_backoff_secs
-        .max(initial_backoff);
+pub async fn run(
+    config: Config,
+    host: String,
+    port: u16,
+    port_file: Option<PathBuf>,
+) -> Result<()> {
+    let mut config = config;
+    let (initial_backoff, max_backoff) = backoff_bounds(&config);
 
-    crate::health::mark_component_ok("daemon");
+    prepare(&config).await;
 
-    if config.heartbeat.enabled {
-        let _ =
-            crate::heartbeat::engine::HeartbeatEngine::ensure_heartbeat_file(&config.workspace_dir)
-                .await;
-    }
+    let observer: Arc<dyn Observer> =
+        Arc::from(crate::observability::create_observer(&config.observability));
 
     let mut handles: Vec<JoinHandle<()>> = vec![spawn_state_writer(config.clone())];
 
@@ -35,71 +40,580 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
             move || {
                 let cfg = gateway_cfg.clone();
                 let host = gateway_host.clone();
-                async move { crate::gateway::run_gateway(&host, port, cfg).await }
+                let port_file = port_file.clone();
+                async move {
+                    crate::gateway::run_gateway(&host, port, port_file.as_deref(), cfg).await
+                }
             },
         ));
     }
 
-    {
-        if has_supervised_channels(&config) {
-            let channels_cfg = config.clone();
-            handles.push(spawn_component_supervisor(
-                "channels",
-                initial_backoff,
-                max_backoff,
-                move || {
-                    let cfg = channels_cfg.clone();
-                    async move { crate::channels::start_channels(cfg).await }
-                },
+    let channels_busy = Arc::default();
+    let heartbeat_busy = Arc::default();
+    let scheduler_busy = Arc::default();
+    let mut reloadable = ReloadableComponents {
+        channels: spawn_channels(&config, &channels_busy),
+        heartbeat: spawn_heartbeat(&config, &observer, &heartbeat_busy),
+        scheduler: Some(spawn_scheduler(&config, &scheduler_busy)),
+        channels_busy,
+        heartbeat_busy,
+        scheduler_busy,
+    };
+
+    println!("🧠 TinyClaw daemon started");
+    println!("   Gateway:  http://{host}:{port}");
+    println!("   Components: gateway, channels, heartbeat, scheduler");
+    println!("   Ctrl+C to stop");
+
+    let mut signals = Signals::install()?;
+    let signal = loop {
+        match signals.next().await? {
+            DaemonSignal::Reload => {
+                tracing::info!("Received SIGHUP; reloading config");
+                reload(&mut config, &mut reloadable, &observer);
+            }
+            DaemonSignal::Shutdown(name) => break name,
+        }
+    };
+    tracing::info!("Received {signal}; shutting down");
+    handles.extend(reloadable.into_handles());
+    shutdown(handles, observer.as_ref(), &config).await;
+
+    Ok(())
+}
+
+fn backoff_bounds(config: &Config) -> (u64, u64) {
+    let initial = config.reliability.channel_initial_backoff_secs.max(1);
+    let max = config.reliability.channel_max_backoff_secs.max(initial);
+    (initial, max)
+}
+
+/// Components a config reload may restart. The gateway is never one of
+/// them, so reloading doesn't drop webhook connections.
+struct ReloadableComponents {
+    channels: Option<JoinHandle<()>>,
+    heartbeat: Option<JoinHandle<()>>,
+    /// Always running; optional so a reload can hand the old task over
+    scheduler: Option<JoinHandle<()>>,
+    /// Read-locked by channels while a message is answered
+    channels_busy: Arc<tokio::sync::RwLock<()>>,
+    /// Held by the heartbeat while a cycle runs
+    heartbeat_busy: Arc<tokio::sync::Mutex<()>>,
+    /// Held by the scheduler while due jobs run
+    scheduler_busy: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl ReloadableComponents {
+    fn into_handles(self) -> Vec<JoinHandle<()>> {
+        self.channels
+            .into_iter()
+            .chain(self.heartbeat)
+            .chain(self.scheduler)
+            .collect()
+    }
+}
+
+fn spawn_channels(config: &Config, busy: &Arc<tokio::sync::RwLock<()>>) -> Option<JoinHandle<()>> {
+    if !has_supervised_channels(config) {
+        crate::health::mark_component_ok("channels");
+        tracing::info!("No real-time channels configured; channel supervisor disabled");
+        return None;
+    }
+    let (initial_backoff, max_backoff) = backoff_bounds(config);
+    let channels_cfg = config.clone();
+    let busy = Arc::clone(busy);
+    Some(spawn_component_supervisor(
+        "channels",
+        initial_backoff,
+        max_backoff,
+        move || {
+            let cfg = channels_cfg.clone();
+            let busy = Arc::clone(&busy);
+            async move { crate::channels::serve_channels(cfg, busy).await }
+        },
+    ))
+}
+
+fn spawn_heartbeat(
+    config: &Config,
+    observer: &Arc<dyn Observer>,
+    busy: &Arc<tokio::sync::Mutex<()>>,
+) -> Option<JoinHandle<()>> {
+    if !config.heartbeat.enabled {
+        return None;
+    }
+    let (initial_backoff, max_backoff) = backoff_bounds(config);
+    let heartbeat_cfg = config.clone();
+    let heartbeat_observer = observer.clone();
+    let busy = Arc::clone(busy);
+    Some(spawn_component_supervisor(
+        "heartbeat",
+        initial_backoff,
+        max_backoff,
+        move || {
+            let cfg = heartbeat_cfg.clone();
+            let observer = heartbeat_observer.clone();
+            let busy = Arc::clone(&busy);
+            async move { run_heartbeat_worker(cfg, observer, busy).await }
+        },
+    ))
+}
+
+fn spawn_scheduler(config: &Config, busy: &Arc<tokio::sync::Mutex<()>>) -> JoinHandle<()> {
+    let (initial_backoff, max_backoff) = backoff_bounds(config);
+    let scheduler_cfg = config.clone();
+    let busy = Arc::clone(busy);
+    spawn_component_supervisor("scheduler", initial_backoff, max_backoff, move || {
+        let cfg = scheduler_cfg.clone();
+        let busy = Arc::clone(&busy);
+        async move { crate::cron::scheduler::run(cfg, busy).await }
+    })
+}
+
+/// Stop `old` once it is between jobs (`idle` resolves, to a guard held
+/// while it is stopped), then start its replacement, so a reload never cuts
+/// off a running channel turn, heartbeat task or cron job. Aborting the
+/// returned handle also stops the replacement.
+fn restart_when_idle<I, F>(old: Option<JoinHandle<()>>, idle: I, start: F) -> JoinHandle<()>
+where
+    I: std::future::Future + Send + 'static,
+    I::Output: Send,
+    F: FnOnce() -> Option<JoinHandle<()>> + Send + 'static,
+{
+    tokio::spawn(async move {
+        if let Some(old) = old {
+            let _idle = idle.await;
+            old.abort();
+            let _ = old.await;
+        }
+        if let Some(new) = start() {
+            let mut new = AbortOnDrop(new);
+            let _ = (&mut new.0).await;
+        }
+    })
+}
+
+/// Re-read `config.toml` and hot-apply the settings that are safe to change
+/// while running. An invalid file is rejected and the current config kept.
+fn reload(
+    current: &mut Config,
+    components: &mut ReloadableComponents,
+    observer: &Arc<dyn Observer>,
+) {
+    let loaded = Config::load_or_init().and_then(|new| {
+        validate_reload(&new)?;
+        Ok(new)
+    });
+    let new = match loaded {
+        Ok(new) => new,
+        Err(e) => {
+            tracing::error!("Reload rejected, keeping current config: {e:#}");
+            return;
+        }
+    };
+
+    let plan = ReloadPlan::new(current, &new);
+    if !plan.needs_restart.is_empty() {
+        tracing::warn!(
+            "Reload: {} changed; restart the daemon to apply",
+            plan.needs_restart.join(", ")
+        );
+    }
+    if plan.changes.is_empty() {
+        tracing::info!("Reload: no reloadable settings changed");
+        return;
+    }
+
+    let applied = with_reloadable(current, &new);
+    if plan.log_level {
+        if let Err(e) = crate::observability::log_level::parse(&applied.observability.log_level)
+            .and_then(crate::observability::log_level::set)
+        {
+            tracing::warn!("Reload: {e:#}");
+        }
+    }
+    if plan.channels {
+        let config = applied.clone();
+        let busy = Arc::clone(&components.channels_busy);
+        components.channels = Some(restart_when_idle(
+            components.channels.take(),
+            Arc::clone(&busy).write_owned(),
+            move || spawn_channels(&config, &busy),
+        ));
+    }
+    if plan.heartbeat {
+        let (config, observer) = (applied.clone(), observer.clone());
+        let busy = Arc::clone(&components.heartbeat_busy);
+        components.heartbeat = Some(restart_when_idle(
+            components.heartbeat.take(),
+            Arc::clone(&busy).lock_owned(),
+            move || spawn_heartbeat(&config, &observer, &busy),
+        ));
+    }
+    if plan.scheduler {
+        let config = applied.clone();
+        let busy = Arc::clone(&components.scheduler_busy);
+        components.scheduler = Some(restart_when_idle(
+            components.scheduler.take(),
+            Arc::clone(&busy).lock_owned(),
+            move || Some(spawn_scheduler(&config, &busy)),
+        ));
+    }
+    for change in &plan.changes {
+        tracing::info!("Reloaded {change}");
+    }
+    *current = applied;
+}
+
+/// Checks a reloaded config must pass before any of it is applied.
+fn validate_reload(config: &Config) -> Result<()> {
+    crate::observability::log_level::parse(&config.observability.log_level)?;
+    if config.heartbeat.enabled && config.heartbeat.interval_minutes == 0 {
+        anyhow::bail!("heartbeat.interval_minutes must be at least 1");
+    }
+    let mut hints = std::collections::HashSet::new();
+    for route in &config.model_routes {
+        if route.hint.trim().is_empty() || route.model.trim().is_empty() {
+            anyhow::bail!("Every model route needs a hint and a model");
+        }
+        if !hints.insert(route.hint.as_str()) {
+            anyhow::bail!("Duplicate model route hint '{}'", route.hint);
+        }
+        crate::providers::create_provider(&route.provider, route.api_key.as_deref())
+            .with_context(|| format!("Invalid provider for model route '{}'", route.hint))?;
+    }
+    Ok(())
+}
+
+/// What a reload changes. Only `heartbeat`, `model_routes`,
+/// `channels_config` and `observability.log_level` are applied live; other
+/// differences are reported as needing a restart.
+#[derive(Debug, Default)]
+#[allow(clippy::struct_excessive_bools)]
+struct ReloadPlan {
+    channels: bool,
+    heartbeat: bool,
+    scheduler: bool,
+    log_level: bool,
+    /// One human-readable line per applied setting
+    changes: Vec<String>,
+    /// Top-level config keys that changed but are not reloadable
+    needs_restart: Vec<String>,
+}
+
+impl ReloadPlan {
+    fn new(current: &Config, new: &Config) -> Self {
+        let mut plan = Self::default();
+
+        let heartbeat_changed = current.heartbeat.enabled != new.heartbeat.enabled
+            || current.heartbeat.interval_minutes != new.heartbeat.interval_minutes;
+        if heartbeat_changed {
+            plan.changes.push(format!(
+                "heartbeat: {} -> {}",
+                describe_heartbeat(current),
+                describe_heartbeat(new)
+            ));
+        }
+
+        let routes_changed = !same(&current.model_routes, &new.model_routes);
+        if routes_changed {
+            plan.changes.push(format!(
+                "model routes: {} -> {}",
+                describe_routes(current),
+                describe_routes(new)
             ));
-        } else {
-            crate::health::mark_component_ok("channels");
-            tracing::info!("No real-time channels configured; channel supervisor disabled");
         }
+
+        plan.channels = !same(&current.channels_config, &new.channels_config);
+        if plan.channels {
+            plan.changes.push(format!(
+                "channels: {} -> {}",
+                describe_channels(current),
+                describe_channels(new)
+            ));
+        }
+
+        plan.log_level = current.observability.log_level != new.observability.log_level;
+        if plan.log_level {
+            plan.changes.push(format!(
+                "log level: {} -> {}",
+                current.observability.log_level, new.observability.log_level
+            ));
+        }
+
+        // Heartbeat tasks and scheduled agent jobs build their provider from
+        // the config they were started with
+        plan.heartbeat = heartbeat_changed || routes_changed;
+        plan.scheduler = routes_changed;
+
+        // Whatever still differs once the reloadable settings are copied
+        // across needs a restart
+        let merged = with_reloadable(new, current);
+        if let (Ok(toml::Value::Table(old)), Ok(toml::Value::Table(merged))) = (
+            toml::Value::try_from(current),
+            toml::Value::try_from(&merged),
+        ) {
+            let mut keys: Vec<String> = old
+                .keys()
+                .chain(merged.keys())
+                .filter(|key| old.get(*key) != merged.get(*key))
+                .cloned()
+                .collect();
+            keys.sort();
+            keys.dedup();
+            plan.needs_restart = keys;
+        }
+        plan
     }
+}
+
+/// `current` with the reloadable settings taken from `new`.
+fn with_reloadable(current: &Config, new: &Config) -> Config {
+    let mut applied = current.clone();
+    applied.heartbeat.clone_from(&new.heartbeat);
+    applied.model_routes.clone_from(&new.model_routes);
+    applied.channels_config.clone_from(&new.channels_config);
+    applied
+        .observability
+        .log_level
+        .clone_from(&new.observability.log_level);
+    applied
+}
 
+/// Compare two config sections by their serialized form.
+fn same<T: serde::Serialize>(a: &T, b: &T) -> bool {
+    match (toml::Value::try_from(a), toml::Value::try_from(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn describe_heartbeat(config: &Config) -> String {
     if config.heartbeat.enabled {
-        let heartbeat_cfg = config.clone();
-        handles.push(spawn_component_supervisor(
-            "heartbeat",
-            initial_backoff,
-            max_backoff,
-            move || {
-                let cfg = heartbeat_cfg.clone();
-                async move { run_heartbeat_worker(cfg).await }
-            },
-        ));
+        format!("every {} min", config.heartbeat.interval_minutes)
+    } else {
+        "off".to_string()
     }
+}
 
-    {
-        let scheduler_cfg = config.clone();
-        handles.push(spawn_component_supervisor(
-            "scheduler",
-            initial_backoff,
-            max_backoff,
-            move || {
-                let cfg = scheduler_cfg.clone();
-                async move { crate::cron::scheduler::run(cfg).await }
-            },
-        ));
+fn describe_routes(config: &Config) -> String {
+    if config.model_routes.is_empty() {
+        return "none".to_string();
     }
+    config
+        .model_routes
+        .iter()
+        .map(|r| format!("{}={}/{}", r.hint, r.provider, r.model))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
 
-    println!("🧠 TinyClaw daemon started");
-    println!("   Gateway:  http://{host}:{port}");
-    println!("   Components: gateway, channels, heartbeat, scheduler");
-    println!("   Ctrl+C to stop");
+fn describe_channels(config: &Config) -> String {
+    let names = supervised_channels(config);
+    if names.is_empty() {
+        "none".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum DaemonSignal {
+    /// SIGHUP: re-read the config
+    Reload,
+    /// SIGINT or SIGTERM
+    Shutdown(&'static str),
+}
+
+/// Signal streams, registered once so none are missed between waits.
+struct Signals {
+    #[cfg(unix)]
+    hangup: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+}
+
+impl Signals {
+    fn install() -> Result<Self> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            Ok(Self {
+                hangup: signal(SignalKind::hangup())?,
+                terminate: signal(SignalKind::terminate())?,
+            })
+        }
+        #[cfg(not(unix))]
+        {
+            Ok(Self {})
+        }
+    }
+
+    /// Wait for SIGINT (Ctrl+C) or, on Unix, SIGTERM from the service
+    /// manager or SIGHUP.
+    async fn next(&mut self) -> Result<DaemonSignal> {
+        #[cfg(unix)]
+        {
+            tokio::select! {
+                res = tokio::signal::ctrl_c() => {
+                    res.map(|()| DaemonSignal::Shutdown("SIGINT")).map_err(Into::into)
+                }
+                _ = self.terminate.recv() => Ok(DaemonSignal::Shutdown("SIGTERM")),
+                _ = self.hangup.recv() => Ok(DaemonSignal::Reload),
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            tokio::signal::ctrl_c().await?;
+            Ok(DaemonSignal::Shutdown("Ctrl+C"))
+        }
+    }
+}
 
-    tokio::signal::ctrl_c().await?;
+/// Stop taking new work, drain in-flight turns (bounded), stop components and
+/// flush state. Each phase is logged so restarts are easy to diagnose.
+async fn shutdown(handles: Vec<JoinHandle<()>>, observer: &dyn Observer, config: &Config) {
     crate::health::mark_component_error("daemon", "shutdown requested");
 
+    let work = crate::health::work_tracker();
+    work.request_shutdown();
+    tracing::info!("Shutdown: no longer accepting new work");
+
+    let pending = work.in_flight();
+    if pending > 0 {
+        tracing::info!("Shutdown: waiting for {pending} in-flight task(s)");
+    }
+    if drain_in_flight(work, Duration::from_secs(SHUTDOWN_DRAIN_SECONDS)).await {
+        tracing::info!("Shutdown: in-flight work drained");
+    } else {
+        tracing::warn!(
+            "Shutdown: {} task(s) still running after {SHUTDOWN_DRAIN_SECONDS}s, aborting",
+            work.in_flight()
+        );
+    }
+
     for handle in &handles {
         handle.abort();
     }
     for handle in handles {
         let _ = handle.await;
     }
+    tracing::info!("Shutdown: components stopped");
 
-    Ok(())
+    observer.flush();
+    write_state_file(&state_file_path(config)).await;
+    tracing::info!("Shutdown: state flushed, exiting");
+}
+
+/// Poll until no work is in flight or `timeout` elapses. Returns true if drained.
+async fn drain_in_flight(work: &crate::health::WorkTracker, timeout: Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    while work.in_flight() > 0 {
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    true
+}
+
+/// Setup shared by the long-running daemon and the `--once` self-test.
+async fn prepare(config: &Config) {
+    crate::health::mark_component_ok("daemon");
+    crate::providers::throttle::install(config.reliability.requests_per_minute);
+
+    if config.heartbeat.enabled {
+        let _ =
+            crate::heartbeat::engine::HeartbeatEngine::ensure_heartbeat_file(&config.workspace_dir)
+                .await;
+    }
+}
+
+/// How long the gateway must stay up during `--once` to count as started.
+const SELF_TEST_GATEWAY_GRACE: Duration = Duration::from_secs(2);
+
+/// Self-test: start the gateway, run one heartbeat cycle and one scheduler
+/// evaluation, print a summary and return an error if any step failed.
+pub async fn run_once(
+    config: Config,
+    host: String,
+    port: u16,
+    port_file: Option<PathBuf>,
+) -> Result<()> {
+    prepare(&config).await;
+
+    let mut checks: Vec<(&'static str, std::result::Result<String, String>)> = Vec::new();
+
+    let cfg = config.clone();
+    let bind_host = host.clone();
+    let gateway = tokio::spawn(async move {
+        crate::gateway::run_gateway(&bind_host, port, port_file.as_deref(), cfg).await
+    });
+    tokio::time::sleep(SELF_TEST_GATEWAY_GRACE).await;
+    let gateway_result = if gateway.is_finished() {
+        match gateway.await {
+            Ok(Ok(())) => Err("gateway exited immediately".to_string()),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(e) => Err(format!("gateway task panicked: {e}")),
+        }
+    } else {
+        gateway.abort();
+        let _ = gateway.await;
+        Ok(format!("started on {host}:{port}"))
+    };
+    checks.push(("gateway", gateway_result));
+
+    let heartbeat_result = if config.heartbeat.enabled {
+        let observer = Arc::from(crate::observability::create_observer(&config.observability));
+        let engine = heartbeat_engine(&config, observer);
+        match run_heartbeat_cycle(&config, &engine).await {
+            Ok((tasks, 0)) => Ok(format!("{tasks} task(s) ran")),
+            Ok((tasks, failed)) => Err(format!("{failed} of {tasks} task(s) failed")),
+            Err(e) => Err(e.to_string()),
+        }
+    } else {
+        Ok("disabled, skipped".to_string())
+    };
+    checks.push(("heartbeat", heartbeat_result));
+
+    let security =
+        crate::security::SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
+    let scheduler_result = match crate::cron::scheduler::tick(&config, &security).await {
+        Ok(summary) if summary.failed == 0 => Ok(format!("{} due job(s) ran", summary.ran)),
+        Ok(summary) => Err(format!(
+            "{} of {} due job(s) failed",
+            summary.failed, summary.ran
+        )),
+        Err(e) => Err(e.to_string()),
+    };
+    checks.push(("scheduler", scheduler_result));
+
+    let (report, all_ok) = format_self_test_report(&checks);
+    println!("{report}");
+    if all_ok {
+        Ok(())
+    } else {
+        anyhow::bail!("daemon self-test failed")
+    }
+}
+
+fn format_self_test_report(
+    checks: &[(&str, std::result::Result<String, String>)],
+) -> (String, bool) {
+    let mut report = String::from("🧠 TinyClaw daemon self-test");
+    let mut all_ok = true;
+    for (name, result) in checks {
+        match result {
+            Ok(detail) => {
+                let _ = write!(report, "\n   ✅ {name:<10} {detail}");
+            }
+            Err(detail) => {
+                all_ok = false;
+                let _ = write!(report, "\n   ❌ {name:<10} {detail}");
+            }
+        }
+    }
+    (report, all_ok)
 }
 
 pub fn state_file_path(config: &Config) -> PathBuf {
@@ -120,19 +634,23 @@ fn spawn_state_writer(config: Config) -> JoinHandle<()> {
         let mut interval = tokio::time::interval(Duration::from_secs(STATUS_FLUSH_SECONDS));
         loop {
             interval.tick().await;
-            let mut json = crate::health::snapshot_json();
-            if let Some(obj) = json.as_object_mut() {
-                obj.insert(
-                    "written_at".into(),
-                    serde_json::json!(Utc::now().to_rfc3339()),
-                );
-            }
-            let data = serde_json::to_vec_pretty(&json).unwrap_or_else(|_| b"{}".to_vec());
-            let _ = tokio::fs::write(&path, data).await;
+            write_state_file(&path).await;
         }
     })
 }
 
+async fn write_state_file(path: &Path) {
+    let mut json = crate::health::snapshot_json();
+    if let Some(obj) = json.as_object_mut() {
+        obj.insert(
+            "written_at".into(),
+            serde_json::json!(Utc::now().to_rfc3339()),
+        );
+    }
+    let data = serde_json::to_vec_pretty(&json).unwrap_or_else(|_| b"{}".to_vec());
+    let _ = tokio::fs::write(path, data).await;
+}
+
 fn spawn_component_supervisor<F, Fut>(
     name: &'static str,
     initial_backoff_secs: u64,
@@ -170,48 +688,80 @@ where
     })
 }
 
-async fn run_heartbeat_worker(config: Config) -> Result<()> {
-    let observer: std::sync::Arc<dyn crate::observability::Observer> =
-        std::sync::Arc::from(crate::observability::create_observer(&config.observability));
-    let engine = crate::heartbeat::engine::HeartbeatEngine::new(
+fn heartbeat_engine(
+    config: &Config,
+    observer: Arc<dyn Observer>,
+) -> crate::heartbeat::engine::HeartbeatEngine {
+    crate::heartbeat::engine::HeartbeatEngine::new(
         config.heartbeat.clone(),
         config.workspace_dir.clone(),
         observer,
-    );
+    )
+}
+
+async fn run_heartbeat_worker(
+    config: Config,
+    observer: Arc<dyn Observer>,
+    busy: Arc<tokio::sync::Mutex<()>>,
+) -> Result<()> {
+    let engine = heartbeat_engine(&config, observer);
 
     let interval_mins = config.heartbeat.interval_minutes.max(5);
     let mut interval = tokio::time::interval(Duration::from_secs(u64::from(interval_mins) * 60));
 
     loop {
         interval.tick().await;
+        let _busy = busy.lock().await;
+        run_heartbeat_cycle(&config, &engine).await?;
+    }
+}
 
-        let tasks = engine.collect_tasks().await?;
-        if tasks.is_empty() {
-            continue;
-        }
+/// Run every task in HEARTBEAT.md once. Returns `(tasks, failed)`.
+async fn run_heartbeat_cycle(
+    config: &Config,
+    engine: &crate::heartbeat::engine::HeartbeatEngine,
+) -> Result<(usize, usize)> {
+    let tasks = engine.collect_tasks().await?;
+    let total = tasks.len();
+    let mut failed = 0;
 
-        for task in tasks {
-            let prompt = format!("[Heartbeat Task] {task}");
-            let temp = config.default_temperature;
-            if let Err(e) = crate::agent::run(config.clone(), Some(prompt), None, None, temp).await
-            {
-                crate::health::mark_component_error("heartbeat", e.to_string());
-                tracing::warn!("Heartbeat task failed: {e}");
-            } else {
-                crate::health::mark_component_ok("heartbeat");
-            }
+    for task in tasks {
+        let Some(_work) = crate::health::work_tracker().begin() else {
+            tracing::info!("Shutdown in progress; skipping remaining heartbeat tasks");
+            break;
+        };
+        let prompt = format!("[Heartbeat Task] {task}");
+        if let Err(e) = crate::agent::run_unattended(config.clone(), prompt).await {
+            failed += 1;
+            crate::health::mark_component_error("heartbeat", e.to_string());
+            tracing::warn!("Heartbeat task failed: {e}");
+        } else {
+            crate::health::mark_component_ok("heartbeat");
         }
     }
+
+    Ok((total, failed))
 }
 
 fn has_supervised_channels(config: &Config) -> bool {
-    config.channels_config.telegram.is_some()
-        || config.channels_config.discord.is_some()
-        || config.channels_config.slack.is_some()
-        || config.channels_config.imessage.is_some()
-        || config.channels_config.matrix.is_some()
-        || config.channels_config.whatsapp.is_some()
-        || config.channels_config.email.is_some()
+    !supervised_channels(config).is_empty()
+}
+
+/// Names of the configured real-time channels the daemon supervises.
+fn supervised_channels(config: &Config) -> Vec<&'static str> {
+    let channels = &config.channels_config;
+    [
+        ("telegram", channels.telegram.is_some()),
+        ("discord", channels.discord.is_some()),
+        ("slack", channels.slack.is_some()),
+        ("imessage", channels.imessage.is_some()),
+        ("matrix", channels.matrix.is_some()),
+        ("whatsapp", channels.whatsapp.is_some()),
+        ("email", channels.email.is_some()),
+    ]
+    .into_iter()
+    .filter_map(|(name, configured)| configured.then_some(name))
+    .collect()
 }
 
 #[cfg(test)]
@@ -238,6 +788,59 @@ mod tests {
         assert_eq!(path, tmp.path().join("daemon_state.json"));
     }
 
+    #[tokio::test]
+    async fn restart_waits_for_the_running_job() {
+        use tokio::sync::oneshot;
+
+        let busy: Arc<tokio::sync::Mutex<()>> = Arc::default();
+        let (started_tx, started_rx) = oneshot::channel();
+        let (release_tx, release_rx) = oneshot::channel::<()>();
+        let (finished_tx, finished_rx) = oneshot::channel();
+        let job_busy = Arc::clone(&busy);
+        let old = tokio::spawn(async move {
+            {
+                let _job = job_busy.lock().await;
+                let _ = started_tx.send(());
+                let _ = release_rx.await;
+                let _ = finished_tx.send(());
+            }
+            std::future::pending::<()>().await;
+        });
+        started_rx.await.unwrap();
+
+        let (new_tx, mut new_rx) = oneshot::channel();
+        let handle = restart_when_idle(Some(old), busy.lock_owned(), move || {
+            let _ = new_tx.send(());
+            Some(tokio::spawn(std::future::pending()))
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(new_rx.try_recv().is_err(), "replacement waits for the job");
+
+        release_tx.send(()).unwrap();
+        finished_rx.await.expect("the running job finishes");
+        new_rx.await.expect("the replacement starts");
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn channel_restart_waits_for_in_flight_turns() {
+        let busy: Arc<tokio::sync::RwLock<()>> = Arc::default();
+        let turn = Arc::clone(&busy).read_owned().await;
+        let old = tokio::spawn(std::future::pending());
+
+        let (new_tx, mut new_rx) = tokio::sync::oneshot::channel();
+        let handle = restart_when_idle(Some(old), Arc::clone(&busy).write_owned(), move || {
+            let _ = new_tx.send(());
+            Some(tokio::spawn(std::future::pending()))
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(new_rx.try_recv().is_err(), "replacement waits for the turn");
+
+        drop(turn);
+        new_rx.await.expect("the replacement starts");
+        handle.abort();
+    }
+
     #[tokio::test]
     async fn supervisor_marks_error_and_restart_on_failure() {
         let handle = spawn_component_supervisor("daemon-test-fail", 1, 1, || async {
@@ -276,6 +879,165 @@ mod tests {
             .contains("component exited unexpectedly"));
     }
 
+    #[test]
+    fn self_test_report_passes_when_all_checks_ok() {
+        let checks = vec![
+            ("gateway", Ok("started".to_string())),
+            ("scheduler", Ok("0 due job(s) ran".to_string())),
+        ];
+        let (report, ok) = format_self_test_report(&checks);
+        assert!(ok);
+        assert!(report.contains("✅ gateway"));
+        assert!(!report.contains('❌'));
+    }
+
+    #[test]
+    fn self_test_report_fails_on_any_error() {
+        let checks = vec![
+            ("gateway", Ok("started".to_string())),
+            ("heartbeat", Err("1 of 2 task(s) failed".to_string())),
+        ];
+        let (report, ok) = format_self_test_report(&checks);
+        assert!(!ok);
+        assert!(report.contains("❌ heartbeat  1 of 2 task(s) failed"));
+    }
+
+    #[tokio::test]
+    async fn heartbeat_cycle_without_tasks_is_noop() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+        let engine = heartbeat_engine(&config, Arc::new(crate::observability::NoopObserver));
+
+        let (tasks, failed) = run_heartbeat_cycle(&config, &engine).await.unwrap();
+        assert_eq!((tasks, failed), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn drain_returns_immediately_when_idle() {
+        let work = crate::health::WorkTracker::new();
+        assert!(drain_in_flight(&work, Duration::from_millis(10)).await);
+    }
+
+    #[tokio::test]
+    async fn drain_waits_for_in_flight_work() {
+        let work: &'static crate::health::WorkTracker =
+            Box::leak(Box::new(crate::health::WorkTracker::new()));
+        let guard = work.begin().unwrap();
+        work.request_shutdown();
+        assert!(work.begin().is_none());
+
+        let release = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            drop(guard);
+        });
+        assert!(drain_in_flight(work, Duration::from_secs(5)).await);
+        release.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn drain_times_out_when_work_is_stuck() {
+        let work = crate::health::WorkTracker::new();
+        let _guard = work.begin().unwrap();
+        assert!(!drain_in_flight(&work, Duration::from_millis(150)).await);
+    }
+
+    #[test]
+    fn reload_plan_restarts_only_affected_components() {
+        let current = Config::default();
+
+        let mut new = current.clone();
+        new.heartbeat.enabled = true;
+        new.heartbeat.interval_minutes = 10;
+        let plan = ReloadPlan::new(&current, &new);
+        assert!(plan.heartbeat);
+        assert!(!plan.channels && !plan.scheduler && !plan.log_level);
+        assert_eq!(plan.changes, vec!["heartbeat: off -> every 10 min"]);
+        assert!(plan.needs_restart.is_empty(), "{:?}", plan.needs_restart);
+
+        let mut new = current.clone();
+        new.model_routes.push(crate::config::ModelRouteConfig {
+            hint: "fast".into(),
+            provider: "groq".into(),
+            model: "llama-3.3-70b".into(),
+            api_key: None,
+            temperature: None,
+        });
+        new.observability.log_level = "debug".into();
+        let plan = ReloadPlan::new(&current, &new);
+        assert!(plan.heartbeat && plan.scheduler && plan.log_level);
+        assert!(!plan.channels);
+        assert_eq!(
+            plan.changes,
+            vec![
+                "model routes: none -> fast=groq/llama-3.3-70b",
+                "log level: info -> debug"
+            ]
+        );
+
+        let mut new = current.clone();
+        new.channels_config.telegram = Some(crate::config::TelegramConfig {
+            bot_token: "token".into(),
+            allowed_users: vec![],
+        });
+        let plan = ReloadPlan::new(&current, &new);
+        assert!(plan.channels && !plan.heartbeat && !plan.scheduler);
+        assert_eq!(plan.changes, vec!["channels: none -> telegram"]);
+    }
+
+    #[test]
+    fn reload_keeps_settings_that_need_a_restart() {
+        let current = Config::default();
+        let mut new = current.clone();
+        new.default_provider = Some("anthropic".into());
+        new.gateway.port = current.gateway.port + 1;
+        new.heartbeat.enabled = true;
+
+        let plan = ReloadPlan::new(&current, &new);
+        assert_eq!(plan.needs_restart, vec!["default_provider", "gateway"]);
+
+        let applied = with_reloadable(&current, &new);
+        assert!(applied.heartbeat.enabled);
+        assert_eq!(applied.default_provider, current.default_provider);
+        assert_eq!(applied.gateway.port, current.gateway.port);
+
+        // The shared provider throttle is installed once per process
+        let mut new = current.clone();
+        new.reliability.requests_per_minute = 30;
+        let plan = ReloadPlan::new(&current, &new);
+        assert_eq!(plan.needs_restart, vec!["reliability"]);
+    }
+
+    #[test]
+    fn reload_validation_rejects_bad_settings() {
+        assert!(validate_reload(&Config::default()).is_ok());
+
+        let mut config = Config::default();
+        config.observability.log_level = "chatty".into();
+        assert!(validate_reload(&config).is_err());
+
+        let route = |hint: &str, provider: &str| crate::config::ModelRouteConfig {
+            hint: hint.into(),
+            provider: provider.into(),
+            model: "m".into(),
+            api_key: None,
+            temperature: None,
+        };
+        let mut config = Config {
+            model_routes: vec![route("fast", "groq"), route("fast", "openai")],
+            ..Config::default()
+        };
+        let err = validate_reload(&config).unwrap_err().to_string();
+        assert!(err.contains("Duplicate"), "{err}");
+
+        config.model_routes = vec![route("fast", "no-such-provider")];
+        assert!(validate_reload(&config).is_err());
+
+        let mut config = Config::default();
+        config.heartbeat.enabled = true;
+        config.heartbeat.interval_minutes = 0;
+        assert!(validate_reload(&config).is_err());
+    }
+
     #[test]
     fn detects_no_supervised_channels() {
         let config = Config::default();
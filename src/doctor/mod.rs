@@ -1,15 +1,81 @@
 use crate::config::Config;
+use crate::memory::sqlite::SqliteMemory;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use std::path::Path;
 
 const DAEMON_STALE_SECONDS: i64 = 30;
 const SCHEDULER_STALE_SECONDS: i64 = 120;
 const CHANNEL_STALE_SECONDS: i64 = 300;
 
-pub fn run(config: &Config) -> Result<()> {
+/// Outcome of one repairable check.
+#[derive(Debug, PartialEq)]
+enum Check {
+    Ok(String),
+    /// Problem with a safe, idempotent repair that `--fix` applies
+    Fixable(String),
+    /// Problem repaired during this run
+    Fixed(String),
+    /// Problem that needs a human; never changed automatically
+    Manual(String),
+    /// Not a problem; something the user may want to know about
+    Info(String),
+}
+
+/// Report daemon health, then workspace problems. With `fix`, apply the
+/// safe repairs and report what changed.
+pub fn run(config: &Config, fix: bool) -> Result<()> {
+    println!("🩺 TinyClaw Doctor");
+    report_daemon(config)?;
+
+    println!();
+    println!("  Workspace checks:");
+    let state_file = crate::daemon::state_file_path(config);
+    let sqlite = config.memory.backend == "sqlite";
+    let results = [
+        check_dir(&config.workspace_dir.join("sessions"), "sessions", fix),
+        check_dir(&config.workspace_dir.join("exports"), "exports", fix),
+        check_state_file(&state_file, fix),
+        if sqlite {
+            check_memory_schema(&config.workspace_dir.join("memory").join("brain.db"), fix)
+        } else {
+            Ok(Check::Ok(format!(
+                "memory backend '{}' needs no migration",
+                config.memory.backend
+            )))
+        },
+        check_service_unit(config),
+    ];
+
+    let (mut fixable, mut fixed) = (0_u32, 0_u32);
+    for result in results {
+        match result.unwrap_or_else(|e| Check::Manual(format!("{e:#}"))) {
+            Check::Ok(msg) => println!("  ✅ {msg}"),
+            Check::Fixable(msg) => {
+                fixable += 1;
+                println!("  ❌ {msg}");
+            }
+            Check::Fixed(msg) => {
+                fixed += 1;
+                tracing::info!("doctor --fix: {msg}");
+                println!("  🔧 {msg}");
+            }
+            Check::Manual(msg) => println!("  ⚠️ {msg}"),
+            Check::Info(msg) => println!("  ℹ️ {msg}"),
+        }
+    }
+
+    if fixable > 0 {
+        println!("  💡 Run `tinyclaw doctor --fix` to repair {fixable} issue(s)");
+    } else if fixed > 0 {
+        println!("  Applied {fixed} fix(es)");
+    }
+    Ok(())
+}
+
+fn report_daemon(config: &Config) -> Result<()> {
     let state_file = crate::daemon::state_file_path(config);
     if !state_file.exists() {
-        println!("🩺 TinyClaw Doctor");
         println!("  ❌ daemon state file not found: {}", state_file.display());
         println!("  💡 Start daemon with: tinyclaw daemon");
         return Ok(());
@@ -20,7 +86,6 @@ pub fn run(config: &Config) -> Result<()> {
     let snapshot: serde_json::Value = serde_json::from_str(&raw)
         .with_context(|| format!("Failed to parse {}", state_file.display()))?;
 
-    println!("🩺 TinyClaw Doctor");
     println!("  State file: {}", state_file.display());
 
     let updated_at = snapshot
@@ -114,3 +179,205 @@ fn parse_rfc3339(raw: &str) -> Option<DateTime<Utc>> {
         .ok()
         .map(|dt| dt.with_timezone(&Utc))
 }
+
+fn check_dir(path: &Path, label: &str, fix: bool) -> Result<Check> {
+    if path.is_dir() {
+        return Ok(Check::Ok(format!("{label} directory present")));
+    }
+    if path.exists() {
+        return Ok(Check::Manual(format!(
+            "{} is not a directory; move it aside so {label} can be saved",
+            path.display()
+        )));
+    }
+    if !fix {
+        return Ok(Check::Fixable(format!(
+            "{label} directory missing: {}",
+            path.display()
+        )));
+    }
+    std::fs::create_dir_all(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    Ok(Check::Fixed(format!(
+        "created {label} directory {}",
+        path.display()
+    )))
+}
+
+/// A state file whose heartbeat stopped is left behind by a daemon that
+/// exited uncleanly; a running daemon rewrites it within seconds.
+fn check_state_file(path: &Path, fix: bool) -> Result<Check> {
+    if !path.exists() {
+        return Ok(Check::Ok("no stale daemon state file".into()));
+    }
+
+    let age = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|raw| serde_json::from_str::<serde_json::Value>(&raw).ok())
+        .and_then(|snapshot| {
+            snapshot
+                .get("updated_at")
+                .and_then(serde_json::Value::as_str)
+                .and_then(parse_rfc3339)
+        })
+        .map(|ts| Utc::now().signed_duration_since(ts).num_seconds());
+    if age.is_some_and(|age| age <= DAEMON_STALE_SECONDS) {
+        return Ok(Check::Ok("daemon state file is current".into()));
+    }
+
+    let last = age.map_or_else(|| "unknown".to_string(), |age| format!("{age}s ago"));
+    if !fix {
+        return Ok(Check::Fixable(format!(
+            "stale daemon state file {} (last heartbeat {last})",
+            path.display()
+        )));
+    }
+    std::fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))?;
+    Ok(Check::Fixed(format!(
+        "removed stale daemon state file {} (last heartbeat {last})",
+        path.display()
+    )))
+}
+
+fn check_memory_schema(db_path: &Path, fix: bool) -> Result<Check> {
+    if !db_path.exists() {
+        return Ok(Check::Ok("memory database not created yet".into()));
+    }
+    let pending = SqliteMemory::pending_migrations(db_path)?;
+    if pending.is_empty() {
+        return Ok(Check::Ok("memory database schema up to date".into()));
+    }
+    if !fix {
+        return Ok(Check::Fixable(format!(
+            "memory database needs migration: {}",
+            pending.join(", ")
+        )));
+    }
+    let applied = SqliteMemory::migrate(db_path)?;
+    Ok(Check::Fixed(format!(
+        "migrated memory database: {}",
+        applied.join(", ")
+    )))
+}
+
+/// Informational only: running as a service is opt-in, so `--fix` never
+/// installs one.
+fn check_service_unit(config: &Config) -> Result<Check> {
+    let Some(unit) = crate::service::unit_file(config)? else {
+        return Ok(Check::Ok(
+            "service management not supported on this platform".into(),
+        ));
+    };
+    if unit.exists() {
+        return Ok(Check::Ok(format!(
+            "service unit installed ({})",
+            unit.display()
+        )));
+    }
+    Ok(Check::Info(
+        "no service unit installed (optional: `tinyclaw service install` runs the daemon in the background)"
+            .into(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_dir_creates_missing_directory_once() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().join("sessions");
+
+        assert!(matches!(
+            check_dir(&dir, "sessions", false).unwrap(),
+            Check::Fixable(_)
+        ));
+        assert!(!dir.exists());
+
+        assert!(matches!(
+            check_dir(&dir, "sessions", true).unwrap(),
+            Check::Fixed(_)
+        ));
+        assert!(dir.is_dir());
+        assert!(matches!(
+            check_dir(&dir, "sessions", true).unwrap(),
+            Check::Ok(_)
+        ));
+    }
+
+    #[test]
+    fn check_dir_leaves_conflicting_file_alone() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("exports");
+        std::fs::write(&path, "not a dir").unwrap();
+
+        assert!(matches!(
+            check_dir(&path, "exports", true).unwrap(),
+            Check::Manual(_)
+        ));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "not a dir");
+    }
+
+    #[test]
+    fn check_state_file_removes_only_stale_state() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("daemon_state.json");
+
+        let fresh = serde_json::json!({ "updated_at": Utc::now().to_rfc3339() });
+        std::fs::write(&path, fresh.to_string()).unwrap();
+        assert!(matches!(
+            check_state_file(&path, true).unwrap(),
+            Check::Ok(_)
+        ));
+        assert!(path.exists());
+
+        let stale = serde_json::json!({
+            "updated_at": (Utc::now() - chrono::Duration::hours(1)).to_rfc3339()
+        });
+        std::fs::write(&path, stale.to_string()).unwrap();
+        assert!(matches!(
+            check_state_file(&path, false).unwrap(),
+            Check::Fixable(_)
+        ));
+        assert!(path.exists());
+        assert!(matches!(
+            check_state_file(&path, true).unwrap(),
+            Check::Fixed(_)
+        ));
+        assert!(!path.exists());
+        assert!(matches!(
+            check_state_file(&path, true).unwrap(),
+            Check::Ok(_)
+        ));
+    }
+
+    #[test]
+    fn check_memory_schema_migrates_legacy_database() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db_path = tmp.path().join("brain.db");
+        rusqlite::Connection::open(&db_path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE memories (
+                    id TEXT PRIMARY KEY, key TEXT NOT NULL UNIQUE, content TEXT NOT NULL,
+                    category TEXT NOT NULL DEFAULT 'core',
+                    created_at TEXT NOT NULL, updated_at TEXT NOT NULL
+                );",
+            )
+            .unwrap();
+
+        assert!(matches!(
+            check_memory_schema(&db_path, false).unwrap(),
+            Check::Fixable(_)
+        ));
+        let Check::Fixed(msg) = check_memory_schema(&db_path, true).unwrap() else {
+            panic!("expected a migration");
+        };
+        assert!(msg.contains("embedding"), "{msg}");
+        assert!(matches!(
+            check_memory_schema(&db_path, true).unwrap(),
+            Check::Ok(_)
+        ));
+    }
+}
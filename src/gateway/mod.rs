@@ -10,22 +10,25 @@
 use crate::channels::{Channel, WhatsAppChannel};
 use crate::config::Config;
 use crate::memory::{self, Memory, MemoryCategory};
-use crate::providers::{self, Provider};
+use crate::providers::{self, ChatMessage, Provider};
 use crate::security::pairing::{constant_time_eq, is_public_bind, PairingGuard};
 use crate::util::truncate_with_ellipsis;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use axum::{
-    body::Bytes,
+    body::{Body, Bytes},
     extract::{Query, State},
     http::{header, HeaderMap, StatusCode},
-    response::{IntoResponse, Json},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 use tower_http::limit::RequestBodyLimitLayer;
 use tower_http::timeout::TimeoutLayer;
 
@@ -33,8 +36,12 @@ use tower_http::timeout::TimeoutLayer;
 pub const MAX_BODY_SIZE: usize = 65_536;
 /// Request timeout (30s) — prevents slow-loris attacks
 pub const REQUEST_TIMEOUT_SECS: u64 = 30;
+/// `Accept` value that selects the streamed JSON-lines webhook reply.
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
 /// Sliding window used by gateway rate limiting.
 pub const RATE_LIMIT_WINDOW_SECS: u64 = 60;
+/// Longest pause between provider warmup retries while not yet ready.
+const WARMUP_MAX_BACKOFF_SECS: u64 = 30;
 
 #[derive(Debug)]
 struct SlidingWindowRateLimiter {
@@ -146,6 +153,55 @@ fn client_key_from_headers(headers: &HeaderMap) -> String {
     "unknown".into()
 }
 
+/// Readiness flag behind `GET /readyz`.
+///
+/// Config is parsed and validated before the listener starts serving, so the
+/// only thing left to wait for is a successful provider warmup.
+#[derive(Debug, Default)]
+pub struct Readiness {
+    provider_warm: AtomicBool,
+}
+
+impl Readiness {
+    pub fn is_ready(&self) -> bool {
+        self.provider_warm.load(Ordering::Acquire)
+    }
+
+    pub fn mark_ready(&self) {
+        self.provider_warm.store(true, Ordering::Release);
+    }
+}
+
+/// Warm up `provider`, retrying with exponential backoff until it succeeds,
+/// then flip `readiness` so `/readyz` starts returning 200.
+pub async fn warm_up_provider(provider: Arc<dyn Provider>, readiness: Arc<Readiness>) {
+    let mut backoff = Duration::from_secs(1);
+    loop {
+        match provider.warmup().await {
+            Ok(()) => {
+                readiness.mark_ready();
+                crate::health::mark_component_ok("provider");
+                return;
+            }
+            Err(e) => {
+                tracing::warn!("Provider warmup failed, retrying in {backoff:?}: {e}");
+                crate::health::mark_component_error("provider", e.to_string());
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(Duration::from_secs(WARMUP_MAX_BACKOFF_SECS));
+            }
+        }
+    }
+}
+
+/// Liveness and readiness probes. Both are unauthenticated, bypass pairing and
+/// rate limiting, and do no I/O, so orchestrators can poll them freely.
+pub fn probe_routes(readiness: Arc<Readiness>) -> Router {
+    Router::new()
+        .route("/healthz", get(handle_healthz))
+        .route("/readyz", get(handle_readyz))
+        .with_state(readiness)
+}
+
 /// Shared state for all axum handlers
 #[derive(Clone)]
 pub struct AppState {
@@ -163,9 +219,31 @@ pub struct AppState {
     pub whatsapp_app_secret: Option<Arc<str>>,
 }
 
+/// Write the bound address (`host:port`) to `path` so supervisors of a
+/// `--port 0` run can discover the port. Goes through a temp file and rename,
+/// so readers never observe a partial write.
+pub fn write_port_file(path: &Path, addr: SocketAddr) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, format!("{addr}\n"))?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
 /// Run the HTTP gateway using axum with proper HTTP/1.1 compliance.
+///
+/// When `port_file` is set, the bound address is written there before the
+/// server starts accepting requests.
 #[allow(clippy::too_many_lines)]
-pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
+pub async fn run_gateway(
+    host: &str,
+    port: u16,
+    port_file: Option<&Path>,
+    config: Config,
+) -> Result<()> {
     // ── Security: refuse public bind without tunnel or explicit opt-in ──
     if is_public_bind(host) && config.tunnel.provider == "none" && !config.gateway.allow_public_bind
     {
@@ -178,24 +256,33 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
 
     let addr: SocketAddr = format!("{host}:{port}").parse()?;
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    let actual_port = listener.local_addr()?.port();
+    let local_addr = listener.local_addr()?;
+    let actual_port = local_addr.port();
     let display_addr = format!("{host}:{actual_port}");
+    if let Some(path) = port_file {
+        write_port_file(path, local_addr)
+            .with_context(|| format!("Failed to write port file {}", path.display()))?;
+    }
 
-    let provider: Arc<dyn Provider> = Arc::from(providers::create_resilient_provider(
+    let mut provider = providers::create_resilient_provider(
         config.default_provider.as_deref().unwrap_or("openrouter"),
         config.api_key.as_deref(),
+        &config.providers,
         &config.reliability,
-    )?);
-    let model = config
-        .default_model
-        .clone()
-        .unwrap_or_else(|| "anthropic/claude-sonnet-4-20250514".into());
+    )?;
+    provider.set_seed(config.providers.seed);
+    provider.set_reasoning_effort(config.providers.reasoning_effort);
+    provider.set_stop_sequences(&config.providers.stop_sequences)?;
+    let provider: Arc<dyn Provider> = Arc::from(provider);
+    let model =
+        config.default_model_for(config.default_provider.as_deref().unwrap_or("openrouter"));
     let temperature = config.default_temperature;
     let mem: Arc<dyn Memory> = Arc::from(memory::create_memory(
         &config.memory,
         &config.workspace_dir,
         config.api_key.as_deref(),
     )?);
+    memory::gc_expired(mem.as_ref()).await;
 
     // Extract webhook secret for authentication
     let webhook_secret: Option<Arc<str>> = config
@@ -272,11 +359,14 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     }
     println!("  POST /pair      — pair a new client (X-Pairing-Code header)");
     println!("  POST /webhook   — {{\"message\": \"your prompt\"}}");
+    println!("                    (Accept: {NDJSON_CONTENT_TYPE} streams JSON lines)");
     if whatsapp_channel.is_some() {
         println!("  GET  /whatsapp  — Meta webhook verification");
         println!("  POST /whatsapp  — WhatsApp message webhook");
     }
     println!("  GET  /health    — health check");
+    println!("  GET  /healthz   — liveness probe");
+    println!("  GET  /readyz    — readiness probe (503 until provider warmup succeeds)");
     if let Some(code) = pairing.pairing_code() {
         println!();
         println!("  🔐 PAIRING REQUIRED — use this one-time code:");
@@ -296,6 +386,9 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
 
     crate::health::mark_component_ok("gateway");
 
+    let readiness = Arc::new(Readiness::default());
+    tokio::spawn(warm_up_provider(provider.clone(), readiness.clone()));
+
     // Build shared state
     let state = AppState {
         provider,
@@ -319,6 +412,7 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         .route("/whatsapp", get(handle_whatsapp_verify))
         .route("/whatsapp", post(handle_whatsapp_message))
         .with_state(state)
+        .merge(probe_routes(readiness))
         .layer(RequestBodyLimitLayer::new(MAX_BODY_SIZE))
         .layer(TimeoutLayer::with_status_code(
             StatusCode::REQUEST_TIMEOUT,
@@ -345,6 +439,25 @@ async fn handle_health(State(state): State<AppState>) -> impl IntoResponse {
     Json(body)
 }
 
+/// GET /healthz — liveness: 200 whenever the process can answer HTTP at all.
+/// A failing liveness probe means "restart me", so it never checks dependencies.
+async fn handle_healthz() -> impl IntoResponse {
+    Json(serde_json::json!({"status": "ok"}))
+}
+
+/// GET /readyz — readiness: 503 until the provider has warmed up, then 200.
+/// A failing readiness probe means "don't route traffic here yet".
+async fn handle_readyz(State(readiness): State<Arc<Readiness>>) -> impl IntoResponse {
+    if readiness.is_ready() {
+        (StatusCode::OK, Json(serde_json::json!({"status": "ready"})))
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"status": "warming_up"})),
+        )
+    }
+}
+
 /// POST /pair — exchange one-time code for bearer token
 async fn handle_pair(State(state): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
     let client_key = client_key_from_headers(&headers);
@@ -401,7 +514,7 @@ async fn handle_webhook(
     State(state): State<AppState>,
     headers: HeaderMap,
     body: Result<Json<WebhookBody>, axum::extract::rejection::JsonRejection>,
-) -> impl IntoResponse {
+) -> Response {
     let client_key = client_key_from_headers(&headers);
     if !state.rate_limiter.allow_webhook(&client_key) {
         tracing::warn!("/webhook rate limit exceeded for key: {client_key}");
@@ -409,9 +522,14 @@ async fn handle_webhook(
             "error": "Too many webhook requests. Please retry later.",
             "retry_after": RATE_LIMIT_WINDOW_SECS,
         });
-        return (StatusCode::TOO_MANY_REQUESTS, Json(err));
+        return (StatusCode::TOO_MANY_REQUESTS, Json(err)).into_response();
     }
 
+    let Some(work) = crate::health::work_tracker().begin() else {
+        let err = serde_json::json!({"error": "Shutting down; not accepting new requests"});
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(err)).into_response();
+    };
+
     // ── Bearer token auth (pairing) ──
     if state.pairing.require_pairing() {
         let auth = headers
@@ -424,7 +542,7 @@ async fn handle_webhook(
             let err = serde_json::json!({
                 "error": "Unauthorized — pair first via POST /pair, then send Authorization: Bearer <token>"
             });
-            return (StatusCode::UNAUTHORIZED, Json(err));
+            return (StatusCode::UNAUTHORIZED, Json(err)).into_response();
         }
     }
 
@@ -438,7 +556,7 @@ async fn handle_webhook(
             _ => {
                 tracing::warn!("Webhook: rejected request — invalid or missing X-Webhook-Secret");
                 let err = serde_json::json!({"error": "Unauthorized — invalid or missing X-Webhook-Secret header"});
-                return (StatusCode::UNAUTHORIZED, Json(err));
+                return (StatusCode::UNAUTHORIZED, Json(err)).into_response();
             }
         }
     }
@@ -450,7 +568,7 @@ async fn handle_webhook(
             let err = serde_json::json!({
                 "error": format!("Invalid JSON: {e}. Expected: {{\"message\": \"...\"}}")
             });
-            return (StatusCode::BAD_REQUEST, Json(err));
+            return (StatusCode::BAD_REQUEST, Json(err)).into_response();
         }
     };
 
@@ -468,7 +586,7 @@ async fn handle_webhook(
                 "idempotent": true,
                 "message": "Request already processed for this idempotency key"
             });
-            return (StatusCode::OK, Json(body));
+            return (StatusCode::OK, Json(body)).into_response();
         }
     }
 
@@ -481,6 +599,10 @@ async fn handle_webhook(
             .await;
     }
 
+    if wants_ndjson(&headers) {
+        return ndjson_reply(state, message.clone(), work);
+    }
+
     match state
         .provider
         .chat(message, &state.model, state.temperature)
@@ -488,7 +610,7 @@ async fn handle_webhook(
     {
         Ok(response) => {
             let body = serde_json::json!({"response": response, "model": state.model});
-            (StatusCode::OK, Json(body))
+            (StatusCode::OK, Json(body)).into_response()
         }
         Err(e) => {
             tracing::error!(
@@ -496,11 +618,95 @@ async fn handle_webhook(
                 providers::sanitize_api_error(&e.to_string())
             );
             let err = serde_json::json!({"error": "LLM request failed"});
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(err))
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(err)).into_response()
         }
     }
 }
 
+/// Whether the client asked for `application/x-ndjson` in `Accept`.
+fn wants_ndjson(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|media| {
+            media
+                .split(';')
+                .next()
+                .is_some_and(|t| t.trim().eq_ignore_ascii_case(NDJSON_CONTENT_TYPE))
+        })
+}
+
+/// One event per line, easy to consume with `curl -N | jq`:
+/// `{"type":"token","text":...}` for each chunk as it arrives, then
+/// `{"type":"done","response":...,"model":...}` or `{"type":"error",...}`.
+///
+/// Providers without real streaming send their whole reply as one token.
+/// The format reserves `{"type":"tool",...}` for tool calls, but the
+/// webhook runs no tools, so it never sends one.
+fn ndjson_reply(
+    state: AppState,
+    message: String,
+    work: crate::health::WorkGuard<'static>,
+) -> Response {
+    let (line_tx, line_rx) = mpsc::channel::<String>(64);
+
+    tokio::spawn(async move {
+        // Keep the request counted as in flight until the stream ends
+        let _work = work;
+        let (token_tx, mut token_rx) = mpsc::channel::<String>(64);
+        let tokens = {
+            let line_tx = line_tx.clone();
+            tokio::spawn(async move {
+                while let Some(text) = token_rx.recv().await {
+                    let event = serde_json::json!({"type": "token", "text": text});
+                    if line_tx.send(ndjson_line(&event)).await.is_err() {
+                        break;
+                    }
+                }
+            })
+        };
+
+        let history = [ChatMessage::user(message)];
+        let result = state
+            .provider
+            .chat_with_history_stream(&history, &state.model, state.temperature, token_tx)
+            .await;
+        // Every token goes out before the final event
+        let _ = tokens.await;
+
+        let last = match result {
+            Ok(response) => {
+                serde_json::json!({"type": "done", "response": response, "model": state.model})
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Webhook provider error: {}",
+                    providers::sanitize_api_error(&e.to_string())
+                );
+                serde_json::json!({"type": "error", "error": "LLM request failed"})
+            }
+        };
+        let _ = line_tx.send(ndjson_line(&last)).await;
+    });
+
+    let lines = futures_util::stream::unfold(line_rx, |mut rx| async move {
+        rx.recv()
+            .await
+            .map(|line| (Ok::<_, std::convert::Infallible>(line), rx))
+    });
+    (
+        [(header::CONTENT_TYPE, NDJSON_CONTENT_TYPE)],
+        Body::from_stream(lines),
+    )
+        .into_response()
+}
+
+fn ndjson_line(event: &serde_json::Value) -> String {
+    format!("{event}\n")
+}
+
 /// `WhatsApp` verification query params
 #[derive(serde::Deserialize)]
 pub struct WhatsAppVerifyQuery {
@@ -578,6 +784,13 @@ async fn handle_whatsapp_message(
         );
     };
 
+    let Some(_work) = crate::health::work_tracker().begin() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"error": "Shutting down; not accepting new requests"})),
+        );
+    };
+
     // ── Security: Verify X-Hub-Signature-256 if app_secret is configured ──
     if let Some(ref app_secret) = state.whatsapp_app_secret {
         let signature = headers
@@ -714,6 +927,51 @@ mod tests {
         assert_clone::<AppState>();
     }
 
+    #[test]
+    fn port_file_records_bound_address() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("run").join("gateway.port");
+
+        write_port_file(&path, "127.0.0.1:43127".parse().unwrap()).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "127.0.0.1:43127\n");
+
+        // A restart on a new ephemeral port replaces the old address
+        write_port_file(&path, "[::1]:50001".parse().unwrap()).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[::1]:50001\n");
+        assert!(!tmp.path().join("run").join("gateway.port.tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn run_gateway_writes_port_file_for_random_port() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("gateway.port");
+        let config = Config {
+            workspace_dir: tmp.path().to_path_buf(),
+            config_path: tmp.path().join("config.toml"),
+            ..Config::default()
+        };
+
+        let port_file = path.clone();
+        let server = tokio::spawn(async move {
+            let port_file = Some(port_file.as_path());
+            run_gateway("127.0.0.1", 0, port_file, config).await
+        });
+
+        let mut contents = String::new();
+        for _ in 0..100 {
+            if let Ok(text) = std::fs::read_to_string(&path) {
+                contents = text;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        server.abort();
+
+        let addr: SocketAddr = contents.trim().parse().expect("port file holds host:port");
+        assert_eq!(addr.ip().to_string(), "127.0.0.1");
+        assert_ne!(addr.port(), 0);
+    }
+
     #[test]
     fn gateway_rate_limiter_blocks_after_limit() {
         let limiter = GatewayRateLimiter::new(2, 2);
@@ -783,6 +1041,10 @@ mod tests {
 
     #[async_trait]
     impl Provider for MockProvider {
+        fn name(&self) -> &str {
+            "mock"
+        }
+
         async fn chat_with_system(
             &self,
             _system_prompt: Option<&str>,
@@ -841,6 +1103,177 @@ mod tests {
         assert_eq!(provider_impl.calls.load(Ordering::SeqCst), 1);
     }
 
+    #[tokio::test]
+    async fn whatsapp_verify_handshake_echoes_challenge_for_matching_token() {
+        let state = AppState {
+            provider: Arc::new(MockProvider::default()),
+            model: "test-model".into(),
+            temperature: 0.0,
+            mem: Arc::new(MockMemory),
+            auto_save: false,
+            webhook_secret: None,
+            pairing: Arc::new(PairingGuard::new(false, &[])),
+            rate_limiter: Arc::new(GatewayRateLimiter::new(100, 100)),
+            idempotency_store: Arc::new(IdempotencyStore::new(Duration::from_secs(300))),
+            whatsapp: Some(Arc::new(WhatsAppChannel::new(
+                "access".into(),
+                "123456".into(),
+                "my-verify-token".into(),
+                vec!["*".into()],
+            ))),
+            whatsapp_app_secret: None,
+        };
+        let verify = |query: &str| {
+            let uri: axum::http::Uri = format!("/whatsapp?{query}").parse().unwrap();
+            let params = Query::try_from_uri(&uri).unwrap();
+            let state = state.clone();
+            async move {
+                let response = handle_whatsapp_verify(State(state), params)
+                    .await
+                    .into_response();
+                let status = response.status();
+                let body = response.into_body().collect().await.unwrap().to_bytes();
+                (status, String::from_utf8(body.to_vec()).unwrap())
+            }
+        };
+
+        // Meta sends these three parameters and expects the challenge back as-is
+        assert_eq!(
+            verify("hub.mode=subscribe&hub.verify_token=my-verify-token&hub.challenge=1158201444")
+                .await,
+            (StatusCode::OK, "1158201444".into())
+        );
+        assert_eq!(
+            verify("hub.mode=subscribe&hub.verify_token=wrong&hub.challenge=1158201444")
+                .await
+                .0,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            verify("hub.mode=unsubscribe&hub.verify_token=my-verify-token&hub.challenge=1")
+                .await
+                .0,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            verify("hub.mode=subscribe&hub.verify_token=my-verify-token")
+                .await
+                .0,
+            StatusCode::BAD_REQUEST
+        );
+
+        let unconfigured = AppState {
+            whatsapp: None,
+            ..state.clone()
+        };
+        let response = handle_whatsapp_verify(
+            State(unconfigured),
+            Query(WhatsAppVerifyQuery {
+                mode: Some("subscribe".into()),
+                verify_token: Some("my-verify-token".into()),
+                challenge: Some("1".into()),
+            }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    /// Streams its reply a word at a time.
+    struct StreamingProvider;
+
+    #[async_trait]
+    impl Provider for StreamingProvider {
+        fn name(&self) -> &str {
+            "streaming"
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("one two three".into())
+        }
+
+        async fn chat_with_history_stream(
+            &self,
+            _messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+            token_tx: mpsc::Sender<String>,
+        ) -> anyhow::Result<String> {
+            for token in ["one", " two", " three"] {
+                token_tx.send(token.into()).await?;
+            }
+            Ok("one two three".into())
+        }
+
+        fn supports_streaming(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn webhook_ndjson_streams_each_token_before_done() {
+        let state = AppState {
+            provider: Arc::new(StreamingProvider),
+            model: "test-model".into(),
+            temperature: 0.0,
+            mem: Arc::new(MockMemory),
+            auto_save: false,
+            webhook_secret: None,
+            pairing: Arc::new(PairingGuard::new(false, &[])),
+            rate_limiter: Arc::new(GatewayRateLimiter::new(100, 100)),
+            idempotency_store: Arc::new(IdempotencyStore::new(Duration::from_secs(300))),
+            whatsapp: None,
+            whatsapp_app_secret: None,
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::ACCEPT,
+            HeaderValue::from_static("text/plain, application/x-ndjson;q=0.9"),
+        );
+
+        let body = Ok(Json(WebhookBody {
+            message: "count".into(),
+        }));
+        let response = handle_webhook(State(state), headers, body).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let payload = response.into_body().collect().await.unwrap().to_bytes();
+        let events: Vec<serde_json::Value> = String::from_utf8(payload.to_vec())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            events,
+            [
+                serde_json::json!({"type": "token", "text": "one"}),
+                serde_json::json!({"type": "token", "text": " two"}),
+                serde_json::json!({"type": "token", "text": " three"}),
+                serde_json::json!({"type": "done", "response": "one two three", "model": "test-model"}),
+            ]
+        );
+    }
+
+    #[test]
+    fn ndjson_is_selected_only_by_accept() {
+        let accept = |value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::ACCEPT, HeaderValue::from_static(value));
+            wants_ndjson(&headers)
+        };
+        assert!(accept("application/x-ndjson"));
+        assert!(accept("application/json, Application/X-NDJSON; q=0.5"));
+        assert!(!accept("application/json"));
+        assert!(!accept("*/*"));
+        assert!(!wants_ndjson(&HeaderMap::new()));
+    }
+
     // ══════════════════════════════════════════════════════════
     // WhatsApp Signature Verification Tests (CWE-345 Prevention)
     // ══════════════════════════════════════════════════════════
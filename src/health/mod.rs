@@ -1,6 +1,7 @@
 use chrono::Utc;
 use serde::Serialize;
 use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Mutex, OnceLock};
 use std::time::Instant;
 
@@ -104,3 +105,61 @@ pub fn snapshot_json() -> serde_json::Value {
         })
     })
 }
+
+/// Tracks in-flight units of work (agent turns, channel replies, scheduled
+/// jobs) so the daemon can stop taking new work and drain on shutdown.
+#[derive(Debug, Default)]
+pub struct WorkTracker {
+    shutting_down: AtomicBool,
+    in_flight: AtomicUsize,
+}
+
+/// Held for the duration of one unit of work; releases its slot on drop.
+#[derive(Debug)]
+pub struct WorkGuard<'a> {
+    tracker: &'a WorkTracker,
+}
+
+impl Drop for WorkGuard<'_> {
+    fn drop(&mut self) {
+        self.tracker.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl WorkTracker {
+    pub const fn new() -> Self {
+        Self {
+            shutting_down: AtomicBool::new(false),
+            in_flight: AtomicUsize::new(0),
+        }
+    }
+
+    /// Start a unit of work. Returns `None` once shutdown has been requested.
+    pub fn begin(&self) -> Option<WorkGuard<'_>> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        if self.shutting_down.load(Ordering::SeqCst) {
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(WorkGuard { tracker: self })
+    }
+
+    pub fn request_shutdown(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+}
+
+static WORK: WorkTracker = WorkTracker::new();
+
+/// Process-wide work tracker shared by the daemon components.
+pub fn work_tracker() -> &'static WorkTracker {
+    &WORK
+}
@@ -53,6 +53,7 @@ impl HeartbeatEngine {
                     self.observer.record_event(&ObserverEvent::Error {
                         component: "heartbeat".into(),
                         message: e.to_string(),
+                        turn: None,
                     });
                 }
             }
@@ -472,6 +472,26 @@ pub fn is_aieos_configured(config: &IdentityConfig) -> bool {
     config.format == "aieos" && (config.aieos_path.is_some() || config.aieos_inline.is_some())
 }
 
+/// Resolve the assistant's display name for interactive UIs.
+///
+/// Prefers an explicit `identity.name`, then the AIEOS nickname or first
+/// name. Returns `None` when nothing is configured (or AIEOS fails to load).
+pub fn display_name(config: &IdentityConfig, workspace_dir: &Path) -> Option<String> {
+    if let Some(name) = config.name.as_deref().map(str::trim) {
+        if !name.is_empty() {
+            return Some(name.to_string());
+        }
+    }
+
+    let identity = load_aieos_identity(config, workspace_dir).ok().flatten()?;
+    let names = identity.identity?.names?;
+    names
+        .nickname
+        .or(names.first)
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -730,6 +750,8 @@ mod tests {
             format: "aieos".into(),
             aieos_path: Some("identity.json".into()),
             aieos_inline: None,
+            prompt_suffix: None,
+            name: None,
         };
         assert!(is_aieos_configured(&config));
     }
@@ -740,6 +762,8 @@ mod tests {
             format: "aieos".into(),
             aieos_path: None,
             aieos_inline: Some("{\"identity\":{}}".into()),
+            prompt_suffix: None,
+            name: None,
         };
         assert!(is_aieos_configured(&config));
     }
@@ -750,6 +774,8 @@ mod tests {
             format: "openclaw".into(),
             aieos_path: Some("identity.json".into()),
             aieos_inline: None,
+            prompt_suffix: None,
+            name: None,
         };
         assert!(!is_aieos_configured(&config));
     }
@@ -760,6 +786,8 @@ mod tests {
             format: "aieos".into(),
             aieos_path: None,
             aieos_inline: None,
+            prompt_suffix: None,
+            name: None,
         };
         assert!(!is_aieos_configured(&config));
     }
@@ -780,4 +808,53 @@ mod tests {
         assert!(identity.identity.is_none());
         assert!(identity.psychology.is_none());
     }
+
+    #[test]
+    fn display_name_prefers_configured_name() {
+        let config = IdentityConfig {
+            format: "aieos".into(),
+            aieos_inline: Some(r#"{"identity":{"names":{"first":"Nova"}}}"#.into()),
+            name: Some("  Claw  ".into()),
+            ..IdentityConfig::default()
+        };
+        assert_eq!(
+            display_name(&config, &test_workspace_dir()).as_deref(),
+            Some("Claw")
+        );
+    }
+
+    #[test]
+    fn display_name_falls_back_to_aieos_names() {
+        let config = IdentityConfig {
+            format: "aieos".into(),
+            aieos_inline: Some(
+                r#"{"identity":{"names":{"first":"Nova","nickname":"Nov"}}}"#.into(),
+            ),
+            ..IdentityConfig::default()
+        };
+        assert_eq!(
+            display_name(&config, &test_workspace_dir()).as_deref(),
+            Some("Nov")
+        );
+
+        let config = IdentityConfig {
+            format: "aieos".into(),
+            aieos_inline: Some(r#"{"identity":{"names":{"first":"Nova"}}}"#.into()),
+            ..IdentityConfig::default()
+        };
+        assert_eq!(
+            display_name(&config, &test_workspace_dir()).as_deref(),
+            Some("Nova")
+        );
+    }
+
+    #[test]
+    fn display_name_none_when_unconfigured() {
+        assert!(display_name(&IdentityConfig::default(), &test_workspace_dir()).is_none());
+        let blank = IdentityConfig {
+            name: Some("   ".into()),
+            ..IdentityConfig::default()
+        };
+        assert!(display_name(&blank, &test_workspace_dir()).is_none());
+    }
 }
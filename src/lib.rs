@@ -16,6 +16,7 @@ use serde::{Deserialize, Serialize};
 
 // ── Always compiled (tiny tier) ────────────────────────────────
 pub mod agent;
+pub mod bench;
 pub mod channels;
 pub mod config;
 pub mod health;
@@ -23,11 +24,14 @@ pub mod identity;
 pub mod memory;
 pub mod observability;
 pub mod onboard;
+pub mod persona;
 pub mod providers;
+pub mod replay;
 pub mod runtime;
 pub mod security;
 pub mod session;
 pub mod skills;
+pub mod status;
 pub mod tools;
 pub mod util;
 
@@ -114,6 +118,29 @@ pub enum SkillCommands {
         /// Skill name to remove
         name: String,
     },
+    /// Check installed skills and explain why any would not load
+    Doctor,
+}
+
+/// Memory backup subcommands
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MemoryCommands {
+    /// Export all memory entries to a portable JSON file
+    Export {
+        /// Destination file
+        file: std::path::PathBuf,
+    },
+    /// Import entries from a JSON export into the current backend
+    Import {
+        /// Export file to load
+        file: std::path::PathBuf,
+        /// Keep existing entries and add the imported ones (default)
+        #[arg(long, conflicts_with = "replace")]
+        merge: bool,
+        /// Remove existing entries first (they are backed up to memory/backups/)
+        #[arg(long)]
+        replace: bool,
+    },
 }
 
 /// Migration subcommands
@@ -150,6 +177,14 @@ pub enum CronCommands {
         /// Task ID
         id: String,
     },
+    /// Show a task's recent runs: time, status and output
+    Logs {
+        /// Task ID
+        id: String,
+        /// How many runs to show, newest first
+        #[arg(short = 'n', long, default_value = "10")]
+        limit: usize,
+    },
 }
 
 /// Integration subcommands
@@ -10,11 +10,11 @@
 
 use anyhow::{bail, Result};
 use clap::{Parser, Subcommand};
-use tracing::{info, Level};
-use tracing_subscriber::FmtSubscriber;
+use tracing::info;
 
 // ── Always compiled (tiny tier) ────────────────────────────────
 mod agent;
+mod bench;
 mod channels;
 mod config;
 mod health;
@@ -22,11 +22,14 @@ mod identity;
 mod memory;
 mod observability;
 mod onboard;
+mod persona;
 mod providers;
+mod replay;
 mod runtime;
 mod security;
 mod session;
 mod skills;
+mod status;
 mod tools;
 mod util;
 
@@ -64,6 +67,11 @@ use config::Config;
 #[command(version = "0.1.0")]
 #[command(about = "Ultra-efficient AI assistant. Fork of ZeroClaw.", long_about = None)]
 struct Cli {
+    /// Keep config.toml and the workspace (sessions, memory, exports) in
+    /// DIR instead of ~/.tinyclaw (or set `TINYCLAW_WORKSPACE`)
+    #[arg(long, global = true, value_name = "DIR")]
+    workspace: Option<std::path::PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -95,6 +103,11 @@ enum Commands {
         #[arg(long)]
         channels_only: bool,
 
+        /// With --channels-only: save one channel from JSON without prompts,
+        /// e.g. `--add telegram '{"bot_token": "...", "allowed_users": ["alice"]}'`
+        #[arg(long, num_args = 2, value_names = ["TYPE", "JSON"], requires = "channels_only")]
+        add: Option<Vec<String>>,
+
         /// API key (used in quick mode, ignored with --interactive)
         #[arg(long)]
         api_key: Option<String>,
@@ -119,17 +132,81 @@ enum Commands {
         #[arg(long)]
         model: Option<String>,
 
-        /// Temperature (0.0 - 2.0)
-        #[arg(short, long, default_value = "0.7")]
-        temperature: f64,
+        /// Temperature (0.0 - 2.0, default 0.7 or the model route's)
+        #[arg(short, long)]
+        temperature: Option<f64>,
+
+        /// Use this file's contents as the base system prompt
+        #[arg(long)]
+        system_file: Option<std::path::PathBuf>,
+
+        /// Prepend this text file's contents to the first message, repeatable
+        /// (relative to the workspace; workspace files only unless
+        /// `autonomy.workspace_only = false`; images are not supported)
+        #[arg(long = "attach-file", value_name = "PATH")]
+        attach_files: Vec<std::path::PathBuf>,
+
+        /// Show model reasoning blocks (toggle later with /thinking on|off)
+        #[arg(long)]
+        thinking: bool,
+
+        /// Render messages as plain text without styling (toggle with /plain)
+        #[arg(long)]
+        plain: bool,
+
+        /// Color theme: dark, light, high-contrast or monochrome
+        /// (overrides `tui.theme`)
+        #[arg(long, value_name = "NAME", value_parser = tui::theme::parse_preset)]
+        theme: Option<String>,
+
+        /// Bypass the response cache even if `reliability.cache` is on
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Log what the session shows (messages, streamed tokens, tool calls)
+        /// with timings to this file, for `--replay`
+        #[arg(long, value_name = "FILE")]
+        record: Option<std::path::PathBuf>,
+
+        /// Play back a `--record` file at its original pace without calling
+        /// any provider
+        #[arg(long, value_name = "FILE", conflicts_with = "record")]
+        replay: Option<std::path::PathBuf>,
     },
 
     /// Start the AI agent loop
     Agent {
         /// Single message mode (don't enter interactive mode)
-        #[arg(short, long)]
+        #[arg(short, long, conflicts_with = "template")]
         message: Option<String>,
 
+        /// Read the message from stdin until EOF and stream only the reply
+        #[arg(long, conflicts_with_all = ["message", "template"])]
+        pipe: bool,
+
+        /// Answer each prompt in this file as its own conversation: one per
+        /// line (`#` comments skipped), or a JSON array if it ends in `.json`
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["message", "template", "pipe", "resume"])]
+        batch: Option<std::path::PathBuf>,
+
+        /// With --batch, report a failed prompt and go on to the next one
+        /// instead of stopping with an error
+        #[arg(long, requires = "batch")]
+        continue_on_error: bool,
+
+        /// Single-message output: text or json (`{"response", "session", "model"}`;
+        /// one line per prompt with --batch)
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        output: agent::OutputFormat,
+
+        /// Run a prompt template as the single message (`{{key}}` placeholders)
+        #[arg(long, value_name = "FILE")]
+        template: Option<std::path::PathBuf>,
+
+        /// Template variable, repeatable (`--var ticket=ENG-42`)
+        #[arg(long = "var", value_name = "KEY=VALUE", requires = "template", value_parser = agent::template::parse_var)]
+        vars: Vec<(String, String)>,
+
         /// Provider to use (openrouter, anthropic, openai)
         #[arg(short, long)]
         provider: Option<String>,
@@ -138,9 +215,105 @@ enum Commands {
         #[arg(long)]
         model: Option<String>,
 
+        /// Temperature (0.0 - 2.0; default: the persona's, then the model
+        /// route's, then 0.7)
+        #[arg(short, long)]
+        temperature: Option<f64>,
+
+        /// Use this file's contents as the base system prompt
+        #[arg(long)]
+        system_file: Option<std::path::PathBuf>,
+
+        /// Prepend this text file's contents to the first message, repeatable
+        /// (relative to the workspace; workspace files only unless
+        /// `autonomy.workspace_only = false`; images are not supported)
+        #[arg(long = "attach-file", value_name = "PATH")]
+        attach_files: Vec<std::path::PathBuf>,
+
+        /// Apply a persona pack from `workspace/personas/<NAME>.toml`
+        #[arg(long, value_name = "NAME")]
+        persona: Option<String>,
+
+        /// Offer the model only these tools, comma-separated, e.g.
+        /// `file_read,search_files` (overrides `autonomy.tools_only`)
+        #[arg(long, value_name = "NAMES", value_delimiter = ',')]
+        tools_only: Vec<String>,
+
+        /// Sampling seed for reproducible outputs (overrides providers.seed)
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Reasoning effort: low, medium or high (overrides
+        /// `providers.reasoning_effort`; ignored by providers without one)
+        #[arg(long, value_name = "LEVEL")]
+        reasoning: Option<providers::ReasoningEffort>,
+
+        /// Stop sequence, repeatable (overrides `providers.stop_sequences`)
+        #[arg(long = "stop", value_name = "SEQ")]
+        stop: Vec<String>,
+
+        /// Stop after N user/assistant exchanges (default: unlimited)
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        max_turns: Option<u32>,
+
+        /// Continue a saved session; each turn is saved back to it
+        #[arg(long, value_name = "ID")]
+        resume: Option<String>,
+
+        /// Bypass the response cache even if `reliability.cache` is on
+        #[arg(long)]
+        no_cache: bool,
+    },
+
+    /// List the models a provider offers (ids, context length, pricing)
+    Models {
+        /// Provider to query (defaults to the configured provider)
+        #[arg(short, long)]
+        provider: Option<String>,
+    },
+
+    /// Replay a saved session's user turns and diff the new answers
+    Replay {
+        /// Session ID (see /sessions in the TUI)
+        session_id: String,
+
+        /// Provider to use (defaults to the configured provider)
+        #[arg(short, long)]
+        provider: Option<String>,
+
+        /// Model to use (defaults to the configured model)
+        #[arg(long)]
+        model: Option<String>,
+
+        /// Temperature (0.0 - 2.0; defaults to the model route's, then the
+        /// configured default)
+        #[arg(short, long)]
+        temperature: Option<f64>,
+    },
+
+    /// Run a prompt set against several provider/model combos and compare
+    /// latency, tokens and cost
+    Bench {
+        /// Prompts file: one prompt per line (`#` comments skipped), or a
+        /// JSON array of strings if it ends in `.json`
+        prompts: std::path::PathBuf,
+
+        /// Combo to run as PROVIDER or PROVIDER=MODEL (repeatable; defaults
+        /// to the configured provider and model)
+        #[arg(short, long = "combo", value_parser = bench::parse_combo)]
+        combos: Vec<bench::Combo>,
+
+        /// Prompts in flight at once per combo
+        #[arg(short = 'j', long, default_value = "1")]
+        concurrency: usize,
+
         /// Temperature (0.0 - 2.0)
         #[arg(short, long, default_value = "0.7")]
         temperature: f64,
+
+        /// Also write per-prompt results and responses to this JSON file
+        #[arg(long)]
+        json: Option<std::path::PathBuf>,
     },
 
     /// Start the gateway server (webhooks, websockets)
@@ -153,6 +326,10 @@ enum Commands {
         /// Host to bind to
         #[arg(long, default_value = "127.0.0.1")]
         host: String,
+
+        /// Write the bound address (host:port) to this file once listening
+        #[arg(long)]
+        port_file: Option<std::path::PathBuf>,
     },
 
     /// Start long-running autonomous runtime (gateway + channels + heartbeat + scheduler)
@@ -165,6 +342,14 @@ enum Commands {
         /// Host to bind to
         #[arg(long, default_value = "127.0.0.1")]
         host: String,
+
+        /// Write the gateway's bound address (host:port) to this file once listening
+        #[arg(long)]
+        port_file: Option<std::path::PathBuf>,
+
+        /// Self-test: start the gateway, run one heartbeat and scheduler tick, then exit
+        #[arg(long)]
+        once: bool,
     },
 
     /// Manage OS service lifecycle (launchd/systemd user service)
@@ -176,10 +361,18 @@ enum Commands {
 
     /// Run diagnostics for daemon/scheduler/channel freshness
     #[cfg(feature = "daemon-feature")]
-    Doctor,
+    Doctor {
+        /// Apply safe repairs (missing dirs, stale state, memory schema, service unit)
+        #[arg(long)]
+        fix: bool,
+    },
 
     /// Show system status (full details)
-    Status,
+    Status {
+        /// Output format: text, json or toml
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        format: status::StatusFormat,
+    },
 
     /// Configure and manage scheduled tasks
     #[cfg(feature = "daemon-feature")]
@@ -208,6 +401,12 @@ enum Commands {
         skill_command: SkillCommands,
     },
 
+    /// Back up or transfer memory between backends and machines
+    Memory {
+        #[command(subcommand)]
+        memory_command: MemoryCommands,
+    },
+
     /// Migrate data from other agent runtimes
     #[cfg(feature = "full")]
     Migrate {
@@ -248,6 +447,14 @@ enum CronCommands {
         /// Task ID
         id: String,
     },
+    /// Show a task's recent runs: time, status and output
+    Logs {
+        /// Task ID
+        id: String,
+        /// How many runs to show, newest first
+        #[arg(short = 'n', long, default_value = "10")]
+        limit: usize,
+    },
 }
 
 #[cfg(feature = "channels-feature")]
@@ -287,6 +494,28 @@ enum SkillCommands {
         /// Skill name
         name: String,
     },
+    /// Check installed skills and explain why any would not load
+    Doctor,
+}
+
+#[derive(Subcommand, Debug)]
+enum MemoryCommands {
+    /// Export all memory entries to a portable JSON file
+    Export {
+        /// Destination file
+        file: std::path::PathBuf,
+    },
+    /// Import entries from a JSON export into the current backend
+    Import {
+        /// Export file to load
+        file: std::path::PathBuf,
+        /// Keep existing entries and add the imported ones (default)
+        #[arg(long, conflicts_with = "replace")]
+        merge: bool,
+        /// Remove existing entries first (they are backed up to memory/backups/)
+        #[arg(long)]
+        replace: bool,
+    },
 }
 
 #[cfg(feature = "full")]
@@ -308,18 +537,26 @@ async fn main() -> Result<()> {
     }
 
     let cli = Cli::parse();
+    if let Some(dir) = cli.workspace.clone() {
+        config::set_tinyclaw_dir(dir);
+    }
 
-    // Initialize logging
-    let subscriber = FmtSubscriber::builder()
-        .with_max_level(Level::INFO)
-        .finish();
-
-    tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+    // Initialize logging; keep stdout for the reply when a script reads it
+    let logs_to_stderr = matches!(
+        &cli.command,
+        Commands::Agent { pipe: true, .. }
+            | Commands::Agent {
+                output: agent::OutputFormat::Json,
+                ..
+            }
+    );
+    observability::log_level::init(logs_to_stderr);
 
     // Onboard runs quick setup by default, or the interactive wizard with --interactive
     if let Commands::Onboard {
         interactive,
         channels_only,
+        add,
         api_key,
         provider,
         memory,
@@ -332,6 +569,19 @@ async fn main() -> Result<()> {
             bail!("--channels-only does not accept --api-key, --provider, or --memory");
         }
 
+        if let Some([channel_type, json]) = add.as_deref() {
+            #[cfg(feature = "channels-feature")]
+            return channels::handle_command(
+                ChannelCommands::Add {
+                    channel_type: channel_type.clone(),
+                    config: json.clone(),
+                },
+                &Config::load_or_init()?,
+            );
+            #[cfg(not(feature = "channels-feature"))]
+            bail!("--add {channel_type} {json}: this build has no channel support");
+        }
+
         let config = if *channels_only {
             onboard::run_channels_repair_wizard()?
         } else if *interactive {
@@ -342,6 +592,7 @@ async fn main() -> Result<()> {
         // Auto-start channels if user said yes during wizard
         #[cfg(feature = "channels-feature")]
         if std::env::var("ZEROCLAW_AUTOSTART_CHANNELS").as_deref() == Ok("1") {
+            providers::http::configure(&config.network);
             channels::start_channels(config).await?;
         }
         return Ok(());
@@ -349,6 +600,11 @@ async fn main() -> Result<()> {
 
     // All other commands need config loaded first
     let config = Config::load_or_init()?;
+    providers::http::configure(&config.network);
+    match observability::log_level::parse(&config.observability.log_level) {
+        Ok(level) => observability::log_level::set(level)?,
+        Err(e) => tracing::warn!("{e}; keeping info"),
+    }
 
     match cli.command {
         Commands::Onboard { .. } => unreachable!(),
@@ -358,81 +614,166 @@ async fn main() -> Result<()> {
             provider,
             model,
             temperature,
-        } => tui::run(config, provider, model, temperature).await,
+            system_file,
+            attach_files,
+            thinking,
+            plain,
+            theme,
+            no_cache,
+            record,
+            replay,
+        } => {
+            let mut config = config;
+            if no_cache {
+                config.reliability.cache = false;
+            }
+            if theme.is_some() {
+                config.tui.theme = theme;
+            }
+            if let Some(path) = replay {
+                return tui::replay(&config, &path, thinking, plain);
+            }
+            tui::run(
+                config,
+                provider,
+                model,
+                temperature,
+                system_file,
+                attach_files,
+                thinking,
+                plain,
+                record,
+            )
+            .await
+        }
 
         Commands::Agent {
             message,
+            pipe,
+            batch,
+            continue_on_error,
+            output,
+            template,
+            vars,
+            provider,
+            model,
+            temperature,
+            system_file,
+            attach_files,
+            persona,
+            tools_only,
+            seed,
+            reasoning,
+            stop,
+            max_turns,
+            resume,
+            no_cache,
+        } => {
+            let message = match template {
+                Some(path) => Some(agent::template::load(&path, &vars)?),
+                None if pipe => Some(agent::loop_::read_piped_message(std::io::stdin().lock())?),
+                None => message,
+            };
+            let batch = batch.map(|path| bench::load_prompts(&path)).transpose()?;
+            if output == agent::OutputFormat::Json && message.is_none() && batch.is_none() {
+                bail!("--output json needs a message (--message, --template, --pipe or --batch)");
+            }
+            let mut config = config;
+            if no_cache {
+                config.reliability.cache = false;
+            }
+            if seed.is_some() {
+                config.providers.seed = seed;
+            }
+            if reasoning.is_some() {
+                config.providers.reasoning_effort = reasoning;
+            }
+            if !stop.is_empty() {
+                config.providers.stop_sequences = stop;
+            }
+            if !tools_only.is_empty() {
+                config.autonomy.tools_only = tools_only;
+            }
+            agent::run(
+                config,
+                agent::RunOptions {
+                    message,
+                    provider,
+                    model,
+                    temperature,
+                    system_file,
+                    max_turns,
+                    persona,
+                    resume,
+                    output,
+                    batch,
+                    continue_on_error,
+                    attach_files,
+                },
+            )
+            .await
+        }
+
+        Commands::Models { provider } => {
+            let provider_name = provider
+                .as_deref()
+                .or(config.default_provider.as_deref())
+                .unwrap_or("openrouter");
+            let api_key = config
+                .providers
+                .api_key_for(provider_name)
+                .or(config.api_key.as_deref());
+            providers::print_models(provider_name, api_key).await
+        }
+
+        Commands::Replay {
+            session_id,
             provider,
             model,
             temperature,
-        } => agent::run(config, message, provider, model, temperature).await,
+        } => replay::run(config, &session_id, provider, model, temperature).await,
+
+        Commands::Bench {
+            prompts,
+            combos,
+            concurrency,
+            temperature,
+            json,
+        } => bench::run(config, &prompts, combos, temperature, concurrency, json).await,
 
         #[cfg(feature = "gateway-feature")]
-        Commands::Gateway { port, host } => {
+        Commands::Gateway {
+            port,
+            host,
+            port_file,
+        } => {
             if port == 0 {
                 info!("Starting TinyClaw Gateway on {host} (random port)");
             } else {
                 info!("Starting TinyClaw Gateway on {host}:{port}");
             }
-            gateway::run_gateway(&host, port, config).await
+            gateway::run_gateway(&host, port, port_file.as_deref(), config).await
         }
 
         #[cfg(feature = "daemon-feature")]
-        Commands::Daemon { port, host } => {
+        Commands::Daemon {
+            port,
+            host,
+            port_file,
+            once,
+        } => {
+            if once {
+                return daemon::run_once(config, host, port, port_file).await;
+            }
             if port == 0 {
                 info!("Starting TinyClaw Daemon on {host} (random port)");
             } else {
                 info!("Starting TinyClaw Daemon on {host}:{port}");
             }
-            daemon::run(config, host, port).await
+            daemon::run(config, host, port, port_file).await
         }
 
-        Commands::Status => {
-            println!("TinyClaw Status");
-            println!();
-            println!("Version:     {}", env!("CARGO_PKG_VERSION"));
-            println!("Workspace:   {}", config.workspace_dir.display());
-            println!("Config:      {}", config.config_path.display());
-            println!();
-            println!(
-                "Provider:      {}",
-                config.default_provider.as_deref().unwrap_or("openrouter")
-            );
-            println!(
-                "Model:         {}",
-                config.default_model.as_deref().unwrap_or("(default)")
-            );
-            println!("Observability: {}", config.observability.backend);
-            println!("Autonomy:      {:?}", config.autonomy.level);
-            println!("Runtime:       {}", config.runtime.kind);
-            println!(
-                "Heartbeat:     {}",
-                if config.heartbeat.enabled {
-                    format!("every {}min", config.heartbeat.interval_minutes)
-                } else {
-                    "disabled".into()
-                }
-            );
-            println!(
-                "Memory:        {} (auto-save: {})",
-                config.memory.backend,
-                if config.memory.auto_save { "on" } else { "off" }
-            );
-
-            #[cfg(feature = "tiny")]
-            {
-                let tier = if cfg!(feature = "full") {
-                    "full"
-                } else if cfg!(feature = "standard") {
-                    "standard"
-                } else {
-                    "tiny"
-                };
-                println!("Build tier:    {tier}");
-            }
-
-            Ok(())
-        }
+        Commands::Status { format } => status::run(&config, format),
 
         #[cfg(feature = "daemon-feature")]
         Commands::Cron { cron_command } => cron::handle_command(cron_command, &config),
@@ -441,7 +782,7 @@ async fn main() -> Result<()> {
         Commands::Service { service_command } => service::handle_command(&service_command, &config),
 
         #[cfg(feature = "daemon-feature")]
-        Commands::Doctor => doctor::run(&config),
+        Commands::Doctor { fix } => doctor::run(&config, fix),
 
         #[cfg(feature = "channels-feature")]
         Commands::Channel { channel_command } => match channel_command {
@@ -459,6 +800,10 @@ async fn main() -> Result<()> {
             skills::handle_command(skill_command, &config.workspace_dir)
         }
 
+        Commands::Memory { memory_command } => {
+            memory::handle_command(memory_command, &config).await
+        }
+
         #[cfg(feature = "full")]
         Commands::Migrate { migrate_command } => {
             migration::handle_command(migrate_command, &config).await
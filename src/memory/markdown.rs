@@ -211,6 +211,28 @@ impl Memory for MarkdownMemory {
         Ok(all.len())
     }
 
+    /// `forget` never deletes, so clearing removes `MEMORY.md` and the
+    /// daily logs outright. Only used by `memory import --replace`, which
+    /// backs the entries up first.
+    async fn clear(&self) -> anyhow::Result<usize> {
+        let removed = self.read_all_entries().await?.len();
+        let core_path = self.core_path();
+        if core_path.exists() {
+            fs::remove_file(&core_path).await?;
+        }
+        let mem_dir = self.memory_dir();
+        if mem_dir.exists() {
+            let mut dir = fs::read_dir(&mem_dir).await?;
+            while let Some(entry) = dir.next_entry().await? {
+                let path = entry.path();
+                if path.extension().and_then(|e| e.to_str()) == Some("md") {
+                    fs::remove_file(&path).await?;
+                }
+            }
+        }
+        Ok(removed)
+    }
+
     async fn health_check(&self) -> bool {
         self.workspace_dir.exists()
     }
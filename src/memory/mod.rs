@@ -4,6 +4,8 @@ pub mod hygiene;
 pub mod markdown;
 pub mod sqlite;
 pub mod traits;
+pub mod transfer;
+pub mod ttl;
 pub mod vector;
 
 pub use markdown::MarkdownMemory;
@@ -12,11 +14,17 @@ pub use traits::Memory;
 #[allow(unused_imports)]
 pub use traits::{MemoryCategory, MemoryEntry};
 
-use crate::config::MemoryConfig;
+use crate::config::{Config, MemoryConfig};
+use anyhow::Result;
 use std::path::Path;
 use std::sync::Arc;
 
-/// Factory: create the right memory backend from config
+/// Header of the recalled-memories block prepended to a user message.
+pub(crate) const MEMORY_CONTEXT_PREFIX: &str = "[Memory context]\n";
+use transfer::ImportMode;
+
+/// Factory: create the right memory backend from config, with
+/// `ttl_days` expiry applied when configured
 pub fn create_memory(
     config: &MemoryConfig,
     workspace_dir: &Path,
@@ -27,6 +35,27 @@ pub fn create_memory(
         tracing::warn!("memory hygiene skipped: {e}");
     }
 
+    let mem = create_backend(config, workspace_dir, api_key)?;
+    if config.ttl_days.values().all(|days| *days == 0) {
+        return Ok(mem);
+    }
+    Ok(Box::new(ttl::ExpiringMemory::new(mem, &config.ttl_days)))
+}
+
+/// Delete expired memories at startup. Best-effort: failures are logged.
+pub async fn gc_expired(mem: &dyn Memory) {
+    match mem.gc().await {
+        Ok(0) => {}
+        Ok(removed) => tracing::info!(removed, "Deleted expired memories"),
+        Err(e) => tracing::warn!("memory gc skipped: {e}"),
+    }
+}
+
+fn create_backend(
+    config: &MemoryConfig,
+    workspace_dir: &Path,
+    api_key: Option<&str>,
+) -> anyhow::Result<Box<dyn Memory>> {
     match config.backend.as_str() {
         "sqlite" => {
             let embedder: Arc<dyn embeddings::EmbeddingProvider> =
@@ -55,6 +84,53 @@ pub fn create_memory(
     }
 }
 
+/// Handle `tinyclaw memory` subcommands.
+pub async fn handle_command(command: crate::MemoryCommands, config: &Config) -> Result<()> {
+    let memory = create_memory(
+        &config.memory,
+        &config.workspace_dir,
+        config.api_key.as_deref(),
+    )?;
+    match command {
+        crate::MemoryCommands::Export { file } => {
+            let count = transfer::export_to_file(memory.as_ref(), &file).await?;
+            println!(
+                "✅ Exported {count} memories from {} to {}",
+                memory.name(),
+                file.display()
+            );
+        }
+        crate::MemoryCommands::Import { file, replace, .. } => {
+            let dump = transfer::read_export(&file)?;
+            let mode = if replace {
+                let backup = transfer::pre_import_backup_path(&config.workspace_dir);
+                let saved = transfer::export_to_file(memory.as_ref(), &backup).await?;
+                println!(
+                    "🛟 Backed up {saved} existing memories to {}",
+                    backup.display()
+                );
+                ImportMode::Replace
+            } else {
+                ImportMode::Merge
+            };
+            let stats = transfer::import(memory.as_ref(), &dump, mode).await?;
+            println!(
+                "✅ Imported {} memories into {} from {}",
+                stats.imported,
+                memory.name(),
+                file.display()
+            );
+            if stats.removed > 0 {
+                println!("  Removed:  {}", stats.removed);
+            }
+            if stats.skipped > 0 {
+                println!("  Skipped:  {}", stats.skipped);
+            }
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -4,6 +4,7 @@ use super::vector;
 use async_trait::async_trait;
 use chrono::Local;
 use rusqlite::{params, Connection};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use uuid::Uuid;
@@ -111,6 +112,52 @@ impl SqliteMemory {
         Ok(())
     }
 
+    /// Schema upgrades an existing `brain.db` still needs. Databases created
+    /// by older builds can lack the embedding column or the FTS5 index, which
+    /// `CREATE ... IF NOT EXISTS` in [`Self::init_schema`] cannot add.
+    pub fn pending_migrations(db_path: &Path) -> anyhow::Result<Vec<&'static str>> {
+        let conn = Connection::open(db_path)?;
+        Self::pending(&conn)
+    }
+
+    /// Apply every pending schema upgrade and return what was changed.
+    /// Running it on an up-to-date database is a no-op.
+    pub fn migrate(db_path: &Path) -> anyhow::Result<Vec<&'static str>> {
+        let conn = Connection::open(db_path)?;
+        let pending = Self::pending(&conn)?;
+        if pending.is_empty() {
+            return Ok(pending);
+        }
+
+        let has_memories = table_exists(&conn, "memories")?;
+        if has_memories && !column_exists(&conn, "memories", "embedding")? {
+            conn.execute_batch("ALTER TABLE memories ADD COLUMN embedding BLOB")?;
+        }
+        let had_fts = table_exists(&conn, "memories_fts")?;
+        Self::init_schema(&conn)?;
+        if has_memories && !had_fts {
+            // Index rows that predate the FTS table
+            conn.execute_batch("INSERT INTO memories_fts(memories_fts) VALUES ('rebuild')")?;
+        }
+        Ok(pending)
+    }
+
+    fn pending(conn: &Connection) -> anyhow::Result<Vec<&'static str>> {
+        let mut pending = Vec::new();
+        if !table_exists(conn, "memories")? {
+            pending.push("create memories table");
+        } else if !column_exists(conn, "memories", "embedding")? {
+            pending.push("add memories.embedding column");
+        }
+        if !table_exists(conn, "memories_fts")? {
+            pending.push("create memories_fts full-text index");
+        }
+        if !table_exists(conn, "embedding_cache")? {
+            pending.push("create embedding_cache table");
+        }
+        Ok(pending)
+    }
+
     fn category_to_str(cat: &MemoryCategory) -> String {
         match cat {
             MemoryCategory::Core => "core".into(),
@@ -334,6 +381,24 @@ impl SqliteMemory {
     }
 }
 
+fn table_exists(conn: &Connection, name: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE name = ?1",
+        params![name],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+}
+
+fn column_exists(conn: &Connection, table: &str, column: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+        params![table, column],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+}
+
 #[async_trait]
 impl Memory for SqliteMemory {
     fn name(&self) -> &str {
@@ -582,6 +647,16 @@ impl Memory for SqliteMemory {
         Ok(count as usize)
     }
 
+    async fn updated_at(&self) -> anyhow::Result<HashMap<String, String>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+        let mut stmt = conn.prepare("SELECT key, updated_at FROM memories")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
     async fn health_check(&self) -> bool {
         self.conn
             .lock()
@@ -1234,6 +1309,48 @@ mod tests {
         assert_eq!(mem2.count().await.unwrap(), 2);
     }
 
+    #[test]
+    fn migrate_upgrades_legacy_schema() {
+        let tmp = TempDir::new().unwrap();
+        let db_path = tmp.path().join("brain.db");
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE memories (
+                    id TEXT PRIMARY KEY, key TEXT NOT NULL UNIQUE, content TEXT NOT NULL,
+                    category TEXT NOT NULL DEFAULT 'core',
+                    created_at TEXT NOT NULL, updated_at TEXT NOT NULL
+                );
+                INSERT INTO memories VALUES ('1', 'lang', 'Rust is the preferred language', 'core', 't', 't');",
+            )
+            .unwrap();
+        }
+
+        let pending = SqliteMemory::pending_migrations(&db_path).unwrap();
+        assert!(
+            pending.contains(&"add memories.embedding column"),
+            "{pending:?}"
+        );
+        assert!(pending.contains(&"create memories_fts full-text index"));
+
+        assert_eq!(SqliteMemory::migrate(&db_path).unwrap(), pending);
+        assert!(SqliteMemory::pending_migrations(&db_path)
+            .unwrap()
+            .is_empty());
+        assert!(SqliteMemory::migrate(&db_path).unwrap().is_empty());
+
+        // Pre-existing rows are searchable after the FTS rebuild
+        let conn = Connection::open(&db_path).unwrap();
+        let hits: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM memories_fts WHERE memories_fts MATCH 'preferred'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hits, 1);
+    }
+
     #[tokio::test]
     async fn schema_triple_open() {
         let tmp = TempDir::new().unwrap();
@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// A single memory entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,6 +64,29 @@ pub trait Memory: Send + Sync {
     /// Count total memories
     async fn count(&self) -> anyhow::Result<usize>;
 
+    /// Remove every memory, returning how many were removed
+    async fn clear(&self) -> anyhow::Result<usize> {
+        let mut removed = 0;
+        for entry in self.list(None).await? {
+            if self.forget(&entry.key).await? {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// When each entry was last written (RFC 3339), by key, for
+    /// `[memory] ttl_days`. Entries left out are aged by their `timestamp`.
+    async fn updated_at(&self) -> anyhow::Result<HashMap<String, String>> {
+        Ok(HashMap::new())
+    }
+
+    /// Delete entries past their category's `[memory] ttl_days`, returning
+    /// how many were removed. Backends keep everything by default.
+    async fn gc(&self) -> anyhow::Result<usize> {
+        Ok(0)
+    }
+
     /// Health check
     async fn health_check(&self) -> bool;
 }
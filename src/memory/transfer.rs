@@ -0,0 +1,303 @@
+//! Backend-independent memory backup: `tinyclaw memory export <file>` dumps
+//! every entry to portable JSON and `tinyclaw memory import <file>` loads it
+//! into whichever backend is configured.
+
+use super::traits::{Memory, MemoryCategory, MemoryEntry};
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Bumped when the export layout changes incompatibly.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Top-level JSON document written by `memory export`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemoryExport {
+    pub version: u32,
+    pub exported_at: String,
+    /// Backend the entries came from (informational)
+    pub backend: String,
+    pub entries: Vec<ExportedEntry>,
+}
+
+/// One memory, without backend-specific ids or scores.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedEntry {
+    pub key: String,
+    pub content: String,
+    /// `core`, `daily`, `conversation` or a custom category name
+    pub category: String,
+    /// When the backend recorded the entry. Informational: backends stamp
+    /// imported entries with the import time
+    #[serde(default)]
+    pub timestamp: String,
+}
+
+/// What `memory import` does with entries already in the backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Keep existing entries; skip imported ones that are already present
+    Merge,
+    /// Remove every existing entry first
+    Replace,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportStats {
+    pub imported: usize,
+    pub skipped: usize,
+    pub removed: usize,
+}
+
+fn parse_category(name: &str) -> MemoryCategory {
+    match name {
+        "core" => MemoryCategory::Core,
+        "daily" => MemoryCategory::Daily,
+        "conversation" => MemoryCategory::Conversation,
+        other => MemoryCategory::Custom(other.to_string()),
+    }
+}
+
+/// Markdown memory stores `- **key**: content` lines and reports them under
+/// positional keys; recover the original key so it survives a transfer.
+fn portable_key_and_content(entry: &MemoryEntry) -> (String, String) {
+    if let Some(rest) = entry.content.strip_prefix("**") {
+        if let Some((key, content)) = rest.split_once("**: ") {
+            if !key.is_empty() {
+                return (key.to_string(), content.to_string());
+            }
+        }
+    }
+    (entry.key.clone(), entry.content.clone())
+}
+
+/// Collect every entry from `memory`, oldest first.
+pub async fn export(memory: &dyn Memory) -> Result<MemoryExport> {
+    let mut entries: Vec<ExportedEntry> = memory
+        .list(None)
+        .await?
+        .iter()
+        .map(|entry| {
+            let (key, content) = portable_key_and_content(entry);
+            ExportedEntry {
+                key,
+                content,
+                category: entry.category.to_string(),
+                timestamp: entry.timestamp.clone(),
+            }
+        })
+        .collect();
+    // Stable sort keeps the backend's order within one timestamp
+    entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+    Ok(MemoryExport {
+        version: FORMAT_VERSION,
+        exported_at: Local::now().to_rfc3339(),
+        backend: memory.name().to_string(),
+        entries,
+    })
+}
+
+/// Export `memory` to `path` as pretty JSON. Returns the entry count.
+pub async fn export_to_file(memory: &dyn Memory, path: &Path) -> Result<usize> {
+    let dump = export(memory).await?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&dump)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(dump.entries.len())
+}
+
+/// Read and version-check an export file.
+pub fn read_export(path: &Path) -> Result<MemoryExport> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let dump: MemoryExport = serde_json::from_str(&raw)
+        .with_context(|| format!("Invalid memory export {}", path.display()))?;
+    if dump.version != FORMAT_VERSION {
+        anyhow::bail!(
+            "Unsupported memory export version {} (expected {FORMAT_VERSION})",
+            dump.version
+        );
+    }
+    Ok(dump)
+}
+
+/// Load `dump` into `memory`. In merge mode an entry whose key and content
+/// already exist is skipped, so importing the same file twice is harmless.
+pub async fn import(
+    memory: &dyn Memory,
+    dump: &MemoryExport,
+    mode: ImportMode,
+) -> Result<ImportStats> {
+    let mut stats = ImportStats::default();
+    let mut existing: HashSet<(String, String)> = HashSet::new();
+
+    match mode {
+        ImportMode::Replace => stats.removed = memory.clear().await?,
+        ImportMode::Merge => {
+            for entry in memory.list(None).await? {
+                let (key, content) = portable_key_and_content(&entry);
+                existing.insert((key, content.trim().to_string()));
+            }
+        }
+    }
+
+    for entry in &dump.entries {
+        if entry.key.trim().is_empty() {
+            stats.skipped += 1;
+            continue;
+        }
+        let identity = (entry.key.clone(), entry.content.trim().to_string());
+        if !existing.insert(identity) {
+            stats.skipped += 1;
+            continue;
+        }
+        memory
+            .store(&entry.key, &entry.content, parse_category(&entry.category))
+            .await
+            .with_context(|| format!("Failed to import memory '{}'", entry.key))?;
+        stats.imported += 1;
+    }
+    Ok(stats)
+}
+
+/// Where `memory import --replace` saves the entries it is about to remove.
+pub fn pre_import_backup_path(workspace_dir: &Path) -> PathBuf {
+    let stamp = Local::now().format("%Y%m%d-%H%M%S");
+    workspace_dir
+        .join("memory")
+        .join("backups")
+        .join(format!("pre-import-{stamp}.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{MarkdownMemory, SqliteMemory};
+    use tempfile::TempDir;
+
+    fn pairs(dump: &MemoryExport) -> Vec<(String, String, String)> {
+        let mut out: Vec<_> = dump
+            .entries
+            .iter()
+            .map(|e| (e.key.clone(), e.content.clone(), e.category.clone()))
+            .collect();
+        out.sort();
+        out
+    }
+
+    #[tokio::test]
+    async fn round_trip_sqlite_to_markdown_and_back() {
+        let src_dir = TempDir::new().unwrap();
+        let sqlite = SqliteMemory::new(src_dir.path()).unwrap();
+        sqlite
+            .store("user_lang", "Prefers Rust", MemoryCategory::Core)
+            .await
+            .unwrap();
+        sqlite
+            .store("standup", "Shipped the exporter", MemoryCategory::Daily)
+            .await
+            .unwrap();
+
+        let file = src_dir.path().join("backup/memory.json");
+        assert_eq!(export_to_file(&sqlite, &file).await.unwrap(), 2);
+        let dump = read_export(&file).unwrap();
+        assert_eq!(dump.backend, "sqlite");
+
+        let md_dir = TempDir::new().unwrap();
+        let markdown = MarkdownMemory::new(md_dir.path());
+        let stats = import(&markdown, &dump, ImportMode::Merge).await.unwrap();
+        assert_eq!(stats.imported, 2);
+
+        // Keys survive markdown's positional ids
+        let from_markdown = export(&markdown).await.unwrap();
+        assert_eq!(pairs(&from_markdown), pairs(&dump));
+
+        let back_dir = TempDir::new().unwrap();
+        let sqlite_again = SqliteMemory::new(back_dir.path()).unwrap();
+        import(&sqlite_again, &from_markdown, ImportMode::Merge)
+            .await
+            .unwrap();
+        assert_eq!(pairs(&export(&sqlite_again).await.unwrap()), pairs(&dump));
+    }
+
+    #[tokio::test]
+    async fn merge_skips_present_entries() {
+        let tmp = TempDir::new().unwrap();
+        let memory = MarkdownMemory::new(tmp.path());
+        memory
+            .store("a", "kept", MemoryCategory::Core)
+            .await
+            .unwrap();
+        let dump = export(&memory).await.unwrap();
+
+        let stats = import(&memory, &dump, ImportMode::Merge).await.unwrap();
+        assert_eq!(
+            stats,
+            ImportStats {
+                imported: 0,
+                skipped: 1,
+                removed: 0
+            }
+        );
+        assert_eq!(memory.count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn replace_clears_existing_entries() {
+        for backend in ["sqlite", "markdown"] {
+            let tmp = TempDir::new().unwrap();
+            let memory: Box<dyn Memory> = if backend == "sqlite" {
+                Box::new(SqliteMemory::new(tmp.path()).unwrap())
+            } else {
+                Box::new(MarkdownMemory::new(tmp.path()))
+            };
+            memory
+                .store("old", "stale fact", MemoryCategory::Core)
+                .await
+                .unwrap();
+            memory
+                .store("log", "old log", MemoryCategory::Daily)
+                .await
+                .unwrap();
+
+            let dump = MemoryExport {
+                version: FORMAT_VERSION,
+                exported_at: String::new(),
+                backend: "test".into(),
+                entries: vec![ExportedEntry {
+                    key: "new".into(),
+                    content: "fresh fact".into(),
+                    category: "core".into(),
+                    timestamp: String::new(),
+                }],
+            };
+            let stats = import(memory.as_ref(), &dump, ImportMode::Replace)
+                .await
+                .unwrap();
+            assert_eq!(stats.removed, 2, "{backend}");
+            assert_eq!(stats.imported, 1, "{backend}");
+
+            let after = export(memory.as_ref()).await.unwrap();
+            assert_eq!(after.entries.len(), 1, "{backend}");
+            assert_eq!(after.entries[0].key, "new", "{backend}");
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_export_version() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("future.json");
+        std::fs::write(
+            &path,
+            r#"{"version": 99, "exported_at": "", "backend": "x", "entries": []}"#,
+        )
+        .unwrap();
+        let err = read_export(&path).unwrap_err().to_string();
+        assert!(err.contains("version 99"), "{err}");
+    }
+}
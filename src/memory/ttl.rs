@@ -0,0 +1,270 @@
+use super::traits::{Memory, MemoryCategory, MemoryEntry};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone};
+use std::collections::HashMap;
+
+/// Hides entries older than their category's `[memory] ttl_days` from
+/// `recall`, `list` and `get`, and deletes them in [`Memory::gc`].
+///
+/// Age is taken from the backend's [`Memory::updated_at`] (last write for
+/// sqlite), else the entry's timestamp (the daily file's date for markdown).
+/// Entries without a parseable timestamp, like markdown's `MEMORY.md`, never
+/// expire.
+pub struct ExpiringMemory {
+    inner: Box<dyn Memory>,
+    ttl: HashMap<String, Duration>,
+}
+
+impl ExpiringMemory {
+    /// `ttl_days` maps a category name (`conversation`, `daily`, `core` or a
+    /// custom one) to days; categories left out, or set to 0, never expire.
+    pub fn new(inner: Box<dyn Memory>, ttl_days: &HashMap<String, u32>) -> Self {
+        let ttl = ttl_days
+            .iter()
+            .filter(|(_, days)| **days > 0)
+            .map(|(category, days)| {
+                (
+                    category.trim().to_ascii_lowercase(),
+                    Duration::days(i64::from(*days)),
+                )
+            })
+            .collect();
+        Self { inner, ttl }
+    }
+
+    fn is_expired(
+        &self,
+        entry: &MemoryEntry,
+        updated: &HashMap<String, String>,
+        now: DateTime<Local>,
+    ) -> bool {
+        let Some(ttl) = self.ttl.get(&category_key(&entry.category)) else {
+            return false;
+        };
+        let timestamp = updated.get(&entry.key).unwrap_or(&entry.timestamp);
+        written_at(timestamp).is_some_and(|written| now - written > *ttl)
+    }
+
+    async fn fresh(&self, entries: Vec<MemoryEntry>) -> anyhow::Result<Vec<MemoryEntry>> {
+        let updated = self.inner.updated_at().await?;
+        let now = Local::now();
+        Ok(entries
+            .into_iter()
+            .filter(|e| !self.is_expired(e, &updated, now))
+            .collect())
+    }
+}
+
+fn category_key(category: &MemoryCategory) -> String {
+    category.to_string().to_ascii_lowercase()
+}
+
+/// An RFC 3339 timestamp, or a `YYYY-MM-DD` date (counted from its start).
+fn written_at(timestamp: &str) -> Option<DateTime<Local>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(timestamp) {
+        return Some(time.with_timezone(&Local));
+    }
+    let date = NaiveDate::parse_from_str(timestamp.get(..10)?, "%Y-%m-%d").ok()?;
+    Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+        .earliest()
+}
+
+#[async_trait]
+impl Memory for ExpiringMemory {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn store(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+    ) -> anyhow::Result<()> {
+        self.inner.store(key, content, category).await
+    }
+
+    async fn recall(&self, query: &str, limit: usize) -> anyhow::Result<Vec<MemoryEntry>> {
+        // Ask for extra so entries that expired since the last gc don't
+        // leave the result short
+        let entries = self.inner.recall(query, limit.saturating_mul(2)).await?;
+        let mut entries = self.fresh(entries).await?;
+        entries.truncate(limit);
+        Ok(entries)
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<MemoryEntry>> {
+        let entry = self.inner.get(key).await?;
+        Ok(self.fresh(entry.into_iter().collect()).await?.pop())
+    }
+
+    async fn list(&self, category: Option<&MemoryCategory>) -> anyhow::Result<Vec<MemoryEntry>> {
+        self.fresh(self.inner.list(category).await?).await
+    }
+
+    async fn forget(&self, key: &str) -> anyhow::Result<bool> {
+        self.inner.forget(key).await
+    }
+
+    async fn count(&self) -> anyhow::Result<usize> {
+        Ok(self.list(None).await?.len())
+    }
+
+    async fn clear(&self) -> anyhow::Result<usize> {
+        self.inner.clear().await
+    }
+
+    async fn updated_at(&self) -> anyhow::Result<HashMap<String, String>> {
+        self.inner.updated_at().await
+    }
+
+    async fn gc(&self) -> anyhow::Result<usize> {
+        let updated = self.inner.updated_at().await?;
+        let now = Local::now();
+        let mut removed = 0;
+        for entry in self.inner.list(None).await? {
+            if self.is_expired(&entry, &updated, now) && self.inner.forget(&entry.key).await? {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    async fn health_check(&self) -> bool {
+        self.inner.health_check().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::SqliteMemory;
+    use tempfile::TempDir;
+
+    fn expiring(tmp: &TempDir) -> ExpiringMemory {
+        let ttl = HashMap::from([("conversation".to_string(), 7), ("daily".to_string(), 0)]);
+        ExpiringMemory::new(Box::new(SqliteMemory::new(tmp.path()).unwrap()), &ttl)
+    }
+
+    /// Backdate `key` in the sqlite database by `days`.
+    fn backdate(tmp: &TempDir, key: &str, days: i64) {
+        let conn = rusqlite::Connection::open(tmp.path().join("memory").join("brain.db")).unwrap();
+        let old = (Local::now() - Duration::days(days)).to_rfc3339();
+        conn.execute(
+            "UPDATE memories SET created_at = ?1, updated_at = ?1 WHERE key = ?2",
+            rusqlite::params![old, key],
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn expired_entry_is_left_out_of_recall_but_fresh_one_is_not() {
+        let tmp = TempDir::new().unwrap();
+        let mem = expiring(&tmp);
+        mem.store(
+            "old",
+            "deploy notes from last month",
+            MemoryCategory::Conversation,
+        )
+        .await
+        .unwrap();
+        mem.store(
+            "new",
+            "deploy notes from today",
+            MemoryCategory::Conversation,
+        )
+        .await
+        .unwrap();
+        backdate(&tmp, "old", 8);
+
+        let keys: Vec<String> = mem
+            .recall("deploy notes", 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.key)
+            .collect();
+        assert_eq!(keys, ["new"]);
+        assert!(mem.get("old").await.unwrap().is_none());
+        assert_eq!(mem.list(None).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn categories_without_ttl_never_expire() {
+        let tmp = TempDir::new().unwrap();
+        let mem = expiring(&tmp);
+        mem.store("fact", "user prefers tabs", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("log", "daily standup", MemoryCategory::Daily)
+            .await
+            .unwrap();
+        backdate(&tmp, "fact", 400);
+        backdate(&tmp, "log", 400);
+
+        assert_eq!(mem.list(None).await.unwrap().len(), 2);
+        assert_eq!(mem.gc().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn gc_deletes_only_expired_entries() {
+        let tmp = TempDir::new().unwrap();
+        let mem = expiring(&tmp);
+        mem.store("old", "stale", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        mem.store("new", "fresh", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        backdate(&tmp, "old", 30);
+
+        assert_eq!(mem.gc().await.unwrap(), 1);
+        assert_eq!(mem.count().await.unwrap(), 1);
+        assert!(mem.get("new").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn count_leaves_out_expired_entries() {
+        let tmp = TempDir::new().unwrap();
+        let mem = expiring(&tmp);
+        mem.store("old", "stale", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        mem.store("new", "fresh", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        backdate(&tmp, "old", 30);
+
+        assert_eq!(mem.count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn age_counts_from_the_last_update_not_creation() {
+        let tmp = TempDir::new().unwrap();
+        let mem = expiring(&tmp);
+        mem.store("topic", "rewritten today", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        let conn = rusqlite::Connection::open(tmp.path().join("memory").join("brain.db")).unwrap();
+        let created = (Local::now() - Duration::days(30)).to_rfc3339();
+        conn.execute(
+            "UPDATE memories SET created_at = ?1 WHERE key = 'topic'",
+            rusqlite::params![created],
+        )
+        .unwrap();
+
+        let entry = mem.get("topic").await.unwrap().expect("updated entry kept");
+        assert_eq!(entry.timestamp, created, "timestamp is the creation time");
+        assert_eq!(mem.gc().await.unwrap(), 0);
+    }
+
+    #[test]
+    fn timestamps_parse_as_rfc3339_or_date() {
+        assert!(written_at("2026-10-17T09:30:00+02:00").is_some());
+        assert!(written_at("2026-10-17").is_some());
+        assert!(written_at("2026-10-17_archived").is_some());
+        assert!(written_at("MEMORY").is_none());
+        assert!(written_at("").is_none());
+    }
+}
@@ -1,4 +1,4 @@
-use super::traits::{Observer, ObserverEvent, ObserverMetric};
+use super::traits::{Observer, ObserverEvent, ObserverMetric, TurnContext};
 use tracing::info;
 
 /// Log-based observer — uses tracing, zero external deps
@@ -13,32 +13,84 @@ impl LogObserver {
 impl Observer for LogObserver {
     fn record_event(&self, event: &ObserverEvent) {
         match event {
-            ObserverEvent::AgentStart { provider, model } => {
-                info!(provider = %provider, model = %model, "agent.start");
+            ObserverEvent::AgentStart {
+                provider,
+                model,
+                turn,
+            } => {
+                let (request_id, session_id) = turn_fields(turn.as_ref());
+                info!(
+                    provider = %provider,
+                    model = %model,
+                    request_id,
+                    session_id,
+                    "agent.start"
+                );
             }
             ObserverEvent::AgentEnd {
+                provider,
                 duration,
                 tokens_used,
+                turn,
             } => {
                 let ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
-                info!(duration_ms = ms, tokens = ?tokens_used, "agent.end");
+                let (request_id, session_id) = turn_fields(turn.as_ref());
+                info!(
+                    provider = %provider,
+                    duration_ms = ms,
+                    tokens = ?tokens_used,
+                    request_id,
+                    session_id,
+                    "agent.end"
+                );
             }
             ObserverEvent::ToolCall {
                 tool,
                 duration,
                 success,
+                turn,
             } => {
                 let ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
-                info!(tool = %tool, duration_ms = ms, success = success, "tool.call");
+                let (request_id, session_id) = turn_fields(turn.as_ref());
+                info!(
+                    tool = %tool,
+                    duration_ms = ms,
+                    success = success,
+                    request_id,
+                    session_id,
+                    "tool.call"
+                );
             }
-            ObserverEvent::ChannelMessage { channel, direction } => {
-                info!(channel = %channel, direction = %direction, "channel.message");
+            ObserverEvent::ChannelMessage {
+                channel,
+                direction,
+                turn,
+            } => {
+                let (request_id, session_id) = turn_fields(turn.as_ref());
+                info!(
+                    channel = %channel,
+                    direction = %direction,
+                    request_id,
+                    session_id,
+                    "channel.message"
+                );
             }
             ObserverEvent::HeartbeatTick => {
                 info!("heartbeat.tick");
             }
-            ObserverEvent::Error { component, message } => {
-                info!(component = %component, error = %message, "error");
+            ObserverEvent::Error {
+                component,
+                message,
+                turn,
+            } => {
+                let (request_id, session_id) = turn_fields(turn.as_ref());
+                info!(
+                    component = %component,
+                    error = %message,
+                    request_id,
+                    session_id,
+                    "error"
+                );
             }
         }
     }
@@ -66,6 +118,14 @@ impl Observer for LogObserver {
     }
 }
 
+/// `request_id` and `session_id` log fields; absent outside a turn.
+fn turn_fields(turn: Option<&TurnContext>) -> (Option<&str>, Option<&str>) {
+    (
+        turn.map(|t| t.request_id.as_str()),
+        turn.and_then(|t| t.session_id.as_deref()),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -82,31 +142,48 @@ mod tests {
         obs.record_event(&ObserverEvent::AgentStart {
             provider: "openrouter".into(),
             model: "claude-sonnet".into(),
+            turn: None,
         });
         obs.record_event(&ObserverEvent::AgentEnd {
+            provider: "openrouter".into(),
             duration: Duration::from_millis(500),
             tokens_used: Some(100),
+            turn: None,
         });
         obs.record_event(&ObserverEvent::AgentEnd {
+            provider: "openrouter".into(),
             duration: Duration::ZERO,
             tokens_used: None,
+            turn: None,
         });
         obs.record_event(&ObserverEvent::ToolCall {
             tool: "shell".into(),
             duration: Duration::from_millis(10),
             success: false,
+            turn: Some(TurnContext::new(Some("20260101-abc"))),
         });
         obs.record_event(&ObserverEvent::ChannelMessage {
             channel: "telegram".into(),
             direction: "outbound".into(),
+            turn: Some(TurnContext::new(None)),
         });
         obs.record_event(&ObserverEvent::HeartbeatTick);
         obs.record_event(&ObserverEvent::Error {
             component: "provider".into(),
             message: "timeout".into(),
+            turn: None,
         });
     }
 
+    #[test]
+    fn turn_fields_carry_request_and_session() {
+        assert_eq!(turn_fields(None), (None, None));
+        let turn = TurnContext::new(Some("s1"));
+        let (request_id, session_id) = turn_fields(Some(&turn));
+        assert_eq!(request_id, Some(turn.request_id.as_str()));
+        assert_eq!(session_id, Some("s1"));
+    }
+
     #[test]
     fn log_observer_all_metrics_no_panic() {
         let obs = LogObserver::new();
@@ -0,0 +1,59 @@
+//! Process-wide tracing subscriber whose level can change at runtime, so
+//! `[observability] log_level` applies at startup and on a daemon reload.
+
+use anyhow::{Context, Result};
+use std::sync::OnceLock;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::{fmt, reload, Registry};
+
+static HANDLE: OnceLock<reload::Handle<LevelFilter, Registry>> = OnceLock::new();
+
+/// Install the global subscriber at `info`. Call once, before any logging.
+/// Logs go to stdout, or to stderr with `to_stderr` so that stdout carries
+/// only the command's own output.
+pub fn init(to_stderr: bool) {
+    let (filter, handle) = reload::Layer::new(LevelFilter::INFO);
+    let writer = if to_stderr {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+    let subscriber = tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(writer));
+    tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+    let _ = HANDLE.set(handle);
+}
+
+/// Parse a level name: `off`, `error`, `warn`, `info`, `debug` or `trace`.
+pub fn parse(level: &str) -> Result<LevelFilter> {
+    level.trim().parse().map_err(|_| {
+        anyhow::anyhow!(
+            "Invalid log level {level:?}: expected off, error, warn, info, debug or trace"
+        )
+    })
+}
+
+/// Change the level of the subscriber installed by [`init`].
+pub fn set(level: LevelFilter) -> Result<()> {
+    HANDLE
+        .get()
+        .context("Logging is not initialized")?
+        .reload(level)
+        .context("Failed to change log level")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_level_names() {
+        assert_eq!(parse("debug").unwrap(), LevelFilter::DEBUG);
+        assert_eq!(parse(" WARN ").unwrap(), LevelFilter::WARN);
+        assert_eq!(parse("off").unwrap(), LevelFilter::OFF);
+        assert!(parse("loud").is_err());
+    }
+}
@@ -1,55 +1,79 @@
 pub mod log;
+pub mod log_level;
 pub mod multi;
 pub mod noop;
 #[cfg(feature = "otel")]
 pub mod otel;
+pub mod tool_stats;
 pub mod traits;
 
 pub use self::log::LogObserver;
+pub use multi::MultiObserver;
 pub use noop::NoopObserver;
 #[cfg(feature = "otel")]
 pub use otel::OtelObserver;
-pub use traits::{Observer, ObserverEvent};
+pub use tool_stats::{ToolStat, ToolStats};
+pub use traits::{Observer, ObserverEvent, TurnContext};
 
 use crate::config::ObservabilityConfig;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-/// Factory: create the right observer from config
+/// Set by the first fallback, so the warning isn't repeated by every
+/// subsystem that builds its own observer.
+static FALLBACK_REPORTED: AtomicBool = AtomicBool::new(false);
+
+/// Factory: create the right observer from config. A backend that can't be
+/// set up is replaced by [`NoopObserver`]; telemetry never stops startup.
 pub fn create_observer(config: &ObservabilityConfig) -> Box<dyn Observer> {
     match config.backend.as_str() {
         "log" => Box::new(LogObserver::new()),
         #[cfg(feature = "otel")]
-        "otel" | "opentelemetry" | "otlp" => {
-            match OtelObserver::new(
+        "otel" | "opentelemetry" | "otlp" => init_or_noop(&config.backend, || {
+            let obs = OtelObserver::new(
                 config.otel_endpoint.as_deref(),
                 config.otel_service_name.as_deref(),
-            ) {
-                Ok(obs) => {
-                    tracing::info!(
-                        endpoint = config.otel_endpoint.as_deref().unwrap_or("http://localhost:4318"),
-                        "OpenTelemetry observer initialized"
-                    );
-                    Box::new(obs)
-                }
-                Err(e) => {
-                    tracing::error!("Failed to create OTel observer: {e}. Falling back to noop.");
-                    Box::new(NoopObserver)
-                }
-            }
-        }
+                &config.otel_headers,
+            )?;
+            tracing::info!(
+                endpoint = config
+                    .otel_endpoint
+                    .as_deref()
+                    .unwrap_or("http://localhost:4318"),
+                "OpenTelemetry observer initialized"
+            );
+            Ok(Box::new(obs))
+        }),
         #[cfg(not(feature = "otel"))]
-        "otel" | "opentelemetry" | "otlp" => {
-            tracing::warn!("OTel not compiled in this build (use --features otel). Falling back to noop.");
-            Box::new(NoopObserver)
-        }
+        "otel" | "opentelemetry" | "otlp" => fall_back(
+            &config.backend,
+            "OTel not compiled in this build (use --features otel)",
+        ),
         "none" | "noop" => Box::new(NoopObserver),
-        _ => {
-            tracing::warn!(
-                "Unknown observability backend '{}', falling back to noop",
-                config.backend
-            );
-            Box::new(NoopObserver)
-        }
+        _ => fall_back(&config.backend, "unknown backend"),
+    }
+}
+
+/// Run a backend's `init`, falling back to [`NoopObserver`] if it errors.
+fn init_or_noop(
+    backend: &str,
+    init: impl FnOnce() -> Result<Box<dyn Observer>, String>,
+) -> Box<dyn Observer> {
+    match init() {
+        Ok(observer) => observer,
+        Err(reason) => fall_back(backend, &reason),
+    }
+}
+
+/// Warn (once per process) and mark the `observability` health component
+/// as failed, then carry on without telemetry.
+fn fall_back(backend: &str, reason: &str) -> Box<dyn Observer> {
+    if !FALLBACK_REPORTED.swap(true, Ordering::Relaxed) {
+        tracing::warn!(
+            "Observability backend '{backend}' unavailable: {reason}. Continuing without telemetry."
+        );
+        crate::health::mark_component_error("observability", format!("{backend}: {reason}"));
     }
+    Box::new(NoopObserver)
 }
 
 #[cfg(test)]
@@ -90,6 +114,7 @@ mod tests {
             backend: "otel".into(),
             otel_endpoint: Some("http://127.0.0.1:19999".into()),
             otel_service_name: Some("test".into()),
+            ..ObservabilityConfig::default()
         };
         assert_eq!(create_observer(&cfg).name(), "otel");
     }
@@ -101,6 +126,7 @@ mod tests {
             backend: "opentelemetry".into(),
             otel_endpoint: Some("http://127.0.0.1:19999".into()),
             otel_service_name: Some("test".into()),
+            ..ObservabilityConfig::default()
         };
         assert_eq!(create_observer(&cfg).name(), "otel");
     }
@@ -112,6 +138,7 @@ mod tests {
             backend: "otlp".into(),
             otel_endpoint: Some("http://127.0.0.1:19999".into()),
             otel_service_name: Some("test".into()),
+            ..ObservabilityConfig::default()
         };
         assert_eq!(create_observer(&cfg).name(), "otel");
     }
@@ -134,6 +161,21 @@ mod tests {
         assert_eq!(create_observer(&cfg).name(), "noop");
     }
 
+    #[test]
+    fn failing_backend_yields_a_working_noop() {
+        let observer = init_or_noop("collector", || Err("connection refused".into()));
+
+        assert_eq!(observer.name(), "noop");
+        observer.record_event(&ObserverEvent::AgentStart {
+            provider: "openrouter".into(),
+            model: "test".into(),
+            turn: None,
+        });
+        observer.flush();
+        let health = crate::health::snapshot();
+        assert_eq!(health.components["observability"].status, "error");
+    }
+
     #[test]
     fn factory_garbage_falls_back_to_noop() {
         let cfg = ObservabilityConfig {
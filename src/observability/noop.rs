@@ -32,27 +32,35 @@ mod tests {
         obs.record_event(&ObserverEvent::AgentStart {
             provider: "test".into(),
             model: "test".into(),
+            turn: None,
         });
         obs.record_event(&ObserverEvent::AgentEnd {
+            provider: "openrouter".into(),
             duration: Duration::from_millis(100),
             tokens_used: Some(42),
+            turn: None,
         });
         obs.record_event(&ObserverEvent::AgentEnd {
+            provider: "openrouter".into(),
             duration: Duration::ZERO,
             tokens_used: None,
+            turn: None,
         });
         obs.record_event(&ObserverEvent::ToolCall {
             tool: "shell".into(),
             duration: Duration::from_secs(1),
             success: true,
+            turn: None,
         });
         obs.record_event(&ObserverEvent::ChannelMessage {
             channel: "cli".into(),
             direction: "inbound".into(),
+            turn: None,
         });
         obs.record_event(&ObserverEvent::Error {
             component: "test".into(),
             message: "boom".into(),
+            turn: None,
         });
     }
 
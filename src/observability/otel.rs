@@ -1,17 +1,32 @@
-use super::traits::{Observer, ObserverEvent, ObserverMetric};
+use super::traits::{Observer, ObserverEvent, ObserverMetric, TurnContext};
 use opentelemetry::metrics::{Counter, Gauge, Histogram};
-use opentelemetry::trace::{Span, SpanKind, Status, Tracer};
-use opentelemetry::{global, KeyValue};
-use opentelemetry_otlp::WithExportConfig;
+use opentelemetry::trace::{Span, SpanKind, Status, TraceContextExt, Tracer};
+use opentelemetry::{global, Context, KeyValue};
+use opentelemetry_otlp::{WithExportConfig, WithHttpConfig};
 use opentelemetry_sdk::metrics::SdkMeterProvider;
 use opentelemetry_sdk::trace::SdkTracerProvider;
-use std::time::SystemTime;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// Upper bound on a single OTLP export, so an unreachable collector cannot
+/// stall a flush for the SDK's default 10 seconds.
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(3);
 
 /// OpenTelemetry-backed observer — exports traces and metrics via OTLP.
 pub struct OtelObserver {
     tracer_provider: SdkTracerProvider,
     meter_provider: SdkMeterProvider,
 
+    /// Open `agent.invocation` spans by the request id of their turn (`None`
+    /// for a run spanning many turns); tool calls and errors are children
+    /// of their turn's span, or of the run's.
+    agent_spans: Mutex<HashMap<Option<String>, Context>>,
+    /// Set while flushes fail, so an unreachable collector is logged once
+    /// per outage rather than on every flush.
+    flush_failing: AtomicBool,
+
     // Metrics instruments
     agent_starts: Counter<u64>,
     agent_duration: Histogram<f64>,
@@ -31,7 +46,12 @@ impl OtelObserver {
     ///
     /// Uses HTTP/protobuf transport (port 4318 by default).
     /// Falls back to `http://localhost:4318` if no endpoint is provided.
-    pub fn new(endpoint: Option<&str>, service_name: Option<&str>) -> Result<Self, String> {
+    /// `headers` are sent with every export request.
+    pub fn new(
+        endpoint: Option<&str>,
+        service_name: Option<&str>,
+        headers: &HashMap<String, String>,
+    ) -> Result<Self, String> {
         let endpoint = endpoint.unwrap_or("http://localhost:4318");
         let service_name = service_name.unwrap_or("tinyclaw");
 
@@ -39,6 +59,8 @@ impl OtelObserver {
         let span_exporter = opentelemetry_otlp::SpanExporter::builder()
             .with_http()
             .with_endpoint(endpoint)
+            .with_timeout(EXPORT_TIMEOUT)
+            .with_headers(headers.clone())
             .build()
             .map_err(|e| format!("Failed to create OTLP span exporter: {e}"))?;
 
@@ -55,6 +77,8 @@ impl OtelObserver {
         let metric_exporter = opentelemetry_otlp::MetricExporter::builder()
             .with_http()
             .with_endpoint(endpoint)
+            .with_timeout(EXPORT_TIMEOUT)
+            .with_headers(headers.clone())
             .build()
             .map_err(|e| format!("Failed to create OTLP metric exporter: {e}"))?;
 
@@ -135,6 +159,8 @@ impl OtelObserver {
         Ok(Self {
             tracer_provider,
             meter_provider: meter_provider_clone,
+            agent_spans: Mutex::new(HashMap::new()),
+            flush_failing: AtomicBool::new(false),
             agent_starts,
             agent_duration,
             tool_calls,
@@ -148,14 +174,64 @@ impl OtelObserver {
             queue_depth,
         })
     }
+
+    /// Context of the agent span `turn` belongs to: its own invocation's,
+    /// else the enclosing run's, else an empty context.
+    fn parent_context(&self, turn: Option<&TurnContext>) -> Context {
+        let Ok(spans) = self.agent_spans.lock() else {
+            return Context::new();
+        };
+        turn.and_then(|t| spans.get(&Some(t.request_id.clone())))
+            .or_else(|| spans.get(&None))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Log a failed flush once per outage; export carries on either way.
+    fn flush_failed(&self, reason: &str) {
+        if self.flush_failing.swap(true, Ordering::Relaxed) {
+            tracing::debug!("OTel export failed ({reason})");
+        } else {
+            tracing::warn!("OTel export failed ({reason}); will keep retrying");
+        }
+    }
+}
+
+/// Key of the agent span opened for `turn`.
+fn span_key(turn: Option<&TurnContext>) -> Option<String> {
+    turn.map(|t| t.request_id.clone())
 }
 
 impl Observer for OtelObserver {
+    #[allow(clippy::too_many_lines)]
     fn record_event(&self, event: &ObserverEvent) {
         let tracer = global::tracer("tinyclaw");
 
         match event {
-            ObserverEvent::AgentStart { provider, model } => {
+            ObserverEvent::AgentStart {
+                provider,
+                model,
+                turn,
+            } => {
+                let span = tracer.build(
+                    opentelemetry::trace::SpanBuilder::from_name("agent.invocation")
+                        .with_kind(SpanKind::Internal)
+                        .with_attributes(with_turn(
+                            vec![
+                                KeyValue::new("provider", provider.clone()),
+                                KeyValue::new("model", model.clone()),
+                            ],
+                            turn.as_ref(),
+                        )),
+                );
+                if let Ok(mut spans) = self.agent_spans.lock() {
+                    let cx = Context::new().with_span(span);
+                    // A run that never reported AgentEnd still gets closed
+                    if let Some(previous) = spans.insert(span_key(turn.as_ref()), cx) {
+                        previous.span().end();
+                    }
+                }
+
                 self.agent_starts.add(
                     1,
                     &[
@@ -165,27 +241,50 @@ impl Observer for OtelObserver {
                 );
             }
             ObserverEvent::AgentEnd {
+                provider,
                 duration,
                 tokens_used,
+                turn,
             } => {
                 let secs = duration.as_secs_f64();
-                let start_time = SystemTime::now()
-                    .checked_sub(*duration)
-                    .unwrap_or(SystemTime::now());
-
-                // Create a completed span with correct timing
-                let mut span = tracer.build(
-                    opentelemetry::trace::SpanBuilder::from_name("agent.invocation")
-                        .with_kind(SpanKind::Internal)
-                        .with_start_time(start_time)
-                        .with_attributes(vec![
-                            KeyValue::new("duration_s", secs),
-                        ]),
-                );
-                if let Some(t) = tokens_used {
-                    span.set_attribute(KeyValue::new("tokens_used", *t as i64));
+                let tokens = tokens_used.map(|t| i64::try_from(t).unwrap_or(i64::MAX));
+                let open = self
+                    .agent_spans
+                    .lock()
+                    .ok()
+                    .and_then(|mut spans| spans.remove(&span_key(turn.as_ref())));
+
+                if let Some(cx) = open {
+                    let span = cx.span();
+                    span.set_attribute(KeyValue::new("served_by", provider.clone()));
+                    span.set_attribute(KeyValue::new("duration_s", secs));
+                    if let Some(t) = tokens {
+                        span.set_attribute(KeyValue::new("tokens_used", t));
+                    }
+                    span.end();
+                } else {
+                    let start_time = SystemTime::now()
+                        .checked_sub(*duration)
+                        .unwrap_or(SystemTime::now());
+
+                    // No AgentStart seen: create a completed span with correct timing
+                    let mut span = tracer.build(
+                        opentelemetry::trace::SpanBuilder::from_name("agent.invocation")
+                            .with_kind(SpanKind::Internal)
+                            .with_start_time(start_time)
+                            .with_attributes(with_turn(
+                                vec![
+                                    KeyValue::new("served_by", provider.clone()),
+                                    KeyValue::new("duration_s", secs),
+                                ],
+                                turn.as_ref(),
+                            )),
+                    );
+                    if let Some(t) = tokens {
+                        span.set_attribute(KeyValue::new("tokens_used", t));
+                    }
+                    span.end();
                 }
-                span.end();
 
                 self.agent_duration.record(secs, &[]);
                 // Note: tokens are recorded via record_metric(TokensUsed) to avoid
@@ -195,6 +294,7 @@ impl Observer for OtelObserver {
                 tool,
                 duration,
                 success,
+                turn,
             } => {
                 let secs = duration.as_secs_f64();
                 let start_time = SystemTime::now()
@@ -207,15 +307,19 @@ impl Observer for OtelObserver {
                     Status::error("")
                 };
 
-                let mut span = tracer.build(
+                let mut span = tracer.build_with_context(
                     opentelemetry::trace::SpanBuilder::from_name("tool.call")
                         .with_kind(SpanKind::Internal)
                         .with_start_time(start_time)
-                        .with_attributes(vec![
-                            KeyValue::new("tool.name", tool.clone()),
-                            KeyValue::new("tool.success", *success),
-                            KeyValue::new("duration_s", secs),
-                        ]),
+                        .with_attributes(with_turn(
+                            vec![
+                                KeyValue::new("tool.name", tool.clone()),
+                                KeyValue::new("tool.success", *success),
+                                KeyValue::new("duration_s", secs),
+                            ],
+                            turn.as_ref(),
+                        )),
+                    &self.parent_context(turn.as_ref()),
                 );
                 span.set_status(status);
                 span.end();
@@ -227,7 +331,28 @@ impl Observer for OtelObserver {
                 self.tool_calls.add(1, &attrs);
                 self.tool_duration.record(secs, &[KeyValue::new("tool", tool.clone())]);
             }
-            ObserverEvent::ChannelMessage { channel, direction } => {
+            ObserverEvent::ChannelMessage {
+                channel,
+                direction,
+                turn,
+            } => {
+                // Only turn-scoped messages get a span, so they sit in the
+                // turn's trace next to its tool calls
+                if let Some(turn) = turn {
+                    let mut span = tracer.build_with_context(
+                        opentelemetry::trace::SpanBuilder::from_name("channel.message")
+                            .with_kind(SpanKind::Internal)
+                            .with_attributes(with_turn(
+                                vec![
+                                    KeyValue::new("channel", channel.clone()),
+                                    KeyValue::new("direction", direction.clone()),
+                                ],
+                                Some(turn),
+                            )),
+                        &self.parent_context(Some(turn)),
+                    );
+                    span.end();
+                }
                 self.channel_messages.add(
                     1,
                     &[
@@ -239,15 +364,23 @@ impl Observer for OtelObserver {
             ObserverEvent::HeartbeatTick => {
                 self.heartbeat_ticks.add(1, &[]);
             }
-            ObserverEvent::Error { component, message } => {
+            ObserverEvent::Error {
+                component,
+                message,
+                turn,
+            } => {
                 // Create an error span for visibility in trace backends
-                let mut span = tracer.build(
+                let mut span = tracer.build_with_context(
                     opentelemetry::trace::SpanBuilder::from_name("error")
                         .with_kind(SpanKind::Internal)
-                        .with_attributes(vec![
-                            KeyValue::new("component", component.clone()),
-                            KeyValue::new("error.message", message.clone()),
-                        ]),
+                        .with_attributes(with_turn(
+                            vec![
+                                KeyValue::new("component", component.clone()),
+                                KeyValue::new("error.message", message.clone()),
+                            ],
+                            turn.as_ref(),
+                        )),
+                    &self.parent_context(turn.as_ref()),
                 );
                 span.set_status(Status::error(message.clone()));
                 span.end();
@@ -275,11 +408,21 @@ impl Observer for OtelObserver {
     }
 
     fn flush(&self) {
-        if let Err(e) = self.tracer_provider.force_flush() {
-            tracing::warn!("OTel trace flush failed: {e}");
-        }
-        if let Err(e) = self.meter_provider.force_flush() {
-            tracing::warn!("OTel metric flush failed: {e}");
+        let traces = self.tracer_provider.force_flush().err();
+        let metrics = self.meter_provider.force_flush().err();
+        let errors: Vec<String> = [
+            traces.map(|e| format!("trace flush: {e}")),
+            metrics.map(|e| format!("metric flush: {e}")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if errors.is_empty() {
+            if self.flush_failing.swap(false, Ordering::Relaxed) {
+                tracing::info!("OTel export recovered");
+            }
+        } else {
+            self.flush_failed(&errors.join("; "));
         }
     }
 
@@ -288,6 +431,18 @@ impl Observer for OtelObserver {
     }
 }
 
+/// Span attributes plus the turn's `request_id` and `session.id`. Metrics
+/// never get these: one label value per turn would explode cardinality.
+fn with_turn(mut attributes: Vec<KeyValue>, turn: Option<&TurnContext>) -> Vec<KeyValue> {
+    if let Some(turn) = turn {
+        attributes.push(KeyValue::new("request_id", turn.request_id.clone()));
+        if let Some(session) = &turn.session_id {
+            attributes.push(KeyValue::new("session.id", session.clone()));
+        }
+    }
+    attributes
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -305,6 +460,7 @@ mod tests {
         OtelObserver::new(
             Some("http://127.0.0.1:19999"),
             Some("tinyclaw-test"),
+            &HashMap::new(),
         )
         .expect("observer creation should not fail with valid endpoint format")
     }
@@ -321,33 +477,47 @@ mod tests {
         obs.record_event(&ObserverEvent::AgentStart {
             provider: "openrouter".into(),
             model: "claude-sonnet".into(),
+            turn: None,
         });
         obs.record_event(&ObserverEvent::AgentEnd {
+            provider: "openrouter".into(),
             duration: Duration::from_millis(500),
             tokens_used: Some(100),
+            turn: None,
         });
         obs.record_event(&ObserverEvent::AgentEnd {
+            provider: "openrouter".into(),
             duration: Duration::ZERO,
             tokens_used: None,
+            turn: None,
         });
         obs.record_event(&ObserverEvent::ToolCall {
             tool: "shell".into(),
             duration: Duration::from_millis(10),
             success: true,
+            turn: None,
         });
         obs.record_event(&ObserverEvent::ToolCall {
             tool: "file_read".into(),
             duration: Duration::from_millis(5),
             success: false,
+            turn: Some(TurnContext::new(Some("20260101-abc"))),
         });
         obs.record_event(&ObserverEvent::ChannelMessage {
             channel: "telegram".into(),
             direction: "inbound".into(),
+            turn: None,
+        });
+        obs.record_event(&ObserverEvent::ChannelMessage {
+            channel: "telegram".into(),
+            direction: "outbound".into(),
+            turn: Some(TurnContext::new(None)),
         });
         obs.record_event(&ObserverEvent::HeartbeatTick);
         obs.record_event(&ObserverEvent::Error {
             component: "provider".into(),
             message: "timeout".into(),
+            turn: None,
         });
     }
 
@@ -368,4 +538,105 @@ mod tests {
         obs.flush();
     }
 
+    #[test]
+    fn tool_spans_nest_under_agent_span() {
+        let obs = test_observer();
+        obs.record_event(&ObserverEvent::AgentStart {
+            provider: "openrouter".into(),
+            model: "claude-sonnet".into(),
+            turn: None,
+        });
+        let parent = obs.parent_context(None);
+        assert!(parent.has_active_span());
+        let trace_id = parent.span().span_context().trace_id();
+
+        obs.record_event(&ObserverEvent::ToolCall {
+            tool: "shell".into(),
+            duration: Duration::from_millis(10),
+            success: true,
+            turn: None,
+        });
+        assert_eq!(
+            obs.parent_context(None).span().span_context().trace_id(),
+            trace_id
+        );
+
+        obs.record_event(&ObserverEvent::AgentEnd {
+            provider: "openrouter".into(),
+            duration: Duration::from_millis(50),
+            tokens_used: Some(42),
+            turn: None,
+        });
+        assert!(!obs.parent_context(None).has_active_span());
+    }
+
+    #[test]
+    fn concurrent_turns_keep_their_own_parent() {
+        let obs = test_observer();
+        let (first, second) = (TurnContext::new(Some("a")), TurnContext::new(Some("b")));
+        for turn in [&first, &second] {
+            obs.record_event(&ObserverEvent::AgentStart {
+                provider: "openrouter".into(),
+                model: "claude-sonnet".into(),
+                turn: Some(turn.clone()),
+            });
+        }
+        let trace_of = |turn: &TurnContext| {
+            obs.parent_context(Some(turn))
+                .span()
+                .span_context()
+                .trace_id()
+        };
+        let first_trace = trace_of(&first);
+        assert_ne!(first_trace, trace_of(&second));
+
+        obs.record_event(&ObserverEvent::AgentEnd {
+            provider: "openrouter".into(),
+            duration: Duration::from_millis(5),
+            tokens_used: None,
+            turn: Some(second.clone()),
+        });
+        assert_eq!(trace_of(&first), first_trace);
+        assert!(!obs.parent_context(Some(&second)).has_active_span());
+    }
+
+    #[test]
+    fn unreachable_collector_keeps_exporting() {
+        let obs = test_observer();
+        obs.record_event(&ObserverEvent::AgentStart {
+            provider: "openrouter".into(),
+            model: "claude-sonnet".into(),
+            turn: None,
+        });
+        obs.record_event(&ObserverEvent::ToolCall {
+            tool: "shell".into(),
+            duration: Duration::from_millis(10),
+            success: true,
+            turn: None,
+        });
+
+        let started = std::time::Instant::now();
+        obs.flush();
+        assert!(obs.flush_failing.load(Ordering::Relaxed));
+        assert!(started.elapsed() < EXPORT_TIMEOUT * 3);
+
+        // Still recording: the next flush tries the collector again
+        assert!(obs.parent_context(None).has_active_span());
+        obs.record_event(&ObserverEvent::HeartbeatTick);
+        obs.flush();
+    }
+
+    #[test]
+    fn turn_adds_request_and_session_attributes() {
+        let base = vec![KeyValue::new("tool.name", "shell")];
+        assert_eq!(with_turn(base.clone(), None).len(), 1);
+
+        let turn = TurnContext::new(Some("s1"));
+        let attributes = with_turn(base.clone(), Some(&turn));
+        assert!(attributes.contains(&KeyValue::new("request_id", turn.request_id.clone())));
+        assert!(attributes.contains(&KeyValue::new("session.id", "s1")));
+
+        let attributes = with_turn(base, Some(&TurnContext::new(None)));
+        assert_eq!(attributes.len(), 2);
+    }
 }
@@ -0,0 +1,151 @@
+use super::traits::{Observer, ObserverEvent, ObserverMetric};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+/// Aggregated calls of one tool.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolStat {
+    pub calls: u64,
+    pub successes: u64,
+    pub failures: u64,
+    pub total_duration_ms: u64,
+}
+
+/// Observer that tallies `ToolCall` events per tool. Clones share one table,
+/// so a handle can be kept for reporting while another feeds the observer.
+#[derive(Clone, Default)]
+pub struct ToolStats {
+    inner: Arc<Mutex<BTreeMap<String, ToolStat>>>,
+}
+
+impl ToolStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Per-tool totals keyed by tool name.
+    pub fn snapshot(&self) -> BTreeMap<String, ToolStat> {
+        self.inner.lock().map(|m| m.clone()).unwrap_or_default()
+    }
+
+    /// Rows sorted by call count, most used first.
+    pub fn ranked(&self) -> Vec<(String, ToolStat)> {
+        let mut rows: Vec<_> = self.snapshot().into_iter().collect();
+        rows.sort_by(|a, b| b.1.calls.cmp(&a.1.calls).then_with(|| a.0.cmp(&b.0)));
+        rows
+    }
+
+    /// Plain-text table for `/stats`.
+    pub fn table(&self) -> String {
+        let rows = self.ranked();
+        if rows.is_empty() {
+            return "No tool calls yet this session.".to_string();
+        }
+
+        let width = rows.iter().map(|(name, _)| name.len()).fold(4, usize::max);
+        let mut out = format!(
+            "{:<width$}  {:>5}  {:>4}  {:>4}  {:>9}  {:>9}\n",
+            "Tool", "Calls", "OK", "Fail", "Total", "Avg"
+        );
+        for (name, stat) in rows {
+            let avg = stat.total_duration_ms / stat.calls.max(1);
+            let _ = writeln!(
+                out,
+                "{name:<width$}  {:>5}  {:>4}  {:>4}  {:>6} ms  {avg:>6} ms",
+                stat.calls, stat.successes, stat.failures, stat.total_duration_ms
+            );
+        }
+        out.truncate(out.trim_end().len());
+        out
+    }
+}
+
+impl Observer for ToolStats {
+    fn record_event(&self, event: &ObserverEvent) {
+        let ObserverEvent::ToolCall {
+            tool,
+            duration,
+            success,
+            ..
+        } = event
+        else {
+            return;
+        };
+        if let Ok(mut map) = self.inner.lock() {
+            let stat = map.entry(tool.clone()).or_default();
+            stat.calls += 1;
+            if *success {
+                stat.successes += 1;
+            } else {
+                stat.failures += 1;
+            }
+            stat.total_duration_ms = stat
+                .total_duration_ms
+                .saturating_add(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX));
+        }
+    }
+
+    fn record_metric(&self, _metric: &ObserverMetric) {}
+
+    fn name(&self) -> &str {
+        "tool_stats"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn call(stats: &ToolStats, tool: &str, ms: u64, success: bool) {
+        stats.record_event(&ObserverEvent::ToolCall {
+            tool: tool.into(),
+            duration: Duration::from_millis(ms),
+            success,
+            turn: None,
+        });
+    }
+
+    #[test]
+    fn aggregates_calls_per_tool() {
+        let stats = ToolStats::new();
+        let handle = stats.clone();
+        call(&stats, "shell", 100, true);
+        call(&stats, "shell", 300, false);
+        call(&stats, "file_read", 5, true);
+        stats.record_event(&ObserverEvent::HeartbeatTick);
+
+        let snap = handle.snapshot();
+        assert_eq!(
+            snap["shell"],
+            ToolStat {
+                calls: 2,
+                successes: 1,
+                failures: 1,
+                total_duration_ms: 400,
+            }
+        );
+        assert_eq!(snap["file_read"].calls, 1);
+        assert_eq!(snap.len(), 2);
+    }
+
+    #[test]
+    fn table_sorted_by_call_count() {
+        let stats = ToolStats::new();
+        assert_eq!(stats.table(), "No tool calls yet this session.");
+
+        call(&stats, "file_read", 10, true);
+        for _ in 0..3 {
+            call(&stats, "shell", 20, true);
+        }
+        let table = stats.table();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3, "{table}");
+        assert!(lines[0].starts_with("Tool"));
+        assert!(lines[1].starts_with("shell "), "{table}");
+        assert!(lines[1].contains("60 ms"), "{table}");
+        assert!(lines[2].starts_with("file_read"), "{table}");
+    }
+}
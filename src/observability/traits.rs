@@ -1,29 +1,59 @@
 use std::time::Duration;
 
+/// Ties together the events of one user turn (a message, every tool call
+/// made to answer it, and any error that ended it), so backends can group
+/// them. Provider calls are not reported as events of their own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnContext {
+    /// Fresh for every turn
+    pub request_id: String,
+    pub session_id: Option<String>,
+}
+
+impl TurnContext {
+    pub fn new(session_id: Option<&str>) -> Self {
+        Self {
+            request_id: uuid::Uuid::new_v4().to_string(),
+            session_id: session_id.map(str::to_string),
+        }
+    }
+}
+
 /// Events the observer can record
 #[derive(Debug, Clone)]
 pub enum ObserverEvent {
     AgentStart {
         provider: String,
         model: String,
+        /// Set when the invocation answers a single turn (a channel
+        /// message); `None` for a CLI or TUI run spanning many turns
+        turn: Option<TurnContext>,
     },
     AgentEnd {
+        /// Provider that served the last response, which may be a fallback
+        provider: String,
         duration: Duration,
         tokens_used: Option<u64>,
+        /// The `turn` of the matching [`ObserverEvent::AgentStart`]
+        turn: Option<TurnContext>,
     },
     ToolCall {
         tool: String,
         duration: Duration,
         success: bool,
+        turn: Option<TurnContext>,
     },
     ChannelMessage {
         channel: String,
         direction: String,
+        turn: Option<TurnContext>,
     },
     HeartbeatTick,
     Error {
         component: String,
         message: String,
+        /// Set when the error ended a user turn
+        turn: Option<TurnContext>,
     },
 }
 
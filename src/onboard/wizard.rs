@@ -106,6 +106,12 @@ pub fn run_wizard() -> Result<Config> {
         secrets: secrets_config,
         browser: BrowserConfig::default(),
         identity: crate::config::IdentityConfig::default(),
+        tui: crate::config::TuiConfig::default(),
+        network: crate::config::NetworkConfig::default(),
+        security: crate::config::SecurityConfig::default(),
+        providers: crate::config::ProvidersConfig::default(),
+        session: crate::config::SessionConfig::default(),
+        check: crate::config::CheckConfig::default(),
     };
 
     println!(
@@ -169,7 +175,7 @@ pub fn run_channels_repair_wizard() -> Result<Config> {
     );
     println!();
 
-    let mut config = Config::load_or_init()?;
+    let mut config = Config::load_base_or_init()?;
 
     print_step(1, 1, "Channels (How You Talk to TinyClaw)");
     config.channels_config = setup_channels()?;
@@ -234,17 +240,14 @@ pub fn run_quick_setup(
     );
     println!();
 
-    let home = directories::UserDirs::new()
-        .map(|u| u.home_dir().to_path_buf())
-        .context("Could not find home directory")?;
-    let tinyclaw_dir = home.join(".tinyclaw");
+    let tinyclaw_dir = crate::config::tinyclaw_dir()?;
     let workspace_dir = tinyclaw_dir.join("workspace");
     let config_path = tinyclaw_dir.join("config.toml");
 
     fs::create_dir_all(&workspace_dir).context("Failed to create workspace directory")?;
 
     let provider_name = provider.unwrap_or("openrouter").to_string();
-    let model = default_model_for_provider(&provider_name);
+    let model = crate::providers::default_model(&provider_name).to_string();
     let memory_backend_name = memory_backend.unwrap_or("sqlite").to_string();
 
     // Create memory config based on backend choice
@@ -274,6 +277,8 @@ pub fn run_quick_setup(
             0
         },
         chunk_max_tokens: 512,
+        ttl_days: std::collections::HashMap::new(),
+        recall_limit: 5,
     };
 
     let config = Config {
@@ -297,6 +302,12 @@ pub fn run_quick_setup(
         secrets: SecretsConfig::default(),
         browser: BrowserConfig::default(),
         identity: crate::config::IdentityConfig::default(),
+        tui: crate::config::TuiConfig::default(),
+        network: crate::config::NetworkConfig::default(),
+        security: crate::config::SecurityConfig::default(),
+        providers: crate::config::ProvidersConfig::default(),
+        session: crate::config::SessionConfig::default(),
+        check: crate::config::CheckConfig::default(),
     };
 
     config.save()?;
@@ -394,20 +405,6 @@ pub fn run_quick_setup(
     Ok(config)
 }
 
-/// Pick a sensible default model for the given provider.
-fn default_model_for_provider(provider: &str) -> String {
-    match provider {
-        "anthropic" => "claude-sonnet-4-20250514".into(),
-        "openai" => "gpt-4o".into(),
-        "glm" | "zhipu" | "zai" | "z.ai" => "glm-5".into(),
-        "ollama" => "llama3.2".into(),
-        "groq" => "llama-3.3-70b-versatile".into(),
-        "deepseek" => "deepseek-chat".into(),
-        "gemini" | "google" | "google-gemini" => "gemini-2.0-flash".into(),
-        _ => "anthropic/claude-sonnet-4-20250514".into(),
-    }
-}
-
 // ── Step helpers ─────────────────────────────────────────────────
 
 fn print_step(current: u8, total: u8, title: &str) {
@@ -427,10 +424,7 @@ fn print_bullet(text: &str) {
 // ── Step 1: Workspace ────────────────────────────────────────────
 
 fn setup_workspace() -> Result<(PathBuf, PathBuf)> {
-    let home = directories::UserDirs::new()
-        .map(|u| u.home_dir().to_path_buf())
-        .context("Could not find home directory")?;
-    let default_dir = home.join(".tinyclaw");
+    let default_dir = crate::config::tinyclaw_dir()?;
 
     print_bullet(&format!(
         "Default location: {}",
@@ -1102,6 +1096,8 @@ fn setup_memory() -> Result<MemoryConfig> {
         keyword_weight: 0.3,
         embedding_cache_size: if backend == "sqlite" { 10000 } else { 0 },
         chunk_max_tokens: 512,
+        ttl_days: std::collections::HashMap::new(),
+        recall_limit: 5,
     })
 }
 
@@ -1124,6 +1120,7 @@ fn setup_channels() -> Result<ChannelsConfig> {
         whatsapp: None,
         email: None,
         irc: None,
+        ..ChannelsConfig::default()
     };
 
     loop {
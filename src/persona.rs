@@ -0,0 +1,280 @@
+//! Persona packs: named assistant configurations stored as TOML files in
+//! `<workspace>/personas/<name>.toml`.
+//!
+//! A pack bundles a base prompt, default model, temperature and the tools the
+//! assistant may use, so one install can switch between setups with
+//! `tinyclaw agent --persona <name>` or `/persona <name>` in the TUI.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Directory under the workspace holding persona files.
+pub const PERSONAS_DIR: &str = "personas";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Persona {
+    /// Pack name; defaults to the file stem and must match it when set
+    #[serde(default)]
+    pub name: String,
+    /// Base system prompt, used in place of the workspace-built prompt
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// Default model for this persona (an explicit `--model` still wins)
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Sampling temperature (0.0 - 2.0)
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    /// Tools this persona may use; all tools when unset
+    #[serde(default)]
+    pub tools: Option<Vec<String>>,
+}
+
+impl Persona {
+    /// Check the fields a pack file can get wrong.
+    pub fn validate(&self) -> Result<()> {
+        validate_name(&self.name)?;
+        if let Some(prompt) = &self.prompt {
+            if prompt.trim().is_empty() {
+                anyhow::bail!("Persona '{}': prompt is empty", self.name);
+            }
+        }
+        if let Some(model) = &self.model {
+            if model.is_empty() || model.chars().any(char::is_whitespace) {
+                anyhow::bail!("Persona '{}': invalid model name {model:?}", self.name);
+            }
+        }
+        if let Some(temperature) = self.temperature {
+            if !(0.0..=2.0).contains(&temperature) {
+                anyhow::bail!(
+                    "Persona '{}': temperature {temperature} is outside 0.0 - 2.0",
+                    self.name
+                );
+            }
+        }
+        if let Some(tools) = &self.tools {
+            if tools.iter().any(|t| t.trim().is_empty()) {
+                anyhow::bail!("Persona '{}': tool names must not be empty", self.name);
+            }
+        }
+        Ok(())
+    }
+
+    /// Tools from `available` this persona switches off. Errors when the pack
+    /// names a tool that does not exist.
+    pub fn disabled_tools<'a>(
+        &self,
+        available: impl IntoIterator<Item = &'a str>,
+    ) -> Result<HashSet<String>> {
+        let available: Vec<&str> = available.into_iter().collect();
+        let Some(enabled) = &self.tools else {
+            return Ok(HashSet::new());
+        };
+        let unknown: Vec<&str> = enabled
+            .iter()
+            .map(String::as_str)
+            .filter(|t| !available.contains(t))
+            .collect();
+        if !unknown.is_empty() {
+            anyhow::bail!(
+                "Persona '{}' enables unknown tool(s): {}",
+                self.name,
+                unknown.join(", ")
+            );
+        }
+        Ok(available
+            .into_iter()
+            .filter(|t| !enabled.iter().any(|e| e == t))
+            .map(String::from)
+            .collect())
+    }
+}
+
+/// Persona names are file stems, so keep them to a safe character set.
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        anyhow::bail!("Invalid persona name {name:?}: use letters, digits, '-' and '_' only");
+    }
+    Ok(())
+}
+
+fn persona_path(workspace_dir: &Path, name: &str) -> PathBuf {
+    workspace_dir
+        .join(PERSONAS_DIR)
+        .join(format!("{name}.toml"))
+}
+
+/// Names of all persona files in the workspace, sorted.
+pub fn list(workspace_dir: &Path) -> Result<Vec<String>> {
+    let dir = workspace_dir.join(PERSONAS_DIR);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names = Vec::new();
+    for entry in
+        std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("toml") {
+            continue;
+        }
+        if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+            names.push(stem.to_string());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Load and validate `personas/<name>.toml`. An unknown name lists the
+/// personas that do exist.
+pub fn load(workspace_dir: &Path, name: &str) -> Result<Persona> {
+    validate_name(name)?;
+    let path = persona_path(workspace_dir, name);
+    if !path.exists() {
+        let available = list(workspace_dir)?;
+        if available.is_empty() {
+            anyhow::bail!(
+                "Unknown persona '{name}': no personas in {}",
+                workspace_dir.join(PERSONAS_DIR).display()
+            );
+        }
+        anyhow::bail!(
+            "Unknown persona '{name}'. Available: {}",
+            available.join(", ")
+        );
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut persona: Persona = toml::from_str(&content)
+        .with_context(|| format!("Invalid persona file {}", path.display()))?;
+    if persona.name.is_empty() {
+        persona.name = name.to_string();
+    } else if persona.name != name {
+        anyhow::bail!(
+            "Persona file {} declares name '{}'",
+            path.display(),
+            persona.name
+        );
+    }
+    persona.validate()?;
+    Ok(persona)
+}
+
+/// Write a persona to `personas/<name>.toml`, creating the directory.
+pub fn save(workspace_dir: &Path, persona: &Persona) -> Result<PathBuf> {
+    persona.validate()?;
+    let path = persona_path(workspace_dir, &persona.name);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, toml::to_string_pretty(persona)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn reviewer() -> Persona {
+        Persona {
+            name: "reviewer".into(),
+            prompt: Some("You review code. Be terse.".into()),
+            model: Some("anthropic/claude-sonnet-4".into()),
+            temperature: Some(0.2),
+            tools: Some(vec!["file_read".into(), "git".into()]),
+        }
+    }
+
+    #[test]
+    fn persona_round_trips_through_file() {
+        let tmp = TempDir::new().unwrap();
+        let path = save(tmp.path(), &reviewer()).unwrap();
+        assert_eq!(path, tmp.path().join("personas/reviewer.toml"));
+
+        assert_eq!(load(tmp.path(), "reviewer").unwrap(), reviewer());
+        assert_eq!(list(tmp.path()).unwrap(), vec!["reviewer"]);
+    }
+
+    #[test]
+    fn name_defaults_to_file_stem() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join(PERSONAS_DIR);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("terse.toml"), "temperature = 0.1\n").unwrap();
+
+        let persona = load(tmp.path(), "terse").unwrap();
+        assert_eq!(persona.name, "terse");
+        assert_eq!(persona.temperature, Some(0.1));
+        assert!(persona.tools.is_none());
+    }
+
+    #[test]
+    fn unknown_persona_lists_available() {
+        let tmp = TempDir::new().unwrap();
+        let err = load(tmp.path(), "ghost").unwrap_err().to_string();
+        assert!(err.contains("no personas"), "{err}");
+
+        save(tmp.path(), &reviewer()).unwrap();
+        save(
+            tmp.path(),
+            &Persona {
+                name: "writer".into(),
+                ..Persona::default()
+            },
+        )
+        .unwrap();
+        let err = load(tmp.path(), "ghost").unwrap_err().to_string();
+        assert!(err.contains("Available: reviewer, writer"), "{err}");
+
+        assert!(load(tmp.path(), "../reviewer").is_err());
+    }
+
+    #[test]
+    fn invalid_pack_files_are_rejected() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join(PERSONAS_DIR);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, body) in [
+            ("hot", "temperature = 3.5\n"),
+            ("blank", "prompt = \"  \"\n"),
+            ("typo", "temprature = 0.5\n"),
+            ("mistyped", "model = 7\n"),
+            ("renamed", "name = \"other\"\n"),
+        ] {
+            std::fs::write(dir.join(format!("{name}.toml")), body).unwrap();
+            assert!(load(tmp.path(), name).is_err(), "{name} should be rejected");
+        }
+    }
+
+    #[test]
+    fn disabled_tools_inverts_enabled_list() {
+        let available = ["shell", "file_read", "git"];
+        let disabled = reviewer().disabled_tools(available).unwrap();
+        assert_eq!(disabled, HashSet::from(["shell".to_string()]));
+
+        assert!(Persona::default()
+            .disabled_tools(available)
+            .unwrap()
+            .is_empty());
+
+        let err = Persona {
+            name: "x".into(),
+            tools: Some(vec!["teleport".into()]),
+            ..Persona::default()
+        }
+        .disabled_tools(available)
+        .unwrap_err();
+        assert!(err.to_string().contains("teleport"));
+    }
+}
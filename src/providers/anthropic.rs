@@ -1,4 +1,6 @@
-use crate::providers::traits::{Provider, TokenUsage, UsageTracker};
+use crate::providers::traits::{
+    validate_stop_sequences, ModelInfo, Provider, ReasoningEffort, TokenUsage, UsageTracker,
+};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -9,6 +11,24 @@ pub struct AnthropicProvider {
     base_url: String,
     client: Client,
     usage_tracker: Option<UsageTracker>,
+    thinking: bool,
+    reasoning_effort: Option<ReasoningEffort>,
+    stop: Vec<String>,
+}
+
+/// Token budget for extended thinking; `max_tokens` must exceed it.
+const THINKING_BUDGET_TOKENS: u32 = 4096;
+
+/// Answer token limit, on top of the thinking budget when thinking is on.
+const MAX_ANSWER_TOKENS: u32 = 4096;
+
+/// Thinking budget for each reasoning effort. 1024 is the API minimum.
+fn thinking_budget(effort: Option<ReasoningEffort>) -> u32 {
+    match effort {
+        Some(ReasoningEffort::Low) => 1024,
+        Some(ReasoningEffort::Medium) | None => THINKING_BUDGET_TOKENS,
+        Some(ReasoningEffort::High) => 16_384,
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -21,6 +41,18 @@ struct ChatRequest {
     temperature: f64,
     #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<ThinkingConfig>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop_sequences: Vec<String>,
+}
+
+/// Extended-thinking request parameters.
+#[derive(Debug, Serialize)]
+struct ThinkingConfig {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    budget_tokens: u32,
 }
 
 /// SSE event data for Anthropic streaming
@@ -53,7 +85,51 @@ struct StreamMessageDeltaEvent {
 #[derive(Debug, Serialize)]
 struct Message {
     role: String,
-    content: String,
+    content: MessageContent,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum MessageContent {
+    Text(String),
+    Blocks(Vec<RequestBlock>),
+}
+
+impl From<String> for MessageContent {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+/// Content block sent in a request message.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum RequestBlock {
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+    },
+}
+
+impl From<&crate::providers::ChatMessage> for Message {
+    /// Results of native tool calls become a user message with a
+    /// `tool_result` block; results of `<tool_call>` blocks go as user text.
+    fn from(m: &crate::providers::ChatMessage) -> Self {
+        if let (Some(id), "tool") = (&m.tool_call_id, m.role.as_str()) {
+            return Self {
+                role: "user".into(),
+                content: MessageContent::Blocks(vec![RequestBlock::ToolResult {
+                    tool_use_id: id.clone(),
+                    content: m.content.clone(),
+                }]),
+            };
+        }
+        let (role, content) = m.inline_tool_results();
+        Self {
+            role: role.to_string(),
+            content: content.into_owned().into(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -73,9 +149,48 @@ struct AnthropicUsage {
 
 #[derive(Debug, Deserialize)]
 struct ContentBlock {
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
     text: String,
+    #[serde(default)]
+    thinking: String,
 }
 
+/// Join response blocks into one answer, wrapping thinking blocks in
+/// `<thinking>` tags ahead of the text, or dropping them when not shown.
+fn render_content(blocks: Vec<ContentBlock>, show_thinking: bool) -> Option<String> {
+    let mut out = String::new();
+    let mut has_text = false;
+    for block in blocks {
+        if block.kind == "thinking" {
+            if !show_thinking {
+                continue;
+            }
+            out.push_str("<thinking>\n");
+            out.push_str(&block.thinking);
+            out.push_str("\n</thinking>\n\n");
+        } else if !has_text {
+            out.push_str(&block.text);
+            has_text = true;
+        }
+    }
+    has_text.then_some(out)
+}
+
+/// Anthropic models with their context window and list price (USD per million
+/// input/output tokens). Anthropic has no public pricing endpoint, so this is
+/// maintained by hand.
+const KNOWN_MODELS: &[(&str, u64, f64, f64)] = &[
+    ("claude-opus-4-20250514", 200_000, 15.0, 75.0),
+    ("claude-sonnet-4-20250514", 200_000, 3.0, 15.0),
+    ("claude-3-7-sonnet-20250219", 200_000, 3.0, 15.0),
+    ("claude-3-5-sonnet-20241022", 200_000, 3.0, 15.0),
+    ("claude-3-5-haiku-20241022", 200_000, 0.8, 4.0),
+    ("claude-3-opus-20240229", 200_000, 15.0, 75.0),
+    ("claude-3-haiku-20240307", 200_000, 0.25, 1.25),
+];
+
 impl AnthropicProvider {
     pub fn new(api_key: Option<&str>) -> Self {
         Self::with_base_url(api_key, None)
@@ -92,12 +207,31 @@ impl AnthropicProvider {
                 .filter(|k| !k.is_empty())
                 .map(ToString::to_string),
             base_url,
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(120))
-                .connect_timeout(std::time::Duration::from_secs(10))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
+            client: super::http::shared_client(std::time::Duration::from_secs(120)),
             usage_tracker: None,
+            thinking: false,
+            reasoning_effort: None,
+            stop: Vec::new(),
+        }
+    }
+
+    /// Request parameters that depend on whether extended thinking is on:
+    /// `(max_tokens, temperature, thinking)`. A reasoning effort turns
+    /// thinking on with its budget even when the reasoning is not shown. The
+    /// API requires temperature 1 while thinking.
+    fn sampling(&self, temperature: f64) -> (u32, f64, Option<ThinkingConfig>) {
+        if self.thinking || self.reasoning_effort.is_some() {
+            let budget_tokens = thinking_budget(self.reasoning_effort);
+            (
+                MAX_ANSWER_TOKENS + budget_tokens,
+                1.0,
+                Some(ThinkingConfig {
+                    kind: "enabled",
+                    budget_tokens,
+                }),
+            )
+        } else {
+            (MAX_ANSWER_TOKENS, temperature, None)
         }
     }
 
@@ -108,6 +242,10 @@ impl AnthropicProvider {
 
 #[async_trait]
 impl Provider for AnthropicProvider {
+    fn name(&self) -> &str {
+        "anthropic"
+    }
+
     async fn chat_with_system(
         &self,
         system_prompt: Option<&str>,
@@ -121,16 +259,19 @@ impl Provider for AnthropicProvider {
             )
         })?;
 
+        let (max_tokens, temperature, thinking) = self.sampling(temperature);
         let request = ChatRequest {
             model: model.to_string(),
-            max_tokens: 4096,
+            max_tokens,
             system: system_prompt.map(ToString::to_string),
             messages: vec![Message {
                 role: "user".to_string(),
-                content: message.to_string(),
+                content: message.to_string().into(),
             }],
             temperature,
             stream: None,
+            thinking,
+            stop_sequences: self.stop.clone(),
         };
 
         let mut request = self
@@ -162,11 +303,7 @@ impl Provider for AnthropicProvider {
             });
         }
 
-        chat_response
-            .content
-            .into_iter()
-            .next()
-            .map(|c| c.text)
+        render_content(chat_response.content, self.thinking)
             .ok_or_else(|| anyhow::anyhow!("No response from Anthropic"))
     }
 
@@ -174,6 +311,7 @@ impl Provider for AnthropicProvider {
         true
     }
 
+    #[allow(clippy::too_many_lines)]
     async fn chat_with_history_stream(
         &self,
         history: &[crate::providers::ChatMessage],
@@ -192,20 +330,20 @@ impl Provider for AnthropicProvider {
             if msg.role == "system" {
                 system_prompt = Some(msg.content.clone());
             } else {
-                messages.push(Message {
-                    role: msg.role.clone(),
-                    content: msg.content.clone(),
-                });
+                messages.push(Message::from(msg));
             }
         }
 
+        let (max_tokens, temperature, thinking) = self.sampling(temperature);
         let request_body = ChatRequest {
             model: model.to_string(),
-            max_tokens: 4096,
+            max_tokens,
             system: system_prompt,
             messages,
             temperature,
             stream: Some(true),
+            thinking,
+            stop_sequences: self.stop.clone(),
         };
 
         let mut req = self
@@ -229,6 +367,7 @@ impl Provider for AnthropicProvider {
 
         let mut full_text = String::new();
         let mut buffer = String::new();
+        let mut in_thinking = false;
 
         // Read SSE chunks
         let mut stream = response;
@@ -255,14 +394,43 @@ impl Provider for AnthropicProvider {
                     let event_type = event.get("type").and_then(|t| t.as_str()).unwrap_or("");
 
                     match event_type {
+                        "content_block_start" => {
+                            let kind = event
+                                .get("content_block")
+                                .and_then(|b| b.get("type"))
+                                .and_then(|t| t.as_str());
+                            if kind == Some("thinking") {
+                                in_thinking = true;
+                                if self.thinking {
+                                    full_text.push_str("<thinking>\n");
+                                    let _ = token_tx.send("<thinking>\n".to_string()).await;
+                                }
+                            }
+                        }
+                        // Reasoning requested only for its effort stays hidden
+                        "content_block_delta" if in_thinking && !self.thinking => {}
                         "content_block_delta" => {
                             if let Some(delta) = event.get("delta") {
-                                if let Some(text) = delta.get("text").and_then(|t| t.as_str()) {
+                                let text = delta
+                                    .get("text")
+                                    .or_else(|| delta.get("thinking"))
+                                    .and_then(|t| t.as_str());
+                                if let Some(text) = text {
                                     full_text.push_str(text);
                                     let _ = token_tx.send(text.to_string()).await;
                                 }
                             }
                         }
+                        "content_block_stop" if in_thinking => {
+                            in_thinking = false;
+                            if self.thinking {
+                                full_text.push_str("\n</thinking>\n\n");
+                                let _ = token_tx.send("\n</thinking>\n\n".to_string()).await;
+                            }
+                        }
+                        // The message is complete, whether by end of turn, max
+                        // tokens or a stop sequence
+                        "message_stop" => return Ok(full_text),
                         "message_delta" => {
                             // End of message — may contain usage
                             if let Some(usage) = event.get("usage") {
@@ -305,11 +473,58 @@ impl Provider for AnthropicProvider {
     fn set_usage_tracker(&mut self, tracker: UsageTracker) {
         self.usage_tracker = Some(tracker);
     }
+
+    fn set_thinking(&mut self, enabled: bool) {
+        self.thinking = enabled;
+    }
+
+    fn set_reasoning_effort(&mut self, effort: Option<ReasoningEffort>) {
+        self.reasoning_effort = effort;
+    }
+
+    fn set_stop_sequences(&mut self, stop: &[String]) -> anyhow::Result<()> {
+        validate_stop_sequences("Anthropic", stop, None)?;
+        self.stop = stop.to_vec();
+        Ok(())
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        Ok(KNOWN_MODELS
+            .iter()
+            .map(|(id, context, prompt, completion)| ModelInfo {
+                id: (*id).to_string(),
+                context_length: Some(*context),
+                prompt_price_per_m: Some(*prompt),
+                completion_price_per_m: Some(*completion),
+            })
+            .collect())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::providers::ChatMessage;
+
+    #[test]
+    fn tool_messages_map_to_tool_result_blocks_only_with_a_call_id() {
+        let native =
+            serde_json::to_value(Message::from(&ChatMessage::tool_result("toolu_1", "a.txt")))
+                .unwrap();
+        assert_eq!(
+            native,
+            serde_json::json!({
+                "role": "user",
+                "content": [{"type": "tool_result", "tool_use_id": "toolu_1", "content": "a.txt"}]
+            })
+        );
+
+        let inline = serde_json::to_value(Message::from(&ChatMessage::tool("a.txt"))).unwrap();
+        assert_eq!(
+            inline,
+            serde_json::json!({"role": "user", "content": "[Tool results]\na.txt"})
+        );
+    }
 
     #[test]
     fn creates_with_key() {
@@ -396,10 +611,12 @@ mod tests {
             system: None,
             messages: vec![Message {
                 role: "user".to_string(),
-                content: "hello".to_string(),
+                content: "hello".to_string().into(),
             }],
             temperature: 0.7,
             stream: None,
+            thinking: None,
+            stop_sequences: vec![],
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(
@@ -418,13 +635,35 @@ mod tests {
             system: Some("You are TinyClaw".to_string()),
             messages: vec![Message {
                 role: "user".to_string(),
-                content: "hello".to_string(),
+                content: "hello".to_string().into(),
             }],
             temperature: 0.7,
             stream: None,
+            thinking: None,
+            stop_sequences: vec![],
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"system\":\"You are TinyClaw\""));
+        assert!(!json.contains("stop_sequences"));
+    }
+
+    #[test]
+    fn chat_request_serializes_stop_sequences() {
+        let mut p = AnthropicProvider::new(Some("sk-ant-test"));
+        p.set_stop_sequences(&["</answer>".into()]).unwrap();
+        let req = ChatRequest {
+            model: "claude-3-opus".to_string(),
+            max_tokens: 4096,
+            system: None,
+            messages: vec![],
+            temperature: 0.7,
+            stream: Some(true),
+            thinking: None,
+            stop_sequences: p.stop.clone(),
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["stop_sequences"], serde_json::json!(["</answer>"]));
+        assert!(p.set_stop_sequences(&[String::new()]).is_err());
     }
 
     #[test]
@@ -452,6 +691,65 @@ mod tests {
         assert_eq!(resp.content[1].text, "Second");
     }
 
+    #[test]
+    fn thinking_blocks_are_wrapped_before_text() {
+        let json = r#"{"content":[
+            {"type":"thinking","thinking":"Check units first.","signature":"sig"},
+            {"type":"text","text":"42 km"}
+        ]}"#;
+        let resp: ChatResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            render_content(resp.content, true).unwrap(),
+            "<thinking>\nCheck units first.\n</thinking>\n\n42 km"
+        );
+        let resp: ChatResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(render_content(resp.content, false).unwrap(), "42 km");
+    }
+
+    #[test]
+    fn thinking_request_forces_budget_and_temperature() {
+        let mut p = AnthropicProvider::new(Some("sk-ant-test"));
+        let (max_tokens, temperature, thinking) = p.sampling(0.3);
+        assert_eq!((max_tokens, temperature), (MAX_ANSWER_TOKENS, 0.3));
+        assert!(thinking.is_none());
+
+        p.set_thinking(true);
+        let (max_tokens, temperature, thinking) = p.sampling(0.3);
+        assert!(max_tokens > THINKING_BUDGET_TOKENS);
+        assert!((temperature - 1.0).abs() < f64::EPSILON);
+        let json = serde_json::to_value(thinking.unwrap()).unwrap();
+        assert_eq!(json["type"], "enabled");
+        assert_eq!(json["budget_tokens"], THINKING_BUDGET_TOKENS);
+    }
+
+    #[test]
+    fn reasoning_effort_maps_to_thinking_budget() {
+        let mut p = AnthropicProvider::new(Some("sk-ant-test"));
+        for (effort, budget) in [
+            (ReasoningEffort::Low, 1024),
+            (ReasoningEffort::Medium, THINKING_BUDGET_TOKENS),
+            (ReasoningEffort::High, 16_384),
+        ] {
+            p.set_reasoning_effort(Some(effort));
+            let (max_tokens, temperature, thinking) = p.sampling(0.3);
+            assert_eq!(max_tokens, MAX_ANSWER_TOKENS + budget);
+            assert!((temperature - 1.0).abs() < f64::EPSILON);
+            let json = serde_json::to_value(thinking.unwrap()).unwrap();
+            assert_eq!(json["budget_tokens"], budget, "{effort}");
+        }
+
+        p.set_reasoning_effort(None);
+        assert!(p.sampling(0.3).2.is_none());
+    }
+
+    #[tokio::test]
+    async fn list_models_returns_known_set_without_key() {
+        let p = AnthropicProvider::new(None);
+        let models = p.list_models().await.unwrap();
+        assert!(models.iter().any(|m| m.id == "claude-sonnet-4-20250514"));
+        assert!(models.iter().all(|m| m.context_length.is_some()));
+    }
+
     #[test]
     fn temperature_range_serializes() {
         for temp in [0.0, 0.5, 1.0, 2.0] {
@@ -462,6 +760,8 @@ mod tests {
                 messages: vec![],
                 temperature: temp,
                 stream: None,
+                thinking: None,
+                stop_sequences: vec![],
             };
             let json = serde_json::to_string(&req).unwrap();
             assert!(json.contains(&format!("{temp}")));
@@ -0,0 +1,446 @@
+use super::traits::{ChatMessage, ModelInfo, ReasoningEffort, Served, UsageTracker};
+use super::Provider;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Cache directory under the workspace.
+pub const CACHE_DIR: &str = "cache/responses";
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    created_at: DateTime<Utc>,
+    response: String,
+}
+
+/// Serves repeated identical requests from disk.
+///
+/// Responses are stored under `<workspace>/cache/responses/<key>.json`, where
+/// the key hashes the provider name, model, temperature, sampling options and
+/// message roles and contents. Entries older than the TTL are ignored and
+/// replaced. Concurrent misses for the same key wait for the first to fill
+/// it rather than each calling the provider. Cache read/write failures are
+/// logged and never fail a request.
+pub struct CachedProvider {
+    inner: Box<dyn Provider>,
+    provider_name: String,
+    dir: PathBuf,
+    ttl: Duration,
+    seed: Option<u64>,
+    stop: Vec<String>,
+    thinking: bool,
+    reasoning_effort: Option<ReasoningEffort>,
+    /// One lock per key being fetched, so a miss is filled only once
+    in_flight: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl CachedProvider {
+    pub fn new(
+        inner: Box<dyn Provider>,
+        provider_name: &str,
+        workspace_dir: &Path,
+        ttl: Duration,
+    ) -> Self {
+        Self {
+            inner,
+            provider_name: provider_name.to_string(),
+            dir: workspace_dir.join(CACHE_DIR),
+            ttl,
+            seed: None,
+            stop: Vec::new(),
+            thinking: false,
+            reasoning_effort: None,
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Hex SHA-256 over everything that shapes the response. Message
+    /// timestamps are left out so a replayed conversation hits.
+    fn key(&self, messages: &[ChatMessage], model: &str, temperature: f64) -> String {
+        let messages: Vec<(&str, &str)> = messages
+            .iter()
+            .map(|m| (m.role.as_str(), m.content.as_str()))
+            .collect();
+        let material = serde_json::json!({
+            "provider": self.provider_name,
+            "model": model,
+            "temperature": temperature,
+            "seed": self.seed,
+            "stop": self.stop,
+            "thinking": self.thinking,
+            "reasoning_effort": self.reasoning_effort,
+            "messages": messages,
+        });
+        hex::encode(Sha256::digest(material.to_string().as_bytes()))
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+
+    async fn lookup(&self, key: &str) -> Option<String> {
+        let raw = tokio::fs::read_to_string(self.path(key)).await.ok()?;
+        let entry: CacheEntry = serde_json::from_str(&raw).ok()?;
+        let age = Utc::now().signed_duration_since(entry.created_at);
+        if age.to_std().map_or(true, |age| age > self.ttl) {
+            return None;
+        }
+        tracing::debug!(key, "Response cache hit");
+        Some(entry.response)
+    }
+
+    /// Serve `key` from the cache (as provider `cache`), or run `request`
+    /// and store its response. A request for a key another caller is already
+    /// fetching waits for that fetch and then reads its result.
+    async fn fill(
+        &self,
+        key: &str,
+        request: impl Future<Output = anyhow::Result<Served>>,
+    ) -> anyhow::Result<Served> {
+        if let Some(cached) = self.lookup(key).await {
+            return Ok(Served::new(cached, "cache"));
+        }
+        let gate = Arc::clone(
+            self.in_flight
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .entry(key.to_string())
+                .or_default(),
+        );
+        let result = {
+            let _fetching = gate.lock().await;
+            match self.lookup(key).await {
+                Some(cached) => Ok(Served::new(cached, "cache")),
+                None => match request.await {
+                    Ok(served) => {
+                        self.store(key, &served.text).await;
+                        Ok(served)
+                    }
+                    Err(e) => Err(e),
+                },
+            }
+        };
+        let mut in_flight = self
+            .in_flight
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        // Only the map and this caller still hold it: nobody is waiting
+        if Arc::strong_count(&gate) == 2 {
+            in_flight.remove(key);
+        }
+        result
+    }
+
+    async fn store(&self, key: &str, response: &str) {
+        let entry = CacheEntry {
+            created_at: Utc::now(),
+            response: response.to_string(),
+        };
+        let write = async {
+            tokio::fs::create_dir_all(&self.dir).await?;
+            let json = serde_json::to_string(&entry)?;
+            // Write then rename so a concurrent reader never sees half a file
+            let tmp = self.dir.join(format!("{key}.tmp"));
+            tokio::fs::write(&tmp, json).await?;
+            tokio::fs::rename(&tmp, self.path(key)).await?;
+            anyhow::Ok(())
+        };
+        if let Err(e) = write.await {
+            tracing::warn!("Failed to write response cache entry: {e}");
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for CachedProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn set_usage_tracker(&mut self, tracker: UsageTracker) {
+        self.inner.set_usage_tracker(tracker);
+    }
+
+    fn set_thinking(&mut self, enabled: bool) {
+        self.thinking = enabled;
+        self.inner.set_thinking(enabled);
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+        self.inner.set_seed(seed);
+    }
+
+    fn set_reasoning_effort(&mut self, effort: Option<ReasoningEffort>) {
+        self.reasoning_effort = effort;
+        self.inner.set_reasoning_effort(effort);
+    }
+
+    fn set_stop_sequences(&mut self, stop: &[String]) -> anyhow::Result<()> {
+        self.inner.set_stop_sequences(stop)?;
+        self.stop = stop.to_vec();
+        Ok(())
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.inner.supports_streaming()
+    }
+
+    fn supports_system_prompt(&self, model: &str) -> bool {
+        self.inner.supports_system_prompt(model)
+    }
+
+    async fn warmup(&self) -> anyhow::Result<()> {
+        self.inner.warmup().await
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        self.inner.list_models().await
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system) = system_prompt {
+            messages.push(ChatMessage::system(system));
+        }
+        messages.push(ChatMessage::user(message));
+        let key = self.key(&messages, model, temperature);
+        let request = async {
+            let text = self
+                .inner
+                .chat_with_system(system_prompt, message, model, temperature)
+                .await?;
+            Ok(Served::new(text, self.inner.name()))
+        };
+        self.fill(&key, request).await.map(|served| served.text)
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        self.chat_with_history_served(messages, model, temperature)
+            .await
+            .map(|served| served.text)
+    }
+
+    async fn chat_with_history_served(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<Served> {
+        let key = self.key(messages, model, temperature);
+        let request = self
+            .inner
+            .chat_with_history_served(messages, model, temperature);
+        self.fill(&key, request).await
+    }
+
+    async fn chat_with_history_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        token_tx: tokio::sync::mpsc::Sender<String>,
+    ) -> anyhow::Result<String> {
+        self.chat_with_history_stream_served(messages, model, temperature, token_tx)
+            .await
+            .map(|served| served.text)
+    }
+
+    async fn chat_with_history_stream_served(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        token_tx: tokio::sync::mpsc::Sender<String>,
+    ) -> anyhow::Result<Served> {
+        let key = self.key(messages, model, temperature);
+        let request = self.inner.chat_with_history_stream_served(
+            messages,
+            model,
+            temperature,
+            token_tx.clone(),
+        );
+        let served = self.fill(&key, request).await?;
+        if served.provider == "cache" {
+            let _ = token_tx.send(served.text.clone()).await;
+        }
+        Ok(served)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    /// Answers with a numbered reply so repeated upstream calls are visible.
+    struct CountingProvider {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Provider for CountingProvider {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(format!("reply {n} to {message}"))
+        }
+    }
+
+    fn cached(dir: &Path, ttl: Duration) -> (CachedProvider, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let inner = Box::new(CountingProvider {
+            calls: calls.clone(),
+        });
+        (CachedProvider::new(inner, "mock", dir, ttl), calls)
+    }
+
+    #[tokio::test]
+    async fn identical_requests_hit_the_cache() {
+        let tmp = TempDir::new().unwrap();
+        let (provider, calls) = cached(tmp.path(), Duration::from_secs(60));
+        let history = [ChatMessage::system("be brief"), ChatMessage::user("hi")];
+
+        let first = provider
+            .chat_with_history_served(&history, "m", 0.7)
+            .await
+            .unwrap();
+        assert_eq!(first.provider, "counting");
+        let first = first.text;
+        // Same conversation built later: timestamps differ, key does not
+        let again = [ChatMessage::system("be brief"), ChatMessage::user("hi")];
+        let second = provider
+            .chat_with_history_served(&again, "m", 0.7)
+            .await
+            .unwrap();
+        assert_eq!(first, "reply 1 to hi");
+        assert_eq!(second.text, first);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(provider.name(), "counting");
+        assert_eq!(second.provider, "cache");
+
+        // A fresh wrapper over the same directory reads the stored entry
+        let (reopened, reopened_calls) = cached(tmp.path(), Duration::from_secs(60));
+        assert_eq!(
+            reopened
+                .chat_with_history(&history, "m", 0.7)
+                .await
+                .unwrap(),
+            first
+        );
+        assert_eq!(reopened_calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn different_requests_miss_the_cache() {
+        let tmp = TempDir::new().unwrap();
+        let (mut provider, calls) = cached(tmp.path(), Duration::from_secs(60));
+        let history = [ChatMessage::user("hi")];
+
+        provider
+            .chat_with_history(&history, "m", 0.7)
+            .await
+            .unwrap();
+        provider
+            .chat_with_history(&history, "other", 0.7)
+            .await
+            .unwrap();
+        provider
+            .chat_with_history(&history, "m", 0.2)
+            .await
+            .unwrap();
+        provider
+            .chat_with_history(&[ChatMessage::user("hello")], "m", 0.7)
+            .await
+            .unwrap();
+        provider.set_seed(Some(7));
+        provider
+            .chat_with_history(&history, "m", 0.7)
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn expired_entries_are_refetched() {
+        let tmp = TempDir::new().unwrap();
+        let (provider, calls) = cached(tmp.path(), Duration::ZERO);
+        let history = [ChatMessage::user("hi")];
+
+        provider
+            .chat_with_history(&history, "m", 0.7)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let second = provider
+            .chat_with_history(&history, "m", 0.7)
+            .await
+            .unwrap();
+        assert_eq!(second, "reply 2 to hi");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn concurrent_misses_call_the_provider_once() {
+        let tmp = TempDir::new().unwrap();
+        let (provider, calls) = cached(tmp.path(), Duration::from_secs(60));
+        let history = [ChatMessage::user("hi")];
+
+        let (first, second) = tokio::join!(
+            provider.chat_with_history(&history, "m", 0.7),
+            provider.chat_with_history(&history, "m", 0.7),
+        );
+        assert_eq!(first.unwrap(), "reply 1 to hi");
+        assert_eq!(second.unwrap(), "reply 1 to hi");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(provider.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn streaming_hit_replays_text_through_token_channel() {
+        let tmp = TempDir::new().unwrap();
+        let (provider, calls) = cached(tmp.path(), Duration::from_secs(60));
+        let history = [ChatMessage::user("hi")];
+        provider
+            .chat_with_history(&history, "m", 0.7)
+            .await
+            .unwrap();
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        let streamed = provider
+            .chat_with_history_stream(&history, "m", 0.7, tx)
+            .await
+            .unwrap();
+        assert_eq!(streamed, "reply 1 to hi");
+        assert_eq!(rx.recv().await.as_deref(), Some("reply 1 to hi"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
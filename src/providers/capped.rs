@@ -0,0 +1,290 @@
+use super::traits::{ChatMessage, ModelInfo, ReasoningEffort, UsageTracker};
+use super::Provider;
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+/// Stops a streamed reply once it passes `[providers] max_response_chars`.
+///
+/// A model stuck in a loop can stream tens of thousands of tokens. This
+/// watches the token stream of any provider and, at the cap, drops the
+/// request (so nothing more is read or billed) and returns what arrived so
+/// far followed by a truncation notice. Non-streaming calls are complete
+/// before they can be measured and pass through unchanged.
+pub struct CappedProvider {
+    inner: Box<dyn Provider>,
+    max_chars: usize,
+}
+
+impl CappedProvider {
+    pub fn new(inner: Box<dyn Provider>, max_chars: usize) -> Self {
+        Self { inner, max_chars }
+    }
+}
+
+/// `provider` wrapped in [`CappedProvider`] when a cap is set; otherwise
+/// unchanged.
+pub fn with_response_cap(
+    provider: Box<dyn Provider>,
+    max_chars: Option<usize>,
+) -> Box<dyn Provider> {
+    match max_chars {
+        Some(max_chars) => Box::new(CappedProvider::new(provider, max_chars)),
+        None => provider,
+    }
+}
+
+/// Appended to (and streamed after) a reply cut off at the cap.
+fn truncation_notice(max_chars: usize) -> String {
+    format!("\n\n[Response truncated: it passed the {max_chars}-character limit]")
+}
+
+/// Characters still allowed in a streamed reply.
+struct Budget {
+    left: usize,
+}
+
+impl Budget {
+    /// The part of `token` that fits, and whether any of it had to be cut.
+    fn fit<'a>(&mut self, token: &'a str) -> (&'a str, bool) {
+        if let Some((end, _)) = token.char_indices().nth(self.left) {
+            self.left = 0;
+            (&token[..end], true)
+        } else {
+            self.left -= token.chars().count();
+            (token, false)
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for CappedProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn set_usage_tracker(&mut self, tracker: UsageTracker) {
+        self.inner.set_usage_tracker(tracker);
+    }
+
+    fn set_thinking(&mut self, enabled: bool) {
+        self.inner.set_thinking(enabled);
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.inner.set_seed(seed);
+    }
+
+    fn set_reasoning_effort(&mut self, effort: Option<ReasoningEffort>) {
+        self.inner.set_reasoning_effort(effort);
+    }
+
+    fn set_stop_sequences(&mut self, stop: &[String]) -> anyhow::Result<()> {
+        self.inner.set_stop_sequences(stop)
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.inner.supports_streaming()
+    }
+
+    fn supports_system_prompt(&self, model: &str) -> bool {
+        self.inner.supports_system_prompt(model)
+    }
+
+    async fn warmup(&self) -> anyhow::Result<()> {
+        self.inner.warmup().await
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        self.inner.list_models().await
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        self.inner
+            .chat_with_system(system_prompt, message, model, temperature)
+            .await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        self.inner
+            .chat_with_history(messages, model, temperature)
+            .await
+    }
+
+    async fn chat_with_history_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        token_tx: mpsc::Sender<String>,
+    ) -> anyhow::Result<String> {
+        let (inner_tx, mut inner_rx) = mpsc::channel::<String>(64);
+        let stream = self
+            .inner
+            .chat_with_history_stream(messages, model, temperature, inner_tx);
+        tokio::pin!(stream);
+
+        let mut budget = Budget {
+            left: self.max_chars,
+        };
+        let mut received = String::new();
+        let mut finished = None;
+        loop {
+            let token = tokio::select! {
+                biased;
+                Some(token) = inner_rx.recv() => token,
+                result = &mut stream, if finished.is_none() => {
+                    finished = Some(result);
+                    continue;
+                }
+                else => break,
+            };
+            let (head, cut) = budget.fit(&token);
+            if !head.is_empty() {
+                received.push_str(head);
+                let _ = token_tx.send(head.to_string()).await;
+            }
+            if cut {
+                // Returning drops the inner stream, which ends the request
+                tracing::warn!(
+                    provider = self.inner.name(),
+                    max_chars = self.max_chars,
+                    "Response passed max_response_chars; truncating it"
+                );
+                let notice = truncation_notice(self.max_chars);
+                received.push_str(&notice);
+                let _ = token_tx.send(notice).await;
+                return Ok(received);
+            }
+        }
+        finished.unwrap_or_else(|| Ok(received))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Streams `token` forever, counting how many it sent.
+    struct RunawayProvider {
+        token: &'static str,
+        sent: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Provider for RunawayProvider {
+        fn name(&self) -> &str {
+            "runaway"
+        }
+
+        fn supports_streaming(&self) -> bool {
+            true
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok(self.token.into())
+        }
+
+        async fn chat_with_history_stream(
+            &self,
+            _messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+            token_tx: mpsc::Sender<String>,
+        ) -> anyhow::Result<String> {
+            loop {
+                self.sent.fetch_add(1, Ordering::SeqCst);
+                let _ = token_tx.send(self.token.into()).await;
+                tokio::task::yield_now().await;
+            }
+        }
+    }
+
+    async fn stream(provider: &dyn Provider) -> (String, String) {
+        let (tx, mut rx) = mpsc::channel(1024);
+        let reply = tokio::time::timeout(
+            Duration::from_secs(5),
+            provider.chat_with_history_stream(&[ChatMessage::user("go")], "m", 0.7, tx),
+        )
+        .await
+        .expect("the cap should end the stream")
+        .unwrap();
+        let mut streamed = String::new();
+        while let Some(token) = rx.recv().await {
+            streamed.push_str(&token);
+        }
+        (reply, streamed)
+    }
+
+    #[tokio::test]
+    async fn runaway_stream_is_cut_at_the_cap() {
+        let sent = Arc::new(AtomicUsize::new(0));
+        let provider = with_response_cap(
+            Box::new(RunawayProvider {
+                token: "ab€",
+                sent: Arc::clone(&sent),
+            }),
+            Some(10),
+        );
+
+        let (reply, streamed) = stream(provider.as_ref()).await;
+        assert_eq!(reply, format!("ab€ab€ab€a{}", truncation_notice(10)));
+        assert_eq!(streamed, reply, "the UI sees what the caller gets");
+
+        // The request was dropped, not left running in the background
+        let after = sent.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(sent.load(Ordering::SeqCst), after);
+    }
+
+    /// Answers with one fixed reply, streamed in a single token.
+    struct FixedProvider(&'static str);
+
+    #[async_trait]
+    impl Provider for FixedProvider {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok(self.0.into())
+        }
+    }
+
+    #[tokio::test]
+    async fn replies_within_the_cap_pass_through() {
+        let provider = with_response_cap(Box::new(FixedProvider("ten chars!")), Some(10));
+        let (reply, streamed) = stream(provider.as_ref()).await;
+        assert_eq!(reply, "ten chars!");
+        assert_eq!(streamed, "ten chars!");
+
+        let provider = with_response_cap(Box::new(FixedProvider("eleven chars")), Some(10));
+        let (reply, _) = stream(provider.as_ref()).await;
+        assert_eq!(reply, format!("eleven cha{}", truncation_notice(10)));
+    }
+}
@@ -0,0 +1,163 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Stops calling a provider that keeps failing.
+///
+/// After `threshold` consecutive failures the circuit opens: [`Self::check`]
+/// refuses calls until `cooldown` has passed, so `ReliableProvider` fails
+/// over at once instead of retrying a dead endpoint. After the cooldown one
+/// probe is let through; a success closes the circuit, a failure reopens it
+/// for another cooldown. A `threshold` of 0 never opens.
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Closed { failures: u32 },
+    Open { until: Instant },
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold,
+            cooldown,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    /// A breaker that never opens.
+    pub fn disabled() -> Self {
+        Self::new(0, Duration::ZERO)
+    }
+
+    /// `Err` with the time left while the circuit is open; `Ok` when a call
+    /// may go ahead.
+    pub fn check(&self) -> Result<(), Duration> {
+        self.check_at(Instant::now())
+    }
+
+    pub fn record_success(&self) {
+        *self.lock() = State::Closed { failures: 0 };
+    }
+
+    /// Count a failed call. Returns true when this failure opened the circuit.
+    pub fn record_failure(&self) -> bool {
+        self.record_failure_at(Instant::now())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn check_at(&self, now: Instant) -> Result<(), Duration> {
+        let mut state = self.lock();
+        match *state {
+            State::Closed { .. } => Ok(()),
+            State::Open { until } if now < until => Err(until - now),
+            State::Open { .. } => {
+                // Cooldown over: let a probe through, one failure reopens
+                *state = State::Closed {
+                    failures: self.threshold.saturating_sub(1),
+                };
+                Ok(())
+            }
+        }
+    }
+
+    fn record_failure_at(&self, now: Instant) -> bool {
+        if self.threshold == 0 {
+            return false;
+        }
+        let mut state = self.lock();
+        let failures = match *state {
+            State::Closed { failures } => failures + 1,
+            // A call that started before the circuit opened
+            State::Open { .. } => return false,
+        };
+        if failures >= self.threshold {
+            *state = State::Open {
+                until: now + self.cooldown,
+            };
+            true
+        } else {
+            *state = State::Closed { failures };
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COOLDOWN: Duration = Duration::from_secs(30);
+
+    #[test]
+    fn opens_after_threshold_consecutive_failures() {
+        let breaker = CircuitBreaker::new(3, COOLDOWN);
+        let now = Instant::now();
+        assert!(!breaker.record_failure_at(now));
+        assert!(!breaker.record_failure_at(now));
+        assert!(breaker.check_at(now).is_ok());
+        assert!(breaker.record_failure_at(now));
+
+        let left = breaker.check_at(now + Duration::from_secs(10)).unwrap_err();
+        assert_eq!(left, Duration::from_secs(20));
+    }
+
+    #[test]
+    fn success_resets_the_count() {
+        let breaker = CircuitBreaker::new(2, COOLDOWN);
+        let now = Instant::now();
+        breaker.record_failure_at(now);
+        breaker.record_success();
+        assert!(!breaker.record_failure_at(now));
+        assert!(breaker.check_at(now).is_ok());
+    }
+
+    #[test]
+    fn closes_after_cooldown_when_probe_succeeds() {
+        let breaker = CircuitBreaker::new(2, COOLDOWN);
+        let now = Instant::now();
+        breaker.record_failure_at(now);
+        breaker.record_failure_at(now);
+        assert!(breaker.check_at(now).is_err());
+
+        let later = now + COOLDOWN;
+        assert!(breaker.check_at(later).is_ok());
+        breaker.record_success();
+        assert!(!breaker.record_failure_at(later));
+        assert!(breaker.check_at(later).is_ok());
+    }
+
+    #[test]
+    fn failed_probe_reopens_for_another_cooldown() {
+        let breaker = CircuitBreaker::new(3, COOLDOWN);
+        let now = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure_at(now);
+        }
+
+        let later = now + COOLDOWN;
+        assert!(breaker.check_at(later).is_ok());
+        assert!(breaker.record_failure_at(later));
+        assert!(breaker.check_at(later + Duration::from_secs(1)).is_err());
+        assert!(breaker.check_at(later + COOLDOWN).is_ok());
+    }
+
+    #[test]
+    fn zero_threshold_never_opens() {
+        let breaker = CircuitBreaker::disabled();
+        let now = Instant::now();
+        for _ in 0..100 {
+            assert!(!breaker.record_failure_at(now));
+        }
+        assert!(breaker.check_at(now).is_ok());
+    }
+}
@@ -11,7 +11,11 @@ use serde::{Deserialize, Serialize};
 /// Used by: Venice, Vercel AI Gateway, Cloudflare AI Gateway, Moonshot,
 /// Synthetic, `OpenCode` Zen, `Z.AI`, `GLM`, `MiniMax`, Bedrock, Qianfan, Groq, Mistral, `xAI`, etc.
 pub struct OpenAiCompatibleProvider {
-    pub(crate) name: String,
+    /// Display name used in error messages, e.g. "Together AI"
+    pub(crate) label: String,
+    /// `label` lowercased with runs of other characters turned into `-`,
+    /// e.g. `together-ai` for "Together AI"
+    id: String,
     pub(crate) base_url: String,
     pub(crate) api_key: Option<String>,
     pub(crate) auth_header: AuthStyle,
@@ -33,15 +37,12 @@ pub enum AuthStyle {
 impl OpenAiCompatibleProvider {
     pub fn new(name: &str, base_url: &str, api_key: Option<&str>, auth_style: AuthStyle) -> Self {
         Self {
-            name: name.to_string(),
+            label: name.to_string(),
+            id: slug(name),
             base_url: base_url.trim_end_matches('/').to_string(),
             api_key: api_key.map(ToString::to_string),
             auth_header: auth_style,
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(120))
-                .connect_timeout(std::time::Duration::from_secs(10))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
+            client: super::http::shared_client(std::time::Duration::from_secs(120)),
             usage_tracker: None,
         }
     }
@@ -90,6 +91,28 @@ struct ChatRequest {
 struct Message {
     role: String,
     content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+impl From<&ChatMessage> for Message {
+    /// Results of native tool calls keep the `tool` role; results of
+    /// `<tool_call>` blocks go as user text.
+    fn from(m: &ChatMessage) -> Self {
+        if let (Some(id), "tool") = (&m.tool_call_id, m.role.as_str()) {
+            return Self {
+                role: "tool".into(),
+                content: m.content.clone(),
+                tool_call_id: Some(id.clone()),
+            };
+        }
+        let (role, content) = m.inline_tool_results();
+        Self {
+            role: role.to_string(),
+            content: content.into_owned(),
+            tool_call_id: None,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -232,18 +255,30 @@ impl OpenAiCompatibleProvider {
 
         if !response.status().is_success() {
             let error = response.text().await?;
-            anyhow::bail!("{} Responses API error: {error}", self.name);
+            anyhow::bail!("{} Responses API error: {error}", self.label);
         }
 
         let responses: ResponsesResponse = response.json().await?;
 
         extract_responses_text(responses)
-            .ok_or_else(|| anyhow::anyhow!("No response from {} Responses API", self.name))
+            .ok_or_else(|| anyhow::anyhow!("No response from {} Responses API", self.label))
     }
 }
 
+fn slug(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
 #[async_trait]
 impl Provider for OpenAiCompatibleProvider {
+    fn name(&self) -> &str {
+        &self.id
+    }
+
     async fn chat_with_system(
         &self,
         system_prompt: Option<&str>,
@@ -254,7 +289,7 @@ impl Provider for OpenAiCompatibleProvider {
         let api_key = self.api_key.as_ref().ok_or_else(|| {
             anyhow::anyhow!(
                 "{} API key not set. Run `tinyclaw onboard` or set the appropriate env var.",
-                self.name
+                self.label
             )
         })?;
 
@@ -264,12 +299,14 @@ impl Provider for OpenAiCompatibleProvider {
             messages.push(Message {
                 role: "system".to_string(),
                 content: sys.to_string(),
+                tool_call_id: None,
             });
         }
 
         messages.push(Message {
             role: "user".to_string(),
             content: message.to_string(),
+            tool_call_id: None,
         });
 
         let request = ChatRequest {
@@ -297,12 +334,12 @@ impl Provider for OpenAiCompatibleProvider {
                     .map_err(|responses_err| {
                         anyhow::anyhow!(
                             "{} API error ({status}): {sanitized} (chat completions unavailable; responses fallback failed: {responses_err})",
-                            self.name
+                            self.label
                         )
                     });
             }
 
-            anyhow::bail!("{} API error ({status}): {sanitized}", self.name);
+            return Err(super::status_error(&self.label, status, &sanitized));
         }
 
         let chat_response: ApiChatResponse = response.json().await?;
@@ -313,7 +350,7 @@ impl Provider for OpenAiCompatibleProvider {
             .into_iter()
             .next()
             .map(|c| c.message.content)
-            .ok_or_else(|| anyhow::anyhow!("No response from {}", self.name))
+            .ok_or_else(|| anyhow::anyhow!("No response from {}", self.label))
     }
 
     async fn chat_with_history(
@@ -325,17 +362,11 @@ impl Provider for OpenAiCompatibleProvider {
         let api_key = self.api_key.as_ref().ok_or_else(|| {
             anyhow::anyhow!(
                 "{} API key not set. Run `tinyclaw onboard` or set the appropriate env var.",
-                self.name
+                self.label
             )
         })?;
 
-        let api_messages: Vec<Message> = messages
-            .iter()
-            .map(|m| Message {
-                role: m.role.clone(),
-                content: m.content.clone(),
-            })
-            .collect();
+        let api_messages: Vec<Message> = messages.iter().map(Message::from).collect();
 
         let request = ChatRequest {
             model: model.to_string(),
@@ -356,26 +387,28 @@ impl Provider for OpenAiCompatibleProvider {
             if status == reqwest::StatusCode::NOT_FOUND {
                 // Extract system prompt and last user message for responses fallback
                 let system = messages.iter().find(|m| m.role == "system");
-                let last_user = messages.iter().rfind(|m| m.role == "user");
+                let last_user = messages
+                    .iter()
+                    .rfind(|m| m.role == "user" || m.role == "tool");
                 if let Some(user_msg) = last_user {
                     return self
                         .chat_via_responses(
                             api_key,
                             system.map(|m| m.content.as_str()),
-                            &user_msg.content,
+                            &user_msg.inline_tool_results().1,
                             model,
                         )
                         .await
                         .map_err(|responses_err| {
                             anyhow::anyhow!(
                                 "{} API error (chat completions unavailable; responses fallback failed: {responses_err})",
-                                self.name
+                                self.label
                             )
                         });
                 }
             }
 
-            return Err(super::api_error(&self.name, response).await);
+            return Err(super::api_error(&self.label, response).await);
         }
 
         let chat_response: ApiChatResponse = response.json().await?;
@@ -386,7 +419,7 @@ impl Provider for OpenAiCompatibleProvider {
             .into_iter()
             .next()
             .map(|c| c.message.content)
-            .ok_or_else(|| anyhow::anyhow!("No response from {}", self.name))
+            .ok_or_else(|| anyhow::anyhow!("No response from {}", self.label))
     }
 
     fn set_usage_tracker(&mut self, tracker: UsageTracker) {
@@ -402,10 +435,27 @@ mod tests {
         OpenAiCompatibleProvider::new(name, url, key, AuthStyle::Bearer)
     }
 
+    #[test]
+    fn tool_messages_map_to_the_tool_role_only_with_a_call_id() {
+        let native =
+            serde_json::to_value(Message::from(&ChatMessage::tool_result("call_1", "a.txt")))
+                .unwrap();
+        assert_eq!(
+            native,
+            serde_json::json!({"role": "tool", "content": "a.txt", "tool_call_id": "call_1"})
+        );
+
+        let inline = serde_json::to_value(Message::from(&ChatMessage::tool("a.txt"))).unwrap();
+        assert_eq!(
+            inline,
+            serde_json::json!({"role": "user", "content": "[Tool results]\na.txt"})
+        );
+    }
+
     #[test]
     fn creates_with_key() {
         let p = make_provider("venice", "https://api.venice.ai", Some("vn-key"));
-        assert_eq!(p.name, "venice");
+        assert_eq!(p.label, "venice");
         assert_eq!(p.base_url, "https://api.venice.ai");
         assert_eq!(p.api_key.as_deref(), Some("vn-key"));
     }
@@ -443,10 +493,12 @@ mod tests {
                 Message {
                     role: "system".to_string(),
                     content: "You are TinyClaw".to_string(),
+                    tool_call_id: None,
                 },
                 Message {
                     role: "user".to_string(),
                     content: "hello".to_string(),
+                    tool_call_id: None,
                 },
             ],
             temperature: 0.7,
@@ -507,11 +559,11 @@ mod tests {
 
         for p in providers {
             let result = p.chat_with_system(None, "test", "model", 0.7).await;
-            assert!(result.is_err(), "{} should fail without key", p.name);
+            assert!(result.is_err(), "{} should fail without key", p.label);
             assert!(
                 result.unwrap_err().to_string().contains("API key not set"),
                 "{} error should mention key",
-                p.name
+                p.label
             );
         }
     }
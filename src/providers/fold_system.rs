@@ -0,0 +1,374 @@
+use super::traits::{ChatMessage, ModelInfo, ReasoningEffort, UsageTracker};
+use super::Provider;
+use async_trait::async_trait;
+
+/// Sends the system prompt as the opening of the first user message instead
+/// of as a `system` message.
+///
+/// Some OpenAI-compatible endpoints, local models and reasoning models like
+/// `o1` ignore or reject the system role. Calls to a model for which
+/// [`Provider::supports_system_prompt`] is false are folded, as is every call
+/// to a provider with `[providers.<name>] fold_system_into_user = true`, so
+/// the instructions still reach the model.
+pub struct FoldSystemProvider {
+    inner: Box<dyn Provider>,
+    /// Fold for every model, not just those without a system role
+    always: bool,
+}
+
+impl FoldSystemProvider {
+    pub fn new(inner: Box<dyn Provider>, always: bool) -> Self {
+        Self { inner, always }
+    }
+
+    fn folds(&self, model: &str) -> bool {
+        self.always || !self.inner.supports_system_prompt(model)
+    }
+}
+
+/// `provider` wrapped in [`FoldSystemProvider`], folding for the models it
+/// can't send a system prompt to, or for all of them when `force`
+/// (`fold_system_into_user`) is set.
+pub fn fold_system_if_needed(provider: Box<dyn Provider>, force: bool) -> Box<dyn Provider> {
+    Box::new(FoldSystemProvider::new(provider, force))
+}
+
+/// `messages` with every `system` message merged, in order, into the top of
+/// the first user message. With no user message the system text becomes
+/// one, where the first system message was.
+pub fn fold_system_into_user(messages: &[ChatMessage]) -> Vec<ChatMessage> {
+    let system: Vec<&str> = messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .map(|m| m.content.as_str())
+        .collect();
+    if system.is_empty() {
+        return messages.to_vec();
+    }
+    let system = system.join("\n\n");
+
+    let first_user = messages.iter().position(|m| m.role == "user");
+    let first_system = messages.iter().position(|m| m.role == "system");
+    let mut folded = Vec::with_capacity(messages.len());
+    for (i, msg) in messages.iter().enumerate() {
+        if msg.role == "system" {
+            if first_user.is_none() && Some(i) == first_system {
+                folded.push(ChatMessage::user(system.clone()));
+            }
+            continue;
+        }
+        let mut msg = msg.clone();
+        if Some(i) == first_user {
+            msg.content = format!("{system}\n\n{}", msg.content);
+        }
+        folded.push(msg);
+    }
+    folded
+}
+
+#[async_trait]
+impl Provider for FoldSystemProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn set_usage_tracker(&mut self, tracker: UsageTracker) {
+        self.inner.set_usage_tracker(tracker);
+    }
+
+    fn set_thinking(&mut self, enabled: bool) {
+        self.inner.set_thinking(enabled);
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.inner.set_seed(seed);
+    }
+
+    fn set_reasoning_effort(&mut self, effort: Option<ReasoningEffort>) {
+        self.inner.set_reasoning_effort(effort);
+    }
+
+    fn set_stop_sequences(&mut self, stop: &[String]) -> anyhow::Result<()> {
+        self.inner.set_stop_sequences(stop)
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.inner.supports_streaming()
+    }
+
+    async fn warmup(&self) -> anyhow::Result<()> {
+        self.inner.warmup().await
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        self.inner.list_models().await
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        match system_prompt {
+            Some(system) if self.folds(model) => {
+                let message = format!("{system}\n\n{message}");
+                self.inner
+                    .chat_with_system(None, &message, model, temperature)
+                    .await
+            }
+            _ => {
+                self.inner
+                    .chat_with_system(system_prompt, message, model, temperature)
+                    .await
+            }
+        }
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        if !self.folds(model) {
+            return self
+                .inner
+                .chat_with_history(messages, model, temperature)
+                .await;
+        }
+        self.inner
+            .chat_with_history(&fold_system_into_user(messages), model, temperature)
+            .await
+    }
+
+    async fn chat_with_history_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        token_tx: tokio::sync::mpsc::Sender<String>,
+    ) -> anyhow::Result<String> {
+        if !self.folds(model) {
+            return self
+                .inner
+                .chat_with_history_stream(messages, model, temperature, token_tx)
+                .await;
+        }
+        self.inner
+            .chat_with_history_stream(
+                &fold_system_into_user(messages),
+                model,
+                temperature,
+                token_tx,
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn roles_and_contents(messages: &[ChatMessage]) -> Vec<(&str, &str)> {
+        messages
+            .iter()
+            .map(|m| (m.role.as_str(), m.content.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn system_prompt_opens_first_user_message() {
+        let messages = vec![
+            ChatMessage::system("Be brief."),
+            ChatMessage::user("hi"),
+            ChatMessage::assistant("hello"),
+            ChatMessage::user("bye"),
+        ];
+        let folded = fold_system_into_user(&messages);
+        assert_eq!(
+            roles_and_contents(&folded),
+            vec![
+                ("user", "Be brief.\n\nhi"),
+                ("assistant", "hello"),
+                ("user", "bye"),
+            ]
+        );
+        assert_eq!(folded[0].timestamp, messages[1].timestamp);
+    }
+
+    #[test]
+    fn system_prompt_alone_becomes_user_message() {
+        let folded = fold_system_into_user(&[ChatMessage::system("Be brief.")]);
+        assert_eq!(roles_and_contents(&folded), vec![("user", "Be brief.")]);
+
+        let plain = vec![ChatMessage::user("hi")];
+        assert_eq!(
+            roles_and_contents(&fold_system_into_user(&plain)),
+            vec![("user", "hi")]
+        );
+    }
+
+    /// System prompt and messages of one call
+    type Call = (Option<String>, Vec<ChatMessage>);
+
+    /// Records the system prompt and messages each call receives.
+    #[derive(Default)]
+    struct RecordingProvider {
+        calls: Arc<Mutex<Vec<Call>>>,
+        /// Report no support for the system role for models starting with this
+        rejects_system: Option<&'static str>,
+    }
+
+    #[async_trait]
+    impl Provider for RecordingProvider {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn supports_system_prompt(&self, model: &str) -> bool {
+            self.rejects_system
+                .is_none_or(|prefix| !model.starts_with(prefix))
+        }
+
+        async fn chat_with_system(
+            &self,
+            system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            self.calls.lock().unwrap().push((
+                system_prompt.map(ToString::to_string),
+                vec![ChatMessage::user(message)],
+            ));
+            Ok("ok".into())
+        }
+
+        async fn chat_with_history(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            self.calls.lock().unwrap().push((None, messages.to_vec()));
+            Ok("ok".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn inner_provider_never_sees_a_system_prompt() {
+        let inner = RecordingProvider::default();
+        let calls = inner.calls.clone();
+        let provider = FoldSystemProvider::new(Box::new(inner), true);
+
+        provider
+            .chat_with_system(Some("Be brief."), "hi", "model", 0.0)
+            .await
+            .unwrap();
+        let history = [ChatMessage::system("Be brief."), ChatMessage::user("hi")];
+        provider
+            .chat_with_history(&history, "model", 0.0)
+            .await
+            .unwrap();
+
+        let calls = calls.lock().unwrap();
+        for (system, messages) in calls.iter() {
+            assert_eq!(system, &None);
+            assert_eq!(
+                roles_and_contents(messages),
+                vec![("user", "Be brief.\n\nhi")]
+            );
+        }
+        assert_eq!(calls.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn provider_without_system_role_gets_the_prompt_folded() {
+        let inner = RecordingProvider {
+            rejects_system: Some(""),
+            ..RecordingProvider::default()
+        };
+        let calls = inner.calls.clone();
+        let provider = fold_system_if_needed(Box::new(inner), false);
+        assert!(provider.supports_system_prompt("model"));
+
+        let history = [ChatMessage::system("Be brief."), ChatMessage::user("hi")];
+        provider
+            .chat_with_history(&history, "model", 0.0)
+            .await
+            .unwrap();
+        provider
+            .chat_with_system(Some("Be brief."), "hi", "model", 0.0)
+            .await
+            .unwrap();
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        for (system, messages) in calls.iter() {
+            assert_eq!(system, &None);
+            assert!(messages.iter().all(|m| m.role != "system"));
+            assert_eq!(
+                roles_and_contents(messages),
+                vec![("user", "Be brief.\n\nhi")]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn provider_with_system_role_is_left_alone_unless_forced() {
+        let inner = RecordingProvider::default();
+        let calls = inner.calls.clone();
+        let provider = fold_system_if_needed(Box::new(inner), false);
+        let history = [ChatMessage::system("Be brief."), ChatMessage::user("hi")];
+        provider
+            .chat_with_history(&history, "model", 0.0)
+            .await
+            .unwrap();
+        assert_eq!(
+            roles_and_contents(&calls.lock().unwrap()[0].1),
+            vec![("system", "Be brief."), ("user", "hi")]
+        );
+
+        let inner = RecordingProvider::default();
+        let calls = inner.calls.clone();
+        let provider = fold_system_if_needed(Box::new(inner), true);
+        provider
+            .chat_with_history(&history, "model", 0.0)
+            .await
+            .unwrap();
+        assert_eq!(
+            roles_and_contents(&calls.lock().unwrap()[0].1),
+            vec![("user", "Be brief.\n\nhi")]
+        );
+    }
+
+    #[tokio::test]
+    async fn only_models_without_system_role_get_the_prompt_folded() {
+        let inner = RecordingProvider {
+            rejects_system: Some("o1"),
+            ..RecordingProvider::default()
+        };
+        let calls = inner.calls.clone();
+        let provider = fold_system_if_needed(Box::new(inner), false);
+        let history = [ChatMessage::system("Be brief."), ChatMessage::user("hi")];
+        for model in ["o1-mini", "gpt-4o"] {
+            provider
+                .chat_with_history(&history, model, 0.0)
+                .await
+                .unwrap();
+        }
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(
+            roles_and_contents(&calls[0].1),
+            vec![("user", "Be brief.\n\nhi")]
+        );
+        assert_eq!(
+            roles_and_contents(&calls[1].1),
+            vec![("system", "Be brief."), ("user", "hi")]
+        );
+    }
+}
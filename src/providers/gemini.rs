@@ -137,11 +137,7 @@ impl GeminiProvider {
 
         Self {
             auth: resolved_auth,
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(120))
-                .connect_timeout(std::time::Duration::from_secs(10))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
+            client: super::http::shared_client(std::time::Duration::from_secs(120)),
         }
     }
 
@@ -254,6 +250,10 @@ impl GeminiProvider {
 
 #[async_trait]
 impl Provider for GeminiProvider {
+    fn name(&self) -> &str {
+        "gemini"
+    }
+
     async fn chat_with_system(
         &self,
         system_prompt: Option<&str>,
@@ -301,9 +301,7 @@ impl Provider for GeminiProvider {
             .await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Gemini API error ({status}): {error_text}");
+            return Err(super::api_error("Gemini", response).await);
         }
 
         let result: GenerateContentResponse = response.json().await?;
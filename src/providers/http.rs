@@ -0,0 +1,301 @@
+use crate::config::NetworkConfig;
+use anyhow::Context;
+use reqwest::{Certificate, Client, ClientBuilder, NoProxy, Proxy};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::time::Duration;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+static NETWORK: OnceLock<NetworkConfig> = OnceLock::new();
+
+/// Provider clients by request timeout. A `Client` is an `Arc` handle to its
+/// connection pool, so every clone shares the same warm connections.
+static CLIENTS: OnceLock<Mutex<HashMap<Duration, Client>>> = OnceLock::new();
+
+/// Install `[network]` settings for every provider HTTP client created after
+/// this call. Only the first call takes effect.
+pub fn configure(config: &NetworkConfig) {
+    let _ = NETWORK.set(config.clone());
+}
+
+/// The process-wide client for provider requests that time out after
+/// `timeout`.
+///
+/// Providers rebuilt later, e.g. on `/model` or a daemon reload, get the
+/// same client back and reuse its open connections instead of paying for a
+/// new TLS handshake.
+pub fn shared_client(timeout: Duration) -> Client {
+    CLIENTS
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(timeout)
+        .or_insert_with(|| build_client(timeout, NETWORK.get()))
+        .clone()
+}
+
+/// A new client with the proxy, TLS and connection pool settings of `config`.
+///
+/// Without a configured proxy, reqwest's default `HTTP(S)_PROXY` environment
+/// handling still applies.
+fn build_client(timeout: Duration, config: Option<&NetworkConfig>) -> Client {
+    let defaults = NetworkConfig::default();
+    let pool = config.unwrap_or(&defaults);
+    let mut builder = apply_tls(apply_network(Client::builder(), config), config)
+        .timeout(timeout)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .pool_idle_timeout(secs(pool.pool_idle_timeout_secs))
+        .tcp_keepalive(secs(pool.tcp_keepalive_secs));
+    if let Some(max) = pool.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max);
+    }
+    builder.build().unwrap_or_else(|e| {
+        tracing::warn!("Failed to build provider HTTP client, using defaults: {e}");
+        Client::new()
+    })
+}
+
+/// `None` for 0, meaning "no limit" / "off" in `[network]`.
+fn secs(value: u64) -> Option<Duration> {
+    (value > 0).then(|| Duration::from_secs(value))
+}
+
+fn apply_network(builder: ClientBuilder, config: Option<&NetworkConfig>) -> ClientBuilder {
+    let Some(url) = config.and_then(|c| c.proxy.as_deref()).map(str::trim) else {
+        return builder;
+    };
+    if url.is_empty() {
+        return builder;
+    }
+
+    match Proxy::all(url) {
+        Ok(proxy) => {
+            let no_proxy = config
+                .map(|c| c.no_proxy.join(","))
+                .and_then(|list| NoProxy::from_string(&list));
+            builder.proxy(proxy.no_proxy(no_proxy))
+        }
+        Err(e) => {
+            tracing::warn!("Ignoring invalid network.proxy {url:?}: {e}");
+            builder
+        }
+    }
+}
+
+/// Trust `ca_cert_path` on top of the built-in roots, and turn off
+/// certificate checks when `danger_accept_invalid_certs` asks for it.
+fn apply_tls(mut builder: ClientBuilder, config: Option<&NetworkConfig>) -> ClientBuilder {
+    let Some(config) = config else {
+        return builder;
+    };
+    if let Some(path) = &config.ca_cert_path {
+        match load_ca_certs(path) {
+            Ok(certs) => {
+                for cert in certs {
+                    builder = builder.add_root_certificate(cert);
+                }
+            }
+            Err(e) => tracing::warn!("Ignoring network.ca_cert_path: {e:#}"),
+        }
+    }
+    if config.danger_accept_invalid_certs {
+        tracing::warn!(
+            "network.danger_accept_invalid_certs is on: provider TLS certificates are NOT \
+             verified and traffic can be intercepted. Use it for local development only."
+        );
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    builder
+}
+
+/// Certificates in `path`: a PEM bundle, or a single DER certificate.
+pub fn load_ca_certs(path: &Path) -> anyhow::Result<Vec<Certificate>> {
+    let bytes = std::fs::read(path)
+        .with_context(|| format!("Failed to read network.ca_cert_path {}", path.display()))?;
+    let certs = if bytes.windows(10).any(|w| w == b"-----BEGIN") {
+        Certificate::from_pem_bundle(&bytes)
+    } else {
+        Certificate::from_der(&bytes).map(|cert| vec![cert])
+    }
+    .with_context(|| format!("Invalid certificate in {}", path.display()))?;
+    if certs.is_empty() {
+        anyhow::bail!("No certificates found in {}", path.display());
+    }
+    // The certificates are only parsed when a client is built
+    certs
+        .iter()
+        .fold(
+            Client::builder().tls_built_in_root_certs(false),
+            |b, cert| b.add_root_certificate(cert.clone()),
+        )
+        .build()
+        .with_context(|| format!("Invalid certificate in {}", path.display()))?;
+    Ok(certs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn network(proxy: Option<&str>, no_proxy: &[&str]) -> NetworkConfig {
+        NetworkConfig {
+            proxy: proxy.map(ToString::to_string),
+            no_proxy: no_proxy.iter().map(ToString::to_string).collect(),
+            ..NetworkConfig::default()
+        }
+    }
+
+    /// A keep-alive HTTP/1.1 server answering every request with `ok`.
+    /// Returns its URL and the number of connections it accepted.
+    async fn counting_server() -> (String, Arc<AtomicUsize>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let accepted = Arc::new(AtomicUsize::new(0));
+        let count = accepted.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                count.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut pending = Vec::new();
+                    let mut buf = [0u8; 1024];
+                    while let Ok(n @ 1..) = socket.read(&mut buf).await {
+                        pending.extend_from_slice(&buf[..n]);
+                        while let Some(end) = pending.windows(4).position(|w| w == b"\r\n\r\n") {
+                            pending.drain(..end + 4);
+                            let reply = b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok";
+                            if socket.write_all(reply).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        (url, accepted)
+    }
+
+    async fn get(client: &Client, url: &str) -> String {
+        client.get(url).send().await.unwrap().text().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn recreated_providers_reuse_the_shared_connection() {
+        let (url, accepted) = counting_server().await;
+        // A timeout no provider uses, so other tests don't share this client
+        let timeout = Duration::from_secs(7);
+        for _ in 0..3 {
+            // What each newly created provider does
+            let client = shared_client(timeout);
+            assert_eq!(get(&client, &url).await, "ok");
+        }
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn pool_size_limits_idle_connections() {
+        let (url, accepted) = counting_server().await;
+        let config = NetworkConfig {
+            pool_max_idle_per_host: Some(0),
+            ..NetworkConfig::default()
+        };
+        let client = build_client(Duration::from_secs(5), Some(&config));
+        assert_eq!(get(&client, &url).await, "ok");
+        assert_eq!(get(&client, &url).await, "ok");
+        assert_eq!(accepted.load(Ordering::SeqCst), 2, "nothing kept idle");
+    }
+
+    #[test]
+    fn builder_uses_configured_proxy() {
+        let config = network(Some("http://proxy.corp.example:3128"), &["localhost"]);
+        let builder = apply_network(reqwest::Client::builder(), Some(&config));
+        let debug = format!("{builder:?}");
+        assert!(debug.contains("proxies"), "{debug}");
+        assert!(debug.contains("proxy.corp.example"), "{debug}");
+        assert!(debug.contains("localhost"), "{debug}");
+        assert!(builder.build().is_ok());
+    }
+
+    #[test]
+    fn builder_unchanged_without_proxy() {
+        for config in [
+            None,
+            Some(network(None, &["localhost"])),
+            Some(network(Some("  "), &[])),
+        ] {
+            let builder = apply_network(reqwest::Client::builder(), config.as_ref());
+            assert!(!format!("{builder:?}").contains("proxies"));
+        }
+    }
+
+    /// Self-signed CA generated for these tests.
+    const TEST_CA_PEM: &str = "-----BEGIN CERTIFICATE-----
+MIIBjDCCATOgAwIBAgIUWbUr0tZDguk1FPpud/IY/SdiYdkwCgYIKoZIzj0EAwIw
+GzEZMBcGA1UEAwwQdGlueWNsYXctdGVzdC1jYTAgFw0yNjEwMTcwMDQzNTVaGA8y
+MTI2MDkyMzAwNDM1NVowGzEZMBcGA1UEAwwQdGlueWNsYXctdGVzdC1jYTBZMBMG
+ByqGSM49AgEGCCqGSM49AwEHA0IABGYW7ynegZivFTNOT6UuGE8fWYh8OBO4W3nq
+l2opYhIHiPMuGpT1AuF3GT5jaEnd7sHd/dftdTRy+z1kaqOtzryjUzBRMB0GA1Ud
+DgQWBBTcAVdJZwqvxi0nEHGCDLzJreR6jzAfBgNVHSMEGDAWgBTcAVdJZwqvxi0n
+EHGCDLzJreR6jzAPBgNVHRMBAf8EBTADAQH/MAoGCCqGSM49BAMCA0cAMEQCID9V
+7nYFuoyQfRr5u/7nAADIz9YpEADyCnqXVpxuJUgeAiAOFOHbbo1k3qhdVIXcGYpm
+vAe7BFenr7SycXENE21UgQ==
+-----END CERTIFICATE-----
+";
+
+    #[test]
+    fn custom_ca_is_added_to_the_builder() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ca.pem");
+        std::fs::write(&path, format!("{TEST_CA_PEM}{TEST_CA_PEM}")).unwrap();
+        assert_eq!(load_ca_certs(&path).unwrap().len(), 2);
+
+        let config = NetworkConfig {
+            ca_cert_path: Some(path),
+            ..NetworkConfig::default()
+        };
+        let builder = apply_tls(reqwest::Client::builder(), Some(&config));
+        // Certificate checks stay on
+        assert!(!format!("{builder:?}").contains("danger_accept_invalid_certs"));
+        assert!(builder.build().is_ok());
+    }
+
+    #[test]
+    fn unreadable_or_invalid_ca_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.pem");
+        let err = load_ca_certs(&missing).unwrap_err().to_string();
+        assert!(err.contains("missing.pem"), "{err}");
+
+        let garbage = dir.path().join("garbage.der");
+        std::fs::write(&garbage, b"not a certificate").unwrap();
+        assert!(load_ca_certs(&garbage).is_err());
+
+        let empty = dir.path().join("empty.pem");
+        std::fs::write(&empty, "-----BEGIN NOTHING-----\n").unwrap();
+        assert!(load_ca_certs(&empty).is_err());
+    }
+
+    #[test]
+    fn accept_invalid_certs_only_when_opted_in() {
+        let builder = apply_tls(reqwest::Client::builder(), Some(&NetworkConfig::default()));
+        assert!(!format!("{builder:?}").contains("danger_accept_invalid_certs"));
+
+        let config = NetworkConfig {
+            danger_accept_invalid_certs: true,
+            ..NetworkConfig::default()
+        };
+        let builder = apply_tls(reqwest::Client::builder(), Some(&config));
+        assert!(format!("{builder:?}").contains("danger_accept_invalid_certs"));
+    }
+
+    #[test]
+    fn invalid_proxy_is_ignored() {
+        let config = network(Some("not a url"), &[]);
+        let builder = apply_network(reqwest::Client::builder(), Some(&config));
+        assert!(!format!("{builder:?}").contains("proxies"));
+    }
+}
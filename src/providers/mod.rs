@@ -1,18 +1,29 @@
 pub mod anthropic;
+pub mod cache;
+pub mod capped;
+pub mod circuit;
 pub mod compatible;
+pub mod fold_system;
 pub mod gemini;
+pub mod http;
 pub mod ollama;
 pub mod openai;
 pub mod openrouter;
+pub mod race;
 pub mod reliable;
 pub mod router;
+pub mod throttle;
+pub mod tokens;
 pub mod traits;
 
-pub use traits::{ChatMessage, Provider, TokenUsage, UsageTracker};
+pub use traits::{ChatMessage, ModelInfo, Provider, ReasoningEffort, Served, UsageTracker};
 
+use crate::util::truncate_bytes_safe;
 use compatible::{AuthStyle, OpenAiCompatibleProvider};
 use reliable::ReliableProvider;
 
+use std::fmt::Write;
+
 const MAX_API_ERROR_CHARS: usize = 200;
 
 fn is_secret_char(c: char) -> bool {
@@ -72,12 +83,100 @@ pub fn sanitize_api_error(input: &str) -> String {
         return scrubbed;
     }
 
-    let mut end = MAX_API_ERROR_CHARS;
-    while end > 0 && !scrubbed.is_char_boundary(end) {
-        end -= 1;
+    format!("{}...", truncate_bytes_safe(&scrubbed, MAX_API_ERROR_CHARS))
+}
+
+/// A provider rejected its credentials (HTTP 401/403).
+///
+/// Carries the provider-specific fix so the CLI and TUI can show it
+/// prominently; find it in an error chain with [`auth_failure`].
+#[derive(Debug)]
+pub struct AuthError {
+    /// Provider display name, e.g. `OpenAI`
+    pub provider: String,
+    pub status: reqwest::StatusCode,
+    /// Sanitized response body
+    pub detail: String,
+}
+
+impl AuthError {
+    /// One-line description without the remediation steps.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} API error ({}): {}",
+            self.provider, self.status, self.detail
+        )
     }
 
-    format!("{}...", &scrubbed[..end])
+    /// Which env var or config field to fix, and how to re-run onboarding.
+    pub fn guidance(&self) -> String {
+        let id = provider_id(&self.provider);
+        let env_vars = match id.as_str() {
+            "gemini" => &["GEMINI_API_KEY", "GOOGLE_API_KEY"][..],
+            other => provider_env_vars(other),
+        };
+
+        let mut out = if self.status == reqwest::StatusCode::FORBIDDEN {
+            format!(
+                "{} accepted the key but denied access. Check the key's permissions, \
+                 credits and model access, or replace it:",
+                self.provider
+            )
+        } else {
+            format!("{} rejected the API key. To fix it:", self.provider)
+        };
+        if let Some(primary) = env_vars.first() {
+            let _ = write!(out, "\n  - export {primary}=<your key>");
+            for alt in &env_vars[1..] {
+                let _ = write!(out, " (or {alt})");
+            }
+        }
+        if id == "custom" {
+            // Custom endpoints are keyed by URL, so only the top-level key applies
+            out.push_str(
+                "\n  - or set the top-level `api_key` in ~/.tinyclaw/config.toml\
+                 \n  - or re-run `tinyclaw onboard --api-key <your key>`",
+            );
+        } else {
+            let _ = write!(
+                out,
+                "\n  - or set `api_key` under [providers.{id}] (or the top-level `api_key`) in ~/.tinyclaw/config.toml\
+                 \n  - or re-run `tinyclaw onboard --provider {id} --api-key <your key>`"
+            );
+        }
+        out
+    }
+}
+
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}\n{}", self.summary(), self.guidance())
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+/// The [`AuthError`] anywhere in `err`'s chain, if a provider rejected its
+/// credentials.
+pub fn auth_failure(err: &anyhow::Error) -> Option<&AuthError> {
+    err.chain().find_map(|e| e.downcast_ref::<AuthError>())
+}
+
+/// Provider error for a non-success status and an already sanitized body.
+/// 401/403 become an [`AuthError`] carrying remediation steps.
+pub fn status_error(provider: &str, status: reqwest::StatusCode, sanitized: &str) -> anyhow::Error {
+    if matches!(
+        status,
+        reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN
+    ) {
+        return AuthError {
+            provider: provider.to_string(),
+            status,
+            detail: sanitized.to_string(),
+        }
+        .into();
+    }
+    anyhow::anyhow!("{provider} API error ({status}): {sanitized}")
 }
 
 /// Build a sanitized provider error from a failed HTTP response.
@@ -87,8 +186,86 @@ pub async fn api_error(provider: &str, response: reqwest::Response) -> anyhow::E
         .text()
         .await
         .unwrap_or_else(|_| "<failed to read provider error body>".to_string());
-    let sanitized = sanitize_api_error(&body);
-    anyhow::anyhow!("{provider} API error ({status}): {sanitized}")
+    status_error(provider, status, &sanitize_api_error(&body))
+}
+
+/// Config id for a provider display name (`Together AI` -> `together`).
+/// Unknown names are lowercased as-is.
+fn provider_id(display_name: &str) -> String {
+    let lower = display_name.to_ascii_lowercase();
+    let id = match lower.as_str() {
+        "together ai" => "together",
+        "fireworks ai" => "fireworks",
+        "opencode zen" => "opencode",
+        "vercel ai gateway" => "vercel",
+        "cloudflare ai gateway" => "cloudflare",
+        "amazon bedrock" => "bedrock",
+        "github copilot" => "copilot",
+        "z.ai" => "zai",
+        other => other,
+    };
+    id.to_string()
+}
+
+/// Built-in model for provider `name` when neither `--model` nor a
+/// configured default applies; always an id the provider accepts.
+pub fn default_model(name: &str) -> &'static str {
+    match name {
+        "anthropic" => "claude-sonnet-4-20250514",
+        name if name.starts_with("anthropic-custom:") => "claude-sonnet-4-20250514",
+        "openai" | "copilot" | "github-copilot" => "gpt-4o",
+        name if name.starts_with("custom:") => "gpt-4o",
+        "ollama" => "llama3.2",
+        "gemini" | "google" | "google-gemini" => "gemini-2.0-flash",
+        "venice" => "llama-3.3-70b",
+        "vercel" | "vercel-ai" => "openai/gpt-4o",
+        "cloudflare" | "cloudflare-ai" => "@cf/meta/llama-3.3-70b-instruct-fp8-fast",
+        "moonshot" | "kimi" => "moonshot-v1-128k",
+        "synthetic" => "hf:meta-llama/Llama-3.3-70B-Instruct",
+        "opencode" | "opencode-zen" => "claude-sonnet-4",
+        "glm" | "zhipu" | "zai" | "z.ai" => "glm-5",
+        "minimax" => "abab6.5s-chat",
+        "bedrock" | "aws-bedrock" => "anthropic.claude-3-5-sonnet-20241022-v2:0",
+        "qianfan" | "baidu" => "ernie-4.0-8k",
+        "groq" => "llama-3.3-70b-versatile",
+        "mistral" => "mistral-large-latest",
+        "xai" | "grok" => "grok-3",
+        "deepseek" => "deepseek-chat",
+        "together" | "together-ai" => "meta-llama/Meta-Llama-3.1-70B-Instruct-Turbo",
+        "fireworks" | "fireworks-ai" => "accounts/fireworks/models/llama-v3p3-70b-instruct",
+        "perplexity" => "sonar-pro",
+        "cohere" => "command-r-plus",
+        // OpenRouter, and anything unknown
+        _ => "anthropic/claude-sonnet-4-20250514",
+    }
+}
+
+/// Provider-specific API key environment variables, in lookup order.
+fn provider_env_vars(name: &str) -> &'static [&'static str] {
+    match name {
+        "anthropic" => &["ANTHROPIC_OAUTH_TOKEN", "ANTHROPIC_API_KEY"],
+        "openrouter" => &["OPENROUTER_API_KEY"],
+        "openai" => &["OPENAI_API_KEY"],
+        "venice" => &["VENICE_API_KEY"],
+        "groq" => &["GROQ_API_KEY"],
+        "mistral" => &["MISTRAL_API_KEY"],
+        "deepseek" => &["DEEPSEEK_API_KEY"],
+        "xai" | "grok" => &["XAI_API_KEY"],
+        "together" | "together-ai" => &["TOGETHER_API_KEY"],
+        "fireworks" | "fireworks-ai" => &["FIREWORKS_API_KEY"],
+        "perplexity" => &["PERPLEXITY_API_KEY"],
+        "cohere" => &["COHERE_API_KEY"],
+        "moonshot" | "kimi" => &["MOONSHOT_API_KEY"],
+        "glm" | "zhipu" => &["GLM_API_KEY"],
+        "minimax" => &["MINIMAX_API_KEY"],
+        "qianfan" | "baidu" => &["QIANFAN_API_KEY"],
+        "zai" | "z.ai" => &["ZAI_API_KEY"],
+        "synthetic" => &["SYNTHETIC_API_KEY"],
+        "opencode" | "opencode-zen" => &["OPENCODE_API_KEY"],
+        "vercel" | "vercel-ai" => &["VERCEL_API_KEY"],
+        "cloudflare" | "cloudflare-ai" => &["CLOUDFLARE_API_KEY"],
+        _ => &[],
+    }
 }
 
 /// Resolve API key for a provider from config and environment variables.
@@ -96,7 +273,7 @@ pub async fn api_error(provider: &str, response: reqwest::Response) -> anyhow::E
 /// Resolution order:
 /// 1. Explicitly provided `api_key` parameter (trimmed, filtered if empty)
 /// 2. Provider-specific environment variable (e.g., `ANTHROPIC_OAUTH_TOKEN`, `OPENROUTER_API_KEY`)
-/// 3. Generic fallback variables (`ZEROCLAW_API_KEY`, `API_KEY`)
+/// 3. `TINYCLAW_API_KEY`, then the deprecated `ZEROCLAW_API_KEY` and `API_KEY`
 ///
 /// For Anthropic, the provider-specific env var is `ANTHROPIC_OAUTH_TOKEN` (for setup-tokens)
 /// followed by `ANTHROPIC_API_KEY` (for regular API keys).
@@ -105,32 +282,7 @@ fn resolve_api_key(name: &str, api_key: Option<&str>) -> Option<String> {
         return Some(key.to_string());
     }
 
-    let provider_env_candidates: Vec<&str> = match name {
-        "anthropic" => vec!["ANTHROPIC_OAUTH_TOKEN", "ANTHROPIC_API_KEY"],
-        "openrouter" => vec!["OPENROUTER_API_KEY"],
-        "openai" => vec!["OPENAI_API_KEY"],
-        "venice" => vec!["VENICE_API_KEY"],
-        "groq" => vec!["GROQ_API_KEY"],
-        "mistral" => vec!["MISTRAL_API_KEY"],
-        "deepseek" => vec!["DEEPSEEK_API_KEY"],
-        "xai" | "grok" => vec!["XAI_API_KEY"],
-        "together" | "together-ai" => vec!["TOGETHER_API_KEY"],
-        "fireworks" | "fireworks-ai" => vec!["FIREWORKS_API_KEY"],
-        "perplexity" => vec!["PERPLEXITY_API_KEY"],
-        "cohere" => vec!["COHERE_API_KEY"],
-        "moonshot" | "kimi" => vec!["MOONSHOT_API_KEY"],
-        "glm" | "zhipu" => vec!["GLM_API_KEY"],
-        "minimax" => vec!["MINIMAX_API_KEY"],
-        "qianfan" | "baidu" => vec!["QIANFAN_API_KEY"],
-        "zai" | "z.ai" => vec!["ZAI_API_KEY"],
-        "synthetic" => vec!["SYNTHETIC_API_KEY"],
-        "opencode" | "opencode-zen" => vec!["OPENCODE_API_KEY"],
-        "vercel" | "vercel-ai" => vec!["VERCEL_API_KEY"],
-        "cloudflare" | "cloudflare-ai" => vec!["CLOUDFLARE_API_KEY"],
-        _ => vec![],
-    };
-
-    for env_var in provider_env_candidates {
+    for env_var in provider_env_vars(name) {
         if let Ok(value) = std::env::var(env_var) {
             let value = value.trim();
             if !value.is_empty() {
@@ -139,16 +291,25 @@ fn resolve_api_key(name: &str, api_key: Option<&str>) -> Option<String> {
         }
     }
 
-    for env_var in ["ZEROCLAW_API_KEY", "API_KEY"] {
-        if let Ok(value) = std::env::var(env_var) {
-            let value = value.trim();
-            if !value.is_empty() {
-                return Some(value.to_string());
-            }
-        }
-    }
+    crate::config::schema::env_var_or_legacy("TINYCLAW_API_KEY", &["ZEROCLAW_API_KEY", "API_KEY"])
+        .map(|value| value.trim().to_string())
+}
 
-    None
+/// Pick the configured key for provider `name`: a model route's `api_key`,
+/// then `[providers.<name>] api_key`, then the top-level `api_key`. `None`
+/// leaves [`create_provider`] to fall back to the provider's env var.
+fn provider_api_key<'a>(
+    name: &str,
+    api_key: Option<&'a str>,
+    providers: &'a crate::config::ProvidersConfig,
+    model_routes: &'a [crate::config::ModelRouteConfig],
+) -> Option<&'a str> {
+    model_routes
+        .iter()
+        .find(|r| r.provider == name)
+        .and_then(|r| r.api_key.as_deref())
+        .or_else(|| providers.api_key_for(name))
+        .or(api_key)
 }
 
 /// Factory: create the right provider from config
@@ -273,17 +434,46 @@ pub fn create_provider(name: &str, api_key: Option<&str>) -> anyhow::Result<Box<
     }
 }
 
+/// [`create_provider`] plus the per-provider settings in `[providers.<name>]`
+/// that go beyond the API key.
+fn create_configured_provider(
+    name: &str,
+    api_key: Option<&str>,
+    provider_config: &crate::config::ProvidersConfig,
+) -> anyhow::Result<Box<dyn Provider>> {
+    let entry = provider_config.entries.get(name);
+    let provider: Box<dyn Provider> = match (name, entry) {
+        ("openrouter", Some(entry)) => {
+            let key = resolve_api_key(name, api_key);
+            Box::new(
+                openrouter::OpenRouterProvider::new(key.as_deref())
+                    .with_attribution(entry.referer.as_deref(), entry.app_title.as_deref()),
+            )
+        }
+        _ => create_provider(name, api_key)?,
+    };
+    let force_fold = entry.is_some_and(|entry| entry.fold_system_into_user);
+    let provider = fold_system::fold_system_if_needed(provider, force_fold);
+    let provider = capped::with_response_cap(provider, provider_config.max_response_chars);
+    Ok(throttle::with_shared_throttle(provider))
+}
+
 /// Create provider chain with retry and fallback behavior.
 pub fn create_resilient_provider(
     primary_name: &str,
     api_key: Option<&str>,
+    provider_config: &crate::config::ProvidersConfig,
     reliability: &crate::config::ReliabilityConfig,
 ) -> anyhow::Result<Box<dyn Provider>> {
     let mut providers: Vec<(String, Box<dyn Provider>)> = Vec::new();
 
     providers.push((
         primary_name.to_string(),
-        create_provider(primary_name, api_key)?,
+        create_configured_provider(
+            primary_name,
+            provider_api_key(primary_name, api_key, provider_config, &[]),
+            provider_config,
+        )?,
     ));
 
     for fallback in &reliability.fallback_providers {
@@ -291,16 +481,18 @@ pub fn create_resilient_provider(
             continue;
         }
 
-        if api_key.is_some() && fallback != "ollama" {
+        let own_key = provider_config.api_key_for(fallback);
+        if own_key.is_none() && api_key.is_some() && fallback != "ollama" {
             tracing::warn!(
                 fallback_provider = fallback,
                 primary_provider = primary_name,
                 "Fallback provider will use the primary provider's API key — \
-                 this will fail if the providers require different keys"
+                 this will fail if the providers require different keys \
+                 (set [providers.<name>] api_key)"
             );
         }
 
-        match create_provider(fallback, api_key) {
+        match create_configured_provider(fallback, own_key.or(api_key), provider_config) {
             Ok(provider) => providers.push((fallback.clone(), provider)),
             Err(e) => {
                 tracing::warn!(
@@ -311,25 +503,83 @@ pub fn create_resilient_provider(
         }
     }
 
-    Ok(Box::new(ReliableProvider::new(
-        providers,
-        reliability.provider_retries,
-        reliability.provider_backoff_ms,
+    Ok(Box::new(
+        ReliableProvider::new(
+            providers,
+            reliability.provider_retries,
+            reliability.provider_backoff_ms,
+        )
+        .with_circuit_breaker(
+            reliability.circuit_breaker_threshold,
+            std::time::Duration::from_secs(reliability.circuit_breaker_cooldown_secs),
+        ),
+    ))
+}
+
+/// Create a `RaceProvider` from `reliability.race_providers`, which must name
+/// exactly two distinct providers.
+pub fn create_race_provider(
+    api_key: Option<&str>,
+    provider_config: &crate::config::ProvidersConfig,
+    reliability: &crate::config::ReliabilityConfig,
+) -> anyhow::Result<Box<dyn Provider>> {
+    let [first, second] = reliability.race_providers.as_slice() else {
+        anyhow::bail!(
+            "reliability.race requires exactly two race_providers (got {})",
+            reliability.race_providers.len()
+        );
+    };
+    if first == second {
+        anyhow::bail!("reliability.race_providers must name two different providers");
+    }
+
+    let first_key = provider_api_key(first, api_key, provider_config, &[]);
+    let second_key = provider_api_key(second, api_key, provider_config, &[]);
+    Ok(Box::new(race::RaceProvider::new(
+        (
+            first.clone(),
+            create_configured_provider(first, first_key, provider_config)?,
+        ),
+        (
+            second.clone(),
+            create_configured_provider(second, second_key, provider_config)?,
+        ),
     )))
 }
 
+/// The `temperature` of the `[[model_routes]]` entry that `model` selects
+/// (`hint:<name>`), if that route sets one.
+pub fn route_temperature(
+    model_routes: &[crate::config::ModelRouteConfig],
+    model: &str,
+) -> Option<f64> {
+    let hint = model.strip_prefix("hint:")?;
+    model_routes
+        .iter()
+        .find(|route| route.hint == hint)
+        .and_then(|route| route.temperature)
+}
+
 /// Create a RouterProvider if model routes are configured, otherwise return a
 /// standard resilient provider. The router wraps individual providers per route,
 /// each with its own retry/fallback chain.
 pub fn create_routed_provider(
     primary_name: &str,
     api_key: Option<&str>,
+    provider_config: &crate::config::ProvidersConfig,
     reliability: &crate::config::ReliabilityConfig,
     model_routes: &[crate::config::ModelRouteConfig],
     default_model: &str,
 ) -> anyhow::Result<Box<dyn Provider>> {
+    if reliability.race {
+        if !model_routes.is_empty() {
+            tracing::warn!("Provider racing is enabled; model_routes are ignored");
+        }
+        return create_race_provider(api_key, provider_config, reliability);
+    }
+
     if model_routes.is_empty() {
-        return create_resilient_provider(primary_name, api_key, reliability);
+        return create_resilient_provider(primary_name, api_key, provider_config, reliability);
     }
 
     // Collect unique provider names needed
@@ -343,12 +593,8 @@ pub fn create_routed_provider(
     // Create each provider (with its own resilience wrapper)
     let mut providers: Vec<(String, Box<dyn Provider>)> = Vec::new();
     for name in &needed {
-        let key = model_routes
-            .iter()
-            .find(|r| &r.provider == name)
-            .and_then(|r| r.api_key.as_deref())
-            .or(api_key);
-        match create_resilient_provider(name, key, reliability) {
+        let key = provider_api_key(name, api_key, provider_config, model_routes);
+        match create_resilient_provider(name, key, provider_config, reliability) {
             Ok(provider) => providers.push((name.clone(), provider)),
             Err(e) => {
                 if name == primary_name {
@@ -383,10 +629,116 @@ pub fn create_routed_provider(
     )))
 }
 
+/// Wrap `provider` in a [`cache::CachedProvider`] when `reliability.cache` is
+/// on; otherwise return it unchanged.
+pub fn with_response_cache(
+    provider: Box<dyn Provider>,
+    provider_name: &str,
+    reliability: &crate::config::ReliabilityConfig,
+    workspace_dir: &std::path::Path,
+) -> Box<dyn Provider> {
+    if !reliability.cache {
+        return provider;
+    }
+    Box::new(cache::CachedProvider::new(
+        provider,
+        provider_name,
+        workspace_dir,
+        std::time::Duration::from_secs(reliability.cache_ttl_secs),
+    ))
+}
+
+/// Warm up `provider`'s connection pool (TLS handshake, DNS, HTTP/2 setup)
+/// so the first real message doesn't hit a cold-start timeout. Warmup only
+/// saves time, so a failure is logged and never stops startup; returns
+/// whether it succeeded.
+pub async fn warm_up(provider: &dyn Provider) -> bool {
+    match provider.warmup().await {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!(
+                provider = provider.name(),
+                "Provider warmup failed (non-fatal): {e}"
+            );
+            false
+        }
+    }
+}
+
+/// [`warm_up`] on a background task, so startup doesn't wait for it.
+/// `provider` is usually a copy of the one in use: both get the same
+/// [`http::shared_client`], so the connections it opens are reused.
+pub fn warm_up_in_background(provider: Box<dyn Provider>) {
+    tokio::spawn(async move {
+        warm_up(provider.as_ref()).await;
+    });
+}
+
+/// Render one row of `tinyclaw models` output.
+pub fn format_model_line(model: &ModelInfo) -> String {
+    let context = model
+        .context_length
+        .map_or_else(|| "-".to_string(), |c| format!("{c}"));
+    let pricing = match (model.prompt_price_per_m, model.completion_price_per_m) {
+        (Some(input), Some(output)) => format!("${input:.2}/${output:.2} per 1M"),
+        (Some(input), None) => format!("${input:.2} per 1M in"),
+        _ => "-".to_string(),
+    };
+    format!("{:<48} {:>9}  {pricing}", model.id, context)
+}
+
+/// Query a provider's model catalogue and print it, sorted by id.
+pub async fn print_models(provider_name: &str, api_key: Option<&str>) -> anyhow::Result<()> {
+    let provider = create_provider(provider_name, api_key)?;
+    let mut models = provider.list_models().await?;
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+
+    if models.is_empty() {
+        println!("No models reported by {provider_name}.");
+        return Ok(());
+    }
+
+    println!("{:<48} {:>9}  PRICE (in/out)", "MODEL", "CONTEXT");
+    for model in &models {
+        println!("{}", format_model_line(model));
+    }
+    println!();
+    println!("{} models from {provider_name}", models.len());
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // ── Model listing ────────────────────────────────────────
+
+    #[test]
+    fn format_model_line_with_context_and_pricing() {
+        let line = format_model_line(&ModelInfo {
+            id: "anthropic/claude-sonnet-4".into(),
+            context_length: Some(200_000),
+            prompt_price_per_m: Some(3.0),
+            completion_price_per_m: Some(15.0),
+        });
+        assert!(line.starts_with("anthropic/claude-sonnet-4"));
+        assert!(line.contains("200000"));
+        assert!(line.contains("$3.00/$15.00 per 1M"));
+    }
+
+    #[test]
+    fn format_model_line_without_metadata() {
+        let line = format_model_line(&ModelInfo::new("llama3:latest"));
+        assert!(line.starts_with("llama3:latest"));
+        assert!(line.trim_end().ends_with('-'));
+    }
+
+    #[tokio::test]
+    async fn print_models_rejects_unsupported_provider() {
+        let err = print_models("groq", Some("gsk-test")).await.unwrap_err();
+        assert!(err.to_string().contains("does not support listing models"));
+    }
+
     // ── Primary providers ────────────────────────────────────
 
     #[test]
@@ -627,23 +979,229 @@ mod tests {
                 "openai".into(),
                 "openai".into(),
             ],
+            circuit_breaker_threshold: 5,
+            circuit_breaker_cooldown_secs: 30,
             channel_initial_backoff_secs: 2,
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
+            race: false,
+            race_providers: Vec::new(),
+            cache: false,
+            cache_ttl_secs: 60,
+            requests_per_minute: 0,
         };
 
-        let provider = create_resilient_provider("openrouter", Some("sk-test"), &reliability);
+        let provider =
+            create_resilient_provider("openrouter", Some("sk-test"), &no_keys(), &reliability);
         assert!(provider.is_ok());
     }
 
     #[test]
     fn resilient_provider_errors_for_invalid_primary() {
         let reliability = crate::config::ReliabilityConfig::default();
-        let provider = create_resilient_provider("totally-invalid", Some("sk-test"), &reliability);
+        let provider =
+            create_resilient_provider("totally-invalid", Some("sk-test"), &no_keys(), &reliability);
         assert!(provider.is_err());
     }
 
+    #[test]
+    fn routed_provider_builds_race_when_enabled() {
+        let reliability = crate::config::ReliabilityConfig {
+            race: true,
+            race_providers: vec!["openrouter".into(), "openai".into()],
+            ..crate::config::ReliabilityConfig::default()
+        };
+        let provider = create_routed_provider(
+            "openrouter",
+            Some("sk-test"),
+            &no_keys(),
+            &reliability,
+            &[],
+            "model",
+        );
+        assert!(provider.is_ok());
+    }
+
+    #[test]
+    fn race_provider_requires_two_distinct_providers() {
+        let mut reliability = crate::config::ReliabilityConfig {
+            race: true,
+            race_providers: vec!["openrouter".into()],
+            ..crate::config::ReliabilityConfig::default()
+        };
+        assert!(create_race_provider(Some("sk-test"), &no_keys(), &reliability).is_err());
+
+        reliability.race_providers = vec!["openai".into(), "openai".into()];
+        assert!(create_race_provider(Some("sk-test"), &no_keys(), &reliability).is_err());
+
+        reliability.race_providers = vec!["openai".into(), "totally-invalid".into()];
+        assert!(create_race_provider(Some("sk-test"), &no_keys(), &reliability).is_err());
+    }
+
+    /// Answers one chat completion request and returns its JSON body.
+    async fn capture_chat_request(listener: tokio::net::TcpListener) -> serde_json::Value {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 4096];
+        let body_start = loop {
+            let n = socket.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+            if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                break end + 4;
+            }
+        };
+        let headers = String::from_utf8_lossy(&request[..body_start]).to_lowercase();
+        let length: usize = headers
+            .lines()
+            .find_map(|l| l.strip_prefix("content-length:"))
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+        while request.len() < body_start + length {
+            let n = socket.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+        }
+        let reply = r#"{"choices":[{"message":{"content":"ok"}}]}"#;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{reply}",
+            reply.len()
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        serde_json::from_slice(&request[body_start..]).unwrap()
+    }
+
+    #[tokio::test]
+    async fn configured_provider_folds_system_prompt_into_user_message() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let name = format!("custom:http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(capture_chat_request(listener));
+
+        let mut config = keys(&[(name.as_str(), "sk-test")]);
+        config.entries.get_mut(&name).unwrap().fold_system_into_user = true;
+        let provider =
+            create_configured_provider(&name, config.api_key_for(&name), &config).unwrap();
+        let history = [
+            ChatMessage::system("Answer in French."),
+            ChatMessage::user("Hello"),
+        ];
+        let reply = provider
+            .chat_with_history(&history, "model", 0.0)
+            .await
+            .unwrap();
+        assert_eq!(reply, "ok");
+
+        let body = server.await.unwrap();
+        assert_eq!(
+            body["messages"],
+            serde_json::json!([{"role": "user", "content": "Answer in French.\n\nHello"}])
+        );
+    }
+
+    // ── Per-provider API keys ────────────────────────────────
+
+    fn no_keys() -> crate::config::ProvidersConfig {
+        crate::config::ProvidersConfig::default()
+    }
+
+    fn keys(pairs: &[(&str, &str)]) -> crate::config::ProvidersConfig {
+        let mut config = no_keys();
+        for (name, key) in pairs {
+            config.entries.insert(
+                (*name).to_string(),
+                crate::config::schema::ProviderEntryConfig {
+                    api_key: Some((*key).to_string()),
+                    ..Default::default()
+                },
+            );
+        }
+        config
+    }
+
+    #[test]
+    fn provider_api_key_selected_per_provider() {
+        let config = keys(&[("openai", "sk-openai"), ("anthropic", "sk-ant")]);
+        let shared = Some("sk-shared");
+
+        assert_eq!(
+            provider_api_key("openai", shared, &config, &[]),
+            Some("sk-openai")
+        );
+        assert_eq!(
+            provider_api_key("anthropic", shared, &config, &[]),
+            Some("sk-ant")
+        );
+        // Unconfigured providers fall back to the top-level key, then to env
+        assert_eq!(
+            provider_api_key("groq", shared, &config, &[]),
+            Some("sk-shared")
+        );
+        assert_eq!(provider_api_key("groq", None, &config, &[]), None);
+    }
+
+    #[test]
+    fn provider_api_key_prefers_route_override() {
+        let config = keys(&[("anthropic", "sk-ant"), ("openai", "  ")]);
+        let routes = vec![crate::config::ModelRouteConfig {
+            hint: "reasoning".into(),
+            provider: "anthropic".into(),
+            model: "claude-sonnet-4".into(),
+            api_key: Some("sk-route".into()),
+            temperature: None,
+        }];
+
+        assert_eq!(
+            provider_api_key("anthropic", None, &config, &routes),
+            Some("sk-route")
+        );
+        // A blank per-provider key counts as unset
+        assert_eq!(
+            provider_api_key("openai", Some("sk-shared"), &config, &routes),
+            Some("sk-shared")
+        );
+    }
+
+    #[test]
+    fn route_temperature_applies_only_to_its_hint() {
+        let route = |hint: &str, temperature| crate::config::ModelRouteConfig {
+            hint: hint.into(),
+            provider: "openai".into(),
+            model: "gpt-4o".into(),
+            api_key: None,
+            temperature,
+        };
+        let routes = [route("code", Some(0.1)), route("chat", None)];
+
+        assert_eq!(route_temperature(&routes, "hint:code"), Some(0.1));
+        assert_eq!(route_temperature(&routes, "hint:chat"), None);
+        assert_eq!(route_temperature(&routes, "hint:unknown"), None);
+        assert_eq!(route_temperature(&routes, "code"), None);
+    }
+
+    #[test]
+    fn routed_provider_uses_per_provider_keys() {
+        let config = keys(&[("openai", "sk-openai"), ("anthropic", "sk-ant")]);
+        let routes = vec![crate::config::ModelRouteConfig {
+            hint: "reasoning".into(),
+            provider: "anthropic".into(),
+            model: "claude-sonnet-4".into(),
+            api_key: None,
+            temperature: None,
+        }];
+        let provider = create_routed_provider(
+            "openai",
+            None,
+            &config,
+            &crate::config::ReliabilityConfig::default(),
+            &routes,
+            "gpt-4o",
+        );
+        assert!(provider.is_ok());
+    }
+
     #[test]
     fn factory_all_providers_create_successfully() {
         let providers = [
@@ -681,6 +1239,127 @@ mod tests {
         }
     }
 
+    #[test]
+    fn each_provider_resolves_to_its_own_default_model() {
+        let openrouter = default_model("openrouter");
+        let providers = [
+            "anthropic",
+            "anthropic-custom:https://api.example.com",
+            "openai",
+            "ollama",
+            "google",
+            "venice",
+            "vercel",
+            "cloudflare",
+            "kimi",
+            "synthetic",
+            "opencode",
+            "zai",
+            "glm",
+            "minimax",
+            "bedrock",
+            "qianfan",
+            "groq",
+            "mistral",
+            "grok",
+            "deepseek",
+            "together",
+            "fireworks",
+            "perplexity",
+            "cohere",
+            "copilot",
+            "custom:http://localhost:1234",
+        ];
+        for name in providers {
+            assert!(create_provider(name, Some("test-key")).is_ok());
+            let model = default_model(name);
+            assert!(
+                !model.is_empty() && !model.contains(char::is_whitespace),
+                "default model of '{name}': {model:?}"
+            );
+            // The OpenRouter id is what other providers used to be sent
+            assert_ne!(model, openrouter, "'{name}' has no default of its own");
+        }
+        assert_eq!(default_model("anthropic"), "claude-sonnet-4-20250514");
+        assert_eq!(default_model("openai"), "gpt-4o");
+        assert_eq!(default_model("ollama"), "llama3.2");
+        assert_eq!(openrouter, "anthropic/claude-sonnet-4-20250514");
+    }
+
+    #[test]
+    fn each_provider_reports_its_name() {
+        let expected = [
+            ("openrouter", "openrouter"),
+            ("anthropic", "anthropic"),
+            ("anthropic-custom:https://api.example.com", "anthropic"),
+            ("openai", "openai"),
+            ("ollama", "ollama"),
+            ("google", "gemini"),
+            ("venice", "venice"),
+            ("vercel", "vercel-ai-gateway"),
+            ("cloudflare", "cloudflare-ai-gateway"),
+            ("kimi", "moonshot"),
+            ("synthetic", "synthetic"),
+            ("opencode", "opencode-zen"),
+            ("zai", "z-ai"),
+            ("glm", "glm"),
+            ("minimax", "minimax"),
+            ("bedrock", "amazon-bedrock"),
+            ("qianfan", "qianfan"),
+            ("groq", "groq"),
+            ("mistral", "mistral"),
+            ("grok", "xai"),
+            ("deepseek", "deepseek"),
+            ("together", "together-ai"),
+            ("fireworks", "fireworks-ai"),
+            ("perplexity", "perplexity"),
+            ("cohere", "cohere"),
+            ("copilot", "github-copilot"),
+            ("custom:http://localhost:1234", "custom"),
+        ];
+        for (key, name) in expected {
+            let provider = create_provider(key, Some("test-key")).unwrap();
+            assert_eq!(provider.name(), name, "name of provider '{key}'");
+        }
+    }
+
+    #[test]
+    fn wrappers_report_their_primary_provider() {
+        let reliability = crate::config::ReliabilityConfig {
+            fallback_providers: vec!["openai".into()],
+            ..crate::config::ReliabilityConfig::default()
+        };
+        let resilient =
+            create_resilient_provider("groq", Some("sk-test"), &no_keys(), &reliability).unwrap();
+        assert_eq!(resilient.name(), "groq");
+
+        let model_routes = [crate::config::ModelRouteConfig {
+            hint: "fast".into(),
+            provider: "openai".into(),
+            model: "gpt-4o-mini".into(),
+            api_key: None,
+            temperature: None,
+        }];
+        let routed = create_routed_provider(
+            "anthropic",
+            Some("sk-test"),
+            &no_keys(),
+            &reliability,
+            &model_routes,
+            "model",
+        )
+        .unwrap();
+        assert_eq!(routed.name(), "anthropic");
+
+        let racing = crate::config::ReliabilityConfig {
+            race: true,
+            race_providers: vec!["openai".into(), "openrouter".into()],
+            ..crate::config::ReliabilityConfig::default()
+        };
+        let raced = create_race_provider(Some("sk-test"), &no_keys(), &racing).unwrap();
+        assert_eq!(raced.name(), "openai");
+    }
+
     // ── API error sanitization ───────────────────────────────
 
     #[test]
@@ -768,4 +1447,144 @@ mod tests {
         let result = sanitize_api_error(input);
         assert_eq!(result, input);
     }
+
+    #[test]
+    fn unauthorized_status_carries_provider_remediation() {
+        let err = status_error("OpenAI", reqwest::StatusCode::UNAUTHORIZED, "invalid key");
+        let auth = auth_failure(&err).expect("401 should be an auth error");
+        assert_eq!(
+            auth.summary(),
+            "OpenAI API error (401 Unauthorized): invalid key"
+        );
+        let text = err.to_string();
+        assert!(text.contains("export OPENAI_API_KEY="), "{text}");
+        assert!(text.contains("[providers.openai]"), "{text}");
+        assert!(
+            text.contains("tinyclaw onboard --provider openai"),
+            "{text}"
+        );
+
+        let text = status_error("Together AI", reqwest::StatusCode::UNAUTHORIZED, "").to_string();
+        assert!(text.contains("TOGETHER_API_KEY"), "{text}");
+        assert!(text.contains("[providers.together]"), "{text}");
+
+        let text = status_error("Anthropic", reqwest::StatusCode::UNAUTHORIZED, "").to_string();
+        assert!(
+            text.contains("ANTHROPIC_OAUTH_TOKEN=<your key> (or ANTHROPIC_API_KEY)"),
+            "{text}"
+        );
+
+        let text = status_error("Gemini", reqwest::StatusCode::UNAUTHORIZED, "").to_string();
+        assert!(text.contains("GEMINI_API_KEY"), "{text}");
+    }
+
+    #[test]
+    fn forbidden_is_auth_error_other_statuses_are_not() {
+        let err = status_error("Groq", reqwest::StatusCode::FORBIDDEN, "no access");
+        assert!(auth_failure(&err).is_some());
+        assert!(err.to_string().contains("denied access"));
+
+        let err = status_error("Groq", reqwest::StatusCode::INTERNAL_SERVER_ERROR, "boom");
+        assert!(auth_failure(&err).is_none());
+        assert_eq!(
+            err.to_string(),
+            "Groq API error (500 Internal Server Error): boom"
+        );
+    }
+
+    #[tokio::test]
+    async fn provider_401_response_yields_remediation() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 8192];
+                let _ = socket.read(&mut buf).await;
+                let body = r#"{"error":{"message":"Incorrect API key provided"}}"#;
+                let response = format!(
+                    "HTTP/1.1 401 Unauthorized\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let provider = create_provider(&format!("custom:http://{addr}"), Some("bad-key")).unwrap();
+        let err = provider
+            .chat_with_system(None, "hi", "model", 0.7)
+            .await
+            .unwrap_err();
+        let auth = auth_failure(&err).expect("401 should be an auth error");
+        assert_eq!(auth.status, reqwest::StatusCode::UNAUTHORIZED);
+        assert!(auth.detail.contains("Incorrect API key"), "{}", auth.detail);
+        assert!(auth.guidance().contains("top-level `api_key`"));
+    }
+
+    /// Can't reach its endpoint during warmup.
+    struct UnreachableProvider;
+
+    #[async_trait::async_trait]
+    impl Provider for UnreachableProvider {
+        fn name(&self) -> &str {
+            "unreachable"
+        }
+
+        async fn warmup(&self) -> anyhow::Result<()> {
+            anyhow::bail!("connection refused")
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("still works".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_warmup_is_reported_not_propagated() {
+        let provider = UnreachableProvider;
+        assert!(!warm_up(&provider).await);
+        assert_eq!(provider.chat("hi", "m", 0.7).await.unwrap(), "still works");
+
+        let ollama = create_provider("ollama", None).unwrap();
+        assert!(warm_up(ollama.as_ref()).await, "no-op warmups succeed");
+    }
+
+    /// Signals that its warmup started, then never finishes it.
+    struct HangingWarmup(std::sync::Arc<tokio::sync::Notify>);
+
+    #[async_trait::async_trait]
+    impl Provider for HangingWarmup {
+        fn name(&self) -> &str {
+            "hanging"
+        }
+
+        async fn warmup(&self) -> anyhow::Result<()> {
+            self.0.notify_one();
+            std::future::pending().await
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("ok".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn background_warmup_runs_without_blocking_the_caller() {
+        let started = std::sync::Arc::new(tokio::sync::Notify::new());
+        warm_up_in_background(Box::new(HangingWarmup(started.clone())));
+        started.notified().await;
+    }
 }
@@ -1,4 +1,4 @@
-use crate::providers::traits::Provider;
+use crate::providers::traits::{ModelInfo, Provider};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -37,6 +37,17 @@ struct ResponseMessage {
     content: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct TagsResponse {
+    #[serde(default)]
+    models: Vec<LocalModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalModel {
+    name: String,
+}
+
 impl OllamaProvider {
     pub fn new(base_url: Option<&str>) -> Self {
         Self {
@@ -44,17 +55,17 @@ impl OllamaProvider {
                 .unwrap_or("http://localhost:11434")
                 .trim_end_matches('/')
                 .to_string(),
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(300)) // Ollama runs locally, may be slow
-                .connect_timeout(std::time::Duration::from_secs(10))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
+            client: super::http::shared_client(std::time::Duration::from_secs(300)), // Ollama runs locally, may be slow
         }
     }
 }
 
 #[async_trait]
 impl Provider for OllamaProvider {
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
     async fn chat_with_system(
         &self,
         system_prompt: Option<&str>,
@@ -95,6 +106,24 @@ impl Provider for OllamaProvider {
         let chat_response: ChatResponse = response.json().await?;
         Ok(chat_response.message.content)
     }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let url = format!("{}/api/tags", self.base_url);
+
+        let response = self.client.get(&url).send().await?;
+
+        if !response.status().is_success() {
+            let err = super::api_error("Ollama", response).await;
+            anyhow::bail!("{err}. Is Ollama running? (brew install ollama && ollama serve)");
+        }
+
+        let tags: TagsResponse = response.json().await?;
+        Ok(tags
+            .models
+            .into_iter()
+            .map(|m| ModelInfo::new(m.name))
+            .collect())
+    }
 }
 
 #[cfg(test)]
@@ -179,6 +208,21 @@ mod tests {
         assert!(resp.message.content.is_empty());
     }
 
+    #[test]
+    fn tags_response_deserializes() {
+        let json =
+            r#"{"models":[{"name":"llama3:latest","size":4661224676},{"name":"mistral:7b"}]}"#;
+        let resp: TagsResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.models.len(), 2);
+        assert_eq!(resp.models[0].name, "llama3:latest");
+    }
+
+    #[test]
+    fn tags_response_without_models() {
+        let resp: TagsResponse = serde_json::from_str("{}").unwrap();
+        assert!(resp.models.is_empty());
+    }
+
     #[test]
     fn response_with_multiline() {
         let json = r#"{"message":{"role":"assistant","content":"line1\nline2\nline3"}}"#;
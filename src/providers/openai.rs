@@ -1,4 +1,6 @@
-use crate::providers::traits::{Provider, TokenUsage, UsageTracker};
+use crate::providers::traits::{
+    validate_stop_sequences, ModelInfo, Provider, ReasoningEffort, TokenUsage, UsageTracker,
+};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -7,13 +9,25 @@ pub struct OpenAiProvider {
     api_key: Option<String>,
     client: Client,
     usage_tracker: Option<UsageTracker>,
+    seed: Option<u64>,
+    reasoning_effort: Option<ReasoningEffort>,
+    stop: Vec<String>,
 }
 
+/// Most stop sequences the Chat Completions API accepts.
+pub(crate) const MAX_STOP_SEQUENCES: usize = 4;
+
 #[derive(Debug, Serialize)]
 struct ChatRequest {
     model: String,
     messages: Vec<Message>,
     temperature: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reasoning_effort: Option<ReasoningEffort>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -27,6 +41,8 @@ struct ChatResponse {
     choices: Vec<Choice>,
     #[serde(default)]
     usage: Option<ApiUsage>,
+    #[serde(default)]
+    system_fingerprint: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -49,16 +65,25 @@ struct ResponseMessage {
     content: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct ModelsResponse {
+    data: Vec<ApiModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiModel {
+    id: String,
+}
+
 impl OpenAiProvider {
     pub fn new(api_key: Option<&str>) -> Self {
         Self {
             api_key: api_key.map(ToString::to_string),
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(120))
-                .connect_timeout(std::time::Duration::from_secs(10))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
+            client: super::http::shared_client(std::time::Duration::from_secs(120)),
             usage_tracker: None,
+            seed: None,
+            reasoning_effort: None,
+            stop: Vec::new(),
         }
     }
 
@@ -71,10 +96,25 @@ impl OpenAiProvider {
             });
         }
     }
+
+    fn track_fingerprint(&self, fingerprint: Option<&str>) {
+        if let (Some(tracker), Some(fp)) = (&self.usage_tracker, fingerprint) {
+            tracker.record_system_fingerprint(fp);
+        }
+    }
 }
 
 #[async_trait]
 impl Provider for OpenAiProvider {
+    fn name(&self) -> &str {
+        "openai"
+    }
+
+    /// The `o1` reasoning models reject the `system` role.
+    fn supports_system_prompt(&self, model: &str) -> bool {
+        !model.starts_with("o1")
+    }
+
     async fn chat_with_system(
         &self,
         system_prompt: Option<&str>,
@@ -104,6 +144,9 @@ impl Provider for OpenAiProvider {
             model: model.to_string(),
             messages,
             temperature,
+            seed: self.seed,
+            reasoning_effort: self.reasoning_effort,
+            stop: self.stop.clone(),
         };
 
         let response = self
@@ -120,6 +163,7 @@ impl Provider for OpenAiProvider {
 
         let chat_response: ChatResponse = response.json().await?;
         self.track_usage(&chat_response.usage);
+        self.track_fingerprint(chat_response.system_fingerprint.as_deref());
 
         chat_response
             .choices
@@ -132,6 +176,44 @@ impl Provider for OpenAiProvider {
     fn set_usage_tracker(&mut self, tracker: UsageTracker) {
         self.usage_tracker = Some(tracker);
     }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    fn set_reasoning_effort(&mut self, effort: Option<ReasoningEffort>) {
+        self.reasoning_effort = effort;
+    }
+
+    fn set_stop_sequences(&mut self, stop: &[String]) -> anyhow::Result<()> {
+        validate_stop_sequences("OpenAI", stop, Some(MAX_STOP_SEQUENCES))?;
+        self.stop = stop.to_vec();
+        Ok(())
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let api_key = self.api_key.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("OpenAI API key not set. Set OPENAI_API_KEY or edit config.toml.")
+        })?;
+
+        let response = self
+            .client
+            .get("https://api.openai.com/v1/models")
+            .header("Authorization", format!("Bearer {api_key}"))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(super::api_error("OpenAI", response).await);
+        }
+
+        let models: ModelsResponse = response.json().await?;
+        Ok(models
+            .data
+            .into_iter()
+            .map(|m| ModelInfo::new(m.id))
+            .collect())
+    }
 }
 
 #[cfg(test)]
@@ -156,6 +238,14 @@ mod tests {
         assert_eq!(p.api_key.as_deref(), Some(""));
     }
 
+    #[test]
+    fn o1_models_take_no_system_prompt() {
+        let p = OpenAiProvider::new(None);
+        assert!(!p.supports_system_prompt("o1-mini"));
+        assert!(!p.supports_system_prompt("o1"));
+        assert!(p.supports_system_prompt("gpt-4o"));
+    }
+
     #[tokio::test]
     async fn chat_fails_without_key() {
         let p = OpenAiProvider::new(None);
@@ -188,6 +278,9 @@ mod tests {
                 },
             ],
             temperature: 0.7,
+            seed: None,
+            reasoning_effort: None,
+            stop: vec![],
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"role\":\"system\""));
@@ -204,12 +297,92 @@ mod tests {
                 content: "hello".to_string(),
             }],
             temperature: 0.0,
+            seed: None,
+            reasoning_effort: None,
+            stop: vec![],
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(!json.contains("system"));
         assert!(json.contains("\"temperature\":0.0"));
     }
 
+    #[test]
+    fn request_serializes_seed_when_set() {
+        let mut p = OpenAiProvider::new(Some("sk-test"));
+        p.set_seed(Some(42));
+        let req = ChatRequest {
+            model: "gpt-4o".to_string(),
+            messages: vec![],
+            temperature: 0.0,
+            seed: p.seed,
+            reasoning_effort: None,
+            stop: vec![],
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["seed"], 42);
+        assert!(json.get("stop").is_none());
+    }
+
+    #[test]
+    fn request_serializes_reasoning_effort_when_set() {
+        let mut p = OpenAiProvider::new(Some("sk-test"));
+        p.set_reasoning_effort(Some(ReasoningEffort::Low));
+        let req = ChatRequest {
+            model: "o3-mini".to_string(),
+            messages: vec![],
+            temperature: 1.0,
+            seed: None,
+            reasoning_effort: p.reasoning_effort,
+            stop: vec![],
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["reasoning_effort"], "low");
+
+        p.set_reasoning_effort(None);
+        let req = ChatRequest {
+            reasoning_effort: p.reasoning_effort,
+            ..req
+        };
+        assert!(serde_json::to_value(&req)
+            .unwrap()
+            .get("reasoning_effort")
+            .is_none());
+    }
+
+    #[test]
+    fn request_serializes_stop_sequences() {
+        let mut p = OpenAiProvider::new(Some("sk-test"));
+        p.set_stop_sequences(&["</answer>".into(), "\nUser:".into()])
+            .unwrap();
+        let req = ChatRequest {
+            model: "gpt-4o".to_string(),
+            messages: vec![],
+            temperature: 0.0,
+            seed: None,
+            reasoning_effort: None,
+            stop: p.stop.clone(),
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["stop"], serde_json::json!(["</answer>", "\nUser:"]));
+    }
+
+    #[test]
+    fn stop_sequences_respect_api_limit() {
+        let mut p = OpenAiProvider::new(Some("sk-test"));
+        let too_many: Vec<String> = (0..5).map(|i| format!("<stop{i}>")).collect();
+        let err = p.set_stop_sequences(&too_many).unwrap_err();
+        assert!(err.to_string().contains("at most 4"), "{err}");
+        assert!(p.set_stop_sequences(&["  ".into()]).is_err());
+        assert!(p.stop.is_empty());
+    }
+
+    #[test]
+    fn response_exposes_system_fingerprint() {
+        let json = r#"{"choices":[],"system_fingerprint":"fp_44709d6fcb"}"#;
+        let resp: ChatResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.system_fingerprint.as_deref(), Some("fp_44709d6fcb"));
+    }
+
     #[test]
     fn response_deserializes_single_choice() {
         let json = r#"{"choices":[{"message":{"content":"Hi!"}}]}"#;
@@ -248,6 +421,21 @@ mod tests {
         assert_eq!(resp.choices[0].message.content.len(), 100_000);
     }
 
+    #[tokio::test]
+    async fn list_models_fails_without_key() {
+        let p = OpenAiProvider::new(None);
+        let result = p.list_models().await;
+        assert!(result.unwrap_err().to_string().contains("API key not set"));
+    }
+
+    #[test]
+    fn models_response_deserializes() {
+        let json = r#"{"object":"list","data":[{"id":"gpt-4o","object":"model"},{"id":"gpt-4o-mini","object":"model"}]}"#;
+        let resp: ModelsResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.data.len(), 2);
+        assert_eq!(resp.data[1].id, "gpt-4o-mini");
+    }
+
     #[test]
     fn response_deserializes_with_usage() {
         let json = r#"{"choices":[{"message":{"content":"Hi!"}}],"usage":{"prompt_tokens":10,"completion_tokens":5,"total_tokens":15}}"#;
@@ -1,12 +1,39 @@
-use crate::providers::traits::{ChatMessage, Provider, TokenUsage, UsageTracker};
+use crate::providers::traits::{
+    find_stop_sequence, stop_sequence_holdback, validate_stop_sequences, ChatMessage, ModelInfo,
+    Provider, ReasoningEffort, TokenUsage, UsageTracker,
+};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
+/// Attribution sent with every chat request unless `[providers.openrouter]`
+/// sets `referer` / `app_title`.
+pub const DEFAULT_REFERER: &str = "https://github.com/theonlyhennygod/tinyclaw";
+pub const DEFAULT_APP_TITLE: &str = "TinyClaw";
+
+const CHAT_COMPLETIONS_URL: &str = "https://openrouter.ai/api/v1/chat/completions";
+
+/// Cheap authenticated endpoint hit by [`OpenRouterProvider::warmup`].
+const AUTH_KEY_URL: &str = "https://openrouter.ai/api/v1/auth/key";
+
+/// A warmup attempt taking longer than this is abandoned; the real request
+/// will hit the same network anyway.
+const WARMUP_TIMEOUT_SECS: u64 = 3;
+
+/// Pause before the one warmup retry, plus up to 250ms of jitter.
+const WARMUP_RETRY_DELAY_MS: u64 = 250;
+
 pub struct OpenRouterProvider {
     api_key: Option<String>,
     client: Client,
+    /// `HTTP-Referer` header: the app URL usage is attributed to
+    referer: String,
+    /// `X-Title` header: the app name shown in rankings
+    app_title: String,
     usage_tracker: Option<UsageTracker>,
+    seed: Option<u64>,
+    reasoning_effort: Option<ReasoningEffort>,
+    stop: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -16,6 +43,18 @@ struct ChatRequest {
     temperature: f64,
     #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reasoning: Option<ReasoningConfig>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop: Vec<String>,
+}
+
+/// The unified `reasoning` request parameter, mapped upstream per model.
+#[derive(Debug, Serialize)]
+struct ReasoningConfig {
+    effort: ReasoningEffort,
 }
 
 /// A single SSE delta chunk from the streaming API.
@@ -24,11 +63,15 @@ struct StreamChunk {
     choices: Vec<StreamChoice>,
     #[serde(default)]
     usage: Option<ApiUsage>,
+    #[serde(default)]
+    system_fingerprint: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct StreamChoice {
     delta: Option<StreamDelta>,
+    #[serde(default)]
+    finish_reason: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -40,6 +83,28 @@ struct StreamDelta {
 struct Message {
     role: String,
     content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+impl From<&ChatMessage> for Message {
+    /// Results of native tool calls keep the `tool` role; results of
+    /// `<tool_call>` blocks go as user text.
+    fn from(m: &ChatMessage) -> Self {
+        if let (Some(id), "tool") = (&m.tool_call_id, m.role.as_str()) {
+            return Self {
+                role: "tool".into(),
+                content: m.content.clone(),
+                tool_call_id: Some(id.clone()),
+            };
+        }
+        let (role, content) = m.inline_tool_results();
+        Self {
+            role: role.to_string(),
+            content: content.into_owned(),
+            tool_call_id: None,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -47,6 +112,8 @@ struct ApiChatResponse {
     choices: Vec<Choice>,
     #[serde(default)]
     usage: Option<ApiUsage>,
+    #[serde(default)]
+    system_fingerprint: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -69,17 +136,116 @@ struct ResponseMessage {
     content: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct ModelsResponse {
+    data: Vec<ApiModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiModel {
+    id: String,
+    #[serde(default)]
+    context_length: Option<u64>,
+    #[serde(default)]
+    pricing: Option<ApiPricing>,
+}
+
+/// Prices arrive as decimal strings in USD per token.
+#[derive(Debug, Deserialize)]
+struct ApiPricing {
+    #[serde(default)]
+    prompt: Option<String>,
+    #[serde(default)]
+    completion: Option<String>,
+}
+
+fn per_token_to_per_million(price: Option<&str>) -> Option<f64> {
+    price
+        .and_then(|p| p.trim().parse::<f64>().ok())
+        .filter(|p| *p >= 0.0)
+        .map(|p| p * 1_000_000.0)
+}
+
+impl From<ApiModel> for ModelInfo {
+    fn from(model: ApiModel) -> Self {
+        let pricing = model.pricing.as_ref();
+        Self {
+            id: model.id,
+            context_length: model.context_length,
+            prompt_price_per_m: per_token_to_per_million(
+                pricing.and_then(|p| p.prompt.as_deref()),
+            ),
+            completion_price_per_m: per_token_to_per_million(
+                pricing.and_then(|p| p.completion.as_deref()),
+            ),
+        }
+    }
+}
+
 impl OpenRouterProvider {
     pub fn new(api_key: Option<&str>) -> Self {
         Self {
             api_key: api_key.map(ToString::to_string),
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(120))
-                .connect_timeout(std::time::Duration::from_secs(10))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
+            client: super::http::shared_client(std::time::Duration::from_secs(120)),
+            referer: DEFAULT_REFERER.to_string(),
+            app_title: DEFAULT_APP_TITLE.to_string(),
             usage_tracker: None,
+            seed: None,
+            reasoning_effort: None,
+            stop: Vec::new(),
+        }
+    }
+
+    /// Report usage under your own app identity. Blank values keep the
+    /// defaults.
+    pub fn with_attribution(mut self, referer: Option<&str>, app_title: Option<&str>) -> Self {
+        if let Some(referer) = referer.map(str::trim).filter(|r| !r.is_empty()) {
+            self.referer = referer.to_string();
+        }
+        if let Some(title) = app_title.map(str::trim).filter(|t| !t.is_empty()) {
+            self.app_title = title.to_string();
+        }
+        self
+    }
+
+    /// Warm up against `url`, retrying once after a short jittered pause.
+    async fn warmup_against(&self, url: &str, api_key: &str) -> anyhow::Result<()> {
+        let Err(e) = self.ping(url, api_key).await else {
+            return Ok(());
+        };
+        let jitter_ms = u64::from(chrono::Utc::now().timestamp_subsec_millis() % 250);
+        tracing::debug!("OpenRouter warmup failed, retrying once: {e}");
+        tokio::time::sleep(std::time::Duration::from_millis(
+            WARMUP_RETRY_DELAY_MS + jitter_ms,
+        ))
+        .await;
+        self.ping(url, api_key).await
+    }
+
+    async fn ping(&self, url: &str, api_key: &str) -> anyhow::Result<()> {
+        let response = self
+            .client
+            .get(url)
+            .header("Authorization", format!("Bearer {api_key}"))
+            .timeout(std::time::Duration::from_secs(WARMUP_TIMEOUT_SECS))
+            .send()
+            .await?;
+        // Rate limited, but the connection is up; another try would only
+        // count against the limit
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Ok(());
         }
+        response.error_for_status()?;
+        Ok(())
+    }
+
+    /// Chat completions request with auth and attribution headers.
+    fn chat_request(&self, api_key: &str) -> reqwest::RequestBuilder {
+        self.client
+            .post(CHAT_COMPLETIONS_URL)
+            .header("Authorization", format!("Bearer {api_key}"))
+            .header("HTTP-Referer", &self.referer)
+            .header("X-Title", &self.app_title)
     }
 
     fn track_usage(&self, usage: &Option<ApiUsage>) {
@@ -91,22 +257,27 @@ impl OpenRouterProvider {
             });
         }
     }
+
+    fn track_fingerprint(&self, fingerprint: Option<&str>) {
+        if let (Some(tracker), Some(fp)) = (&self.usage_tracker, fingerprint) {
+            tracker.record_system_fingerprint(fp);
+        }
+    }
 }
 
 #[async_trait]
 impl Provider for OpenRouterProvider {
+    fn name(&self) -> &str {
+        "openrouter"
+    }
+
     async fn warmup(&self) -> anyhow::Result<()> {
         // Hit a lightweight endpoint to establish TLS + HTTP/2 connection pool.
         // This prevents the first real chat request from timing out on cold start.
-        if let Some(api_key) = self.api_key.as_ref() {
-            self.client
-                .get("https://openrouter.ai/api/v1/auth/key")
-                .header("Authorization", format!("Bearer {api_key}"))
-                .send()
-                .await?
-                .error_for_status()?;
+        match self.api_key.as_deref() {
+            Some(api_key) => self.warmup_against(AUTH_KEY_URL, api_key).await,
+            None => Ok(()),
         }
-        Ok(())
     }
 
     async fn chat_with_system(
@@ -125,12 +296,14 @@ impl Provider for OpenRouterProvider {
             messages.push(Message {
                 role: "system".to_string(),
                 content: sys.to_string(),
+                tool_call_id: None,
             });
         }
 
         messages.push(Message {
             role: "user".to_string(),
             content: message.to_string(),
+            tool_call_id: None,
         });
 
         let request = ChatRequest {
@@ -138,20 +311,14 @@ impl Provider for OpenRouterProvider {
             messages,
             temperature,
             stream: None,
+            seed: self.seed,
+            reasoning: self
+                .reasoning_effort
+                .map(|effort| ReasoningConfig { effort }),
+            stop: self.stop.clone(),
         };
 
-        let response = self
-            .client
-            .post("https://openrouter.ai/api/v1/chat/completions")
-            .header("Authorization", format!("Bearer {api_key}"))
-            .header(
-                "HTTP-Referer",
-                "https://github.com/theonlyhennygod/tinyclaw",
-            )
-            .header("X-Title", "TinyClaw")
-            .json(&request)
-            .send()
-            .await?;
+        let response = self.chat_request(api_key).json(&request).send().await?;
 
         if !response.status().is_success() {
             return Err(super::api_error("OpenRouter", response).await);
@@ -159,6 +326,7 @@ impl Provider for OpenRouterProvider {
 
         let chat_response: ApiChatResponse = response.json().await?;
         self.track_usage(&chat_response.usage);
+        self.track_fingerprint(chat_response.system_fingerprint.as_deref());
 
         chat_response
             .choices
@@ -177,33 +345,21 @@ impl Provider for OpenRouterProvider {
         let api_key = self.api_key.as_ref()
             .ok_or_else(|| anyhow::anyhow!("OpenRouter API key not set. Run `tinyclaw onboard` or set OPENROUTER_API_KEY env var."))?;
 
-        let api_messages: Vec<Message> = messages
-            .iter()
-            .map(|m| Message {
-                role: m.role.clone(),
-                content: m.content.clone(),
-            })
-            .collect();
+        let api_messages: Vec<Message> = messages.iter().map(Message::from).collect();
 
         let request = ChatRequest {
             model: model.to_string(),
             messages: api_messages,
             temperature,
             stream: None,
+            seed: self.seed,
+            reasoning: self
+                .reasoning_effort
+                .map(|effort| ReasoningConfig { effort }),
+            stop: self.stop.clone(),
         };
 
-        let response = self
-            .client
-            .post("https://openrouter.ai/api/v1/chat/completions")
-            .header("Authorization", format!("Bearer {api_key}"))
-            .header(
-                "HTTP-Referer",
-                "https://github.com/theonlyhennygod/tinyclaw",
-            )
-            .header("X-Title", "TinyClaw")
-            .json(&request)
-            .send()
-            .await?;
+        let response = self.chat_request(api_key).json(&request).send().await?;
 
         if !response.status().is_success() {
             return Err(super::api_error("OpenRouter", response).await);
@@ -211,6 +367,7 @@ impl Provider for OpenRouterProvider {
 
         let chat_response: ApiChatResponse = response.json().await?;
         self.track_usage(&chat_response.usage);
+        self.track_fingerprint(chat_response.system_fingerprint.as_deref());
 
         chat_response
             .choices
@@ -230,37 +387,32 @@ impl Provider for OpenRouterProvider {
         let api_key = self.api_key.as_ref()
             .ok_or_else(|| anyhow::anyhow!("OpenRouter API key not set."))?;
 
-        let api_messages: Vec<Message> = messages
-            .iter()
-            .map(|m| Message {
-                role: m.role.clone(),
-                content: m.content.clone(),
-            })
-            .collect();
+        let api_messages: Vec<Message> = messages.iter().map(Message::from).collect();
 
         let request = ChatRequest {
             model: model.to_string(),
             messages: api_messages,
             temperature,
             stream: Some(true),
+            seed: self.seed,
+            reasoning: self
+                .reasoning_effort
+                .map(|effort| ReasoningConfig { effort }),
+            stop: self.stop.clone(),
         };
 
-        let mut response = self
-            .client
-            .post("https://openrouter.ai/api/v1/chat/completions")
-            .header("Authorization", format!("Bearer {api_key}"))
-            .header("HTTP-Referer", "https://github.com/theonlyhennygod/tinyclaw")
-            .header("X-Title", "TinyClaw")
-            .json(&request)
-            .send()
-            .await?;
+        let mut response = self.chat_request(api_key).json(&request).send().await?;
 
         if !response.status().is_success() {
             return Err(super::api_error("OpenRouter", response).await);
         }
 
         let mut full_response = String::new();
+        // Bytes of `full_response` already streamed; the rest might be the
+        // start of a stop sequence split across chunks
+        let mut sent = 0;
         let mut last_usage: Option<ApiUsage> = None;
+        let mut finished = false;
         let mut buf = String::new();
 
         while let Some(chunk) = response.chunk().await? {
@@ -276,8 +428,11 @@ impl Provider for OpenRouterProvider {
                 }
                 if let Some(data) = line.strip_prefix("data: ") {
                     if data.trim() == "[DONE]" {
+                        if sent < full_response.len() {
+                            let _ = token_tx.send(full_response[sent..].to_string()).await;
+                        }
                         self.track_usage(&last_usage);
-                        if full_response.is_empty() {
+                        if full_response.is_empty() && !finished {
                             anyhow::bail!("No response from OpenRouter stream");
                         }
                         return Ok(full_response);
@@ -286,14 +441,32 @@ impl Provider for OpenRouterProvider {
                         if let Some(usage) = sc.usage {
                             last_usage = Some(usage);
                         }
+                        self.track_fingerprint(sc.system_fingerprint.as_deref());
                         for choice in sc.choices {
-                            if let Some(delta) = choice.delta {
-                                if let Some(content) = delta.content {
-                                    if !content.is_empty() {
-                                        full_response.push_str(&content);
-                                        let _ = token_tx.send(content).await;
-                                    }
+                            // A stop sequence ends the choice with an empty or absent delta
+                            finished |= choice.finish_reason.is_some();
+                            let Some(content) = choice.delta.and_then(|d| d.content) else {
+                                continue;
+                            };
+                            if content.is_empty() {
+                                continue;
+                            }
+                            full_response.push_str(&content);
+                            // Some upstream models ignore `stop`; cut the stream ourselves
+                            if let Some(end) = find_stop_sequence(&full_response, &self.stop) {
+                                if end > sent {
+                                    let _ =
+                                        token_tx.send(full_response[sent..end].to_string()).await;
                                 }
+                                full_response.truncate(end);
+                                self.track_usage(&last_usage);
+                                return Ok(full_response);
+                            }
+                            let ready = full_response.len()
+                                - stop_sequence_holdback(&full_response, &self.stop);
+                            if ready > sent {
+                                let _ = token_tx.send(full_response[sent..ready].to_string()).await;
+                                sent = ready;
                             }
                         }
                     }
@@ -301,9 +474,12 @@ impl Provider for OpenRouterProvider {
             }
         }
 
+        if sent < full_response.len() {
+            let _ = token_tx.send(full_response[sent..].to_string()).await;
+        }
         self.track_usage(&last_usage);
 
-        if full_response.is_empty() {
+        if full_response.is_empty() && !finished {
             anyhow::bail!("No response from OpenRouter stream");
         }
         Ok(full_response)
@@ -316,4 +492,227 @@ impl Provider for OpenRouterProvider {
     fn set_usage_tracker(&mut self, tracker: UsageTracker) {
         self.usage_tracker = Some(tracker);
     }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    fn set_reasoning_effort(&mut self, effort: Option<ReasoningEffort>) {
+        self.reasoning_effort = effort;
+    }
+
+    fn set_stop_sequences(&mut self, stop: &[String]) -> anyhow::Result<()> {
+        // OpenRouter forwards `stop` upstream; OpenAI models cap it at 4
+        validate_stop_sequences("OpenRouter", stop, Some(super::openai::MAX_STOP_SEQUENCES))?;
+        self.stop = stop.to_vec();
+        Ok(())
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        // The models catalogue is public; send the key when we have one anyway.
+        let mut request = self.client.get("https://openrouter.ai/api/v1/models");
+        if let Some(api_key) = self.api_key.as_ref() {
+            request = request.header("Authorization", format!("Bearer {api_key}"));
+        }
+
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(super::api_error("OpenRouter", response).await);
+        }
+
+        let models: ModelsResponse = response.json().await?;
+        Ok(models.data.into_iter().map(ModelInfo::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_messages_map_to_the_tool_role_only_with_a_call_id() {
+        let native =
+            serde_json::to_value(Message::from(&ChatMessage::tool_result("call_1", "a.txt")))
+                .unwrap();
+        assert_eq!(
+            native,
+            serde_json::json!({"role": "tool", "content": "a.txt", "tool_call_id": "call_1"})
+        );
+
+        let inline = serde_json::to_value(Message::from(&ChatMessage::tool("a.txt"))).unwrap();
+        assert_eq!(
+            inline,
+            serde_json::json!({"role": "user", "content": "[Tool results]\na.txt"})
+        );
+    }
+
+    #[test]
+    fn request_serializes_seed_only_when_set() {
+        let mut req = ChatRequest {
+            model: "openai/gpt-4o".to_string(),
+            messages: vec![],
+            temperature: 0.0,
+            stream: None,
+            seed: None,
+            reasoning: None,
+            stop: vec![],
+        };
+        assert!(!serde_json::to_string(&req).unwrap().contains("seed"));
+
+        req.seed = Some(7);
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["seed"], 7);
+    }
+
+    #[test]
+    fn request_serializes_reasoning_effort_as_unified_parameter() {
+        let mut p = OpenRouterProvider::new(Some("sk-or-test"));
+        let request = |p: &OpenRouterProvider| ChatRequest {
+            model: "openai/o3-mini".to_string(),
+            messages: vec![],
+            temperature: 0.0,
+            stream: None,
+            seed: None,
+            reasoning: p.reasoning_effort.map(|effort| ReasoningConfig { effort }),
+            stop: vec![],
+        };
+        assert!(serde_json::to_value(request(&p))
+            .unwrap()
+            .get("reasoning")
+            .is_none());
+
+        p.set_reasoning_effort(Some(ReasoningEffort::High));
+        let json = serde_json::to_value(request(&p)).unwrap();
+        assert_eq!(json["reasoning"], serde_json::json!({"effort": "high"}));
+    }
+
+    #[test]
+    fn request_serializes_stop_sequences() {
+        let mut p = OpenRouterProvider::new(Some("sk-or-test"));
+        p.set_stop_sequences(&["</answer>".into()]).unwrap();
+        let req = ChatRequest {
+            model: "openai/gpt-4o".to_string(),
+            messages: vec![],
+            temperature: 0.0,
+            stream: Some(true),
+            seed: None,
+            reasoning: None,
+            stop: p.stop.clone(),
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["stop"], serde_json::json!(["</answer>"]));
+
+        let too_many: Vec<String> = (0..5).map(|i| format!("#{i}")).collect();
+        assert!(p.set_stop_sequences(&too_many).is_err());
+    }
+
+    #[test]
+    fn chat_requests_carry_configured_attribution() {
+        let header = |p: &OpenRouterProvider, name: &str| {
+            let request = p.chat_request("sk-or-test").build().unwrap();
+            request.headers()[name].to_str().unwrap().to_string()
+        };
+
+        let default = OpenRouterProvider::new(Some("sk-or-test"));
+        assert_eq!(header(&default, "HTTP-Referer"), DEFAULT_REFERER);
+        assert_eq!(header(&default, "X-Title"), DEFAULT_APP_TITLE);
+
+        let custom = OpenRouterProvider::new(Some("sk-or-test"))
+            .with_attribution(Some("https://myapp.example"), Some("My App"));
+        assert_eq!(header(&custom, "HTTP-Referer"), "https://myapp.example");
+        assert_eq!(header(&custom, "X-Title"), "My App");
+        assert_eq!(header(&custom, "Authorization"), "Bearer sk-or-test");
+
+        let blank = OpenRouterProvider::new(None).with_attribution(Some(" "), None);
+        assert_eq!(header(&blank, "HTTP-Referer"), DEFAULT_REFERER);
+    }
+
+    #[test]
+    fn stream_chunk_reports_finish_reason() {
+        let data = r#"{"choices":[{"delta":{},"finish_reason":"stop"}]}"#;
+        let chunk: StreamChunk = serde_json::from_str(data).unwrap();
+        assert_eq!(chunk.choices[0].finish_reason.as_deref(), Some("stop"));
+    }
+
+    #[test]
+    fn stream_chunk_exposes_system_fingerprint() {
+        let data = r#"{"choices":[],"system_fingerprint":"fp_abc"}"#;
+        let chunk: StreamChunk = serde_json::from_str(data).unwrap();
+        assert_eq!(chunk.system_fingerprint.as_deref(), Some("fp_abc"));
+    }
+
+    #[test]
+    fn models_response_parses_context_and_pricing() {
+        let json = r#"{"data":[{"id":"anthropic/claude-sonnet-4","context_length":200000,"pricing":{"prompt":"0.000003","completion":"0.000015"}}]}"#;
+        let resp: ModelsResponse = serde_json::from_str(json).unwrap();
+        let models: Vec<ModelInfo> = resp.data.into_iter().map(ModelInfo::from).collect();
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].id, "anthropic/claude-sonnet-4");
+        assert_eq!(models[0].context_length, Some(200_000));
+        assert!((models[0].prompt_price_per_m.unwrap() - 3.0).abs() < 1e-9);
+        assert!((models[0].completion_price_per_m.unwrap() - 15.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn models_response_tolerates_missing_pricing() {
+        let json = r#"{"data":[{"id":"some/model"}]}"#;
+        let resp: ModelsResponse = serde_json::from_str(json).unwrap();
+        let info = ModelInfo::from(resp.data.into_iter().next().unwrap());
+        assert_eq!(info.id, "some/model");
+        assert!(info.context_length.is_none());
+        assert!(info.prompt_price_per_m.is_none());
+    }
+
+    #[test]
+    fn per_token_price_rejects_garbage_and_negatives() {
+        assert_eq!(per_token_to_per_million(Some("abc")), None);
+        assert_eq!(per_token_to_per_million(Some("-1")), None);
+        assert_eq!(per_token_to_per_million(None), None);
+        assert_eq!(per_token_to_per_million(Some("0")), Some(0.0));
+    }
+
+    /// Answer each request with the next of `statuses` (the last repeats);
+    /// returns the URL and a count of requests served.
+    async fn status_server(
+        statuses: &'static [u16],
+    ) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::Ordering;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hits = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = std::sync::Arc::clone(&hits);
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = [0u8; 4096];
+                let _ = socket.read(&mut buf).await;
+                let n = counter.fetch_add(1, Ordering::SeqCst);
+                let status = statuses[n.min(statuses.len() - 1)];
+                let reply = format!(
+                    "HTTP/1.1 {status} X\r\ncontent-length: 2\r\nconnection: close\r\n\r\n{{}}"
+                );
+                let _ = socket.write_all(reply.as_bytes()).await;
+            }
+        });
+        (format!("http://{addr}/api/v1/auth/key"), hits)
+    }
+
+    #[tokio::test]
+    async fn warmup_retries_once_and_treats_rate_limits_as_warm() {
+        use std::sync::atomic::Ordering;
+        let p = OpenRouterProvider::new(Some("sk-or-test"));
+
+        let (flaky, hits) = status_server(&[502, 200]).await;
+        p.warmup_against(&flaky, "sk-or-test").await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        let (limited, hits) = status_server(&[429]).await;
+        p.warmup_against(&limited, "sk-or-test").await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 1, "no retry into a rate limit");
+
+        let (down, hits) = status_server(&[503]).await;
+        assert!(p.warmup_against(&down, "sk-or-test").await.is_err());
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
 }
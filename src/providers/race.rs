@@ -0,0 +1,302 @@
+use super::traits::{ChatMessage, ModelInfo, ReasoningEffort, Served, UsageTracker};
+use super::Provider;
+use async_trait::async_trait;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Races two providers against each other and returns whichever answers first.
+///
+/// Both providers receive the same request; the slower call is dropped (and its
+/// HTTP request cancelled) as soon as the other succeeds. If the first finisher
+/// fails, the other call is awaited instead, so racing doubles as failover.
+/// This trades cost (two requests per turn) for latency.
+pub struct RaceProvider {
+    first: (String, Box<dyn Provider>),
+    second: (String, Box<dyn Provider>),
+    wins: [AtomicU64; 2],
+}
+
+impl RaceProvider {
+    pub fn new(first: (String, Box<dyn Provider>), second: (String, Box<dyn Provider>)) -> Self {
+        Self {
+            first,
+            second,
+            wins: [AtomicU64::new(0), AtomicU64::new(0)],
+        }
+    }
+
+    /// Number of races each provider has won so far.
+    pub fn wins(&self) -> [(&str, u64); 2] {
+        [
+            (self.first.0.as_str(), self.wins[0].load(Ordering::Relaxed)),
+            (self.second.0.as_str(), self.wins[1].load(Ordering::Relaxed)),
+        ]
+    }
+
+    fn contender(&self, index: usize) -> &str {
+        if index == 0 {
+            &self.first.0
+        } else {
+            &self.second.0
+        }
+    }
+
+    fn record_win(&self, index: usize) {
+        self.wins[index].fetch_add(1, Ordering::Relaxed);
+        tracing::debug!(provider = self.contender(index), "Provider won race");
+    }
+
+    async fn race<T>(
+        &self,
+        first: impl Future<Output = anyhow::Result<T>>,
+        second: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        tokio::pin!(first, second);
+
+        let (winner, result) = tokio::select! {
+            res = &mut first => (0, res),
+            res = &mut second => (1, res),
+        };
+
+        let err = match result {
+            Ok(resp) => {
+                self.record_win(winner);
+                return Ok(resp);
+            }
+            Err(e) => e,
+        };
+
+        let other = 1 - winner;
+        tracing::warn!(
+            provider = self.contender(winner),
+            "Race contender failed, waiting for the other: {err}"
+        );
+        let fallback = if other == 0 {
+            first.await
+        } else {
+            second.await
+        };
+
+        match fallback {
+            Ok(resp) => {
+                self.record_win(other);
+                Ok(resp)
+            }
+            Err(e) => anyhow::bail!(
+                "Both raced providers failed:\n{}: {err}\n{}: {e}",
+                self.contender(winner),
+                self.contender(other)
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for RaceProvider {
+    fn name(&self) -> &str {
+        self.first.1.name()
+    }
+
+    fn set_usage_tracker(&mut self, tracker: UsageTracker) {
+        self.first.1.set_usage_tracker(tracker.clone());
+        self.second.1.set_usage_tracker(tracker);
+    }
+
+    fn set_thinking(&mut self, enabled: bool) {
+        self.first.1.set_thinking(enabled);
+        self.second.1.set_thinking(enabled);
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.first.1.set_seed(seed);
+        self.second.1.set_seed(seed);
+    }
+
+    fn set_reasoning_effort(&mut self, effort: Option<ReasoningEffort>) {
+        self.first.1.set_reasoning_effort(effort);
+        self.second.1.set_reasoning_effort(effort);
+    }
+
+    fn set_stop_sequences(&mut self, stop: &[String]) -> anyhow::Result<()> {
+        self.first.1.set_stop_sequences(stop)?;
+        self.second.1.set_stop_sequences(stop)
+    }
+
+    async fn warmup(&self) -> anyhow::Result<()> {
+        for (name, provider) in [&self.first, &self.second] {
+            if let Err(e) = provider.warmup().await {
+                tracing::warn!(provider = name.as_str(), "Warmup failed (non-fatal): {e}");
+            }
+        }
+        Ok(())
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        match self.first.1.list_models().await {
+            Ok(models) => Ok(models),
+            Err(_) => self.second.1.list_models().await,
+        }
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let (first, second) = (&self.first.1, &self.second.1);
+        self.race(
+            first.chat_with_system(system_prompt, message, model, temperature),
+            second.chat_with_system(system_prompt, message, model, temperature),
+        )
+        .await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let (first, second) = (&self.first.1, &self.second.1);
+        self.race(
+            first.chat_with_history(messages, model, temperature),
+            second.chat_with_history(messages, model, temperature),
+        )
+        .await
+    }
+
+    async fn chat_with_history_served(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<Served> {
+        let (first, second) = (&self.first.1, &self.second.1);
+        self.race(
+            first.chat_with_history_served(messages, model, temperature),
+            second.chat_with_history_served(messages, model, temperature),
+        )
+        .await
+    }
+
+    async fn chat_with_history_stream_served(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        token_tx: tokio::sync::mpsc::Sender<String>,
+    ) -> anyhow::Result<Served> {
+        let served = self
+            .chat_with_history_served(messages, model, temperature)
+            .await?;
+        let _ = token_tx.send(served.text.clone()).await;
+        Ok(served)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    struct DelayedProvider {
+        name: &'static str,
+        delay_ms: u64,
+        result: Result<&'static str, &'static str>,
+    }
+
+    #[async_trait]
+    impl Provider for DelayedProvider {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            tokio::time::sleep(Duration::from_millis(self.delay_ms)).await;
+            match self.result {
+                Ok(resp) => Ok(resp.to_string()),
+                Err(e) => anyhow::bail!(e),
+            }
+        }
+    }
+
+    fn race(
+        first: (u64, Result<&'static str, &'static str>),
+        second: (u64, Result<&'static str, &'static str>),
+    ) -> RaceProvider {
+        RaceProvider::new(
+            (
+                "first".into(),
+                Box::new(DelayedProvider {
+                    name: "first",
+                    delay_ms: first.0,
+                    result: first.1,
+                }),
+            ),
+            (
+                "second".into(),
+                Box::new(DelayedProvider {
+                    name: "second",
+                    delay_ms: second.0,
+                    result: second.1,
+                }),
+            ),
+        )
+    }
+
+    #[tokio::test]
+    async fn faster_provider_wins() {
+        let provider = race((5_000, Ok("slow")), (5, Ok("fast")));
+        let started = std::time::Instant::now();
+        let served = provider
+            .chat_with_history_served(&[ChatMessage::user("hi")], "model", 0.0)
+            .await
+            .unwrap();
+        assert_eq!(served.text, "fast");
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(provider.wins(), [("first", 0), ("second", 1)]);
+        assert_eq!(provider.name(), "first");
+        assert_eq!(served.provider, "second");
+    }
+
+    #[tokio::test]
+    async fn history_calls_are_raced() {
+        let provider = race((5, Ok("fast")), (5_000, Ok("slow")));
+        let resp = provider
+            .chat_with_history(&[ChatMessage::user("hi")], "model", 0.0)
+            .await
+            .unwrap();
+        assert_eq!(resp, "fast");
+        assert_eq!(provider.wins(), [("first", 1), ("second", 0)]);
+    }
+
+    #[tokio::test]
+    async fn fast_failure_falls_back_to_other_provider() {
+        let provider = race((5, Err("boom")), (50, Ok("backup")));
+        let served = provider
+            .chat_with_history_served(&[ChatMessage::user("hi")], "model", 0.0)
+            .await
+            .unwrap();
+        assert_eq!(served.text, "backup");
+        assert_eq!(provider.wins(), [("first", 0), ("second", 1)]);
+        assert_eq!(served.provider, "second");
+    }
+
+    #[tokio::test]
+    async fn both_failures_are_reported() {
+        let provider = race((5, Err("first down")), (20, Err("second down")));
+        let err = provider.chat("hi", "model", 0.0).await.unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("first: first down"));
+        assert!(msg.contains("second: second down"));
+        assert_eq!(provider.wins(), [("first", 0), ("second", 0)]);
+    }
+}
@@ -1,4 +1,5 @@
-use super::traits::{ChatMessage, UsageTracker};
+use super::circuit::CircuitBreaker;
+use super::traits::{ChatMessage, ModelInfo, ReasoningEffort, Served, UsageTracker};
 use super::Provider;
 use async_trait::async_trait;
 use std::time::Duration;
@@ -27,11 +28,41 @@ fn is_non_retryable(err: &anyhow::Error) -> bool {
     false
 }
 
+/// One attempt's line in the "All providers failed" summary. Auth failures
+/// stay one line; the remediation steps travel with the error [`all_failed`]
+/// keeps.
+fn describe_failure(err: &anyhow::Error) -> String {
+    match super::auth_failure(err) {
+        Some(auth) => auth.summary(),
+        None => err.to_string(),
+    }
+}
+
+/// Error after every provider failed. When one rejected its credentials the
+/// [`super::AuthError`] is kept in the chain so callers can surface the fix.
+fn all_failed(failures: &[String], auth_error: Option<anyhow::Error>) -> anyhow::Error {
+    let summary = format!("All providers failed. Attempts:\n{}", failures.join("\n"));
+    match auth_error {
+        Some(err) => err.context(summary),
+        None => anyhow::anyhow!(summary),
+    }
+}
+
+/// Summary line for a provider skipped because its circuit is open.
+fn circuit_open(provider_name: &str, remaining: Duration) -> String {
+    format!(
+        "{provider_name}: circuit open after repeated failures, skipped (retry in {}s)",
+        remaining.as_secs().max(1)
+    )
+}
+
 /// Provider wrapper with retry + fallback behavior.
 pub struct ReliableProvider {
     providers: Vec<(String, Box<dyn Provider>)>,
     max_retries: u32,
     base_backoff_ms: u64,
+    /// One per provider, in the same order
+    breakers: Vec<CircuitBreaker>,
 }
 
 impl ReliableProvider {
@@ -40,22 +71,84 @@ impl ReliableProvider {
         max_retries: u32,
         base_backoff_ms: u64,
     ) -> Self {
+        let breakers = providers
+            .iter()
+            .map(|_| CircuitBreaker::disabled())
+            .collect();
         Self {
             providers,
             max_retries,
             base_backoff_ms: base_backoff_ms.max(50),
+            breakers,
+        }
+    }
+
+    /// Give each provider a circuit breaker that opens after `threshold`
+    /// consecutive failed calls (0 disables it) and stays open for `cooldown`.
+    #[must_use]
+    pub fn with_circuit_breaker(mut self, threshold: u32, cooldown: Duration) -> Self {
+        self.breakers = self
+            .providers
+            .iter()
+            .map(|_| CircuitBreaker::new(threshold, cooldown))
+            .collect();
+        self
+    }
+
+    /// Count a failed call against provider `index`. Only retryable errors
+    /// (5xx, timeouts, 429) count; a bad request or key is not an outage.
+    fn record_failure(&self, index: usize, provider_name: &str, err: &anyhow::Error) {
+        if is_non_retryable(err) {
+            return;
+        }
+        if self.breakers[index].record_failure() {
+            tracing::warn!(
+                provider = provider_name,
+                "Provider keeps failing; circuit opened, skipping it until the cooldown ends"
+            );
         }
     }
 }
 
 #[async_trait]
 impl Provider for ReliableProvider {
+    fn name(&self) -> &str {
+        self.providers
+            .first()
+            .map_or("reliable", |(_, provider)| provider.name())
+    }
+
     fn set_usage_tracker(&mut self, tracker: UsageTracker) {
         for (_, provider) in &mut self.providers {
             provider.set_usage_tracker(tracker.clone());
         }
     }
 
+    fn set_thinking(&mut self, enabled: bool) {
+        for (_, provider) in &mut self.providers {
+            provider.set_thinking(enabled);
+        }
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        for (_, provider) in &mut self.providers {
+            provider.set_seed(seed);
+        }
+    }
+
+    fn set_reasoning_effort(&mut self, effort: Option<ReasoningEffort>) {
+        for (_, provider) in &mut self.providers {
+            provider.set_reasoning_effort(effort);
+        }
+    }
+
+    fn set_stop_sequences(&mut self, stop: &[String]) -> anyhow::Result<()> {
+        for (_, provider) in &mut self.providers {
+            provider.set_stop_sequences(stop)?;
+        }
+        Ok(())
+    }
+
     async fn warmup(&self) -> anyhow::Result<()> {
         for (name, provider) in &self.providers {
             tracing::info!(provider = name, "Warming up provider connection pool");
@@ -66,6 +159,17 @@ impl Provider for ReliableProvider {
         Ok(())
     }
 
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let mut failures = Vec::new();
+        for (name, provider) in &self.providers {
+            match provider.list_models().await {
+                Ok(models) => return Ok(models),
+                Err(e) => failures.push(format!("{name}: {e}")),
+            }
+        }
+        anyhow::bail!("No provider could list models:\n{}", failures.join("\n"))
+    }
+
     async fn chat_with_system(
         &self,
         system_prompt: Option<&str>,
@@ -74,8 +178,13 @@ impl Provider for ReliableProvider {
         temperature: f64,
     ) -> anyhow::Result<String> {
         let mut failures = Vec::new();
+        let mut auth_error = None;
 
-        for (provider_name, provider) in &self.providers {
+        for (index, (provider_name, provider)) in self.providers.iter().enumerate() {
+            if let Err(remaining) = self.breakers[index].check() {
+                failures.push(circuit_open(provider_name, remaining));
+                continue;
+            }
             let mut backoff_ms = self.base_backoff_ms;
 
             for attempt in 0..=self.max_retries {
@@ -91,15 +200,21 @@ impl Provider for ReliableProvider {
                                 "Provider recovered after retries"
                             );
                         }
+                        self.breakers[index].record_success();
                         return Ok(resp);
                     }
                     Err(e) => {
+                        self.record_failure(index, provider_name, &e);
                         let non_retryable = is_non_retryable(&e);
                         failures.push(format!(
-                            "{provider_name} attempt {}/{}: {e}",
+                            "{provider_name} attempt {}/{}: {}",
                             attempt + 1,
-                            self.max_retries + 1
+                            self.max_retries + 1,
+                            describe_failure(&e)
                         ));
+                        if super::auth_failure(&e).is_some() {
+                            auth_error = Some(e);
+                        }
 
                         if non_retryable {
                             tracing::warn!(
@@ -109,6 +224,10 @@ impl Provider for ReliableProvider {
                             break;
                         }
 
+                        if self.breakers[index].check().is_err() {
+                            break;
+                        }
+
                         if attempt < self.max_retries {
                             tracing::warn!(
                                 provider = provider_name,
@@ -126,7 +245,7 @@ impl Provider for ReliableProvider {
             tracing::warn!(provider = provider_name, "Switching to fallback provider");
         }
 
-        anyhow::bail!("All providers failed. Attempts:\n{}", failures.join("\n"))
+        Err(all_failed(&failures, auth_error))
     }
 
     async fn chat_with_history(
@@ -135,14 +254,30 @@ impl Provider for ReliableProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<String> {
+        self.chat_with_history_served(messages, model, temperature)
+            .await
+            .map(|served| served.text)
+    }
+
+    async fn chat_with_history_served(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<Served> {
         let mut failures = Vec::new();
+        let mut auth_error = None;
 
-        for (provider_name, provider) in &self.providers {
+        for (index, (provider_name, provider)) in self.providers.iter().enumerate() {
+            if let Err(remaining) = self.breakers[index].check() {
+                failures.push(circuit_open(provider_name, remaining));
+                continue;
+            }
             let mut backoff_ms = self.base_backoff_ms;
 
             for attempt in 0..=self.max_retries {
                 match provider
-                    .chat_with_history(messages, model, temperature)
+                    .chat_with_history_served(messages, model, temperature)
                     .await
                 {
                     Ok(resp) => {
@@ -153,15 +288,21 @@ impl Provider for ReliableProvider {
                                 "Provider recovered after retries"
                             );
                         }
+                        self.breakers[index].record_success();
                         return Ok(resp);
                     }
                     Err(e) => {
+                        self.record_failure(index, provider_name, &e);
                         let non_retryable = is_non_retryable(&e);
                         failures.push(format!(
-                            "{provider_name} attempt {}/{}: {e}",
+                            "{provider_name} attempt {}/{}: {}",
                             attempt + 1,
-                            self.max_retries + 1
+                            self.max_retries + 1,
+                            describe_failure(&e)
                         ));
+                        if super::auth_failure(&e).is_some() {
+                            auth_error = Some(e);
+                        }
 
                         if non_retryable {
                             tracing::warn!(
@@ -171,6 +312,10 @@ impl Provider for ReliableProvider {
                             break;
                         }
 
+                        if self.breakers[index].check().is_err() {
+                            break;
+                        }
+
                         if attempt < self.max_retries {
                             tracing::warn!(
                                 provider = provider_name,
@@ -188,7 +333,7 @@ impl Provider for ReliableProvider {
             tracing::warn!(provider = provider_name, "Switching to fallback provider");
         }
 
-        anyhow::bail!("All providers failed. Attempts:\n{}", failures.join("\n"))
+        Err(all_failed(&failures, auth_error))
     }
 
     async fn chat_with_history_stream(
@@ -198,15 +343,31 @@ impl Provider for ReliableProvider {
         temperature: f64,
         token_tx: tokio::sync::mpsc::Sender<String>,
     ) -> anyhow::Result<String> {
+        self.chat_with_history_stream_served(messages, model, temperature, token_tx)
+            .await
+            .map(|served| served.text)
+    }
+
+    async fn chat_with_history_stream_served(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        token_tx: tokio::sync::mpsc::Sender<String>,
+    ) -> anyhow::Result<Served> {
         // Try the first provider that supports streaming; fall back to non-streaming
-        for (provider_name, provider) in &self.providers {
-            if provider.supports_streaming() {
+        for (index, (provider_name, provider)) in self.providers.iter().enumerate() {
+            if provider.supports_streaming() && self.breakers[index].check().is_ok() {
                 match provider
-                    .chat_with_history_stream(messages, model, temperature, token_tx.clone())
+                    .chat_with_history_stream_served(messages, model, temperature, token_tx.clone())
                     .await
                 {
-                    Ok(resp) => return Ok(resp),
+                    Ok(served) => {
+                        self.breakers[index].record_success();
+                        return Ok(served);
+                    }
                     Err(e) => {
+                        self.record_failure(index, provider_name, &e);
                         tracing::warn!(
                             provider = provider_name,
                             "Streaming failed, trying next: {e}"
@@ -216,14 +377,22 @@ impl Provider for ReliableProvider {
             }
         }
         // No streaming provider succeeded, fall back to non-streaming with retry
-        let response = self.chat_with_history(messages, model, temperature).await?;
-        let _ = token_tx.send(response.clone()).await;
-        Ok(response)
+        let served = self
+            .chat_with_history_served(messages, model, temperature)
+            .await?;
+        let _ = token_tx.send(served.text.clone()).await;
+        Ok(served)
     }
 
     fn supports_streaming(&self) -> bool {
         self.providers.iter().any(|(_, p)| p.supports_streaming())
     }
+
+    fn supports_system_prompt(&self, model: &str) -> bool {
+        self.providers
+            .iter()
+            .all(|(_, p)| p.supports_system_prompt(model))
+    }
 }
 
 #[cfg(test)]
@@ -241,6 +410,10 @@ mod tests {
 
     #[async_trait]
     impl Provider for MockProvider {
+        fn name(&self) -> &str {
+            self.response
+        }
+
         async fn chat_with_system(
             &self,
             _system_prompt: Option<&str>,
@@ -343,10 +516,16 @@ mod tests {
             1,
         );
 
-        let result = provider.chat("hello", "test", 0.0).await.unwrap();
-        assert_eq!(result, "from fallback");
+        let served = provider
+            .chat_with_history_served(&[ChatMessage::user("hello")], "test", 0.0)
+            .await
+            .unwrap();
+        assert_eq!(served.text, "from fallback");
         assert_eq!(primary_calls.load(Ordering::SeqCst), 2);
         assert_eq!(fallback_calls.load(Ordering::SeqCst), 1);
+        // Mocks are named after their reply
+        assert_eq!(provider.name(), "never");
+        assert_eq!(served.provider, "from fallback");
     }
 
     #[tokio::test]
@@ -515,4 +694,142 @@ mod tests {
         assert_eq!(primary_calls.load(Ordering::SeqCst), 2);
         assert_eq!(fallback_calls.load(Ordering::SeqCst), 1);
     }
+
+    #[tokio::test]
+    async fn open_circuit_fails_over_without_calling_provider() {
+        let primary_calls = Arc::new(AtomicUsize::new(0));
+        let fallback_calls = Arc::new(AtomicUsize::new(0));
+
+        let provider = ReliableProvider::new(
+            vec![
+                (
+                    "primary".into(),
+                    Box::new(MockProvider {
+                        calls: Arc::clone(&primary_calls),
+                        fail_until_attempt: usize::MAX,
+                        response: "never",
+                        error: "primary down",
+                    }),
+                ),
+                (
+                    "fallback".into(),
+                    Box::new(MockProvider {
+                        calls: Arc::clone(&fallback_calls),
+                        fail_until_attempt: 0,
+                        response: "from fallback",
+                        error: "fallback err",
+                    }),
+                ),
+            ],
+            5,
+            1,
+        )
+        .with_circuit_breaker(2, Duration::from_secs(60));
+
+        // The second failure opens the circuit, cutting the retries short
+        assert_eq!(
+            provider.chat("hello", "test", 0.0).await.unwrap(),
+            "from fallback"
+        );
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 2);
+
+        assert_eq!(
+            provider.chat("hello", "test", 0.0).await.unwrap(),
+            "from fallback"
+        );
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 2);
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn open_circuit_error_says_provider_was_skipped() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![(
+                "primary".into(),
+                Box::new(MockProvider {
+                    calls: Arc::clone(&calls),
+                    fail_until_attempt: usize::MAX,
+                    response: "never",
+                    error: "primary down",
+                }),
+            )],
+            0,
+            1,
+        )
+        .with_circuit_breaker(1, Duration::from_secs(60));
+
+        provider.chat("hello", "test", 0.0).await.unwrap_err();
+        let err = provider.chat("hello", "test", 0.0).await.unwrap_err();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(err.to_string().contains("primary: circuit open"), "{err}");
+    }
+
+    struct UnauthorizedProvider {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Provider for UnauthorizedProvider {
+        fn name(&self) -> &str {
+            "unauthorized"
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Err(super::super::status_error(
+                "OpenAI",
+                reqwest::StatusCode::UNAUTHORIZED,
+                "invalid key",
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn auth_failure_survives_aggregation_without_retries() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![(
+                "openai".into(),
+                Box::new(UnauthorizedProvider {
+                    calls: Arc::clone(&calls),
+                }) as Box<dyn Provider>,
+            )],
+            3,
+            1,
+        );
+
+        let err = provider.chat("hello", "test", 0.0).await.unwrap_err();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let auth = super::super::auth_failure(&err).expect("auth error kept in chain");
+        assert!(auth.guidance().contains("OPENAI_API_KEY"));
+        assert!(err.to_string().starts_with("All providers failed"));
+    }
+
+    #[tokio::test]
+    async fn non_retryable_errors_leave_the_circuit_closed() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![(
+                "openai".into(),
+                Box::new(UnauthorizedProvider {
+                    calls: Arc::clone(&calls),
+                }) as Box<dyn Provider>,
+            )],
+            0,
+            1,
+        )
+        .with_circuit_breaker(1, Duration::from_secs(60));
+
+        provider.chat("hello", "test", 0.0).await.unwrap_err();
+        let err = provider.chat("hello", "test", 0.0).await.unwrap_err();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(!err.to_string().contains("circuit open"), "{err}");
+    }
 }
@@ -1,4 +1,4 @@
-use super::traits::{ChatMessage, UsageTracker};
+use super::traits::{ChatMessage, ModelInfo, ReasoningEffort, Served, UsageTracker};
 use super::Provider;
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -88,10 +88,20 @@ impl RouterProvider {
         // Not a hint or hint not found — use default provider with the model as-is
         (self.default_index, model.to_string())
     }
+
+    /// Resolve `model` to the provider that handles it.
+    fn route(&self, model: &str) -> (&dyn Provider, String) {
+        let (index, resolved_model) = self.resolve(model);
+        (&*self.providers[index].1, resolved_model)
+    }
 }
 
 #[async_trait]
 impl Provider for RouterProvider {
+    fn name(&self) -> &str {
+        self.providers[self.default_index].1.name()
+    }
+
     async fn chat_with_system(
         &self,
         system_prompt: Option<&str>,
@@ -99,11 +109,9 @@ impl Provider for RouterProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<String> {
-        let (provider_idx, resolved_model) = self.resolve(model);
-
-        let (provider_name, provider) = &self.providers[provider_idx];
+        let (provider, resolved_model) = self.route(model);
         tracing::info!(
-            provider = provider_name.as_str(),
+            provider = provider.name(),
             model = resolved_model.as_str(),
             "Router dispatching request"
         );
@@ -119,19 +127,55 @@ impl Provider for RouterProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<String> {
-        let (provider_idx, resolved_model) = self.resolve(model);
-        let (_, provider) = &self.providers[provider_idx];
+        let (provider, resolved_model) = self.route(model);
         provider
             .chat_with_history(messages, &resolved_model, temperature)
             .await
     }
 
+    async fn chat_with_history_served(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<Served> {
+        let (provider, resolved_model) = self.route(model);
+        provider
+            .chat_with_history_served(messages, &resolved_model, temperature)
+            .await
+    }
+
     fn set_usage_tracker(&mut self, tracker: UsageTracker) {
         for (_, provider) in &mut self.providers {
             provider.set_usage_tracker(tracker.clone());
         }
     }
 
+    fn set_thinking(&mut self, enabled: bool) {
+        for (_, provider) in &mut self.providers {
+            provider.set_thinking(enabled);
+        }
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        for (_, provider) in &mut self.providers {
+            provider.set_seed(seed);
+        }
+    }
+
+    fn set_reasoning_effort(&mut self, effort: Option<ReasoningEffort>) {
+        for (_, provider) in &mut self.providers {
+            provider.set_reasoning_effort(effort);
+        }
+    }
+
+    fn set_stop_sequences(&mut self, stop: &[String]) -> anyhow::Result<()> {
+        for (_, provider) in &mut self.providers {
+            provider.set_stop_sequences(stop)?;
+        }
+        Ok(())
+    }
+
     async fn chat_with_history_stream(
         &self,
         messages: &[ChatMessage],
@@ -139,17 +183,35 @@ impl Provider for RouterProvider {
         temperature: f64,
         token_tx: tokio::sync::mpsc::Sender<String>,
     ) -> anyhow::Result<String> {
-        let (provider_idx, resolved_model) = self.resolve(model);
-        let (_, provider) = &self.providers[provider_idx];
+        let (provider, resolved_model) = self.route(model);
         provider
             .chat_with_history_stream(messages, &resolved_model, temperature, token_tx)
             .await
     }
 
+    async fn chat_with_history_stream_served(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        token_tx: tokio::sync::mpsc::Sender<String>,
+    ) -> anyhow::Result<Served> {
+        let (provider, resolved_model) = self.route(model);
+        provider
+            .chat_with_history_stream_served(messages, &resolved_model, temperature, token_tx)
+            .await
+    }
+
     fn supports_streaming(&self) -> bool {
         self.providers.iter().any(|(_, p)| p.supports_streaming())
     }
 
+    fn supports_system_prompt(&self, model: &str) -> bool {
+        self.providers
+            .iter()
+            .all(|(_, p)| p.supports_system_prompt(model))
+    }
+
     async fn warmup(&self) -> anyhow::Result<()> {
         for (name, provider) in &self.providers {
             tracing::info!(provider = name, "Warming up routed provider");
@@ -159,6 +221,11 @@ impl Provider for RouterProvider {
         }
         Ok(())
     }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let (_, provider) = &self.providers[self.default_index];
+        provider.list_models().await
+    }
 }
 
 #[cfg(test)]
@@ -193,6 +260,10 @@ mod tests {
 
     #[async_trait]
     impl Provider for MockProvider {
+        fn name(&self) -> &str {
+            self.response
+        }
+
         async fn chat_with_system(
             &self,
             _system_prompt: Option<&str>,
@@ -247,6 +318,10 @@ mod tests {
     // Arc<MockProvider> should also be a Provider
     #[async_trait]
     impl Provider for Arc<MockProvider> {
+        fn name(&self) -> &str {
+            self.as_ref().name()
+        }
+
         async fn chat_with_system(
             &self,
             system_prompt: Option<&str>,
@@ -275,6 +350,12 @@ mod tests {
         assert_eq!(mocks[1].call_count(), 1);
         assert_eq!(mocks[1].last_model(), "claude-opus");
         assert_eq!(mocks[0].call_count(), 0);
+        assert_eq!(router.name(), "fast-response");
+        let served = router
+            .chat_with_history_served(&[ChatMessage::user("hello")], "hint:reasoning", 0.5)
+            .await
+            .unwrap();
+        assert_eq!(served.provider, "smart-response");
     }
 
     #[tokio::test]
@@ -0,0 +1,221 @@
+use super::traits::{ChatMessage, ModelInfo, ReasoningEffort, UsageTracker};
+use super::Provider;
+use async_trait::async_trait;
+use std::sync::{Arc, OnceLock, Weak};
+use std::time::Duration;
+use tokio::sync::{mpsc, Semaphore};
+use tokio::time::{Instant, MissedTickBehavior};
+
+/// Scheduler shared by every provider created after [`install`].
+static SHARED: OnceLock<Arc<CallScheduler>> = OnceLock::new();
+
+/// Token bucket that paces provider calls to `[reliability]
+/// requests_per_minute`.
+///
+/// The bucket is a semaphore holding up to one minute's worth of permits.
+/// Each call takes a permit for good, and a background task puts one back
+/// every `60s / requests_per_minute`, so bursts are allowed until the bucket
+/// is empty and later calls queue up at the configured rate.
+pub struct CallScheduler {
+    permits: Arc<Semaphore>,
+}
+
+impl CallScheduler {
+    /// Must be called inside a Tokio runtime, which runs the refill task.
+    pub fn new(requests_per_minute: u32) -> Self {
+        Self::with_burst(requests_per_minute, requests_per_minute as usize)
+    }
+
+    fn with_burst(requests_per_minute: u32, burst: usize) -> Self {
+        let permits = Arc::new(Semaphore::new(burst));
+        let period = Duration::from_secs(60) / requests_per_minute.max(1);
+        tokio::spawn(refill(Arc::downgrade(&permits), burst, period));
+        Self { permits }
+    }
+
+    /// Wait for a permit to make one provider call.
+    pub async fn acquire(&self) {
+        if let Ok(permit) = self.permits.acquire().await {
+            permit.forget();
+        }
+    }
+}
+
+/// Add a permit every `period` until the bucket holds `burst`. Stops once
+/// the scheduler is dropped.
+async fn refill(permits: Weak<Semaphore>, burst: usize, period: Duration) {
+    let mut ticks = tokio::time::interval_at(Instant::now() + period, period);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        ticks.tick().await;
+        let Some(permits) = permits.upgrade() else {
+            return;
+        };
+        if permits.available_permits() < burst {
+            permits.add_permits(1);
+        }
+    }
+}
+
+/// Share one scheduler allowing `requests_per_minute` calls (0 disables it)
+/// between every provider created after this call, so the daemon's gateway,
+/// channels, heartbeat and cron jobs stay under one rate limit together.
+/// Only the first call takes effect, so a changed limit needs a daemon
+/// restart (a reload reports `reliability` as needing one).
+pub fn install(requests_per_minute: u32) {
+    if requests_per_minute > 0 {
+        let _ = SHARED.get_or_init(|| Arc::new(CallScheduler::new(requests_per_minute)));
+    }
+}
+
+/// `provider` wrapped in a [`ThrottledProvider`] on the installed scheduler;
+/// unchanged when none is installed.
+pub fn with_shared_throttle(provider: Box<dyn Provider>) -> Box<dyn Provider> {
+    match SHARED.get() {
+        Some(scheduler) => Box::new(ThrottledProvider::new(provider, Arc::clone(scheduler))),
+        None => provider,
+    }
+}
+
+/// Takes a permit from a [`CallScheduler`] before each request to `inner`.
+pub struct ThrottledProvider {
+    inner: Box<dyn Provider>,
+    scheduler: Arc<CallScheduler>,
+}
+
+impl ThrottledProvider {
+    pub fn new(inner: Box<dyn Provider>, scheduler: Arc<CallScheduler>) -> Self {
+        Self { inner, scheduler }
+    }
+}
+
+#[async_trait]
+impl Provider for ThrottledProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn set_usage_tracker(&mut self, tracker: UsageTracker) {
+        self.inner.set_usage_tracker(tracker);
+    }
+
+    fn set_thinking(&mut self, enabled: bool) {
+        self.inner.set_thinking(enabled);
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.inner.set_seed(seed);
+    }
+
+    fn set_reasoning_effort(&mut self, effort: Option<ReasoningEffort>) {
+        self.inner.set_reasoning_effort(effort);
+    }
+
+    fn set_stop_sequences(&mut self, stop: &[String]) -> anyhow::Result<()> {
+        self.inner.set_stop_sequences(stop)
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.inner.supports_streaming()
+    }
+
+    fn supports_system_prompt(&self, model: &str) -> bool {
+        self.inner.supports_system_prompt(model)
+    }
+
+    async fn warmup(&self) -> anyhow::Result<()> {
+        self.scheduler.acquire().await;
+        self.inner.warmup().await
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        self.inner.list_models().await
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        self.scheduler.acquire().await;
+        self.inner
+            .chat_with_system(system_prompt, message, model, temperature)
+            .await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        self.scheduler.acquire().await;
+        self.inner
+            .chat_with_history(messages, model, temperature)
+            .await
+    }
+
+    async fn chat_with_history_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        token_tx: mpsc::Sender<String>,
+    ) -> anyhow::Result<String> {
+        self.scheduler.acquire().await;
+        self.inner
+            .chat_with_history_stream(messages, model, temperature, token_tx)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn concurrent_acquirers_are_throttled_to_the_rate() {
+        // 1200 per minute is one call every 50ms, after a burst of 2
+        let scheduler = Arc::new(CallScheduler::with_burst(1200, 2));
+        let done = Arc::new(AtomicUsize::new(0));
+        let started = std::time::Instant::now();
+
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let scheduler = Arc::clone(&scheduler);
+                let done = Arc::clone(&done);
+                tokio::spawn(async move {
+                    scheduler.acquire().await;
+                    done.fetch_add(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(
+            done.load(Ordering::SeqCst),
+            2,
+            "only the burst goes at once"
+        );
+
+        for task in tasks {
+            task.await.unwrap();
+        }
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(290), "{elapsed:?}");
+        assert!(elapsed < Duration::from_secs(2), "{elapsed:?}");
+    }
+
+    #[tokio::test]
+    async fn refill_stops_at_the_burst() {
+        let scheduler = CallScheduler::with_burst(6000, 3);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(scheduler.permits.available_permits(), 3);
+
+        scheduler.acquire().await;
+        assert_eq!(scheduler.permits.available_permits(), 2);
+    }
+}
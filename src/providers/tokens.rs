@@ -0,0 +1,111 @@
+use super::traits::ChatMessage;
+
+/// Per-message framing overhead (role markers, separators) added by chat APIs.
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// Estimates how many tokens a model will see for a piece of text.
+///
+/// Used for pre-send budgeting (history trimming, `/cost` estimates); the
+/// provider's own usage report remains the source of truth after a call.
+pub trait TokenCounter: Send + Sync {
+    /// Short label shown alongside estimates (e.g. "heuristic").
+    fn name(&self) -> &str;
+
+    /// Count tokens in `text` as tokenized for `model`.
+    fn count(&self, text: &str, model: &str) -> usize;
+
+    /// Count tokens for a whole chat request, including per-message overhead.
+    fn count_messages(&self, messages: &[ChatMessage], model: &str) -> usize {
+        messages
+            .iter()
+            .map(|m| self.count(&m.content, model) + MESSAGE_OVERHEAD_TOKENS)
+            .sum()
+    }
+}
+
+/// Model-agnostic estimate of roughly four characters per token.
+pub struct HeuristicCounter;
+
+impl TokenCounter for HeuristicCounter {
+    fn name(&self) -> &str {
+        "heuristic"
+    }
+
+    fn count(&self, text: &str, _model: &str) -> usize {
+        text.chars().count().div_ceil(4)
+    }
+}
+
+/// Exact counts for GPT models via the tiktoken BPE tables; other models fall
+/// back to the heuristic.
+#[cfg(feature = "tiktoken")]
+pub struct TiktokenCounter;
+
+#[cfg(feature = "tiktoken")]
+impl TokenCounter for TiktokenCounter {
+    fn name(&self) -> &str {
+        "tiktoken"
+    }
+
+    fn count(&self, text: &str, model: &str) -> usize {
+        use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer};
+
+        // OpenRouter-style ids ("openai/gpt-4o") carry a vendor prefix
+        let bare = model.strip_prefix("openai/").unwrap_or(model);
+        let bpe = match get_tokenizer(bare) {
+            Some(Tokenizer::O200kBase) => tiktoken_rs::o200k_base_singleton(),
+            Some(Tokenizer::Cl100kBase) => tiktoken_rs::cl100k_base_singleton(),
+            _ => return HeuristicCounter.count(text, model),
+        };
+        let bpe = bpe.lock();
+        bpe.encode_with_special_tokens(text).len()
+    }
+}
+
+/// The most accurate counter compiled into this build.
+pub fn token_counter() -> &'static dyn TokenCounter {
+    #[cfg(feature = "tiktoken")]
+    {
+        &TiktokenCounter
+    }
+    #[cfg(not(feature = "tiktoken"))]
+    {
+        &HeuristicCounter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heuristic_rounds_up_by_chars() {
+        assert_eq!(HeuristicCounter.count("", "any"), 0);
+        assert_eq!(HeuristicCounter.count("abc", "any"), 1);
+        assert_eq!(HeuristicCounter.count("abcdefgh", "any"), 2);
+        // Counts characters, not bytes
+        assert_eq!(HeuristicCounter.count("😀😀😀😀", "any"), 1);
+    }
+
+    #[test]
+    fn count_messages_adds_overhead() {
+        let messages = vec![ChatMessage::system("abcd"), ChatMessage::user("abcdefgh")];
+        assert_eq!(
+            HeuristicCounter.count_messages(&messages, "any"),
+            1 + 2 + 2 * MESSAGE_OVERHEAD_TOKENS
+        );
+    }
+
+    #[test]
+    fn default_counter_handles_unknown_models() {
+        let counter = token_counter();
+        assert_eq!(counter.count("abcdefgh", "some/unknown-model"), 2);
+    }
+
+    #[cfg(feature = "tiktoken")]
+    #[test]
+    fn tiktoken_counts_openai_models_exactly() {
+        assert_eq!(TiktokenCounter.count("hello world", "gpt-4o"), 2);
+        assert_eq!(TiktokenCounter.count("hello world", "openai/gpt-4"), 2);
+    }
+}
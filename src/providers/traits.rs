@@ -1,36 +1,180 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How much a reasoning model should think before answering
+/// (`providers.reasoning_effort`, `agent --reasoning`). Each provider maps it
+/// to its native parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    Low,
+    Medium,
+    High,
+}
+
+impl ReasoningEffort {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
+impl std::fmt::Display for ReasoningEffort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ReasoningEffort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "low" => Ok(Self::Low),
+            "medium" => Ok(Self::Medium),
+            "high" => Ok(Self::High),
+            _ => Err(format!(
+                "invalid reasoning effort {s:?}: expected low, medium or high"
+            )),
+        }
+    }
+}
+
+/// Header on tool results sent as a user message, for APIs without a tool
+/// role (and how sessions stored them before there was one).
+pub const TOOL_RESULTS_PREFIX: &str = "[Tool results]\n";
 
 /// A single message in a conversation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
+    /// `system`, `user`, `assistant` or `tool`
     pub role: String,
     pub content: String,
+    /// When the message was created. Absent in sessions saved before
+    /// messages carried timestamps.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Model that produced an assistant message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// The native tool call a `tool` message answers. Results of
+    /// `<tool_call>` blocks have none and are sent as user text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
 }
 
 impl ChatMessage {
-    pub fn system(content: impl Into<String>) -> Self {
+    fn new(role: &str, content: impl Into<String>) -> Self {
         Self {
-            role: "system".into(),
+            role: role.into(),
             content: content.into(),
+            timestamp: Some(Utc::now()),
+            model: None,
+            tool_call_id: None,
         }
     }
 
+    pub fn system(content: impl Into<String>) -> Self {
+        Self::new("system", content)
+    }
+
     pub fn user(content: impl Into<String>) -> Self {
-        Self {
-            role: "user".into(),
-            content: content.into(),
-        }
+        Self::new("user", content)
     }
 
     pub fn assistant(content: impl Into<String>) -> Self {
+        Self::new("assistant", content)
+    }
+
+    /// Results of the `<tool_call>` blocks in the previous reply.
+    pub fn tool(content: impl Into<String>) -> Self {
+        Self::new("tool", content)
+    }
+
+    /// Result of the native tool call `call_id`.
+    pub fn tool_result(call_id: impl Into<String>, content: impl Into<String>) -> Self {
         Self {
-            role: "assistant".into(),
-            content: content.into(),
+            tool_call_id: Some(call_id.into()),
+            ..Self::new("tool", content)
+        }
+    }
+
+    /// The tool results this message carries, if it is a tool message or a
+    /// `[Tool results]` user message from a session saved before the tool role.
+    pub fn tool_results(&self) -> Option<&str> {
+        match self.role.as_str() {
+            "tool" => Some(&self.content),
+            "user" => self.content.strip_prefix(TOOL_RESULTS_PREFIX),
+            _ => None,
         }
     }
+
+    /// Role and text for an API without a tool role: tool results become a
+    /// user message under [`TOOL_RESULTS_PREFIX`], anything else is unchanged.
+    pub fn inline_tool_results(&self) -> (&str, Cow<'_, str>) {
+        if self.role == "tool" {
+            (
+                "user",
+                Cow::Owned(format!("{TOOL_RESULTS_PREFIX}{}", self.content)),
+            )
+        } else {
+            (&self.role, Cow::Borrowed(&self.content))
+        }
+    }
+
+    /// Record which model produced this message.
+    #[must_use]
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+}
+
+/// Check `stop` against a provider's limits: at most `max` entries (when the
+/// API caps it) and no blank sequences.
+pub fn validate_stop_sequences(
+    provider: &str,
+    stop: &[String],
+    max: Option<usize>,
+) -> anyhow::Result<()> {
+    if let Some(max) = max {
+        if stop.len() > max {
+            anyhow::bail!(
+                "{provider} accepts at most {max} stop sequences, got {}",
+                stop.len()
+            );
+        }
+    }
+    if stop.iter().any(|s| s.trim().is_empty()) {
+        anyhow::bail!("Stop sequences must not be empty or whitespace-only");
+    }
+    Ok(())
+}
+
+/// Byte offset of the earliest stop sequence in `text`, for providers that
+/// stream from upstreams which may not honour `stop` themselves.
+pub fn find_stop_sequence(text: &str, stop: &[String]) -> Option<usize> {
+    stop.iter().filter_map(|s| text.find(s.as_str())).min()
+}
+
+/// Bytes at the end of `text` that could be the start of a stop sequence
+/// split across stream chunks, and so must not be streamed yet. At most the
+/// longest stop sequence's length minus one.
+pub fn stop_sequence_holdback(text: &str, stop: &[String]) -> usize {
+    let longest = stop.iter().map(String::len).max().unwrap_or(0);
+    let earliest = text.len().saturating_sub(longest.saturating_sub(1));
+    (earliest..text.len())
+        .filter(|&i| text.is_char_boundary(i))
+        .find(|&i| stop.iter().any(|s| s.starts_with(&text[i..])))
+        .map_or(0, |i| text.len() - i)
 }
 
 /// A tool call requested by the LLM.
@@ -77,8 +221,20 @@ pub struct UsageTracker {
     pub completion_tokens: Arc<AtomicU64>,
     pub total_tokens: Arc<AtomicU64>,
     pub request_count: Arc<AtomicU64>,
+    /// Sum of all recorded provider call durations, in milliseconds.
+    pub latency_total_ms: Arc<AtomicU64>,
+    /// Number of provider calls with a recorded duration.
+    pub latency_samples: Arc<AtomicU64>,
+    pub last_latency_ms: Arc<AtomicU64>,
+    /// Exponential moving average of call latency, in milliseconds.
+    pub ema_latency_ms: Arc<AtomicU64>,
+    /// Latest backend `system_fingerprint` reported by the provider.
+    pub system_fingerprint: Arc<Mutex<Option<String>>>,
 }
 
+/// Weight given to the newest sample in the latency moving average.
+const LATENCY_EMA_ALPHA: f64 = 0.2;
+
 impl Default for UsageTracker {
     fn default() -> Self {
         Self::new()
@@ -92,6 +248,11 @@ impl UsageTracker {
             completion_tokens: Arc::new(AtomicU64::new(0)),
             total_tokens: Arc::new(AtomicU64::new(0)),
             request_count: Arc::new(AtomicU64::new(0)),
+            latency_total_ms: Arc::new(AtomicU64::new(0)),
+            latency_samples: Arc::new(AtomicU64::new(0)),
+            last_latency_ms: Arc::new(AtomicU64::new(0)),
+            ema_latency_ms: Arc::new(AtomicU64::new(0)),
+            system_fingerprint: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -117,6 +278,74 @@ impl UsageTracker {
         self.request_count.load(Ordering::Relaxed)
     }
 
+    /// Record how long a single provider call took.
+    pub fn record_latency(&self, elapsed: Duration) {
+        let ms = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+        let first = self.latency_samples.fetch_add(1, Ordering::Relaxed) == 0;
+        self.latency_total_ms.fetch_add(ms, Ordering::Relaxed);
+        self.last_latency_ms.store(ms, Ordering::Relaxed);
+        let _ = self
+            .ema_latency_ms
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |prev| {
+                if first {
+                    return Some(ms);
+                }
+                #[allow(
+                    clippy::cast_possible_truncation,
+                    clippy::cast_precision_loss,
+                    clippy::cast_sign_loss
+                )]
+                let next = (LATENCY_EMA_ALPHA * ms as f64 + (1.0 - LATENCY_EMA_ALPHA) * prev as f64)
+                    .round() as u64;
+                Some(next)
+            });
+    }
+
+    /// Mean provider latency over the session, or `None` before the first call.
+    pub fn avg_latency_ms(&self) -> Option<u64> {
+        let samples = self.latency_samples.load(Ordering::Relaxed);
+        (samples > 0).then(|| self.latency_total_ms.load(Ordering::Relaxed) / samples)
+    }
+
+    /// Latency of the most recent provider call.
+    pub fn last_latency_ms(&self) -> Option<u64> {
+        (self.latency_samples.load(Ordering::Relaxed) > 0)
+            .then(|| self.last_latency_ms.load(Ordering::Relaxed))
+    }
+
+    /// Moving average weighted towards recent calls.
+    pub fn ema_latency_ms(&self) -> Option<u64> {
+        (self.latency_samples.load(Ordering::Relaxed) > 0)
+            .then(|| self.ema_latency_ms.load(Ordering::Relaxed))
+    }
+
+    /// Record the backend fingerprint from a response, warning when it differs
+    /// from the previous one (outputs may change even with a fixed seed).
+    pub fn record_system_fingerprint(&self, fingerprint: &str) {
+        let mut current = self
+            .system_fingerprint
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        match current.as_deref() {
+            Some(prev) if prev == fingerprint => return,
+            Some(prev) => {
+                tracing::warn!(
+                    "Provider backend changed: system_fingerprint {prev} -> {fingerprint}"
+                );
+            }
+            None => tracing::debug!(system_fingerprint = fingerprint, "Provider backend"),
+        }
+        *current = Some(fingerprint.to_string());
+    }
+
+    /// Most recent backend fingerprint, when the provider reports one.
+    pub fn system_fingerprint(&self) -> Option<String> {
+        self.system_fingerprint
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
     /// Estimate cost in USD. Rough averages across common models.
     pub fn estimated_cost_usd(&self) -> f64 {
         let prompt = self.prompt_tokens.load(Ordering::Relaxed) as f64;
@@ -126,6 +355,30 @@ impl UsageTracker {
     }
 }
 
+/// A model advertised by a provider's list-models endpoint.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub id: String,
+    /// Maximum context window in tokens, when the provider reports it.
+    #[serde(default)]
+    pub context_length: Option<u64>,
+    /// Input price in USD per million tokens, when the provider reports it.
+    #[serde(default)]
+    pub prompt_price_per_m: Option<f64>,
+    /// Output price in USD per million tokens, when the provider reports it.
+    #[serde(default)]
+    pub completion_price_per_m: Option<f64>,
+}
+
+impl ModelInfo {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            ..Self::default()
+        }
+    }
+}
+
 /// A tool result to feed back to the LLM.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolResultMessage {
@@ -148,8 +401,29 @@ pub enum ConversationMessage {
     ToolResult(ToolResultMessage),
 }
 
+/// A response together with the provider that produced it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Served {
+    pub text: String,
+    /// [`Provider::name`] of the provider that answered, or `cache`
+    pub provider: String,
+}
+
+impl Served {
+    pub fn new(text: String, provider: &str) -> Self {
+        Self {
+            text,
+            provider: provider.to_string(),
+        }
+    }
+}
+
 #[async_trait]
 pub trait Provider: Send + Sync {
+    /// Stable identifier for this provider, e.g. `openrouter` or `groq`.
+    /// Wrappers report the provider they try first.
+    fn name(&self) -> &str;
+
     async fn chat(&self, message: &str, model: &str, temperature: f64) -> anyhow::Result<String> {
         self.chat_with_system(None, message, model, temperature)
             .await
@@ -164,7 +438,8 @@ pub trait Provider: Send + Sync {
     ) -> anyhow::Result<String>;
 
     /// Multi-turn conversation. Default implementation extracts the last user
-    /// message and delegates to `chat_with_system`.
+    /// message (or tool results, as user text) and delegates to
+    /// `chat_with_system`.
     async fn chat_with_history(
         &self,
         messages: &[ChatMessage],
@@ -177,10 +452,10 @@ pub trait Provider: Send + Sync {
             .map(|m| m.content.as_str());
         let last_user = messages
             .iter()
-            .rfind(|m| m.role == "user")
-            .map(|m| m.content.as_str())
-            .unwrap_or("");
-        self.chat_with_system(system, last_user, model, temperature)
+            .rfind(|m| m.role == "user" || m.role == "tool")
+            .map(|m| m.inline_tool_results().1)
+            .unwrap_or_default();
+        self.chat_with_system(system, &last_user, model, temperature)
             .await
     }
 
@@ -205,22 +480,111 @@ pub trait Provider: Send + Sync {
         Ok(response)
     }
 
+    /// [`Provider::chat_with_history`], also naming the provider that
+    /// answered this call. Fallback, routing, racing and caching wrappers
+    /// override it to report the one that actually did.
+    async fn chat_with_history_served(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<Served> {
+        let text = self.chat_with_history(messages, model, temperature).await?;
+        Ok(Served::new(text, self.name()))
+    }
+
+    /// [`Provider::chat_with_history_stream`], also naming the provider that
+    /// answered this call (see [`Provider::chat_with_history_served`]).
+    async fn chat_with_history_stream_served(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        token_tx: tokio::sync::mpsc::Sender<String>,
+    ) -> anyhow::Result<Served> {
+        let text = self
+            .chat_with_history_stream(messages, model, temperature, token_tx)
+            .await?;
+        Ok(Served::new(text, self.name()))
+    }
+
     /// Returns true if this provider supports real token-by-token streaming.
     fn supports_streaming(&self) -> bool {
         false
     }
 
+    /// Whether the API accepts a `system` message for `model`. When it
+    /// returns false the system prompt is folded into the first user message
+    /// instead (see `fold_system`).
+    fn supports_system_prompt(&self, _model: &str) -> bool {
+        true
+    }
+
     /// Set a usage tracker for this provider. Providers that support it will
     /// accumulate token counts into the tracker after each API call.
     fn set_usage_tracker(&mut self, _tracker: UsageTracker) {
         // Default: no-op. Providers override to store the tracker.
     }
+
+    /// Set a sampling seed for reproducible outputs. Providers whose API has no
+    /// seed parameter ignore it.
+    fn set_seed(&mut self, seed: Option<u64>) {
+        if seed.is_some() {
+            tracing::debug!("Provider does not support a sampling seed; ignoring it");
+        }
+    }
+
+    /// Set sequences that end generation. Errors when the list exceeds what the
+    /// provider's API accepts; providers without stop support ignore it.
+    fn set_stop_sequences(&mut self, stop: &[String]) -> anyhow::Result<()> {
+        if !stop.is_empty() {
+            tracing::debug!("Provider does not support stop sequences; ignoring them");
+        }
+        Ok(())
+    }
+
+    /// Set how much reasoning to request, mapped to the provider's native
+    /// parameter. Providers without one ignore it.
+    fn set_reasoning_effort(&mut self, effort: Option<ReasoningEffort>) {
+        if effort.is_some() {
+            tracing::debug!("Provider has no reasoning-effort parameter; ignoring it");
+        }
+    }
+
+    /// Ask for the model's reasoning alongside its answer. Providers with a
+    /// native reasoning API return it wrapped in `<thinking>` tags.
+    fn set_thinking(&mut self, _enabled: bool) {
+        // Default: no-op. Providers without a reasoning API ignore this.
+    }
+
+    /// List the models this provider can serve.
+    /// Default implementation reports that listing is unsupported.
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        anyhow::bail!("This provider does not support listing models")
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn reasoning_effort_parses_and_validates() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            effort: ReasoningEffort,
+        }
+
+        assert_eq!("High".parse::<ReasoningEffort>(), Ok(ReasoningEffort::High));
+        assert_eq!(ReasoningEffort::Medium.to_string(), "medium");
+        let err = "max".parse::<ReasoningEffort>().unwrap_err();
+        assert!(err.contains("low, medium or high"), "{err}");
+
+        let parsed: Wrapper = toml::from_str(r#"effort = "low""#).unwrap();
+        assert_eq!(parsed.effort, ReasoningEffort::Low);
+        assert!(toml::from_str::<Wrapper>(r#"effort = "extreme""#).is_err());
+    }
+
     #[test]
     fn chat_message_constructors() {
         let sys = ChatMessage::system("Be helpful");
@@ -232,6 +596,57 @@ mod tests {
 
         let asst = ChatMessage::assistant("Hi there");
         assert_eq!(asst.role, "assistant");
+        assert!(asst.timestamp.is_some());
+        assert!(asst.model.is_none());
+        assert_eq!(asst.with_model("gpt-4o").model.as_deref(), Some("gpt-4o"));
+    }
+
+    #[test]
+    fn tool_messages_inline_as_user_text() {
+        let xml = ChatMessage::tool("<tool_result name=\"shell\">ok</tool_result>");
+        assert_eq!(xml.role, "tool");
+        assert!(xml.tool_call_id.is_none());
+        let (role, content) = xml.inline_tool_results();
+        assert_eq!(role, "user");
+        assert_eq!(
+            content,
+            "[Tool results]\n<tool_result name=\"shell\">ok</tool_result>"
+        );
+
+        let native = ChatMessage::tool_result("call_1", "ok");
+        assert_eq!(native.tool_call_id.as_deref(), Some("call_1"));
+        assert_eq!(native.tool_results(), Some("ok"));
+
+        // Sessions saved before the tool role
+        let legacy = ChatMessage::user("[Tool results]\nok");
+        assert_eq!(legacy.tool_results(), Some("ok"));
+        assert_eq!(
+            legacy.inline_tool_results(),
+            ("user", "[Tool results]\nok".into())
+        );
+        assert_eq!(ChatMessage::user("hi").tool_results(), None);
+
+        let json = serde_json::to_string(&ChatMessage::user("hi")).unwrap();
+        assert!(!json.contains("tool_call_id"));
+    }
+
+    #[test]
+    fn stop_sequence_validation_and_search() {
+        let stop = vec!["</answer>".to_string(), "\nUser:".to_string()];
+        assert!(validate_stop_sequences("OpenAI", &stop, Some(4)).is_ok());
+        assert!(validate_stop_sequences("OpenAI", &stop, Some(1)).is_err());
+        assert!(validate_stop_sequences("Anthropic", &[" ".into()], None).is_err());
+
+        assert_eq!(find_stop_sequence("42</answer> extra", &stop), Some(2));
+        assert_eq!(find_stop_sequence("a\nUser: b</answer>", &stop), Some(1));
+        assert_eq!(find_stop_sequence("no stop here", &stop), None);
+        assert_eq!(find_stop_sequence("</answer>", &[]), None);
+
+        assert_eq!(stop_sequence_holdback("42</ans", &stop), 5);
+        assert_eq!(stop_sequence_holdback("a line\n", &stop), 1);
+        assert_eq!(stop_sequence_holdback("a < b", &stop), 0);
+        assert_eq!(stop_sequence_holdback("café<", &stop), 1);
+        assert_eq!(stop_sequence_holdback("anything", &[]), 0);
     }
 
     #[test]
@@ -255,6 +670,46 @@ mod tests {
         assert_eq!(with_tools.text_or_empty(), "Let me check");
     }
 
+    #[test]
+    fn model_info_new_leaves_optional_fields_empty() {
+        let info = ModelInfo::new("gpt-4o");
+        assert_eq!(info.id, "gpt-4o");
+        assert!(info.context_length.is_none());
+        assert!(info.prompt_price_per_m.is_none());
+        assert!(info.completion_price_per_m.is_none());
+    }
+
+    #[test]
+    fn usage_tracker_latency_starts_empty() {
+        let tracker = UsageTracker::new();
+        assert_eq!(tracker.avg_latency_ms(), None);
+        assert_eq!(tracker.last_latency_ms(), None);
+        assert_eq!(tracker.ema_latency_ms(), None);
+    }
+
+    #[test]
+    fn usage_tracker_records_latency() {
+        let tracker = UsageTracker::new();
+        tracker.record_latency(Duration::from_millis(100));
+        assert_eq!(tracker.ema_latency_ms(), Some(100));
+
+        // Clones share the same counters.
+        tracker.clone().record_latency(Duration::from_millis(300));
+        assert_eq!(tracker.avg_latency_ms(), Some(200));
+        assert_eq!(tracker.last_latency_ms(), Some(300));
+        // 0.2 * 300 + 0.8 * 100
+        assert_eq!(tracker.ema_latency_ms(), Some(140));
+    }
+
+    #[test]
+    fn usage_tracker_keeps_latest_fingerprint() {
+        let tracker = UsageTracker::new();
+        assert_eq!(tracker.system_fingerprint(), None);
+        tracker.record_system_fingerprint("fp_a");
+        tracker.clone().record_system_fingerprint("fp_b");
+        assert_eq!(tracker.system_fingerprint().as_deref(), Some("fp_b"));
+    }
+
     #[test]
     fn tool_call_serialization() {
         let tc = ToolCall {
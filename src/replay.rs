@@ -0,0 +1,282 @@
+//! Replay a saved session's user turns against the current config.
+//!
+//! Each user message is re-sent in order (building fresh history from the new
+//! answers) and the new assistant text is diffed against the stored one. Tools
+//! are not executed during replay, so turns that relied on tool output will
+//! naturally diverge.
+
+use crate::channels::build_system_prompt;
+use crate::config::Config;
+use crate::memory::MEMORY_CONTEXT_PREFIX;
+use crate::providers::{self, ChatMessage, UsageTracker};
+use crate::session;
+use crate::util::truncate_with_ellipsis;
+use anyhow::{Context, Result};
+use std::fmt::Write;
+
+/// One user turn from a saved session and the assistant answer it produced.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayTurn {
+    pub user: String,
+    pub expected: String,
+}
+
+/// Split a saved conversation into user turns.
+///
+/// Tool-result messages are folded into the turn that triggered them, memory
+/// context is stripped from user messages, and the last assistant message of
+/// each turn is taken as its final answer.
+pub fn extract_turns(messages: &[ChatMessage]) -> Vec<ReplayTurn> {
+    let mut turns: Vec<ReplayTurn> = Vec::new();
+    for msg in messages {
+        match msg.role.as_str() {
+            "user" if msg.tool_results().is_none() => {
+                turns.push(ReplayTurn {
+                    user: strip_memory_context(&msg.content).to_string(),
+                    expected: String::new(),
+                });
+            }
+            "assistant" => {
+                if let Some(turn) = turns.last_mut() {
+                    turn.expected.clone_from(&msg.content);
+                }
+            }
+            _ => {}
+        }
+    }
+    turns
+}
+
+fn strip_memory_context(content: &str) -> &str {
+    content
+        .strip_prefix(MEMORY_CONTEXT_PREFIX)
+        .and_then(|rest| rest.split_once("\n\n"))
+        .map_or(content, |(_, message)| message)
+}
+
+/// Line-based unified-style diff (`-` stored, `+` replayed, two spaces for
+/// unchanged lines).
+pub fn line_diff(old: &str, new: &str) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    // Longest-common-subsequence table, filled from the end
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            let _ = writeln!(out, "  {}", a[i]);
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            let _ = writeln!(out, "- {}", a[i]);
+            i += 1;
+        } else {
+            let _ = writeln!(out, "+ {}", b[j]);
+            j += 1;
+        }
+    }
+    for line in &a[i..] {
+        let _ = writeln!(out, "- {line}");
+    }
+    for line in &b[j..] {
+        let _ = writeln!(out, "+ {line}");
+    }
+    out
+}
+
+/// Replay `session_id` and print per-turn diffs plus token/cost deltas.
+pub async fn run(
+    config: Config,
+    session_id: &str,
+    provider_override: Option<String>,
+    model_override: Option<String>,
+    temperature: Option<f64>,
+) -> Result<()> {
+    let stored = session::load(&config.workspace_dir, session_id)
+        .with_context(|| format!("Failed to load session '{session_id}'"))?;
+    let turns = extract_turns(&stored.messages);
+    if turns.is_empty() {
+        anyhow::bail!("Session '{session_id}' has no user turns to replay");
+    }
+
+    let provider_name = provider_override
+        .as_deref()
+        .or(config.default_provider.as_deref())
+        .unwrap_or("openrouter");
+    let model_name = model_override.unwrap_or_else(|| config.default_model_for(provider_name));
+    let model_name = model_name.as_str();
+    // An explicit --temperature beats the model route's, which beats the
+    // configured default
+    let temperature = temperature
+        .or_else(|| providers::route_temperature(&config.model_routes, model_name))
+        .unwrap_or(config.default_temperature);
+
+    let mut provider = providers::create_routed_provider(
+        provider_name,
+        config.api_key.as_deref(),
+        &config.providers,
+        &config.reliability,
+        &config.model_routes,
+        model_name,
+    )?;
+    let usage = UsageTracker::new();
+    provider.set_usage_tracker(usage.clone());
+    provider.set_seed(config.providers.seed);
+    provider.set_reasoning_effort(config.providers.reasoning_effort);
+    provider.set_stop_sequences(&config.providers.stop_sequences)?;
+
+    let skills = crate::skills::load_skills(&config.workspace_dir);
+    let system_prompt = build_system_prompt(
+        &config.workspace_dir,
+        model_name,
+        &[],
+        &skills,
+        Some(&config.identity),
+    );
+    let mut history = vec![ChatMessage::system(&system_prompt)];
+
+    println!("Replaying session {session_id} ({} turns)", turns.len());
+    println!("  Stored model: {}", stored.model);
+    println!("  Replay model: {model_name} via {provider_name}");
+
+    let counter = providers::tokens::token_counter();
+    let (mut changed, mut failed) = (0usize, 0usize);
+    let (mut stored_tokens, mut replay_tokens) = (0u64, 0u64);
+
+    for (idx, turn) in turns.iter().enumerate() {
+        println!();
+        println!("── Turn {}/{} ──", idx + 1, turns.len());
+        println!("User: {}", truncate_with_ellipsis(&turn.user, 120));
+
+        history.push(ChatMessage::user(&turn.user));
+        let before = usage.snapshot();
+        let response = match provider
+            .chat_with_history(&history, model_name, temperature)
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                failed += 1;
+                println!("  ❌ Request failed: {e}");
+                // Keep the conversation comparable for later turns
+                history.push(ChatMessage::assistant(&turn.expected));
+                continue;
+            }
+        };
+        let after = usage.snapshot();
+        history.push(ChatMessage::assistant(&response));
+
+        if response.trim() == turn.expected.trim() {
+            println!("  ✅ Unchanged");
+        } else {
+            changed += 1;
+            print!("{}", line_diff(turn.expected.trim(), response.trim()));
+        }
+
+        let completion = after.completion_tokens - before.completion_tokens;
+        // Stored answers carry no usage data, so estimate their size
+        let estimated = counter.count(&turn.expected, model_name) as u64;
+        stored_tokens += estimated;
+        replay_tokens += completion;
+        println!(
+            "  Tokens: {} prompt, {completion} completion (stored ≈ {estimated}, Δ {:+})",
+            after.prompt_tokens - before.prompt_tokens,
+            i128::from(completion) - i128::from(estimated),
+        );
+    }
+
+    let snap = usage.snapshot();
+    println!();
+    println!(
+        "Summary: {} turns, {changed} changed, {failed} failed",
+        turns.len()
+    );
+    println!(
+        "  Completion tokens: {replay_tokens} replayed vs ≈ {stored_tokens} stored (Δ {:+})",
+        i128::from(replay_tokens) - i128::from(stored_tokens)
+    );
+    println!(
+        "  Replay usage: {} total tokens, est. ${:.4}",
+        snap.total_tokens,
+        usage.estimated_cost_usd()
+    );
+    if let Some(fingerprint) = usage.system_fingerprint() {
+        println!("  Backend fingerprint: {fingerprint}");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_turns_folds_tool_results_into_turn() {
+        let messages = vec![
+            ChatMessage::system("sys"),
+            ChatMessage::user("list files"),
+            ChatMessage::assistant("<tool_call>{\"name\":\"shell\"}</tool_call>"),
+            ChatMessage::user("[Tool results]\n<tool_result name=\"shell\">a.txt</tool_result>"),
+            ChatMessage::assistant("There is a.txt"),
+            ChatMessage::user("thanks"),
+            ChatMessage::assistant("You're welcome"),
+        ];
+        let turns = extract_turns(&messages);
+        assert_eq!(
+            turns,
+            vec![
+                ReplayTurn {
+                    user: "list files".into(),
+                    expected: "There is a.txt".into(),
+                },
+                ReplayTurn {
+                    user: "thanks".into(),
+                    expected: "You're welcome".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn extract_turns_strips_memory_context() {
+        let messages = vec![
+            ChatMessage::user("[Memory context]\n- lang: Rust\n\nWhat do I use?"),
+            ChatMessage::assistant("Rust"),
+        ];
+        let turns = extract_turns(&messages);
+        assert_eq!(turns[0].user, "What do I use?");
+    }
+
+    #[test]
+    fn extract_turns_keeps_unanswered_turn() {
+        let turns = extract_turns(&[ChatMessage::user("hello")]);
+        assert_eq!(turns.len(), 1);
+        assert!(turns[0].expected.is_empty());
+    }
+
+    #[test]
+    fn line_diff_marks_changes() {
+        let diff = line_diff("a\nb\nc", "a\nx\nc\nd");
+        assert_eq!(diff, "  a\n- b\n+ x\n  c\n+ d\n");
+    }
+
+    #[test]
+    fn line_diff_identical_has_no_markers() {
+        let diff = line_diff("same\ntext", "same\ntext");
+        assert!(!diff.contains("\n- ") && !diff.contains("\n+ "));
+        assert!(!diff.starts_with('-') && !diff.starts_with('+'));
+    }
+}
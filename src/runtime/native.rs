@@ -24,10 +24,7 @@ impl RuntimeAdapter for NativeRuntime {
     }
 
     fn storage_path(&self) -> PathBuf {
-        directories::UserDirs::new().map_or_else(
-            || PathBuf::from(".tinyclaw"),
-            |u| u.home_dir().join(".tinyclaw"),
-        )
+        crate::config::tinyclaw_dir().unwrap_or_else(|_| PathBuf::from(".tinyclaw"))
     }
 
     fn supports_long_running(&self) -> bool {
@@ -1,7 +1,10 @@
+pub mod output;
 pub mod pairing;
 pub mod policy;
+pub mod redact;
 pub mod secrets;
 
+pub use output::OutputTransforms;
 #[allow(unused_imports)]
 pub use pairing::PairingGuard;
 pub use policy::{AutonomyLevel, SecurityPolicy};
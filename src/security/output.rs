@@ -0,0 +1,108 @@
+use crate::config::SecurityConfig;
+use anyhow::{Context, Result};
+use regex::Regex;
+
+/// Rewrites final assistant replies with the `[[security.output_transforms]]`
+/// rules, e.g. to mask identifiers or append a disclaimer.
+#[derive(Default)]
+pub struct OutputTransforms {
+    rules: Vec<(Regex, String)>,
+}
+
+impl OutputTransforms {
+    /// Compile the configured rules, failing on the first invalid pattern.
+    pub fn from_config(config: &SecurityConfig) -> Result<Self> {
+        let rules = config
+            .output_transforms
+            .iter()
+            .enumerate()
+            .map(|(i, rule)| {
+                Regex::new(&rule.pattern)
+                    .map(|re| (re, rule.replacement.clone()))
+                    .with_context(|| {
+                        format!(
+                            "Invalid security.output_transforms[{i}] pattern {:?}",
+                            rule.pattern
+                        )
+                    })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Whether there are no rules, so replies pass through unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Apply every rule to `text` in config order, each to the previous
+    /// rule's output. Replacements may use `$1` / `${name}` capture groups.
+    pub fn apply(&self, text: &str) -> String {
+        let mut out = text.to_string();
+        for (re, replacement) in &self.rules {
+            if re.is_match(&out) {
+                out = re.replace_all(&out, replacement.as_str()).into_owned();
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::schema::OutputTransform;
+
+    fn transforms(rules: &[(&str, &str)]) -> Result<OutputTransforms> {
+        OutputTransforms::from_config(&SecurityConfig {
+            output_transforms: rules
+                .iter()
+                .map(|(pattern, replacement)| OutputTransform {
+                    pattern: (*pattern).to_string(),
+                    replacement: (*replacement).to_string(),
+                })
+                .collect(),
+            ..SecurityConfig::default()
+        })
+    }
+
+    #[test]
+    fn masking_rule_replaces_every_match() {
+        let t = transforms(&[(r"\b\d{4}-\d{4}-\d{4}-(\d{4})\b", "****-$1")]).unwrap();
+        assert_eq!(
+            t.apply("Cards 1111-2222-3333-4444 and 5555-6666-7777-8888 are on file."),
+            "Cards ****-4444 and ****-8888 are on file."
+        );
+        assert_eq!(t.apply("nothing to mask"), "nothing to mask");
+    }
+
+    #[test]
+    fn footer_rule_appends_once() {
+        let t = transforms(&[(r"\z", "\n\n_Generated by TinyClaw_")]).unwrap();
+        assert_eq!(t.apply("Done."), "Done.\n\n_Generated by TinyClaw_");
+    }
+
+    #[test]
+    fn rules_apply_in_config_order() {
+        let t = transforms(&[("secret", "[masked]"), (r"\[masked\]", "***")]).unwrap();
+        assert_eq!(t.apply("the secret"), "the ***");
+
+        let t = transforms(&[(r"\[masked\]", "***"), ("secret", "[masked]")]).unwrap();
+        assert_eq!(t.apply("the secret"), "the [masked]");
+    }
+
+    #[test]
+    fn invalid_pattern_is_an_error() {
+        let err = transforms(&[("ok", ""), ("(unclosed", "")])
+            .err()
+            .expect("invalid regex should fail");
+        assert!(err.to_string().contains("output_transforms[1]"), "{err}");
+    }
+
+    #[test]
+    fn no_rules_is_identity() {
+        let t = OutputTransforms::from_config(&SecurityConfig::default()).unwrap();
+        assert!(t.is_empty());
+        assert_eq!(t.apply("unchanged"), "unchanged");
+    }
+}
@@ -90,6 +90,8 @@ pub struct SecurityPolicy {
     pub max_cost_per_day_cents: u32,
     pub require_approval_for_medium_risk: bool,
     pub block_high_risk_commands: bool,
+    pub max_file_read_bytes: u64,
+    pub max_download_bytes: u64,
     pub tracker: ActionTracker,
 }
 
@@ -139,6 +141,8 @@ impl Default for SecurityPolicy {
             max_cost_per_day_cents: 500,
             require_approval_for_medium_risk: true,
             block_high_risk_commands: true,
+            max_file_read_bytes: 10 * 1024 * 1024,
+            max_download_bytes: 100 * 1024 * 1024,
             tracker: ActionTracker::new(),
         }
     }
@@ -500,6 +504,8 @@ impl SecurityPolicy {
             max_cost_per_day_cents: autonomy_config.max_cost_per_day_cents,
             require_approval_for_medium_risk: autonomy_config.require_approval_for_medium_risk,
             block_high_risk_commands: autonomy_config.block_high_risk_commands,
+            max_file_read_bytes: autonomy_config.max_file_read_bytes,
+            max_download_bytes: autonomy_config.max_download_bytes,
             tracker: ActionTracker::new(),
         }
     }
@@ -784,6 +790,9 @@ mod tests {
             max_cost_per_day_cents: 1000,
             require_approval_for_medium_risk: false,
             block_high_risk_commands: false,
+            max_file_read_bytes: 4096,
+            max_download_bytes: 8192,
+            tools_only: Vec::new(),
         };
         let workspace = PathBuf::from("/tmp/test-workspace");
         let policy = SecurityPolicy::from_config(&autonomy_config, &workspace);
@@ -796,6 +805,8 @@ mod tests {
         assert_eq!(policy.max_cost_per_day_cents, 1000);
         assert!(!policy.require_approval_for_medium_risk);
         assert!(!policy.block_high_risk_commands);
+        assert_eq!(policy.max_file_read_bytes, 4096);
+        assert_eq!(policy.max_download_bytes, 8192);
         assert_eq!(policy.workspace_dir, PathBuf::from("/tmp/test-workspace"));
     }
 
@@ -1097,6 +1108,9 @@ mod tests {
             max_cost_per_day_cents: 100,
             require_approval_for_medium_risk: true,
             block_high_risk_commands: true,
+            max_file_read_bytes: 1024 * 1024,
+            max_download_bytes: 1024 * 1024,
+            tools_only: Vec::new(),
         };
         let workspace = PathBuf::from("/tmp/test");
         let policy = SecurityPolicy::from_config(&autonomy_config, &workspace);
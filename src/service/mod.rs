@@ -16,7 +16,7 @@ pub fn handle_command(command: &crate::ServiceCommands, config: &Config) -> Resu
     }
 }
 
-fn install(config: &Config) -> Result<()> {
+pub(crate) fn install(config: &Config) -> Result<()> {
     if cfg!(target_os = "macos") {
         install_macos(config)
     } else if cfg!(target_os = "linux") {
@@ -184,7 +184,7 @@ fn install_linux(config: &Config) -> Result<()> {
 
     let exe = std::env::current_exe().context("Failed to resolve current executable")?;
     let unit = format!(
-        "[Unit]\nDescription=TinyClaw daemon\nAfter=network.target\n\n[Service]\nType=simple\nExecStart={} daemon\nRestart=always\nRestartSec=3\n\n[Install]\nWantedBy=default.target\n",
+        "[Unit]\nDescription=TinyClaw daemon\nAfter=network.target\n\n[Service]\nType=simple\nExecStart={} daemon\nExecReload=/bin/kill -HUP $MAINPID\nRestart=always\nRestartSec=3\n\n[Install]\nWantedBy=default.target\n",
         exe.display()
     );
 
@@ -196,6 +196,18 @@ fn install_linux(config: &Config) -> Result<()> {
     Ok(())
 }
 
+/// Path of the launchd plist or systemd unit for this platform, or `None`
+/// where service management is unsupported.
+pub(crate) fn unit_file(config: &Config) -> Result<Option<PathBuf>> {
+    if cfg!(target_os = "macos") {
+        macos_service_file().map(Some)
+    } else if cfg!(target_os = "linux") {
+        linux_service_file(config).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
 fn macos_service_file() -> Result<PathBuf> {
     let home = directories::UserDirs::new()
         .map(|u| u.home_dir().to_path_buf())
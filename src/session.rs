@@ -1,7 +1,13 @@
+use crate::observability::ToolStat;
 use crate::providers::ChatMessage;
-use anyhow::Result;
+use crate::util::truncate_with_ellipsis;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 /// Metadata for a saved session (shown in listing).
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +27,9 @@ pub struct Session {
     pub updated_at: String,
     pub model: String,
     pub messages: Vec<ChatMessage>,
+    /// Per-tool call counts and timings for this session
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tool_stats: BTreeMap<String, ToolStat>,
 }
 
 fn sessions_dir(workspace_dir: &Path) -> PathBuf {
@@ -31,6 +40,20 @@ fn session_path(workspace_dir: &Path, id: &str) -> PathBuf {
     sessions_dir(workspace_dir).join(format!("{id}.json"))
 }
 
+/// Last good state, refreshed before each write.
+fn backup_path(workspace_dir: &Path, id: &str) -> PathBuf {
+    sessions_dir(workspace_dir).join(format!("{id}.json.bak"))
+}
+
+/// A temp file older than this is left over from a crash rather than a save
+/// still in progress, here or in another process.
+const STALE_TEMP_SECS: u64 = 60;
+
+/// In-progress write; left behind only if the process died mid-save.
+fn temp_path(workspace_dir: &Path, id: &str) -> PathBuf {
+    sessions_dir(workspace_dir).join(format!("{id}.json.tmp"))
+}
+
 /// Generate a short session ID from timestamp.
 pub fn new_session_id() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -51,6 +74,34 @@ fn now_iso() -> String {
     format!("{secs}")
 }
 
+fn read_session(path: &Path) -> Result<Session> {
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read session {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("Corrupt session file {}", path.display()))
+}
+
+/// Write `session` without ever leaving a half-written file in place: the
+/// JSON goes to a temp file that is synced and then renamed over the old
+/// one. A readable previous version is kept as the `.bak`.
+fn write_session(workspace_dir: &Path, session: &Session) -> Result<PathBuf> {
+    std::fs::create_dir_all(sessions_dir(workspace_dir))?;
+    let path = session_path(workspace_dir, &session.id);
+    let tmp = temp_path(workspace_dir, &session.id);
+
+    let json = serde_json::to_string_pretty(session)?;
+    let mut file = File::create(&tmp)?;
+    file.write_all(json.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+
+    // Never copy a damaged file over the last good backup
+    if read_session(&path).is_ok() {
+        std::fs::copy(&path, backup_path(workspace_dir, &session.id))?;
+    }
+    std::fs::rename(&tmp, &path)?;
+    Ok(path)
+}
+
 /// Save a session to disk.
 pub fn save(
     workspace_dir: &Path,
@@ -58,9 +109,6 @@ pub fn save(
     model: &str,
     messages: &[ChatMessage],
 ) -> Result<PathBuf> {
-    let dir = sessions_dir(workspace_dir);
-    std::fs::create_dir_all(&dir)?;
-
     let now = now_iso();
     let session = Session {
         id: id.to_string(),
@@ -68,12 +116,9 @@ pub fn save(
         updated_at: now,
         model: model.to_string(),
         messages: messages.to_vec(),
+        tool_stats: BTreeMap::new(),
     };
-
-    let path = session_path(workspace_dir, id);
-    let json = serde_json::to_string_pretty(&session)?;
-    std::fs::write(&path, json)?;
-    Ok(path)
+    write_session(workspace_dir, &session)
 }
 
 /// Update an existing session (preserves created_at).
@@ -82,14 +127,9 @@ pub fn update(
     id: &str,
     model: &str,
     messages: &[ChatMessage],
+    tool_stats: BTreeMap<String, ToolStat>,
 ) -> Result<PathBuf> {
-    let path = session_path(workspace_dir, id);
-    let created_at = if path.exists() {
-        let existing: Session = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
-        existing.created_at
-    } else {
-        now_iso()
-    };
+    let created_at = load(workspace_dir, id).map_or_else(|_| now_iso(), |s| s.created_at);
 
     let session = Session {
         id: id.to_string(),
@@ -97,24 +137,65 @@ pub fn update(
         updated_at: now_iso(),
         model: model.to_string(),
         messages: messages.to_vec(),
+        tool_stats,
     };
+    write_session(workspace_dir, &session)
+}
 
-    let dir = sessions_dir(workspace_dir);
-    std::fs::create_dir_all(&dir)?;
-    let json = serde_json::to_string_pretty(&session)?;
-    std::fs::write(&path, json)?;
-    Ok(path)
+/// The session, or the `.bak` left by the previous save when the file is
+/// missing or corrupt, along with the error that sent it to the backup.
+/// Writes nothing.
+fn read_or_backup(workspace_dir: &Path, id: &str) -> Result<(Session, Option<anyhow::Error>)> {
+    let err = match read_session(&session_path(workspace_dir, id)) {
+        Ok(session) => return Ok((session, None)),
+        Err(e) => e,
+    };
+    match read_session(&backup_path(workspace_dir, id)) {
+        Ok(session) => Ok((session, Some(err))),
+        Err(_) => Err(err),
+    }
 }
 
-/// Load a session from disk.
+/// Load a session from disk. A missing or corrupt file is restored from the
+/// `.bak` left by the previous save, when that one is readable.
 pub fn load(workspace_dir: &Path, id: &str) -> Result<Session> {
+    let (session, err) = read_or_backup(workspace_dir, id)?;
+    let Some(err) = err else {
+        return Ok(session);
+    };
     let path = session_path(workspace_dir, id);
-    let json = std::fs::read_to_string(&path)?;
-    let session: Session = serde_json::from_str(&json)?;
+    let backup = backup_path(workspace_dir, id);
+    tracing::warn!(
+        "{err:#}; restored the previous save from {}",
+        backup.display()
+    );
+
+    // Repair the same way a save does, so a crash here is harmless too
+    let tmp = temp_path(workspace_dir, id);
+    if let Err(e) = std::fs::copy(&backup, &tmp).and_then(|_| std::fs::rename(&tmp, &path)) {
+        tracing::warn!("Failed to repair session {id}: {e}");
+    }
     Ok(session)
 }
 
-/// List all saved sessions (most recent first).
+fn meta(session: Session) -> SessionMeta {
+    let preview = session
+        .messages
+        .iter()
+        .find(|m| m.role == "user")
+        .map(|m| truncate_with_ellipsis(&m.content, 60))
+        .unwrap_or_default();
+    SessionMeta {
+        id: session.id,
+        created_at: session.created_at,
+        updated_at: session.updated_at,
+        message_count: session.messages.len(),
+        preview,
+    }
+}
+
+/// List all saved sessions (most recent first). A corrupt session is listed
+/// from its backup but only repaired once it is loaded.
 pub fn list(workspace_dir: &Path) -> Result<Vec<SessionMeta>> {
     let dir = sessions_dir(workspace_dir);
     if !dir.exists() {
@@ -128,28 +209,12 @@ pub fn list(workspace_dir: &Path) -> Result<Vec<SessionMeta>> {
         if path.extension().map_or(true, |e| e != "json") {
             continue;
         }
-        if let Ok(json) = std::fs::read_to_string(&path) {
-            if let Ok(session) = serde_json::from_str::<Session>(&json) {
-                let preview = session
-                    .messages
-                    .iter()
-                    .find(|m| m.role == "user")
-                    .map(|m| {
-                        if m.content.len() > 60 {
-                            format!("{}...", &m.content[..60])
-                        } else {
-                            m.content.clone()
-                        }
-                    })
-                    .unwrap_or_default();
-                sessions.push(SessionMeta {
-                    id: session.id,
-                    created_at: session.created_at,
-                    updated_at: session.updated_at,
-                    message_count: session.messages.len(),
-                    preview,
-                });
-            }
+        let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        match read_or_backup(workspace_dir, id) {
+            Ok((session, _)) => sessions.push(meta(session)),
+            Err(e) => tracing::warn!("Skipping unreadable session: {e:#}"),
         }
     }
 
@@ -158,15 +223,208 @@ pub fn list(workspace_dir: &Path) -> Result<Vec<SessionMeta>> {
     Ok(sessions)
 }
 
-/// Delete a session.
+/// Find sessions whose last save was cut off by a crash, clearing the
+/// leftover temp files. Each returned session holds its last complete
+/// state and can be resumed with `/resume <id>`. Temp files younger than
+/// [`STALE_TEMP_SECS`] may belong to a save still running and are left
+/// alone.
+pub fn recover_interrupted(workspace_dir: &Path) -> Vec<SessionMeta> {
+    recover_stale(workspace_dir, Duration::from_secs(STALE_TEMP_SECS))
+}
+
+fn recover_stale(workspace_dir: &Path, min_age: Duration) -> Vec<SessionMeta> {
+    let Ok(entries) = std::fs::read_dir(sessions_dir(workspace_dir)) else {
+        return Vec::new();
+    };
+
+    let mut sessions = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(id) = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_suffix(".json.tmp"))
+        else {
+            continue;
+        };
+        let age = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok());
+        if age.is_none_or(|age| age < min_age) {
+            continue;
+        }
+        if let Ok(session) = load(workspace_dir, id) {
+            sessions.push(meta(session));
+        }
+        if let Err(e) = std::fs::remove_file(&path) {
+            tracing::warn!("Failed to remove {}: {e}", path.display());
+        }
+    }
+    sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    sessions
+}
+
+/// Delete a session, along with its backup and any partial write.
 pub fn delete(workspace_dir: &Path, id: &str) -> Result<()> {
-    let path = session_path(workspace_dir, id);
-    if path.exists() {
-        std::fs::remove_file(&path)?;
+    for path in [
+        session_path(workspace_dir, id),
+        backup_path(workspace_dir, id),
+        temp_path(workspace_dir, id),
+    ] {
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
     }
     Ok(())
 }
 
+/// When a finished turn is written to the session file
+/// (`[session] save_every_turns` / `save_interval_secs`).
+///
+/// A turn is saved once `every_turns` finished turns are waiting, or once
+/// `interval` has passed since the last save, whichever comes first; with
+/// both off every turn is saved. Turns held back stay in memory until the
+/// next save, so the caller must write them out before it exits.
+#[derive(Debug, Clone)]
+pub struct SaveSchedule {
+    every_turns: u32,
+    interval: Option<Duration>,
+    unsaved_turns: u32,
+    last_save: Option<Instant>,
+}
+
+impl SaveSchedule {
+    /// `0` turns off either trigger.
+    pub fn new(every_turns: u32, interval_secs: u64) -> Self {
+        Self {
+            every_turns,
+            interval: (interval_secs > 0).then(|| Duration::from_secs(interval_secs)),
+            unsaved_turns: 0,
+            last_save: None,
+        }
+    }
+
+    /// Count a finished turn; `true` when the session should be saved now.
+    pub fn turn_finished(&mut self) -> bool {
+        self.turn_finished_at(Instant::now())
+    }
+
+    fn turn_finished_at(&mut self, now: Instant) -> bool {
+        self.unsaved_turns += 1;
+        let by_turns = self.every_turns > 0 && self.unsaved_turns >= self.every_turns;
+        let by_time = self.interval.is_some_and(|interval| {
+            self.last_save
+                .is_none_or(|last| now.duration_since(last) >= interval)
+        });
+        by_turns || by_time || (self.every_turns == 0 && self.interval.is_none())
+    }
+
+    /// Finished turns not written yet.
+    pub fn unsaved_turns(&self) -> u32 {
+        self.unsaved_turns
+    }
+
+    /// Record that the session file was just written.
+    pub fn saved(&mut self) {
+        self.saved_at(Instant::now());
+    }
+
+    fn saved_at(&mut self, now: Instant) {
+        self.unsaved_turns = 0;
+        self.last_save = Some(now);
+    }
+}
+
+impl Default for SaveSchedule {
+    /// Save after every turn.
+    fn default() -> Self {
+        Self::new(1, 0)
+    }
+}
+
+/// A session's state, copied so it can be written after its owner is gone.
+pub struct Snapshot {
+    pub workspace_dir: PathBuf,
+    pub id: String,
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    pub tool_stats: BTreeMap<String, ToolStat>,
+}
+
+impl Snapshot {
+    /// Save the snapshot with [`update`].
+    pub fn write(&self) -> Result<PathBuf> {
+        update(
+            &self.workspace_dir,
+            &self.id,
+            &self.model,
+            &self.messages,
+            self.tool_stats.clone(),
+        )
+    }
+}
+
+/// Append-only, human-readable log of a session (`[session] transcript`).
+///
+/// Every entry is written straight to `transcripts/<session>.log` so the log
+/// survives a crash mid-turn, unlike the session JSON rewritten after each turn.
+pub struct Transcript {
+    file: File,
+    path: PathBuf,
+}
+
+impl Transcript {
+    /// Open (or continue) the transcript for session `id`.
+    pub fn open(workspace_dir: &Path, id: &str) -> Result<Self> {
+        let dir = workspace_dir.join("transcripts");
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{id}.log"));
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        Ok(Self { file, path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn user(&self, text: &str) {
+        self.append("user", text);
+    }
+
+    pub fn assistant(&self, text: &str) {
+        self.append("assistant", text);
+    }
+
+    pub fn tool_call(&self, name: &str, arguments: &serde_json::Value) {
+        self.append(&format!("tool_call {name}"), &arguments.to_string());
+    }
+
+    pub fn tool_result(&self, name: &str, output: &str) {
+        self.append(&format!("tool_result {name}"), output);
+    }
+
+    pub fn error(&self, message: &str) {
+        self.append("error", message);
+    }
+
+    /// Write one entry. Failures are logged, never fatal to the turn.
+    fn append(&self, label: &str, body: &str) {
+        let entry = format!(
+            "[{}] {label}\n{}\n\n",
+            chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"),
+            body.trim_end()
+        );
+        if let Err(e) = (&self.file).write_all(entry.as_bytes()) {
+            tracing::warn!("Failed to write transcript {}: {e}", self.path.display());
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -193,6 +451,52 @@ mod tests {
         assert_eq!(session.messages.len(), 3);
         assert_eq!(session.messages[0].role, "system");
         assert_eq!(session.messages[1].content, "Hello");
+        assert!(session.messages[1].timestamp.is_some());
+    }
+
+    #[test]
+    fn save_keeps_message_metadata() {
+        let dir = TempDir::new().unwrap();
+        let ws = dir.path();
+
+        let msgs = vec![
+            ChatMessage::user("Hello"),
+            ChatMessage::assistant("Hi there!").with_model("gpt-4o"),
+        ];
+        save(ws, "meta", "gpt-4o", &msgs).unwrap();
+
+        let session = load(ws, "meta").unwrap();
+        assert_eq!(session.messages[0].timestamp, msgs[0].timestamp);
+        assert_eq!(session.messages[0].model, None);
+        assert_eq!(session.messages[1].model.as_deref(), Some("gpt-4o"));
+    }
+
+    #[test]
+    fn loads_sessions_without_message_metadata() {
+        let dir = TempDir::new().unwrap();
+        let ws = dir.path();
+        std::fs::create_dir_all(sessions_dir(ws)).unwrap();
+        std::fs::write(
+            session_path(ws, "legacy"),
+            r#"{
+                "id": "legacy",
+                "created_at": "1700000000",
+                "updated_at": "1700000100",
+                "model": "gpt-4",
+                "messages": [
+                    {"role": "user", "content": "Hello"},
+                    {"role": "assistant", "content": "Hi there!"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let session = load(ws, "legacy").unwrap();
+        assert_eq!(session.messages.len(), 2);
+        assert_eq!(session.messages[1].content, "Hi there!");
+        assert!(session.messages[1].timestamp.is_none());
+        assert!(session.messages[1].model.is_none());
+        assert_eq!(list(ws).unwrap()[0].message_count, 2);
     }
 
     #[test]
@@ -205,13 +509,37 @@ mod tests {
 
         let mut msgs = test_messages();
         msgs.push(ChatMessage::user("Follow up"));
-        update(ws, "test-2", "gpt-4", &msgs).unwrap();
+        update(ws, "test-2", "gpt-4", &msgs, BTreeMap::new()).unwrap();
 
         let updated = load(ws, "test-2").unwrap();
         assert_eq!(updated.created_at, original.created_at);
         assert_eq!(updated.messages.len(), 4);
     }
 
+    #[test]
+    fn update_stores_tool_stats() {
+        let dir = TempDir::new().unwrap();
+        let ws = dir.path();
+
+        let stat = ToolStat {
+            calls: 3,
+            successes: 2,
+            failures: 1,
+            total_duration_ms: 120,
+        };
+        let stats = BTreeMap::from([("shell".to_string(), stat.clone())]);
+        let path = update(ws, "stats", "gpt-4", &test_messages(), stats).unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(json["tool_stats"]["shell"]["calls"], 3);
+        assert_eq!(load(ws, "stats").unwrap().tool_stats["shell"], stat);
+
+        save(ws, "plain", "gpt-4", &test_messages()).unwrap();
+        let raw = std::fs::read_to_string(session_path(ws, "plain")).unwrap();
+        assert!(!raw.contains("tool_stats"));
+    }
+
     #[test]
     fn list_returns_sessions() {
         let dir = TempDir::new().unwrap();
@@ -271,4 +599,148 @@ mod tests {
         let sessions = list(ws).unwrap();
         assert!(sessions[0].preview.len() <= 63); // 60 + "..."
     }
+
+    #[test]
+    fn preview_handles_multibyte_text() {
+        let dir = TempDir::new().unwrap();
+        let ws = dir.path();
+        save(ws, "emoji", "gpt-4", &[ChatMessage::user("é😀".repeat(50))]).unwrap();
+        assert!(list(ws).unwrap()[0].preview.ends_with("..."));
+    }
+
+    #[test]
+    fn truncated_session_does_not_break_list() {
+        let dir = TempDir::new().unwrap();
+        let ws = dir.path();
+        save(ws, "good", "gpt-4", &test_messages()).unwrap();
+        save(ws, "broken", "gpt-4", &test_messages()).unwrap();
+        let raw = std::fs::read_to_string(session_path(ws, "broken")).unwrap();
+        std::fs::write(session_path(ws, "broken"), &raw[..raw.len() / 2]).unwrap();
+
+        // No backup yet: the broken session is skipped, the rest still list
+        let ids: Vec<String> = list(ws).unwrap().into_iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec!["good"]);
+        assert!(load(ws, "broken").is_err());
+    }
+
+    #[test]
+    fn corrupt_session_falls_back_to_backup() {
+        let dir = TempDir::new().unwrap();
+        let ws = dir.path();
+        save(ws, "s", "gpt-4", &test_messages()).unwrap();
+        let mut msgs = test_messages();
+        msgs.push(ChatMessage::user("Follow up"));
+        update(ws, "s", "gpt-4", &msgs, BTreeMap::new()).unwrap();
+        assert!(backup_path(ws, "s").exists());
+        assert!(!temp_path(ws, "s").exists());
+
+        std::fs::write(session_path(ws, "s"), "{\"id\": \"s\", \"mess").unwrap();
+        assert_eq!(list(ws).unwrap()[0].message_count, 3);
+        assert!(
+            read_session(&session_path(ws, "s")).is_err(),
+            "listing never writes"
+        );
+        // Loading copies the backup back over the corrupt file
+        assert_eq!(load(ws, "s").unwrap().messages.len(), 3);
+        assert_eq!(
+            read_session(&session_path(ws, "s")).unwrap().messages.len(),
+            3
+        );
+
+        // A later save keeps working and refreshes the backup
+        update(ws, "s", "gpt-4", &msgs, BTreeMap::new()).unwrap();
+        assert_eq!(load(ws, "s").unwrap().messages.len(), 4);
+
+        delete(ws, "s").unwrap();
+        assert!(!backup_path(ws, "s").exists());
+        assert!(load(ws, "s").is_err());
+    }
+
+    #[test]
+    fn interrupted_save_is_reported_once() {
+        let dir = TempDir::new().unwrap();
+        let ws = dir.path();
+        save(ws, "crashed", "gpt-4", &test_messages()).unwrap();
+        save(ws, "clean", "gpt-4", &test_messages()).unwrap();
+        assert!(recover_interrupted(ws).is_empty());
+
+        // A crash mid-save leaves the partial temp file next to the last good state
+        std::fs::write(temp_path(ws, "crashed"), "{\"id\": \"cra").unwrap();
+        // Too recent to tell from a save still being written
+        assert!(recover_interrupted(ws).is_empty());
+        assert!(temp_path(ws, "crashed").exists());
+
+        let interrupted = recover_stale(ws, Duration::ZERO);
+        assert_eq!(interrupted.len(), 1);
+        assert_eq!(interrupted[0].id, "crashed");
+        assert_eq!(interrupted[0].message_count, 3);
+        assert!(!temp_path(ws, "crashed").exists());
+        assert!(recover_stale(ws, Duration::ZERO).is_empty());
+        assert_eq!(list(ws).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn transcript_appends_each_entry_immediately() {
+        let tmp = TempDir::new().unwrap();
+        let transcript = Transcript::open(tmp.path(), "abc").unwrap();
+        assert_eq!(
+            transcript.path(),
+            tmp.path().join("transcripts").join("abc.log")
+        );
+        let read = || std::fs::read_to_string(transcript.path()).unwrap();
+
+        transcript.user("list files");
+        let after_user = read();
+        assert!(after_user.contains("] user\nlist files\n"), "{after_user}");
+
+        transcript.tool_call("shell", &serde_json::json!({"command": "ls"}));
+        transcript.tool_result("shell", "a.txt\nb.txt\n");
+        let after_tool = read();
+        assert!(after_tool.starts_with(&after_user));
+        assert!(after_tool.contains("] tool_call shell\n{\"command\":\"ls\"}\n"));
+        assert!(after_tool.contains("] tool_result shell\na.txt\nb.txt\n\n"));
+
+        transcript.assistant("Two files.");
+        assert!(read().ends_with("] assistant\nTwo files.\n\n"));
+
+        // Reopening continues the same log instead of truncating it
+        let reopened = Transcript::open(tmp.path(), "abc").unwrap();
+        reopened.user("again");
+        let all = read();
+        assert!(all.starts_with(&after_tool));
+        assert!(all.ends_with("] user\nagain\n\n"));
+    }
+
+    #[test]
+    fn save_schedule_holds_back_turns_until_a_trigger_fires() {
+        let start = Instant::now();
+        let every_turn = SaveSchedule::default();
+        let mut both_off = SaveSchedule::new(0, 0);
+        assert!(every_turn.clone().turn_finished_at(start));
+        assert!(both_off.turn_finished_at(start));
+
+        let mut by_turns = SaveSchedule::new(3, 0);
+        let due: Vec<bool> = (0..6).map(|_| by_turns.turn_finished_at(start)).collect();
+        assert_eq!(due, [false, false, true, true, true, true]);
+        by_turns.saved_at(start);
+        assert_eq!(by_turns.unsaved_turns(), 0);
+        assert!(!by_turns.turn_finished_at(start));
+
+        // The first turn is saved right away, later ones once the interval passed
+        let mut by_time = SaveSchedule::new(0, 30);
+        assert!(by_time.turn_finished_at(start));
+        by_time.saved_at(start);
+        assert!(!by_time.turn_finished_at(start + Duration::from_secs(10)));
+        assert!(!by_time.turn_finished_at(start + Duration::from_secs(29)));
+        assert!(by_time.turn_finished_at(start + Duration::from_secs(30)));
+        assert_eq!(by_time.unsaved_turns(), 3);
+
+        // Whichever comes first
+        let mut either = SaveSchedule::new(2, 60);
+        either.saved_at(start);
+        assert!(!either.turn_finished_at(start));
+        assert!(either.turn_finished_at(start + Duration::from_secs(1)));
+        either.saved_at(start);
+        assert!(either.turn_finished_at(start + Duration::from_secs(61)));
+    }
 }
@@ -0,0 +1,374 @@
+//! `tinyclaw skills doctor`: check each installed skill the way the loader
+//! sees it and explain why one would be skipped or misbehave.
+
+use super::{skills_dir, SkillManifest, SkillTool};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Outcome of checking one skill, ordered from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Health {
+    Ok,
+    /// Loaded, but something looks wrong
+    Warn,
+    /// Not loaded, or a tool can't work
+    Fail,
+}
+
+#[derive(Debug)]
+pub struct SkillReport {
+    /// Directory name under `skills/`
+    pub name: String,
+    pub health: Health,
+    pub notes: Vec<String>,
+}
+
+impl SkillReport {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            health: Health::Ok,
+            notes: Vec::new(),
+        }
+    }
+
+    fn warn(&mut self, note: impl Into<String>) {
+        self.health = self.health.max(Health::Warn);
+        self.notes.push(note.into());
+    }
+
+    fn fail(&mut self, note: impl Into<String>) {
+        self.health = Health::Fail;
+        self.notes.push(note.into());
+    }
+}
+
+/// Check every skill directory in the workspace, sorted by name.
+pub fn check_skills(workspace_dir: &Path) -> Vec<SkillReport> {
+    let Ok(entries) = std::fs::read_dir(skills_dir(workspace_dir)) else {
+        return Vec::new();
+    };
+    let mut reports: Vec<SkillReport> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let is_link = entry.file_type().is_ok_and(|t| t.is_symlink());
+            // Plain files such as the README are not skills
+            if !path.is_dir() && !is_link {
+                return None;
+            }
+            Some(check_skill_dir(&path))
+        })
+        .collect();
+    reports.sort_by(|a, b| a.name.cmp(&b.name));
+    reports
+}
+
+fn check_skill_dir(dir: &Path) -> SkillReport {
+    let name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut report = SkillReport::new(name);
+
+    if !dir.is_dir() {
+        report.fail("broken link: the target directory does not exist");
+        return report;
+    }
+
+    let manifest_path = dir.join("SKILL.toml");
+    let md_path = dir.join("SKILL.md");
+    if manifest_path.exists() {
+        check_manifest(dir, &manifest_path, &mut report);
+        if md_path.exists() {
+            report.warn("SKILL.md is ignored because SKILL.toml takes priority");
+        }
+    } else if md_path.exists() {
+        match std::fs::read_to_string(&md_path) {
+            Ok(content) if content.trim().is_empty() => report.warn("SKILL.md is empty"),
+            Ok(_) => {}
+            Err(e) => report.fail(format!("SKILL.md can't be read: {e}")),
+        }
+    } else {
+        report.fail("no SKILL.toml or SKILL.md, so the skill is not loaded");
+    }
+    report
+}
+
+fn check_manifest(dir: &Path, path: &Path, report: &mut SkillReport) {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            report.fail(format!("SKILL.toml can't be read: {e}"));
+            return;
+        }
+    };
+    let manifest: SkillManifest = match toml::from_str(&content) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            let line = e
+                .span()
+                .map(|span| content[..span.start].lines().count().max(1));
+            let at = line.map(|l| format!(" (line {l})")).unwrap_or_default();
+            report.fail(format!("SKILL.toml is invalid{at}: {}", e.message()));
+            return;
+        }
+    };
+
+    if manifest.skill.name.trim().is_empty() {
+        report.fail("[skill] name is empty");
+    }
+    if manifest.skill.description.trim().is_empty() {
+        report.warn("[skill] description is empty");
+    }
+
+    let mut seen = HashSet::new();
+    for tool in &manifest.tools {
+        if !seen.insert(tool.name.as_str()) {
+            report.warn(format!("tool '{}' is defined more than once", tool.name));
+        }
+        check_tool(dir, tool, report);
+    }
+}
+
+fn check_tool(dir: &Path, tool: &SkillTool, report: &mut SkillReport) {
+    if tool.name.trim().is_empty() {
+        report.fail("a tool has an empty name");
+    }
+    let label = &tool.name;
+    let Some(program) = tool.command.split_whitespace().next() else {
+        report.fail(format!("tool '{label}' has an empty command"));
+        return;
+    };
+
+    match tool.kind.as_str() {
+        "http" => {
+            if !(program.starts_with("http://") || program.starts_with("https://")) {
+                report.fail(format!(
+                    "tool '{label}' is kind \"http\" but its command is not an http(s) URL"
+                ));
+            }
+        }
+        "script" => {
+            let script = dir.join(program);
+            if !script.is_file() {
+                report.fail(format!("tool '{label}': script {program} not found"));
+            } else if !is_executable(&script) {
+                report.fail(format!(
+                    "tool '{label}': script {program} is not executable (chmod +x)"
+                ));
+            }
+        }
+        "shell" => {
+            if !program.contains('/') && !on_path(program) {
+                report.warn(format!("tool '{label}': {program} not found on PATH"));
+            }
+        }
+        other => report.warn(format!(
+            "tool '{label}' has unknown kind \"{other}\" (expected shell, http or script)"
+        )),
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH").is_some_and(|paths| {
+        std::env::split_paths(&paths).any(|dir| {
+            let candidate = dir.join(program);
+            candidate.is_file() || candidate.with_extension("exe").is_file()
+        })
+    })
+}
+
+/// Print one line per skill plus its notes. Errors if any skill failed.
+pub fn run(workspace_dir: &Path) -> anyhow::Result<()> {
+    let reports = check_skills(workspace_dir);
+    if reports.is_empty() {
+        println!(
+            "No skills installed in {}.",
+            skills_dir(workspace_dir).display()
+        );
+        return Ok(());
+    }
+
+    println!("Checking {} skill(s):", reports.len());
+    println!();
+    for report in &reports {
+        let (icon, label) = match report.health {
+            Health::Ok => (console::style("✓").green().bold(), "OK"),
+            Health::Warn => (console::style("!").yellow().bold(), "WARN"),
+            Health::Fail => (console::style("✗").red().bold(), "FAIL"),
+        };
+        println!(
+            "  {icon} {} {label}",
+            console::style(&report.name).white().bold()
+        );
+        for note in &report.notes {
+            println!("      {note}");
+        }
+    }
+    println!();
+
+    let failed = reports.iter().filter(|r| r.health == Health::Fail).count();
+    if failed > 0 {
+        anyhow::bail!("{failed} skill(s) failed checks");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn skill(root: &Path, name: &str, manifest: &str) -> std::path::PathBuf {
+        let dir = skills_dir(root).join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("SKILL.toml"), manifest).unwrap();
+        dir
+    }
+
+    #[test]
+    fn reports_valid_and_malformed_skills() {
+        let tmp = TempDir::new().unwrap();
+        let good = skill(
+            tmp.path(),
+            "deploy",
+            r#"
+[skill]
+name = "deploy"
+description = "Ship it"
+
+[[tools]]
+name = "release"
+description = "Run the release script"
+kind = "script"
+command = "release.sh --prod"
+"#,
+        );
+        std::fs::write(good.join("release.sh"), "#!/bin/sh\necho ok\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(
+                good.join("release.sh"),
+                std::fs::Permissions::from_mode(0o755),
+            )
+            .unwrap();
+        }
+        skill(
+            tmp.path(),
+            "broken",
+            "[skill]\nname = \"broken\"\ndescription = \n",
+        );
+        std::fs::write(skills_dir(tmp.path()).join("README.md"), "# Skills").unwrap();
+
+        let reports = check_skills(tmp.path());
+        assert_eq!(reports.len(), 2, "README is not a skill");
+        assert_eq!(reports[0].name, "broken");
+        assert_eq!(reports[0].health, Health::Fail);
+        assert!(
+            reports[0].notes[0].starts_with("SKILL.toml is invalid (line 3)"),
+            "{:?}",
+            reports[0].notes
+        );
+        assert_eq!(reports[1].name, "deploy");
+        assert_eq!(reports[1].health, Health::Ok, "{:?}", reports[1].notes);
+    }
+
+    #[test]
+    fn flags_missing_entry_points_and_manifests() {
+        let tmp = TempDir::new().unwrap();
+        let dir = skill(
+            tmp.path(),
+            "tools",
+            r#"
+[skill]
+name = "tools"
+description = ""
+
+[[tools]]
+name = "run"
+description = "Missing script"
+kind = "script"
+command = "bin/run"
+
+[[tools]]
+name = "fetch"
+description = "Not a URL"
+kind = "http"
+command = "example.com/api"
+
+[[tools]]
+name = "odd"
+description = "Unknown kind"
+kind = "python"
+command = "main.py"
+"#,
+        );
+        std::fs::write(dir.join("SKILL.md"), "# Shadowed").unwrap();
+        std::fs::create_dir_all(skills_dir(tmp.path()).join("empty")).unwrap();
+
+        let reports = check_skills(tmp.path());
+        let empty = &reports[0];
+        assert_eq!(empty.name, "empty");
+        assert_eq!(empty.health, Health::Fail);
+        assert!(empty.notes[0].contains("no SKILL.toml or SKILL.md"));
+
+        let tools = &reports[1];
+        assert_eq!(tools.health, Health::Fail);
+        let notes = tools.notes.join("\n");
+        for expected in [
+            "description is empty",
+            "script bin/run not found",
+            "not an http(s) URL",
+            "unknown kind \"python\"",
+            "SKILL.md is ignored",
+        ] {
+            assert!(notes.contains(expected), "missing {expected:?} in {notes}");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn flags_non_executable_scripts_and_broken_links() {
+        let tmp = TempDir::new().unwrap();
+        let dir = skill(
+            tmp.path(),
+            "lint",
+            r#"
+[skill]
+name = "lint"
+description = "Lint things"
+
+[[tools]]
+name = "lint"
+description = "Run the linter"
+kind = "script"
+command = "lint.sh"
+"#,
+        );
+        std::fs::write(dir.join("lint.sh"), "#!/bin/sh\n").unwrap();
+        std::os::unix::fs::symlink(
+            tmp.path().join("gone"),
+            skills_dir(tmp.path()).join("linked"),
+        )
+        .unwrap();
+
+        let reports = check_skills(tmp.path());
+        assert_eq!(reports[0].name, "linked");
+        assert!(reports[0].notes[0].contains("broken link"));
+        assert_eq!(reports[1].health, Health::Fail);
+        assert!(reports[1].notes[0].contains("not executable"));
+    }
+}
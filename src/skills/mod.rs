@@ -6,6 +6,8 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::time::{Duration, SystemTime};
 
+pub mod doctor;
+
 const OPEN_SKILLS_REPO_URL: &str = "https://github.com/besoeasy/open-skills";
 const OPEN_SKILLS_SYNC_MARKER: &str = ".tinyclaw-open-skills-sync";
 const OPEN_SKILLS_SYNC_INTERVAL_SECS: u64 = 60 * 60 * 24 * 7;
@@ -108,8 +110,12 @@ fn load_skills_from_directory(skills_dir: &Path) -> Vec<Skill> {
         let md_path = path.join("SKILL.md");
 
         if manifest_path.exists() {
-            if let Ok(skill) = load_skill_toml(&manifest_path) {
-                skills.push(skill);
+            match load_skill_toml(&manifest_path) {
+                Ok(skill) => skills.push(skill),
+                Err(e) => tracing::warn!(
+                    "Skipping skill {}: {e} (run `tinyclaw skills doctor` for details)",
+                    path.display()
+                ),
             }
         } else if md_path.exists() {
             if let Ok(skill) = load_skill_md(&md_path, &path) {
@@ -614,6 +620,7 @@ pub fn handle_command(command: crate::SkillCommands, workspace_dir: &Path) -> Re
             );
             Ok(())
         }
+        crate::SkillCommands::Doctor => doctor::run(workspace_dir),
     }
 }
 
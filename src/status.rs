@@ -0,0 +1,240 @@
+//! `tinyclaw status`: the effective runtime settings, as a readable block or
+//! as JSON/TOML (`--format`) for monitoring scripts.
+
+use crate::config::Config;
+use crate::security::AutonomyLevel;
+use anyhow::Result;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Output format for `tinyclaw status --format`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StatusFormat {
+    #[default]
+    Text,
+    Json,
+    Toml,
+}
+
+impl std::str::FromStr for StatusFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "toml" => Ok(Self::Toml),
+            _ => Err(format!(
+                "invalid status format {s:?}: expected text, json or toml"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusReport {
+    pub version: String,
+    pub workspace: PathBuf,
+    pub config: PathBuf,
+    pub provider: String,
+    /// `None` when the provider's default model is used
+    pub model: Option<String>,
+    pub autonomy: AutonomyLevel,
+    pub runtime: String,
+    /// `tiny`, `standard` or `full`; absent for builds without a tier feature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_tier: Option<&'static str>,
+    pub observability: ObservabilityStatus,
+    pub memory: MemoryStatus,
+    pub heartbeat: HeartbeatStatus,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ObservabilityStatus {
+    pub backend: String,
+    pub log_level: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemoryStatus {
+    pub backend: String,
+    pub auto_save: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HeartbeatStatus {
+    pub enabled: bool,
+    pub interval_minutes: u32,
+}
+
+fn build_tier() -> Option<&'static str> {
+    if cfg!(feature = "full") {
+        Some("full")
+    } else if cfg!(feature = "standard") {
+        Some("standard")
+    } else if cfg!(feature = "tiny") {
+        Some("tiny")
+    } else {
+        None
+    }
+}
+
+impl StatusReport {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            workspace: config.workspace_dir.clone(),
+            config: config.config_path.clone(),
+            provider: config
+                .default_provider
+                .clone()
+                .unwrap_or_else(|| "openrouter".into()),
+            model: config.default_model.clone(),
+            autonomy: config.autonomy.level,
+            runtime: config.runtime.kind.clone(),
+            build_tier: build_tier(),
+            observability: ObservabilityStatus {
+                backend: config.observability.backend.clone(),
+                log_level: config.observability.log_level.clone(),
+            },
+            memory: MemoryStatus {
+                backend: config.memory.backend.clone(),
+                auto_save: config.memory.auto_save,
+            },
+            heartbeat: HeartbeatStatus {
+                enabled: config.heartbeat.enabled,
+                interval_minutes: config.heartbeat.interval_minutes,
+            },
+        }
+    }
+
+    /// The human-readable block printed by default.
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![
+            "TinyClaw Status".to_string(),
+            String::new(),
+            format!("Version:     {}", self.version),
+            format!("Workspace:   {}", self.workspace.display()),
+            format!("Config:      {}", self.config.display()),
+            String::new(),
+            format!("Provider:      {}", self.provider),
+            format!(
+                "Model:         {}",
+                self.model.as_deref().unwrap_or("(default)")
+            ),
+            format!("Observability: {}", self.observability.backend),
+            format!("Autonomy:      {:?}", self.autonomy),
+            format!("Runtime:       {}", self.runtime),
+            format!(
+                "Heartbeat:     {}",
+                if self.heartbeat.enabled {
+                    format!("every {}min", self.heartbeat.interval_minutes)
+                } else {
+                    "disabled".into()
+                }
+            ),
+            format!(
+                "Memory:        {} (auto-save: {})",
+                self.memory.backend,
+                if self.memory.auto_save { "on" } else { "off" }
+            ),
+        ];
+        if let Some(tier) = self.build_tier {
+            lines.push(format!("Build tier:    {tier}"));
+        }
+        lines.join("\n")
+    }
+
+    pub fn render(&self, format: StatusFormat) -> Result<String> {
+        Ok(match format {
+            StatusFormat::Text => self.to_text(),
+            StatusFormat::Json => serde_json::to_string_pretty(self)?,
+            StatusFormat::Toml => toml::to_string_pretty(self)?,
+        })
+    }
+}
+
+/// Print the status of `config` in `format`.
+pub fn run(config: &Config, format: StatusFormat) -> Result<()> {
+    let output = StatusReport::from_config(config).render(format)?;
+    println!("{}", output.trim_end());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        Config {
+            workspace_dir: PathBuf::from("/tmp/ws"),
+            config_path: PathBuf::from("/tmp/config.toml"),
+            default_model: Some("gpt-4o".into()),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn json_contains_expected_keys() {
+        let json = StatusReport::from_config(&config())
+            .render(StatusFormat::Json)
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        for key in [
+            "version",
+            "workspace",
+            "config",
+            "provider",
+            "model",
+            "autonomy",
+            "runtime",
+            "observability",
+            "memory",
+            "heartbeat",
+        ] {
+            assert!(value.get(key).is_some(), "missing {key} in {json}");
+        }
+        assert_eq!(value["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(value["workspace"], "/tmp/ws");
+        assert_eq!(value["provider"], "openrouter");
+        assert_eq!(value["model"], "gpt-4o");
+        assert_eq!(value["autonomy"], "supervised");
+        assert_eq!(value["observability"]["backend"], "none");
+        assert_eq!(value["memory"]["auto_save"], config().memory.auto_save);
+        assert_eq!(value["heartbeat"]["enabled"], false);
+        assert_eq!(value["heartbeat"]["interval_minutes"], 30);
+        assert_eq!(
+            value.get("build_tier").and_then(|v| v.as_str()),
+            build_tier()
+        );
+    }
+
+    #[test]
+    fn toml_output_parses() {
+        let mut config = config();
+        config.default_model = None;
+        let out = StatusReport::from_config(&config)
+            .render(StatusFormat::Toml)
+            .unwrap();
+        let value: toml::Value = toml::from_str(&out).unwrap();
+        assert_eq!(value["provider"].as_str(), Some("openrouter"));
+        assert!(value.get("model").is_none());
+        assert_eq!(
+            value["heartbeat"]["interval_minutes"].as_integer(),
+            Some(30)
+        );
+    }
+
+    #[test]
+    fn text_is_the_default() {
+        assert_eq!("JSON".parse::<StatusFormat>().unwrap(), StatusFormat::Json);
+        assert!("yaml".parse::<StatusFormat>().is_err());
+
+        let text = StatusReport::from_config(&config())
+            .render(StatusFormat::default())
+            .unwrap();
+        assert!(text.starts_with("TinyClaw Status"));
+        assert!(text.contains("Model:         gpt-4o"));
+        assert!(text.contains("Heartbeat:     disabled"));
+    }
+}
@@ -0,0 +1,175 @@
+use super::traits::{Tool, ToolResult};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Reuses results of repeated read-only tool calls within a session.
+///
+/// Entries are keyed by tool name and the call's arguments serialized to
+/// JSON (object keys come out sorted, so argument order doesn't matter).
+/// Only tools whose [`Tool::cache_ttl`] returns `Some` are cached, and only
+/// successful results. Running any other tool empties the cache, since a
+/// shell command or file write may change what an earlier read saw.
+#[derive(Default)]
+pub struct ToolResultCache {
+    entries: Mutex<HashMap<(String, String), (Instant, ToolResult)>>,
+}
+
+impl ToolResultCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop every cached result.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Validate and run `tool`, or return the result of an identical earlier
+    /// call that is still within the tool's TTL.
+    pub async fn execute(
+        &self,
+        tool: &dyn Tool,
+        args: serde_json::Value,
+    ) -> anyhow::Result<ToolResult> {
+        let Some(ttl) = tool.cache_ttl(&args) else {
+            let result = tool.execute_validated(args).await;
+            self.clear();
+            return result;
+        };
+
+        let key = (tool.name().to_string(), args.to_string());
+        if let Some((stored, result)) = self.lock().get(&key) {
+            if stored.elapsed() <= ttl {
+                tracing::debug!(tool = tool.name(), "Tool result served from cache");
+                return Ok(result.clone());
+            }
+        }
+
+        let result = tool.execute_validated(args).await?;
+        if result.success {
+            self.lock().insert(key, (Instant::now(), result.clone()));
+        }
+        Ok(result)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<(String, String), (Instant, ToolResult)>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.lock().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::NativeRuntime;
+    use crate::security::{AutonomyLevel, SecurityPolicy};
+    use crate::tools::{FileReadTool, ShellTool, WebFetchTool};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn policy(dir: &std::path::Path) -> Arc<SecurityPolicy> {
+        Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Full,
+            workspace_dir: dir.to_path_buf(),
+            ..SecurityPolicy::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn repeated_file_read_is_served_from_cache() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let file = tmp.path().join("notes.txt");
+        std::fs::write(&file, "first").unwrap();
+        let tool = FileReadTool::new(policy(tmp.path()));
+        let cache = ToolResultCache::new();
+
+        let first = cache
+            .execute(&tool, json!({"path": "notes.txt"}))
+            .await
+            .unwrap();
+        assert!(first.output.contains("first"));
+
+        // Changed behind the agent's back: an uncached read would see it
+        std::fs::write(&file, "second").unwrap();
+        let again = cache
+            .execute(&tool, json!({"path": "notes.txt"}))
+            .await
+            .unwrap();
+        assert_eq!(again.output, first.output);
+        assert_eq!(cache.len(), 1);
+
+        // Different arguments are a different entry
+        let base64 = cache
+            .execute(&tool, json!({"path": "notes.txt", "encoding": "base64"}))
+            .await
+            .unwrap();
+        assert_ne!(base64.output, first.output);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn failures_are_not_cached() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let tool = FileReadTool::new(policy(tmp.path()));
+        let cache = ToolResultCache::new();
+
+        let missing = cache.execute(&tool, json!({"path": "later.txt"})).await;
+        assert!(missing.map_or(true, |r| !r.success));
+        assert_eq!(cache.len(), 0);
+
+        std::fs::write(tmp.path().join("later.txt"), "now here").unwrap();
+        let found = cache
+            .execute(&tool, json!({"path": "later.txt"}))
+            .await
+            .unwrap();
+        assert!(found.output.contains("now here"));
+    }
+
+    #[tokio::test]
+    async fn side_effecting_tools_are_never_cached_and_clear_the_cache() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("notes.txt"), "first").unwrap();
+        let security = policy(tmp.path());
+        let read = FileReadTool::new(security.clone());
+        let shell = ShellTool::new(security, Arc::new(NativeRuntime::new()));
+        let cache = ToolResultCache::new();
+
+        cache
+            .execute(&read, json!({"path": "notes.txt"}))
+            .await
+            .unwrap();
+        assert_eq!(cache.len(), 1);
+
+        let out = cache
+            .execute(&shell, json!({"command": "echo hi"}))
+            .await
+            .unwrap();
+        assert!(out.success, "{out:?}");
+        assert_eq!(cache.len(), 0);
+
+        std::fs::write(tmp.path().join("notes.txt"), "second").unwrap();
+        let reread = cache
+            .execute(&read, json!({"path": "notes.txt"}))
+            .await
+            .unwrap();
+        assert!(reread.output.contains("second"));
+    }
+
+    #[test]
+    fn web_fetch_downloads_are_not_cacheable() {
+        let tool = WebFetchTool::new(policy(std::path::Path::new(".")));
+        assert!(tool
+            .cache_ttl(&json!({"url": "https://example.com"}))
+            .is_some());
+        assert!(tool
+            .cache_ttl(&json!({"url": "https://example.com", "save_to": "page.html"}))
+            .is_none());
+    }
+}
@@ -0,0 +1,470 @@
+use super::exec_support::{self, failure};
+use super::traits::{Tool, ToolResult};
+use crate::config::CheckConfig;
+use crate::security::{AutonomyLevel, SecurityPolicy};
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Maximum checker execution time before kill; a cold `cargo check` is slow.
+const CHECK_TIMEOUT_SECS: u64 = 300;
+/// Diagnostics beyond this many bytes are cut off.
+const MAX_OUTPUT_BYTES: usize = 32_768;
+/// Toolchain variables passed through to the checker on top of the
+/// [`exec_support`] allowlist.
+const TOOLCHAIN_ENV_VARS: &[&str] = &["CARGO_HOME", "RUSTUP_HOME", "RUSTUP_TOOLCHAIN"];
+/// Progress lines checkers print around their diagnostics.
+const PROGRESS_PREFIXES: &[&str] = &[
+    "Compiling ",
+    "Checking ",
+    "Finished ",
+    "Blocking ",
+    "Updating ",
+    "Locking ",
+    "Downloading ",
+    "Downloaded ",
+];
+
+/// Compiles each `*.py` file under the directory in memory and prints one
+/// `file:line:col: message` per syntax error. `py_compile` would write
+/// `.pyc` files next to the sources.
+const PYTHON_CHECK: &str = r#"
+import os, sys
+failed = 0
+for root, dirs, files in os.walk(sys.argv[1]):
+    dirs[:] = sorted(d for d in dirs if not d.startswith(".") and d not in ("node_modules", "__pycache__", "venv"))
+    for name in sorted(files):
+        if name.endswith(".py"):
+            path = os.path.relpath(os.path.join(root, name), sys.argv[1])
+            try:
+                with open(os.path.join(root, name), "rb") as f:
+                    compile(f.read(), path, "exec")
+            except (SyntaxError, ValueError) as e:
+                failed = 1
+                print(f"{path}:{getattr(e, 'lineno', 0)}:{getattr(e, 'offset', 0)}: {e.__class__.__name__}: {getattr(e, 'msg', e)}")
+sys.exit(failed)
+"#;
+
+/// A supported type-checker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Language {
+    Rust,
+    TypeScript,
+    Python,
+}
+
+impl Language {
+    fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "rust" => Some(Self::Rust),
+            "typescript" | "ts" => Some(Self::TypeScript),
+            "python" | "py" => Some(Self::Python),
+            _ => None,
+        }
+    }
+
+    /// Pick the checker from the project's marker files.
+    fn detect(dir: &Path) -> Option<Self> {
+        if dir.join("Cargo.toml").is_file() {
+            return Some(Self::Rust);
+        }
+        if dir.join("tsconfig.json").is_file() {
+            return Some(Self::TypeScript);
+        }
+        let has_python = ["pyproject.toml", "setup.py", "setup.cfg"]
+            .iter()
+            .any(|marker| dir.join(marker).is_file())
+            || std::fs::read_dir(dir).is_ok_and(|entries| {
+                entries
+                    .flatten()
+                    .any(|e| e.path().extension().is_some_and(|ext| ext == "py"))
+            });
+        has_python.then_some(Self::Python)
+    }
+
+    /// Program and arguments of the check-only run. The program is looked
+    /// up on `PATH`; a project-local compiler such as
+    /// `node_modules/.bin/tsc` only runs when set as `[check] command`.
+    fn command(self) -> Vec<String> {
+        match self {
+            Self::Rust => vec![
+                "cargo".into(),
+                "check".into(),
+                "--quiet".into(),
+                "--message-format".into(),
+                "short".into(),
+            ],
+            Self::TypeScript => vec![
+                "tsc".into(),
+                "--noEmit".into(),
+                "--pretty".into(),
+                "false".into(),
+            ],
+            Self::Python => vec![
+                "python3".into(),
+                "-c".into(),
+                PYTHON_CHECK.into(),
+                ".".into(),
+            ],
+        }
+    }
+}
+
+/// Runs the project's compiler or type-checker in check-only mode
+/// (`cargo check`, `tsc --noEmit`, a Python syntax check) and returns the
+/// diagnostics, so the agent can validate edits without a shell.
+pub struct CheckTool {
+    security: Arc<SecurityPolicy>,
+    config: CheckConfig,
+}
+
+impl CheckTool {
+    pub fn new(security: Arc<SecurityPolicy>, config: CheckConfig) -> Self {
+        Self { security, config }
+    }
+
+    /// The workspace subdirectory to check, after symlinks are resolved.
+    async fn project_dir(&self, path: &str) -> Result<PathBuf, String> {
+        if !self.security.is_path_allowed(path) {
+            return Err(format!("Path not allowed by security policy: {path}"));
+        }
+        let resolved = tokio::fs::canonicalize(self.security.workspace_dir.join(path))
+            .await
+            .map_err(|e| format!("Failed to resolve project path: {e}"))?;
+        if !self.security.is_resolved_path_allowed(&resolved) {
+            return Err(format!(
+                "Resolved path escapes workspace: {}",
+                resolved.display()
+            ));
+        }
+        if !resolved.is_dir() {
+            return Err(format!("Not a directory: {path}"));
+        }
+        Ok(resolved)
+    }
+
+    /// `[check] command`, else the checker for the requested, configured
+    /// or detected language.
+    fn command(&self, dir: &Path, language: Option<&str>) -> Result<Vec<String>, String> {
+        if language.is_none() && !self.config.command.is_empty() {
+            return Ok(self.config.command.clone());
+        }
+        let language = match language.or(self.config.language.as_deref()) {
+            Some(name) => Language::parse(name).ok_or_else(|| {
+                format!("Unknown language: {name} (expected rust, typescript or python)")
+            })?,
+            None => Language::detect(dir).ok_or(
+                "No Cargo.toml, tsconfig.json or Python files found; \
+                 pass 'language' or set [check] language",
+            )?,
+        };
+        Ok(language.command())
+    }
+}
+
+/// Combined output without progress lines, capped at `MAX_OUTPUT_BYTES`.
+fn diagnostics(stdout: &[u8], stderr: &[u8]) -> String {
+    let stdout = String::from_utf8_lossy(stdout);
+    let stderr = String::from_utf8_lossy(stderr);
+    let mut text = stdout
+        .lines()
+        .chain(stderr.lines())
+        .filter(|line| {
+            let line = line.trim_start();
+            !PROGRESS_PREFIXES.iter().any(|p| line.starts_with(p))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if text.len() > MAX_OUTPUT_BYTES {
+        let omitted = text.len() - text.floor_char_boundary(MAX_OUTPUT_BYTES);
+        text.truncate(text.floor_char_boundary(MAX_OUTPUT_BYTES));
+        let _ = write!(
+            text,
+            "\n... [{omitted} more bytes of diagnostics truncated]"
+        );
+    }
+    text
+}
+
+#[async_trait]
+impl Tool for CheckTool {
+    fn name(&self) -> &str {
+        "check"
+    }
+
+    fn description(&self) -> &str {
+        "Type-check or compile the project without building it (cargo check, tsc --noEmit, Python syntax check) and return only the diagnostics"
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Project directory relative to the workspace (default: the workspace root)"
+                },
+                "language": {
+                    "type": "string",
+                    "enum": ["rust", "typescript", "python"],
+                    "description": "Checker to run (default: detected from Cargo.toml, tsconfig.json or *.py files)"
+                }
+            }
+        })
+    }
+
+    /// The first diagnostic, with its location
+    fn preview_len(&self) -> Option<usize> {
+        Some(300)
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let path = args.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+        let language = args.get("language").and_then(|v| v.as_str());
+
+        // Build scripts and proc macros run during `cargo check`
+        if self.security.autonomy == AutonomyLevel::ReadOnly {
+            return Ok(failure(
+                "check is not allowed in read-only mode (checkers can run build scripts)",
+            ));
+        }
+
+        let dir = match self.project_dir(path).await {
+            Ok(dir) => dir,
+            Err(reason) => return Ok(failure(reason)),
+        };
+        let command = match self.command(&dir, language) {
+            Ok(command) => command,
+            Err(reason) => return Ok(failure(reason)),
+        };
+        let Some((program, rest)) = command.split_first() else {
+            return Ok(failure("[check] command is empty"));
+        };
+        if !self.security.is_command_allowed(program) {
+            return Ok(failure(format!(
+                "Command not allowed by security policy: {program} \
+                 (add it to autonomy.allowed_commands)"
+            )));
+        }
+
+        if let Err(refused) = exec_support::record_action(&self.security) {
+            return Ok(refused);
+        }
+
+        let mut cmd = exec_support::command(program, &dir, TOOLCHAIN_ENV_VARS);
+        cmd.args(rest)
+            .env("CARGO_TERM_COLOR", "never")
+            .env("NO_COLOR", "1");
+
+        let result =
+            tokio::time::timeout(Duration::from_secs(CHECK_TIMEOUT_SECS), cmd.output()).await;
+
+        match result {
+            Ok(Ok(output)) => {
+                let found = diagnostics(&output.stdout, &output.stderr);
+                if output.status.success() {
+                    Ok(ToolResult {
+                        success: true,
+                        output: if found.is_empty() {
+                            format!("{program}: no problems found")
+                        } else {
+                            found
+                        },
+                        error: None,
+                    })
+                } else {
+                    Ok(failure(if found.is_empty() {
+                        format!("{program} failed with {}", output.status)
+                    } else {
+                        found
+                    }))
+                }
+            }
+            Ok(Err(e)) => Ok(failure(format!("Failed to run {program}: {e}"))),
+            Err(_) => Ok(failure(format!(
+                "{program} timed out after {CHECK_TIMEOUT_SECS}s and was killed"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn tool(dir: &Path, autonomy: AutonomyLevel, config: CheckConfig) -> CheckTool {
+        let security = Arc::new(SecurityPolicy {
+            autonomy,
+            workspace_dir: dir.to_path_buf(),
+            ..SecurityPolicy::default()
+        });
+        CheckTool::new(security, config)
+    }
+
+    fn rust_project(dir: &Path, main: &str) {
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"scratch\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("src").join("main.rs"), main).unwrap();
+    }
+
+    #[tokio::test]
+    async fn cargo_check_reports_the_type_error() {
+        let tmp = TempDir::new().unwrap();
+        let project = tmp.path().join("scratch");
+        rust_project(
+            &project,
+            "fn main() {\n    let n: u32 = \"four\";\n    println!(\"{n}\");\n}\n",
+        );
+        let check = tool(
+            tmp.path(),
+            AutonomyLevel::Supervised,
+            CheckConfig::default(),
+        );
+
+        let result = check.execute(json!({"path": "scratch"})).await.unwrap();
+
+        assert!(!result.success);
+        let error = result.error.unwrap();
+        assert!(error.contains("src/main.rs:2"), "{error}");
+        assert!(error.contains("mismatched types"), "{error}");
+        assert!(!error.contains("Checking scratch"), "{error}");
+
+        std::fs::write(
+            project.join("src").join("main.rs"),
+            "fn main() {\n    let n: u32 = 4;\n    println!(\"{n}\");\n}\n",
+        )
+        .unwrap();
+        let result = check.execute(json!({"path": "scratch"})).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+    }
+
+    #[tokio::test]
+    async fn python_check_reports_syntax_errors_without_writing_bytecode() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join("pkg")).unwrap();
+        std::fs::write(tmp.path().join("ok.py"), "print('fine')\n").unwrap();
+        std::fs::write(tmp.path().join("pkg").join("bad.py"), "def f(:\n    pass\n").unwrap();
+        let security = SecurityPolicy {
+            workspace_dir: tmp.path().to_path_buf(),
+            allowed_commands: vec!["python3".into()],
+            ..SecurityPolicy::default()
+        };
+        let check = CheckTool::new(Arc::new(security), CheckConfig::default());
+
+        let result = check.execute(json!({})).await.unwrap();
+
+        assert!(!result.success);
+        let error = result.error.unwrap();
+        assert!(error.starts_with("pkg/bad.py:1:"), "{error}");
+        assert!(!error.contains("ok.py"), "{error}");
+        assert!(!tmp.path().join("pkg").join("__pycache__").exists());
+    }
+
+    #[test]
+    fn language_is_detected_from_marker_files() {
+        let tmp = TempDir::new().unwrap();
+        assert_eq!(Language::detect(tmp.path()), None);
+        std::fs::write(tmp.path().join("app.py"), "print(1)\n").unwrap();
+        assert_eq!(Language::detect(tmp.path()), Some(Language::Python));
+        std::fs::write(tmp.path().join("tsconfig.json"), "{}").unwrap();
+        assert_eq!(Language::detect(tmp.path()), Some(Language::TypeScript));
+        std::fs::write(tmp.path().join("Cargo.toml"), "").unwrap();
+        assert_eq!(Language::detect(tmp.path()), Some(Language::Rust));
+    }
+
+    #[test]
+    fn configured_command_and_language_win_over_detection() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("Cargo.toml"), "").unwrap();
+        let custom = tool(
+            tmp.path(),
+            AutonomyLevel::Supervised,
+            CheckConfig {
+                command: vec!["cargo".into(), "clippy".into()],
+                ..CheckConfig::default()
+            },
+        );
+        assert_eq!(
+            custom.command(tmp.path(), None).unwrap(),
+            ["cargo", "clippy"]
+        );
+        assert_eq!(
+            custom.command(tmp.path(), Some("python")).unwrap()[0],
+            "python3"
+        );
+
+        let configured = tool(
+            tmp.path(),
+            AutonomyLevel::Supervised,
+            CheckConfig {
+                language: Some("typescript".into()),
+                ..CheckConfig::default()
+            },
+        );
+        assert_eq!(configured.command(tmp.path(), None).unwrap()[0], "tsc");
+        assert!(configured
+            .command(tmp.path(), Some("cobol"))
+            .unwrap_err()
+            .contains("Unknown language"));
+    }
+
+    #[tokio::test]
+    async fn refused_outside_workspace_and_in_read_only_mode() {
+        let tmp = TempDir::new().unwrap();
+        let check = tool(
+            tmp.path(),
+            AutonomyLevel::Supervised,
+            CheckConfig::default(),
+        );
+        let result = check.execute(json!({"path": "/etc"})).await.unwrap();
+        assert!(result.error.unwrap().contains("not allowed"));
+
+        let read_only = tool(tmp.path(), AutonomyLevel::ReadOnly, CheckConfig::default());
+        let result = read_only.execute(json!({})).await.unwrap();
+        assert!(result.error.unwrap().contains("read-only"));
+    }
+
+    #[tokio::test]
+    async fn checkers_outside_the_command_allowlist_are_refused() {
+        let tmp = TempDir::new().unwrap();
+        let bin = tmp.path().join("node_modules").join(".bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        std::fs::write(bin.join("tsc"), "#!/bin/sh\ntouch ran\n").unwrap();
+        std::fs::write(tmp.path().join("tsconfig.json"), "{}").unwrap();
+        let check = tool(
+            tmp.path(),
+            AutonomyLevel::Supervised,
+            CheckConfig::default(),
+        );
+
+        let result = check.execute(json!({})).await.unwrap();
+        let error = result.error.unwrap();
+        assert!(
+            error.contains("not allowed by security policy: tsc"),
+            "{error}"
+        );
+        assert!(!tmp.path().join("ran").exists());
+        assert_eq!(check.command(tmp.path(), None).unwrap()[0], "tsc");
+    }
+
+    #[test]
+    fn diagnostics_drop_progress_lines_and_are_capped() {
+        let text = diagnostics(
+            b"",
+            b"   Checking scratch v0.1.0\nsrc/main.rs:2:18: error[E0308]: mismatched types\n",
+        );
+        assert_eq!(text, "src/main.rs:2:18: error[E0308]: mismatched types");
+
+        let long = "x".repeat(MAX_OUTPUT_BYTES + 10);
+        let text = diagnostics(long.as_bytes(), b"");
+        assert!(text.ends_with("[10 more bytes of diagnostics truncated]"));
+    }
+}
@@ -0,0 +1,79 @@
+//! Shared plumbing for tools that run a program directly instead of through
+//! a shell (`git`, `check`): failure results, the rate limit, and a child
+//! environment without secrets.
+
+use super::traits::ToolResult;
+use crate::security::SecurityPolicy;
+use std::path::Path;
+use tokio::process::Command;
+
+/// Environment variables passed through to the program. Never API keys or secrets.
+const SAFE_ENV_VARS: &[&str] = &[
+    "PATH", "HOME", "LANG", "LC_ALL", "LC_CTYPE", "USER", "TMPDIR",
+];
+
+/// A failed run with only an error message.
+pub fn failure(error: impl Into<String>) -> ToolResult {
+    ToolResult {
+        success: false,
+        output: String::new(),
+        error: Some(error.into()),
+    }
+}
+
+/// Count one action against the rate limit; `Err` is the result to return
+/// when the limit or the action budget is used up.
+pub fn record_action(security: &SecurityPolicy) -> Result<(), ToolResult> {
+    if security.is_rate_limited() {
+        return Err(failure(
+            "Rate limit exceeded: too many actions in the last hour",
+        ));
+    }
+    if !security.record_action() {
+        return Err(failure("Rate limit exceeded: action budget exhausted"));
+    }
+    Ok(())
+}
+
+/// `program` in `dir` with a cleared environment, passing through only
+/// [`SAFE_ENV_VARS`] and `extra_vars`. The child is killed if the command
+/// is dropped, e.g. on timeout.
+pub fn command(program: &str, dir: &Path, extra_vars: &[&str]) -> Command {
+    let mut cmd = Command::new(program);
+    cmd.current_dir(dir).env_clear().kill_on_drop(true);
+    for var in SAFE_ENV_VARS.iter().chain(extra_vars) {
+        if let Ok(val) = std::env::var(var) {
+            cmd.env(var, val);
+        }
+    }
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_passes_only_allowlisted_variables() {
+        let cmd = command("true", Path::new("."), &["CARGO_HOME"]);
+        for (key, _) in cmd.as_std().get_envs() {
+            let key = key.to_string_lossy();
+            assert!(
+                SAFE_ENV_VARS.contains(&key.as_ref()) || key == "CARGO_HOME",
+                "{key} leaked into the child environment"
+            );
+        }
+    }
+
+    #[test]
+    fn record_action_refuses_once_the_budget_is_spent() {
+        let security = SecurityPolicy {
+            max_actions_per_hour: 1,
+            ..SecurityPolicy::default()
+        };
+        assert!(record_action(&security).is_ok());
+        let refused = record_action(&security).unwrap_err();
+        assert!(!refused.success);
+        assert!(refused.error.unwrap().contains("Rate limit exceeded"));
+    }
+}
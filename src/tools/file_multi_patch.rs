@@ -0,0 +1,319 @@
+use super::file_patch::{replace_once, resolve};
+use super::traits::{ProposedEdit, Tool, ToolResult};
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Applies several `file_patch`-style edits as one change: either every
+/// edit lands or no file is modified.
+pub struct FileMultiPatchTool {
+    security: Arc<SecurityPolicy>,
+}
+
+#[derive(Deserialize)]
+struct Edit {
+    path: String,
+    old_string: String,
+    new_string: String,
+}
+
+/// One file's contents before and after all of its edits.
+struct FileChange {
+    path: String,
+    resolved: PathBuf,
+    before: String,
+    after: String,
+}
+
+impl FileMultiPatchTool {
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self { security }
+    }
+
+    /// Resolve and apply every edit in memory, in order. Edits to the same
+    /// file build on each other. Nothing is written.
+    async fn plan(&self, args: &serde_json::Value) -> Result<Vec<FileChange>, String> {
+        let edits: Vec<Edit> = args
+            .get("edits")
+            .cloned()
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| format!("Invalid 'edits' parameter: {e}"))?
+            .ok_or("Missing 'edits' parameter")?;
+        if edits.is_empty() {
+            return Err("No edits given".into());
+        }
+
+        let mut changes: Vec<FileChange> = Vec::new();
+        for (i, edit) in edits.iter().enumerate() {
+            let fail = |e: String| format!("Edit {} ({}): {e}", i + 1, edit.path);
+            let resolved = resolve(&self.security, &edit.path).await.map_err(fail)?;
+            let index = if let Some(index) = changes.iter().position(|c| c.resolved == resolved) {
+                index
+            } else {
+                let before = tokio::fs::read_to_string(&resolved)
+                    .await
+                    .map_err(|e| fail(format!("Failed to read file: {e}")))?;
+                changes.push(FileChange {
+                    path: edit.path.clone(),
+                    resolved,
+                    after: before.clone(),
+                    before,
+                });
+                changes.len() - 1
+            };
+            let change = &mut changes[index];
+            change.after =
+                replace_once(&change.after, &edit.old_string, &edit.new_string).map_err(fail)?;
+        }
+        Ok(changes)
+    }
+}
+
+/// Write every change, restoring the original contents of each file already
+/// touched if any write fails.
+async fn write_all(changes: &[FileChange]) -> Result<(), String> {
+    for (i, change) in changes.iter().enumerate() {
+        if let Err(e) = tokio::fs::write(&change.resolved, &change.after).await {
+            // The failed write may have truncated its file, so restore it too
+            for done in changes[..=i].iter().rev() {
+                if let Err(restore) = tokio::fs::write(&done.resolved, &done.before).await {
+                    tracing::warn!(path = done.path, "Failed to restore file: {restore}");
+                }
+            }
+            return Err(format!(
+                "Failed to write {}: {e}. Edits were rolled back.",
+                change.path
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl Tool for FileMultiPatchTool {
+    fn name(&self) -> &str {
+        "file_multi_patch"
+    }
+
+    fn description(&self) -> &str {
+        "Apply exact-match replacements across several files at once; if any edit fails, no file is changed"
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "edits": {
+                    "type": "array",
+                    "description": "Edits applied in order; later edits to the same file see earlier ones",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "path": {
+                                "type": "string",
+                                "description": "Relative path to the file within the workspace"
+                            },
+                            "old_string": {
+                                "type": "string",
+                                "description": "Exact text to find and replace (must match exactly once)"
+                            },
+                            "new_string": {
+                                "type": "string",
+                                "description": "Replacement text"
+                            }
+                        },
+                        "required": ["path", "old_string", "new_string"]
+                    }
+                }
+            },
+            "required": ["edits"]
+        })
+    }
+
+    fn parallel_safe(&self) -> bool {
+        false
+    }
+
+    async fn proposed_edits(&self, args: &serde_json::Value) -> Vec<ProposedEdit> {
+        self.plan(args)
+            .await
+            .map(|changes| {
+                changes
+                    .into_iter()
+                    .map(|c| ProposedEdit {
+                        path: c.path,
+                        before: c.before,
+                        after: c.after,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let changes = match self.plan(&args).await {
+            Ok(changes) => changes,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("{e}. No files were changed.")),
+                });
+            }
+        };
+
+        if let Err(e) = write_all(&changes).await {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(e),
+            });
+        }
+
+        let mut output = format!("Patched {} files:", changes.len());
+        for change in &changes {
+            let _ = write!(output, "\n  {} ({} bytes)", change.path, change.after.len());
+        }
+        Ok(ToolResult {
+            success: true,
+            output,
+            error: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+    use tempfile::TempDir;
+
+    fn tool(workspace: &std::path::Path) -> FileMultiPatchTool {
+        FileMultiPatchTool::new(Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Supervised,
+            workspace_dir: workspace.to_path_buf(),
+            ..SecurityPolicy::default()
+        }))
+    }
+
+    fn read(dir: &TempDir, name: &str) -> String {
+        std::fs::read_to_string(dir.path().join(name)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn applies_all_edits_and_reports_each_file() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "fn old() {}\nold();\n").unwrap();
+        std::fs::write(dir.path().join("b.rs"), "use a::old;\n").unwrap();
+
+        let result = tool(dir.path())
+            .execute(json!({"edits": [
+                {"path": "a.rs", "old_string": "fn old()", "new_string": "fn new()"},
+                {"path": "b.rs", "old_string": "a::old", "new_string": "a::new"},
+                {"path": "a.rs", "old_string": "old();", "new_string": "new();"},
+            ]}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(
+            result.output,
+            "Patched 2 files:\n  a.rs (19 bytes)\n  b.rs (12 bytes)"
+        );
+        assert_eq!(read(&dir, "a.rs"), "fn new() {}\nnew();\n");
+        assert_eq!(read(&dir, "b.rs"), "use a::new;\n");
+    }
+
+    #[tokio::test]
+    async fn failing_second_edit_leaves_first_file_untouched() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "alpha").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "beta").unwrap();
+
+        let result = tool(dir.path())
+            .execute(json!({"edits": [
+                {"path": "a.txt", "old_string": "alpha", "new_string": "ALPHA"},
+                {"path": "b.txt", "old_string": "gamma", "new_string": "GAMMA"},
+            ]}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        let error = result.error.unwrap();
+        assert!(error.contains("Edit 2 (b.txt)"), "{error}");
+        assert!(error.contains("No files were changed"), "{error}");
+        assert_eq!(read(&dir, "a.txt"), "alpha");
+        assert_eq!(read(&dir, "b.txt"), "beta");
+    }
+
+    #[tokio::test]
+    async fn failed_write_rolls_back_earlier_files() {
+        let dir = TempDir::new().unwrap();
+        let first = dir.path().join("a.txt");
+        std::fs::write(&first, "alpha").unwrap();
+        let changes = vec![
+            FileChange {
+                path: "a.txt".into(),
+                resolved: first.clone(),
+                before: "alpha".into(),
+                after: "ALPHA".into(),
+            },
+            FileChange {
+                path: "gone/b.txt".into(),
+                resolved: dir.path().join("gone/b.txt"),
+                before: "beta".into(),
+                after: "BETA".into(),
+            },
+        ];
+
+        let error = write_all(&changes).await.unwrap_err();
+        assert!(error.contains("gone/b.txt"), "{error}");
+        assert!(error.contains("rolled back"), "{error}");
+        assert_eq!(std::fs::read_to_string(&first).unwrap(), "alpha");
+    }
+
+    #[tokio::test]
+    async fn rejects_paths_outside_workspace() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "alpha").unwrap();
+
+        let result = tool(dir.path())
+            .execute(json!({"edits": [
+                {"path": "a.txt", "old_string": "alpha", "new_string": "ALPHA"},
+                {"path": "../escape.txt", "old_string": "x", "new_string": "y"},
+            ]}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(read(&dir, "a.txt"), "alpha");
+    }
+
+    #[tokio::test]
+    async fn proposes_one_edit_per_file() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "alpha").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "beta").unwrap();
+        let tool = tool(dir.path());
+
+        let edits = tool
+            .proposed_edits(&json!({"edits": [
+                {"path": "a.txt", "old_string": "alpha", "new_string": "ALPHA"},
+                {"path": "b.txt", "old_string": "beta", "new_string": "BETA"},
+            ]}))
+            .await;
+        let summary: Vec<(&str, &str, &str)> = edits
+            .iter()
+            .map(|e| (e.path.as_str(), e.before.as_str(), e.after.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("a.txt", "alpha", "ALPHA"), ("b.txt", "beta", "BETA")]
+        );
+        assert_eq!(read(&dir, "a.txt"), "alpha");
+
+        assert!(tool.proposed_edits(&json!({"edits": []})).await.is_empty());
+    }
+}
@@ -1,7 +1,8 @@
-use super::traits::{Tool, ToolResult};
+use super::traits::{ProposedEdit, Tool, ToolResult};
 use crate::security::SecurityPolicy;
 use async_trait::async_trait;
 use serde_json::json;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 /// Targeted file editing via old_string/new_string replacement
@@ -13,6 +14,64 @@ impl FilePatchTool {
     pub fn new(security: Arc<SecurityPolicy>) -> Self {
         Self { security }
     }
+
+    /// Resolve `path` and apply the replacement in memory. Returns the
+    /// resolved path with the file's current and patched contents.
+    async fn patch(
+        &self,
+        path: &str,
+        old_string: &str,
+        new_string: &str,
+    ) -> Result<(PathBuf, String, String), String> {
+        let resolved = resolve(&self.security, path).await?;
+        let content = tokio::fs::read_to_string(&resolved)
+            .await
+            .map_err(|e| format!("Failed to read file: {e}"))?;
+        let new_content = replace_once(&content, old_string, new_string)?;
+        Ok((resolved, content, new_content))
+    }
+}
+
+/// Resolve an existing workspace file, rejecting paths the policy forbids
+/// and symlinks that lead out of the workspace.
+pub(super) async fn resolve(security: &SecurityPolicy, path: &str) -> Result<PathBuf, String> {
+    if !security.is_path_allowed(path) {
+        return Err(format!("Path not allowed by security policy: {path}"));
+    }
+
+    let full_path = security.workspace_dir.join(path);
+
+    // Resolve to block symlink escapes
+    let resolved = tokio::fs::canonicalize(&full_path)
+        .await
+        .map_err(|e| format!("Cannot resolve path: {e}"))?;
+
+    if !security.is_resolved_path_allowed(&resolved) {
+        return Err(format!(
+            "Resolved path escapes workspace: {}",
+            resolved.display()
+        ));
+    }
+    Ok(resolved)
+}
+
+/// `content` with `old_string`, which must occur exactly once, replaced by
+/// `new_string`.
+pub(super) fn replace_once(
+    content: &str,
+    old_string: &str,
+    new_string: &str,
+) -> Result<String, String> {
+    let count = content.matches(old_string).count();
+    if count == 0 {
+        return Err("old_string not found in file".into());
+    }
+    if count > 1 {
+        return Err(format!(
+            "old_string found {count} times — must match exactly once. Provide more context."
+        ));
+    }
+    Ok(content.replacen(old_string, new_string, 1))
 }
 
 #[async_trait]
@@ -46,6 +105,30 @@ impl Tool for FilePatchTool {
         })
     }
 
+    fn examples(&self) -> Vec<serde_json::Value> {
+        vec![json!({
+            "path": "src/config.rs",
+            "old_string": "const TIMEOUT_SECS: u64 = 30;",
+            "new_string": "const TIMEOUT_SECS: u64 = 60;"
+        })]
+    }
+
+    fn parallel_safe(&self) -> bool {
+        false
+    }
+
+    async fn proposed_edit(&self, args: &serde_json::Value) -> Option<ProposedEdit> {
+        let path = args.get("path")?.as_str()?;
+        let old_string = args.get("old_string")?.as_str()?;
+        let new_string = args.get("new_string")?.as_str()?;
+        let (_, before, after) = self.patch(path, old_string, new_string).await.ok()?;
+        Some(ProposedEdit {
+            path: path.to_string(),
+            before,
+            after,
+        })
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let path = args
             .get("path")
@@ -62,70 +145,17 @@ impl Tool for FilePatchTool {
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing 'new_string' parameter"))?;
 
-        if !self.security.is_path_allowed(path) {
-            return Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some(format!("Path not allowed by security policy: {path}")),
-            });
-        }
-
-        let full_path = self.security.workspace_dir.join(path);
-
-        // Resolve to block symlink escapes
-        let resolved = match tokio::fs::canonicalize(&full_path).await {
-            Ok(p) => p,
-            Err(e) => {
-                return Ok(ToolResult {
-                    success: false,
-                    output: String::new(),
-                    error: Some(format!("Cannot resolve path: {e}")),
-                });
-            }
-        };
-
-        if !self.security.is_resolved_path_allowed(&resolved) {
-            return Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some(format!(
-                    "Resolved path escapes workspace: {}",
-                    resolved.display()
-                )),
-            });
-        }
-
-        let content = match tokio::fs::read_to_string(&resolved).await {
-            Ok(c) => c,
+        let (resolved, _, new_content) = match self.patch(path, old_string, new_string).await {
+            Ok(patched) => patched,
             Err(e) => {
                 return Ok(ToolResult {
                     success: false,
                     output: String::new(),
-                    error: Some(format!("Failed to read file: {e}")),
+                    error: Some(e),
                 });
             }
         };
 
-        let count = content.matches(old_string).count();
-        if count == 0 {
-            return Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some("old_string not found in file".into()),
-            });
-        }
-        if count > 1 {
-            return Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some(format!(
-                    "old_string found {count} times — must match exactly once. Provide more context."
-                )),
-            });
-        }
-
-        let new_content = content.replacen(old_string, new_string, 1);
-
         match tokio::fs::write(&resolved, &new_content).await {
             Ok(()) => Ok(ToolResult {
                 success: true,
@@ -240,4 +270,31 @@ mod tests {
 
         let _ = tokio::fs::remove_dir_all(&dir).await;
     }
+
+    #[tokio::test]
+    async fn file_patch_proposes_edit_without_writing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        tokio::fs::write(dir.path().join("test.txt"), "hello world")
+            .await
+            .unwrap();
+
+        let tool = FilePatchTool::new(test_security(dir.path().to_path_buf()));
+        let edit = tool
+            .proposed_edit(&json!({"path": "test.txt", "old_string": "hello", "new_string": "bye"}))
+            .await
+            .unwrap();
+        assert_eq!(edit.path, "test.txt");
+        assert_eq!(edit.before, "hello world");
+        assert_eq!(edit.after, "bye world");
+        let content = tokio::fs::read_to_string(dir.path().join("test.txt"))
+            .await
+            .unwrap();
+        assert_eq!(content, "hello world");
+
+        // A patch that would fail has nothing to review
+        assert!(tool
+            .proposed_edit(&json!({"path": "test.txt", "old_string": "xyz", "new_string": "abc"}))
+            .await
+            .is_none());
+    }
 }
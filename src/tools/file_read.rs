@@ -2,7 +2,22 @@ use super::traits::{Tool, ToolResult};
 use crate::security::SecurityPolicy;
 use async_trait::async_trait;
 use serde_json::json;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// Largest file returned in `base64` mode. Encoding inflates the payload by a
+/// third, so this stays well below the general read limit.
+const MAX_BASE64_BYTES: u64 = 1024 * 1024;
+
+/// Bytes read at a time when scanning for line breaks.
+const CHUNK_BYTES: usize = 64 * 1024;
+
+/// Largest file whose lines `head` and `tail` count; bigger files report
+/// their size in bytes instead of scanning all of it.
+const MAX_COUNTED_BYTES: u64 = 64 * 1024 * 1024;
 
 /// Read file contents with path sandboxing
 pub struct FileReadTool {
@@ -22,7 +37,7 @@ impl Tool for FileReadTool {
     }
 
     fn description(&self) -> &str {
-        "Read the contents of a file in the workspace"
+        "Read the contents of a file in the workspace. Use head or tail to read only the first or last lines of a large file such as a log, and encoding \"base64\" for small binary files such as images"
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -32,18 +47,71 @@ impl Tool for FileReadTool {
                 "path": {
                     "type": "string",
                     "description": "Relative path to the file within the workspace"
+                },
+                "encoding": {
+                    "type": "string",
+                    "enum": ["utf8", "base64"],
+                    "description": "How to return the contents: utf8 text (default) or base64 for binary files"
+                },
+                "head": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Return only the first N lines, with the file's line count (its size for very large files)"
+                },
+                "tail": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Return only the last N lines, with the file's line count (its size for very large files; for logs)"
                 }
             },
             "required": ["path"]
         })
     }
 
+    /// Reads are reused until a tool with side effects runs, except `tail`,
+    /// which is used to watch files that grow on their own.
+    fn cache_ttl(&self, args: &serde_json::Value) -> Option<Duration> {
+        args.get("tail").is_none().then_some(Duration::MAX)
+    }
+
+    #[allow(clippy::too_many_lines)]
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let path = args
             .get("path")
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing 'path' parameter"))?;
 
+        let base64 = match args.get("encoding").and_then(|v| v.as_str()) {
+            None | Some("utf8" | "utf-8") => false,
+            Some("base64") => true,
+            Some(other) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!(
+                        "Unsupported encoding: {other} (expected \"utf8\" or \"base64\")"
+                    )),
+                });
+            }
+        };
+
+        let window = match parse_window(&args) {
+            Ok(window) if window.is_some() && base64 => {
+                Err("head and tail only work with utf8 text".to_string())
+            }
+            other => other,
+        };
+        let window = match window {
+            Ok(window) => window,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(e),
+                });
+            }
+        };
+
         // Security check: validate path is within workspace
         if !self.security.is_path_allowed(path) {
             return Ok(ToolResult {
@@ -78,16 +146,41 @@ impl Tool for FileReadTool {
             });
         }
 
+        if let Some(window) = window {
+            let max_bytes = self.security.max_file_read_bytes;
+            let read = tokio::task::spawn_blocking(move || {
+                read_window(&resolved_path, window, max_bytes, MAX_COUNTED_BYTES)
+            })
+            .await?;
+            return Ok(match read {
+                Ok(lines) => ToolResult {
+                    success: true,
+                    output: lines.to_string(),
+                    error: None,
+                },
+                Err(e) => ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("Failed to read file: {e}")),
+                },
+            });
+        }
+
         // Check file size AFTER canonicalization to prevent TOCTOU symlink bypass
-        const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+        let limit = if base64 {
+            self.security.max_file_read_bytes.min(MAX_BASE64_BYTES)
+        } else {
+            self.security.max_file_read_bytes
+        };
         match tokio::fs::metadata(&resolved_path).await {
             Ok(meta) => {
-                if meta.len() > MAX_FILE_SIZE {
+                if meta.len() > limit {
                     return Ok(ToolResult {
                         success: false,
                         output: String::new(),
                         error: Some(format!(
-                            "File too large: {} bytes (limit: {MAX_FILE_SIZE} bytes)",
+                            "File too large: {} bytes (limit: {limit} bytes). \
+                             Use head or tail to read part of it",
                             meta.len()
                         )),
                     });
@@ -102,25 +195,261 @@ impl Tool for FileReadTool {
             }
         }
 
-        match tokio::fs::read_to_string(&resolved_path).await {
+        let bytes = match tokio::fs::read(&resolved_path).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("Failed to read file: {e}")),
+                });
+            }
+        };
+        let content_type = detect_content_type(&bytes);
+
+        if base64 {
+            use base64::Engine;
+            let encoded = base64::engine::general_purpose::STANDARD.encode(&bytes);
+            return Ok(ToolResult {
+                success: true,
+                output: format!(
+                    "Content-Type: {content_type}\nSize: {} bytes\ndata:{content_type};base64,{encoded}",
+                    bytes.len()
+                ),
+                error: None,
+            });
+        }
+
+        match String::from_utf8(bytes) {
             Ok(contents) => Ok(ToolResult {
                 success: true,
                 output: contents,
                 error: None,
             }),
-            Err(e) => Ok(ToolResult {
+            Err(_) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
-                error: Some(format!("Failed to read file: {e}")),
+                error: Some(format!(
+                    "File is not valid UTF-8 text (detected {content_type}). \
+                     Retry with encoding \"base64\" to read binary files"
+                )),
             }),
         }
     }
 }
 
+/// Which lines of the file to return.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Window {
+    Head(usize),
+    Tail(usize),
+}
+
+/// The `head` or `tail` argument, if given.
+fn parse_window(args: &serde_json::Value) -> Result<Option<Window>, String> {
+    let count = |key: &str| match args.get(key) {
+        None => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .filter(|&n| n > 0)
+            .and_then(|n| usize::try_from(n).ok())
+            .map(Some)
+            .ok_or_else(|| format!("'{key}' must be a positive number of lines")),
+    };
+    match (count("head")?, count("tail")?) {
+        (Some(_), Some(_)) => Err("Use either head or tail, not both".into()),
+        (Some(n), None) => Ok(Some(Window::Head(n))),
+        (None, Some(n)) => Ok(Some(Window::Tail(n))),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Lines read by `head` or `tail`, numbered from 1. `total` is `None` when
+/// the file was too large to count, which also leaves a tail's `first`
+/// unknown.
+#[derive(Debug)]
+struct Lines {
+    text: String,
+    first: Option<usize>,
+    total: Option<usize>,
+    size: u64,
+}
+
+impl std::fmt::Display for Lines {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let of = match self.total {
+            Some(total) => total.to_string(),
+            None => format!("a {}-byte file", self.size),
+        };
+        match (count_lines(self.text.as_bytes()), self.first) {
+            (0, _) => writeln!(f, "Lines: none of {of}")?,
+            (n, Some(first)) => writeln!(f, "Lines: {first}-{} of {of}", first + n - 1)?,
+            (n, None) => writeln!(f, "Lines: last {n} of {of}")?,
+        }
+        f.write_str(&self.text)
+    }
+}
+
+/// Lines in `bytes`, counting a last line without a trailing newline.
+fn count_lines(bytes: &[u8]) -> usize {
+    newlines(bytes) + usize::from(bytes.last().is_some_and(|&b| b != b'\n'))
+}
+
+#[allow(clippy::naive_bytecount)]
+fn newlines(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&b| b == b'\n').count()
+}
+
+/// Read the lines selected by `window` without loading the rest of the
+/// file: `head` stops after its lines and `tail` reads backwards from the
+/// end. Counting the total lines still scans the file, a chunk at a time,
+/// so files over `max_counted` bytes go uncounted. Fails when the selected
+/// lines exceed `max_bytes`.
+fn read_window(path: &Path, window: Window, max_bytes: u64, max_counted: u64) -> io::Result<Lines> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let bytes = match window {
+        Window::Head(n) => read_head(&mut file, n, max_bytes)?,
+        Window::Tail(n) => read_tail(&mut file, n, max_bytes)?,
+    };
+    let total = if size <= max_counted {
+        Some(count_file_lines(&mut file)?)
+    } else {
+        None
+    };
+    let text = String::from_utf8(bytes).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "file is not valid UTF-8 text; retry without head/tail and with encoding \"base64\"",
+        )
+    })?;
+    let first = match window {
+        Window::Head(_) => Some(1),
+        Window::Tail(_) => total.map(|total| total + 1 - count_lines(text.as_bytes()).min(total)),
+    };
+    Ok(Lines {
+        text,
+        first,
+        total,
+        size,
+    })
+}
+
+fn too_many_bytes(max_bytes: u64) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("the selected lines exceed the {max_bytes}-byte read limit; ask for fewer lines"),
+    )
+}
+
+fn read_head(file: &mut File, lines: usize, max_bytes: u64) -> io::Result<Vec<u8>> {
+    let mut reader = BufReader::new(file);
+    let mut out = Vec::new();
+    for _ in 0..lines {
+        if reader.read_until(b'\n', &mut out)? == 0 {
+            break;
+        }
+        if out.len() as u64 > max_bytes {
+            return Err(too_many_bytes(max_bytes));
+        }
+    }
+    Ok(out)
+}
+
+/// Read chunks backwards from the end, scanning each once for line breaks,
+/// until `lines` of them are found; the chunks are joined only at the end.
+fn read_tail(file: &mut File, lines: usize, max_bytes: u64) -> io::Result<Vec<u8>> {
+    let end = file.seek(SeekFrom::End(0))?;
+    let mut pos = end;
+    // Newest first
+    let mut chunks: Vec<Vec<u8>> = Vec::new();
+    let mut kept: u64 = 0;
+    let mut breaks = 0;
+    while pos > 0 {
+        if kept > max_bytes {
+            return Err(too_many_bytes(max_bytes));
+        }
+
+        let step = usize::try_from(pos).map_or(CHUNK_BYTES, |pos| pos.min(CHUNK_BYTES));
+        pos -= step as u64;
+        file.seek(SeekFrom::Start(pos))?;
+        let mut chunk = vec![0; step];
+        file.read_exact(&mut chunk)?;
+
+        // The newline ending the last line doesn't start another one
+        let body = if pos + step as u64 == end {
+            chunk.strip_suffix(b"\n").unwrap_or(&chunk)
+        } else {
+            &chunk
+        };
+        let start = body
+            .iter()
+            .rposition(|&b| {
+                breaks += usize::from(b == b'\n');
+                breaks == lines
+            })
+            .map(|i| i + 1);
+        if let Some(start) = start {
+            chunk.drain(..start);
+        }
+        kept += chunk.len() as u64;
+        chunks.push(chunk);
+        if start.is_some() {
+            break;
+        }
+    }
+    if kept > max_bytes {
+        return Err(too_many_bytes(max_bytes));
+    }
+    chunks.reverse();
+    Ok(chunks.concat())
+}
+
+fn count_file_lines(file: &mut File) -> io::Result<usize> {
+    file.seek(SeekFrom::Start(0))?;
+    let mut chunk = vec![0; CHUNK_BYTES];
+    let mut breaks = 0;
+    let mut last = None;
+    loop {
+        let read = file.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        breaks += newlines(&chunk[..read]);
+        last = Some(chunk[read - 1]);
+    }
+    Ok(breaks + usize::from(last.is_some_and(|b| b != b'\n')))
+}
+
+/// Guess a MIME type from magic bytes, falling back to plain text for valid
+/// UTF-8 and `application/octet-stream` for anything else.
+fn detect_content_type(bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        "image/jpeg"
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        "image/gif"
+    } else if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+        "image/webp"
+    } else if bytes.starts_with(b"%PDF-") {
+        "application/pdf"
+    } else if bytes.starts_with(b"PK\x03\x04") {
+        "application/zip"
+    } else if bytes.starts_with(&[0x1F, 0x8B]) {
+        "application/gzip"
+    } else if std::str::from_utf8(bytes).is_ok() {
+        "text/plain"
+    } else {
+        "application/octet-stream"
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::security::{AutonomyLevel, SecurityPolicy};
+    use std::fmt::Write;
 
     fn test_security(workspace: std::path::PathBuf) -> Arc<SecurityPolicy> {
         Arc::new(SecurityPolicy {
@@ -297,4 +626,171 @@ mod tests {
 
         let _ = tokio::fs::remove_dir_all(&dir).await;
     }
+
+    #[tokio::test]
+    async fn file_read_respects_configured_limit() {
+        let dir = tempfile::TempDir::new().unwrap();
+        tokio::fs::write(dir.path().join("notes.txt"), "x".repeat(2048))
+            .await
+            .unwrap();
+
+        let tool = FileReadTool::new(Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Supervised,
+            workspace_dir: dir.path().to_path_buf(),
+            max_file_read_bytes: 1024,
+            ..SecurityPolicy::default()
+        }));
+        let result = tool.execute(json!({"path": "notes.txt"})).await.unwrap();
+        assert!(!result.success);
+        let error = result.error.unwrap();
+        assert!(error.contains("File too large: 2048 bytes"), "{error}");
+        assert!(error.contains("limit: 1024 bytes"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn file_read_base64_round_trip() {
+        use base64::Engine;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend((0..=255u8).rev());
+        tokio::fs::write(dir.path().join("pixel.png"), &png)
+            .await
+            .unwrap();
+
+        let tool = FileReadTool::new(test_security(dir.path().to_path_buf()));
+        let result = tool
+            .execute(json!({"path": "pixel.png", "encoding": "base64"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.starts_with("Content-Type: image/png\n"));
+
+        let encoded = result.output.split("base64,").nth(1).unwrap();
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .unwrap();
+        assert_eq!(decoded, png);
+    }
+
+    #[tokio::test]
+    async fn file_read_binary_as_text_suggests_base64() {
+        let dir = tempfile::TempDir::new().unwrap();
+        tokio::fs::write(dir.path().join("blob.bin"), [0xFF, 0xFE, 0x00, 0x81])
+            .await
+            .unwrap();
+
+        let tool = FileReadTool::new(test_security(dir.path().to_path_buf()));
+        let result = tool.execute(json!({"path": "blob.bin"})).await.unwrap();
+        assert!(!result.success);
+        let error = result.error.unwrap();
+        assert!(error.contains("application/octet-stream"), "{error}");
+        assert!(error.contains("base64"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn file_read_tail_reads_the_end_of_a_file_past_the_limit() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = (1..=20_000).fold(String::new(), |mut log, i| {
+            let _ = writeln!(log, "line {i}");
+            log
+        });
+        assert!(log.len() > 2 * CHUNK_BYTES);
+        tokio::fs::write(dir.path().join("app.log"), &log)
+            .await
+            .unwrap();
+
+        let tool = FileReadTool::new(Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Supervised,
+            workspace_dir: dir.path().to_path_buf(),
+            max_file_read_bytes: 1024,
+            ..SecurityPolicy::default()
+        }));
+        let args = json!({"path": "app.log", "tail": 3});
+        let result = tool.execute(args.clone()).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(
+            result.output,
+            "Lines: 19998-20000 of 20000\nline 19998\nline 19999\nline 20000\n"
+        );
+        assert_eq!(tool.cache_ttl(&args), None, "a growing log is read afresh");
+
+        let result = tool
+            .execute(json!({"path": "app.log", "tail": 5000}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("1024-byte read limit"));
+
+        let path = dir.path().join("app.log");
+        let tail = read_window(&path, Window::Tail(15_000), u64::MAX, u64::MAX).unwrap();
+        assert_eq!(tail.first, Some(5001));
+        assert!(tail.text.starts_with("line 5001\n"));
+        assert_eq!(
+            tail.text.len(),
+            log.len() - log.find("line 5001\n").unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn file_read_head_on_a_short_file_returns_all_of_it() {
+        let dir = tempfile::TempDir::new().unwrap();
+        tokio::fs::write(dir.path().join("notes.txt"), "one\ntwo\nthree")
+            .await
+            .unwrap();
+
+        let tool = FileReadTool::new(test_security(dir.path().to_path_buf()));
+        let result = tool
+            .execute(json!({"path": "notes.txt", "head": 10}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, "Lines: 1-3 of 3\none\ntwo\nthree");
+
+        let result = tool
+            .execute(json!({"path": "notes.txt", "head": 2}))
+            .await
+            .unwrap();
+        assert_eq!(result.output, "Lines: 1-2 of 3\none\ntwo\n");
+
+        let result = tool
+            .execute(json!({"path": "notes.txt", "tail": 10}))
+            .await
+            .unwrap();
+        assert_eq!(result.output, "Lines: 1-3 of 3\none\ntwo\nthree");
+    }
+
+    #[test]
+    fn files_too_large_to_count_report_their_size() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("big.log");
+        std::fs::write(&path, "one\ntwo\nthree\n").unwrap();
+
+        let head = read_window(&path, Window::Head(2), 1024, 0).unwrap();
+        assert_eq!(head.to_string(), "Lines: 1-2 of a 14-byte file\none\ntwo\n");
+        let tail = read_window(&path, Window::Tail(2), 1024, 0).unwrap();
+        assert_eq!(
+            tail.to_string(),
+            "Lines: last 2 of a 14-byte file\ntwo\nthree\n"
+        );
+        let tail = read_window(&path, Window::Tail(2), 1024, 14).unwrap();
+        assert_eq!(tail.to_string(), "Lines: 2-3 of 3\ntwo\nthree\n");
+    }
+
+    #[test]
+    fn head_and_tail_arguments_are_validated() {
+        assert_eq!(parse_window(&json!({"tail": 5})), Ok(Some(Window::Tail(5))));
+        assert_eq!(parse_window(&json!({})), Ok(None));
+        assert!(parse_window(&json!({"head": 1, "tail": 1})).is_err());
+        assert!(parse_window(&json!({"head": 0})).is_err());
+        assert!(parse_window(&json!({"tail": "5"})).is_err());
+    }
+
+    #[test]
+    fn detects_content_types() {
+        assert_eq!(detect_content_type(b"%PDF-1.7"), "application/pdf");
+        assert_eq!(detect_content_type(b"GIF89a..."), "image/gif");
+        assert_eq!(detect_content_type(b"RIFF\0\0\0\0WEBPVP8 "), "image/webp");
+        assert_eq!(detect_content_type(b"plain text"), "text/plain");
+    }
 }
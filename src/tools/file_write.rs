@@ -1,7 +1,8 @@
-use super::traits::{Tool, ToolResult};
+use super::traits::{ProposedEdit, Tool, ToolResult};
 use crate::security::SecurityPolicy;
 use async_trait::async_trait;
 use serde_json::json;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 /// Write file contents with path sandboxing
@@ -15,6 +16,78 @@ impl FileWriteTool {
     }
 }
 
+/// Create `parent` and any missing ancestors, but only when the nearest
+/// existing ancestor resolves inside the workspace, so a symlinked
+/// directory can't be used to create directories outside it.
+pub(crate) async fn create_parent_dirs(
+    security: &SecurityPolicy,
+    parent: &Path,
+) -> Result<(), String> {
+    let mut existing = parent;
+    while tokio::fs::symlink_metadata(existing).await.is_err() {
+        match existing.parent() {
+            Some(up) => existing = up,
+            None => break,
+        }
+    }
+    let resolved = tokio::fs::canonicalize(existing)
+        .await
+        .map_err(|e| format!("Failed to resolve file path: {e}"))?;
+    if !security.is_resolved_path_allowed(&resolved) {
+        return Err(format!(
+            "Resolved path escapes workspace: {}",
+            resolved.display()
+        ));
+    }
+    tokio::fs::create_dir_all(parent)
+        .await
+        .map_err(|e| format!("Failed to create parent directories: {e}"))
+}
+
+/// Where a write to workspace-relative `path` should go: parent
+/// directories are created, the resolved parent must be inside the
+/// workspace, and an existing symlink at the target is refused. The caller
+/// checks `is_path_allowed` first.
+pub(crate) async fn resolve_write_target(
+    security: &SecurityPolicy,
+    path: &str,
+) -> Result<PathBuf, String> {
+    let full_path = security.workspace_dir.join(path);
+    let parent = full_path
+        .parent()
+        .ok_or("Invalid path: missing parent directory")?;
+
+    // Ensure parent directory exists
+    create_parent_dirs(security, parent).await?;
+
+    // Resolve parent AFTER creation to block symlink escapes.
+    let resolved_parent = tokio::fs::canonicalize(parent)
+        .await
+        .map_err(|e| format!("Failed to resolve file path: {e}"))?;
+    if !security.is_resolved_path_allowed(&resolved_parent) {
+        return Err(format!(
+            "Resolved path escapes workspace: {}",
+            resolved_parent.display()
+        ));
+    }
+
+    let file_name = full_path
+        .file_name()
+        .ok_or("Invalid path: missing file name")?;
+    let resolved_target = resolved_parent.join(file_name);
+
+    // If the target already exists and is a symlink, refuse to follow it
+    if let Ok(meta) = tokio::fs::symlink_metadata(&resolved_target).await {
+        if meta.file_type().is_symlink() {
+            return Err(format!(
+                "Refusing to write through symlink: {}",
+                resolved_target.display()
+            ));
+        }
+    }
+    Ok(resolved_target)
+}
+
 #[async_trait]
 impl Tool for FileWriteTool {
     fn name(&self) -> &str {
@@ -22,7 +95,7 @@ impl Tool for FileWriteTool {
     }
 
     fn description(&self) -> &str {
-        "Write contents to a file in the workspace"
+        "Write contents to a file in the workspace, creating missing parent directories. Set no_clobber to refuse overwriting an existing file."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -36,12 +109,53 @@ impl Tool for FileWriteTool {
                 "content": {
                     "type": "string",
                     "description": "Content to write to the file"
+                },
+                "no_clobber": {
+                    "type": "boolean",
+                    "description": "Fail instead of overwriting if the file already exists (default: false)"
                 }
             },
             "required": ["path", "content"]
         })
     }
 
+    fn parallel_safe(&self) -> bool {
+        false
+    }
+
+    async fn proposed_edit(&self, args: &serde_json::Value) -> Option<ProposedEdit> {
+        let path = args.get("path")?.as_str()?;
+        let after = args.get("content")?.as_str()?;
+        let no_clobber = args
+            .get("no_clobber")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+        if !self.security.is_path_allowed(path) {
+            return None;
+        }
+
+        let full_path = self.security.workspace_dir.join(path);
+        // Only read a file `execute` would actually overwrite
+        let before = match tokio::fs::symlink_metadata(&full_path).await {
+            Err(_) => String::new(),
+            Ok(meta) if meta.file_type().is_symlink() || no_clobber => return None,
+            Ok(_) => {
+                let resolved = tokio::fs::canonicalize(&full_path).await.ok()?;
+                if !self.security.is_resolved_path_allowed(&resolved) {
+                    return None;
+                }
+                let bytes = tokio::fs::read(&resolved).await.ok()?;
+                String::from_utf8_lossy(&bytes).into_owned()
+            }
+        };
+        Some(ProposedEdit {
+            path: path.to_string(),
+            before,
+            after: after.to_string(),
+        })
+    }
+
+    #[allow(clippy::too_many_lines)]
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let path = args
             .get("path")
@@ -53,6 +167,11 @@ impl Tool for FileWriteTool {
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing 'content' parameter"))?;
 
+        let no_clobber = args
+            .get("no_clobber")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+
         // Security check: validate path is within workspace
         if !self.security.is_path_allowed(path) {
             return Ok(ToolResult {
@@ -62,72 +181,46 @@ impl Tool for FileWriteTool {
             });
         }
 
-        let full_path = self.security.workspace_dir.join(path);
-
-        let Some(parent) = full_path.parent() else {
-            return Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some("Invalid path: missing parent directory".into()),
-            });
-        };
-
-        // Ensure parent directory exists
-        tokio::fs::create_dir_all(parent).await?;
-
-        // Resolve parent AFTER creation to block symlink escapes.
-        let resolved_parent = match tokio::fs::canonicalize(parent).await {
-            Ok(p) => p,
+        let resolved_target = match resolve_write_target(&self.security, path).await {
+            Ok(target) => target,
             Err(e) => {
                 return Ok(ToolResult {
                     success: false,
                     output: String::new(),
-                    error: Some(format!("Failed to resolve file path: {e}")),
+                    error: Some(e),
                 });
             }
         };
+        let existed = tokio::fs::symlink_metadata(&resolved_target).await.ok();
 
-        if !self.security.is_resolved_path_allowed(&resolved_parent) {
-            return Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some(format!(
-                    "Resolved path escapes workspace: {}",
-                    resolved_parent.display()
-                )),
-            });
-        }
-
-        let Some(file_name) = full_path.file_name() else {
-            return Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some("Invalid path: missing file name".into()),
-            });
+        let write = if no_clobber {
+            // create_new makes the existence check and the write one step
+            write_new(&resolved_target, content).await
+        } else {
+            tokio::fs::write(&resolved_target, content).await
         };
 
-        let resolved_target = resolved_parent.join(file_name);
-
-        // If the target already exists and is a symlink, refuse to follow it
-        if let Ok(meta) = tokio::fs::symlink_metadata(&resolved_target).await {
-            if meta.file_type().is_symlink() {
-                return Ok(ToolResult {
-                    success: false,
-                    output: String::new(),
-                    error: Some(format!(
-                        "Refusing to write through symlink: {}",
-                        resolved_target.display()
-                    )),
-                });
-            }
-        }
-
-        match tokio::fs::write(&resolved_target, content).await {
+        match write {
             Ok(()) => Ok(ToolResult {
                 success: true,
-                output: format!("Written {} bytes to {path}", content.len()),
+                output: format!(
+                    "Written {} bytes to {path} ({})",
+                    content.len(),
+                    if existed.is_some() {
+                        "overwritten"
+                    } else {
+                        "created"
+                    }
+                ),
                 error: None,
             }),
+            Err(e) if no_clobber && e.kind() == std::io::ErrorKind::AlreadyExists => {
+                Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("File already exists and no_clobber is set: {path}")),
+                })
+            }
             Err(e) => Ok(ToolResult {
                 success: false,
                 output: String::new(),
@@ -137,6 +230,18 @@ impl Tool for FileWriteTool {
     }
 }
 
+async fn write_new(path: &Path, content: &str) -> std::io::Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .await?;
+    file.write_all(content.as_bytes()).await?;
+    file.flush().await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -180,6 +285,7 @@ mod tests {
             .unwrap();
         assert!(result.success);
         assert!(result.output.contains("8 bytes"));
+        assert!(result.output.contains("(created)"));
 
         let content = tokio::fs::read_to_string(dir.join("out.txt"))
             .await
@@ -201,6 +307,7 @@ mod tests {
             .await
             .unwrap();
         assert!(result.success);
+        assert!(result.output.contains("(created)"));
 
         let content = tokio::fs::read_to_string(dir.join("a/b/c/deep.txt"))
             .await
@@ -225,6 +332,7 @@ mod tests {
             .await
             .unwrap();
         assert!(result.success);
+        assert!(result.output.contains("(overwritten)"));
 
         let content = tokio::fs::read_to_string(dir.join("exist.txt"))
             .await
@@ -234,6 +342,81 @@ mod tests {
         let _ = tokio::fs::remove_dir_all(&dir).await;
     }
 
+    #[tokio::test]
+    async fn file_write_no_clobber_refuses_existing_file() {
+        let dir = std::env::temp_dir().join("tinyclaw_test_file_write_no_clobber");
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        tokio::fs::write(dir.join("keep.txt"), "original")
+            .await
+            .unwrap();
+
+        let tool = FileWriteTool::new(test_security(dir.clone()));
+        let result = tool
+            .execute(json!({"path": "keep.txt", "content": "new", "no_clobber": true}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.as_deref().unwrap().contains("already exists"));
+        let content = tokio::fs::read_to_string(dir.join("keep.txt"))
+            .await
+            .unwrap();
+        assert_eq!(content, "original");
+
+        // no_clobber still writes new files, including into new directories
+        let result = tool
+            .execute(
+                json!({"path": "reports/2024/summary.md", "content": "# Q4", "no_clobber": true}),
+            )
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.contains("4 bytes"));
+        assert!(result.output.contains("(created)"));
+        let content = tokio::fs::read_to_string(dir.join("reports/2024/summary.md"))
+            .await
+            .unwrap();
+        assert_eq!(content, "# Q4");
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn file_write_proposes_edit_without_writing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        tokio::fs::write(dir.path().join("exist.txt"), "old")
+            .await
+            .unwrap();
+
+        let tool = FileWriteTool::new(test_security(dir.path().to_path_buf()));
+        let edit = tool
+            .proposed_edit(&json!({"path": "exist.txt", "content": "new"}))
+            .await
+            .unwrap();
+        assert_eq!((edit.before.as_str(), edit.after.as_str()), ("old", "new"));
+        let content = tokio::fs::read_to_string(dir.path().join("exist.txt"))
+            .await
+            .unwrap();
+        assert_eq!(content, "old");
+
+        let edit = tool
+            .proposed_edit(&json!({"path": "sub/new.txt", "content": "hi"}))
+            .await
+            .unwrap();
+        assert_eq!(edit.before, "");
+        assert!(!dir.path().join("sub").exists());
+
+        // Calls that would fail have nothing to review
+        assert!(tool
+            .proposed_edit(&json!({"path": "exist.txt", "content": "x", "no_clobber": true}))
+            .await
+            .is_none());
+        assert!(tool
+            .proposed_edit(&json!({"path": "../escape.txt", "content": "x"}))
+            .await
+            .is_none());
+    }
+
     #[tokio::test]
     async fn file_write_blocks_path_traversal() {
         let dir = std::env::temp_dir().join("tinyclaw_test_file_write_traversal");
@@ -322,6 +505,14 @@ mod tests {
             .contains("escapes workspace"));
         assert!(!outside.join("hijack.txt").exists());
 
+        // Missing directories behind the symlink are not created either
+        let result = tool
+            .execute(json!({"path": "escape_dir/new/sub/x.txt", "content": "bad"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(!outside.join("new").exists());
+
         let _ = tokio::fs::remove_dir_all(&root).await;
     }
 }
@@ -0,0 +1,492 @@
+use super::exec_support::{self, failure};
+use super::traits::{Tool, ToolResult};
+use crate::security::{AutonomyLevel, SecurityPolicy};
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Maximum git execution time before kill.
+const GIT_TIMEOUT_SECS: u64 = 30;
+/// Maximum output size in bytes (1MB).
+const MAX_OUTPUT_BYTES: usize = 1_048_576;
+
+/// Subcommands that only inspect the repository.
+const READ_OPERATIONS: &[&str] = &["status", "diff", "log", "show", "branch"];
+/// Subcommands that modify the repository; gated by autonomy level.
+const WRITE_OPERATIONS: &[&str] = &["add", "commit"];
+
+/// Options that read or write files, run external programs, or escape the
+/// workspace.
+const BLOCKED_OPTIONS: &[&str] = &[
+    "--output",
+    "--no-index",
+    "--orderfile",
+    "--pathspec-from-file",
+    "--ignore-revs-file",
+    "--ext-diff",
+    "--textconv",
+    "--exec",
+    "--upload-pack",
+    "--receive-pack",
+    "--git-dir",
+    "--work-tree",
+    "--namespace",
+    "--open-files-in-pager",
+];
+
+/// Short options in [`BLOCKED_OPTIONS`]: `-O<orderfile>` reads any file.
+const BLOCKED_SHORT_OPTIONS: &[char] = &['O'];
+/// Short options whose value follows them in the same argument, so the rest
+/// of a bundle like `-pS<string>` is not more options.
+const SHORT_OPTIONS_WITH_VALUE: &[char] = &['S', 'G', 'n', 'U', 'l', 'L', 'M', 'C', 'B', 'X', 'I'];
+
+/// `git branch` options that create, rename or delete branches.
+const BRANCH_MUTATING_OPTIONS: &[&str] = &[
+    "-d",
+    "-D",
+    "-m",
+    "-M",
+    "-c",
+    "-C",
+    "-f",
+    "-u",
+    "--delete",
+    "--move",
+    "--copy",
+    "--force",
+    "--set-upstream-to",
+    "--unset-upstream",
+    "--edit-description",
+    "--track",
+];
+
+/// Structured git access limited to a whitelist of subcommands
+pub struct GitTool {
+    security: Arc<SecurityPolicy>,
+}
+
+impl GitTool {
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self { security }
+    }
+
+    /// Validate the caller-supplied arguments for a subcommand.
+    fn check_git_args(&self, operation: &str, args: &[String]) -> Result<(), String> {
+        for arg in args {
+            if arg.contains('\0') || arg.contains('\n') {
+                return Err(format!("Invalid git argument: {arg:?}"));
+            }
+            if arg.starts_with('-') {
+                let flag = arg.split('=').next().unwrap_or(arg);
+                if BLOCKED_OPTIONS
+                    .iter()
+                    .any(|b| flag == *b || flag.starts_with(&format!("{b}-")))
+                    || Self::bundles_blocked_short_option(arg)
+                {
+                    return Err(format!("Option not allowed: {flag}"));
+                }
+                if operation == "branch" && BRANCH_MUTATING_OPTIONS.contains(&flag) {
+                    return Err(format!(
+                        "git branch is read-only here; option not allowed: {flag}"
+                    ));
+                }
+            } else if operation == "branch" {
+                return Err("git branch is read-only here; it cannot create branches".into());
+            } else if !self.security.is_path_allowed(arg) {
+                // Also catches diff's implicit --no-index for paths outside the repo
+                return Err(format!("Path not allowed by security policy: {arg}"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a short option bundle such as `-pO/etc/passwd` includes a
+    /// blocked short option.
+    fn bundles_blocked_short_option(arg: &str) -> bool {
+        let Some(bundle) = arg.strip_prefix('-').filter(|b| !b.starts_with('-')) else {
+            return false;
+        };
+        for c in bundle.chars() {
+            if BLOCKED_SHORT_OPTIONS.contains(&c) {
+                return true;
+            }
+            if SHORT_OPTIONS_WITH_VALUE.contains(&c) {
+                return false;
+            }
+        }
+        false
+    }
+
+    /// Build the full argument list passed to the `git` binary.
+    fn build_command_args(operation: &str, args: &[String], message: Option<&str>) -> Vec<String> {
+        let mut full = vec!["--no-pager".to_string(), operation.to_string()];
+        match operation {
+            // Never run user-configured diff drivers or text converters.
+            "diff" | "log" | "show" => {
+                full.push("--no-ext-diff".into());
+                full.push("--no-textconv".into());
+            }
+            "commit" => {
+                full.push("-m".into());
+                full.push(message.unwrap_or_default().to_string());
+            }
+            "add" => full.push("--".into()),
+            _ => {}
+        }
+        full.extend(args.iter().cloned());
+        full
+    }
+}
+
+#[async_trait]
+impl Tool for GitTool {
+    fn name(&self) -> &str {
+        "git"
+    }
+
+    fn description(&self) -> &str {
+        "Run a whitelisted git subcommand (status, diff, log, show, branch; add/commit when autonomy allows) in the workspace"
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "operation": {
+                    "type": "string",
+                    "enum": ["status", "diff", "log", "show", "branch", "add", "commit"],
+                    "description": "The git subcommand to run"
+                },
+                "args": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Extra arguments, e.g. [\"--stat\"], [\"-n\", \"5\"] or file paths for add"
+                },
+                "message": {
+                    "type": "string",
+                    "description": "Commit message (required for commit)"
+                },
+                "approved": {
+                    "type": "boolean",
+                    "description": "Set true to explicitly approve add/commit in supervised mode",
+                    "default": false
+                }
+            },
+            "required": ["operation"]
+        })
+    }
+
+    /// Enough of a diff or log to recognise it
+    fn preview_len(&self) -> Option<usize> {
+        Some(400)
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let operation = args
+            .get("operation")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'operation' parameter"))?;
+        let extra: Vec<String> = args
+            .get("args")
+            .and_then(|v| v.as_array())
+            .map(|a| {
+                a.iter()
+                    .filter_map(|v| v.as_str().map(ToString::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let message = args.get("message").and_then(|v| v.as_str());
+        let approved = args
+            .get("approved")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+
+        let is_write = WRITE_OPERATIONS.contains(&operation);
+        if !is_write && !READ_OPERATIONS.contains(&operation) {
+            return Ok(failure(format!(
+                "git subcommand not allowed: {operation}. Allowed: {}",
+                [READ_OPERATIONS, WRITE_OPERATIONS].concat().join(", ")
+            )));
+        }
+
+        if is_write {
+            match self.security.autonomy {
+                AutonomyLevel::ReadOnly => {
+                    return Ok(failure(format!(
+                        "git {operation} is not allowed in read-only mode"
+                    )));
+                }
+                AutonomyLevel::Supervised if !approved => {
+                    return Ok(failure(format!(
+                        "git {operation} requires explicit approval (approved=true) in supervised mode"
+                    )));
+                }
+                _ => {}
+            }
+        }
+
+        if operation == "commit" {
+            if message.is_none_or(|m| m.trim().is_empty()) {
+                return Ok(failure("Missing 'message' for commit"));
+            }
+            if !extra.is_empty() {
+                return Ok(failure("commit does not accept extra arguments"));
+            }
+        }
+        if operation == "add" && extra.is_empty() {
+            return Ok(failure("add requires at least one path in 'args'"));
+        }
+
+        if let Err(reason) = self.check_git_args(operation, &extra) {
+            return Ok(failure(reason));
+        }
+
+        if let Err(refused) = exec_support::record_action(&self.security) {
+            return Ok(refused);
+        }
+
+        let mut cmd = exec_support::command("git", &self.security.workspace_dir, &[]);
+        cmd.args(Self::build_command_args(operation, &extra, message))
+            .env("GIT_TERMINAL_PROMPT", "0");
+
+        let result =
+            tokio::time::timeout(Duration::from_secs(GIT_TIMEOUT_SECS), cmd.output()).await;
+
+        match result {
+            Ok(Ok(output)) => {
+                let mut stdout = String::from_utf8_lossy(&output.stdout).to_string();
+                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+                if stdout.len() > MAX_OUTPUT_BYTES {
+                    stdout.truncate(stdout.floor_char_boundary(MAX_OUTPUT_BYTES));
+                    stdout.push_str("\n... [output truncated at 1MB]");
+                }
+
+                Ok(ToolResult {
+                    success: output.status.success(),
+                    output: stdout,
+                    error: if stderr.is_empty() {
+                        None
+                    } else {
+                        Some(stderr)
+                    },
+                })
+            }
+            Ok(Err(e)) => Ok(failure(format!("Failed to run git: {e}"))),
+            Err(_) => Ok(failure(format!(
+                "git timed out after {GIT_TIMEOUT_SECS}s and was killed"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn test_security(autonomy: AutonomyLevel, dir: &std::path::Path) -> Arc<SecurityPolicy> {
+        Arc::new(SecurityPolicy {
+            autonomy,
+            workspace_dir: dir.to_path_buf(),
+            ..SecurityPolicy::default()
+        })
+    }
+
+    fn init_repo() -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        for args in [
+            vec!["init", "-q"],
+            vec!["config", "user.email", "test@example.com"],
+            vec!["config", "user.name", "Test"],
+        ] {
+            let status = std::process::Command::new("git")
+                .args(&args)
+                .current_dir(tmp.path())
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+        std::fs::write(tmp.path().join("README.md"), "hello\n").unwrap();
+        tmp
+    }
+
+    #[test]
+    fn git_tool_name_and_schema() {
+        let tool = GitTool::new(test_security(
+            AutonomyLevel::Supervised,
+            &std::env::temp_dir(),
+        ));
+        assert_eq!(tool.name(), "git");
+        let schema = tool.parameters_schema();
+        assert!(schema["properties"]["operation"]["enum"].is_array());
+        assert_eq!(schema["required"][0], "operation");
+    }
+
+    #[tokio::test]
+    async fn status_lists_untracked_file() {
+        let repo = init_repo();
+        let tool = GitTool::new(test_security(AutonomyLevel::ReadOnly, repo.path()));
+        let result = tool
+            .execute(json!({"operation": "status", "args": ["--short"]}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.contains("README.md"));
+    }
+
+    #[tokio::test]
+    async fn rejects_non_whitelisted_subcommand() {
+        let repo = init_repo();
+        let tool = GitTool::new(test_security(AutonomyLevel::Full, repo.path()));
+        for op in ["push", "reset", "checkout", "config", "clean"] {
+            let result = tool.execute(json!({"operation": op})).await.unwrap();
+            assert!(!result.success);
+            assert!(result.error.unwrap().contains("not allowed"));
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_dangerous_options() {
+        let repo = init_repo();
+        let tool = GitTool::new(test_security(AutonomyLevel::Full, repo.path()));
+        for args in [
+            json!(["--output=/tmp/pwned"]),
+            json!(["--ext-diff"]),
+            json!(["--git-dir=/etc"]),
+            json!(["--no-index", "/etc/passwd", "/dev/null"]),
+            json!(["-O/etc/passwd"]),
+            json!(["-pO", "/etc/passwd"]),
+            json!(["--orderfile=/etc/passwd"]),
+        ] {
+            let result = tool
+                .execute(json!({"operation": "diff", "args": args}))
+                .await
+                .unwrap();
+            assert!(!result.success);
+            assert!(result.error.unwrap().contains("Option not allowed"));
+        }
+    }
+
+    #[tokio::test]
+    async fn diff_rejects_paths_outside_workspace() {
+        let repo = init_repo();
+        let tool = GitTool::new(test_security(AutonomyLevel::Full, repo.path()));
+        // Without --no-index, git diff still compares files outside the repo
+        let result = tool
+            .execute(json!({"operation": "diff", "args": ["/etc/passwd", "/dev/null"]}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Path not allowed"));
+
+        let pickaxe = tool
+            .execute(json!({"operation": "log", "args": ["-SFOO", "HEAD~1..HEAD"]}))
+            .await
+            .unwrap();
+        assert!(
+            !pickaxe.error.unwrap_or_default().contains("not allowed"),
+            "revision ranges and option values are not paths"
+        );
+    }
+
+    #[tokio::test]
+    async fn branch_cannot_create_or_delete() {
+        let repo = init_repo();
+        let tool = GitTool::new(test_security(AutonomyLevel::Full, repo.path()));
+        let create = tool
+            .execute(json!({"operation": "branch", "args": ["feature"]}))
+            .await
+            .unwrap();
+        assert!(!create.success);
+        let delete = tool
+            .execute(json!({"operation": "branch", "args": ["-D", "main"]}))
+            .await
+            .unwrap();
+        assert!(!delete.success);
+        assert!(delete.error.unwrap().contains("read-only"));
+    }
+
+    #[tokio::test]
+    async fn write_operations_blocked_in_readonly() {
+        let repo = init_repo();
+        let tool = GitTool::new(test_security(AutonomyLevel::ReadOnly, repo.path()));
+        let result = tool
+            .execute(json!({"operation": "add", "args": ["README.md"], "approved": true}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("read-only"));
+    }
+
+    #[tokio::test]
+    async fn write_operations_need_approval_when_supervised() {
+        let repo = init_repo();
+        let tool = GitTool::new(test_security(AutonomyLevel::Supervised, repo.path()));
+        let denied = tool
+            .execute(json!({"operation": "add", "args": ["README.md"]}))
+            .await
+            .unwrap();
+        assert!(!denied.success);
+        assert!(denied.error.unwrap().contains("approval"));
+
+        let allowed = tool
+            .execute(json!({"operation": "add", "args": ["README.md"], "approved": true}))
+            .await
+            .unwrap();
+        assert!(allowed.success, "{:?}", allowed.error);
+    }
+
+    #[tokio::test]
+    async fn add_commit_and_log_roundtrip() {
+        let repo = init_repo();
+        let tool = GitTool::new(test_security(AutonomyLevel::Full, repo.path()));
+
+        let add = tool
+            .execute(json!({"operation": "add", "args": ["README.md"]}))
+            .await
+            .unwrap();
+        assert!(add.success, "{:?}", add.error);
+
+        let commit = tool
+            .execute(json!({"operation": "commit", "message": "Initial commit"}))
+            .await
+            .unwrap();
+        assert!(commit.success, "{:?}", commit.error);
+
+        let log = tool
+            .execute(json!({"operation": "log", "args": ["--oneline"]}))
+            .await
+            .unwrap();
+        assert!(log.success);
+        assert!(log.output.contains("Initial commit"));
+    }
+
+    #[tokio::test]
+    async fn add_rejects_paths_outside_workspace() {
+        let repo = init_repo();
+        let tool = GitTool::new(test_security(AutonomyLevel::Full, repo.path()));
+        let result = tool
+            .execute(json!({"operation": "add", "args": ["../../etc/passwd"]}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Path not allowed"));
+    }
+
+    #[tokio::test]
+    async fn commit_requires_message() {
+        let repo = init_repo();
+        let tool = GitTool::new(test_security(AutonomyLevel::Full, repo.path()));
+        let result = tool.execute(json!({"operation": "commit"})).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("message"));
+    }
+
+    #[tokio::test]
+    async fn missing_operation_is_error() {
+        let tool = GitTool::new(test_security(AutonomyLevel::Full, &std::env::temp_dir()));
+        assert!(tool.execute(json!({})).await.is_err());
+    }
+}
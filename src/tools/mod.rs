@@ -1,13 +1,23 @@
 pub mod browser;
 pub mod browser_open;
+pub mod cache;
+pub mod check;
 pub mod composio;
+pub mod exec_support;
+pub mod file_multi_patch;
 pub mod file_patch;
 pub mod file_read;
 pub mod file_write;
+pub mod git;
 pub mod image_info;
 pub mod memory_forget;
 pub mod memory_recall;
 pub mod memory_store;
+#[cfg(feature = "plot-feature")]
+pub mod plot;
+pub mod redacted;
+#[cfg(feature = "daemon-feature")]
+pub mod schedule;
 pub mod screenshot;
 pub mod search_files;
 pub mod shell;
@@ -16,24 +26,35 @@ pub mod web_fetch;
 
 pub use browser::BrowserTool;
 pub use browser_open::BrowserOpenTool;
+#[allow(unused_imports)]
+pub use cache::ToolResultCache;
+pub use check::CheckTool;
 pub use composio::ComposioTool;
+pub use file_multi_patch::FileMultiPatchTool;
 pub use file_patch::FilePatchTool;
 pub use file_read::FileReadTool;
 pub use file_write::FileWriteTool;
+pub use git::GitTool;
 pub use image_info::ImageInfoTool;
 pub use memory_forget::MemoryForgetTool;
 pub use memory_recall::MemoryRecallTool;
 pub use memory_store::MemoryStoreTool;
+#[cfg(feature = "plot-feature")]
+pub use plot::PlotTool;
+pub use redacted::RedactedTool;
+#[cfg(feature = "daemon-feature")]
+pub use schedule::ScheduleReminderTool;
 pub use screenshot::ScreenshotTool;
 pub use search_files::SearchFilesTool;
 pub use shell::ShellTool;
 pub use traits::Tool;
 pub use web_fetch::WebFetchTool;
 #[allow(unused_imports)]
-pub use traits::{ToolResult, ToolSpec};
+pub use traits::{prompt_entry, ProposedEdit, ToolResult, ToolSpec};
 
 use crate::memory::Memory;
 use crate::runtime::{NativeRuntime, RuntimeAdapter};
+use crate::security::redact::Redactor;
 use crate::security::SecurityPolicy;
 use std::sync::Arc;
 
@@ -52,6 +73,7 @@ pub fn default_tools_with_runtime(
         Box::new(FileReadTool::new(security.clone())),
         Box::new(FileWriteTool::new(security.clone())),
         Box::new(FilePatchTool::new(security.clone())),
+        Box::new(FileMultiPatchTool::new(security.clone())),
         Box::new(SearchFilesTool::new(security)),
     ]
 }
@@ -62,6 +84,7 @@ pub fn all_tools(
     memory: Arc<dyn Memory>,
     composio_key: Option<&str>,
     browser_config: &crate::config::BrowserConfig,
+    check_config: &crate::config::CheckConfig,
 ) -> Vec<Box<dyn Tool>> {
     all_tools_with_runtime(
         security,
@@ -69,6 +92,7 @@ pub fn all_tools(
         memory,
         composio_key,
         browser_config,
+        check_config,
     )
 }
 
@@ -79,14 +103,18 @@ pub fn all_tools_with_runtime(
     memory: Arc<dyn Memory>,
     composio_key: Option<&str>,
     browser_config: &crate::config::BrowserConfig,
+    check_config: &crate::config::CheckConfig,
 ) -> Vec<Box<dyn Tool>> {
     let mut tools: Vec<Box<dyn Tool>> = vec![
         Box::new(ShellTool::new(security.clone(), runtime)),
         Box::new(FileReadTool::new(security.clone())),
         Box::new(FileWriteTool::new(security.clone())),
         Box::new(FilePatchTool::new(security.clone())),
+        Box::new(FileMultiPatchTool::new(security.clone())),
         Box::new(SearchFilesTool::new(security.clone())),
-        Box::new(WebFetchTool::new()),
+        Box::new(GitTool::new(security.clone())),
+        Box::new(CheckTool::new(security.clone(), check_config.clone())),
+        Box::new(WebFetchTool::new(security.clone())),
         Box::new(MemoryStoreTool::new(memory.clone())),
         Box::new(MemoryRecallTool::new(memory.clone())),
         Box::new(MemoryForgetTool::new(memory)),
@@ -109,6 +137,12 @@ pub fn all_tools_with_runtime(
     // Vision tools are always available
     tools.push(Box::new(ScreenshotTool::new(security.clone())));
     tools.push(Box::new(ImageInfoTool::new(security.clone())));
+    #[cfg(feature = "plot-feature")]
+    tools.push(Box::new(PlotTool::new(security.clone())));
+
+    // Self-reminders need the daemon's scheduler to fire
+    #[cfg(feature = "daemon-feature")]
+    tools.push(Box::new(ScheduleReminderTool::new(security.clone())));
 
     if let Some(key) = composio_key {
         if !key.is_empty() {
@@ -119,17 +153,32 @@ pub fn all_tools_with_runtime(
     tools
 }
 
+/// Wrap every tool in a [`RedactedTool`] when `security.redact_secrets` is on.
+pub fn with_redaction(
+    tools: Vec<Box<dyn Tool>>,
+    config: &crate::config::SecurityConfig,
+) -> Vec<Box<dyn Tool>> {
+    let Some(redactor) = Redactor::from_config(config) else {
+        return tools;
+    };
+    let redactor = Arc::new(redactor);
+    tools
+        .into_iter()
+        .map(|tool| Box::new(RedactedTool::new(tool, redactor.clone())) as Box<dyn Tool>)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{BrowserConfig, MemoryConfig};
+    use crate::config::{BrowserConfig, CheckConfig, MemoryConfig};
     use tempfile::TempDir;
 
     #[test]
-    fn default_tools_has_three() {
+    fn default_tools_has_six() {
         let security = Arc::new(SecurityPolicy::default());
         let tools = default_tools(security);
-        assert_eq!(tools.len(), 5);
+        assert_eq!(tools.len(), 6);
     }
 
     #[test]
@@ -149,7 +198,7 @@ mod tests {
             session_name: None,
         };
 
-        let tools = all_tools(&security, mem, None, &browser);
+        let tools = all_tools(&security, mem, None, &browser, &CheckConfig::default());
         let names: Vec<&str> = tools.iter().map(|t| t.name()).collect();
         assert!(!names.contains(&"browser_open"));
     }
@@ -171,7 +220,7 @@ mod tests {
             session_name: None,
         };
 
-        let tools = all_tools(&security, mem, None, &browser);
+        let tools = all_tools(&security, mem, None, &browser, &CheckConfig::default());
         let names: Vec<&str> = tools.iter().map(|t| t.name()).collect();
         assert!(names.contains(&"browser_open"));
     }
@@ -0,0 +1,435 @@
+use super::file_write::resolve_write_target;
+use super::traits::{Tool, ToolResult};
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use plotters::prelude::*;
+use serde::Deserialize;
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+
+const DEFAULT_SIZE: (u32, u32) = (800, 600);
+const MAX_SIDE: u32 = 4000;
+const MAX_SERIES: usize = 20;
+const MAX_POINTS: usize = 10_000;
+
+/// Renders a line or bar chart from data series to an SVG file in the
+/// workspace.
+pub struct PlotTool {
+    security: Arc<SecurityPolicy>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ChartKind {
+    Line,
+    Bar,
+}
+
+#[derive(Debug, Deserialize)]
+struct Series {
+    #[serde(default)]
+    name: Option<String>,
+    values: Vec<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlotSpec {
+    path: String,
+    #[serde(rename = "type")]
+    kind: ChartKind,
+    series: Vec<Series>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    x_label: Option<String>,
+    #[serde(default)]
+    y_label: Option<String>,
+    #[serde(default)]
+    width: Option<u32>,
+    #[serde(default)]
+    height: Option<u32>,
+}
+
+impl PlotSpec {
+    fn validate(&self) -> Result<(), String> {
+        let is_svg = Path::new(&self.path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+        if !is_svg {
+            return Err("Only SVG output is supported; use a path ending in .svg".into());
+        }
+        if self.series.is_empty() || self.series.len() > MAX_SERIES {
+            return Err(format!("Give between 1 and {MAX_SERIES} series"));
+        }
+        for (i, series) in self.series.iter().enumerate() {
+            if series.values.is_empty() || series.values.len() > MAX_POINTS {
+                return Err(format!(
+                    "Series {} must have between 1 and {MAX_POINTS} values",
+                    i + 1
+                ));
+            }
+        }
+        for side in [self.width, self.height].into_iter().flatten() {
+            if !(100..=MAX_SIDE).contains(&side) {
+                return Err(format!("width and height must be 100-{MAX_SIDE} pixels"));
+            }
+        }
+        Ok(())
+    }
+
+    fn points(&self) -> usize {
+        self.series
+            .iter()
+            .map(|s| s.values.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Value range with some headroom; bar charts always include zero.
+    fn y_range(&self) -> (f64, f64) {
+        let values = self.series.iter().flat_map(|s| s.values.iter().copied());
+        let (mut lo, mut hi) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(v), hi.max(v))
+        });
+        if self.kind == ChartKind::Bar {
+            lo = lo.min(0.0);
+            hi = hi.max(0.0);
+        }
+        if (hi - lo).abs() < f64::EPSILON {
+            return (lo - 1.0, hi + 1.0);
+        }
+        let pad = (hi - lo) * 0.05;
+        let lo = if self.kind == ChartKind::Bar && lo == 0.0 {
+            0.0
+        } else {
+            lo - pad
+        };
+        (lo, hi + pad)
+    }
+
+    /// The label for x position `x`, when it falls on a data point.
+    fn x_label_at(&self, x: f64) -> String {
+        let rounded = x.round();
+        if (x - rounded).abs() > 1e-6 || rounded < 0.0 {
+            return String::new();
+        }
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let index = rounded as usize;
+        match self.labels.get(index) {
+            Some(label) => label.clone(),
+            None if index < self.points() => (index + 1).to_string(),
+            None => String::new(),
+        }
+    }
+}
+
+/// Draw `spec` as an SVG document.
+fn render_svg(spec: &PlotSpec) -> Result<String, String> {
+    let size = (
+        spec.width.unwrap_or(DEFAULT_SIZE.0),
+        spec.height.unwrap_or(DEFAULT_SIZE.1),
+    );
+    let points = spec.points();
+    #[allow(clippy::cast_precision_loss)]
+    let last = (points - 1) as f64;
+    let x_range = match spec.kind {
+        ChartKind::Line if points > 1 => 0.0..last,
+        _ => -0.5..last + 0.5,
+    };
+    let (y_min, y_max) = spec.y_range();
+
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, size).into_drawing_area();
+        root.fill(&WHITE).map_err(|e| e.to_string())?;
+        let mut builder = ChartBuilder::on(&root);
+        builder
+            .margin(16)
+            .x_label_area_size(40)
+            .y_label_area_size(60);
+        if let Some(title) = &spec.title {
+            builder.caption(title, ("sans-serif", 24));
+        }
+        let mut chart = builder
+            .build_cartesian_2d(x_range, y_min..y_max)
+            .map_err(|e| e.to_string())?;
+
+        let x_formatter = |x: &f64| spec.x_label_at(*x);
+        let mut mesh = chart.configure_mesh();
+        mesh.x_labels(points.min(24))
+            .x_label_formatter(&x_formatter);
+        if let Some(label) = &spec.x_label {
+            mesh.x_desc(label);
+        }
+        if let Some(label) = &spec.y_label {
+            mesh.y_desc(label);
+        }
+        mesh.draw().map_err(|e| e.to_string())?;
+
+        #[allow(clippy::cast_precision_loss)]
+        let bar_width = 0.8 / spec.series.len() as f64;
+        for (i, series) in spec.series.iter().enumerate() {
+            let color = Palette99::pick(i).to_rgba();
+            #[allow(clippy::cast_precision_loss)]
+            let drawn = match spec.kind {
+                ChartKind::Line => chart.draw_series(LineSeries::new(
+                    series
+                        .values
+                        .iter()
+                        .enumerate()
+                        .map(|(x, y)| (x as f64, *y)),
+                    color.stroke_width(2),
+                )),
+                ChartKind::Bar => {
+                    chart.draw_series(series.values.iter().enumerate().map(|(x, y)| {
+                        let left = x as f64 - 0.4 + bar_width * i as f64;
+                        Rectangle::new([(left, 0.0), (left + bar_width, *y)], color.filled())
+                    }))
+                }
+            }
+            .map_err(|e| e.to_string())?;
+            if let Some(name) = &series.name {
+                drawn.label(name).legend(move |(x, y)| {
+                    Rectangle::new([(x, y - 5), (x + 16, y + 5)], color.filled())
+                });
+            }
+        }
+
+        if spec.series.iter().any(|s| s.name.is_some()) {
+            chart
+                .configure_series_labels()
+                .background_style(WHITE.mix(0.8))
+                .border_style(BLACK)
+                .draw()
+                .map_err(|e| e.to_string())?;
+        }
+        root.present().map_err(|e| e.to_string())?;
+    }
+    Ok(svg)
+}
+
+impl PlotTool {
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self { security }
+    }
+
+    async fn plot(&self, args: serde_json::Value) -> Result<String, String> {
+        let spec: PlotSpec =
+            serde_json::from_value(args).map_err(|e| format!("Invalid plot spec: {e}"))?;
+        spec.validate()?;
+        if !self.security.is_path_allowed(&spec.path) {
+            return Err(format!(
+                "Path not allowed by security policy: {}",
+                spec.path
+            ));
+        }
+
+        let svg = render_svg(&spec)?;
+        let target = resolve_write_target(&self.security, &spec.path).await?;
+        tokio::fs::write(&target, &svg)
+            .await
+            .map_err(|e| format!("Failed to write chart: {e}"))?;
+
+        let kind = match spec.kind {
+            ChartKind::Line => "line",
+            ChartKind::Bar => "bar",
+        };
+        Ok(format!(
+            "Saved {kind} chart to {} ({} series, {} points, {} bytes SVG)",
+            spec.path,
+            spec.series.len(),
+            spec.points(),
+            svg.len()
+        ))
+    }
+}
+
+#[async_trait]
+impl Tool for PlotTool {
+    fn name(&self) -> &str {
+        "plot"
+    }
+
+    fn description(&self) -> &str {
+        "Render a line or bar chart from data series and save it as an SVG file in the workspace; returns the saved path"
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Workspace-relative output path ending in .svg (e.g. 'charts/sales.svg'); missing directories are created"
+                },
+                "type": {
+                    "type": "string",
+                    "enum": ["line", "bar"],
+                    "description": "'line' connects each series' points; 'bar' draws grouped bars per x position"
+                },
+                "series": {
+                    "type": "array",
+                    "description": "Data to plot (1-20 series). Value i of every series is drawn at x position i",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": {
+                                "type": "string",
+                                "description": "Legend entry; the legend is shown when any series is named"
+                            },
+                            "values": {
+                                "type": "array",
+                                "items": {"type": "number"},
+                                "description": "Y values in x order (1-10000)"
+                            }
+                        },
+                        "required": ["values"]
+                    }
+                },
+                "labels": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "X-axis label for each position (e.g. months); defaults to 1, 2, 3..."
+                },
+                "title": {"type": "string", "description": "Chart title"},
+                "x_label": {"type": "string", "description": "X-axis caption"},
+                "y_label": {"type": "string", "description": "Y-axis caption"},
+                "width": {"type": "integer", "description": "Image width in pixels, 100-4000 (default 800)"},
+                "height": {"type": "integer", "description": "Image height in pixels, 100-4000 (default 600)"}
+            },
+            "required": ["path", "type", "series"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        if !self.security.can_act() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Action blocked: autonomy is read-only".into()),
+            });
+        }
+        Ok(match self.plot(args).await {
+            Ok(output) => ToolResult {
+                success: true,
+                output,
+                error: None,
+            },
+            Err(e) => ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(e),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+    use tempfile::TempDir;
+
+    fn tool(workspace: &Path, autonomy: AutonomyLevel) -> PlotTool {
+        PlotTool::new(Arc::new(SecurityPolicy {
+            autonomy,
+            workspace_dir: workspace.to_path_buf(),
+            ..SecurityPolicy::default()
+        }))
+    }
+
+    #[tokio::test]
+    async fn line_chart_is_written_as_svg() {
+        let dir = TempDir::new().unwrap();
+        let result = tool(dir.path(), AutonomyLevel::Supervised)
+            .execute(json!({
+                "path": "charts/sales.svg",
+                "type": "line",
+                "title": "Monthly sales",
+                "labels": ["Jan", "Feb", "Mar"],
+                "series": [
+                    {"name": "2025", "values": [3.0, 5.5, 4.0]},
+                    {"name": "2026", "values": [4.0, 6.0, 7.5]}
+                ]
+            }))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result
+            .output
+            .starts_with("Saved line chart to charts/sales.svg"));
+
+        let svg = std::fs::read_to_string(dir.path().join("charts/sales.svg")).unwrap();
+        assert!(svg.starts_with("<svg"), "{svg}");
+        assert!(svg.contains("Monthly sales"));
+        assert!(svg.contains("Feb"));
+        assert!(svg.contains("<polyline"));
+    }
+
+    #[tokio::test]
+    async fn bar_chart_draws_one_rect_per_value() {
+        let dir = TempDir::new().unwrap();
+        let result = tool(dir.path(), AutonomyLevel::Supervised)
+            .execute(json!({
+                "path": "bars.svg",
+                "type": "bar",
+                "series": [{"values": [1, 2, 3]}, {"values": [2, 1]}]
+            }))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        let svg = std::fs::read_to_string(dir.path().join("bars.svg")).unwrap();
+        // Five bars besides the white background
+        let bars = svg.matches("<rect").count() - svg.matches(r##"fill="#FFFFFF""##).count();
+        assert_eq!(bars, 5, "{svg}");
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_specs_and_paths() {
+        let dir = TempDir::new().unwrap();
+        let tool = tool(dir.path(), AutonomyLevel::Supervised);
+        let cases = [
+            (
+                json!({"path": "a.png", "type": "line", "series": [{"values": [1]}]}),
+                "SVG",
+            ),
+            (
+                json!({"path": "a.svg", "type": "pie", "series": [{"values": [1]}]}),
+                "Invalid plot spec",
+            ),
+            (
+                json!({"path": "a.svg", "type": "line", "series": []}),
+                "series",
+            ),
+            (
+                json!({"path": "a.svg", "type": "line", "series": [{"values": []}]}),
+                "Series 1",
+            ),
+            (
+                json!({"path": "../a.svg", "type": "line", "series": [{"values": [1]}]}),
+                "not allowed",
+            ),
+        ];
+        for (args, expected) in cases {
+            let result = tool.execute(args).await.unwrap();
+            assert!(!result.success);
+            let error = result.error.unwrap();
+            assert!(error.contains(expected), "{error}");
+        }
+        assert!(!dir.path().join("a.svg").exists());
+    }
+
+    #[tokio::test]
+    async fn read_only_autonomy_blocks_plotting() {
+        let dir = TempDir::new().unwrap();
+        let result = tool(dir.path(), AutonomyLevel::ReadOnly)
+            .execute(json!({"path": "a.svg", "type": "line", "series": [{"values": [1]}]}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(!dir.path().join("a.svg").exists());
+    }
+}
@@ -0,0 +1,264 @@
+use super::traits::{Tool, ToolResult};
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use serde_json::json;
+use std::sync::Arc;
+
+/// Reminders further out than this are rejected.
+const MAX_AHEAD_DAYS: i64 = 365;
+
+/// Let the agent schedule a one-shot follow-up: at `when` the daemon's
+/// scheduler re-invokes the agent with `prompt`.
+pub struct ScheduleReminderTool {
+    security: Arc<SecurityPolicy>,
+}
+
+impl ScheduleReminderTool {
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self { security }
+    }
+
+    fn failure(message: impl Into<String>) -> ToolResult {
+        ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(message.into()),
+        }
+    }
+}
+
+/// Resolve `when` to an absolute time. Accepts relative offsets such as
+/// `in 2h`, `30m` or `1h 30m`, RFC 3339 timestamps, and `YYYY-MM-DD HH:MM`
+/// (read as UTC).
+pub fn parse_when(when: &str, now: DateTime<Utc>) -> anyhow::Result<DateTime<Utc>> {
+    let when = when.trim();
+    if let Ok(ts) = DateTime::parse_from_rfc3339(when) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(when, format) {
+            return Ok(naive.and_utc());
+        }
+    }
+    parse_relative(when)
+        .map(|offset| now + offset)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unrecognised time '{when}': use a relative offset like 'in 2h' or '45m', \
+                 or a timestamp like '2025-06-01T09:00:00Z'"
+            )
+        })
+}
+
+/// Parse `in 1h 30m` style offsets. Returns `None` on anything unexpected.
+fn parse_relative(text: &str) -> Option<Duration> {
+    let text = text.strip_prefix("in ").unwrap_or(text).trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    let mut total = Duration::zero();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            return None;
+        }
+        let amount: i64 = rest[..digits].parse().ok()?;
+        rest = rest[digits..].trim_start();
+
+        let unit_len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let unit = match &rest[..unit_len] {
+            "s" | "sec" | "secs" | "second" | "seconds" => Duration::try_seconds(amount)?,
+            "m" | "min" | "mins" | "minute" | "minutes" => Duration::try_minutes(amount)?,
+            "h" | "hr" | "hrs" | "hour" | "hours" => Duration::try_hours(amount)?,
+            "d" | "day" | "days" => Duration::try_days(amount)?,
+            "w" | "week" | "weeks" => Duration::try_weeks(amount)?,
+            _ => return None,
+        };
+        total = total.checked_add(&unit)?;
+        rest = rest[unit_len..].trim_start_matches([' ', ',']);
+        rest = rest.strip_prefix("and ").unwrap_or(rest);
+    }
+    Some(total)
+}
+
+#[async_trait]
+impl Tool for ScheduleReminderTool {
+    fn name(&self) -> &str {
+        "schedule_reminder"
+    }
+
+    fn description(&self) -> &str {
+        "Schedule a follow-up: at the given time the agent is run again with the stored prompt. Use to check back later on long-running work. Requires the daemon to be running."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "when": {
+                    "type": "string",
+                    "description": "When to run: relative ('in 2h', '30m', '1h 30m') or a timestamp ('2025-06-01T09:00:00Z')"
+                },
+                "prompt": {
+                    "type": "string",
+                    "description": "The message the agent will receive at that time"
+                }
+            },
+            "required": ["when", "prompt"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let when = args
+            .get("when")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'when' parameter"))?;
+        let prompt = args
+            .get("prompt")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'prompt' parameter"))?;
+
+        if !self.security.can_act() {
+            return Ok(Self::failure(
+                "Scheduling reminders is not allowed in read-only mode",
+            ));
+        }
+
+        let now = Utc::now();
+        let run_at = match parse_when(when, now) {
+            Ok(run_at) => run_at,
+            Err(e) => return Ok(Self::failure(e.to_string())),
+        };
+        if run_at <= now {
+            return Ok(Self::failure(format!(
+                "Reminder time {} is in the past",
+                run_at.to_rfc3339()
+            )));
+        }
+        if run_at > now + Duration::days(MAX_AHEAD_DAYS) {
+            return Ok(Self::failure(format!(
+                "Reminder time is more than {MAX_AHEAD_DAYS} days ahead"
+            )));
+        }
+
+        if !self.security.record_action() {
+            return Ok(Self::failure(
+                "Rate limit exceeded: action budget exhausted",
+            ));
+        }
+
+        match crate::cron::add_agent_reminder(&self.security.workspace_dir, run_at, prompt) {
+            Ok(job) => Ok(ToolResult {
+                success: true,
+                output: format!(
+                    "Reminder {} scheduled for {}",
+                    job.id,
+                    job.next_run.to_rfc3339()
+                ),
+                error: None,
+            }),
+            Err(e) => Ok(Self::failure(format!("Failed to schedule reminder: {e}"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+    use tempfile::TempDir;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2025-06-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parses_relative_times() {
+        let now = now();
+        assert_eq!(parse_when("in 2h", now).unwrap(), now + Duration::hours(2));
+        assert_eq!(parse_when("45m", now).unwrap(), now + Duration::minutes(45));
+        assert_eq!(
+            parse_when("in 1h 30m", now).unwrap(),
+            now + Duration::minutes(90)
+        );
+        assert_eq!(
+            parse_when("in 2 days and 3 hours", now).unwrap(),
+            now + Duration::hours(51)
+        );
+        assert_eq!(parse_when("1w", now).unwrap(), now + Duration::days(7));
+
+        for bad in ["", "in", "soon", "in 2", "2 fortnights", "in -1h", "h2"] {
+            assert!(parse_when(bad, now).is_err(), "{bad:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn parses_absolute_times() {
+        let now = now();
+        let expected = DateTime::parse_from_rfc3339("2025-06-02T09:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(parse_when("2025-06-02T09:30:00Z", now).unwrap(), expected);
+        assert_eq!(
+            parse_when("2025-06-02T11:30:00+02:00", now).unwrap(),
+            expected
+        );
+        assert_eq!(parse_when("2025-06-02 09:30", now).unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn schedules_reminder_in_cron_store() {
+        let tmp = TempDir::new().unwrap();
+        let security = Arc::new(SecurityPolicy {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        });
+        let tool = ScheduleReminderTool::new(security);
+
+        let result = tool
+            .execute(json!({"when": "in 2h", "prompt": "check the deploy"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+
+        let past = tool
+            .execute(json!({"when": "2000-01-01T00:00:00Z", "prompt": "too late"}))
+            .await
+            .unwrap();
+        assert!(!past.success);
+
+        let config = crate::config::Config {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..crate::config::Config::default()
+        };
+        let jobs = crate::cron::list_jobs(&config).unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].agent_prompt(), Some("check the deploy"));
+    }
+
+    #[tokio::test]
+    async fn read_only_mode_blocks_scheduling() {
+        let tmp = TempDir::new().unwrap();
+        let security = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::ReadOnly,
+            workspace_dir: tmp.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        });
+        let tool = ScheduleReminderTool::new(security);
+        let result = tool
+            .execute(json!({"when": "in 1h", "prompt": "hi"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(!tmp.path().join("cron").exists());
+    }
+}
@@ -2,8 +2,16 @@ use super::traits::{Tool, ToolResult};
 use crate::security::SecurityPolicy;
 use async_trait::async_trait;
 use serde_json::json;
+use std::collections::HashSet;
 use std::fmt::Write;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
+
+/// Directory names always skipped, on top of hidden entries.
+const DEFAULT_IGNORES: &[&str] = &["target", "node_modules"];
+/// Deepest recursion allowed, whatever `max_depth` asks for.
+const MAX_DEPTH_LIMIT: usize = 64;
 
 /// Regex search across workspace files
 pub struct SearchFilesTool {
@@ -41,12 +49,35 @@ impl Tool for SearchFilesTool {
                 "glob": {
                     "type": "string",
                     "description": "File glob filter, e.g. '*.rs' or '*.py'"
+                },
+                "max_depth": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "How many directory levels below the search path to descend (0 = only its own files, default: 64)"
+                },
+                "ignore": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Extra file/directory name globs to skip, added to the defaults (hidden entries, target, node_modules)"
                 }
             },
             "required": ["pattern"]
         })
     }
 
+    fn examples(&self) -> Vec<serde_json::Value> {
+        vec![
+            json!({"pattern": "fn main", "glob": "*.rs"}),
+            json!({"pattern": "TODO|FIXME", "path": "src", "max_depth": 2}),
+        ]
+    }
+
+    /// Results are reused until a tool with side effects runs.
+    fn cache_ttl(&self, _args: &serde_json::Value) -> Option<Duration> {
+        Some(Duration::MAX)
+    }
+
+    #[allow(clippy::too_many_lines)]
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let pattern = args
             .get("pattern")
@@ -55,6 +86,26 @@ impl Tool for SearchFilesTool {
 
         let subdir = args.get("path").and_then(|v| v.as_str()).unwrap_or(".");
         let glob_filter = args.get("glob").and_then(|v| v.as_str());
+        #[allow(clippy::cast_possible_truncation)]
+        let max_depth = args
+            .get("max_depth")
+            .and_then(serde_json::Value::as_u64)
+            .map_or(MAX_DEPTH_LIMIT, |d| (d as usize).min(MAX_DEPTH_LIMIT));
+
+        let mut ignore = Vec::new();
+        let extra = args.get("ignore").and_then(|v| v.as_array());
+        for name in extra.into_iter().flatten().filter_map(|v| v.as_str()) {
+            match glob::Pattern::new(name) {
+                Ok(p) => ignore.push(p),
+                Err(e) => {
+                    return Ok(ToolResult {
+                        success: false,
+                        output: String::new(),
+                        error: Some(format!("Invalid ignore pattern '{name}': {e}")),
+                    });
+                }
+            }
+        }
 
         if !self.security.is_path_allowed(subdir) {
             return Ok(ToolResult {
@@ -97,16 +148,38 @@ impl Tool for SearchFilesTool {
 
         let glob_pat = glob_filter.map(|g| glob::Pattern::new(g).ok()).flatten();
 
-        let mut results = String::new();
-        let mut match_count: usize = 0;
         const MAX_MATCHES: usize = 100;
-
-        search_recursive(&resolved, &re, &glob_pat, &mut results, &mut match_count, MAX_MATCHES).await;
+        let mut search = Search {
+            re: &re,
+            glob_pat,
+            ignore,
+            security: &self.security,
+            max_depth,
+            max_matches: MAX_MATCHES,
+            results: String::new(),
+            match_count: 0,
+            visited: HashSet::new(),
+            depth_limited: false,
+        };
+        search.visited.insert(resolved.clone());
+        search.walk(&resolved, 0).await;
+        let Search {
+            results,
+            match_count,
+            depth_limited,
+            ..
+        } = search;
+
+        let depth_note = if depth_limited {
+            format!("\n(max_depth {max_depth} reached; deeper directories were not searched)")
+        } else {
+            String::new()
+        };
 
         if match_count == 0 {
             return Ok(ToolResult {
                 success: true,
-                output: "No matches found.".into(),
+                output: format!("No matches found.{depth_note}"),
                 error: None,
             });
         }
@@ -119,65 +192,100 @@ impl Tool for SearchFilesTool {
 
         Ok(ToolResult {
             success: true,
-            output: format!("{match_count} matches:{truncated}\n{results}"),
+            output: format!("{match_count} matches:{truncated}{depth_note}\n{results}"),
             error: None,
         })
     }
 }
 
-async fn search_recursive(
-    dir: &std::path::Path,
-    re: &regex::Regex,
-    glob_pat: &Option<glob::Pattern>,
-    results: &mut String,
-    match_count: &mut usize,
-    max: usize,
-) {
-    let mut entries = match tokio::fs::read_dir(dir).await {
-        Ok(e) => e,
-        Err(_) => return,
-    };
-
-    while let Ok(Some(entry)) = entries.next_entry().await {
-        if *match_count >= max {
+/// State for one recursive search.
+struct Search<'a> {
+    re: &'a regex::Regex,
+    glob_pat: Option<glob::Pattern>,
+    /// Extra name globs from the `ignore` parameter
+    ignore: Vec<glob::Pattern>,
+    security: &'a SecurityPolicy,
+    max_depth: usize,
+    max_matches: usize,
+    results: String,
+    match_count: usize,
+    /// Canonical directories already searched, so symlink loops end
+    visited: HashSet<PathBuf>,
+    /// Set when a directory was skipped for being below `max_depth`
+    depth_limited: bool,
+}
+
+impl Search<'_> {
+    fn is_ignored(&self, name: &str) -> bool {
+        name.starts_with('.')
+            || DEFAULT_IGNORES.contains(&name)
+            || self.ignore.iter().any(|p| p.matches(name))
+    }
+
+    async fn walk(&mut self, dir: &Path, depth: usize) {
+        let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
             return;
-        }
+        };
 
-        let path = entry.path();
-        let file_name = entry.file_name();
-        let name = file_name.to_string_lossy();
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if self.match_count >= self.max_matches {
+                return;
+            }
 
-        // Skip hidden files/dirs and common non-text dirs
-        if name.starts_with('.') || name == "target" || name == "node_modules" {
-            continue;
-        }
+            let path = entry.path();
+            let file_name = entry.file_name();
+            let name = file_name.to_string_lossy();
+            if self.is_ignored(&name) {
+                continue;
+            }
 
-        if let Ok(ft) = entry.file_type().await {
-            if ft.is_dir() {
-                Box::pin(search_recursive(&path, re, glob_pat, results, match_count, max)).await;
-            } else if ft.is_file() {
-                if let Some(ref pat) = glob_pat {
+            // Follow symlinks, but only to real paths inside the workspace
+            let Ok(resolved) = tokio::fs::canonicalize(&path).await else {
+                continue;
+            };
+            if !self.security.is_resolved_path_allowed(&resolved) {
+                continue;
+            }
+            let Ok(meta) = tokio::fs::metadata(&resolved).await else {
+                continue;
+            };
+
+            if meta.is_dir() {
+                if depth >= self.max_depth {
+                    self.depth_limited = true;
+                    continue;
+                }
+                if !self.visited.insert(resolved) {
+                    continue;
+                }
+                Box::pin(self.walk(&path, depth + 1)).await;
+            } else if meta.is_file() {
+                if let Some(ref pat) = self.glob_pat {
                     if !pat.matches(&name) {
                         continue;
                     }
                 }
 
                 // Skip binary/large files
-                if let Ok(meta) = entry.metadata().await {
-                    if meta.len() > 1_000_000 {
-                        continue;
-                    }
+                if meta.len() > 1_000_000 {
+                    continue;
                 }
 
                 if let Ok(content) = tokio::fs::read_to_string(&path).await {
                     let rel = path.strip_prefix(dir).unwrap_or(&path);
                     for (line_num, line) in content.lines().enumerate() {
-                        if *match_count >= max {
+                        if self.match_count >= self.max_matches {
                             return;
                         }
-                        if re.is_match(line) {
-                            let _ = writeln!(results, "{}:{}: {}", rel.display(), line_num + 1, line);
-                            *match_count += 1;
+                        if self.re.is_match(line) {
+                            let _ = writeln!(
+                                self.results,
+                                "{}:{}: {}",
+                                rel.display(),
+                                line_num + 1,
+                                line
+                            );
+                            self.match_count += 1;
                         }
                     }
                 }
@@ -284,4 +392,81 @@ mod tests {
 
         let _ = tokio::fs::remove_dir_all(&dir).await;
     }
+
+    async fn nested_workspace(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        tokio::fs::create_dir_all(dir.join("l1/l2")).await.unwrap();
+        tokio::fs::write(dir.join("top.txt"), "needle 0")
+            .await
+            .unwrap();
+        tokio::fs::write(dir.join("l1/mid.txt"), "needle 1")
+            .await
+            .unwrap();
+        tokio::fs::write(dir.join("l1/l2/deep.txt"), "needle 2")
+            .await
+            .unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn search_files_max_depth_excludes_deeper_matches() {
+        let dir = nested_workspace("tinyclaw_test_search_depth").await;
+        let tool = SearchFilesTool::new(test_security(dir.clone()));
+
+        let result = tool
+            .execute(json!({"pattern": "needle", "max_depth": 1}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert!(result.output.starts_with("2 matches"), "{}", result.output);
+        assert!(!result.output.contains("deep.txt"));
+        assert!(result.output.contains("max_depth 1 reached"));
+
+        let result = tool.execute(json!({"pattern": "needle"})).await.unwrap();
+        assert!(result.output.starts_with("3 matches"), "{}", result.output);
+        assert!(!result.output.contains("max_depth"));
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn search_files_extra_ignore_patterns() {
+        let dir = nested_workspace("tinyclaw_test_search_ignore").await;
+        let tool = SearchFilesTool::new(test_security(dir.clone()));
+
+        let result = tool
+            .execute(json!({"pattern": "needle", "ignore": ["l2", "top.*"]}))
+            .await
+            .unwrap();
+        assert!(result.output.starts_with("1 matches"), "{}", result.output);
+        assert!(result.output.contains("mid.txt"));
+
+        let result = tool
+            .execute(json!({"pattern": "needle", "ignore": ["[oops"]}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn search_files_survives_symlink_loop() {
+        let dir = nested_workspace("tinyclaw_test_search_loop").await;
+        std::os::unix::fs::symlink(&dir, dir.join("l1/l2/back_to_root")).unwrap();
+        let tool = SearchFilesTool::new(test_security(dir.clone()));
+
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            tool.execute(json!({"pattern": "needle"})),
+        )
+        .await
+        .expect("search should not loop")
+        .unwrap();
+        assert!(result.output.starts_with("3 matches"), "{}", result.output);
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
 }
@@ -1,5 +1,7 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::time::Duration;
 
 /// Result of a tool execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,6 +11,16 @@ pub struct ToolResult {
     pub error: Option<String>,
 }
 
+/// A file change a tool call would make, shown to the user for approval.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProposedEdit {
+    /// Path as the model gave it, relative to the workspace
+    pub path: String,
+    /// Current contents, empty for a new file
+    pub before: String,
+    pub after: String,
+}
+
 /// Description of a tool for the LLM
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolSpec {
@@ -32,6 +44,72 @@ pub trait Tool: Send + Sync {
     /// Execute the tool with given arguments
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult>;
 
+    /// Check arguments against `parameters_schema` (required fields, types,
+    /// enums). The error lists every problem so the model can fix its call.
+    fn validate_args(&self, args: &serde_json::Value) -> Result<(), String> {
+        validate_against_schema(&self.parameters_schema(), args)
+    }
+
+    /// Validate, then execute. Invalid arguments come back as a failed
+    /// [`ToolResult`] without running the tool.
+    async fn execute_validated(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        if let Err(problems) = self.validate_args(&args) {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("Invalid arguments for {}: {problems}", self.name())),
+            });
+        }
+        self.execute(args).await
+    }
+
+    /// How long the result of this exact call may be reused within a
+    /// session instead of running the tool again. `None` (the default)
+    /// means never; only read-only tools should return `Some`.
+    fn cache_ttl(&self, _args: &serde_json::Value) -> Option<Duration> {
+        None
+    }
+
+    /// Whether this exact call only reads, so it may be started before the
+    /// reply asking for it has finished streaming and be cancelled if the
+    /// final reply drops it. Defaults to whether the result is cacheable.
+    fn side_effect_free(&self, args: &serde_json::Value) -> bool {
+        self.cache_ttl(args).is_some()
+    }
+
+    /// Characters of output the TUI shows for a call to this tool, when it
+    /// differs from the usual 120. `[tui] tool_preview_len` overrides it.
+    fn preview_len(&self) -> Option<usize> {
+        None
+    }
+
+    /// Whether calls to this tool may run alongside the other calls of the
+    /// same reply. When any call in a reply is to a tool returning `false`
+    /// (tools that change files), the reply's calls run one at a time in the
+    /// order the model wrote them.
+    fn parallel_safe(&self) -> bool {
+        true
+    }
+
+    /// The file change this call would make, worked out without writing
+    /// anything. `None` for tools that don't edit files and for calls that
+    /// would fail before touching the file.
+    async fn proposed_edit(&self, _args: &serde_json::Value) -> Option<ProposedEdit> {
+        None
+    }
+
+    /// Every file change this call would make; tools that edit several files
+    /// at once override this instead of [`Tool::proposed_edit`].
+    async fn proposed_edits(&self, args: &serde_json::Value) -> Vec<ProposedEdit> {
+        self.proposed_edit(args).await.into_iter().collect()
+    }
+
+    /// Sample arguments, shown to the model as complete `<tool_call>`s
+    /// under the schema. Each must pass [`Tool::validate_args`].
+    fn examples(&self) -> Vec<serde_json::Value> {
+        Vec::new()
+    }
+
     /// Get the full spec for LLM registration
     fn spec(&self) -> ToolSpec {
         ToolSpec {
@@ -41,3 +119,240 @@ pub trait Tool: Send + Sync {
         }
     }
 }
+
+/// The system prompt entry for `tool`: name, description, parameter schema
+/// and one line per [`Tool::examples`] entry.
+pub fn prompt_entry(tool: &dyn Tool) -> String {
+    let mut entry = format!(
+        "**{}**: {}\nParameters: `{}`\n",
+        tool.name(),
+        tool.description(),
+        tool.parameters_schema()
+    );
+    for arguments in tool.examples() {
+        let call = serde_json::json!({"name": tool.name(), "arguments": arguments});
+        let _ = writeln!(entry, "Example: <tool_call>{call}</tool_call>");
+    }
+    entry
+}
+
+/// Validate `value` against the subset of JSON Schema used by tool
+/// `parameters_schema`s: `type` (single or list), `required`, `properties`,
+/// `items` and `enum`. Unknown keywords and extra properties are allowed.
+pub fn validate_against_schema(
+    schema: &serde_json::Value,
+    value: &serde_json::Value,
+) -> Result<(), String> {
+    let mut problems = Vec::new();
+    check_value(schema, value, "arguments", &mut problems);
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems.join("; "))
+    }
+}
+
+fn check_value(
+    schema: &serde_json::Value,
+    value: &serde_json::Value,
+    path: &str,
+    problems: &mut Vec<String>,
+) {
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            serde_json::Value::String(t) => vec![t.as_str()],
+            serde_json::Value::Array(ts) => ts.iter().filter_map(|t| t.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| matches_type(t, value)) {
+            problems.push(format!(
+                "{path} must be {}, got {}",
+                types.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(|e| e.as_array()) {
+        if !allowed.contains(value) {
+            let options: Vec<String> = allowed.iter().map(ToString::to_string).collect();
+            problems.push(format!(
+                "{path} must be one of {}, got {value}",
+                options.join(", ")
+            ));
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+            for name in required.iter().filter_map(|n| n.as_str()) {
+                if object.get(name).is_none_or(serde_json::Value::is_null) {
+                    problems.push(format!("missing required parameter '{name}'"));
+                }
+            }
+        }
+        if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+            for (name, property) in properties {
+                match object.get(name) {
+                    // Optional parameters may be sent as null to mean "unset"
+                    None | Some(serde_json::Value::Null) => {}
+                    Some(field) => check_value(property, field, &format!("'{name}'"), problems),
+                }
+            }
+        }
+    }
+
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (i, item) in array.iter().enumerate() {
+            check_value(items, item, &format!("{path}[{i}]"), problems);
+        }
+    }
+}
+
+fn matches_type(expected: &str, value: &serde_json::Value) -> bool {
+    match expected {
+        "string" => value.is_string(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "null" => value.is_null(),
+        // Unknown type keywords are not ours to reject
+        _ => true,
+    }
+}
+
+fn type_name(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "boolean",
+        serde_json::Value::Number(n) if n.is_f64() => "number",
+        serde_json::Value::Number(_) => "integer",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {"type": "string"},
+                "limit": {"type": "integer"},
+                "encoding": {"type": "string", "enum": ["utf8", "base64"]},
+                "args": {"type": "array", "items": {"type": "string"}},
+                "approved": {"type": "boolean"}
+            },
+            "required": ["path"]
+        })
+    }
+
+    #[test]
+    fn accepts_valid_arguments() {
+        let args = json!({
+            "path": "src/main.rs",
+            "limit": 20,
+            "encoding": "base64",
+            "args": ["-n", "5"],
+            "approved": false,
+            "extra": "ignored"
+        });
+        assert_eq!(validate_against_schema(&schema(), &args), Ok(()));
+        assert_eq!(
+            validate_against_schema(&schema(), &json!({"path": "a", "limit": null})),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn reports_missing_required_parameter() {
+        let err = validate_against_schema(&schema(), &json!({"limit": 5})).unwrap_err();
+        assert_eq!(err, "missing required parameter 'path'");
+
+        let err = validate_against_schema(&schema(), &json!({"path": null})).unwrap_err();
+        assert_eq!(err, "missing required parameter 'path'");
+    }
+
+    #[test]
+    fn reports_every_type_and_enum_problem() {
+        let args = json!({
+            "path": 42,
+            "limit": "10",
+            "encoding": "utf16",
+            "args": ["ok", 3]
+        });
+        let err = validate_against_schema(&schema(), &args).unwrap_err();
+        assert!(err.contains("'path' must be string, got integer"), "{err}");
+        assert!(err.contains("'limit' must be integer, got string"), "{err}");
+        assert!(
+            err.contains(r#"'encoding' must be one of "utf8", "base64", got "utf16""#),
+            "{err}"
+        );
+        assert!(
+            err.contains("'args'[1] must be string, got integer"),
+            "{err}"
+        );
+
+        let err = validate_against_schema(&schema(), &json!("src/main.rs")).unwrap_err();
+        assert_eq!(err, "arguments must be object, got string");
+    }
+
+    #[test]
+    fn built_in_examples_match_their_schemas() {
+        let security = std::sync::Arc::new(crate::security::SecurityPolicy::default());
+        let tools: Vec<Box<dyn Tool>> = vec![
+            Box::new(crate::tools::FilePatchTool::new(security.clone())),
+            Box::new(crate::tools::SearchFilesTool::new(security.clone())),
+            Box::new(crate::tools::WebFetchTool::new(security)),
+        ];
+        for tool in &tools {
+            assert!(
+                !tool.examples().is_empty(),
+                "{} has no examples",
+                tool.name()
+            );
+            for example in tool.examples() {
+                assert_eq!(tool.validate_args(&example), Ok(()), "{}", tool.name());
+            }
+        }
+    }
+
+    #[test]
+    fn prompt_entry_lists_examples_as_tool_calls() {
+        let security = std::sync::Arc::new(crate::security::SecurityPolicy::default());
+        let entry = prompt_entry(&crate::tools::SearchFilesTool::new(security));
+        assert!(entry.starts_with("**search_files**: "), "{entry}");
+        assert!(
+            entry.contains(r#"Example: <tool_call>{"arguments":{"glob":"*.rs","pattern":"fn main"},"name":"search_files"}</tool_call>"#),
+            "{entry}"
+        );
+
+        let no_examples = prompt_entry(&crate::tools::FileReadTool::new(std::sync::Arc::new(
+            crate::security::SecurityPolicy::default(),
+        )));
+        assert!(!no_examples.contains("Example:"));
+    }
+
+    #[tokio::test]
+    async fn execute_validated_rejects_before_running() {
+        let security = std::sync::Arc::new(crate::security::SecurityPolicy::default());
+        let tool = crate::tools::FileReadTool::new(security);
+
+        let result = tool.execute_validated(json!({})).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(
+            result.error.as_deref(),
+            Some("Invalid arguments for file_read: missing required parameter 'path'")
+        );
+    }
+}
@@ -1,16 +1,236 @@
+use super::file_write::create_parent_dirs;
 use super::traits::{Tool, ToolResult};
+use crate::security::SecurityPolicy;
+use crate::util::truncate_bytes_safe;
 use async_trait::async_trait;
+use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE, RANGE};
+use reqwest::StatusCode;
 use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
 
-/// HTTP GET tool that fetches a URL and returns the body as text
-pub struct WebFetchTool;
+/// How long a fetched page may be reused within a session
+const CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// HTTP GET tool that fetches a URL and returns the body as text, or
+/// streams it to a workspace file with `save_to`
+pub struct WebFetchTool {
+    security: Arc<SecurityPolicy>,
+}
 
 impl WebFetchTool {
-    pub fn new() -> Self {
-        Self
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self { security }
+    }
+
+    fn failure(message: impl Into<String>) -> ToolResult {
+        ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(message.into()),
+        }
+    }
+
+    /// Check `save_to` against the policy and resolve it to a real path inside
+    /// the workspace, creating missing parent directories.
+    async fn resolve_target(&self, save_to: &str) -> Result<PathBuf, String> {
+        if !self.security.is_path_allowed(save_to) {
+            return Err(format!("Path not allowed by security policy: {save_to}"));
+        }
+        let full_path = self.security.workspace_dir.join(save_to);
+        let (Some(parent), Some(file_name)) = (full_path.parent(), full_path.file_name()) else {
+            return Err("Invalid save_to path: missing file name".into());
+        };
+        create_parent_dirs(&self.security, parent).await?;
+        let resolved_parent = tokio::fs::canonicalize(parent)
+            .await
+            .map_err(|e| format!("Failed to resolve file path: {e}"))?;
+        if !self.security.is_resolved_path_allowed(&resolved_parent) {
+            return Err(format!(
+                "Resolved path escapes workspace: {}",
+                resolved_parent.display()
+            ));
+        }
+        let target = resolved_parent.join(file_name);
+        if let Ok(meta) = tokio::fs::symlink_metadata(&target).await {
+            if meta.file_type().is_symlink() {
+                return Err(format!(
+                    "Refusing to write through symlink: {}",
+                    target.display()
+                ));
+            }
+        }
+        Ok(target)
+    }
+
+    /// Stream the body of `url` into `save_to`. With `resume`, an existing
+    /// partial file is continued via a `Range` request when the server allows.
+    async fn download(&self, url: &str, save_to: &str, resume: bool) -> ToolResult {
+        if !self.security.can_act() {
+            return Self::failure("Saving downloads is not allowed in read-only mode");
+        }
+        if !self.security.record_action() {
+            return Self::failure("Rate limit exceeded: action budget exhausted");
+        }
+        let target = match self.resolve_target(save_to).await {
+            Ok(target) => target,
+            Err(e) => return Self::failure(e),
+        };
+
+        let existing = match tokio::fs::metadata(&target).await {
+            Ok(meta) if resume => meta.len(),
+            _ => 0,
+        };
+        let client = match reqwest::Client::builder()
+            .connect_timeout(std::time::Duration::from_secs(15))
+            .read_timeout(std::time::Duration::from_secs(30))
+            .build()
+        {
+            Ok(client) => client,
+            Err(e) => return Self::failure(format!("Failed to build HTTP client: {e}")),
+        };
+        let mut request = client.get(url);
+        if existing > 0 {
+            request = request.header(RANGE, format!("bytes={existing}-"));
+        }
+        let resp = match request.send().await {
+            Ok(resp) => resp,
+            Err(e) => return Self::failure(format!("Request failed: {e}")),
+        };
+
+        let status = resp.status();
+        if existing > 0 && status == StatusCode::RANGE_NOT_SATISFIABLE {
+            return ToolResult {
+                success: true,
+                output: format!("{save_to} is already complete ({existing} bytes)"),
+                error: None,
+            };
+        }
+        if !status.is_success() {
+            return Self::failure(format!("HTTP {status}"));
+        }
+        // Servers that ignore the Range header resend the whole body
+        let offset = if status == StatusCode::PARTIAL_CONTENT {
+            existing
+        } else {
+            0
+        };
+        let content_type = resp
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let limit = self.security.max_download_bytes;
+        let announced = resp
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+        if announced.is_some_and(|len| offset + len > limit) {
+            return Self::failure(format!(
+                "Download exceeds the {limit}-byte limit (max_download_bytes)"
+            ));
+        }
+
+        match stream_to_file(resp, &target, offset, limit).await {
+            Ok(written) => ToolResult {
+                success: true,
+                output: format!(
+                    "HTTP {status}\nSaved {} bytes to {save_to} ({content_type}){}",
+                    offset + written,
+                    if offset > 0 {
+                        format!(", resumed at byte {offset}")
+                    } else {
+                        String::new()
+                    }
+                ),
+                error: None,
+            },
+            Err(e) => Self::failure(e),
+        }
     }
 }
 
+/// Write the response body after the first `offset` bytes of `path`,
+/// stopping once the file would exceed `limit` bytes. The data goes to a
+/// `.part` file beside `path` that replaces it only once the download
+/// completes, so a failure leaves the existing file intact for a later resume.
+async fn stream_to_file(
+    resp: reqwest::Response,
+    path: &Path,
+    offset: u64,
+    limit: u64,
+) -> Result<u64, String> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".part");
+    let tmp = PathBuf::from(tmp);
+    let result = stream_to_temp(resp, path, &tmp, offset, limit).await;
+    let result = match result {
+        Ok(written) => tokio::fs::rename(&tmp, path)
+            .await
+            .map(|()| written)
+            .map_err(|e| format!("Failed to write {}: {e}", path.display())),
+        Err(e) => Err(e),
+    };
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&tmp).await;
+    }
+    result
+}
+
+/// Fill `tmp` with the first `offset` bytes of `path` followed by the body.
+async fn stream_to_temp(
+    mut resp: reqwest::Response,
+    path: &Path,
+    tmp: &Path,
+    offset: u64,
+    limit: u64,
+) -> Result<u64, String> {
+    if offset > 0 {
+        tokio::fs::copy(path, tmp)
+            .await
+            .map_err(|e| format!("Failed to prepare {}: {e}", tmp.display()))?;
+    }
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(offset == 0)
+        .open(tmp)
+        .await
+        .map_err(|e| format!("Failed to open {}: {e}", tmp.display()))?;
+    file.set_len(offset)
+        .await
+        .map_err(|e| format!("Failed to prepare {}: {e}", tmp.display()))?;
+    file.seek(std::io::SeekFrom::Start(offset))
+        .await
+        .map_err(|e| format!("Failed to prepare {}: {e}", tmp.display()))?;
+
+    let mut written: u64 = 0;
+    loop {
+        match resp.chunk().await {
+            Ok(Some(chunk)) => {
+                written += chunk.len() as u64;
+                if offset + written > limit {
+                    return Err(format!(
+                        "Download exceeds the {limit}-byte limit (max_download_bytes)"
+                    ));
+                }
+                file.write_all(&chunk)
+                    .await
+                    .map_err(|e| format!("Failed to write {}: {e}", tmp.display()))?;
+            }
+            Ok(None) => break,
+            Err(e) => return Err(format!("Download interrupted: {e}")),
+        }
+    }
+    file.flush().await.map_err(|e| e.to_string())?;
+    Ok(written)
+}
+
 #[async_trait]
 impl Tool for WebFetchTool {
     fn name(&self) -> &str {
@@ -18,7 +238,7 @@ impl Tool for WebFetchTool {
     }
 
     fn description(&self) -> &str {
-        "Fetch a URL via HTTP GET and return the response body as text"
+        "Fetch a URL via HTTP GET and return the response body as text. Set save_to to stream a large download into a workspace file instead (resume continues a partial file)."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -28,12 +248,33 @@ impl Tool for WebFetchTool {
                 "url": {
                     "type": "string",
                     "description": "URL to fetch"
+                },
+                "save_to": {
+                    "type": "string",
+                    "description": "Workspace-relative file to save the body to instead of returning it"
+                },
+                "resume": {
+                    "type": "boolean",
+                    "description": "With save_to, continue an existing partial file using a Range request (default: false)"
                 }
             },
             "required": ["url"]
         })
     }
 
+    fn examples(&self) -> Vec<serde_json::Value> {
+        vec![
+            json!({"url": "https://example.com/docs/install"}),
+            json!({"url": "https://example.com/data.csv", "save_to": "data/data.csv"}),
+        ]
+    }
+
+    /// Plain fetches are reused for a few minutes; downloads write a file
+    /// and always run.
+    fn cache_ttl(&self, args: &serde_json::Value) -> Option<Duration> {
+        args.get("save_to").is_none().then_some(CACHE_TTL)
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let url = args
             .get("url")
@@ -49,6 +290,14 @@ impl Tool for WebFetchTool {
             });
         }
 
+        if let Some(save_to) = args.get("save_to").and_then(|v| v.as_str()) {
+            let resume = args
+                .get("resume")
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or(false);
+            return Ok(self.download(url, save_to, resume).await);
+        }
+
         let client = reqwest::Client::builder()
             .timeout(std::time::Duration::from_secs(15))
             .build()?;
@@ -61,7 +310,7 @@ impl Tool for WebFetchTool {
                 // Truncate large responses
                 let max_len = 50_000;
                 let (body, truncated) = if body.len() > max_len {
-                    (body[..max_len].to_string(), true)
+                    (truncate_bytes_safe(&body, max_len).to_string(), true)
                 } else {
                     (body, false)
                 };
@@ -98,24 +347,65 @@ impl Tool for WebFetchTool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::security::AutonomyLevel;
+    use tempfile::TempDir;
+    use tokio::io::AsyncReadExt;
+
+    fn tool() -> WebFetchTool {
+        WebFetchTool::new(Arc::new(SecurityPolicy::default()))
+    }
+
+    fn tool_in(workspace: &Path, max_download_bytes: u64) -> WebFetchTool {
+        WebFetchTool::new(Arc::new(SecurityPolicy {
+            workspace_dir: workspace.to_path_buf(),
+            max_download_bytes,
+            ..SecurityPolicy::default()
+        }))
+    }
+
+    /// Serve `body` over plain HTTP on a local port, honouring `Range`.
+    async fn serve(body: &'static [u8]) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 4096];
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+                let start = request
+                    .lines()
+                    .find_map(|l| l.strip_prefix("range: bytes="))
+                    .and_then(|r| r.trim_end_matches('-').parse::<usize>().ok());
+                let (status, part) = match start {
+                    Some(start) => ("206 Partial Content", &body[start..]),
+                    None => ("200 OK", body),
+                };
+                let head = format!(
+                    "HTTP/1.1 {status}\r\ncontent-type: text/csv\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                    part.len()
+                );
+                let _ = socket.write_all(head.as_bytes()).await;
+                let _ = socket.write_all(part).await;
+            }
+        });
+        format!("http://{addr}/data.csv")
+    }
 
     #[test]
     fn web_fetch_name() {
-        let tool = WebFetchTool::new();
-        assert_eq!(tool.name(), "web_fetch");
+        assert_eq!(tool().name(), "web_fetch");
     }
 
     #[test]
     fn web_fetch_schema() {
-        let tool = WebFetchTool::new();
-        let schema = tool.parameters_schema();
+        let schema = tool().parameters_schema();
         assert!(schema["properties"]["url"].is_object());
+        assert!(schema["properties"]["save_to"].is_object());
     }
 
     #[tokio::test]
     async fn web_fetch_rejects_non_http() {
-        let tool = WebFetchTool::new();
-        let result = tool
+        let result = tool()
             .execute(json!({"url": "ftp://example.com"}))
             .await
             .unwrap();
@@ -125,8 +415,110 @@ mod tests {
 
     #[tokio::test]
     async fn web_fetch_missing_url() {
-        let tool = WebFetchTool::new();
-        let result = tool.execute(json!({})).await;
+        let result = tool().execute(json!({})).await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn long_multibyte_body_is_truncated_on_a_char_boundary() {
+        // 1 + 3 * 20_000 bytes; byte 50_000 falls inside a '€'
+        let body = format!("a{}", "€".repeat(20_000));
+        let url = serve(Box::leak(body.into_bytes().into_boxed_slice())).await;
+
+        let result = tool().execute(json!({"url": url})).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        let text = result
+            .output
+            .strip_prefix("HTTP 200 OK\n")
+            .and_then(|rest| rest.strip_suffix("\n... [truncated]"))
+            .unwrap();
+        assert_eq!(text, format!("a{}", "€".repeat(16_666)));
+    }
+
+    #[tokio::test]
+    async fn save_to_streams_body_to_file() {
+        let tmp = TempDir::new().unwrap();
+        let url = serve(b"id,name\n1,ada\n2,grace\n").await;
+
+        let result = tool_in(tmp.path(), 1024)
+            .execute(json!({"url": url, "save_to": "data/people.csv"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(
+            result.output.contains("Saved 22 bytes"),
+            "{}",
+            result.output
+        );
+        assert!(result.output.contains("text/csv"), "{}", result.output);
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("data/people.csv")).unwrap(),
+            "id,name\n1,ada\n2,grace\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn save_to_resumes_partial_file() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("part.csv"), "id,name\n").unwrap();
+        let url = serve(b"id,name\n1,ada\n").await;
+
+        let result = tool_in(tmp.path(), 1024)
+            .execute(json!({"url": url, "save_to": "part.csv", "resume": true}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(
+            result.output.contains("resumed at byte 8"),
+            "{}",
+            result.output
+        );
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("part.csv")).unwrap(),
+            "id,name\n1,ada\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn save_to_enforces_size_limit_and_policy() {
+        let tmp = TempDir::new().unwrap();
+        let url = serve(b"0123456789abcdef").await;
+
+        let result = tool_in(tmp.path(), 8)
+            .execute(json!({"url": url.clone(), "save_to": "big.bin"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("limit"));
+        assert!(!tmp.path().join("big.bin").exists());
+        assert!(!tmp.path().join("big.bin.part").exists());
+
+        // A failed download leaves an existing file as it was
+        std::fs::write(tmp.path().join("big.bin"), "keep").unwrap();
+        let result = tool_in(tmp.path(), 8)
+            .execute(json!({"url": url.clone(), "save_to": "big.bin"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("big.bin")).unwrap(),
+            "keep"
+        );
+
+        let escape = tool_in(tmp.path(), 1024)
+            .execute(json!({"url": url.clone(), "save_to": "../outside.bin"}))
+            .await
+            .unwrap();
+        assert!(!escape.success);
+
+        let read_only = WebFetchTool::new(Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::ReadOnly,
+            workspace_dir: tmp.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        }))
+        .execute(json!({"url": url, "save_to": "x.bin"}))
+        .await
+        .unwrap();
+        assert!(!read_only.success);
+    }
 }
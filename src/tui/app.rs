@@ -1,4 +1,12 @@
+use super::editor;
+use super::idle::{IdleState, IdleTimer};
+use super::input_history::InputHistory;
+use super::palette::{self, Palette};
+use super::recording::{self, Recorded, Recorder};
+use super::theme::Theme;
 use super::{AgentEvent, AgentState};
+use crate::agent::loop_::split_thinking;
+use crate::config::TuiConfig;
 use crate::providers::UsageTracker;
 use crate::session;
 use anyhow::Result;
@@ -10,13 +18,15 @@ use crossterm::{
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
     Frame, Terminal,
 };
+use std::collections::VecDeque;
+use std::fmt::Write;
 use std::io;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 
 struct DisplayMessage {
     role: Role,
@@ -29,6 +39,18 @@ enum Role {
     Assistant,
     Tool,
     Error,
+    /// Provider rejected its credentials, shown as a banner
+    AuthFailed,
+    /// Model reasoning split out of an answer
+    Thinking,
+}
+
+/// A file edit waiting for the user to apply or reject it.
+struct PendingEdit {
+    tool: String,
+    path: String,
+    diff: String,
+    reply: oneshot::Sender<bool>,
 }
 
 enum UiStatus {
@@ -37,8 +59,20 @@ enum UiStatus {
     UsingTool(String),
 }
 
+/// Terminal poll interval while idle.
+const IDLE_POLL_MS: u64 = 50;
+
+/// Terminal poll interval while the agent is streaming (~60 fps redraws).
+const STREAMING_POLL_MS: u64 = 16;
+
+/// Default assistant label when no identity name is configured.
+pub const DEFAULT_ASSISTANT_NAME: &str = "AI";
+
+#[allow(clippy::struct_excessive_bools)]
 pub struct App {
     model_name: String,
+    assistant_name: String,
+    theme: Theme,
     messages: Vec<DisplayMessage>,
     input: String,
     cursor_pos: usize,
@@ -47,15 +81,51 @@ pub struct App {
     current_response: String,
     should_quit: bool,
     usage_tracker: Option<UsageTracker>,
+    /// Provider shown in the status line
+    served_by: Option<String>,
+    show_thinking: bool,
+    /// Render messages as unstyled text (`--plain` / `/plain`)
+    plain: bool,
+    /// Inputs submitted this session, recalled with Up/Down
+    input_history: InputHistory,
+    /// Open Ctrl+P command palette
+    palette: Option<Palette>,
+    /// Edits awaiting approval, reviewed one at a time
+    pending_edits: VecDeque<PendingEdit>,
+    /// Lines scrolled down in the edit under review
+    edit_scroll: u16,
+    /// `[tui] idle_timeout_secs` countdown
+    idle: IdleTimer,
+    /// Printed after the terminal is restored
+    exit_notice: Option<String>,
+    /// `--record` target
+    recorder: Option<Recorder>,
+    /// Playing back a `--replay` file instead of talking to an agent
+    replaying: bool,
 }
 
 impl App {
-    pub fn new(model_name: String) -> Self {
+    pub fn new(
+        model_name: String,
+        assistant_name: String,
+        tui: &TuiConfig,
+        show_thinking: bool,
+        plain: bool,
+    ) -> Self {
+        let welcome = if assistant_name == DEFAULT_ASSISTANT_NAME {
+            "Welcome to TinyClaw! Type a message and press Enter.".to_string()
+        } else {
+            format!(
+                "Welcome to TinyClaw! {assistant_name} is ready. Type a message and press Enter."
+            )
+        };
         Self {
             model_name,
+            assistant_name,
+            theme: Theme::from_config(tui),
             messages: vec![DisplayMessage {
                 role: Role::Assistant,
-                content: "Welcome to TinyClaw! Type a message and press Enter.".into(),
+                content: welcome,
             }],
             input: String::new(),
             cursor_pos: 0,
@@ -64,11 +134,52 @@ impl App {
             current_response: String::new(),
             should_quit: false,
             usage_tracker: None,
+            served_by: None,
+            show_thinking,
+            plain,
+            input_history: InputHistory::new(tui.history_includes_commands),
+            palette: None,
+            pending_edits: VecDeque::new(),
+            edit_scroll: 0,
+            idle: IdleTimer::new(tui.idle_timeout_secs),
+            exit_notice: None,
+            recorder: None,
+            replaying: false,
+        }
+    }
+
+    /// Offer to resume sessions whose last save was cut off by a crash.
+    pub fn with_interrupted_sessions(mut self, sessions: &[session::SessionMeta]) -> Self {
+        if sessions.is_empty() {
+            return self;
         }
+        let mut notice = String::from(
+            "TinyClaw exited while saving these sessions; their last complete state was kept:",
+        );
+        for s in sessions {
+            let _ = write!(
+                notice,
+                "\n  {} ({} msgs) - {}",
+                s.id, s.message_count, s.preview
+            );
+        }
+        notice.push_str("\nType /resume <id> to continue one.");
+        self.messages.push(DisplayMessage {
+            role: Role::Assistant,
+            content: notice,
+        });
+        self
+    }
+
+    /// Record what the session shows to `path` (`--record`).
+    pub fn with_recording(mut self, path: &std::path::Path) -> Result<Self> {
+        self.recorder = Some(Recorder::create(path, &self.model_name)?);
+        Ok(self)
     }
 
     pub async fn run(mut self, agent: AgentState) -> Result<()> {
         self.usage_tracker = Some(agent.usage_tracker.clone());
+        self.served_by = Some(agent.provider.name().to_string());
         enable_raw_mode()?;
         let mut stdout = io::stdout();
         execute!(stdout, EnterAlternateScreen)?;
@@ -81,15 +192,120 @@ impl App {
         execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
         terminal.show_cursor()?;
 
+        if let Some(notice) = &self.exit_notice {
+            println!("{notice}");
+        }
+        result
+    }
+
+    /// Play back a `--record` file at its original pace. Nothing is sent to
+    /// a provider; keys other than scrolling and quitting are ignored.
+    pub fn replay(mut self, entries: Vec<recording::Entry>) -> Result<()> {
+        self.replaying = true;
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen)?;
+        let backend = CrosstermBackend::new(stdout);
+        let mut terminal = Terminal::new(backend)?;
+
+        let result = self.replay_loop(&mut terminal, entries);
+
+        disable_raw_mode()?;
+        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+        terminal.show_cursor()?;
         result
     }
 
+    fn replay_loop(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+        entries: Vec<recording::Entry>,
+    ) -> Result<()> {
+        let started = std::time::Instant::now();
+        let mut entries = entries.into_iter().peekable();
+        let mut finished = false;
+        loop {
+            let elapsed = started.elapsed();
+            while let Some(entry) =
+                entries.next_if(|e| elapsed >= std::time::Duration::from_millis(e.at_ms))
+            {
+                self.apply_recorded(entry.item);
+            }
+            if entries.peek().is_none() && !finished {
+                finished = true;
+                self.messages.push(DisplayMessage {
+                    role: Role::Assistant,
+                    content: "Replay finished. Press q to exit.".to_string(),
+                });
+            }
+
+            terminal.draw(|f| self.draw(f))?;
+
+            if event::poll(std::time::Duration::from_millis(STREAMING_POLL_MS))? {
+                if let Event::Key(key) = event::read()? {
+                    match (key.code, key.modifiers) {
+                        (KeyCode::Char('c'), m) if m.contains(KeyModifiers::CONTROL) => break,
+                        (KeyCode::Char('q') | KeyCode::Esc, _) => break,
+                        (KeyCode::PageUp, _) => {
+                            self.scroll_offset = self.scroll_offset.saturating_add(10);
+                        }
+                        (KeyCode::PageDown, _) => {
+                            self.scroll_offset = self.scroll_offset.saturating_sub(10);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Show one recorded item the way the live session did.
+    fn apply_recorded(&mut self, item: Recorded) {
+        match item {
+            Recorded::Start { model } => self.model_name = model,
+            Recorded::User { text } => {
+                self.messages.push(DisplayMessage {
+                    role: Role::User,
+                    content: text,
+                });
+                self.ui_status = UiStatus::Thinking;
+                self.current_response.clear();
+                self.scroll_offset = 0;
+            }
+            Recorded::Notice { text } => {
+                self.messages.push(DisplayMessage {
+                    role: Role::Assistant,
+                    content: text,
+                });
+                self.scroll_offset = 0;
+            }
+            Recorded::Event { event } => self.handle_agent_event(event),
+            Recorded::EditAnswer { approve } => {
+                self.handle_edit_review_key(KeyCode::Char(if approve { 'y' } else { 'n' }));
+            }
+        }
+    }
+
+    /// Append `item` to the `--record` file. A failed write stops recording
+    /// rather than the session.
+    fn record(&mut self, item: Recorded<&AgentEvent>) {
+        let Some(recorder) = self.recorder.as_mut() else {
+            return;
+        };
+        if let Err(e) = recorder.record(item) {
+            tracing::warn!("Recording stopped: {e:#}");
+            self.recorder = None;
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
     async fn event_loop(
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
         agent: AgentState,
     ) -> Result<()> {
-        let (event_tx, mut event_rx) = mpsc::channel::<AgentEvent>(64);
+        let (event_tx, mut event_rx) = mpsc::channel::<AgentEvent>(super::EVENT_CHANNEL_CAPACITY);
 
         // Channel to get agent back after task completes
         let (agent_return_tx, mut agent_return_rx) =
@@ -97,6 +313,8 @@ impl App {
 
         let mut agent_opt: Option<AgentState> = Some(agent);
         let mut agent_running = false;
+        // Turns held back by the save schedule while the agent is busy
+        let mut unsaved: Option<session::Snapshot> = None;
 
         loop {
             terminal.draw(|f| self.draw(f))?;
@@ -111,6 +329,7 @@ impl App {
                     Ok(returned_agent) => {
                         agent_opt = Some(returned_agent);
                         agent_running = false;
+                        unsaved = None;
                     }
                     Err(mpsc::error::TryRecvError::Empty) => {}
                     Err(mpsc::error::TryRecvError::Disconnected) => {
@@ -121,16 +340,50 @@ impl App {
 
             // Drain agent events
             while let Ok(evt) = event_rx.try_recv() {
+                self.record(Recorded::Event { event: &evt });
                 self.handle_agent_event(evt);
             }
+            // Tool calls cancelled mid-stream no longer wait for an answer
+            self.pending_edits.retain(|edit| !edit.reply.is_closed());
 
-            // Poll terminal events
-            if event::poll(std::time::Duration::from_millis(50))? {
+            // Only time spent waiting on the user counts as idle
+            if agent_running {
+                self.idle.reset();
+            } else if self.idle.state() == IdleState::Expired {
+                if let Some(agent) = agent_opt.as_mut() {
+                    self.exit_notice = Some(idle_exit_notice(agent, self.idle.timeout()));
+                }
+                break;
+            }
+
+            // Poll terminal events (shorter wait while streaming keeps redraws smooth)
+            let poll_ms = if agent_running {
+                STREAMING_POLL_MS
+            } else {
+                IDLE_POLL_MS
+            };
+            if event::poll(std::time::Duration::from_millis(poll_ms))? {
                 if let Event::Key(key) = event::read()? {
+                    self.idle.reset();
+                    if !self.pending_edits.is_empty()
+                        && !key.modifiers.contains(KeyModifiers::CONTROL)
+                    {
+                        self.handle_edit_review_key(key.code);
+                        continue;
+                    }
+                    if self.palette.is_some() {
+                        self.handle_palette_key(key.code);
+                        continue;
+                    }
                     match (key.code, key.modifiers) {
                         (KeyCode::Char('c'), m) if m.contains(KeyModifiers::CONTROL) => {
                             self.should_quit = true;
                         }
+                        (KeyCode::Char('p'), m)
+                            if m.contains(KeyModifiers::CONTROL) && !agent_running =>
+                        {
+                            self.palette = Some(Palette::default());
+                        }
                         (KeyCode::Enter, _) if !agent_running => {
                             if !self.input.trim().is_empty() {
                                 let user_msg = self.input.clone();
@@ -142,11 +395,27 @@ impl App {
                                     continue;
                                 }
 
+                                // `/edit [draft]` composes the message in $EDITOR
+                                let user_msg = match editor::draft(&user_msg) {
+                                    Some(draft) => {
+                                        self.input_history.push_command(&user_msg);
+                                        match self.compose_in_editor(terminal, draft)? {
+                                            Some(text) => text,
+                                            None => continue,
+                                        }
+                                    }
+                                    None => user_msg,
+                                };
+
                                 // Handle slash commands locally
                                 if let Some(response) = self.handle_slash_command(
                                     user_msg.trim(),
                                     &mut agent_opt,
                                 ) {
+                                    self.input_history.push_command(&user_msg);
+                                    self.record(Recorded::Notice {
+                                        text: response.clone(),
+                                    });
                                     self.messages.push(DisplayMessage {
                                         role: Role::Assistant,
                                         content: response,
@@ -155,6 +424,10 @@ impl App {
                                     continue;
                                 }
 
+                                self.input_history.push(&user_msg);
+                                self.record(Recorded::User {
+                                    text: user_msg.clone(),
+                                });
                                 self.messages.push(DisplayMessage {
                                     role: Role::User,
                                     content: user_msg.clone(),
@@ -166,6 +439,7 @@ impl App {
                                 // Move agent into spawned task, get it back when done
                                 if let Some(mut ag) = agent_opt.take() {
                                     agent_running = true;
+                                    unsaved = ag.unsaved_snapshot();
                                     let tx = event_tx.clone();
                                     let return_tx = agent_return_tx.clone();
                                     tokio::spawn(async move {
@@ -193,6 +467,16 @@ impl App {
                                 self.cursor_pos += 1;
                             }
                         }
+                        (KeyCode::Up, _) if !agent_running => {
+                            if let Some(entry) = self.input_history.prev(&self.input) {
+                                self.set_input(entry);
+                            }
+                        }
+                        (KeyCode::Down, _) if !agent_running => {
+                            if let Some(entry) = self.input_history.next() {
+                                self.set_input(entry);
+                            }
+                        }
                         (KeyCode::Home, _) => self.cursor_pos = 0,
                         (KeyCode::End, _) => self.cursor_pos = self.input.len(),
                         (KeyCode::PageUp, _) => {
@@ -207,9 +491,131 @@ impl App {
             }
         }
 
+        // Write what the save schedule held back. A reply still generating
+        // is lost, but the turns before it are not
+        let saved = match agent_opt.as_mut() {
+            Some(ag) => ag.flush_session().map(|_| ()),
+            None => unsaved.map_or(Ok(()), |snapshot| snapshot.write().map(|_| ())),
+        };
+        if let Err(e) = saved {
+            tracing::warn!("Failed to save session: {e}");
+        }
+
         Ok(())
     }
 
+    /// Suspend the TUI while `$EDITOR` edits `draft`, then restore it.
+    /// Returns the saved text, or `None` (after saying why) when there is
+    /// nothing to send. Only errors restoring the terminal are returned.
+    fn compose_in_editor(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+        draft: &str,
+    ) -> Result<Option<String>> {
+        let Some(command) = editor::editor_command(|name| std::env::var(name).ok()) else {
+            self.messages.push(DisplayMessage {
+                role: Role::Error,
+                content: "No editor set: export EDITOR (or VISUAL), e.g. `export EDITOR=vim`, \
+                          then try /edit again."
+                    .to_string(),
+            });
+            return Ok(None);
+        };
+
+        disable_raw_mode()?;
+        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+        terminal.show_cursor()?;
+        let result = editor::compose(&command, draft);
+        enable_raw_mode()?;
+        execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+        terminal.clear()?;
+        // Time spent in the editor isn't idle time
+        self.idle.reset();
+        self.scroll_offset = 0;
+
+        match result {
+            Ok(Some(text)) => Ok(Some(text)),
+            Ok(None) => {
+                self.messages.push(DisplayMessage {
+                    role: Role::Assistant,
+                    content: "Editor closed with an empty message; nothing sent.".to_string(),
+                });
+                Ok(None)
+            }
+            Err(e) => {
+                self.messages.push(DisplayMessage {
+                    role: Role::Error,
+                    content: format!("{e:#}"),
+                });
+                Ok(None)
+            }
+        }
+    }
+
+    /// Replace the input line, leaving the cursor at its end.
+    fn set_input(&mut self, text: String) {
+        self.input = text;
+        self.cursor_pos = self.input.len();
+    }
+
+    /// Keys while the command palette is open: type to filter, Up/Down to
+    /// move, Enter to fill the input with the selection, Esc to close.
+    fn handle_palette_key(&mut self, code: KeyCode) {
+        let Some(palette) = self.palette.as_mut() else {
+            return;
+        };
+        match code {
+            KeyCode::Esc => self.palette = None,
+            KeyCode::Enter => {
+                if let Some(command) = palette.selection() {
+                    self.set_input(format!("{command} "));
+                }
+                self.palette = None;
+            }
+            KeyCode::Up => palette.select_prev(),
+            KeyCode::Down => palette.select_next(),
+            KeyCode::Backspace => palette.pop(),
+            KeyCode::Char(c) => palette.push(c),
+            _ => {}
+        }
+    }
+
+    /// Keys while an edit is under review: y/Enter apply it, n/Esc reject
+    /// it, Up/Down and PageUp/PageDown scroll the diff.
+    fn handle_edit_review_key(&mut self, code: KeyCode) {
+        let approve = match code {
+            KeyCode::Char('y' | 'Y') | KeyCode::Enter => true,
+            KeyCode::Char('n' | 'N') | KeyCode::Esc => false,
+            KeyCode::Up => {
+                self.edit_scroll = self.edit_scroll.saturating_sub(1);
+                return;
+            }
+            KeyCode::Down => {
+                self.edit_scroll = self.edit_scroll.saturating_add(1);
+                return;
+            }
+            KeyCode::PageUp => {
+                self.edit_scroll = self.edit_scroll.saturating_sub(10);
+                return;
+            }
+            KeyCode::PageDown => {
+                self.edit_scroll = self.edit_scroll.saturating_add(10);
+                return;
+            }
+            _ => return,
+        };
+        if let Some(edit) = self.pending_edits.pop_front() {
+            self.record(Recorded::EditAnswer { approve });
+            let verdict = if approve { "applied" } else { "rejected" };
+            self.messages.push(DisplayMessage {
+                role: Role::Tool,
+                content: format!("[{}] Edit to {} {verdict}", edit.tool, edit.path),
+            });
+            let _ = edit.reply.send(approve);
+        }
+        self.edit_scroll = 0;
+    }
+
     fn handle_agent_event(&mut self, evt: AgentEvent) {
         match evt {
             AgentEvent::Token(text) => {
@@ -226,15 +632,27 @@ impl App {
                 });
                 self.ui_status = UiStatus::Thinking;
             }
+            AgentEvent::ServedBy(name) => {
+                self.served_by = Some(name);
+            }
             AgentEvent::Done(response) => {
+                // Replies are not streamed when output transforms are set, so
+                // streamed text never needs them
                 let content = if self.current_response.is_empty() {
                     response
                 } else {
-                    self.current_response.clone()
+                    crate::agent::loop_::strip_tool_tags(&self.current_response)
                 };
+                let (thinking, answer) = split_thinking(&content);
+                if !thinking.is_empty() {
+                    self.messages.push(DisplayMessage {
+                        role: Role::Thinking,
+                        content: thinking,
+                    });
+                }
                 self.messages.push(DisplayMessage {
                     role: Role::Assistant,
-                    content,
+                    content: if answer.is_empty() { content } else { answer },
                 });
                 self.current_response.clear();
                 self.ui_status = UiStatus::Idle;
@@ -247,6 +665,27 @@ impl App {
                 self.current_response.clear();
                 self.ui_status = UiStatus::Idle;
             }
+            AgentEvent::AuthFailed(guidance) => {
+                self.messages.push(DisplayMessage {
+                    role: Role::AuthFailed,
+                    content: guidance,
+                });
+                self.current_response.clear();
+                self.ui_status = UiStatus::Idle;
+            }
+            AgentEvent::ConfirmRequired {
+                tool,
+                path,
+                diff,
+                reply,
+            } => {
+                self.pending_edits.push_back(PendingEdit {
+                    tool,
+                    path,
+                    diff,
+                    reply,
+                });
+            }
         }
     }
 
@@ -266,20 +705,134 @@ impl App {
         self.draw_messages(frame, chunks[1]);
         self.draw_status(frame, chunks[2]);
         self.draw_input(frame, chunks[3]);
+        if let Some(palette) = &self.palette {
+            self.draw_palette(frame, chunks[1], palette);
+        }
+        if let Some(edit) = self.pending_edits.front() {
+            self.draw_edit_review(frame, chunks[1], edit);
+        }
+    }
+
+    /// Diff of the edit under review, covering the message area.
+    fn draw_edit_review(&self, frame: &mut Frame, area: Rect, edit: &PendingEdit) {
+        let popup = Rect {
+            x: area.x + 1,
+            y: area.y,
+            width: area.width.saturating_sub(2),
+            height: area.height,
+        };
+        let lines: Vec<Line> = if edit.diff.is_empty() {
+            vec![Line::from(Span::styled(
+                "No changes to the file contents",
+                self.styled(Style::default().fg(self.theme.muted)),
+            ))]
+        } else {
+            edit.diff
+                .lines()
+                .map(|line| {
+                    let style = if line.starts_with("+++") || line.starts_with("---") {
+                        Style::default().add_modifier(Modifier::BOLD)
+                    } else if line.starts_with("@@") {
+                        Style::default().fg(self.theme.heading)
+                    } else if line.starts_with('+') {
+                        Style::default().fg(self.theme.added)
+                    } else if line.starts_with('-') {
+                        Style::default().fg(self.theme.removed)
+                    } else {
+                        Style::default()
+                    };
+                    Line::from(Span::styled(line.to_string(), self.styled(style)))
+                })
+                .collect()
+        };
+        let queued = match self.pending_edits.len() - 1 {
+            0 => String::new(),
+            n => format!(", {n} more queued"),
+        };
+
+        let widget = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" {} wants to change {}{queued} ", edit.tool, edit.path))
+                    .title_bottom(" y apply  n reject  Up/Down scroll ")
+                    .border_style(Style::default().fg(self.theme.busy)),
+            )
+            .scroll((self.edit_scroll, 0));
+        frame.render_widget(Clear, popup);
+        frame.render_widget(widget, popup);
+    }
+
+    /// Command palette popup, anchored to the bottom of the message area.
+    fn draw_palette(&self, frame: &mut Frame, area: Rect, palette: &Palette) {
+        let matches = palette.matches();
+        let rows = u16::try_from(matches.len().max(1)).unwrap_or(u16::MAX);
+        let height = rows.saturating_add(2).min(area.height);
+        let popup = Rect {
+            x: area.x + 1,
+            y: area.y + area.height - height,
+            width: area.width.saturating_sub(2),
+            height,
+        };
+
+        let lines: Vec<Line> = if matches.is_empty() {
+            vec![Line::from(Span::styled(
+                "No matching commands",
+                self.styled(Style::default().fg(self.theme.muted)),
+            ))]
+        } else {
+            matches
+                .iter()
+                .enumerate()
+                .map(|(i, (name, description))| {
+                    let style = if i == palette.selected {
+                        self.styled(
+                            Style::default()
+                                .fg(self.theme.on_accent)
+                                .bg(self.theme.accent),
+                        )
+                    } else {
+                        Style::default()
+                    };
+                    let marker = if i == palette.selected { "> " } else { "  " };
+                    Line::from(Span::styled(
+                        format!("{marker}{name:<10} {description}"),
+                        style,
+                    ))
+                })
+                .collect()
+        };
+        // Keep the highlighted row in view
+        let visible = usize::from(height.saturating_sub(2));
+        let scroll =
+            u16::try_from(palette.selected.saturating_sub(visible.saturating_sub(1))).unwrap_or(0);
+
+        let widget = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Commands: {}_ (Esc to close) ", palette.query))
+                    .border_style(Style::default().fg(self.theme.accent)),
+            )
+            .scroll((scroll, 0));
+        frame.render_widget(Clear, popup);
+        frame.render_widget(widget, popup);
     }
 
     fn draw_header(&self, frame: &mut Frame, area: Rect) {
         let header = Paragraph::new(Line::from(vec![
             Span::styled(
                 " TinyClaw ",
-                Style::default().fg(Color::Black).bg(Color::Cyan),
+                Style::default()
+                    .fg(self.theme.on_accent)
+                    .bg(self.theme.accent),
             ),
             Span::raw(" "),
             Span::styled(
                 self.model_name.clone(),
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(self.theme.muted),
             ),
-            Span::raw("  Ctrl+C quit  PageUp/Down scroll"),
+            Span::raw("  Ctrl+C quit  Ctrl+P commands  PageUp/Down scroll"),
         ]));
         frame.render_widget(header, area);
     }
@@ -292,35 +845,51 @@ impl App {
                 Role::User => (
                     "You",
                     Style::default()
-                        .fg(Color::Green)
+                        .fg(self.theme.user)
                         .add_modifier(Modifier::BOLD),
                 ),
-                Role::Assistant => ("AI", Style::default().fg(Color::Cyan)),
-                Role::Tool => ("Tool", Style::default().fg(Color::Yellow)),
+                Role::Assistant => (
+                    self.assistant_name.as_str(),
+                    Style::default().fg(self.theme.assistant),
+                ),
+                Role::Tool => ("Tool", Style::default().fg(self.theme.tool)),
                 Role::Error => (
                     "Error",
                     Style::default()
-                        .fg(Color::Red)
+                        .fg(self.theme.error)
                         .add_modifier(Modifier::BOLD),
                 ),
+                Role::Thinking => {
+                    self.push_thinking_lines(&mut lines, &msg.content);
+                    continue;
+                }
+                Role::AuthFailed => {
+                    self.push_auth_banner(&mut lines, &msg.content);
+                    continue;
+                }
             };
 
-            lines.push(Line::from(Span::styled(format!("{prefix}: "), style)));
-            let rendered = super::markdown::render_to_spans(&msg.content);
-            lines.extend(rendered);
+            lines.push(Line::from(Span::styled(
+                format!("{prefix}: "),
+                self.styled(style),
+            )));
+            lines.extend(self.render_body(&msg.content));
             lines.push(Line::from(""));
         }
 
         if !self.current_response.is_empty() {
+            let (thinking, answer) = split_thinking(&self.current_response);
+            if !thinking.is_empty() {
+                self.push_thinking_lines(&mut lines, &thinking);
+            }
             lines.push(Line::from(Span::styled(
-                "AI: ",
-                Style::default().fg(Color::Cyan),
+                format!("{}: ", self.assistant_name),
+                self.styled(Style::default().fg(self.theme.assistant)),
             )));
-            let rendered = super::markdown::render_to_spans(&self.current_response);
-            lines.extend(rendered);
+            lines.extend(self.render_body(&answer));
             lines.push(Line::from(Span::styled(
                 " ...",
-                Style::default().fg(Color::DarkGray),
+                self.styled(Style::default().fg(self.theme.muted)),
             )));
         }
 
@@ -341,19 +910,97 @@ impl App {
         frame.render_widget(messages_widget, area);
     }
 
+    /// Message body as styled markdown, or verbatim text in plain mode.
+    fn render_body(&self, text: &str) -> Vec<Line<'static>> {
+        if self.plain {
+            super::markdown::render_plain(text)
+        } else {
+            super::markdown::render_to_spans(text, &self.theme)
+        }
+    }
+
+    /// `style`, or no styling at all in plain mode.
+    fn styled(&self, style: Style) -> Style {
+        if self.plain {
+            Style::default()
+        } else {
+            style
+        }
+    }
+
+    /// Render a reasoning block dimmed when expanded, or as a one-line
+    /// placeholder when collapsed with `/thinking off`.
+    fn push_thinking_lines(&self, lines: &mut Vec<Line<'static>>, thinking: &str) {
+        let dim = self.styled(
+            Style::default()
+                .fg(self.theme.muted)
+                .add_modifier(Modifier::ITALIC),
+        );
+        if !self.show_thinking {
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "▸ Thinking ({} lines hidden, /thinking on to expand)",
+                    thinking.lines().count()
+                ),
+                dim,
+            )));
+            return;
+        }
+        lines.push(Line::from(Span::styled("▾ Thinking", dim)));
+        for line in thinking.lines() {
+            lines.push(Line::from(Span::styled(format!("  {line}"), dim)));
+        }
+        lines.push(Line::from(""));
+    }
+
+    /// Reverse-video header over the provider's fix-it steps, so a bad key
+    /// stands out from ordinary errors.
+    fn push_auth_banner(&self, lines: &mut Vec<Line<'static>>, guidance: &str) {
+        lines.push(Line::from(Span::styled(
+            " ⚠ Authentication failed ",
+            self.styled(
+                Style::default()
+                    .fg(self.theme.error)
+                    .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+            ),
+        )));
+        let text = self.styled(Style::default().fg(self.theme.error));
+        for line in guidance.lines() {
+            lines.push(Line::from(Span::styled(line.to_string(), text)));
+        }
+        lines.push(Line::from(""));
+    }
+
     fn draw_status(&self, frame: &mut Frame, area: Rect) {
-        let (text, color) = match &self.ui_status {
-            UiStatus::Idle => ("Ready".to_string(), Color::Green),
-            UiStatus::Thinking => ("Thinking...".to_string(), Color::Yellow),
-            UiStatus::UsingTool(name) => (format!("Running {name}..."), Color::Magenta),
+        let (text, color) = match (&self.ui_status, self.idle.state()) {
+            _ if !self.pending_edits.is_empty() => (
+                "Review edit: y apply, n reject".to_string(),
+                self.theme.busy,
+            ),
+            (UiStatus::Idle, IdleState::Warning { left }) => {
+                let secs = (left + std::time::Duration::from_millis(999)).as_secs();
+                (
+                    format!("Idle, exiting in {secs}s (press any key to stay)"),
+                    self.theme.error,
+                )
+            }
+            (UiStatus::Idle, _) => ("Ready".to_string(), self.theme.ready),
+            (UiStatus::Thinking, _) => ("Thinking...".to_string(), self.theme.busy),
+            (UiStatus::UsingTool(name), _) => (format!("Running {name}..."), self.theme.running),
         };
 
         let usage_text = if let Some(tracker) = &self.usage_tracker {
             let total = tracker.snapshot().total_tokens;
             let cost = tracker.estimated_cost_usd();
             let reqs = tracker.requests();
+            let latency = tracker
+                .ema_latency_ms()
+                .map(|ms| format!(", {ms}ms"))
+                .unwrap_or_default();
             if total > 0 {
-                format!("  [{total} tokens, {reqs} reqs, ~${cost:.4}]")
+                format!("  [{total} tokens, {reqs} reqs, ~${cost:.4}{latency}]")
+            } else if !latency.is_empty() {
+                format!("  [{}]", latency.trim_start_matches(", "))
             } else {
                 String::new()
             }
@@ -361,15 +1008,23 @@ impl App {
             String::new()
         };
 
+        let provider_text = self
+            .served_by
+            .as_ref()
+            .map(|name| format!("  via {name}"))
+            .unwrap_or_default();
+
         let status = Paragraph::new(Line::from(vec![
-            Span::styled(" Status: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(" Status: ", Style::default().fg(self.theme.muted)),
             Span::styled(text, Style::default().fg(color)),
-            Span::styled(usage_text, Style::default().fg(Color::DarkGray)),
+            Span::styled(provider_text, Style::default().fg(self.theme.muted)),
+            Span::styled(usage_text, Style::default().fg(self.theme.muted)),
         ]));
         frame.render_widget(status, area);
     }
 
     /// Handle TUI slash commands. Returns Some(response) if handled, None otherwise.
+    #[allow(clippy::too_many_lines)]
     fn handle_slash_command(
         &mut self,
         cmd: &str,
@@ -377,34 +1032,48 @@ impl App {
     ) -> Option<String> {
         let parts: Vec<&str> = cmd.splitn(2, ' ').collect();
         let command = parts[0];
-        let _arg = parts.get(1).copied().unwrap_or("");
+        let arg = parts.get(1).copied().unwrap_or("").trim();
 
         match command {
-            "/help" => Some(
-                "Available commands:\n\
-                 /help     - Show this help\n\
-                 /cost     - Show token usage and estimated cost\n\
-                 /clear    - Clear message history (keeps system prompt)\n\
-                 /model    - Show current model\n\
-                 /sessions - List saved sessions\n\
-                 /session  - Show current session ID\n\
-                 /export   - Export conversation to file\n\
-                 /quit     - Exit TinyClaw"
-                    .to_string(),
-            ),
+            "/help" => Some(palette::help_text()),
             "/cost" => {
                 if let Some(tracker) = &self.usage_tracker {
                     let snap = tracker.snapshot();
                     let cost = tracker.estimated_cost_usd();
                     let reqs = tracker.requests();
+                    let fmt_ms =
+                        |ms: Option<u64>| ms.map_or_else(|| "n/a".to_string(), |v| format!("{v} ms"));
+                    let fingerprint = tracker
+                        .system_fingerprint()
+                        .unwrap_or_else(|| "n/a".to_string());
+                    let counter = crate::providers::tokens::token_counter();
+                    let next_prompt = agent_opt.as_ref().map_or_else(
+                        || "n/a".to_string(),
+                        |ag| format!("{} tokens", counter.count_messages(&ag.history, &ag.model)),
+                    );
                     Some(format!(
                         "Token Usage:\n\
                          Prompt tokens:     {}\n\
                          Completion tokens: {}\n\
                          Total tokens:      {}\n\
                          Requests:          {reqs}\n\
-                         Estimated cost:    ${cost:.4}",
-                        snap.prompt_tokens, snap.completion_tokens, snap.total_tokens,
+                         Estimated cost:    ${cost:.4}\n\
+                         \n\
+                         Latency:\n\
+                         Last request:      {}\n\
+                         Average:           {}\n\
+                         Recent (EMA):      {}\n\
+                         Backend:           {fingerprint}\n\
+                         \n\
+                         Next request (pre-send, {}):\n\
+                         History prompt:    {next_prompt}",
+                        snap.prompt_tokens,
+                        snap.completion_tokens,
+                        snap.total_tokens,
+                        fmt_ms(tracker.last_latency_ms()),
+                        fmt_ms(tracker.avg_latency_ms()),
+                        fmt_ms(tracker.ema_latency_ms()),
+                        counter.name(),
                     ))
                 } else {
                     Some("Usage tracking not available.".to_string())
@@ -420,13 +1089,178 @@ impl App {
                 self.scroll_offset = 0;
                 Some("Conversation cleared.".to_string())
             }
-            "/model" => {
-                let model = agent_opt
-                    .as_ref()
-                    .map(|a| a.model.as_str())
-                    .unwrap_or("unknown");
-                Some(format!("Current model: {model}"))
+            "/undo" => {
+                let Some(ag) = agent_opt.as_mut() else {
+                    return Some("Can't undo while a reply is generating.".to_string());
+                };
+                let Some(removed) = ag.undo_last_exchange() else {
+                    return Some("Nothing to undo.".to_string());
+                };
+                if let Some(start) = self
+                    .messages
+                    .iter()
+                    .rposition(|m| matches!(m.role, Role::User))
+                {
+                    self.messages.truncate(start);
+                }
+                self.current_response.clear();
+                self.scroll_offset = 0;
+                if let Err(e) = ag.save_session() {
+                    return Some(format!("Undid the last exchange, but saving failed: {e}"));
+                }
+                Some(format!(
+                    "Undid the last exchange ({removed} message{}).",
+                    if removed == 1 { "" } else { "s" }
+                ))
+            }
+            "/model" | "/provider" => {
+                let Some(ag) = agent_opt.as_mut() else {
+                    return Some("Agent not available.".to_string());
+                };
+                if arg.is_empty() {
+                    return Some(format!(
+                        "Current provider: {}\nCurrent model: {}",
+                        ag.provider_name, ag.model
+                    ));
+                }
+                // `/provider <name> [model]` keeps the model unless one is given
+                let (provider, model) = if command == "/model" {
+                    (ag.provider_name.clone(), arg.to_string())
+                } else {
+                    let mut words = arg.split_whitespace();
+                    let provider = words.next().unwrap_or_default().to_string();
+                    let model = words.next().map_or_else(|| ag.model.clone(), String::from);
+                    (provider, model)
+                };
+                match ag.switch_provider(&provider, &model) {
+                    Ok(()) => {
+                        self.model_name.clone_from(&ag.model);
+                        self.served_by = Some(ag.provider.name().to_string());
+                        Some(format!("Switched to {model} via {provider}."))
+                    }
+                    Err(e) => Some(format!("Switch failed, keeping {}: {e}", ag.model)),
+                }
+            }
+            "/tools" => {
+                let Some(ag) = agent_opt.as_mut() else {
+                    return Some("Agent not available.".to_string());
+                };
+                let mut words = arg.split_whitespace();
+                match (words.next(), words.next()) {
+                    (None, _) => {
+                        let mut out = String::from("Tools:\n");
+                        for tool in ag.tools_registry.iter() {
+                            let state = if ag.disabled_tools.contains(tool.name()) {
+                                "off"
+                            } else {
+                                "on "
+                            };
+                            let _ = writeln!(out, "  [{state}] {}", tool.name());
+                        }
+                        Some(out.trim_end().to_string())
+                    }
+                    (Some(action @ ("enable" | "disable")), Some(name)) => {
+                        if !ag.tools_registry.iter().any(|t| t.name() == name) {
+                            return Some(format!("Unknown tool: {name}"));
+                        }
+                        let changed = if action == "disable" {
+                            ag.disabled_tools.insert(name.to_string())
+                        } else {
+                            ag.disabled_tools.remove(name)
+                        };
+                        if changed {
+                            ag.refresh_system_prompt();
+                        }
+                        Some(format!("Tool '{name}' {action}d."))
+                    }
+                    _ => Some("Usage: /tools [enable|disable <name>]".to_string()),
+                }
+            }
+            "/persona" => {
+                let Some(ag) = agent_opt.as_mut() else {
+                    return Some("Agent not available.".to_string());
+                };
+                if arg.is_empty() {
+                    let current = ag.persona.as_deref().unwrap_or("none");
+                    return Some(match crate::persona::list(&ag.workspace_dir) {
+                        Ok(names) if names.is_empty() => format!(
+                            "Current persona: {current}\nNo personas in {}",
+                            ag.workspace_dir
+                                .join(crate::persona::PERSONAS_DIR)
+                                .display()
+                        ),
+                        Ok(names) => format!(
+                            "Current persona: {current}\nAvailable: {}",
+                            names.join(", ")
+                        ),
+                        Err(e) => format!("Error listing personas: {e}"),
+                    });
+                }
+                let applied = crate::persona::load(&ag.workspace_dir, arg)
+                    .and_then(|persona| ag.apply_persona(&persona));
+                match applied {
+                    Ok(()) => {
+                        self.model_name.clone_from(&ag.model);
+                        Some(format!(
+                            "Persona '{arg}' applied ({}, temperature {}).",
+                            ag.model, ag.temperature
+                        ))
+                    }
+                    Err(e) => Some(format!("Persona not applied: {e:#}")),
+                }
             }
+            "/thinking" => {
+                let enabled = match arg {
+                    "" => !self.show_thinking,
+                    "on" => true,
+                    "off" => false,
+                    _ => return Some("Usage: /thinking [on|off]".to_string()),
+                };
+                self.show_thinking = enabled;
+                if let Some(ag) = agent_opt.as_mut() {
+                    ag.set_thinking(enabled);
+                }
+                Some(format!(
+                    "Thinking display {}.",
+                    if enabled { "on" } else { "off" }
+                ))
+            }
+            "/plain" => {
+                self.plain = match arg {
+                    "" => !self.plain,
+                    "on" => true,
+                    "off" => false,
+                    _ => return Some("Usage: /plain [on|off]".to_string()),
+                };
+                Some(format!(
+                    "Plain rendering {}.",
+                    if self.plain { "on" } else { "off" }
+                ))
+            }
+            "/ordered" => {
+                let Some(ag) = agent_opt.as_mut() else {
+                    return Some("Agent not available.".to_string());
+                };
+                ag.sequential_tools = match arg {
+                    "" => !ag.sequential_tools,
+                    "on" => true,
+                    "off" => false,
+                    _ => return Some("Usage: /ordered [on|off]".to_string()),
+                };
+                Some(if ag.sequential_tools {
+                    "Tool calls now run one at a time, in order.".to_string()
+                } else {
+                    "Tool calls now run concurrently (file edits still run in order).".to_string()
+                })
+            }
+            "/info" => Some(agent_opt.as_ref().map_or_else(
+                || "Can't show info while a reply is generating.".to_string(),
+                AgentState::info,
+            )),
+            "/stats" => Some(agent_opt.as_ref().map_or_else(
+                || "Agent not available.".to_string(),
+                |ag| format!("Tool usage:\n{}", ag.tool_stats.table()),
+            )),
             "/session" => {
                 let id = agent_opt
                     .as_ref()
@@ -456,21 +1290,45 @@ impl App {
                     Some("Agent not available.".to_string())
                 }
             }
+            "/resume" => {
+                let Some(ag) = agent_opt.as_mut() else {
+                    return Some("Agent not available.".to_string());
+                };
+                if arg.is_empty() {
+                    return Some("Usage: /resume <session id> (see /sessions)".to_string());
+                }
+                let saved = match session::load(&ag.workspace_dir, arg) {
+                    Ok(saved) => saved,
+                    Err(e) => return Some(format!("Cannot resume {arg}: {e:#}")),
+                };
+                if let Err(e) = ag.resume(saved) {
+                    return Some(format!("Cannot resume {arg}: {e:#}"));
+                }
+                self.messages = ag
+                    .visible_turns()
+                    .into_iter()
+                    .map(|(role, content)| DisplayMessage {
+                        role: if role == "user" {
+                            Role::User
+                        } else {
+                            Role::Assistant
+                        },
+                        content,
+                    })
+                    .collect();
+                self.current_response.clear();
+                self.scroll_offset = 0;
+                Some(format!(
+                    "Resumed session {arg} ({} messages).",
+                    ag.history.len() - 1
+                ))
+            }
             "/export" => {
                 if let Some(ag) = agent_opt.as_ref() {
                     let path = ag.workspace_dir.join("exports");
                     let _ = std::fs::create_dir_all(&path);
                     let file = path.join(format!("{}.md", ag.session_id));
-                    let mut content = String::new();
-                    for msg in &self.messages {
-                        let label = match msg.role {
-                            Role::User => "**You**",
-                            Role::Assistant => "**AI**",
-                            Role::Tool => "**Tool**",
-                            Role::Error => "**Error**",
-                        };
-                        content.push_str(&format!("{label}: {}\n\n", msg.content));
-                    }
+                    let content = ag.export_markdown(&self.assistant_name);
                     match std::fs::write(&file, &content) {
                         Ok(()) => Some(format!("Exported to {}", file.display())),
                         Err(e) => Some(format!("Export failed: {e}")),
@@ -491,10 +1349,14 @@ impl App {
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title(" Message (/quit to exit) ")
+                    .title(if self.replaying {
+                        " Replay (q to exit) "
+                    } else {
+                        " Message (/quit to exit) "
+                    })
                     .border_style(Style::default().fg(match self.ui_status {
-                        UiStatus::Idle => Color::Cyan,
-                        _ => Color::DarkGray,
+                        UiStatus::Idle => self.theme.accent,
+                        _ => self.theme.muted,
                     })),
             )
             .wrap(Wrap { trim: false });
@@ -505,3 +1367,19 @@ impl App {
         frame.set_cursor_position((cursor_x, cursor_y));
     }
 }
+
+/// Save the session before an idle exit and describe what happened.
+fn idle_exit_notice(agent: &mut AgentState, timeout: Option<std::time::Duration>) -> String {
+    let secs = timeout.unwrap_or_default().as_secs();
+    let exited = format!("Exited after {secs}s without input.");
+    if agent.history.iter().all(|m| m.role == "system") {
+        return exited;
+    }
+    match agent.save_session() {
+        Ok(_) => format!(
+            "{exited} Session {} saved; continue it with /resume {}.",
+            agent.session_id, agent.session_id
+        ),
+        Err(e) => format!("{exited} Failed to save session {}: {e}", agent.session_id),
+    }
+}
@@ -0,0 +1,195 @@
+//! Unified line diffs for reviewing file edits before they are written.
+
+use std::fmt::Write;
+
+/// Unchanged lines shown around each change.
+const CONTEXT: usize = 3;
+
+/// Changed regions with more cells than this (old lines × new lines) are
+/// shown as one block replacement rather than diffed line by line.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Keep,
+    Remove,
+    Add,
+}
+
+/// Unified diff from `before` to `after`, or an empty string when the two
+/// have the same lines.
+pub fn unified(path: &str, before: &str, after: &str) -> String {
+    let old: Vec<&str> = before.lines().collect();
+    let new: Vec<&str> = after.lines().collect();
+    let ops = line_ops(&old, &new);
+    if ops.iter().all(|(op, _)| *op == Op::Keep) {
+        return String::new();
+    }
+
+    // Old and new lines consumed before each op, for hunk headers
+    let mut positions = Vec::with_capacity(ops.len() + 1);
+    let (mut old_pos, mut new_pos) = (0, 0);
+    for (op, _) in &ops {
+        positions.push((old_pos, new_pos));
+        match op {
+            Op::Keep => {
+                old_pos += 1;
+                new_pos += 1;
+            }
+            Op::Remove => old_pos += 1,
+            Op::Add => new_pos += 1,
+        }
+    }
+    positions.push((old_pos, new_pos));
+
+    let mut out = if before.is_empty() {
+        format!("--- /dev/null\n+++ b/{path}\n")
+    } else {
+        format!("--- a/{path}\n+++ b/{path}\n")
+    };
+    for (start, end) in hunks(&ops) {
+        let (old_start, new_start) = positions[start];
+        let old_len = positions[end].0 - old_start;
+        let new_len = positions[end].1 - new_start;
+        let _ = writeln!(
+            out,
+            "@@ -{} +{} @@",
+            range(old_start, old_len),
+            range(new_start, new_len)
+        );
+        for (op, line) in &ops[start..end] {
+            let marker = match op {
+                Op::Keep => ' ',
+                Op::Remove => '-',
+                Op::Add => '+',
+            };
+            let _ = writeln!(out, "{marker}{line}");
+        }
+    }
+    out
+}
+
+/// `start,len` as written in a hunk header (1-based; an empty range names
+/// the line before it).
+fn range(start: usize, len: usize) -> String {
+    if len == 0 {
+        format!("{start},0")
+    } else {
+        format!("{},{len}", start + 1)
+    }
+}
+
+/// Op index ranges of each hunk: changes plus their context, merged when
+/// the context of neighbouring changes would overlap.
+fn hunks(ops: &[(Op, &str)]) -> Vec<(usize, usize)> {
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (i, _) in ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (op, _))| *op != Op::Keep)
+    {
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + 1 + CONTEXT).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    hunks
+}
+
+/// Line-level edit script turning `old` into `new`.
+fn line_ops<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Op, &'a str)> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<(Op, &str)> = old[..prefix].iter().map(|l| (Op::Keep, *l)).collect();
+    if a.len().saturating_mul(b.len()) > MAX_DIFF_CELLS {
+        ops.extend(a.iter().map(|l| (Op::Remove, *l)));
+        ops.extend(b.iter().map(|l| (Op::Add, *l)));
+    } else {
+        // lcs[i][j]: longest common subsequence of a[i..] and b[j..]
+        let width = b.len() + 1;
+        let mut lcs = vec![0u32; (a.len() + 1) * width];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i * width + j] = if a[i] == b[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            if a[i] == b[j] {
+                ops.push((Op::Keep, a[i]));
+                i += 1;
+                j += 1;
+            } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+                ops.push((Op::Remove, a[i]));
+                i += 1;
+            } else {
+                ops.push((Op::Add, b[j]));
+                j += 1;
+            }
+        }
+        ops.extend(a[i..].iter().map(|l| (Op::Remove, *l)));
+        ops.extend(b[j..].iter().map(|l| (Op::Add, *l)));
+    }
+    ops.extend(old[old.len() - suffix..].iter().map(|l| (Op::Keep, *l)));
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_text_has_no_diff() {
+        assert_eq!(unified("a.txt", "one\ntwo\n", "one\ntwo\n"), "");
+    }
+
+    #[test]
+    fn changed_line_shows_with_context() {
+        let before = "1\n2\n3\n4\n5\n6\n7\n8\n9\n";
+        let after = "1\n2\n3\n4\nfive\n6\n7\n8\n9\n";
+        assert_eq!(
+            unified("n.txt", before, after),
+            "--- a/n.txt\n+++ b/n.txt\n@@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n"
+        );
+    }
+
+    #[test]
+    fn distant_changes_get_separate_hunks() {
+        let before = (1..=20).fold(String::new(), |mut out, n| {
+            let _ = writeln!(out, "{n}");
+            out
+        });
+        let after: String = (1..=20)
+            .map(|n| match n {
+                2 => "two\n".to_string(),
+                19 => "nineteen\n".to_string(),
+                _ => format!("{n}\n"),
+            })
+            .collect();
+        let diff = unified("n.txt", &before, &after);
+        let headers: Vec<&str> = diff.lines().filter(|l| l.starts_with("@@")).collect();
+        assert_eq!(headers, vec!["@@ -1,5 +1,5 @@", "@@ -16,5 +16,5 @@"]);
+    }
+
+    #[test]
+    fn new_file_is_all_additions() {
+        assert_eq!(
+            unified("new.rs", "", "fn main() {}\n"),
+            "--- /dev/null\n+++ b/new.rs\n@@ -0,0 +1,1 @@\n+fn main() {}\n"
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! `/edit`: compose a message in `$VISUAL` / `$EDITOR` instead of the
+//! single-line input.
+
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::process::Command;
+
+/// The draft after `/edit` (possibly empty), or `None` for other input.
+pub fn draft(input: &str) -> Option<&str> {
+    let rest = input.trim().strip_prefix("/edit")?;
+    (rest.is_empty() || rest.starts_with(char::is_whitespace)).then(|| rest.trim_start())
+}
+
+/// The user's editor as program and arguments (`code --wait` works).
+/// `$VISUAL` wins over `$EDITOR`, as with git and crontab.
+pub fn editor_command(var: impl Fn(&str) -> Option<String>) -> Option<Vec<String>> {
+    ["VISUAL", "EDITOR"].iter().find_map(|name| {
+        let words: Vec<String> = var(name)?.split_whitespace().map(String::from).collect();
+        (!words.is_empty()).then_some(words)
+    })
+}
+
+/// Open `draft` in `editor` and return what was saved, or `None` when the
+/// result is blank. Blocks until the editor exits; the caller suspends the
+/// TUI around it.
+pub fn compose(editor: &[String], draft: &str) -> Result<Option<String>> {
+    let path = draft_path();
+    std::fs::write(&path, draft)
+        .with_context(|| format!("Failed to write draft: {}", path.display()))?;
+    let result = run_editor(editor, &path).and_then(|()| {
+        std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read draft: {}", path.display()))
+    });
+    let _ = std::fs::remove_file(&path);
+
+    let text = result?;
+    let text = text.trim_end();
+    Ok((!text.trim().is_empty()).then(|| text.to_string()))
+}
+
+fn draft_path() -> PathBuf {
+    // .md so editors pick up markdown highlighting
+    std::env::temp_dir().join(format!("tinyclaw-edit-{}.md", uuid::Uuid::new_v4()))
+}
+
+fn run_editor(editor: &[String], path: &std::path::Path) -> Result<()> {
+    let (program, args) = editor.split_first().context("No editor configured")?;
+    let status = Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to start editor `{program}`"))?;
+    if !status.success() {
+        anyhow::bail!("Editor `{program}` exited with {status}; nothing sent");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(pairs: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            pairs
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (*value).to_string())
+        }
+    }
+
+    #[test]
+    fn draft_follows_the_command() {
+        assert_eq!(draft("/edit"), Some(""));
+        assert_eq!(draft("  /edit  fix the tests "), Some("fix the tests"));
+        assert_eq!(draft("/editor"), None);
+        assert_eq!(draft("please /edit this"), None);
+    }
+
+    #[test]
+    fn visual_wins_over_editor_and_blank_is_unset() {
+        assert_eq!(
+            editor_command(env(&[("VISUAL", "code --wait"), ("EDITOR", "vim")])),
+            Some(vec!["code".to_string(), "--wait".to_string()])
+        );
+        assert_eq!(
+            editor_command(env(&[("VISUAL", "  "), ("EDITOR", "vim")])),
+            Some(vec!["vim".to_string()])
+        );
+        assert_eq!(editor_command(env(&[])), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn compose_returns_the_saved_text() {
+        let editor = ["sed", "-i", "s/draft/final answer/"].map(String::from);
+        assert_eq!(
+            compose(&editor, "my draft\n").unwrap().as_deref(),
+            Some("my final answer")
+        );
+
+        let blank = ["sed", "-i", "s/.*//"].map(String::from);
+        assert_eq!(compose(&blank, "my draft").unwrap(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failing_editor_is_an_error() {
+        let err = compose(&["false".to_string()], "draft").unwrap_err();
+        assert!(err.to_string().contains("nothing sent"), "{err}");
+    }
+}
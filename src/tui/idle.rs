@@ -0,0 +1,117 @@
+use std::time::{Duration, Instant};
+
+/// Longest warning shown before an idle exit.
+const WARNING: Duration = Duration::from_secs(10);
+
+/// Where the idle countdown stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleState {
+    Active,
+    /// Exit is close; shown in the status line
+    Warning {
+        left: Duration,
+    },
+    Expired,
+}
+
+/// `[tui] idle_timeout_secs`: time since the last key press, for exiting
+/// abandoned sessions. The caller resets it on every key and while a turn
+/// runs, so it only counts time spent waiting on the user.
+pub struct IdleTimer {
+    timeout: Option<Duration>,
+    last_input: Instant,
+}
+
+impl IdleTimer {
+    /// A timeout of 0 never expires.
+    pub fn new(timeout_secs: u64) -> Self {
+        Self {
+            timeout: (timeout_secs > 0).then(|| Duration::from_secs(timeout_secs)),
+            last_input: Instant::now(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.last_input = Instant::now();
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub fn state(&self) -> IdleState {
+        self.state_at(Instant::now())
+    }
+
+    fn state_at(&self, now: Instant) -> IdleState {
+        let Some(timeout) = self.timeout else {
+            return IdleState::Active;
+        };
+        let idle = now.saturating_duration_since(self.last_input);
+        let Some(left) = timeout.checked_sub(idle).filter(|left| !left.is_zero()) else {
+            return IdleState::Expired;
+        };
+        // Short timeouts warn for their second half only
+        if left <= WARNING.min(timeout / 2) {
+            IdleState::Warning { left }
+        } else {
+            IdleState::Active
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_timeout_never_expires() {
+        let timer = IdleTimer::new(0);
+        let later = timer.last_input + Duration::from_secs(86_400);
+        assert_eq!(timer.state_at(later), IdleState::Active);
+        assert_eq!(timer.timeout(), None);
+    }
+
+    #[test]
+    fn warns_before_expiring() {
+        let timer = IdleTimer::new(60);
+        let start = timer.last_input;
+        assert_eq!(
+            timer.state_at(start + Duration::from_secs(49)),
+            IdleState::Active
+        );
+        assert_eq!(
+            timer.state_at(start + Duration::from_secs(55)),
+            IdleState::Warning {
+                left: Duration::from_secs(5)
+            }
+        );
+        assert_eq!(
+            timer.state_at(start + Duration::from_secs(60)),
+            IdleState::Expired
+        );
+    }
+
+    #[test]
+    fn short_timeout_warns_for_half_of_it() {
+        let timer = IdleTimer::new(4);
+        let start = timer.last_input;
+        assert_eq!(
+            timer.state_at(start + Duration::from_secs(1)),
+            IdleState::Active
+        );
+        assert!(matches!(
+            timer.state_at(start + Duration::from_secs(3)),
+            IdleState::Warning { .. }
+        ));
+    }
+
+    #[test]
+    fn reset_restarts_the_countdown() {
+        let mut timer = IdleTimer::new(60);
+        timer.last_input -= Duration::from_secs(59);
+        assert!(matches!(timer.state(), IdleState::Warning { .. }));
+        timer.reset();
+        assert_eq!(timer.state(), IdleState::Active);
+    }
+}
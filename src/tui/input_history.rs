@@ -0,0 +1,143 @@
+use std::collections::VecDeque;
+
+/// Submitted inputs kept for this session, newest last.
+const MAX_ENTRIES: usize = 100;
+
+/// Ring buffer of submitted inputs recalled with Up/Down, shell style: the
+/// unsent draft is kept aside while browsing and restored past the newest
+/// entry.
+pub struct InputHistory {
+    entries: VecDeque<String>,
+    /// Index into `entries` while browsing, `None` when editing a fresh line
+    cursor: Option<usize>,
+    draft: String,
+    /// Record locally handled slash commands too (`[tui] history_includes_commands`)
+    include_commands: bool,
+}
+
+impl InputHistory {
+    pub fn new(include_commands: bool) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            cursor: None,
+            draft: String::new(),
+            include_commands,
+        }
+    }
+
+    /// Record a submitted input and stop browsing. Blank lines and repeats of
+    /// the newest entry are skipped.
+    pub fn push(&mut self, entry: &str) {
+        self.cursor = None;
+        self.draft.clear();
+        if entry.trim().is_empty() || self.entries.back().is_some_and(|last| last == entry) {
+            return;
+        }
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry.to_string());
+    }
+
+    /// Record a slash command the TUI handled itself, if configured to.
+    /// Browsing stops either way.
+    pub fn push_command(&mut self, entry: &str) {
+        if self.include_commands {
+            self.push(entry);
+        } else {
+            self.cursor = None;
+            self.draft.clear();
+        }
+    }
+
+    /// Step to an older entry. `current` is saved as the draft when browsing
+    /// starts. Returns `None` when there is nothing older.
+    pub fn prev(&mut self, current: &str) -> Option<String> {
+        let index = match self.cursor {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len() - 1
+            }
+            Some(0) => return None,
+            Some(i) => i - 1,
+        };
+        self.cursor = Some(index);
+        self.entries.get(index).cloned()
+    }
+
+    /// Step to a newer entry, or back to the saved draft past the newest.
+    /// Returns `None` when not browsing.
+    pub fn next(&mut self) -> Option<String> {
+        let index = self.cursor?;
+        if index + 1 < self.entries.len() {
+            self.cursor = Some(index + 1);
+            self.entries.get(index + 1).cloned()
+        } else {
+            self.cursor = None;
+            Some(std::mem::take(&mut self.draft))
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recalls_entries_and_restores_draft() {
+        let mut history = InputHistory::new(false);
+        assert_eq!(history.prev("draft"), None);
+        assert_eq!(history.next(), None);
+
+        history.push("first");
+        history.push("second");
+        history.push("second");
+        history.push("   ");
+        assert_eq!(history.len(), 2);
+
+        assert_eq!(history.prev("half-typed").as_deref(), Some("second"));
+        assert_eq!(history.prev("ignored").as_deref(), Some("first"));
+        assert_eq!(history.prev("ignored"), None);
+        assert_eq!(history.next().as_deref(), Some("second"));
+        assert_eq!(history.next().as_deref(), Some("half-typed"));
+        assert_eq!(history.next(), None);
+
+        // Submitting stops browsing, so the next Up starts from the newest
+        history.prev("");
+        history.push("third");
+        assert_eq!(history.prev("").as_deref(), Some("third"));
+    }
+
+    #[test]
+    fn slash_commands_recorded_only_when_enabled() {
+        let mut history = InputHistory::new(false);
+        history.push("hello");
+        history.push_command("/cost");
+        assert_eq!(history.prev("").as_deref(), Some("hello"));
+
+        let mut history = InputHistory::new(true);
+        history.push("hello");
+        history.push_command("/cost");
+        assert_eq!(history.prev("").as_deref(), Some("/cost"));
+    }
+
+    #[test]
+    fn ring_drops_oldest_entries() {
+        let mut history = InputHistory::new(false);
+        for i in 0..MAX_ENTRIES + 5 {
+            history.push(&format!("msg {i}"));
+        }
+        assert_eq!(history.len(), MAX_ENTRIES);
+
+        let mut oldest = None;
+        while let Some(entry) = history.prev("") {
+            oldest = Some(entry);
+        }
+        assert_eq!(oldest.as_deref(), Some("msg 5"));
+    }
+}
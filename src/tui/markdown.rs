@@ -1,21 +1,27 @@
+use super::theme::Theme;
 use ratatui::{
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
 };
 
 /// Render markdown text to ratatui Spans with basic formatting.
-/// Supports: **bold**, *italic*, `code`, ```code blocks```, # headings, - lists
-pub fn render_to_spans(text: &str) -> Vec<Line<'static>> {
+/// Supports: **bold**, *italic*, `code`, ```code blocks```, # headings, - lists,
+/// | tables |
+pub fn render_to_spans(text: &str, theme: &Theme) -> Vec<Line<'static>> {
     let mut lines = Vec::new();
     let mut in_code_block = false;
+    let raw_lines: Vec<&str> = text.lines().collect();
+    let mut next = 0;
 
-    for raw_line in text.lines() {
+    while next < raw_lines.len() {
+        let raw_line = raw_lines[next];
+        next += 1;
         if raw_line.trim_start().starts_with("```") {
             in_code_block = !in_code_block;
             if in_code_block {
                 lines.push(Line::from(Span::styled(
                     "---".to_string(),
-                    Style::default().fg(Color::DarkGray),
+                    Style::default().fg(theme.muted),
                 )));
             }
             continue;
@@ -24,17 +30,23 @@ pub fn render_to_spans(text: &str) -> Vec<Line<'static>> {
         if in_code_block {
             lines.push(Line::from(Span::styled(
                 format!("  {raw_line}"),
-                Style::default().fg(Color::Green),
+                Style::default().fg(theme.code),
             )));
             continue;
         }
 
+        if let Some(table) = Table::parse(&raw_lines[next - 1..]) {
+            next += table.source_lines - 1;
+            lines.extend(table.render(theme));
+            continue;
+        }
+
         // Headings
         if let Some(heading) = raw_line.strip_prefix("### ") {
             lines.push(Line::from(Span::styled(
                 heading.to_string(),
                 Style::default()
-                    .fg(Color::Cyan)
+                    .fg(theme.heading)
                     .add_modifier(Modifier::BOLD),
             )));
             continue;
@@ -43,7 +55,7 @@ pub fn render_to_spans(text: &str) -> Vec<Line<'static>> {
             lines.push(Line::from(Span::styled(
                 heading.to_string(),
                 Style::default()
-                    .fg(Color::Cyan)
+                    .fg(theme.heading)
                     .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
             )));
             continue;
@@ -52,7 +64,7 @@ pub fn render_to_spans(text: &str) -> Vec<Line<'static>> {
             lines.push(Line::from(Span::styled(
                 heading.to_uppercase(),
                 Style::default()
-                    .fg(Color::Cyan)
+                    .fg(theme.heading)
                     .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
             )));
             continue;
@@ -68,14 +80,176 @@ pub fn render_to_spans(text: &str) -> Vec<Line<'static>> {
         };
 
         // Inline formatting
-        lines.push(Line::from(render_inline(&line_text)));
+        lines.push(Line::from(render_inline(&line_text, theme)));
+    }
+
+    lines
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// A GitHub-flavored markdown table: header row, `---|---` separator and
+/// body rows.
+struct Table {
+    header: Vec<String>,
+    aligns: Vec<Align>,
+    rows: Vec<Vec<String>>,
+    /// Markdown lines the table was parsed from
+    source_lines: usize,
+}
+
+impl Table {
+    /// Parse a table starting at `lines[0]`. `None` unless the header is
+    /// followed by a separator with the same number of columns; such lines
+    /// are rendered as plain text.
+    fn parse(lines: &[&str]) -> Option<Self> {
+        let header = split_row(lines.first()?)?;
+        let aligns = split_row(lines.get(1)?)?
+            .iter()
+            .map(|cell| parse_align(cell))
+            .collect::<Option<Vec<_>>>()?;
+        if aligns.len() != header.len() {
+            return None;
+        }
+
+        let mut rows = Vec::new();
+        for line in &lines[2..] {
+            let Some(mut cells) = split_row(line) else {
+                break;
+            };
+            // Ragged rows: pad short ones, drop extra cells like GitHub does
+            cells.resize(header.len(), String::new());
+            rows.push(cells);
+        }
+        Some(Self {
+            header,
+            aligns,
+            source_lines: rows.len() + 2,
+            rows,
+        })
+    }
+
+    /// Columns padded to a common width, separated by `│`, with a rule
+    /// under the header.
+    fn render(&self, theme: &Theme) -> Vec<Line<'static>> {
+        let render_row = |cells: &[String]| -> Vec<Vec<Span<'static>>> {
+            cells.iter().map(|c| render_inline(c, theme)).collect()
+        };
+        let header = render_row(&self.header);
+        let rows: Vec<_> = self.rows.iter().map(|r| render_row(r)).collect();
+
+        let mut widths = vec![0; self.header.len()];
+        for row in std::iter::once(&header).chain(&rows) {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(spans_width(cell));
+            }
+        }
+
+        let border = Style::default().fg(theme.muted);
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let mut lines = vec![self.render_cells(header, &widths, border, Some(bold))];
+        let rule: Vec<String> = widths.iter().map(|w| "─".repeat(*w)).collect();
+        lines.push(Line::from(Span::styled(rule.join("─┼─"), border)));
+        for row in rows {
+            lines.push(self.render_cells(row, &widths, border, None));
+        }
+        lines
+    }
+
+    fn render_cells(
+        &self,
+        cells: Vec<Vec<Span<'static>>>,
+        widths: &[usize],
+        border: Style,
+        emphasis: Option<Style>,
+    ) -> Line<'static> {
+        let mut spans = Vec::new();
+        for (column, mut cell) in cells.into_iter().enumerate() {
+            if column > 0 {
+                spans.push(Span::styled(" │ ", border));
+            }
+            if let Some(style) = emphasis {
+                for span in &mut cell {
+                    span.style = span.style.patch(style);
+                }
+            }
+            let gap = widths[column] - spans_width(&cell);
+            let (before, after) = match self.aligns[column] {
+                Align::Left => (0, gap),
+                Align::Right => (gap, 0),
+                Align::Center => (gap / 2, gap - gap / 2),
+            };
+            if before > 0 {
+                spans.push(Span::raw(" ".repeat(before)));
+            }
+            spans.extend(cell);
+            // No trailing spaces after the last column
+            if after > 0 && column + 1 < widths.len() {
+                spans.push(Span::raw(" ".repeat(after)));
+            }
+        }
+        Line::from(spans)
+    }
+}
+
+/// Cells of a `| a | b |` row, or `None` when the line has no pipe.
+fn split_row(line: &str) -> Option<Vec<String>> {
+    let trimmed = line.trim();
+    if !trimmed.contains('|') {
+        return None;
+    }
+    let inner = trimmed.strip_prefix('|').unwrap_or(trimmed);
+    let inner = inner.strip_suffix('|').unwrap_or(inner);
+    Some(inner.split('|').map(|c| c.trim().to_string()).collect())
+}
+
+/// Column alignment from a separator cell such as `---`, `:--` or `-:`.
+fn parse_align(cell: &str) -> Option<Align> {
+    let left = cell.starts_with(':');
+    let right = cell.ends_with(':');
+    let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+    if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+        return None;
+    }
+    Some(match (left, right) {
+        (true, true) => Align::Center,
+        (false, true) => Align::Right,
+        _ => Align::Left,
+    })
+}
+
+fn spans_width(spans: &[Span]) -> usize {
+    spans.iter().map(Span::width).sum()
+}
+
+/// Render text without any styling, for `--plain` mode. Content is kept
+/// verbatim; fenced code blocks lose their fences and are indented instead.
+pub fn render_plain(text: &str) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut in_code_block = false;
+
+    for raw_line in text.lines() {
+        if raw_line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            lines.push(Line::raw(format!("    {raw_line}")));
+        } else {
+            lines.push(Line::raw(raw_line.to_string()));
+        }
     }
 
     lines
 }
 
 /// Parse inline markdown: **bold**, *italic*, `code`
-fn render_inline(text: &str) -> Vec<Span<'static>> {
+fn render_inline(text: &str, theme: &Theme) -> Vec<Span<'static>> {
     let mut spans = Vec::new();
     let mut remaining = text;
 
@@ -90,7 +264,7 @@ fn render_inline(text: &str) -> Vec<Span<'static>> {
                 spans.push(Span::styled(
                     after[..end].to_string(),
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(theme.inline_code)
                         .add_modifier(Modifier::BOLD),
                 ));
                 remaining = &after[end + 1..];
@@ -148,3 +322,91 @@ fn render_inline(text: &str) -> Vec<Span<'static>> {
 
     spans
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain_text(lines: &[Line]) -> Vec<String> {
+        lines.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn render_to_spans_uses_theme_colors() {
+        let theme = Theme::LIGHT;
+        let lines = render_to_spans("# Title\nrun `cargo test`\n```\nfn main() {}\n```", &theme);
+        assert_eq!(lines[0].spans[0].style.fg, Some(theme.heading));
+        assert_eq!(lines[1].spans[1].style.fg, Some(theme.inline_code));
+        assert_eq!(lines[2].spans[0].style.fg, Some(theme.muted));
+        assert_eq!(lines[3].spans[0].style.fg, Some(theme.code));
+    }
+
+    #[test]
+    fn two_column_table_is_aligned() {
+        let theme = Theme::DARK;
+        let text =
+            "Results:\n| Name | Score |\n|------|------:|\n| alice | 7 |\n| bob | 12 |\ndone";
+        let lines = render_to_spans(text, &theme);
+        assert_eq!(
+            plain_text(&lines),
+            [
+                "Results:",
+                "Name  │ Score",
+                "──────┼──────",
+                "alice │     7",
+                "bob   │    12",
+                "done",
+            ]
+        );
+        assert!(lines[1].spans[0]
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD));
+        assert_eq!(lines[2].spans[0].style.fg, Some(theme.muted));
+    }
+
+    #[test]
+    fn table_cells_keep_inline_markdown_and_pad_ragged_rows() {
+        let text = "| a | b | c |\n| :-: | --- | --- |\n| **x** |\n| 1 | 2 | 3 | 4 |";
+        let lines = render_to_spans(text, &Theme::DARK);
+        assert_eq!(
+            plain_text(&lines),
+            ["a │ b │ c", "──┼───┼──", "x │   │ ", "1 │ 2 │ 3"]
+        );
+        assert!(lines[2].spans[0]
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn malformed_table_renders_raw() {
+        let text = "| a | b |\n| not a separator |\n| 1 | 2 |";
+        let lines = render_to_spans(text, &Theme::DARK);
+        assert_eq!(plain_text(&lines), text.lines().collect::<Vec<_>>());
+
+        // Separator with the wrong number of columns
+        let text = "| a | b |\n|---|\n| 1 | 2 |";
+        let lines = render_to_spans(text, &Theme::DARK);
+        assert_eq!(plain_text(&lines), text.lines().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn render_plain_keeps_content_unstyled() {
+        let text = "# Title\nSome **bold** and `code`.\n```rust\nfn main() {}\n```\ndone";
+        let lines = render_plain(text);
+        assert_eq!(
+            plain_text(&lines),
+            [
+                "# Title",
+                "Some **bold** and `code`.",
+                "    fn main() {}",
+                "done"
+            ]
+        );
+        assert!(lines
+            .iter()
+            .flat_map(|l| &l.spans)
+            .all(|s| s.style == Style::default()));
+    }
+}
@@ -1,72 +1,163 @@
 mod app;
+mod diff;
+mod editor;
+mod idle;
+mod input_history;
 mod markdown;
+mod palette;
+mod recording;
+pub mod theme;
 
+use crate::agent::loop_::{split_thinking, strip_tool_tags, tools_outside_allowlist};
 use crate::channels::build_system_prompt;
 use crate::config::Config;
 use crate::memory::{self, Memory, MemoryCategory};
-use crate::observability::{self, Observer, ObserverEvent};
+use crate::observability::{self, MultiObserver, Observer, ObserverEvent, ToolStats, TurnContext};
 use crate::providers::{self, ChatMessage, Provider, UsageTracker};
 use crate::runtime;
-use crate::security::SecurityPolicy;
-use crate::tools::{self, Tool};
+use crate::security::{AutonomyLevel, OutputTransforms, SecurityPolicy};
+use crate::tools::{self, Tool, ToolResultCache};
 use crate::session;
 use crate::util::truncate_with_ellipsis;
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fmt::Write;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Instant;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 
 /// Maximum agentic tool-use iterations per user message.
 const MAX_TOOL_ITERATIONS: usize = 10;
 
+/// Fed back in place of tool results when the model repeats itself.
+const REPETITION_NOTICE: &str = "Detected repetition: you sent the same reply and tool calls several times in a row, so they were not run again. Use the results you already have, try a different approach, or answer the user.";
+
 /// Maximum non-system messages in history.
 const MAX_HISTORY_MESSAGES: usize = 50;
 
-/// A token/event from the agent to the TUI
+/// Estimated token budget for history sent with each request.
+const MAX_HISTORY_TOKENS: usize = 100_000;
+
+/// Characters of a tool's output shown in the chat, unless the tool or
+/// `[tui] tool_preview_len` asks for another length.
+const DEFAULT_TOOL_PREVIEW_LEN: usize = 120;
+
+/// Tool summaries listed in the system prompt's Tools section.
+const TOOL_DESCS: &[(&str, &str)] = &[
+    ("shell", "Execute terminal commands"),
+    ("file_read", "Read file contents"),
+    ("file_write", "Write file contents"),
+    ("file_patch", "Apply targeted edits to a file"),
+    (
+        "file_multi_patch",
+        "Apply targeted edits across several files, all or nothing",
+    ),
+    ("search_files", "Search for patterns across files"),
+    ("git", "Run whitelisted git subcommands"),
+    (
+        "check",
+        "Type-check the project and return only the diagnostics",
+    ),
+    ("web_fetch", "Fetch a URL via HTTP GET"),
+    ("memory_store", "Save to memory"),
+    ("memory_recall", "Search memory"),
+    ("memory_forget", "Delete a memory entry"),
+    #[cfg(feature = "plot-feature")]
+    ("plot", "Render a line or bar chart to an SVG file"),
+];
+
+/// Capacity of the agent → UI event channel. Streamed tokens are coalesced by
+/// `forward_tokens`, so this only has to absorb bursts between UI frames.
+pub const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// A token/event from the agent to the TUI. Serialized for `--record`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
 pub enum AgentEvent {
     Token(String),
     ToolStart(String),
     ToolResult { name: String, preview: String },
+    /// Provider that answered, which differs from the configured one after
+    /// a fallback or a lost race
+    ServedBy(String),
     Done(String),
     Error(String),
+    /// The provider rejected its credentials; carries the fix-it steps
+    AuthFailed(String),
+    /// A tool wants to change a file; the edit waits for the user's answer
+    /// on `reply` (`true` applies it)
+    ConfirmRequired {
+        tool: String,
+        path: String,
+        diff: String,
+        #[serde(skip, default = "closed_reply")]
+        reply: oneshot::Sender<bool>,
+    },
+}
+
+/// Answer channel for a replayed edit review, which nothing waits on.
+fn closed_reply() -> oneshot::Sender<bool> {
+    oneshot::channel().0
 }
 
 /// Run the TUI interface
+#[allow(clippy::too_many_lines, clippy::too_many_arguments)]
 pub async fn run(
     config: Config,
     provider_override: Option<String>,
     model_override: Option<String>,
-    temperature: f64,
+    temperature: Option<f64>,
+    system_file: Option<PathBuf>,
+    attach_files: Vec<PathBuf>,
+    thinking: bool,
+    plain: bool,
+    record: Option<PathBuf>,
 ) -> Result<()> {
+    // Validate --system-file before touching the terminal
+    let custom_prompt = system_file
+        .as_deref()
+        .map(|path| crate::channels::load_system_prompt_file(path, Some(&config.identity)))
+        .transpose()?;
+    let output_transforms = Arc::new(OutputTransforms::from_config(&config.security)?);
+
     // Wire up subsystems
-    let observer: Arc<dyn Observer> =
-        Arc::from(observability::create_observer(&config.observability));
+    let tool_stats = ToolStats::new();
+    let observer: Arc<dyn Observer> = Arc::new(MultiObserver::new(vec![
+        observability::create_observer(&config.observability),
+        Box::new(tool_stats.clone()),
+    ]));
     let runtime_adapter: Arc<dyn runtime::RuntimeAdapter> =
         Arc::from(runtime::create_runtime(&config.runtime)?);
     let security = Arc::new(SecurityPolicy::from_config(
         &config.autonomy,
         &config.workspace_dir,
     ));
+    let attachment = crate::agent::attach::load(&attach_files, &security)?;
 
     let mem: Arc<dyn Memory> = Arc::from(memory::create_memory(
         &config.memory,
         &config.workspace_dir,
         config.api_key.as_deref(),
     )?);
+    memory::gc_expired(mem.as_ref()).await;
 
     let composio_key = if config.composio.enabled {
         config.composio.api_key.as_deref()
     } else {
         None
     };
-    let tools_registry = Arc::new(tools::all_tools_with_runtime(
-        &security,
-        runtime_adapter,
-        mem.clone(),
-        composio_key,
-        &config.browser,
+    let tools_registry = Arc::new(tools::with_redaction(
+        tools::all_tools_with_runtime(
+            &security,
+            runtime_adapter,
+            mem.clone(),
+            composio_key,
+            &config.browser,
+            &config.check,
+        ),
+        &config.security,
     ));
 
     let provider_name = provider_override
@@ -74,74 +165,153 @@ pub async fn run(
         .or(config.default_provider.as_deref())
         .unwrap_or("openrouter");
 
-    let model_name = model_override
-        .as_deref()
-        .or(config.default_model.as_deref())
-        .unwrap_or("anthropic/claude-sonnet-4-20250514");
+    let model_name = model_override.unwrap_or_else(|| config.default_model_for(provider_name));
+    let model_name = model_name.as_str();
+    let temperature = temperature
+        .or_else(|| providers::route_temperature(&config.model_routes, model_name))
+        .unwrap_or(0.7);
 
-    let mut provider: Box<dyn Provider> = providers::create_routed_provider(
+    let mut provider: Box<dyn Provider> = providers::with_response_cache(
+        providers::create_routed_provider(
+            provider_name,
+            config.api_key.as_deref(),
+            &config.providers,
+            &config.reliability,
+            &config.model_routes,
+            model_name,
+        )?,
         provider_name,
-        config.api_key.as_deref(),
         &config.reliability,
-        &config.model_routes,
-        model_name,
-    )?;
+        &config.workspace_dir,
+    );
 
     let usage_tracker = UsageTracker::new();
     provider.set_usage_tracker(usage_tracker.clone());
-
-    // Build system prompt
-    let skills = crate::skills::load_skills(&config.workspace_dir);
-    let tool_descs: Vec<(&str, &str)> = vec![
-        ("shell", "Execute terminal commands"),
-        ("file_read", "Read file contents"),
-        ("file_write", "Write file contents"),
-        ("file_patch", "Apply targeted edits to a file"),
-        ("search_files", "Search for patterns across files"),
-        ("web_fetch", "Fetch a URL via HTTP GET"),
-        ("memory_store", "Save to memory"),
-        ("memory_recall", "Search memory"),
-        ("memory_forget", "Delete a memory entry"),
-    ];
-    let mut system_prompt = build_system_prompt(
-        &config.workspace_dir,
+    provider.set_thinking(thinking);
+    provider.set_seed(config.providers.seed);
+    provider.set_reasoning_effort(config.providers.reasoning_effort);
+    provider.set_stop_sequences(&config.providers.stop_sequences)?;
+    providers::warm_up_in_background(providers::create_routed_provider(
+        provider_name,
+        config.api_key.as_deref(),
+        &config.providers,
+        &config.reliability,
+        &config.model_routes,
         model_name,
-        &tool_descs,
-        &skills,
-        Some(&config.identity),
-    );
-    system_prompt.push_str(&build_tool_instructions(&tools_registry));
+    )?);
 
-    let history = vec![ChatMessage::system(&system_prompt)];
+    let skills = crate::skills::load_skills(&config.workspace_dir);
 
     observer.record_event(&ObserverEvent::AgentStart {
-        provider: provider_name.to_string(),
+        provider: provider.name().to_string(),
         model: model_name.to_string(),
+        turn: None,
     });
 
+    let session_id = session::new_session_id();
+
     // Bundle agent state and launch TUI
-    let agent_state = AgentState {
+    let mut agent_state = AgentState {
         provider,
+        provider_name: provider_name.to_string(),
+        api_key: config.api_key.clone(),
+        reliability: config.reliability.clone(),
+        model_routes: config.model_routes.clone(),
+        providers: config.providers.clone(),
+        disabled_tools: tools_outside_allowlist(
+            tools_registry.iter().map(|t| t.name()),
+            &config.autonomy.tools_only,
+        ),
         tools_registry,
         observer,
+        tool_stats,
         mem,
-        history,
+        history: Vec::new(),
         model: model_name.to_string(),
         temperature,
         auto_save: config.memory.auto_save,
         usage_tracker: usage_tracker.clone(),
         workspace_dir: config.workspace_dir.clone(),
-        session_id: session::new_session_id(),
+        session_id: session_id.clone(),
+        skills,
+        identity: config.identity.clone(),
+        custom_prompt,
+        attachment,
+        persona: None,
+        persona_prompt: None,
+        persona_disabled_tools: HashSet::new(),
+        show_thinking: thinking,
+        eager_tool_calls: config.tui.eager_tool_calls,
+        tool_cache: config
+            .tui
+            .cache_tool_results
+            .then(|| Arc::new(ToolResultCache::new())),
+        tool_preview_len: config.tui.tool_preview_len,
+        sequential_tools: config.tui.sequential_tools,
+        recall_limit: config.memory.recall_limit,
+        repeat_limit: config.tui.repeat_limit,
+        autonomy: config.autonomy.level,
+        confirm_edits: config.autonomy.level == AutonomyLevel::Supervised,
+        transcript: if config.session.transcript {
+            Some(session::Transcript::open(
+                &config.workspace_dir,
+                &session_id,
+            )?)
+        } else {
+            None
+        },
+        save_schedule: session::SaveSchedule::new(
+            config.session.save_every_turns,
+            config.session.save_interval_secs,
+        ),
+        output_transforms,
+        smooth_output: (config.tui.smooth_output && config.tui.smooth_output_cps > 0)
+            .then_some(config.tui.smooth_output_cps),
     };
+    agent_state.refresh_system_prompt();
+
+    let assistant_name = crate::identity::display_name(&config.identity, &config.workspace_dir)
+        .unwrap_or_else(|| app::DEFAULT_ASSISTANT_NAME.to_string());
 
-    app::App::new(model_name.to_string()).run(agent_state).await
+    let mut app = app::App::new(
+        model_name.to_string(),
+        assistant_name,
+        &config.tui,
+        thinking,
+        plain,
+    )
+    .with_interrupted_sessions(&session::recover_interrupted(&config.workspace_dir));
+    if let Some(path) = &record {
+        app = app.with_recording(path)?;
+    }
+    app.run(agent_state).await
+}
+
+/// Play back a session recorded with `--record`, without a provider. Output
+/// transforms aren't applied again: the recording already holds their result.
+pub fn replay(config: &Config, path: &std::path::Path, thinking: bool, plain: bool) -> Result<()> {
+    let entries = recording::load(path)?;
+    let assistant_name = crate::identity::display_name(&config.identity, &config.workspace_dir)
+        .unwrap_or_else(|| app::DEFAULT_ASSISTANT_NAME.to_string());
+    app::App::new(String::new(), assistant_name, &config.tui, thinking, plain).replay(entries)
 }
 
 /// All the state the agent needs between turns, bundled for ownership transfer.
+#[allow(clippy::struct_excessive_bools)]
 pub struct AgentState {
     pub provider: Box<dyn Provider>,
+    pub provider_name: String,
+    /// Provider settings kept so `/provider` and `/model` can rebuild mid-session
+    pub api_key: Option<String>,
+    pub reliability: crate::config::ReliabilityConfig,
+    pub model_routes: Vec<crate::config::ModelRouteConfig>,
+    pub providers: crate::config::ProvidersConfig,
     pub tools_registry: Arc<Vec<Box<dyn Tool>>>,
+    /// Tools switched off for this session via `/tools disable`
+    pub disabled_tools: HashSet<String>,
     pub observer: Arc<dyn Observer>,
+    /// Per-tool tallies fed by `observer`, shown by `/stats`
+    pub tool_stats: ToolStats,
     pub mem: Arc<dyn Memory>,
     pub history: Vec<ChatMessage>,
     pub model: String,
@@ -150,9 +320,346 @@ pub struct AgentState {
     pub usage_tracker: UsageTracker,
     pub workspace_dir: PathBuf,
     pub session_id: String,
+    pub skills: Vec<crate::skills::Skill>,
+    pub identity: crate::config::IdentityConfig,
+    /// Base prompt from `--system-file`, used instead of `build_system_prompt`
+    pub custom_prompt: Option<String>,
+    /// `--attach-file` contents, prepended to the next message sent
+    pub attachment: Option<String>,
+    /// Persona pack applied with `/persona`
+    pub persona: Option<String>,
+    /// The persona's prompt, layered after the base system prompt
+    pub persona_prompt: Option<String>,
+    /// Tools the persona switched off (not those already off via `/tools`)
+    pub persona_disabled_tools: HashSet<String>,
+    /// Keep reasoning blocks in saved answers (`--thinking` / `/thinking on`)
+    pub show_thinking: bool,
+    /// Run tool calls while the reply streams (`[tui] eager_tool_calls`)
+    pub eager_tool_calls: bool,
+    /// Results of read-only tool calls, when `[tui] cache_tool_results` is on
+    pub tool_cache: Option<Arc<ToolResultCache>>,
+    /// Characters of tool output to show, overriding each tool's own
+    /// (`[tui] tool_preview_len`)
+    pub tool_preview_len: Option<usize>,
+    /// Run each reply's tool calls one at a time (`[tui] sequential_tools`
+    /// / `/ordered`)
+    pub sequential_tools: bool,
+    /// Memories recalled into each message (`[memory] recall_limit`, 0 = off)
+    pub recall_limit: usize,
+    /// Identical replies in a row before the loop guard steps in
+    /// (`[tui] repeat_limit`, 0 = off)
+    pub repeat_limit: usize,
+    /// `[autonomy] level`, shown by `/info`
+    pub autonomy: AutonomyLevel,
+    /// Show a diff and ask before `file_write`/`file_patch` change a file
+    /// (supervised autonomy)
+    pub confirm_edits: bool,
+    /// Append-only turn log, when `[session] transcript` is on
+    pub transcript: Option<session::Transcript>,
+    /// When finished turns are written to the session file
+    /// (`[session] save_every_turns` / `save_interval_secs`)
+    pub save_schedule: session::SaveSchedule,
+    /// `[[security.output_transforms]]`, applied to each final reply
+    pub output_transforms: Arc<OutputTransforms>,
+    /// Characters per second to pace streamed text at (`[tui] smooth_output`)
+    pub smooth_output: Option<u32>,
 }
 
 impl AgentState {
+    /// Rebuild the system prompt from the currently enabled tools, replacing
+    /// the existing system message (or inserting one if history has none).
+    pub fn refresh_system_prompt(&mut self) {
+        let mut system_prompt = if let Some(custom) = &self.custom_prompt {
+            custom.clone()
+        } else {
+            let tool_descs: Vec<(&str, &str)> = TOOL_DESCS
+                .iter()
+                .copied()
+                .filter(|(name, _)| !self.disabled_tools.contains(*name))
+                .collect();
+            build_system_prompt(
+                &self.workspace_dir,
+                &self.model,
+                &tool_descs,
+                &self.skills,
+                Some(&self.identity),
+            )
+        };
+        if let Some(persona_prompt) = &self.persona_prompt {
+            let _ = write!(system_prompt, "## Persona\n\n{}\n\n", persona_prompt.trim());
+        }
+        system_prompt.push_str(&build_tool_instructions(
+            &self.tools_registry,
+            &self.disabled_tools,
+        ));
+
+        match self.history.first_mut() {
+            Some(first) if first.role == "system" => *first = ChatMessage::system(&system_prompt),
+            _ => self.history.insert(0, ChatMessage::system(&system_prompt)),
+        }
+    }
+
+    /// Rebuild the provider for `provider_name` and `model`, keeping history and
+    /// re-attaching the usage tracker. On error the current provider is kept.
+    pub fn switch_provider(&mut self, provider_name: &str, model: &str) -> Result<()> {
+        if model.is_empty() || model.chars().any(char::is_whitespace) {
+            anyhow::bail!("Invalid model name: {model:?}");
+        }
+        let mut provider = providers::with_response_cache(
+            providers::create_routed_provider(
+                provider_name,
+                self.api_key.as_deref(),
+                &self.providers,
+                &self.reliability,
+                &self.model_routes,
+                model,
+            )?,
+            provider_name,
+            &self.reliability,
+            &self.workspace_dir,
+        );
+        provider.set_usage_tracker(self.usage_tracker.clone());
+        provider.set_thinking(self.show_thinking);
+        provider.set_seed(self.providers.seed);
+        provider.set_reasoning_effort(self.providers.reasoning_effort);
+        provider.set_stop_sequences(&self.providers.stop_sequences)?;
+
+        self.provider = provider;
+        self.provider_name = provider_name.to_string();
+        self.model = model.to_string();
+        // The system prompt names the model
+        self.refresh_system_prompt();
+        Ok(())
+    }
+
+    /// Apply a persona pack on top of the session: its prompt is added after
+    /// the base (or `--system-file`) prompt, its tool set narrows whatever
+    /// `/tools` left enabled, and it switches model and temperature when it
+    /// names them. A previous persona's prompt and tool restrictions are
+    /// replaced. Nothing changes if the pack does not fit this session
+    /// (unknown tools, unusable model).
+    pub fn apply_persona(&mut self, persona: &crate::persona::Persona) -> Result<()> {
+        let disabled = persona.disabled_tools(self.tools_registry.iter().map(|t| t.name()))?;
+        if let Some(model) = &persona.model {
+            let provider_name = self.provider_name.clone();
+            self.switch_provider(&provider_name, model)?;
+        }
+        if let Some(temperature) = persona.temperature {
+            self.temperature = temperature;
+        }
+        for tool in self.persona_disabled_tools.drain() {
+            self.disabled_tools.remove(&tool);
+        }
+        self.persona_disabled_tools = disabled
+            .into_iter()
+            .filter(|tool| !self.disabled_tools.contains(tool))
+            .collect();
+        self.disabled_tools
+            .extend(self.persona_disabled_tools.iter().cloned());
+        self.persona_prompt.clone_from(&persona.prompt);
+        self.persona = Some(persona.name.clone());
+        self.refresh_system_prompt();
+        Ok(())
+    }
+
+    /// Turn reasoning display on or off, asking the provider for reasoning
+    /// content when it has a native API for it.
+    pub fn set_thinking(&mut self, enabled: bool) {
+        self.show_thinking = enabled;
+        self.provider.set_thinking(enabled);
+    }
+
+    /// Continue a saved session under the current system prompt; later turns
+    /// save back to it.
+    pub fn resume(&mut self, saved: session::Session) -> Result<()> {
+        if let Err(e) = self.flush_session() {
+            tracing::warn!("Failed to save session: {e}");
+        }
+        if self.transcript.is_some() {
+            self.transcript = Some(session::Transcript::open(&self.workspace_dir, &saved.id)?);
+        }
+        self.history = saved
+            .messages
+            .into_iter()
+            .filter(|m| m.role != "system")
+            .collect();
+        self.session_id = saved.id;
+        self.refresh_system_prompt();
+        Ok(())
+    }
+
+    /// Write the conversation to this session's file.
+    pub fn save_session(&mut self) -> Result<PathBuf> {
+        let path = session::update(
+            &self.workspace_dir,
+            &self.session_id,
+            &self.model,
+            &self.history,
+            self.tool_stats.snapshot(),
+        )?;
+        self.save_schedule.saved();
+        Ok(path)
+    }
+
+    /// Write turns the save schedule held back, if there are any.
+    pub fn flush_session(&mut self) -> Result<Option<PathBuf>> {
+        if self.save_schedule.unsaved_turns() == 0 {
+            return Ok(None);
+        }
+        self.save_session().map(Some)
+    }
+
+    /// A copy of the conversation when it has turns not saved yet, so they
+    /// can still be written if the TUI exits while the next reply generates.
+    pub fn unsaved_snapshot(&self) -> Option<session::Snapshot> {
+        (self.save_schedule.unsaved_turns() > 0).then(|| session::Snapshot {
+            workspace_dir: self.workspace_dir.clone(),
+            id: self.session_id.clone(),
+            model: self.model.clone(),
+            messages: self.history.clone(),
+            tool_stats: self.tool_stats.snapshot(),
+        })
+    }
+
+    /// Drop the most recent user message and everything after it (tool
+    /// rounds and the reply). The system prompt is never removed. Returns how
+    /// many messages went, or `None` when there is no user turn to undo.
+    pub fn undo_last_exchange(&mut self) -> Option<usize> {
+        let start = self
+            .history
+            .iter()
+            .rposition(|m| m.role == "user" && m.tool_results().is_none())?;
+        let removed = self.history.len() - start;
+        self.history.truncate(start);
+        Some(removed)
+    }
+
+    /// Text for `/info`: the provider, model and other settings this session
+    /// runs with, and how much of the history budget is left.
+    pub fn info(&self) -> String {
+        let autonomy = match self.autonomy {
+            AutonomyLevel::ReadOnly => "read-only",
+            AutonomyLevel::Supervised => "supervised",
+            AutonomyLevel::Full => "full",
+        };
+        let enabled: Vec<&str> = self
+            .tools_registry
+            .iter()
+            .map(|t| t.name())
+            .filter(|name| !self.disabled_tools.contains(*name))
+            .collect();
+        let tools = if enabled.is_empty() {
+            "none".to_string()
+        } else {
+            enabled.join(", ")
+        };
+        let messages = self.history.iter().filter(|m| m.role != "system").count();
+        let tokens = providers::tokens::token_counter().count_messages(&self.history, &self.model);
+
+        format!(
+            "Runtime:\n\
+             Provider:     {}\n\
+             Model:        {}\n\
+             Temperature:  {}\n\
+             Persona:      {}\n\
+             Autonomy:     {autonomy}\n\
+             Memory:       {} (auto-save {})\n\
+             Workspace:    {}\n\
+             Session:      {}\n\
+             Tools ({}/{}): {tools}\n\
+             \n\
+             Context window:\n\
+             Messages:     {messages} / {MAX_HISTORY_MESSAGES} ({} left)\n\
+             Tokens (est): {tokens} / {MAX_HISTORY_TOKENS} ({} left)",
+            self.provider_name,
+            self.model,
+            self.temperature,
+            self.persona.as_deref().unwrap_or("none"),
+            self.mem.name(),
+            if self.auto_save { "on" } else { "off" },
+            self.workspace_dir.display(),
+            self.session_id,
+            enabled.len(),
+            self.tools_registry.len(),
+            MAX_HISTORY_MESSAGES.saturating_sub(messages),
+            MAX_HISTORY_TOKENS.saturating_sub(tokens),
+        )
+    }
+
+    /// `(role, text)` for each user message and reply, as the chat pane shows
+    /// them. Tool results and injected memory context are left out.
+    pub fn visible_turns(&self) -> Vec<(&str, String)> {
+        self.history
+            .iter()
+            .filter_map(|msg| match msg.role.as_str() {
+                "user" if msg.tool_results().is_none() => {
+                    Some(("user", strip_memory_context(&msg.content).to_string()))
+                }
+                "assistant" => {
+                    let (_, answer) = split_thinking(&strip_tool_tags(&msg.content));
+                    Some(("assistant", answer))
+                }
+                _ => None,
+            })
+            .filter(|(_, text)| !text.trim().is_empty())
+            .collect()
+    }
+
+    /// Drop reasoning from the final answer and its history entry so it is
+    /// neither saved with the session nor sent back on later turns.
+    fn strip_thinking_from_answer(&mut self, response: String) -> String {
+        let (thinking, answer) = split_thinking(&response);
+        if thinking.is_empty() || answer.is_empty() {
+            return response;
+        }
+        if let Some(last) = self.history.last_mut() {
+            if last.role == "assistant" {
+                last.content.clone_from(&answer);
+            }
+        }
+        answer
+    }
+
+    /// Render the conversation as Markdown for `/export`, labelling each
+    /// message with when it was sent and, for replies, which model wrote it.
+    pub fn export_markdown(&self, assistant_name: &str) -> String {
+        let mut out = String::new();
+        for msg in &self.history {
+            let (label, body) = match msg.role.as_str() {
+                "system" => continue,
+                "user" | "tool" => match msg.tool_results() {
+                    Some(results) => ("Tool", results.to_string()),
+                    None => ("You", strip_memory_context(&msg.content).to_string()),
+                },
+                _ => (assistant_name, strip_tool_tags(&msg.content)),
+            };
+            let (thinking, answer) = split_thinking(&body);
+            let body = if answer.is_empty() { body } else { answer };
+            if body.trim().is_empty() {
+                continue;
+            }
+
+            let mut meta = Vec::new();
+            if let Some(ts) = msg.timestamp {
+                meta.push(ts.format("%Y-%m-%d %H:%M:%S UTC").to_string());
+            }
+            if let Some(model) = &msg.model {
+                meta.push(model.clone());
+            }
+            let meta = if meta.is_empty() {
+                String::new()
+            } else {
+                format!(" _({})_", meta.join(", "))
+            };
+
+            if self.show_thinking && !thinking.is_empty() {
+                let _ = write!(out, "**Thinking**: {thinking}\n\n");
+            }
+            let _ = write!(out, "**{label}**{meta}: {body}\n\n");
+        }
+        out
+    }
+
     /// Handle one user message: enrich with memory, run agent loop, send events.
     pub async fn handle_message(
         &mut self,
@@ -167,29 +674,48 @@ impl AgentState {
                 .await;
         }
 
-        let context = build_context(&*self.mem, user_input).await;
-        let enriched = if context.is_empty() {
-            user_input.to_string()
-        } else {
-            format!("{context}{user_input}")
-        };
+        let context = build_context(&*self.mem, user_input, self.recall_limit).await;
+        let enriched = format!(
+            "{context}{}",
+            crate::agent::attach::prepend(self.attachment.take().as_deref(), user_input)
+        );
 
+        if let Some(transcript) = &self.transcript {
+            transcript.user(user_input);
+        }
         self.history.push(ChatMessage::user(&enriched));
+        trim_history(&mut self.history, &self.model);
 
+        let turn = TurnContext::new(Some(&self.session_id));
         let result = agent_turn_with_events(
             &*self.provider,
             &mut self.history,
             &self.tools_registry,
+            &self.disabled_tools,
             &*self.observer,
+            &turn,
             &self.model,
             self.temperature,
+            &self.usage_tracker,
+            self.transcript.as_ref(),
+            self.eager_tool_calls,
+            self.tool_cache.as_ref(),
+            self.tool_preview_len,
+            self.sequential_tools,
+            self.repeat_limit,
+            self.confirm_edits,
+            &self.output_transforms,
+            self.smooth_output,
             event_tx,
         )
         .await;
 
         match result {
-            Ok(response) => {
-                trim_history(&mut self.history);
+            Ok(mut response) => {
+                if !self.show_thinking {
+                    response = self.strip_thinking_from_answer(response);
+                }
+                trim_history(&mut self.history, &self.model);
                 if self.auto_save {
                     let summary = truncate_with_ellipsis(&response, 100);
                     let _ = self
@@ -197,31 +723,43 @@ impl AgentState {
                         .store("assistant_resp", &summary, MemoryCategory::Daily)
                         .await;
                 }
-                // Auto-save session to disk
-                if let Err(e) = session::update(
-                    &self.workspace_dir,
-                    &self.session_id,
-                    &self.model,
-                    &self.history,
-                ) {
-                    tracing::warn!("Failed to save session: {e}");
+                // Auto-save session to disk, as often as `[session]` allows
+                if self.save_schedule.turn_finished() {
+                    if let Err(e) = self.save_session() {
+                        tracing::warn!("Failed to save session: {e}");
+                    }
                 }
 
                 let _ = event_tx.send(AgentEvent::Done(response)).await;
             }
             Err(e) => {
-                let _ = event_tx.send(AgentEvent::Error(format!("{e:#}"))).await;
+                if let Some(transcript) = &self.transcript {
+                    transcript.error(&format!("{e:#}"));
+                }
+                self.observer.record_event(&ObserverEvent::Error {
+                    component: "agent".into(),
+                    message: format!("{e:#}"),
+                    turn: Some(turn),
+                });
+                let event = match providers::auth_failure(&e) {
+                    Some(auth) => AgentEvent::AuthFailed(auth.to_string()),
+                    None => AgentEvent::Error(format!("{e:#}")),
+                };
+                let _ = event_tx.send(event).await;
             }
         }
     }
 }
 
-/// Build context from memory
-async fn build_context(mem: &dyn Memory, user_msg: &str) -> String {
+/// Build context from up to `limit` memories; empty when `limit` is 0
+async fn build_context(mem: &dyn Memory, user_msg: &str, limit: usize) -> String {
     let mut context = String::new();
-    if let Ok(entries) = mem.recall(user_msg, 5).await {
+    if limit == 0 {
+        return context;
+    }
+    if let Ok(entries) = mem.recall(user_msg, limit).await {
         if !entries.is_empty() {
-            context.push_str("[Memory context]\n");
+            context.push_str(memory::MEMORY_CONTEXT_PREFIX);
             for entry in &entries {
                 let _ = writeln!(context, "- {}: {}", entry.key, entry.content);
             }
@@ -231,6 +769,14 @@ async fn build_context(mem: &dyn Memory, user_msg: &str) -> String {
     context
 }
 
+/// The user's own text, without the memory block prepended by [`build_context`].
+fn strip_memory_context(content: &str) -> &str {
+    content
+        .strip_prefix(memory::MEMORY_CONTEXT_PREFIX)
+        .and_then(|rest| rest.split_once("\n\n"))
+        .map_or(content, |(_, message)| message)
+}
+
 /// Parse XML-style tool calls from response
 fn parse_tool_calls(response: &str) -> (String, Vec<ParsedToolCall>) {
     let mut text_parts = Vec::new();
@@ -267,107 +813,521 @@ fn parse_tool_calls(response: &str) -> (String, Vec<ParsedToolCall>) {
     (text_parts.join("\n"), calls)
 }
 
+#[derive(Clone, PartialEq)]
 struct ParsedToolCall {
     name: String,
     arguments: serde_json::Value,
 }
 
+/// How often smoothed output releases its next slice of text.
+const SMOOTH_TICK_MS: u64 = 16;
+
+/// Smoothed output speeds up so it never trails the stream by much more
+/// than this.
+const SMOOTH_MAX_LAG_MS: u64 = 500;
+
+/// Re-emit streamed text in even slices of about `chars_per_sec`, for
+/// `[tui] smooth_output`. The pace rises with the backlog so the display
+/// stays within [`SMOOTH_MAX_LAG_MS`] of the stream, and whatever is still
+/// buffered goes out at once when the stream ends.
+fn smooth_stream(
+    mut stream_rx: mpsc::Receiver<String>,
+    chars_per_sec: u32,
+) -> mpsc::Receiver<String> {
+    let (paced_tx, paced_rx) = mpsc::channel::<String>(64);
+    tokio::spawn(async move {
+        let mut pending = String::new();
+        // Thousandths of a character owed at `chars_per_sec`
+        let mut budget: u64 = 0;
+        let mut ticker = tokio::time::interval(std::time::Duration::from_millis(SMOOTH_TICK_MS));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                chunk = stream_rx.recv() => match chunk {
+                    Some(chunk) => pending.push_str(&chunk),
+                    None => break,
+                },
+                _ = ticker.tick(), if !pending.is_empty() => {
+                    budget += u64::from(chars_per_sec) * SMOOTH_TICK_MS;
+                    let backlog = pending.chars().count();
+                    let catch_up = (backlog as u64 * SMOOTH_TICK_MS).div_ceil(SMOOTH_MAX_LAG_MS);
+                    let count = (budget / 1000).max(catch_up);
+                    budget %= 1000;
+                    let split = usize::try_from(count)
+                        .ok()
+                        .and_then(|n| pending.char_indices().nth(n))
+                        .map_or(pending.len(), |(i, _)| i);
+                    let slice: String = pending.drain(..split).collect();
+                    if paced_tx.send(slice).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+        if !pending.is_empty() {
+            let _ = paced_tx.send(pending).await;
+        }
+    });
+    paced_rx
+}
+
+/// Forward streamed tokens to the UI, merging everything already buffered into
+/// a single `Token` event so a fast stream can't outpace the UI's drain loop.
+async fn forward_tokens(mut stream_rx: mpsc::Receiver<String>, event_tx: mpsc::Sender<AgentEvent>) {
+    while let Some(mut batch) = stream_rx.recv().await {
+        while let Ok(more) = stream_rx.try_recv() {
+            batch.push_str(&more);
+        }
+        if event_tx.send(AgentEvent::Token(batch)).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Finds `<tool_call>` blocks in a token stream as soon as their closing tag
+/// arrives.
+#[derive(Default)]
+struct ToolCallScanner {
+    buffer: String,
+    /// End of the last complete block; scanning resumes here
+    scanned: usize,
+}
+
+impl ToolCallScanner {
+    /// Append a chunk and return the calls whose blocks it completed.
+    fn push(&mut self, chunk: &str) -> Vec<ParsedToolCall> {
+        self.buffer.push_str(chunk);
+        let mut calls = Vec::new();
+        while let Some(offset) = self.buffer[self.scanned..].find("<tool_call>") {
+            let start = self.scanned + offset;
+            let Some(end) = self.buffer[start..].find("</tool_call>") else {
+                break;
+            };
+            let block_end = start + end + "</tool_call>".len();
+            calls.extend(parse_tool_calls(&self.buffer[start..block_end]).1);
+            self.scanned = block_end;
+        }
+        calls
+    }
+}
+
+/// What [`RepetitionGuard::observe`] wants done with a reply.
+#[derive(Debug, PartialEq, Eq)]
+enum Repetition {
+    /// Not a degenerate loop: run the tool calls
+    No,
+    /// Skip the calls and tell the model it is repeating itself
+    Warn,
+    /// Still repeating after the warning: end the turn
+    Abort,
+}
+
+/// Spots a model stuck sending the same reply and tool calls every
+/// iteration. The warning is given once per turn.
+struct RepetitionGuard {
+    limit: usize,
+    last: Option<(String, Vec<ParsedToolCall>)>,
+    streak: usize,
+    warned: bool,
+}
+
+impl RepetitionGuard {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            last: None,
+            streak: 0,
+            warned: false,
+        }
+    }
+
+    fn observe(&mut self, text: &str, calls: &[ParsedToolCall]) -> Repetition {
+        if self.limit == 0 {
+            return Repetition::No;
+        }
+        let text = text.trim();
+        let same = self
+            .last
+            .as_ref()
+            .is_some_and(|(last_text, last_calls)| last_text == text && last_calls == calls);
+        if same {
+            self.streak += 1;
+        } else {
+            self.last = Some((text.to_string(), calls.to_vec()));
+            self.streak = 1;
+        }
+        if self.streak < self.limit {
+            Repetition::No
+        } else if self.warned {
+            Repetition::Abort
+        } else {
+            self.warned = true;
+            Repetition::Warn
+        }
+    }
+}
+
+/// A tool call already running, keyed by the call that started it.
+type StartedToolCall = (ParsedToolCall, tokio::task::JoinHandle<ToolOutcome>);
+
+/// Like [`forward_tokens`], but also starts each tool call the moment its
+/// block is complete, so tools run while the rest of the reply generates.
+/// Only calls without side effects start early, since a call the final reply
+/// drops is aborted; the first other call holds back the rest of the reply.
+async fn forward_tokens_starting_tools(
+    mut stream_rx: mpsc::Receiver<String>,
+    event_tx: mpsc::Sender<AgentEvent>,
+    tools_registry: Arc<Vec<Box<dyn Tool>>>,
+    disabled_tools: HashSet<String>,
+    tool_cache: Option<Arc<ToolResultCache>>,
+    preview_len: Option<usize>,
+    edit_approval: Option<EditApproval>,
+) -> Vec<StartedToolCall> {
+    let mut scanner = ToolCallScanner::default();
+    let mut started = Vec::new();
+    // Calls from one that may have side effects on wait for the full reply,
+    // which then runs them once the calls started here have finished
+    let mut held = false;
+    while let Some(mut batch) = stream_rx.recv().await {
+        while let Ok(more) = stream_rx.try_recv() {
+            batch.push_str(&more);
+        }
+        let calls = scanner.push(&batch);
+        if event_tx.send(AgentEvent::Token(batch)).await.is_err() {
+            break;
+        }
+        for call in calls {
+            held = held
+                || !find_tool(&tools_registry, &call.name)
+                    .is_some_and(|t| t.side_effect_free(&call.arguments));
+            if held {
+                continue;
+            }
+            let _ = event_tx
+                .send(AgentEvent::ToolStart(call.name.clone()))
+                .await;
+            let handle = spawn_tool_call(
+                &call,
+                &tools_registry,
+                disabled_tools.contains(&call.name),
+                tool_cache.as_ref(),
+                preview_len,
+                edit_approval.as_ref(),
+                event_tx.clone(),
+            );
+            started.push((call, handle));
+        }
+    }
+    started
+}
+
+/// Tool name, output and how long it ran.
+type ToolOutcome = (String, String, std::time::Duration);
+
+/// Present when file edits need the user's approval. Edits take turns, so
+/// each diff is taken against what is on disk when it is shown.
+#[derive(Clone, Default)]
+struct EditApproval(Arc<tokio::sync::Mutex<()>>);
+
+/// Show the user the change a file-editing call would make and wait for
+/// their answer. Returns the error to report instead of running the call
+/// when they reject it.
+async fn confirm_edit(
+    tool: &dyn Tool,
+    args: &serde_json::Value,
+    tx: &mpsc::Sender<AgentEvent>,
+) -> Option<String> {
+    let edits = tool.proposed_edits(args).await;
+    if edits.is_empty() {
+        return None;
+    }
+    let path = edits
+        .iter()
+        .map(|e| e.path.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let diff = edits
+        .iter()
+        .map(|e| diff::unified(&e.path, &e.before, &e.after))
+        .collect();
+    let (reply, answer) = oneshot::channel();
+    let _ = tx
+        .send(AgentEvent::ConfirmRequired {
+            tool: tool.name().to_string(),
+            path: path.clone(),
+            diff,
+            reply,
+        })
+        .await;
+    // A UI that went away without answering counts as a rejection
+    if answer.await.unwrap_or(false) {
+        None
+    } else if edits.len() == 1 {
+        Some(format!(
+            "user rejected edit to {path}; the file was not changed"
+        ))
+    } else {
+        Some(format!(
+            "user rejected edits to {path}; no files were changed"
+        ))
+    }
+}
+
+/// Run one tool call on its own task, reporting the result to the TUI.
+fn spawn_tool_call(
+    call: &ParsedToolCall,
+    tools: &Arc<Vec<Box<dyn Tool>>>,
+    disabled: bool,
+    cache: Option<&Arc<ToolResultCache>>,
+    preview_len: Option<usize>,
+    edit_approval: Option<&EditApproval>,
+    tx: mpsc::Sender<AgentEvent>,
+) -> tokio::task::JoinHandle<ToolOutcome> {
+    let name = call.name.clone();
+    let args = call.arguments.clone();
+    let tools = Arc::clone(tools);
+    let cache = cache.cloned();
+    let edit_approval = edit_approval.cloned();
+    tokio::spawn(async move {
+        let start = Instant::now();
+        // Held until the approved edit is written
+        let mut _edit_turn = None;
+        let mut preview_len = preview_len;
+        let output = if disabled {
+            format!("Error: tool '{name}' is disabled for this session")
+        } else if let Some(tool) = tools.iter().find(|t| t.name() == name) {
+            preview_len = preview_len.or_else(|| tool.preview_len());
+            let mut rejected = None;
+            if let Some(approval) = &edit_approval {
+                if !tool.proposed_edits(&args).await.is_empty() {
+                    _edit_turn = Some(approval.0.lock().await);
+                    rejected = confirm_edit(tool.as_ref(), &args, &tx).await;
+                }
+            }
+            let result = match (rejected, &cache) {
+                (Some(rejected), _) => Ok(crate::tools::ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(rejected),
+                }),
+                (None, Some(cache)) => cache.execute(tool.as_ref(), args).await,
+                (None, None) => tool.execute_validated(args).await,
+            };
+            match result {
+                Ok(r) if r.success => r.output,
+                Ok(r) => format!("Error: {}", r.error.unwrap_or_else(|| r.output)),
+                Err(e) => format!("Error executing {name}: {e}"),
+            }
+        } else {
+            format!("Unknown tool: {name}")
+        };
+        let preview =
+            truncate_with_ellipsis(&output, preview_len.unwrap_or(DEFAULT_TOOL_PREVIEW_LEN));
+        let _ = tx
+            .send(AgentEvent::ToolResult {
+                name: name.clone(),
+                preview,
+            })
+            .await;
+        (name, output, start.elapsed())
+    })
+}
+
+/// Abort tool calls started mid-stream that the final reply does not contain.
+fn abort_started(started: impl IntoIterator<Item = StartedToolCall>) {
+    for (call, handle) in started {
+        tracing::warn!(
+            tool = call.name,
+            "Cancelling tool call missing from final reply"
+        );
+        handle.abort();
+    }
+}
+
 fn find_tool<'a>(tools: &'a [Box<dyn Tool>], name: &str) -> Option<&'a dyn Tool> {
     tools.iter().find(|t| t.name() == name).map(|t| t.as_ref())
 }
 
 /// Agent turn that sends events to the TUI
+#[allow(clippy::too_many_arguments, clippy::too_many_lines)]
 async fn agent_turn_with_events(
     provider: &dyn Provider,
     history: &mut Vec<ChatMessage>,
     tools_registry: &Arc<Vec<Box<dyn Tool>>>,
+    disabled_tools: &HashSet<String>,
     observer: &dyn Observer,
+    turn: &TurnContext,
     model: &str,
     temperature: f64,
+    usage_tracker: &UsageTracker,
+    transcript: Option<&session::Transcript>,
+    eager_tool_calls: bool,
+    tool_cache: Option<&Arc<ToolResultCache>>,
+    tool_preview_len: Option<usize>,
+    sequential_tools: bool,
+    repeat_limit: usize,
+    confirm_edits: bool,
+    output_transforms: &OutputTransforms,
+    smooth_output: Option<u32>,
     event_tx: &mpsc::Sender<AgentEvent>,
 ) -> Result<String> {
+    let mut repetition = RepetitionGuard::new(repeat_limit);
+    let edit_approval = confirm_edits.then(EditApproval::default);
     for _iteration in 0..MAX_TOOL_ITERATIONS {
-        // Use streaming if available for real-time token display
-        let response = if provider.supports_streaming() {
-            let (stream_tx, mut stream_rx) = mpsc::channel::<String>(64);
-            let event_tx2 = event_tx.clone();
-
-            // Forward stream tokens to TUI events
-            let forwarder = tokio::spawn(async move {
-                while let Some(token) = stream_rx.recv().await {
-                    let _ = event_tx2.send(AgentEvent::Token(token)).await;
-                }
-            });
+        let call_start = Instant::now();
+        let mut started = Vec::new();
+        // Use streaming if available for real-time token display. Streamed
+        // tokens would show the reply before the output transforms run.
+        let response = if provider.supports_streaming() && output_transforms.is_empty() {
+            let (stream_tx, stream_rx) = mpsc::channel::<String>(64);
+            let stream_rx = match smooth_output {
+                Some(chars_per_sec) => smooth_stream(stream_rx, chars_per_sec),
+                None => stream_rx,
+            };
+            let forwarder = if eager_tool_calls && !sequential_tools {
+                tokio::spawn(forward_tokens_starting_tools(
+                    stream_rx,
+                    event_tx.clone(),
+                    Arc::clone(tools_registry),
+                    disabled_tools.clone(),
+                    tool_cache.cloned(),
+                    tool_preview_len,
+                    edit_approval.clone(),
+                ))
+            } else {
+                let tx = event_tx.clone();
+                tokio::spawn(async move {
+                    forward_tokens(stream_rx, tx).await;
+                    Vec::new()
+                })
+            };
 
             let result = provider
-                .chat_with_history_stream(history, model, temperature, stream_tx)
+                .chat_with_history_stream_served(history, model, temperature, stream_tx)
                 .await;
-            forwarder.abort();
-            result?
+            // The provider dropped its sender; let the forwarder flush the tail
+            started = forwarder.await.unwrap_or_default();
+            match result {
+                Ok(served) => served,
+                Err(e) => {
+                    abort_started(started);
+                    return Err(e);
+                }
+            }
         } else {
-            let resp = provider
-                .chat_with_history(history, model, temperature)
+            let served = provider
+                .chat_with_history_served(history, model, temperature)
                 .await?;
-            let _ = event_tx.send(AgentEvent::Token(resp.clone())).await;
-            resp
+            if output_transforms.is_empty() {
+                let _ = event_tx.send(AgentEvent::Token(served.text.clone())).await;
+            }
+            served
         };
+        usage_tracker.record_latency(call_start.elapsed());
+        let _ = event_tx.send(AgentEvent::ServedBy(response.provider)).await;
+        let response = response.text;
 
         let (text, tool_calls) = parse_tool_calls(&response);
 
         if tool_calls.is_empty() {
-            history.push(ChatMessage::assistant(&response));
-            return Ok(if text.is_empty() { response } else { text });
+            abort_started(started);
+            let mut cleaned = strip_tool_tags(if text.is_empty() { &response } else { &text });
+            if !output_transforms.is_empty() {
+                cleaned = output_transforms.apply(&cleaned);
+            }
+            if let Some(transcript) = transcript {
+                transcript.assistant(&cleaned);
+            }
+            history.push(
+                ChatMessage::assistant(if cleaned.is_empty() {
+                    &response
+                } else {
+                    &cleaned
+                })
+                .with_model(model),
+            );
+            return Ok(cleaned);
         }
 
-        // Notify TUI of all tool starts
-        for call in &tool_calls {
-            let _ = event_tx
-                .send(AgentEvent::ToolStart(call.name.clone()))
-                .await;
+        match repetition.observe(&text, &tool_calls) {
+            Repetition::No => {}
+            Repetition::Warn => {
+                abort_started(started);
+                tracing::warn!("Model repeated the same reply {repeat_limit} times; nudging it");
+                history.push(ChatMessage::assistant(&response).with_model(model));
+                history.push(ChatMessage::tool(REPETITION_NOTICE));
+                continue;
+            }
+            Repetition::Abort => {
+                abort_started(started);
+                anyhow::bail!("Detected repetition: the model kept sending the same reply");
+            }
         }
 
-        // Execute tools concurrently when multiple are requested
-        let mut handles = Vec::with_capacity(tool_calls.len());
+        if let Some(transcript) = transcript {
+            if !text.is_empty() {
+                transcript.assistant(&text);
+            }
+            for call in &tool_calls {
+                transcript.tool_call(&call.name, &call.arguments);
+            }
+        }
+
+        // One at a time, each finishing before the next starts, when asked
+        // to or when a call edits files
+        let sequential = sequential_tools
+            || tool_calls.iter().any(|call| {
+                find_tool(tools_registry, &call.name).is_some_and(|t| !t.parallel_safe())
+            });
+
+        // Reuse calls already started mid-stream when the final reply agrees,
+        // and start the rest now
+        let mut started = started.into_iter().peekable();
+        let mut outcomes = Vec::with_capacity(tool_calls.len());
+        let mut running = Vec::with_capacity(tool_calls.len());
         for call in &tool_calls {
-            let name = call.name.clone();
-            let args = call.arguments.clone();
-            let tools = Arc::clone(tools_registry);
-            let tx = event_tx.clone();
-            handles.push(tokio::spawn(async move {
-                let start = Instant::now();
-                let output = if let Some(tool) = tools.iter().find(|t| t.name() == name) {
-                    match tool.execute(args).await {
-                        Ok(r) if r.success => r.output,
-                        Ok(r) => format!("Error: {}", r.error.unwrap_or_else(|| r.output)),
-                        Err(e) => format!("Error executing {name}: {e}"),
-                    }
-                } else {
-                    format!("Unknown tool: {name}")
-                };
-                let preview = if output.len() > 120 {
-                    format!("{}...", &output[..120])
-                } else {
-                    output.clone()
-                };
-                let _ = tx
-                    .send(AgentEvent::ToolResult {
-                        name: name.clone(),
-                        preview,
-                    })
+            let eager = started
+                .next_if(|(early, _)| early.name == call.name && early.arguments == call.arguments);
+            let handle = if let Some((_, handle)) = eager {
+                handle
+            } else {
+                let _ = event_tx
+                    .send(AgentEvent::ToolStart(call.name.clone()))
                     .await;
-                (name, output, start.elapsed())
-            }));
+                spawn_tool_call(
+                    call,
+                    tools_registry,
+                    disabled_tools.contains(&call.name),
+                    tool_cache,
+                    tool_preview_len,
+                    edit_approval.as_ref(),
+                    event_tx.clone(),
+                )
+            };
+            if sequential {
+                outcomes.push(handle.await);
+            } else {
+                running.push(handle);
+            }
+        }
+        abort_started(started);
+        for handle in running {
+            outcomes.push(handle.await);
         }
 
         // Collect results in order
         let mut tool_results = String::new();
-        for handle in handles {
-            match handle.await {
+        for outcome in outcomes {
+            match outcome {
                 Ok((name, output, duration)) => {
+                    if let Some(transcript) = transcript {
+                        transcript.tool_result(&name, &output);
+                    }
                     observer.record_event(&ObserverEvent::ToolCall {
                         tool: name.clone(),
                         duration,
                         success: !output.starts_with("Error"),
+                        turn: Some(turn.clone()),
                     });
                     let _ = writeln!(
                         tool_results,
@@ -383,16 +1343,17 @@ async fn agent_turn_with_events(
             }
         }
 
-        history.push(ChatMessage::assistant(&response));
-        history.push(ChatMessage::user(format!(
-            "[Tool results]\n{tool_results}"
-        )));
+        history.push(ChatMessage::assistant(&response).with_model(model));
+        history.push(ChatMessage::tool(tool_results));
     }
 
     anyhow::bail!("Agent exceeded maximum tool iterations ({MAX_TOOL_ITERATIONS})")
 }
 
-fn build_tool_instructions(tools_registry: &[Box<dyn Tool>]) -> String {
+fn build_tool_instructions(
+    tools_registry: &[Box<dyn Tool>],
+    disabled_tools: &HashSet<String>,
+) -> String {
     let mut instructions = String::new();
     instructions.push_str("\n## Tool Use Protocol\n\n");
     instructions.push_str(
@@ -407,30 +1368,1086 @@ fn build_tool_instructions(tools_registry: &[Box<dyn Tool>]) -> String {
         .push_str("Continue reasoning with the results until you can give a final answer.\n\n");
     instructions.push_str("### Available Tools\n\n");
 
-    for tool in tools_registry {
-        let _ = writeln!(
-            instructions,
-            "**{}**: {}\nParameters: `{}`\n",
-            tool.name(),
-            tool.description(),
-            tool.parameters_schema()
-        );
+    for tool in tools_registry
+        .iter()
+        .filter(|t| !disabled_tools.contains(t.name()))
+    {
+        let _ = writeln!(instructions, "{}", crate::tools::prompt_entry(tool.as_ref()));
     }
 
     instructions
 }
 
-fn trim_history(history: &mut Vec<ChatMessage>) {
+fn trim_history(history: &mut Vec<ChatMessage>, model: &str) {
     let has_system = history.first().map_or(false, |m| m.role == "system");
     let non_system_count = if has_system {
         history.len() - 1
     } else {
         history.len()
     };
-    if non_system_count <= MAX_HISTORY_MESSAGES {
-        return;
-    }
     let start = if has_system { 1 } else { 0 };
-    let to_remove = non_system_count - MAX_HISTORY_MESSAGES;
-    history.drain(start..start + to_remove);
+    if non_system_count > MAX_HISTORY_MESSAGES {
+        let to_remove = non_system_count - MAX_HISTORY_MESSAGES;
+        history.drain(start..start + to_remove);
+    }
+
+    // Context-window guard: drop the oldest messages until the estimate fits,
+    // always keeping the system prompt and the latest message
+    let counter = providers::tokens::token_counter();
+    while history.len() > start + 1 && counter.count_messages(history, model) > MAX_HISTORY_TOKENS {
+        history.remove(start);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_agent(workspace: &std::path::Path) -> AgentState {
+        let mut provider = providers::create_provider("ollama", None).unwrap();
+        let usage_tracker = UsageTracker::new();
+        provider.set_usage_tracker(usage_tracker.clone());
+        AgentState {
+            provider,
+            provider_name: "ollama".into(),
+            api_key: None,
+            reliability: crate::config::ReliabilityConfig::default(),
+            model_routes: Vec::new(),
+            providers: crate::config::ProvidersConfig::default(),
+            tools_registry: Arc::new(Vec::new()),
+            disabled_tools: HashSet::new(),
+            observer: Arc::new(crate::observability::NoopObserver),
+            tool_stats: ToolStats::new(),
+            mem: Arc::new(crate::memory::MarkdownMemory::new(workspace)),
+            history: Vec::new(),
+            model: "llama3".into(),
+            temperature: 0.7,
+            auto_save: false,
+            usage_tracker,
+            workspace_dir: workspace.to_path_buf(),
+            session_id: "test".into(),
+            skills: Vec::new(),
+            identity: crate::config::IdentityConfig::default(),
+            custom_prompt: None,
+            attachment: None,
+            persona: None,
+            persona_prompt: None,
+            persona_disabled_tools: HashSet::new(),
+            show_thinking: false,
+            eager_tool_calls: false,
+            tool_cache: None,
+            tool_preview_len: None,
+            sequential_tools: false,
+            recall_limit: 5,
+            repeat_limit: 3,
+            autonomy: AutonomyLevel::Supervised,
+            confirm_edits: false,
+            transcript: None,
+            save_schedule: session::SaveSchedule::default(),
+            output_transforms: Arc::default(),
+            smooth_output: None,
+        }
+    }
+
+    #[test]
+    fn switch_provider_keeps_history() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut agent = test_agent(tmp.path());
+        agent.refresh_system_prompt();
+        agent.history.push(ChatMessage::user("hello"));
+        agent.history.push(ChatMessage::assistant("hi"));
+
+        agent
+            .switch_provider("openrouter", "openai/gpt-4o")
+            .unwrap();
+        assert_eq!(agent.provider_name, "openrouter");
+        assert_eq!(agent.model, "openai/gpt-4o");
+        assert_eq!(agent.history.len(), 3);
+        assert_eq!(agent.history[0].role, "system");
+        assert_eq!(agent.history[1].content, "hello");
+    }
+
+    #[test]
+    fn info_works_before_the_first_turn() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut agent = test_agent(tmp.path());
+        let security = Arc::new(SecurityPolicy::default());
+        agent.tools_registry = Arc::new(vec![
+            Box::new(tools::FileReadTool::new(security)),
+            Box::new(tools::MemoryRecallTool::new(agent.mem.clone())),
+        ]);
+        agent.disabled_tools.insert("memory_recall".into());
+        agent.refresh_system_prompt();
+
+        let info = agent.info();
+        assert!(info.contains("Provider:     ollama"), "{info}");
+        assert!(info.contains("Model:        llama3"));
+        assert!(info.contains("Temperature:  0.7"));
+        assert!(info.contains("Autonomy:     supervised"));
+        assert!(info.contains("Memory:       markdown (auto-save off)"));
+        assert!(info.contains(&format!("Workspace:    {}", tmp.path().display())));
+        assert!(info.contains("Tools (1/2): file_read\n"));
+        assert!(info.contains(&format!(
+            "Messages:     0 / {MAX_HISTORY_MESSAGES} ({MAX_HISTORY_MESSAGES} left)"
+        )));
+        // The system prompt already counts against the token budget
+        let tokens = providers::tokens::token_counter().count_messages(&agent.history, "llama3");
+        assert!(tokens > 0);
+        assert!(info.contains(&format!(
+            "Tokens (est): {tokens} / {MAX_HISTORY_TOKENS} ({} left)",
+            MAX_HISTORY_TOKENS - tokens
+        )));
+    }
+
+    #[test]
+    fn undo_removes_last_exchange_but_never_the_system_prompt() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut agent = test_agent(tmp.path());
+        agent.refresh_system_prompt();
+        assert_eq!(agent.undo_last_exchange(), None);
+
+        agent.history.push(ChatMessage::user("first"));
+        agent.history.push(ChatMessage::assistant("one"));
+        agent.history.push(ChatMessage::user("oops"));
+        agent
+            .history
+            .push(ChatMessage::assistant("<tool_call>{}</tool_call>"));
+        agent.history.push(ChatMessage::user("[Tool results]\nok"));
+        agent.history.push(ChatMessage::assistant("done"));
+
+        assert_eq!(agent.undo_last_exchange(), Some(4));
+        assert_eq!(agent.history.len(), 3);
+        assert_eq!(agent.history[2].content, "one");
+
+        agent.save_session().unwrap();
+        let saved = session::load(tmp.path(), &agent.session_id).unwrap();
+        assert_eq!(saved.messages.len(), 3);
+
+        assert_eq!(agent.undo_last_exchange(), Some(2));
+        assert_eq!(agent.undo_last_exchange(), None);
+        assert_eq!(agent.history.len(), 1);
+        assert_eq!(agent.history[0].role, "system");
+    }
+
+    #[test]
+    fn resume_loads_saved_turns_under_current_prompt() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut agent = test_agent(tmp.path());
+        agent.refresh_system_prompt();
+        agent.history.push(ChatMessage::user("unsaved"));
+
+        let saved = vec![
+            ChatMessage::system("old prompt"),
+            ChatMessage::user("[Memory context]\n- fact\n\nlist files"),
+            ChatMessage::assistant("<tool_call>{\"name\":\"shell\"}</tool_call>"),
+            ChatMessage::user("[Tool results]\na.txt"),
+            ChatMessage::assistant("One file: a.txt"),
+        ];
+        session::save(tmp.path(), "earlier", "gpt-4", &saved).unwrap();
+        agent
+            .resume(session::load(tmp.path(), "earlier").unwrap())
+            .unwrap();
+
+        assert_eq!(agent.session_id, "earlier");
+        assert_eq!(agent.history.len(), 5);
+        assert_ne!(agent.history[0].content, "old prompt");
+        assert_eq!(
+            agent.visible_turns(),
+            vec![
+                ("user", "list files".to_string()),
+                ("assistant", "One file: a.txt".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn hidden_thinking_is_stripped_from_saved_answer() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut agent = test_agent(tmp.path());
+        let raw = "<thinking>Work it out.</thinking>\n\nIt is 4.";
+        agent.history.push(ChatMessage::assistant(raw));
+
+        let answer = agent.strip_thinking_from_answer(raw.to_string());
+        assert_eq!(answer, "It is 4.");
+        assert_eq!(agent.history.last().unwrap().content, "It is 4.");
+    }
+
+    #[test]
+    fn export_includes_timestamps_and_model() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut agent = test_agent(tmp.path());
+        agent.refresh_system_prompt();
+        agent.history.push(ChatMessage::user(
+            "[Memory context]\n- name: Ada\n\nWho am I?",
+        ));
+        agent
+            .history
+            .push(ChatMessage::assistant("You are Ada.").with_model("llama3"));
+        // Messages from an old session carry no metadata
+        let mut legacy = ChatMessage::user("thanks");
+        legacy.timestamp = None;
+        agent.history.push(legacy);
+
+        let out = agent.export_markdown("TinyClaw");
+        let lines: Vec<&str> = out.lines().filter(|l| !l.is_empty()).collect();
+        assert_eq!(lines.len(), 3, "{out}");
+        assert!(lines[0].starts_with("**You** _(20"), "{out}");
+        assert!(lines[0].ends_with(" UTC)_: Who am I?"), "{out}");
+        assert!(lines[1].starts_with("**TinyClaw** _(20"), "{out}");
+        assert!(lines[1].ends_with(", llama3)_: You are Ada."), "{out}");
+        assert_eq!(lines[2], "**You**: thanks");
+    }
+
+    #[test]
+    fn switch_provider_rejects_invalid_names() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut agent = test_agent(tmp.path());
+
+        assert!(agent.switch_provider("no-such-provider", "llama3").is_err());
+        assert!(agent.switch_provider("ollama", "").is_err());
+        assert!(agent.switch_provider("ollama", "two words").is_err());
+        assert_eq!(agent.provider_name, "ollama");
+        assert_eq!(agent.model, "llama3");
+    }
+
+    #[test]
+    fn apply_persona_sets_prompt_model_and_temperature() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut agent = test_agent(tmp.path());
+        agent.refresh_system_prompt();
+        agent.history.push(ChatMessage::user("hello"));
+
+        let persona = crate::persona::Persona {
+            name: "pirate".into(),
+            prompt: Some("You talk like a pirate.".into()),
+            model: Some("mistral".into()),
+            temperature: Some(1.2),
+            tools: None,
+        };
+        agent.apply_persona(&persona).unwrap();
+        assert_eq!(agent.persona.as_deref(), Some("pirate"));
+        assert_eq!(agent.model, "mistral");
+        assert!((agent.temperature - 1.2).abs() < f64::EPSILON);
+        assert!(agent.history[0].content.contains("You talk like a pirate."));
+        assert_eq!(agent.history.len(), 2);
+
+        // A pack naming tools this session lacks is refused untouched
+        let bad = crate::persona::Persona {
+            name: "ghost".into(),
+            tools: Some(vec!["teleport".into()]),
+            ..crate::persona::Persona::default()
+        };
+        assert!(agent.apply_persona(&bad).is_err());
+        assert_eq!(agent.persona.as_deref(), Some("pirate"));
+    }
+
+    #[test]
+    fn apply_persona_keeps_system_file_and_disabled_tools() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut agent = test_agent(tmp.path());
+        agent.tools_registry = Arc::new(tools::default_tools(Arc::new(SecurityPolicy::default())));
+        agent.custom_prompt = Some("Answer in haiku.\n\n".into());
+        agent.disabled_tools.insert("shell".into());
+        agent.refresh_system_prompt();
+
+        let reader = crate::persona::Persona {
+            name: "reader".into(),
+            prompt: Some("You only read files.".into()),
+            tools: Some(vec!["file_read".into(), "shell".into()]),
+            ..crate::persona::Persona::default()
+        };
+        agent.apply_persona(&reader).unwrap();
+        let prompt = &agent.history[0].content;
+        assert!(prompt.starts_with("Answer in haiku."));
+        assert!(prompt.contains("You only read files."));
+        assert!(agent.disabled_tools.contains("shell"));
+        assert!(agent.disabled_tools.contains("file_write"));
+
+        // The next persona replaces the last one's layer, not the session's
+        let open = crate::persona::Persona {
+            name: "open".into(),
+            ..crate::persona::Persona::default()
+        };
+        agent.apply_persona(&open).unwrap();
+        let prompt = &agent.history[0].content;
+        assert!(prompt.starts_with("Answer in haiku."));
+        assert!(!prompt.contains("You only read files."));
+        assert!(agent.disabled_tools.contains("shell"));
+        assert!(!agent.disabled_tools.contains("file_write"));
+    }
+
+    #[tokio::test]
+    async fn forward_tokens_coalesces_fast_stream() {
+        const TOKENS: usize = 10_000;
+        let (stream_tx, stream_rx) = mpsc::channel::<String>(64);
+        let (event_tx, mut event_rx) = mpsc::channel::<AgentEvent>(EVENT_CHANNEL_CAPACITY);
+        let forwarder = tokio::spawn(forward_tokens(stream_rx, event_tx));
+
+        let producer = tokio::spawn(async move {
+            for i in 0..TOKENS {
+                stream_tx.send(format!("{} ", i % 10)).await.unwrap();
+            }
+        });
+
+        // Simulate a slow UI that drains once per frame
+        let mut received = String::new();
+        let mut events = 0usize;
+        loop {
+            tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+            while let Ok(evt) = event_rx.try_recv() {
+                if let AgentEvent::Token(text) = evt {
+                    received.push_str(&text);
+                    events += 1;
+                }
+            }
+            if producer.is_finished() && forwarder.is_finished() {
+                while let Ok(AgentEvent::Token(text)) = event_rx.try_recv() {
+                    received.push_str(&text);
+                    events += 1;
+                }
+                break;
+            }
+        }
+
+        let mut expected = String::new();
+        for i in 0..TOKENS {
+            let _ = write!(expected, "{} ", i % 10);
+        }
+        assert_eq!(received, expected, "no tokens may be lost or reordered");
+        assert!(events < TOKENS, "expected coalescing, got {events} events");
+    }
+
+    #[tokio::test]
+    async fn smooth_stream_paces_text_without_changing_it() {
+        let (stream_tx, stream_rx) = mpsc::channel::<String>(64);
+        let mut paced = smooth_stream(stream_rx, 1000);
+        let text = "héllo wörld ".repeat(20);
+        stream_tx.send(text.clone()).await.unwrap();
+
+        let mut received = paced.recv().await.unwrap();
+        assert!(
+            received.chars().count() < text.chars().count(),
+            "a burst should be split up, got {} chars at once",
+            received.chars().count()
+        );
+        let mut slices = 1;
+        while received.len() < text.len() {
+            received.push_str(&paced.recv().await.unwrap());
+            slices += 1;
+        }
+        assert_eq!(received, text);
+        assert!(slices > 2, "expected several slices, got {slices}");
+
+        drop(stream_tx);
+        assert!(paced.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn smooth_stream_flushes_as_soon_as_stream_ends() {
+        let (stream_tx, stream_rx) = mpsc::channel::<String>(64);
+        // Slow enough that pacing alone would take minutes
+        let mut paced = smooth_stream(stream_rx, 10);
+        let text = "x".repeat(5_000);
+        let start = Instant::now();
+        stream_tx.send(text.clone()).await.unwrap();
+        drop(stream_tx);
+
+        let mut received = String::new();
+        while let Some(slice) = paced.recv().await {
+            received.push_str(&slice);
+        }
+        assert_eq!(received, text);
+        assert!(
+            start.elapsed() < std::time::Duration::from_millis(500),
+            "flush took {:?}",
+            start.elapsed()
+        );
+    }
+
+    #[test]
+    fn scanner_yields_calls_once_their_block_closes() {
+        let mut scanner = ToolCallScanner::default();
+        assert!(scanner.push("Let me check. <tool_").is_empty());
+        assert!(scanner.push("call>{\"name\": \"shell\", ").is_empty());
+        let calls = scanner.push("\"arguments\": {\"command\": \"ls\"}}</tool_call> and");
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "shell");
+        assert_eq!(calls[0].arguments["command"], "ls");
+
+        // Already reported blocks are not reported again
+        let calls = scanner.push(" <tool_call>{\"name\": \"git\"}</tool_call>");
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "git");
+        assert!(scanner.push(" done").is_empty());
+    }
+
+    /// Records whether it ran before the stream that requested it finished.
+    struct PingTool {
+        ran: Arc<tokio::sync::Notify>,
+        calls: Arc<std::sync::atomic::AtomicUsize>,
+        side_effect_free: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl Tool for PingTool {
+        fn name(&self) -> &str {
+            "ping"
+        }
+
+        fn description(&self) -> &str {
+            "Reply with pong"
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object", "properties": {}})
+        }
+
+        fn side_effect_free(&self, _args: &serde_json::Value) -> bool {
+            self.side_effect_free
+        }
+
+        async fn execute(&self, _args: serde_json::Value) -> Result<crate::tools::ToolResult> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.ran.notify_one();
+            Ok(crate::tools::ToolResult {
+                success: true,
+                output: "pong".into(),
+                error: None,
+            })
+        }
+    }
+
+    /// Streams a tool call, then waits briefly for the tool before finishing.
+    struct ToolCallingStream {
+        tool_ran: Arc<tokio::sync::Notify>,
+        ran_mid_stream: Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for ToolCallingStream {
+        fn name(&self) -> &str {
+            "tool-calling-stream"
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> Result<String> {
+            Ok("All good.".into())
+        }
+
+        fn supports_streaming(&self) -> bool {
+            true
+        }
+
+        async fn chat_with_history_stream(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+            token_tx: mpsc::Sender<String>,
+        ) -> Result<String> {
+            if messages.iter().any(|m| m.content.contains("<tool_result")) {
+                let _ = token_tx.send("All good.".into()).await;
+                return Ok("All good.".into());
+            }
+            let call = r#"Checking. <tool_call>{"name": "ping", "arguments": {}}</tool_call>"#;
+            let _ = token_tx.send(call.into()).await;
+            let waited = tokio::time::timeout(
+                std::time::Duration::from_millis(200),
+                self.tool_ran.notified(),
+            )
+            .await;
+            self.ran_mid_stream
+                .store(waited.is_ok(), std::sync::atomic::Ordering::SeqCst);
+            let _ = token_tx.send(" Still thinking.".into()).await;
+            Ok(format!("{call} Still thinking."))
+        }
+    }
+
+    async fn run_tool_turn(eager: bool, side_effect_free: bool) -> (bool, usize, String) {
+        let tool_ran = Arc::new(tokio::sync::Notify::new());
+        let ran_mid_stream = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let provider = ToolCallingStream {
+            tool_ran: tool_ran.clone(),
+            ran_mid_stream: ran_mid_stream.clone(),
+        };
+        let tools: Arc<Vec<Box<dyn Tool>>> = Arc::new(vec![Box::new(PingTool {
+            ran: tool_ran,
+            calls: calls.clone(),
+            side_effect_free,
+        })]);
+        let (event_tx, _event_rx) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
+        let mut history = vec![ChatMessage::user("ping it")];
+
+        let answer = agent_turn_with_events(
+            &provider,
+            &mut history,
+            &tools,
+            &HashSet::new(),
+            &crate::observability::NoopObserver,
+            &TurnContext::new(None),
+            "model",
+            0.7,
+            &UsageTracker::new(),
+            None,
+            eager,
+            None,
+            None,
+            false,
+            3,
+            false,
+            &OutputTransforms::default(),
+            None,
+            &event_tx,
+        )
+        .await
+        .unwrap();
+        assert!(history
+            .iter()
+            .any(|m| m.content.contains("<tool_result name=\"ping\">\npong")));
+        (
+            ran_mid_stream.load(std::sync::atomic::Ordering::SeqCst),
+            calls.load(std::sync::atomic::Ordering::SeqCst),
+            answer,
+        )
+    }
+
+    /// Prints multibyte text and asks for a short preview.
+    struct EuroTool;
+
+    #[async_trait::async_trait]
+    impl Tool for EuroTool {
+        fn name(&self) -> &str {
+            "euros"
+        }
+
+        fn description(&self) -> &str {
+            "Print euro signs"
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object", "properties": {}})
+        }
+
+        async fn execute(&self, _args: serde_json::Value) -> Result<crate::tools::ToolResult> {
+            Ok(crate::tools::ToolResult {
+                success: true,
+                output: format!("a{}", "€".repeat(200)),
+                error: None,
+            })
+        }
+
+        fn preview_len(&self) -> Option<usize> {
+            Some(10)
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_preview_is_cut_on_char_boundaries_at_the_chosen_length() {
+        let tools: Arc<Vec<Box<dyn Tool>>> = Arc::new(vec![Box::new(EuroTool)]);
+        let call = ParsedToolCall {
+            name: "euros".into(),
+            arguments: serde_json::json!({}),
+        };
+        let preview = |configured| {
+            let tools = Arc::clone(&tools);
+            let call = call.clone();
+            async move {
+                let (tx, mut rx) = mpsc::channel(4);
+                let (_, output, _) =
+                    spawn_tool_call(&call, &tools, false, None, configured, None, tx)
+                        .await
+                        .unwrap();
+                assert_eq!(output.chars().count(), 201, "the model gets it all");
+                match rx.recv().await {
+                    Some(AgentEvent::ToolResult { preview, .. }) => preview,
+                    other => panic!("expected a tool result, got {other:?}"),
+                }
+            }
+        };
+
+        // Byte 120 falls inside a '€', where slicing by bytes used to panic
+        assert_eq!(preview(Some(120)).await, format!("a{}...", "€".repeat(119)));
+        assert_eq!(preview(None).await, format!("a{}...", "€".repeat(9)));
+    }
+
+    #[tokio::test]
+    async fn eager_tool_calls_run_while_reply_streams() {
+        let (mid_stream, calls, answer) = run_tool_turn(true, true).await;
+        assert!(mid_stream, "tool should start before the stream ends");
+        assert_eq!(calls, 1, "eager call is reused, not run twice");
+        assert_eq!(answer, "All good.");
+
+        let (mid_stream, calls, answer) = run_tool_turn(false, true).await;
+        assert!(!mid_stream, "default waits for the full reply");
+        assert_eq!(calls, 1);
+        assert_eq!(answer, "All good.");
+
+        let (mid_stream, calls, answer) = run_tool_turn(true, false).await;
+        assert!(!mid_stream, "side effects wait for the full reply");
+        assert_eq!(calls, 1);
+        assert_eq!(answer, "All good.");
+    }
+
+    #[tokio::test]
+    async fn output_transforms_turn_off_token_streaming() {
+        let security = crate::config::SecurityConfig {
+            output_transforms: vec![crate::config::schema::OutputTransform {
+                pattern: "good".into(),
+                replacement: "fine".into(),
+            }],
+            ..crate::config::SecurityConfig::default()
+        };
+        let provider = ToolCallingStream {
+            tool_ran: Arc::new(tokio::sync::Notify::new()),
+            ran_mid_stream: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        };
+        let (event_tx, mut event_rx) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
+        let mut history = vec![ChatMessage::user("hi")];
+
+        let answer = agent_turn_with_events(
+            &provider,
+            &mut history,
+            &Arc::new(Vec::new()),
+            &HashSet::new(),
+            &crate::observability::NoopObserver,
+            &TurnContext::new(None),
+            "model",
+            0.7,
+            &UsageTracker::new(),
+            None,
+            false,
+            None,
+            None,
+            false,
+            3,
+            false,
+            &OutputTransforms::from_config(&security).unwrap(),
+            None,
+            &event_tx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(answer, "All fine.");
+        drop(event_tx);
+        while let Some(event) = event_rx.recv().await {
+            assert!(!matches!(event, AgentEvent::Token(_)), "raw reply shown");
+        }
+    }
+
+    /// Sends the same tool call forever, or answers once it has been told
+    /// it is repeating itself (when `recovers`).
+    struct StuckProvider {
+        recovers: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for StuckProvider {
+        fn name(&self) -> &str {
+            "stuck"
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> Result<String> {
+            if self.recovers && message.contains(REPETITION_NOTICE) {
+                return Ok("Done pinging.".into());
+            }
+            Ok(r#"Again. <tool_call>{"name": "ping", "arguments": {}}</tool_call>"#.into())
+        }
+    }
+
+    async fn run_stuck_turn(
+        recovers: bool,
+        repeat_limit: usize,
+    ) -> (Result<String>, usize, Vec<ChatMessage>) {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let tools: Arc<Vec<Box<dyn Tool>>> = Arc::new(vec![Box::new(PingTool {
+            ran: Arc::new(tokio::sync::Notify::new()),
+            calls: calls.clone(),
+            side_effect_free: true,
+        })]);
+        let (event_tx, _event_rx) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
+        let mut history = vec![ChatMessage::user("ping it")];
+        let result = agent_turn_with_events(
+            &StuckProvider { recovers },
+            &mut history,
+            &tools,
+            &HashSet::new(),
+            &crate::observability::NoopObserver,
+            &TurnContext::new(None),
+            "model",
+            0.7,
+            &UsageTracker::new(),
+            None,
+            false,
+            None,
+            None,
+            false,
+            repeat_limit,
+            false,
+            &OutputTransforms::default(),
+            None,
+            &event_tx,
+        )
+        .await;
+        (
+            result,
+            calls.load(std::sync::atomic::Ordering::SeqCst),
+            history,
+        )
+    }
+
+    #[tokio::test]
+    async fn repeated_identical_replies_end_the_turn_early() {
+        let (result, calls, history) = run_stuck_turn(false, 3).await;
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("Detected repetition"), "{err}");
+        // Run twice, skipped on the third identical reply, stopped on the fourth
+        assert_eq!(calls, 2);
+        let notices = history
+            .iter()
+            .filter(|m| m.content.contains(REPETITION_NOTICE))
+            .count();
+        assert_eq!(notices, 1);
+
+        let (result, calls, _) = run_stuck_turn(false, 0).await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("maximum tool iterations"));
+        assert_eq!(calls, MAX_TOOL_ITERATIONS);
+    }
+
+    #[tokio::test]
+    async fn repetition_notice_lets_the_model_recover() {
+        let (result, calls, history) = run_stuck_turn(true, 2).await;
+        assert_eq!(result.unwrap(), "Done pinging.");
+        assert_eq!(calls, 1);
+        assert!(history
+            .iter()
+            .any(|m| m.role == "tool" && m.content == REPETITION_NOTICE));
+    }
+
+    /// Logs when each call starts and ends, taking a while in between.
+    struct StepTool {
+        log: Arc<std::sync::Mutex<Vec<String>>>,
+        parallel_safe: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl Tool for StepTool {
+        fn name(&self) -> &str {
+            "step"
+        }
+
+        fn description(&self) -> &str {
+            "Do one numbered step"
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object", "properties": {"n": {"type": "integer"}}})
+        }
+
+        fn parallel_safe(&self) -> bool {
+            self.parallel_safe
+        }
+
+        async fn execute(&self, args: serde_json::Value) -> Result<crate::tools::ToolResult> {
+            let n = &args["n"];
+            self.log.lock().unwrap().push(format!("start {n}"));
+            tokio::time::sleep(std::time::Duration::from_millis(30)).await;
+            self.log.lock().unwrap().push(format!("end {n}"));
+            Ok(crate::tools::ToolResult {
+                success: true,
+                output: format!("step {n} done"),
+                error: None,
+            })
+        }
+    }
+
+    /// Asks for steps 1, 2 and 3 in one reply, then answers.
+    struct ThreeStepProvider;
+
+    #[async_trait::async_trait]
+    impl Provider for ThreeStepProvider {
+        fn name(&self) -> &str {
+            "three-steps"
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> Result<String> {
+            if message.contains("<tool_result") {
+                return Ok("Done.".into());
+            }
+            Ok((1..=3).fold(String::new(), |mut reply, n| {
+                let _ = write!(
+                    reply,
+                    r#"<tool_call>{{"name": "step", "arguments": {{"n": {n}}}}}</tool_call>"#
+                );
+                reply
+            }))
+        }
+    }
+
+    async fn run_step_turn(sequential_tools: bool, parallel_safe: bool) -> Vec<String> {
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let tools: Arc<Vec<Box<dyn Tool>>> = Arc::new(vec![Box::new(StepTool {
+            log: log.clone(),
+            parallel_safe,
+        })]);
+        let (event_tx, _event_rx) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
+        let mut history = vec![ChatMessage::user("do the steps")];
+        let answer = agent_turn_with_events(
+            &ThreeStepProvider,
+            &mut history,
+            &tools,
+            &HashSet::new(),
+            &crate::observability::NoopObserver,
+            &TurnContext::new(None),
+            "model",
+            0.7,
+            &UsageTracker::new(),
+            None,
+            false,
+            None,
+            None,
+            sequential_tools,
+            3,
+            false,
+            &OutputTransforms::default(),
+            None,
+            &event_tx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(answer, "Done.");
+        let results = history.iter().find_map(ChatMessage::tool_results).unwrap();
+        let order: Vec<_> = results
+            .match_indices("step ")
+            .map(|(i, _)| &results[i..i + 6])
+            .collect();
+        assert_eq!(
+            order,
+            ["step 1", "step 2", "step 3"],
+            "results keep call order"
+        );
+        let log = log.lock().unwrap().clone();
+        log
+    }
+
+    #[tokio::test]
+    async fn sequential_tool_calls_run_one_at_a_time_in_order() {
+        let in_order = ["start 1", "end 1", "start 2", "end 2", "start 3", "end 3"];
+        assert_eq!(run_step_turn(true, true).await, in_order);
+        // A tool that isn't parallel-safe forces it without the setting
+        assert_eq!(run_step_turn(false, false).await, in_order);
+
+        let concurrent = run_step_turn(false, true).await;
+        assert_eq!(concurrent[..3], ["start 1", "start 2", "start 3"]);
+    }
+
+    /// Asks to write `notes.txt`, then answers once it sees the result.
+    struct EditingProvider;
+
+    #[async_trait::async_trait]
+    impl Provider for EditingProvider {
+        fn name(&self) -> &str {
+            "editing"
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> Result<String> {
+            if message.contains("<tool_result") {
+                return Ok("Done.".into());
+            }
+            Ok(r#"<tool_call>{"name": "file_write", "arguments": {"path": "notes.txt", "content": "new\n"}}</tool_call>"#.into())
+        }
+    }
+
+    /// Run an edit turn under supervision, answering the confirmation with
+    /// `approve`. Returns the diff shown, the file afterwards and the history.
+    async fn run_edit_turn(approve: bool) -> (String, String, Vec<ChatMessage>) {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("notes.txt"), "old\n").unwrap();
+        let security = Arc::new(SecurityPolicy {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        });
+        let tools: Arc<Vec<Box<dyn Tool>>> =
+            Arc::new(vec![Box::new(crate::tools::FileWriteTool::new(security))]);
+        let (event_tx, mut event_rx) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
+        let reviewer = tokio::spawn(async move {
+            while let Some(event) = event_rx.recv().await {
+                if let AgentEvent::ConfirmRequired { diff, reply, .. } = event {
+                    reply.send(approve).unwrap();
+                    return diff;
+                }
+            }
+            panic!("no confirmation requested");
+        });
+
+        let mut history = vec![ChatMessage::user("update the notes")];
+        let answer = agent_turn_with_events(
+            &EditingProvider,
+            &mut history,
+            &tools,
+            &HashSet::new(),
+            &crate::observability::NoopObserver,
+            &TurnContext::new(None),
+            "model",
+            0.7,
+            &UsageTracker::new(),
+            None,
+            false,
+            None,
+            None,
+            false,
+            3,
+            true,
+            &OutputTransforms::default(),
+            None,
+            &event_tx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(answer, "Done.");
+        let diff = reviewer.await.unwrap();
+        let content = std::fs::read_to_string(tmp.path().join("notes.txt")).unwrap();
+        (diff, content, history)
+    }
+
+    #[tokio::test]
+    async fn supervised_edits_wait_for_approval() {
+        let (diff, content, history) = run_edit_turn(true).await;
+        assert!(diff.contains("-old\n+new\n"), "{diff}");
+        assert_eq!(content, "new\n");
+        assert!(history.iter().any(|m| m.content.contains("Written 4 bytes")));
+
+        let (_, content, history) = run_edit_turn(false).await;
+        assert_eq!(content, "old\n");
+        assert!(history
+            .iter()
+            .any(|m| m.content.contains("Error: user rejected edit to notes.txt")));
+    }
+
+    /// Answers every message the same way, without tool calls.
+    struct EchoProvider;
+
+    #[async_trait::async_trait]
+    impl Provider for EchoProvider {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> Result<String> {
+            Ok("Noted.".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn rapid_turns_are_saved_in_batches() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut agent = test_agent(tmp.path());
+        agent.provider = Box::new(EchoProvider);
+        agent.save_schedule = session::SaveSchedule::new(3, 0);
+        agent.refresh_system_prompt();
+        let (event_tx, mut event_rx) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
+        tokio::spawn(async move { while event_rx.recv().await.is_some() {} });
+
+        let saved_messages = |agent: &AgentState| {
+            session::load(tmp.path(), &agent.session_id)
+                .map(|s| s.messages.len())
+                .ok()
+        };
+        let mut saved = Vec::new();
+        for i in 0..7 {
+            agent.handle_message(&format!("turn {i}"), &event_tx).await;
+            saved.push(saved_messages(&agent));
+        }
+        // System prompt plus a user message and a reply per turn
+        assert_eq!(
+            saved,
+            [None, None, Some(7), Some(7), Some(7), Some(13), Some(13)]
+        );
+
+        let unsaved = agent.unsaved_snapshot().expect("turn 7 is held back");
+        assert_eq!(unsaved.messages.len(), 15);
+        assert!(agent.flush_session().unwrap().is_some());
+        assert_eq!(saved_messages(&agent), Some(15));
+        assert!(agent.flush_session().unwrap().is_none());
+        assert!(agent.unsaved_snapshot().is_none());
+    }
+
+    #[tokio::test]
+    async fn forward_tokens_stops_when_ui_is_gone() {
+        let (stream_tx, stream_rx) = mpsc::channel::<String>(4);
+        let (event_tx, event_rx) = mpsc::channel::<AgentEvent>(1);
+        drop(event_rx);
+        let forwarder = tokio::spawn(forward_tokens(stream_rx, event_tx));
+        stream_tx.send("hi".into()).await.unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(1), forwarder)
+            .await
+            .expect("forwarder should exit")
+            .unwrap();
+    }
+
+    #[test]
+    fn trim_history_caps_message_count() {
+        let mut history = vec![ChatMessage::system("sys")];
+        for i in 0..MAX_HISTORY_MESSAGES + 5 {
+            history.push(ChatMessage::user(format!("msg {i}")));
+        }
+        trim_history(&mut history, "model");
+        assert_eq!(history.len(), MAX_HISTORY_MESSAGES + 1);
+        assert_eq!(history[0].role, "system");
+        assert_eq!(history[1].content, "msg 5");
+    }
+
+    #[test]
+    fn trim_history_enforces_token_budget() {
+        let big = "x".repeat(MAX_HISTORY_TOKENS * 8);
+        let mut history = vec![
+            ChatMessage::system("sys"),
+            ChatMessage::user(&big),
+            ChatMessage::assistant("ok"),
+            ChatMessage::user("latest"),
+        ];
+        trim_history(&mut history, "model");
+        let contents: Vec<&str> = history.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["sys", "ok", "latest"]);
+    }
+
+    #[test]
+    fn trim_history_keeps_latest_message_even_if_oversized() {
+        let big = "x".repeat(MAX_HISTORY_TOKENS * 8);
+        let mut history = vec![ChatMessage::system("sys"), ChatMessage::user(&big)];
+        trim_history(&mut history, "model");
+        assert_eq!(history.len(), 2);
+    }
 }
@@ -0,0 +1,165 @@
+use std::fmt::Write;
+
+/// Slash commands the TUI handles, in `/help` order.
+pub const COMMANDS: &[(&str, &str)] = &[
+    ("/help", "Show this help"),
+    ("/cost", "Show token usage, cost and latency"),
+    ("/stats", "Show per-tool call counts and timings"),
+    ("/info", "Show provider, model, settings and headroom"),
+    ("/clear", "Clear message history (keeps system prompt)"),
+    ("/undo", "Remove the last message and its reply"),
+    ("/edit", "Write a message in $EDITOR (/edit [draft])"),
+    ("/model", "Show or switch model (/model <name>)"),
+    (
+        "/provider",
+        "Show or switch provider (/provider <name> [model])",
+    ),
+    ("/tools", "List tools (/tools enable|disable <name>)"),
+    ("/persona", "List personas or apply one (/persona <name>)"),
+    (
+        "/thinking",
+        "Show or hide model reasoning (/thinking on|off)",
+    ),
+    (
+        "/plain",
+        "Toggle unstyled message rendering (/plain on|off)",
+    ),
+    ("/ordered", "Run tool calls one at a time (/ordered on|off)"),
+    ("/sessions", "List saved sessions"),
+    ("/session", "Show current session ID"),
+    ("/resume", "Continue a saved session (/resume <id>)"),
+    ("/export", "Export conversation to file"),
+    ("/quit", "Exit TinyClaw"),
+];
+
+/// Text for `/help`, built from [`COMMANDS`] so the two stay in sync.
+pub fn help_text() -> String {
+    let mut out = String::from("Available commands:");
+    for (name, description) in COMMANDS {
+        let _ = write!(out, "\n{name:<9} - {description}");
+    }
+    out.push_str("\nCtrl+P opens the command palette; Up/Down recall earlier input.");
+    out
+}
+
+/// Score `candidate` against `query` as a case-insensitive subsequence
+/// match, higher is better. Consecutive and leading matches score more.
+/// Returns `None` when some query character is missing.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<u32> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut previous: Option<usize> = None;
+    for q in query.to_lowercase().chars().filter(|c| *c != '/') {
+        let offset = candidate[pos..].iter().position(|c| *c == q)?;
+        let index = pos + offset;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == index) {
+            score += 3;
+        }
+        // Right after the leading slash
+        if index == 1 {
+            score += 5;
+        }
+        previous = Some(index);
+        pos = index + 1;
+    }
+    Some(score)
+}
+
+/// Ctrl+P command palette: a query and the highlighted row.
+#[derive(Default)]
+pub struct Palette {
+    pub query: String,
+    pub selected: usize,
+}
+
+impl Palette {
+    /// Commands matching the query, best first; ties keep `/help` order.
+    pub fn matches(&self) -> Vec<(&'static str, &'static str)> {
+        let mut scored: Vec<(u32, (&str, &str))> = COMMANDS
+            .iter()
+            .filter_map(|&entry| fuzzy_score(&self.query, entry.0).map(|s| (s, entry)))
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0));
+        scored.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.selected = 0;
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self) {
+        let count = self.matches().len();
+        if self.selected + 1 < count {
+            self.selected += 1;
+        }
+    }
+
+    /// The highlighted command, if any match.
+    pub fn selection(&self) -> Option<&'static str> {
+        self.matches().get(self.selected).map(|(name, _)| *name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_prefers_leading_and_consecutive_matches() {
+        assert!(fuzzy_score("xyz", "/model").is_none());
+        assert_eq!(fuzzy_score("", "/model"), Some(0));
+        assert!(fuzzy_score("mdl", "/model").is_some());
+        assert!(fuzzy_score("/MOD", "/model").is_some());
+        assert!(fuzzy_score("sn", "/sessions") > fuzzy_score("sn", "/persona"));
+        assert!(fuzzy_score("ab", "/xabx") > fuzzy_score("ab", "/xaxb"));
+    }
+
+    #[test]
+    fn palette_filters_and_selects() {
+        let mut palette = Palette::default();
+        assert_eq!(palette.matches().len(), COMMANDS.len());
+        assert_eq!(palette.selection(), Some("/help"));
+
+        for c in "thk".chars() {
+            palette.push(c);
+        }
+        assert_eq!(palette.selection(), Some("/thinking"));
+
+        palette.pop();
+        palette.pop();
+        palette.pop();
+        palette.push('s');
+        let names: Vec<&str> = palette.matches().iter().map(|(n, _)| *n).collect();
+        assert_eq!(names[..3], ["/stats", "/sessions", "/session"]);
+        palette.select_next();
+        assert_eq!(palette.selection(), Some("/sessions"));
+        palette.select_prev();
+        palette.select_prev();
+        assert_eq!(palette.selection(), Some("/stats"));
+
+        palette.push('q');
+        assert!(palette.matches().is_empty());
+        assert_eq!(palette.selection(), None);
+    }
+
+    #[test]
+    fn help_lists_every_command() {
+        let help = help_text();
+        for (name, _) in COMMANDS {
+            assert!(help.contains(&format!("\n{name} ")), "{name} missing");
+        }
+        assert!(help.contains("/help     - Show this help"));
+    }
+}
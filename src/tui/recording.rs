@@ -0,0 +1,178 @@
+//! `tinyclaw tui --record <file>`: what the TUI showed, as JSON lines with
+//! the milliseconds since the session started, so `tinyclaw tui --replay
+//! <file>` can play it back at the same pace without calling a provider.
+//!
+//! Agent events are stored as they reached the UI (after streamed tokens
+//! were coalesced), along with the user's messages, slash command output and
+//! answers to edit reviews. Replies are recorded after
+//! `[[security.output_transforms]]` ran (the TUI doesn't stream tokens when
+//! any are set), so replay shows them exactly as they were and needs no
+//! config of its own.
+
+use super::AgentEvent;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::time::Instant;
+
+/// One line of a recording. `E` is `&AgentEvent` when writing and
+/// [`AgentEvent`] when reading.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Recorded<E = AgentEvent> {
+    /// First line: the model shown in the header
+    Start { model: String },
+    /// A message sent to the agent
+    User { text: String },
+    /// Output of a slash command
+    Notice { text: String },
+    /// An event from the agent
+    Event { event: E },
+    /// The answer to an edit review (`true` applied it)
+    EditAnswer { approve: bool },
+}
+
+/// A recorded item and when it happened.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry<E = AgentEvent> {
+    pub at_ms: u64,
+    #[serde(flatten)]
+    pub item: Recorded<E>,
+}
+
+/// Appends entries to a recording, one flushed line each, so the file is
+/// usable even if the TUI is killed.
+pub struct Recorder {
+    file: File,
+    started: Instant,
+}
+
+impl Recorder {
+    /// Create (or truncate) `path` and write the [`Recorded::Start`] line.
+    pub fn create(path: &Path, model: &str) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create recording {}", path.display()))?;
+        let mut recorder = Self {
+            file,
+            started: Instant::now(),
+        };
+        recorder.record(Recorded::Start {
+            model: model.to_string(),
+        })?;
+        Ok(recorder)
+    }
+
+    pub fn record(&mut self, item: Recorded<&AgentEvent>) -> Result<()> {
+        let entry = Entry {
+            at_ms: u64::try_from(self.started.elapsed().as_millis()).unwrap_or(u64::MAX),
+            item,
+        };
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+/// Read a recording made with `--record`.
+pub fn load(path: &Path) -> Result<Vec<Entry>> {
+    let file =
+        File::open(path).with_context(|| format!("Failed to open recording {}", path.display()))?;
+    let mut entries = Vec::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = serde_json::from_str(&line)
+            .with_context(|| format!("{}:{}: not a recording entry", path.display(), i + 1))?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::oneshot;
+
+    #[test]
+    fn recording_round_trips_events_in_order() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("demo.jsonl");
+        let mut recorder = Recorder::create(&path, "llama3").unwrap();
+        recorder
+            .record(Recorded::User {
+                text: "list files".into(),
+            })
+            .unwrap();
+        let (reply, _answer) = oneshot::channel();
+        for event in [
+            AgentEvent::Token("Checking".into()),
+            AgentEvent::ToolStart("shell".into()),
+            AgentEvent::ToolResult {
+                name: "shell".into(),
+                preview: "a.txt".into(),
+            },
+            AgentEvent::ConfirmRequired {
+                tool: "file_write".into(),
+                path: "a.txt".into(),
+                diff: "+hi\n".into(),
+                reply,
+            },
+            AgentEvent::Done("One file.".into()),
+        ] {
+            recorder.record(Recorded::Event { event: &event }).unwrap();
+        }
+        recorder
+            .record(Recorded::EditAnswer { approve: true })
+            .unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text
+            .lines()
+            .nth(2)
+            .unwrap()
+            .contains(r#""kind":"event","event":{"type":"token","data":"Checking"}"#));
+
+        let entries = load(&path).unwrap();
+        assert_eq!(entries.len(), 8);
+        assert!(entries.windows(2).all(|w| w[0].at_ms <= w[1].at_ms));
+        assert!(matches!(&entries[0].item, Recorded::Start { model } if model == "llama3"));
+        assert!(matches!(&entries[1].item, Recorded::User { text } if text == "list files"));
+        assert!(matches!(
+            &entries[4].item,
+            Recorded::Event { event: AgentEvent::ToolResult { name, preview } }
+                if name == "shell" && preview == "a.txt"
+        ));
+        // The edit's answer channel is not recorded; replay gets a closed one
+        let Recorded::Event {
+            event: AgentEvent::ConfirmRequired { diff, reply, .. },
+        } = &entries[5].item
+        else {
+            panic!("expected the edit review, got {:?}", entries[5]);
+        };
+        assert_eq!(diff, "+hi\n");
+        assert!(reply.is_closed());
+        assert!(matches!(
+            entries[7].item,
+            Recorded::EditAnswer { approve: true }
+        ));
+    }
+
+    #[test]
+    fn bad_line_is_reported_with_its_number() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("broken.jsonl");
+        std::fs::write(
+            &path,
+            "{\"at_ms\":0,\"kind\":\"start\",\"model\":\"m\"}\n\nnot json\n",
+        )
+        .unwrap();
+        let err = load(&path).unwrap_err();
+        assert!(format!("{err:#}").contains("broken.jsonl:3"), "{err:#}");
+    }
+}
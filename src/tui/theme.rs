@@ -0,0 +1,240 @@
+//! TUI color themes: named presets plus per-role overrides from `[tui]`.
+
+use crate::config::TuiConfig;
+use ratatui::style::Color;
+use std::str::FromStr;
+
+/// Preset names accepted by `[tui] theme` and `--theme`.
+pub const PRESETS: &[&str] = &["dark", "light", "high-contrast", "monochrome"];
+
+/// Every color the TUI draws with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Header badge, input border and palette selection
+    pub accent: Color,
+    /// Text drawn on an `accent` background
+    pub on_accent: Color,
+    pub user: Color,
+    pub assistant: Color,
+    pub tool: Color,
+    pub error: Color,
+    /// Hints, reasoning and status details
+    pub muted: Color,
+    pub heading: Color,
+    /// Fenced code blocks
+    pub code: Color,
+    pub inline_code: Color,
+    /// Added and removed lines in edit diffs
+    pub added: Color,
+    pub removed: Color,
+    /// Status line while idle, waiting on the model or the user, and
+    /// running a tool
+    pub ready: Color,
+    pub busy: Color,
+    pub running: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
+
+impl Theme {
+    pub const DARK: Self = Self {
+        accent: Color::Cyan,
+        on_accent: Color::Black,
+        user: Color::Green,
+        assistant: Color::Cyan,
+        tool: Color::Yellow,
+        error: Color::Red,
+        muted: Color::DarkGray,
+        heading: Color::Cyan,
+        code: Color::Green,
+        inline_code: Color::Yellow,
+        added: Color::Green,
+        removed: Color::Red,
+        ready: Color::Green,
+        busy: Color::Yellow,
+        running: Color::Magenta,
+    };
+
+    /// Darker colors that stay readable on a light background.
+    pub const LIGHT: Self = Self {
+        accent: Color::Blue,
+        on_accent: Color::White,
+        user: Color::Green,
+        assistant: Color::Blue,
+        tool: Color::Magenta,
+        error: Color::Red,
+        muted: Color::DarkGray,
+        heading: Color::Blue,
+        code: Color::Green,
+        inline_code: Color::Magenta,
+        added: Color::Green,
+        removed: Color::Red,
+        ready: Color::Green,
+        busy: Color::Magenta,
+        running: Color::Blue,
+    };
+
+    /// Bright colors only, and no dim gray text.
+    pub const HIGH_CONTRAST: Self = Self {
+        accent: Color::LightYellow,
+        on_accent: Color::Black,
+        user: Color::LightGreen,
+        assistant: Color::LightCyan,
+        tool: Color::LightYellow,
+        error: Color::LightRed,
+        muted: Color::White,
+        heading: Color::LightCyan,
+        code: Color::LightGreen,
+        inline_code: Color::LightYellow,
+        added: Color::LightGreen,
+        removed: Color::LightRed,
+        ready: Color::LightGreen,
+        busy: Color::LightYellow,
+        running: Color::LightMagenta,
+    };
+
+    /// The terminal's own foreground everywhere; only the accent badge is
+    /// drawn in reverse.
+    pub const MONOCHROME: Self = Self {
+        accent: Color::White,
+        on_accent: Color::Black,
+        user: Color::Reset,
+        assistant: Color::Reset,
+        tool: Color::Reset,
+        error: Color::Reset,
+        muted: Color::Reset,
+        heading: Color::Reset,
+        code: Color::Reset,
+        inline_code: Color::Reset,
+        added: Color::Reset,
+        removed: Color::Reset,
+        ready: Color::Reset,
+        busy: Color::Reset,
+        running: Color::Reset,
+    };
+
+    /// The preset called `name` (case-insensitive), if there is one.
+    pub fn preset(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "dark" => Some(Self::DARK),
+            "light" => Some(Self::LIGHT),
+            "high-contrast" => Some(Self::HIGH_CONTRAST),
+            "monochrome" => Some(Self::MONOCHROME),
+            _ => None,
+        }
+    }
+
+    /// The `[tui] theme` preset (default `dark`) with the `*_color`
+    /// overrides applied on top.
+    pub fn from_config(config: &TuiConfig) -> Self {
+        let base = match config.theme.as_deref() {
+            None => Self::DARK,
+            Some(name) => Self::preset(name).unwrap_or_else(|| {
+                tracing::warn!("Unknown TUI theme {name:?}, using dark");
+                Self::DARK
+            }),
+        };
+        Self {
+            accent: parse_color(config.accent_color.as_deref(), base.accent),
+            user: parse_color(config.user_color.as_deref(), base.user),
+            assistant: parse_color(config.assistant_color.as_deref(), base.assistant),
+            tool: parse_color(config.tool_color.as_deref(), base.tool),
+            error: parse_color(config.error_color.as_deref(), base.error),
+            ..base
+        }
+    }
+}
+
+/// `--theme` value parser: a known preset name, lowercased.
+pub fn parse_preset(name: &str) -> Result<String, String> {
+    match Theme::preset(name) {
+        Some(_) => Ok(name.trim().to_ascii_lowercase()),
+        None => Err(format!(
+            "unknown theme {name:?}; expected one of {}",
+            PRESETS.join(", ")
+        )),
+    }
+}
+
+/// Parse a color name (`"magenta"`, `"light-blue"`) or hex (`"#ff8800"`),
+/// falling back when unset or unrecognized.
+fn parse_color(value: Option<&str>, fallback: Color) -> Color {
+    match value.map(str::trim) {
+        Some(v) if !v.is_empty() => Color::from_str(v).unwrap_or_else(|_| {
+            tracing::warn!("Unrecognized TUI color {v:?}, using default");
+            fallback
+        }),
+        _ => fallback,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_is_dark_theme() {
+        assert_eq!(Theme::from_config(&TuiConfig::default()), Theme::DARK);
+        assert_eq!(Theme::default().accent, Color::Cyan);
+        assert_eq!(Theme::default().user, Color::Green);
+        assert_eq!(Theme::default().tool, Color::Yellow);
+    }
+
+    #[test]
+    fn presets_map_to_expected_colors() {
+        let light = Theme::preset("light").unwrap();
+        assert_eq!(light.accent, Color::Blue);
+        assert_eq!(light.on_accent, Color::White);
+        assert_eq!(light.tool, Color::Magenta);
+
+        let high = Theme::preset("High-Contrast").unwrap();
+        assert_eq!(high.muted, Color::White);
+        assert_eq!(high.error, Color::LightRed);
+
+        let mono = Theme::preset("monochrome").unwrap();
+        assert_eq!(mono.user, Color::Reset);
+        assert_eq!(mono.accent, Color::White);
+
+        assert!(Theme::preset("solarized").is_none());
+        for name in PRESETS {
+            assert!(Theme::preset(name).is_some(), "{name}");
+        }
+    }
+
+    #[test]
+    fn role_overrides_apply_on_top_of_preset() {
+        let config = TuiConfig {
+            theme: Some("light".into()),
+            user_color: Some("#ff8800".into()),
+            tool_color: Some("cyan".into()),
+            error_color: Some("not-a-color".into()),
+            ..TuiConfig::default()
+        };
+        let theme = Theme::from_config(&config);
+        assert_eq!(theme.user, Color::Rgb(0xff, 0x88, 0x00));
+        assert_eq!(theme.tool, Color::Cyan);
+        // Unrecognized overrides keep the preset color
+        assert_eq!(theme.error, Theme::LIGHT.error);
+        assert_eq!(theme.accent, Theme::LIGHT.accent);
+    }
+
+    #[test]
+    fn unknown_theme_falls_back_to_dark() {
+        let config = TuiConfig {
+            theme: Some("neon".into()),
+            ..TuiConfig::default()
+        };
+        assert_eq!(Theme::from_config(&config), Theme::DARK);
+    }
+
+    #[test]
+    fn theme_flag_accepts_only_presets() {
+        assert_eq!(parse_preset("Light").unwrap(), "light");
+        let err = parse_preset("neon").unwrap_err();
+        assert!(err.contains("high-contrast"), "{err}");
+    }
+}
@@ -43,10 +43,48 @@ pub fn truncate_with_ellipsis(s: &str, max_chars: usize) -> String {
     }
 }
 
+/// The longest prefix of `s` that is at most `max_bytes` long, cut at a
+/// character boundary so multi-byte UTF-8 is never split.
+///
+/// # Examples
+/// ```
+/// use tinyclaw::util::truncate_bytes_safe;
+///
+/// assert_eq!(truncate_bytes_safe("hello", 3), "hel");
+/// // '€' is 3 bytes; a cut inside it backs off to the previous character
+/// assert_eq!(truncate_bytes_safe("a€b", 2), "a");
+/// assert_eq!(truncate_bytes_safe("a€b", 10), "a€b");
+/// ```
+pub fn truncate_bytes_safe(s: &str, max_bytes: usize) -> &str {
+    &s[..s.floor_char_boundary(max_bytes)]
+}
+
+/// A spawned task that is aborted when this handle is dropped, so tasks
+/// started by a future stop when that future is cancelled.
+pub struct AbortOnDrop(pub tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn abort_on_drop_stops_the_task() {
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let guard = AbortOnDrop(tokio::spawn(async move {
+            let _tx = tx;
+            std::future::pending::<()>().await;
+        }));
+        drop(guard);
+        // The task's sender is dropped once the abort lands
+        assert!(rx.await.is_err());
+    }
+
     #[test]
     fn test_truncate_ascii_no_truncation() {
         // ASCII string shorter than limit - no change
@@ -135,4 +173,13 @@ mod tests {
         // Edge case: max_chars = 0
         assert_eq!(truncate_with_ellipsis("hello", 0), "...");
     }
+
+    #[test]
+    fn test_truncate_bytes_safe_snaps_to_char_boundary() {
+        // Every cut inside a multi-byte character backs off to its start
+        let s = "aé你🦀"; // 1 + 2 + 3 + 4 bytes
+        let cuts: Vec<usize> = (0..=11).map(|n| truncate_bytes_safe(s, n).len()).collect();
+        assert_eq!(cuts, [0, 1, 1, 3, 3, 3, 6, 6, 6, 6, 10, 10]);
+        assert_eq!(truncate_bytes_safe(s, 9), "aé你");
+    }
 }
@@ -0,0 +1,62 @@
+//! Integration tests for `tinyclaw agent --attach-file`: file contents reach
+//! the provider in front of the user's message.
+
+mod common;
+
+use common::{fake_provider, run_agent, write_config, REPLY};
+
+#[test]
+fn attached_text_file_is_sent_before_the_message() {
+    let home = tempfile::tempdir().unwrap();
+    let (base, bodies) = fake_provider();
+    write_config(home.path(), &base);
+    let notes = home.path().join(".tinyclaw/workspace/notes.txt");
+    std::fs::write(&notes, "release on friday").unwrap();
+
+    let output = run_agent(
+        home.path(),
+        &[
+            "--attach-file",
+            notes.to_str().unwrap(),
+            "--message",
+            "When is the release?",
+        ],
+        "",
+    );
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains(REPLY));
+
+    let bodies = bodies.lock().unwrap();
+    let request = bodies
+        .iter()
+        .find(|b| b.contains("messages"))
+        .expect("a chat request");
+    let attached = format!(
+        r"[Attached file: {}]\nrelease on friday\n[End of {}]\n\nWhen is the release?",
+        notes.display(),
+        notes.display()
+    );
+    assert!(request.contains(&attached), "{request}");
+}
+
+#[test]
+fn attachment_outside_workspace_is_refused() {
+    let home = tempfile::tempdir().unwrap();
+    let (base, bodies) = fake_provider();
+    write_config(home.path(), &base);
+    let secret = home.path().join("secret.txt");
+    std::fs::write(&secret, "token").unwrap();
+
+    let output = run_agent(
+        home.path(),
+        &["--attach-file", secret.to_str().unwrap(), "--message", "Hi"],
+        "",
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("security policy"));
+    assert!(bodies.lock().unwrap().is_empty());
+}
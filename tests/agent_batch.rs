@@ -0,0 +1,79 @@
+//! Integration tests for `tinyclaw agent --batch`, with and without
+//! `--continue-on-error`.
+
+mod common;
+
+use common::{fake_provider, run_agent, write_config, FAIL_MARKER, REPLY};
+
+fn write_prompts(dir: &std::path::Path) -> std::path::PathBuf {
+    let path = dir.join("prompts.txt");
+    std::fs::write(
+        &path,
+        format!("# smoke test\nfirst question\n{FAIL_MARKER} second question\nthird question\n"),
+    )
+    .unwrap();
+    path
+}
+
+#[test]
+fn failing_item_does_not_abort_batch() {
+    let home = tempfile::tempdir().unwrap();
+    let (base, _) = fake_provider();
+    write_config(home.path(), &base);
+    let prompts = write_prompts(home.path());
+
+    let output = run_agent(
+        home.path(),
+        &[
+            "--batch",
+            prompts.to_str().unwrap(),
+            "--continue-on-error",
+            "--output",
+            "json",
+        ],
+        "",
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "stderr: {stderr}");
+    assert!(stderr.contains("1 of 3 prompts failed"), "{stderr}");
+
+    let items: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let statuses: Vec<(u64, &str)> = items
+        .iter()
+        .map(|item| {
+            (
+                item["index"].as_u64().unwrap(),
+                item["status"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(statuses, vec![(1, "ok"), (2, "error"), (3, "ok")]);
+    assert_eq!(items[0]["response"], REPLY);
+    assert_eq!(items[2]["response"], REPLY);
+    assert!(
+        items[1]["error"].as_str().unwrap().contains("500"),
+        "{}",
+        items[1]
+    );
+    // Each prompt is saved as its own session
+    assert_ne!(items[0]["session"], items[2]["session"]);
+}
+
+#[test]
+fn batch_stops_at_first_failure_without_continue_on_error() {
+    let home = tempfile::tempdir().unwrap();
+    let (base, _) = fake_provider();
+    write_config(home.path(), &base);
+    let prompts = write_prompts(home.path());
+
+    let output = run_agent(home.path(), &["--batch", prompts.to_str().unwrap()], "");
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("── Prompt 1/3 ──"), "{stdout}");
+    assert!(!stdout.contains("Prompt 3/3"), "{stdout}");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Prompt 2/3 failed"));
+}
@@ -0,0 +1,70 @@
+//! Integration tests for `tinyclaw agent --pipe`: the prompt comes from stdin
+//! and stdout carries only the reply.
+
+mod common;
+
+use common::{fake_provider, run_agent, write_config, REPLY};
+
+#[test]
+fn pipe_reads_prompt_from_stdin_and_prints_only_reply() {
+    let home = tempfile::tempdir().unwrap();
+    let (base, bodies) = fake_provider();
+    write_config(home.path(), &base);
+
+    let output = run_agent(
+        home.path(),
+        &["--pipe"],
+        "What is 2 + 2?\nAnswer briefly.\n",
+    );
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("{REPLY}\n")
+    );
+
+    let bodies = bodies.lock().unwrap();
+    let request = bodies
+        .iter()
+        .find(|b| b.contains("messages"))
+        .expect("a chat request");
+    assert!(
+        request.contains(r"What is 2 + 2?\nAnswer briefly."),
+        "{request}"
+    );
+}
+
+#[test]
+fn pipe_with_json_output_prints_one_object() {
+    let home = tempfile::tempdir().unwrap();
+    let (base, _) = fake_provider();
+    write_config(home.path(), &base);
+
+    let output = run_agent(home.path(), &["--pipe", "--output", "json"], "Hi");
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 1, "{stdout}");
+    let value: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(value["response"], REPLY);
+    assert_eq!(value["model"], "test-model");
+    assert!(value["session"].as_str().is_some_and(|s| !s.is_empty()));
+}
+
+#[test]
+fn pipe_rejects_empty_stdin() {
+    let home = tempfile::tempdir().unwrap();
+    let (base, _) = fake_provider();
+    write_config(home.path(), &base);
+
+    let output = run_agent(home.path(), &["--pipe"], " \n");
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("empty prompt"));
+}
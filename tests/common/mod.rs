@@ -0,0 +1,122 @@
+//! Shared helpers for tests that run the `tinyclaw` binary against a fake
+//! OpenAI-compatible provider.
+
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::sync::{Arc, Mutex};
+
+pub const REPLY: &str = "Four.";
+
+/// Prompts containing this get an HTTP 500 from [`fake_provider`].
+pub const FAIL_MARKER: &str = "PLEASE_FAIL";
+
+/// Answer one OpenAI-style request with `REPLY` (or a 500 for prompts with
+/// `FAIL_MARKER`), recording the request body.
+fn answer(mut stream: TcpStream, bodies: &Mutex<Vec<String>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    let _ = reader.read_exact(&mut body);
+    let body = String::from_utf8_lossy(&body).into_owned();
+    let (status, payload) = if body.contains(FAIL_MARKER) {
+        (
+            "500 Internal Server Error",
+            r#"{"error":"upstream exploded"}"#.to_string(),
+        )
+    } else {
+        let reply = serde_json::json!({
+            "choices": [{"message": {"role": "assistant", "content": REPLY}}]
+        });
+        ("200 OK", reply.to_string())
+    };
+    bodies.lock().unwrap().push(body);
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{payload}",
+        payload.len()
+    );
+}
+
+/// Serve canned completions on a local port; returns the base URL and the
+/// request bodies seen so far.
+pub fn fake_provider() -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let bodies = Arc::new(Mutex::new(Vec::new()));
+    let seen = bodies.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            answer(stream, &seen);
+        }
+    });
+    (base, bodies)
+}
+
+pub fn write_config(home: &Path, provider_url: &str) {
+    write_config_in(&home.join(".tinyclaw"), provider_url);
+}
+
+/// Write a config for [`fake_provider`] to `dir/config.toml`, with the
+/// workspace at `dir/workspace` (what `--workspace dir` expects).
+pub fn write_config_in(dir: &Path, provider_url: &str) {
+    std::fs::create_dir_all(dir.join("workspace")).unwrap();
+    std::fs::write(
+        dir.join("config.toml"),
+        format!(
+            r#"api_key = "test-key"
+default_provider = "custom:{provider_url}"
+default_model = "test-model"
+default_temperature = 0.0
+workspace_dir = "{workspace}"
+config_path = "{config}"
+
+[memory]
+backend = "none"
+auto_save = false
+
+[reliability]
+provider_retries = 0
+"#,
+            workspace = dir.join("workspace").display(),
+            config = dir.join("config.toml").display(),
+        ),
+    )
+    .unwrap();
+}
+
+pub fn run_agent(home: &Path, args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tinyclaw"))
+        .arg("agent")
+        .args(args)
+        .env("HOME", home)
+        .env_remove("ZEROCLAW_API_KEY")
+        .env_remove("API_KEY")
+        .env_remove("RUST_LOG")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
@@ -0,0 +1,114 @@
+//! Integration tests for the streamed JSON-lines reply of `POST /webhook`
+//! (`Accept: application/x-ndjson`), against a fake provider.
+
+#![cfg(feature = "gateway-feature")]
+
+mod common;
+
+use common::{fake_provider, FAIL_MARKER, REPLY};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
+use tempfile::TempDir;
+use tinyclaw::config::Config;
+use tinyclaw::gateway::{run_gateway, NDJSON_CONTENT_TYPE};
+
+/// Start an unpaired gateway backed by [`fake_provider`]; returns its base URL.
+async fn start_gateway(tmp: &Path) -> String {
+    let (provider_url, _) = fake_provider();
+    let mut config = Config {
+        workspace_dir: tmp.to_path_buf(),
+        config_path: tmp.join("config.toml"),
+        api_key: Some("test-key".into()),
+        default_provider: Some(format!("custom:{provider_url}")),
+        default_model: Some("test-model".into()),
+        ..Config::default()
+    };
+    config.memory.backend = "none".into();
+    config.memory.auto_save = false;
+    config.reliability.provider_retries = 0;
+    config.gateway.require_pairing = false;
+
+    let port_file = tmp.join("gateway.port");
+    let path = port_file.clone();
+    tokio::spawn(async move { run_gateway("127.0.0.1", 0, Some(&path), config).await });
+
+    for _ in 0..250 {
+        if let Ok(text) = std::fs::read_to_string(&port_file) {
+            let addr: SocketAddr = text.trim().parse().unwrap();
+            return format!("http://{addr}");
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("gateway never wrote its port file");
+}
+
+async fn ndjson_events(base: &str, message: &str) -> Vec<serde_json::Value> {
+    let response = reqwest::Client::new()
+        .post(format!("{base}/webhook"))
+        .header("Accept", NDJSON_CONTENT_TYPE)
+        .json(&serde_json::json!({"message": message}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.headers()["content-type"].to_str().unwrap(),
+        NDJSON_CONTENT_TYPE
+    );
+
+    let body = tokio::time::timeout(Duration::from_secs(10), response.text())
+        .await
+        .expect("stream should end after the final event")
+        .unwrap();
+    assert!(body.ends_with('\n'), "every event ends its line: {body:?}");
+    body.lines()
+        .map(|line| serde_json::from_str(line).expect("each line is one JSON object"))
+        .collect()
+}
+
+#[tokio::test]
+async fn ndjson_streams_tokens_then_done() {
+    let tmp = TempDir::new().unwrap();
+    let base = start_gateway(tmp.path()).await;
+
+    let events = ndjson_events(&base, "What is 2+2?").await;
+    let (last, tokens) = events.split_last().expect("at least the final event");
+
+    assert!(!tokens.is_empty());
+    assert!(tokens.iter().all(|e| e["type"] == "token"), "{events:?}");
+    let streamed: String = tokens.iter().map(|e| e["text"].as_str().unwrap()).collect();
+    assert_eq!(streamed, REPLY);
+
+    assert_eq!(last["type"], "done");
+    assert_eq!(last["response"], REPLY);
+    assert_eq!(last["model"], "test-model");
+}
+
+#[tokio::test]
+async fn ndjson_ends_with_error_event_when_provider_fails() {
+    let tmp = TempDir::new().unwrap();
+    let base = start_gateway(tmp.path()).await;
+
+    let events = ndjson_events(&base, FAIL_MARKER).await;
+    let types: Vec<&str> = events.iter().map(|e| e["type"].as_str().unwrap()).collect();
+    assert_eq!(types, ["error"]);
+    assert_eq!(events[0]["error"], "LLM request failed");
+}
+
+#[tokio::test]
+async fn plain_json_reply_without_ndjson_accept() {
+    let tmp = TempDir::new().unwrap();
+    let base = start_gateway(tmp.path()).await;
+
+    let reply: serde_json::Value = reqwest::Client::new()
+        .post(format!("{base}/webhook"))
+        .json(&serde_json::json!({"message": "What is 2+2?"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(reply["response"], REPLY);
+}
@@ -0,0 +1,80 @@
+//! Integration tests for the gateway liveness (`/healthz`) and readiness
+//! (`/readyz`) probes, served over a real socket.
+
+#![cfg(feature = "gateway-feature")]
+
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+use tinyclaw::gateway::{probe_routes, warm_up_provider, Readiness};
+use tinyclaw::providers::Provider;
+use tokio::sync::Notify;
+
+/// Provider whose warmup blocks until the test releases it.
+struct GatedProvider {
+    release: Arc<Notify>,
+}
+
+#[async_trait]
+impl Provider for GatedProvider {
+    fn name(&self) -> &str {
+        "gated"
+    }
+
+    async fn chat_with_system(
+        &self,
+        _system_prompt: Option<&str>,
+        _message: &str,
+        _model: &str,
+        _temperature: f64,
+    ) -> anyhow::Result<String> {
+        Ok("ok".into())
+    }
+
+    async fn warmup(&self) -> anyhow::Result<()> {
+        self.release.notified().await;
+        Ok(())
+    }
+}
+
+async fn status(client: &reqwest::Client, base: &str, path: &str) -> u16 {
+    client
+        .get(format!("{base}{path}"))
+        .send()
+        .await
+        .unwrap()
+        .status()
+        .as_u16()
+}
+
+#[tokio::test]
+async fn readyz_turns_ready_after_provider_warmup() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let readiness = Arc::new(Readiness::default());
+    let app = probe_routes(readiness.clone());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let release = Arc::new(Notify::new());
+    let provider: Arc<dyn Provider> = Arc::new(GatedProvider {
+        release: release.clone(),
+    });
+    let warmup = tokio::spawn(warm_up_provider(provider, readiness.clone()));
+
+    let client = reqwest::Client::new();
+
+    // Before warmup: alive but not ready
+    assert_eq!(status(&client, &base, "/healthz").await, 200);
+    assert_eq!(status(&client, &base, "/readyz").await, 503);
+
+    release.notify_one();
+    tokio::time::timeout(Duration::from_secs(5), warmup)
+        .await
+        .expect("warmup should finish once released")
+        .unwrap();
+
+    // After warmup: both probes pass
+    assert!(readiness.is_ready());
+    assert_eq!(status(&client, &base, "/healthz").await, 200);
+    assert_eq!(status(&client, &base, "/readyz").await, 200);
+}
@@ -0,0 +1,60 @@
+//! Integration tests for `tinyclaw onboard --channels-only --add <type>
+//! <json>`: a channel is saved to `config.toml` without any prompts.
+
+#![cfg(feature = "channels-feature")]
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn onboard_add(home: &Path, channel_type: &str, json: &str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_tinyclaw"))
+        .args(["onboard", "--channels-only", "--add", channel_type, json])
+        .env("HOME", home)
+        .env_remove("TINYCLAW_WORKSPACE")
+        .stdin(std::process::Stdio::null())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn add_saves_the_channel_without_prompts() {
+    let home = tempfile::tempdir().unwrap();
+
+    let output = onboard_add(
+        home.path(),
+        "telegram",
+        r#"{"bot_token": "123:abc", "allowed_users": ["alice"]}"#,
+    );
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Telegram saved"));
+
+    let saved: toml::Value = std::fs::read_to_string(home.path().join(".tinyclaw/config.toml"))
+        .unwrap()
+        .parse()
+        .unwrap();
+    let telegram = &saved["channels_config"]["telegram"];
+    assert_eq!(telegram["bot_token"].as_str(), Some("123:abc"));
+    assert_eq!(telegram["allowed_users"][0].as_str(), Some("alice"));
+}
+
+#[test]
+fn add_rejects_fields_the_channel_does_not_have() {
+    let home = tempfile::tempdir().unwrap();
+
+    let output = onboard_add(
+        home.path(),
+        "telegram",
+        r#"{"bot_token": "123:abc", "allowed_users": [], "chat": "x"}"#,
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unknown field(s) chat"), "{stderr}");
+
+    let config =
+        std::fs::read_to_string(home.path().join(".tinyclaw/config.toml")).unwrap_or_default();
+    assert!(!config.contains("[channels_config.telegram]"), "{config}");
+}
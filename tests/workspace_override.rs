@@ -0,0 +1,59 @@
+//! Integration tests for `tinyclaw --workspace <dir>` and
+//! `TINYCLAW_WORKSPACE`: config and sessions live in that directory instead
+//! of `~/.tinyclaw`.
+
+mod common;
+
+use common::{fake_provider, run_agent, write_config_in};
+use std::process::Command;
+
+#[test]
+fn workspace_flag_moves_config_and_sessions() {
+    let home = tempfile::tempdir().unwrap();
+    let project = home.path().join("project");
+    let (base, bodies) = fake_provider();
+    write_config_in(&project, &base);
+
+    let output = run_agent(
+        home.path(),
+        &["--workspace", project.to_str().unwrap(), "-m", "Hi"],
+        "",
+    );
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(bodies
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|b| b.contains("messages")));
+
+    let sessions: Vec<_> = std::fs::read_dir(project.join("workspace/sessions"))
+        .unwrap()
+        .collect();
+    assert_eq!(sessions.len(), 1);
+    assert!(!home.path().join(".tinyclaw").exists());
+}
+
+#[test]
+fn workspace_env_var_is_where_a_fresh_config_is_written() {
+    let home = tempfile::tempdir().unwrap();
+    let dir = home.path().join("isolated");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tinyclaw"))
+        .args(["status", "--format", "json"])
+        .env("HOME", home.path())
+        .env("TINYCLAW_WORKSPACE", &dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(dir.join("config.toml").exists());
+    assert!(dir.join("workspace").is_dir());
+    assert!(!home.path().join(".tinyclaw").exists());
+}